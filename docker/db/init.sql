@@ -4,17 +4,86 @@ CREATE EXTENSION IF NOT EXISTS pg_trgm WITH SCHEMA public;
 --
 -- DEFINITION OF TABLES, TYPES AND INDICES
 --
+-- The table that stores the version of the database schema
+-- The service checks this version at startup against the version it requires
+CREATE TABLE IF NOT EXISTS schema_version(
+    version int NOT NULL -- The version of the database schema
+);
+
+INSERT INTO schema_version(version)
+    VALUES (18);
+
+-- The table that records the optional schema features applied by the service at startup
+CREATE TABLE IF NOT EXISTS schema_features(
+    name text PRIMARY KEY, -- The name of the optional feature
+    version int NOT NULL, -- The schema version the feature has been applied to
+    applied_at timestamp with time zone NOT NULL DEFAULT now() -- When the feature has been applied
+);
+
 -- Define type for the quantity type of the product
 CREATE TYPE QuantityType AS ENUM(
     'weight',
     'volume'
 );
 
+-- Define type for the main material of the packaging of the product
+CREATE TYPE PackagingMaterial AS ENUM(
+    'plastic',
+    'glass',
+    'paper',
+    'metal',
+    'composite',
+    'other'
+);
+
+-- Define type for the grade of the Eco-Score from A (low impact) to E (high impact)
+CREATE TYPE EcoScoreGrade AS ENUM(
+    'A',
+    'B',
+    'C',
+    'D',
+    'E'
+);
+
+-- Define type for the allergens that must be declared on food labels
+CREATE TYPE Allergen AS ENUM(
+    'gluten',
+    'crustaceans',
+    'eggs',
+    'fish',
+    'peanuts',
+    'soybeans',
+    'milk',
+    'nuts',
+    'celery',
+    'mustard',
+    'sesame',
+    'sulphites',
+    'lupin',
+    'molluscs'
+);
+
+-- Define type for the role of the additional images, i.e., what the image shows
+CREATE TYPE ImageRole AS ENUM(
+    'front',
+    'nutrition',
+    'ingredients',
+    'packaging',
+    'other'
+);
+
 -- The table that stores the product images like previews and full images
 CREATE TABLE IF NOT EXISTS product_image(
     id serial PRIMARY KEY, -- The id of the product image
-    data bytea NOT NULL, -- The data of image
-    content_type varchar(32) NOT NULL -- The content type of the image
+    data bytea NOT NULL, -- The data of image, empty if the image is in the image store
+    content_type varchar(32) NOT NULL, -- The content type of the image
+    storage_key varchar(255), -- The key of the image in the image store, if any
+    storage_size int8 -- The size of the image in the image store in bytes, if any
+);
+
+-- The keys of the deleted images of the image store, whose objects are yet to be deleted
+CREATE TABLE IF NOT EXISTS deleted_stored_images(
+    storage_key varchar(255) PRIMARY KEY
 );
 
 -- The table stores the nutrition information of the products
@@ -37,11 +106,23 @@ CREATE TABLE IF NOT EXISTS nutrients(
     zinc_mg real -- The amount of zinc in the product in milligrams
 );
 
+-- The stores and retailers where the products are sold
+CREATE TABLE IF NOT EXISTS stores(
+    id serial PRIMARY KEY, -- The id of the store
+    name varchar(64) NOT NULL, -- The name of the store
+    chain varchar(64) -- The retail chain the store belongs to
+);
+
+-- Index for the chain in stores
+CREATE INDEX IF NOT EXISTS stores_chain_index ON stores(chain);
+
 -- Products which have been scanned by the users, but are not in the database
 CREATE TABLE IF NOT EXISTS reported_missing_products(
     id serial PRIMARY KEY, -- The id of the reported entry
     product_id varchar(64) NOT NULL, -- The id of the missing product
-    date timestamp with time zone NOT NULL -- The date when the request was made
+    date timestamp with time zone NOT NULL, -- The date when the request was made
+    store_id int, -- The store where the product has been scanned
+    FOREIGN KEY (store_id) REFERENCES stores(id) ON DELETE SET NULL
 );
 
 -- Index for product_id in reported_missing_products
@@ -65,6 +146,29 @@ CREATE TABLE IF NOT EXISTS product_description(
     -- The ratio between volume and weight, i.e. volume(ml) = weight(g) * volume_weight_ratio
     -- Is only defined if the quantity type is volume
     volume_weight_ratio real,
+    market varchar(2), -- The market the product is sold in as ISO 3166-1 alpha-2 country code
+    -- The packaging of the product, only defined if packaging_deposit is not null
+    packaging_net_quantity real, -- The net weight or volume of the pack in grams or ml
+    packaging_units int, -- The number of units in the pack
+    packaging_material PackagingMaterial, -- The main material of the packaging
+    packaging_deposit boolean, -- True if there is a deposit on the packaging
+    -- The dietary labels of the product, null if unknown
+    vegan boolean,
+    vegetarian boolean,
+    organic boolean,
+    gluten_free boolean,
+    lactose_free boolean,
+    -- The NOVA group of the product from 1 (unprocessed) to 4 (ultra-processed)
+    nova_group int CHECK (nova_group BETWEEN 1 AND 4),
+    -- The Eco-Score of the product, only defined if eco_score_grade is not null
+    eco_score_grade EcoScoreGrade, -- The grade from A (low impact) to E (high impact)
+    eco_score_value int CHECK (eco_score_value BETWEEN 0 AND 100), -- The numeric score
+    eco_score_source varchar(64), -- The source of the score
+    allergens Allergen[] NOT NULL DEFAULT '{}', -- The allergens contained in the product
+    -- The canonical GTIN-14 of the product id, i.e., the id zero-padded to 14 digits if it is a GTIN
+    gtin varchar(14) GENERATED ALWAYS AS (
+        CASE WHEN product_id ~ '^[0-9]{1,14}$' THEN lpad(product_id, 14, '0') END
+    ) STORED,
     preview int, -- Reference onto a preview image
     photo int, -- Reference onto a full image
     nutrients int NOT NULL, -- Reference onto the nutrients of the product
@@ -76,25 +180,165 @@ CREATE TABLE IF NOT EXISTS product_description(
 -- Index for product_id in product_description
 CREATE INDEX IF NOT EXISTS product_description_product_id_index ON product_description(product_id);
 
+-- Index for the canonical GTIN-14 in product_description
+CREATE INDEX IF NOT EXISTS product_description_gtin_index ON product_description(gtin);
+
+-- Index for the market in product_description
+CREATE INDEX IF NOT EXISTS product_description_market_index ON product_description(market);
+
 -- Index for the name of the product in product_description
 CREATE INDEX IF NOT EXISTS product_description_name_producer_trgm_idx ON product_description USING gin(name_producer gin_trgm_ops);
 
+-- Returns the text search vector of the name and producer of a product for the full-text
+-- search. The words are indexed unchanged and stemmed in the language of the market of the
+-- product, e.g. German for DE, such that "Joghurt" also finds "Joghurts". The products
+-- without market or of markets without a dedicated language are stemmed as English.
+CREATE OR REPLACE FUNCTION search_vector(name_producer text, market text) RETURNS tsvector
+    LANGUAGE sql IMMUTABLE PARALLEL SAFE
+    AS $$
+        SELECT to_tsvector('pg_catalog.simple', coalesce(name_producer, ''))
+            || to_tsvector(
+                CASE upper(market)
+                    WHEN 'DE' THEN 'pg_catalog.german'::regconfig
+                    WHEN 'AT' THEN 'pg_catalog.german'::regconfig
+                    WHEN 'CH' THEN 'pg_catalog.german'::regconfig
+                    WHEN 'FR' THEN 'pg_catalog.french'::regconfig
+                    WHEN 'IT' THEN 'pg_catalog.italian'::regconfig
+                    WHEN 'ES' THEN 'pg_catalog.spanish'::regconfig
+                    WHEN 'NL' THEN 'pg_catalog.dutch'::regconfig
+                    ELSE 'pg_catalog.english'::regconfig
+                END,
+                coalesce(name_producer, ''))
+    $$;
+
+-- Returns the text search query for the given query in the syntax of to_tsquery, which
+-- matches the unchanged words and the words stemmed in any of the languages of
+-- search_vector.
+CREATE OR REPLACE FUNCTION search_query(query text) RETURNS tsquery
+    LANGUAGE sql IMMUTABLE STRICT PARALLEL SAFE
+    AS $$
+        SELECT to_tsquery('pg_catalog.simple', query)
+            || to_tsquery('pg_catalog.german', query)
+            || to_tsquery('pg_catalog.english', query)
+            || to_tsquery('pg_catalog.french', query)
+            || to_tsquery('pg_catalog.italian', query)
+            || to_tsquery('pg_catalog.spanish', query)
+            || to_tsquery('pg_catalog.dutch', query)
+    $$;
+
+-- Index for the full-text search on the name and producer of the product in product_description
+CREATE INDEX IF NOT EXISTS product_description_search_vector_idx ON product_description USING gin(search_vector(name_producer, market));
+
 -- The table that stores the products
 CREATE TABLE IF NOT EXISTS products(
     product_id varchar(64) NOT NULL, -- The id of the product
     product_description_id int NOT NULL, -- The id of the product description entry
+    market varchar(2), -- The market of the product, see product_description
     PRIMARY KEY (product_id),
     FOREIGN KEY (product_description_id) REFERENCES product_description(id) ON DELETE CASCADE
 );
 
+-- Index for product_description_id in products
+CREATE INDEX IF NOT EXISTS products_product_description_id_index ON products(product_description_id);
+
+-- This table stores the additional images of the product descriptions besides the preview and
+-- full image, e.g. photos of the nutrition table or the ingredient list
+CREATE TABLE IF NOT EXISTS additional_images(
+    id serial PRIMARY KEY, -- The id of the entry, which defines the order of the images
+    product_description_id int NOT NULL, -- The id of the product description entry
+    role ImageRole NOT NULL, -- What the image shows
+    image int NOT NULL, -- Reference onto the image
+    FOREIGN KEY (product_description_id) REFERENCES product_description(id) ON DELETE CASCADE,
+    FOREIGN KEY (image) REFERENCES product_image(id) ON DELETE CASCADE
+);
+
+-- Index for product_description_id in additional_images
+CREATE INDEX IF NOT EXISTS additional_images_product_description_id_index ON additional_images(product_description_id);
+
+-- This table stores the photos of the reported missing products, e.g. of their packaging or
+-- barcode. The reports are not referenced, as they may be partitioned and expire by dropping
+-- whole partitions.
+CREATE TABLE IF NOT EXISTS missing_product_photos(
+    missing_product_id int PRIMARY KEY, -- The id of the reported missing product
+    image int NOT NULL, -- Reference onto the image
+    FOREIGN KEY (image) REFERENCES product_image(id) ON DELETE CASCADE
+);
+
+-- Define type for the review status of product requests
+CREATE TYPE RequestStatus AS ENUM(
+    'pending',
+    'approved',
+    'rejected'
+);
+
 -- This table stores requested products
 CREATE TABLE IF NOT EXISTS requested_products(
     id serial PRIMARY KEY, -- The id of the entry
     product_description_id int NOT NULL, -- The id of the product description entry
     date timestamp with time zone NOT NULL, -- The date when the product was missing
+    status RequestStatus NOT NULL DEFAULT 'pending', -- The review status of the request
+    review_comment text, -- The comment of the reviewer, e.g. why the request was rejected
     FOREIGN KEY (product_description_id) REFERENCES product_description(id) ON DELETE CASCADE
 );
 
+-- Define type for the outcome of processed product requests
+CREATE TYPE RequestOutcome AS ENUM(
+    'approved',
+    'rejected'
+);
+
+-- This table stores the processed product requests, which are moved here instead of being deleted
+CREATE TABLE IF NOT EXISTS archived_product_requests(
+    id int PRIMARY KEY, -- The original id of the product request
+    product_id varchar(64) NOT NULL, -- The id of the requested product
+    name varchar(64) NOT NULL, -- The name of the requested product
+    producer varchar(64), -- The producer of the requested product
+    date timestamp with time zone NOT NULL, -- The date when the product was requested
+    archived_date timestamp with time zone NOT NULL, -- The date when the request was archived
+    outcome RequestOutcome NOT NULL, -- If the requested product was approved or rejected
+    review_comment text -- The comment of the reviewer, e.g. why the request was rejected
+);
+
+-- Index for product_id in archived_product_requests
+CREATE INDEX IF NOT EXISTS archived_product_requests_product_id_index ON archived_product_requests(product_id);
+
+-- This table stores the recipes, which are composed of products
+CREATE TABLE IF NOT EXISTS recipes(
+    id serial PRIMARY KEY, -- The id of the recipe
+    name varchar(64) NOT NULL, -- The name of the recipe
+    servings int NOT NULL, -- The number of portions the recipe yields
+    image int, -- Reference onto an image of the recipe
+    FOREIGN KEY (image) REFERENCES product_image(id)
+);
+
+-- This table stores the ingredients of the recipes
+-- The ingredients reference the products by their id, i.e., they are kept if the product is deleted
+CREATE TABLE IF NOT EXISTS recipe_ingredients(
+    id serial PRIMARY KEY, -- The id of the ingredient entry
+    recipe_id int NOT NULL, -- The id of the recipe
+    position int NOT NULL, -- The position of the ingredient in the recipe
+    product_id varchar(64) NOT NULL, -- The id of the product
+    amount real NOT NULL, -- The amount of the product in grams or ml depending on the quantity type
+    FOREIGN KEY (recipe_id) REFERENCES recipes(id) ON DELETE CASCADE
+);
+
+-- Index for recipe_id in recipe_ingredients
+CREATE INDEX IF NOT EXISTS recipe_ingredients_recipe_id_index ON recipe_ingredients(recipe_id);
+
+-- This table stores the last observed availability of the products in the stores
+-- The products are referenced by their id, i.e., the availability is kept if the product is deleted
+CREATE TABLE IF NOT EXISTS product_availability(
+    store_id int NOT NULL, -- The id of the store
+    product_id varchar(64) NOT NULL, -- The id of the product
+    available boolean NOT NULL, -- If the product has been seen or has been missing at the store
+    date timestamp with time zone NOT NULL, -- The date when the availability was observed
+    PRIMARY KEY (store_id, product_id),
+    FOREIGN KEY (store_id) REFERENCES stores(id) ON DELETE CASCADE
+);
+
+-- Index for product_id in product_availability
+CREATE INDEX IF NOT EXISTS product_availability_product_id_index ON product_availability(product_id);
+
 --
 -- DEFINITION OF VIEWS
 --
@@ -125,7 +369,25 @@ SELECT
     n.magnesium_mg,
     n.sodium_mg,
     n.zinc_mg,
-    p.name_producer
+    p.name_producer,
+    p.market,
+    p.packaging_net_quantity,
+    p.packaging_units,
+    p.packaging_material,
+    p.packaging_deposit,
+    p.vegan,
+    p.vegetarian,
+    p.organic,
+    p.gluten_free,
+    p.lactose_free,
+    p.nova_group,
+    p.eco_score_grade,
+    p.eco_score_value,
+    p.eco_score_source,
+    p.allergens,
+    p.gtin,
+    r.status,
+    r.review_comment
 FROM
     requested_products r
     JOIN product_description p ON p.id = r.product_description_id
@@ -159,7 +421,25 @@ SELECT
     n.magnesium_mg,
     n.sodium_mg,
     n.zinc_mg,
-    p.name_producer
+    p.name_producer,
+    p.market,
+    p.packaging_net_quantity,
+    p.packaging_units,
+    p.packaging_material,
+    p.packaging_deposit,
+    p.vegan,
+    p.vegetarian,
+    p.organic,
+    p.gluten_free,
+    p.lactose_free,
+    p.nova_group,
+    p.eco_score_grade,
+    p.eco_score_value,
+    p.eco_score_source,
+    p.allergens,
+    p.gtin,
+    r.status,
+    r.review_comment
 FROM
     requested_products r
     JOIN product_description p ON p.id = r.product_description_id
@@ -191,7 +471,23 @@ SELECT
     n.magnesium_mg,
     n.sodium_mg,
     n.zinc_mg,
-    p.name_producer
+    p.name_producer,
+    p.market,
+    p.packaging_net_quantity,
+    p.packaging_units,
+    p.packaging_material,
+    p.packaging_deposit,
+    p.vegan,
+    p.vegetarian,
+    p.organic,
+    p.gluten_free,
+    p.lactose_free,
+    p.nova_group,
+    p.eco_score_grade,
+    p.eco_score_value,
+    p.eco_score_source,
+    p.allergens,
+    p.gtin
 FROM
     products r
     JOIN product_description p ON p.id = r.product_description_id
@@ -223,7 +519,23 @@ SELECT
     n.magnesium_mg,
     n.sodium_mg,
     n.zinc_mg,
-    p.name_producer
+    p.name_producer,
+    p.market,
+    p.packaging_net_quantity,
+    p.packaging_units,
+    p.packaging_material,
+    p.packaging_deposit,
+    p.vegan,
+    p.vegetarian,
+    p.organic,
+    p.gluten_free,
+    p.lactose_free,
+    p.nova_group,
+    p.eco_score_grade,
+    p.eco_score_value,
+    p.eco_score_source,
+    p.allergens,
+    p.gtin
 FROM
     products r
     JOIN product_description p ON p.id = r.product_description_id
@@ -235,7 +547,8 @@ CREATE VIEW requested_products_full_image AS
 SELECT
     r.id AS r_id,
     pi.data,
-    pi.content_type
+    pi.content_type,
+    pi.storage_key
 FROM
     requested_products r
     JOIN product_description p ON p.id = r.product_description_id
@@ -245,12 +558,16 @@ FROM
 -- DEFINITION OF FUNCTIONS
 --
 -- Trigger function to delete the product description when a product request is deleted
+-- The description is kept if it has been taken over by a product, i.e., the request has been approved
 CREATE OR REPLACE FUNCTION trigger_func_delete_product_or_requested_product()
     RETURNS TRIGGER
     AS $$
 BEGIN
     DELETE FROM product_description
-    WHERE id = OLD.product_description_id;
+    WHERE id = OLD.product_description_id
+        AND NOT EXISTS (
+            SELECT 1 FROM products
+            WHERE product_description_id = OLD.product_description_id);
     RETURN OLD;
 END;
 $$
@@ -285,6 +602,55 @@ END;
 $$
 LANGUAGE plpgsql;
 
+-- Trigger function to delete the image when a recipe is deleted
+CREATE OR REPLACE FUNCTION trigger_func_delete_recipe()
+    RETURNS TRIGGER
+    AS $$
+BEGIN
+    DELETE FROM product_image
+    WHERE id = OLD.image;
+    RETURN OLD;
+END;
+$$
+LANGUAGE plpgsql;
+
+-- Trigger function to delete the image when an additional image is deleted
+CREATE OR REPLACE FUNCTION trigger_func_delete_additional_image()
+    RETURNS TRIGGER
+    AS $$
+BEGIN
+    DELETE FROM product_image
+    WHERE id = OLD.image;
+    RETURN OLD;
+END;
+$$
+LANGUAGE plpgsql;
+
+-- Trigger function to delete the image when the photo of a missing product is deleted
+CREATE OR REPLACE FUNCTION trigger_func_delete_missing_product_photo()
+    RETURNS TRIGGER
+    AS $$
+BEGIN
+    DELETE FROM product_image
+    WHERE id = OLD.image;
+    RETURN OLD;
+END;
+$$
+LANGUAGE plpgsql;
+
+-- Trigger function to remember the storage key of a deleted image of the image store
+CREATE OR REPLACE FUNCTION trigger_func_delete_stored_image()
+    RETURNS TRIGGER
+    AS $$
+BEGIN
+    INSERT INTO deleted_stored_images(storage_key)
+        VALUES (OLD.storage_key)
+    ON CONFLICT DO NOTHING;
+    RETURN OLD;
+END;
+$$
+LANGUAGE plpgsql;
+
 --
 -- DEFINITION OF TRIGGERS
 --
@@ -312,3 +678,27 @@ CREATE TRIGGER trigger_insert_product_description
     FOR EACH ROW
     EXECUTE FUNCTION trigger_insert_product_description();
 
+-- Trigger to delete the image when a recipe is deleted
+CREATE TRIGGER trigger_delete_recipe
+    AFTER DELETE ON recipes
+    FOR EACH ROW
+    EXECUTE FUNCTION trigger_func_delete_recipe();
+
+-- Trigger to delete the image when an additional image is deleted
+CREATE TRIGGER trigger_delete_additional_image
+    AFTER DELETE ON additional_images
+    FOR EACH ROW
+    EXECUTE FUNCTION trigger_func_delete_additional_image();
+
+-- Trigger to delete the image when the photo of a missing product is deleted
+CREATE TRIGGER trigger_delete_missing_product_photo
+    AFTER DELETE ON missing_product_photos
+    FOR EACH ROW
+    EXECUTE FUNCTION trigger_func_delete_missing_product_photo();
+
+-- Trigger to remember the storage key of a deleted image of the image store
+CREATE TRIGGER trigger_delete_stored_image
+    AFTER DELETE ON product_image
+    FOR EACH ROW
+    WHEN (OLD.storage_key IS NOT NULL)
+    EXECUTE FUNCTION trigger_func_delete_stored_image();
@@ -0,0 +1,11 @@
+-- Makes the products unique by their id and market instead of their id only, i.e., the same
+-- product id can be added once per market and once without market.
+--
+-- The migration is applied by the service if market_specific_products is enabled in the
+-- Postgres config and can also be applied manually. It requires Postgres 15 or newer.
+
+ALTER TABLE products DROP CONSTRAINT IF EXISTS products_pkey;
+
+-- Products without market are treated as a market of their own
+CREATE UNIQUE INDEX IF NOT EXISTS products_product_id_market_index
+    ON products(product_id, market) NULLS NOT DISTINCT;
@@ -0,0 +1,49 @@
+-- Converts the table reported_missing_products into a table that is partitioned by month.
+-- The partitions are named reported_missing_products_yYYYYmMM and are created on demand by the
+-- service. Old reports are removed by dropping whole partitions instead of deleting rows.
+--
+-- The migration is applied by the service if partition_missing_products is enabled in the
+-- Postgres config and can also be applied manually. The existing reports are kept.
+
+-- Detach the id sequence and the existing reports from the old table
+ALTER SEQUENCE reported_missing_products_id_seq OWNED BY NONE;
+ALTER TABLE reported_missing_products RENAME TO reported_missing_products_unpartitioned;
+ALTER TABLE reported_missing_products_unpartitioned DROP CONSTRAINT reported_missing_products_pkey;
+DROP INDEX reported_missing_products_product_id_index;
+
+-- Products which have been scanned by the users, but are not in the database
+-- The primary key of a partitioned table must contain the partition key
+CREATE TABLE reported_missing_products(
+    id int NOT NULL DEFAULT nextval('reported_missing_products_id_seq'), -- The id of the reported entry
+    product_id varchar(64) NOT NULL, -- The id of the missing product
+    date timestamp with time zone NOT NULL, -- The date when the request was made
+    store_id int, -- The store where the product has been scanned
+    PRIMARY KEY (id, date),
+    FOREIGN KEY (store_id) REFERENCES stores(id) ON DELETE SET NULL
+) PARTITION BY RANGE (date);
+
+ALTER SEQUENCE reported_missing_products_id_seq OWNED BY reported_missing_products.id;
+
+-- Index for product_id in reported_missing_products
+CREATE INDEX reported_missing_products_product_id_index ON reported_missing_products(product_id);
+
+-- Create the partitions for the months of the existing reports
+DO $$
+DECLARE
+    month timestamp;
+BEGIN
+    FOR month IN
+        SELECT DISTINCT date_trunc('month', date AT TIME ZONE 'UTC')
+        FROM reported_missing_products_unpartitioned
+    LOOP
+        EXECUTE format('CREATE TABLE %I PARTITION OF reported_missing_products FOR VALUES FROM (%L) TO (%L)',
+            'reported_missing_products_' || to_char(month, '"y"YYYY"m"MM'),
+            month AT TIME ZONE 'UTC',
+            (month + interval '1 month') AT TIME ZONE 'UTC');
+    END LOOP;
+END $$;
+
+INSERT INTO reported_missing_products(id, product_id, date, store_id)
+    SELECT id, product_id, date, store_id FROM reported_missing_products_unpartitioned;
+
+DROP TABLE reported_missing_products_unpartitioned;
@@ -0,0 +1,17 @@
+-- Creates the optional table for tracking the prices of the products observed in stores.
+--
+-- The migration is applied by the service if price_tracking is enabled in the Postgres config
+-- and can also be applied manually. It can be applied repeatedly.
+
+-- The table that stores the prices of the products observed in stores
+CREATE TABLE IF NOT EXISTS prices(
+    id serial PRIMARY KEY, -- The id of the price entry
+    product_id varchar(64) NOT NULL, -- The id of the product
+    store varchar(64) NOT NULL, -- The store where the price has been observed
+    price bigint NOT NULL, -- The price in minor units of the currency, e.g. cents
+    currency varchar(3) NOT NULL, -- The ISO 4217 code of the currency, e.g. EUR
+    observed_at timestamp with time zone NOT NULL -- The date when the price has been observed
+);
+
+-- Index for finding the cheapest price of a product in a currency
+CREATE INDEX IF NOT EXISTS prices_product_id_currency_price_index ON prices(product_id, currency, price);
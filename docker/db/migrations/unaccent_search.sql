@@ -0,0 +1,24 @@
+-- Enables the accent- and case-insensitive search on the names and producers of the products,
+-- e.g. "cafe" matches "café". The service additionally searches for the German transcription
+-- of the umlauts in the search string, e.g. "Müsli" also matches "Muesli".
+--
+-- The migration is applied by the service if unaccent_search is enabled in the Postgres config
+-- and can also be applied manually. It requires the unaccent extension of the Postgres contrib
+-- package.
+
+CREATE EXTENSION IF NOT EXISTS unaccent WITH SCHEMA public;
+
+-- Folds the given text for the search, i.e., strips the accents and converts it to lower case.
+-- unaccent itself is only stable as it depends on the configured dictionary, the wrapper fixes
+-- the dictionary and can therefore be used in an index.
+CREATE OR REPLACE FUNCTION search_fold(value text) RETURNS text
+    LANGUAGE sql IMMUTABLE STRICT PARALLEL SAFE
+    AS $$
+        SELECT lower(public.unaccent('public.unaccent'::regdictionary, value))
+    $$;
+
+CREATE INDEX IF NOT EXISTS product_description_name_producer_fold_trgm_idx
+    ON product_description USING gin(search_fold(name_producer) gin_trgm_ops);
+
+-- The index is rebuilt, as it may have been created with a previous definition of search_fold.
+REINDEX INDEX product_description_name_producer_fold_trgm_idx;
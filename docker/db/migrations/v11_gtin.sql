@@ -0,0 +1,209 @@
+-- Migrates the database schema from v10 to v11.
+-- Adds the canonical GTIN-14 of the products, i.e., the product id zero-padded to 14 digits, so
+-- that lookups by UPC-A, EAN-13 or ids with stripped leading zeros find the same product.
+
+ALTER TABLE product_description ADD COLUMN IF NOT EXISTS gtin varchar(14) GENERATED ALWAYS AS (
+    CASE WHEN product_id ~ '^[0-9]{1,14}$' THEN lpad(product_id, 14, '0') END
+) STORED;
+
+-- Index for the canonical GTIN-14 in product_description
+CREATE INDEX IF NOT EXISTS product_description_gtin_index ON product_description(gtin);
+
+-- Create a view that joins the requested products with the product description and nutrients
+CREATE OR REPLACE VIEW requested_products_full AS
+SELECT
+    r.id r_id,
+    r.date,
+    p.name,
+    p.producer,
+    p.quantity_type,
+    p.portion,
+    p.product_id,
+    p.volume_weight_ratio,
+    p.preview,
+    p.photo,
+    n.kcal,
+    n.protein_grams,
+    n.fat_grams,
+    n.carbohydrates_grams,
+    n.sugar_grams,
+    n.salt_grams,
+    n.vitamin_a_mg,
+    n.vitamin_c_mg,
+    n.vitamin_d_mug,
+    n.iron_mg,
+    n.calcium_mg,
+    n.magnesium_mg,
+    n.sodium_mg,
+    n.zinc_mg,
+    p.name_producer,
+    p.market,
+    p.packaging_net_quantity,
+    p.packaging_units,
+    p.packaging_material,
+    p.packaging_deposit,
+    p.vegan,
+    p.vegetarian,
+    p.organic,
+    p.gluten_free,
+    p.lactose_free,
+    p.nova_group,
+    p.eco_score_grade,
+    p.eco_score_value,
+    p.eco_score_source,
+    p.allergens,
+    p.gtin
+FROM
+    requested_products r
+    JOIN product_description p ON p.id = r.product_description_id
+    JOIN nutrients n ON p.nutrients = n.id;
+
+-- Create a view that joins the requested products with the product description and nutrients including the preview image
+CREATE OR REPLACE VIEW requested_products_full_with_preview AS
+SELECT
+    r.id AS r_id,
+    r.date,
+    p.name,
+    p.producer,
+    p.quantity_type,
+    p.portion,
+    p.product_id,
+    p.volume_weight_ratio,
+    pi.data AS preview,
+    pi.content_type AS preview_content_type,
+    p.photo,
+    n.kcal,
+    n.protein_grams,
+    n.fat_grams,
+    n.carbohydrates_grams,
+    n.sugar_grams,
+    n.salt_grams,
+    n.vitamin_a_mg,
+    n.vitamin_c_mg,
+    n.vitamin_d_mug,
+    n.iron_mg,
+    n.calcium_mg,
+    n.magnesium_mg,
+    n.sodium_mg,
+    n.zinc_mg,
+    p.name_producer,
+    p.market,
+    p.packaging_net_quantity,
+    p.packaging_units,
+    p.packaging_material,
+    p.packaging_deposit,
+    p.vegan,
+    p.vegetarian,
+    p.organic,
+    p.gluten_free,
+    p.lactose_free,
+    p.nova_group,
+    p.eco_score_grade,
+    p.eco_score_value,
+    p.eco_score_source,
+    p.allergens,
+    p.gtin
+FROM
+    requested_products r
+    JOIN product_description p ON p.id = r.product_description_id
+    JOIN nutrients n ON p.nutrients = n.id
+    LEFT JOIN product_image pi ON p.preview = pi.id;
+
+-- Create a view that joins the products with the product description and nutrients
+CREATE OR REPLACE VIEW products_full AS
+SELECT
+    r.product_id,
+    p.name,
+    p.producer,
+    p.quantity_type,
+    p.portion,
+    p.volume_weight_ratio,
+    p.preview,
+    p.photo,
+    n.kcal,
+    n.protein_grams,
+    n.fat_grams,
+    n.carbohydrates_grams,
+    n.sugar_grams,
+    n.salt_grams,
+    n.vitamin_a_mg,
+    n.vitamin_c_mg,
+    n.vitamin_d_mug,
+    n.iron_mg,
+    n.calcium_mg,
+    n.magnesium_mg,
+    n.sodium_mg,
+    n.zinc_mg,
+    p.name_producer,
+    p.market,
+    p.packaging_net_quantity,
+    p.packaging_units,
+    p.packaging_material,
+    p.packaging_deposit,
+    p.vegan,
+    p.vegetarian,
+    p.organic,
+    p.gluten_free,
+    p.lactose_free,
+    p.nova_group,
+    p.eco_score_grade,
+    p.eco_score_value,
+    p.eco_score_source,
+    p.allergens,
+    p.gtin
+FROM
+    products r
+    JOIN product_description p ON p.id = r.product_description_id
+    JOIN nutrients n ON p.nutrients = n.id;
+
+-- Create a view that joins the products with the product description and nutrients including the preview image
+CREATE OR REPLACE VIEW products_full_with_preview AS
+SELECT
+    r.product_id,
+    p.name,
+    p.producer,
+    p.quantity_type,
+    p.portion,
+    p.volume_weight_ratio,
+    pi.data AS preview,
+    pi.content_type AS preview_content_type,
+    p.photo,
+    n.kcal,
+    n.protein_grams,
+    n.fat_grams,
+    n.carbohydrates_grams,
+    n.sugar_grams,
+    n.salt_grams,
+    n.vitamin_a_mg,
+    n.vitamin_c_mg,
+    n.vitamin_d_mug,
+    n.iron_mg,
+    n.calcium_mg,
+    n.magnesium_mg,
+    n.sodium_mg,
+    n.zinc_mg,
+    p.name_producer,
+    p.market,
+    p.packaging_net_quantity,
+    p.packaging_units,
+    p.packaging_material,
+    p.packaging_deposit,
+    p.vegan,
+    p.vegetarian,
+    p.organic,
+    p.gluten_free,
+    p.lactose_free,
+    p.nova_group,
+    p.eco_score_grade,
+    p.eco_score_value,
+    p.eco_score_source,
+    p.allergens,
+    p.gtin
+FROM
+    products r
+    JOIN product_description p ON p.id = r.product_description_id
+    JOIN nutrients n ON p.nutrients = n.id
+    LEFT JOIN product_image pi ON p.preview = pi.id;
+
+INSERT INTO schema_version(version)
+    VALUES (11);
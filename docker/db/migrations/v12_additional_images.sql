@@ -0,0 +1,47 @@
+-- Migrates the database schema from v11 to v12.
+-- Adds the additional images of the product descriptions besides the preview and full image,
+-- e.g. photos of the nutrition table or the ingredient list of a product request.
+
+-- Define type for the role of the additional images, i.e., what the image shows
+CREATE TYPE ImageRole AS ENUM(
+    'front',
+    'nutrition',
+    'ingredients',
+    'packaging',
+    'other'
+);
+
+-- This table stores the additional images of the product descriptions besides the preview and
+-- full image, e.g. photos of the nutrition table or the ingredient list
+CREATE TABLE IF NOT EXISTS additional_images(
+    id serial PRIMARY KEY, -- The id of the entry, which defines the order of the images
+    product_description_id int NOT NULL, -- The id of the product description entry
+    role ImageRole NOT NULL, -- What the image shows
+    image int NOT NULL, -- Reference onto the image
+    FOREIGN KEY (product_description_id) REFERENCES product_description(id) ON DELETE CASCADE,
+    FOREIGN KEY (image) REFERENCES product_image(id) ON DELETE CASCADE
+);
+
+-- Index for product_description_id in additional_images
+CREATE INDEX IF NOT EXISTS additional_images_product_description_id_index ON additional_images(product_description_id);
+
+-- Trigger function to delete the image when an additional image is deleted
+CREATE OR REPLACE FUNCTION trigger_func_delete_additional_image()
+    RETURNS TRIGGER
+    AS $$
+BEGIN
+    DELETE FROM product_image
+    WHERE id = OLD.image;
+    RETURN OLD;
+END;
+$$
+LANGUAGE plpgsql;
+
+-- Trigger to delete the image when an additional image is deleted
+CREATE TRIGGER trigger_delete_additional_image
+    AFTER DELETE ON additional_images
+    FOR EACH ROW
+    EXECUTE FUNCTION trigger_func_delete_additional_image();
+
+INSERT INTO schema_version(version)
+    VALUES (12);
@@ -0,0 +1,32 @@
+-- Migrates the database schema from v12 to v13.
+-- Adds the photos of the reported missing products, e.g. of their packaging or barcode.
+
+-- This table stores the photos of the reported missing products, e.g. of their packaging or
+-- barcode. The reports are not referenced, as they may be partitioned and expire by dropping
+-- whole partitions.
+CREATE TABLE IF NOT EXISTS missing_product_photos(
+    missing_product_id int PRIMARY KEY, -- The id of the reported missing product
+    image int NOT NULL, -- Reference onto the image
+    FOREIGN KEY (image) REFERENCES product_image(id) ON DELETE CASCADE
+);
+
+-- Trigger function to delete the image when the photo of a missing product is deleted
+CREATE OR REPLACE FUNCTION trigger_func_delete_missing_product_photo()
+    RETURNS TRIGGER
+    AS $$
+BEGIN
+    DELETE FROM product_image
+    WHERE id = OLD.image;
+    RETURN OLD;
+END;
+$$
+LANGUAGE plpgsql;
+
+-- Trigger to delete the image when the photo of a missing product is deleted
+CREATE TRIGGER trigger_delete_missing_product_photo
+    AFTER DELETE ON missing_product_photos
+    FOR EACH ROW
+    EXECUTE FUNCTION trigger_func_delete_missing_product_photo();
+
+INSERT INTO schema_version(version)
+    VALUES (13);
@@ -0,0 +1,25 @@
+-- Migrates the database schema from v13 to v14.
+-- Keeps the product description of an approved product request, which is taken over by the
+-- product, when the request is deleted.
+
+-- Index for product_description_id in products
+CREATE INDEX IF NOT EXISTS products_product_description_id_index ON products(product_description_id);
+
+-- Trigger function to delete the product description when a product request is deleted
+-- The description is kept if it has been taken over by a product, i.e., the request has been approved
+CREATE OR REPLACE FUNCTION trigger_func_delete_product_or_requested_product()
+    RETURNS TRIGGER
+    AS $$
+BEGIN
+    DELETE FROM product_description
+    WHERE id = OLD.product_description_id
+        AND NOT EXISTS (
+            SELECT 1 FROM products
+            WHERE product_description_id = OLD.product_description_id);
+    RETURN OLD;
+END;
+$$
+LANGUAGE plpgsql;
+
+INSERT INTO schema_version(version)
+    VALUES (14);
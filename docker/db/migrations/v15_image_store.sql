@@ -0,0 +1,45 @@
+-- Migrates the database schema from v14 to v15.
+-- Allows storing the images in an external image store, e.g. the local filesystem or an S3
+-- bucket, instead of the bytea column. Such images are referenced by their storage key.
+
+ALTER TABLE product_image ADD COLUMN IF NOT EXISTS storage_key varchar(255);
+ALTER TABLE product_image ADD COLUMN IF NOT EXISTS storage_size int8;
+
+-- The keys of the deleted images of the image store, whose objects are yet to be deleted
+CREATE TABLE IF NOT EXISTS deleted_stored_images(
+    storage_key varchar(255) PRIMARY KEY
+);
+
+CREATE OR REPLACE VIEW requested_products_full_image AS
+SELECT
+    r.id AS r_id,
+    pi.data,
+    pi.content_type,
+    pi.storage_key
+FROM
+    requested_products r
+    JOIN product_description p ON p.id = r.product_description_id
+    JOIN product_image pi ON p.photo = pi.id;
+
+-- Trigger function to remember the storage key of a deleted image of the image store
+CREATE OR REPLACE FUNCTION trigger_func_delete_stored_image()
+    RETURNS TRIGGER
+    AS $$
+BEGIN
+    INSERT INTO deleted_stored_images(storage_key)
+        VALUES (OLD.storage_key)
+    ON CONFLICT DO NOTHING;
+    RETURN OLD;
+END;
+$$
+LANGUAGE plpgsql;
+
+-- Trigger to remember the storage key of a deleted image of the image store
+CREATE TRIGGER trigger_delete_stored_image
+    AFTER DELETE ON product_image
+    FOR EACH ROW
+    WHEN (OLD.storage_key IS NOT NULL)
+    EXECUTE FUNCTION trigger_func_delete_stored_image();
+
+INSERT INTO schema_version(version)
+    VALUES (15);
@@ -0,0 +1,121 @@
+-- Migrates the database schema from v15 to v16.
+-- Adds the review status and the comment of the reviewer to the product requests, which is also
+-- kept in the archive.
+
+-- Define type for the review status of product requests
+CREATE TYPE RequestStatus AS ENUM(
+    'pending',
+    'approved',
+    'rejected'
+);
+
+ALTER TABLE requested_products ADD COLUMN IF NOT EXISTS status RequestStatus NOT NULL DEFAULT 'pending';
+ALTER TABLE requested_products ADD COLUMN IF NOT EXISTS review_comment text;
+ALTER TABLE archived_product_requests ADD COLUMN IF NOT EXISTS review_comment text;
+
+-- Create a view that joins the requested products with the product description and nutrients
+CREATE OR REPLACE VIEW requested_products_full AS
+SELECT
+    r.id r_id,
+    r.date,
+    p.name,
+    p.producer,
+    p.quantity_type,
+    p.portion,
+    p.product_id,
+    p.volume_weight_ratio,
+    p.preview,
+    p.photo,
+    n.kcal,
+    n.protein_grams,
+    n.fat_grams,
+    n.carbohydrates_grams,
+    n.sugar_grams,
+    n.salt_grams,
+    n.vitamin_a_mg,
+    n.vitamin_c_mg,
+    n.vitamin_d_mug,
+    n.iron_mg,
+    n.calcium_mg,
+    n.magnesium_mg,
+    n.sodium_mg,
+    n.zinc_mg,
+    p.name_producer,
+    p.market,
+    p.packaging_net_quantity,
+    p.packaging_units,
+    p.packaging_material,
+    p.packaging_deposit,
+    p.vegan,
+    p.vegetarian,
+    p.organic,
+    p.gluten_free,
+    p.lactose_free,
+    p.nova_group,
+    p.eco_score_grade,
+    p.eco_score_value,
+    p.eco_score_source,
+    p.allergens,
+    p.gtin,
+    r.status,
+    r.review_comment
+FROM
+    requested_products r
+    JOIN product_description p ON p.id = r.product_description_id
+    JOIN nutrients n ON p.nutrients = n.id;
+
+-- Create a view that joins the requested products with the product description and nutrients including the preview image
+CREATE OR REPLACE VIEW requested_products_full_with_preview AS
+SELECT
+    r.id AS r_id,
+    r.date,
+    p.name,
+    p.producer,
+    p.quantity_type,
+    p.portion,
+    p.product_id,
+    p.volume_weight_ratio,
+    pi.data AS preview,
+    pi.content_type AS preview_content_type,
+    p.photo,
+    n.kcal,
+    n.protein_grams,
+    n.fat_grams,
+    n.carbohydrates_grams,
+    n.sugar_grams,
+    n.salt_grams,
+    n.vitamin_a_mg,
+    n.vitamin_c_mg,
+    n.vitamin_d_mug,
+    n.iron_mg,
+    n.calcium_mg,
+    n.magnesium_mg,
+    n.sodium_mg,
+    n.zinc_mg,
+    p.name_producer,
+    p.market,
+    p.packaging_net_quantity,
+    p.packaging_units,
+    p.packaging_material,
+    p.packaging_deposit,
+    p.vegan,
+    p.vegetarian,
+    p.organic,
+    p.gluten_free,
+    p.lactose_free,
+    p.nova_group,
+    p.eco_score_grade,
+    p.eco_score_value,
+    p.eco_score_source,
+    p.allergens,
+    p.gtin,
+    r.status,
+    r.review_comment
+FROM
+    requested_products r
+    JOIN product_description p ON p.id = r.product_description_id
+    JOIN nutrients n ON p.nutrients = n.id
+    LEFT JOIN product_image pi ON p.preview = pi.id;
+
+INSERT INTO schema_version(version)
+    VALUES (16);
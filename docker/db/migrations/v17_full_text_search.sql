@@ -0,0 +1,46 @@
+-- Migrates the database schema from v16 to v17.
+-- Adds the full-text search on the names and producers of the products, which replaces the
+-- substring search. The trigram index is kept for the fuzzy fallback and the similarity.
+
+-- Returns the text search vector of the name and producer of a product for the full-text
+-- search. The words are indexed unchanged and stemmed in the language of the market of the
+-- product, e.g. German for DE, such that "Joghurt" also finds "Joghurts". The products
+-- without market or of markets without a dedicated language are stemmed as English.
+CREATE OR REPLACE FUNCTION search_vector(name_producer text, market text) RETURNS tsvector
+    LANGUAGE sql IMMUTABLE PARALLEL SAFE
+    AS $$
+        SELECT to_tsvector('pg_catalog.simple', coalesce(name_producer, ''))
+            || to_tsvector(
+                CASE upper(market)
+                    WHEN 'DE' THEN 'pg_catalog.german'::regconfig
+                    WHEN 'AT' THEN 'pg_catalog.german'::regconfig
+                    WHEN 'CH' THEN 'pg_catalog.german'::regconfig
+                    WHEN 'FR' THEN 'pg_catalog.french'::regconfig
+                    WHEN 'IT' THEN 'pg_catalog.italian'::regconfig
+                    WHEN 'ES' THEN 'pg_catalog.spanish'::regconfig
+                    WHEN 'NL' THEN 'pg_catalog.dutch'::regconfig
+                    ELSE 'pg_catalog.english'::regconfig
+                END,
+                coalesce(name_producer, ''))
+    $$;
+
+-- Returns the text search query for the given query in the syntax of to_tsquery, which
+-- matches the unchanged words and the words stemmed in any of the languages of
+-- search_vector.
+CREATE OR REPLACE FUNCTION search_query(query text) RETURNS tsquery
+    LANGUAGE sql IMMUTABLE STRICT PARALLEL SAFE
+    AS $$
+        SELECT to_tsquery('pg_catalog.simple', query)
+            || to_tsquery('pg_catalog.german', query)
+            || to_tsquery('pg_catalog.english', query)
+            || to_tsquery('pg_catalog.french', query)
+            || to_tsquery('pg_catalog.italian', query)
+            || to_tsquery('pg_catalog.spanish', query)
+            || to_tsquery('pg_catalog.dutch', query)
+    $$;
+
+CREATE INDEX IF NOT EXISTS product_description_search_vector_idx
+    ON product_description USING gin(search_vector(name_producer, market));
+
+INSERT INTO schema_version(version)
+    VALUES (17);
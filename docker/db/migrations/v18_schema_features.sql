@@ -0,0 +1,13 @@
+-- Migrates the database schema from v17 to v18.
+-- Adds the table that records the optional schema features, i.e., the partitioning, price
+-- tracking, market specific products and unaccented search. The service applies the enabled
+-- features at startup, which have not been applied to the current schema version.
+
+CREATE TABLE IF NOT EXISTS schema_features(
+    name text PRIMARY KEY, -- The name of the optional feature
+    version int NOT NULL, -- The schema version the feature has been applied to
+    applied_at timestamp with time zone NOT NULL DEFAULT now() -- When the feature has been applied
+);
+
+INSERT INTO schema_version(version)
+    VALUES (18);
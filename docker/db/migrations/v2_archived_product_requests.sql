@@ -0,0 +1,25 @@
+-- Migrates the database schema from v1 to v2.
+-- Adds the archive for processed product requests.
+
+-- Define type for the outcome of processed product requests
+CREATE TYPE RequestOutcome AS ENUM(
+    'approved',
+    'rejected'
+);
+
+-- This table stores the processed product requests, which are moved here instead of being deleted
+CREATE TABLE IF NOT EXISTS archived_product_requests(
+    id int PRIMARY KEY, -- The original id of the product request
+    product_id varchar(64) NOT NULL, -- The id of the requested product
+    name varchar(64) NOT NULL, -- The name of the requested product
+    producer varchar(64), -- The producer of the requested product
+    date timestamp with time zone NOT NULL, -- The date when the product was requested
+    archived_date timestamp with time zone NOT NULL, -- The date when the request was archived
+    outcome RequestOutcome NOT NULL -- If the requested product was approved or rejected
+);
+
+-- Index for product_id in archived_product_requests
+CREATE INDEX IF NOT EXISTS archived_product_requests_product_id_index ON archived_product_requests(product_id);
+
+INSERT INTO schema_version(version)
+    VALUES (2);
@@ -0,0 +1,46 @@
+-- Migrates the database schema from v2 to v3.
+-- Adds the recipes, which are composed of products.
+
+-- This table stores the recipes, which are composed of products
+CREATE TABLE IF NOT EXISTS recipes(
+    id serial PRIMARY KEY, -- The id of the recipe
+    name varchar(64) NOT NULL, -- The name of the recipe
+    servings int NOT NULL, -- The number of portions the recipe yields
+    image int, -- Reference onto an image of the recipe
+    FOREIGN KEY (image) REFERENCES product_image(id)
+);
+
+-- This table stores the ingredients of the recipes
+-- The ingredients reference the products by their id, i.e., they are kept if the product is deleted
+CREATE TABLE IF NOT EXISTS recipe_ingredients(
+    id serial PRIMARY KEY, -- The id of the ingredient entry
+    recipe_id int NOT NULL, -- The id of the recipe
+    position int NOT NULL, -- The position of the ingredient in the recipe
+    product_id varchar(64) NOT NULL, -- The id of the product
+    amount real NOT NULL, -- The amount of the product in grams or ml depending on the quantity type
+    FOREIGN KEY (recipe_id) REFERENCES recipes(id) ON DELETE CASCADE
+);
+
+-- Index for recipe_id in recipe_ingredients
+CREATE INDEX IF NOT EXISTS recipe_ingredients_recipe_id_index ON recipe_ingredients(recipe_id);
+
+-- Trigger function to delete the image when a recipe is deleted
+CREATE OR REPLACE FUNCTION trigger_func_delete_recipe()
+    RETURNS TRIGGER
+    AS $$
+BEGIN
+    DELETE FROM product_image
+    WHERE id = OLD.image;
+    RETURN OLD;
+END;
+$$
+LANGUAGE plpgsql;
+
+-- Trigger to delete the image when a recipe is deleted
+CREATE TRIGGER trigger_delete_recipe
+    AFTER DELETE ON recipes
+    FOR EACH ROW
+    EXECUTE FUNCTION trigger_func_delete_recipe();
+
+INSERT INTO schema_version(version)
+    VALUES (3);
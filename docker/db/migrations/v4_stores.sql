@@ -0,0 +1,34 @@
+-- Migrates the database schema from v3 to v4.
+-- Adds the stores, the availability of the products in the stores and the optional store of the
+-- reported missing products.
+
+-- The stores and retailers where the products are sold
+CREATE TABLE IF NOT EXISTS stores(
+    id serial PRIMARY KEY, -- The id of the store
+    name varchar(64) NOT NULL, -- The name of the store
+    chain varchar(64) -- The retail chain the store belongs to
+);
+
+-- Index for the chain in stores
+CREATE INDEX IF NOT EXISTS stores_chain_index ON stores(chain);
+
+-- The store where the missing product has been scanned, works for partitioned tables as well
+ALTER TABLE reported_missing_products
+    ADD COLUMN IF NOT EXISTS store_id int REFERENCES stores(id) ON DELETE SET NULL;
+
+-- This table stores the last observed availability of the products in the stores
+-- The products are referenced by their id, i.e., the availability is kept if the product is deleted
+CREATE TABLE IF NOT EXISTS product_availability(
+    store_id int NOT NULL, -- The id of the store
+    product_id varchar(64) NOT NULL, -- The id of the product
+    available boolean NOT NULL, -- If the product has been seen or has been missing at the store
+    date timestamp with time zone NOT NULL, -- The date when the availability was observed
+    PRIMARY KEY (store_id, product_id),
+    FOREIGN KEY (store_id) REFERENCES stores(id) ON DELETE CASCADE
+);
+
+-- Index for product_id in product_availability
+CREATE INDEX IF NOT EXISTS product_availability_product_id_index ON product_availability(product_id);
+
+INSERT INTO schema_version(version)
+    VALUES (4);
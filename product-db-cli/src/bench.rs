@@ -0,0 +1,547 @@
+use std::{
+    collections::BTreeMap,
+    fmt::Display,
+    time::{Duration, Instant},
+};
+
+use anyhow::{bail, Result};
+use clap::ValueEnum;
+use log::{debug, info, warn};
+use product_db::{
+    service_json::ProductQueryResponse, ProductDescription, ProductGenerator, ProductID,
+    ProductInfo, ProductQuery, SearchFilter,
+};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use reqwest::{Client, StatusCode, Url};
+
+/// The number of products that are fetched for choosing the ids of the product gets.
+const SAMPLE_PRODUCTS: i32 = 200;
+
+/// The page size of the benchmarked product queries.
+const QUERY_LIMIT: i32 = 20;
+
+/// The mix of operations the benchmark sends to the service.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum BenchProfile {
+    /// Mostly product gets and queries with a few submissions.
+    ReadHeavy,
+    /// Reads and submissions in similar proportions.
+    Mixed,
+    /// Mostly submissions of product requests.
+    WriteHeavy,
+}
+
+impl BenchProfile {
+    /// Returns the relative weights of the operations of the profile.
+    fn weights(self) -> [(Operation, u32); 3] {
+        let (get, query, submit) = match self {
+            BenchProfile::ReadHeavy => (70, 25, 5),
+            BenchProfile::Mixed => (40, 30, 30),
+            BenchProfile::WriteHeavy => (10, 10, 80),
+        };
+
+        [
+            (Operation::GetProduct, get),
+            (Operation::QueryProducts, query),
+            (Operation::SubmitRequest, submit),
+        ]
+    }
+
+    /// Chooses the next operation according to the weights of the profile.
+    ///
+    /// # Arguments
+    /// * `rng` - The random number generator.
+    fn choose(self, rng: &mut impl Rng) -> Operation {
+        let weights = self.weights();
+        let total: u32 = weights.iter().map(|(_, weight)| weight).sum();
+
+        let mut value = rng.random_range(0..total);
+        for (operation, weight) in weights {
+            if value < weight {
+                return operation;
+            }
+            value -= weight;
+        }
+
+        unreachable!("the value is smaller than the sum of the weights")
+    }
+}
+
+/// The operations sent by the benchmark.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Operation {
+    /// `GET user/product/{id}` of a known or unknown product.
+    GetProduct,
+    /// `POST user/product/query` with a search filter.
+    QueryProducts,
+    /// `POST user/product_request` with a new product.
+    SubmitRequest,
+}
+
+impl Display for Operation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Operation::GetProduct => write!(f, "get product"),
+            Operation::QueryProducts => write!(f, "query products"),
+            Operation::SubmitRequest => write!(f, "submit request"),
+        }
+    }
+}
+
+/// The options of the benchmark.
+#[derive(Debug, Clone)]
+pub struct BenchOptions {
+    /// The base url of the running instance including the prefix, e.g. `http://localhost:8888/v1`.
+    pub target: Url,
+    /// The mix of operations.
+    pub profile: BenchProfile,
+    /// The duration of the benchmark.
+    pub duration: Duration,
+    /// The number of concurrent clients.
+    pub concurrency: usize,
+}
+
+/// The measured latency of a single request.
+#[derive(Debug, Clone, Copy)]
+struct Sample {
+    operation: Operation,
+    latency: Duration,
+    failed: bool,
+}
+
+/// The statistics of the requests of a single operation or of all operations.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LatencyStats {
+    /// The number of requests.
+    pub requests: usize,
+    /// The number of failed requests.
+    pub errors: usize,
+    /// The requests per second.
+    pub throughput: f64,
+    /// The median latency.
+    pub p50: Duration,
+    /// The 90th percentile of the latency.
+    pub p90: Duration,
+    /// The 99th percentile of the latency.
+    pub p99: Duration,
+    /// The maximal latency.
+    pub max: Duration,
+}
+
+impl LatencyStats {
+    /// Computes the statistics of the given latencies.
+    ///
+    /// # Arguments
+    /// * `latencies` - The latencies of the requests.
+    /// * `errors` - The number of failed requests.
+    /// * `elapsed` - The duration of the benchmark.
+    fn new(mut latencies: Vec<Duration>, errors: usize, elapsed: Duration) -> Self {
+        latencies.sort_unstable();
+
+        Self {
+            requests: latencies.len(),
+            errors,
+            throughput: latencies.len() as f64 / elapsed.as_secs_f64(),
+            p50: percentile(&latencies, 50.0),
+            p90: percentile(&latencies, 90.0),
+            p99: percentile(&latencies, 99.0),
+            max: latencies.last().copied().unwrap_or_default(),
+        }
+    }
+}
+
+/// The result of a benchmark.
+#[derive(Debug, Clone)]
+pub struct BenchReport {
+    /// The actual duration of the benchmark.
+    pub elapsed: Duration,
+    /// The statistics of all requests.
+    pub total: LatencyStats,
+    /// The statistics of the requests per operation.
+    operations: BTreeMap<Operation, LatencyStats>,
+}
+
+impl BenchReport {
+    /// Computes the report from the measured samples.
+    ///
+    /// # Arguments
+    /// * `samples` - The measured samples of all clients.
+    /// * `elapsed` - The actual duration of the benchmark.
+    fn new(samples: &[Sample], elapsed: Duration) -> Self {
+        let mut latencies: BTreeMap<Operation, (Vec<Duration>, usize)> = BTreeMap::new();
+        for sample in samples {
+            let (operation_latencies, errors) = latencies.entry(sample.operation).or_default();
+            operation_latencies.push(sample.latency);
+            *errors += usize::from(sample.failed);
+        }
+
+        let total = LatencyStats::new(
+            samples.iter().map(|sample| sample.latency).collect(),
+            samples.iter().filter(|sample| sample.failed).count(),
+            elapsed,
+        );
+        let operations = latencies
+            .into_iter()
+            .map(|(operation, (latencies, errors))| {
+                (operation, LatencyStats::new(latencies, errors, elapsed))
+            })
+            .collect();
+
+        Self {
+            elapsed,
+            total,
+            operations,
+        }
+    }
+}
+
+impl Display for BenchReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Duration: {:.1}s", self.elapsed.as_secs_f64())?;
+        writeln!(
+            f,
+            "{:<16} {:>9} {:>7} {:>10} {:>10} {:>10} {:>10} {:>10}",
+            "operation", "requests", "errors", "req/s", "p50", "p90", "p99", "max"
+        )?;
+
+        let rows = self
+            .operations
+            .iter()
+            .map(|(operation, stats)| (operation.to_string(), stats))
+            .chain(std::iter::once(("total".to_string(), &self.total)));
+        for (name, stats) in rows {
+            writeln!(
+                f,
+                "{:<16} {:>9} {:>7} {:>10.1} {:>10} {:>10} {:>10} {:>10}",
+                name,
+                stats.requests,
+                stats.errors,
+                stats.throughput,
+                format_latency(stats.p50),
+                format_latency(stats.p90),
+                format_latency(stats.p99),
+                format_latency(stats.max)
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns the given percentile of the sorted latencies using the nearest-rank method.
+///
+/// # Arguments
+/// * `sorted` - The latencies in ascending order.
+/// * `percentile` - The percentile between 0 and 100.
+fn percentile(sorted: &[Duration], percentile: f64) -> Duration {
+    if sorted.is_empty() {
+        return Duration::ZERO;
+    }
+
+    let rank = (percentile / 100.0 * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+/// Formats the latency in milliseconds.
+///
+/// # Arguments
+/// * `latency` - The latency to format.
+fn format_latency(latency: Duration) -> String {
+    format!("{:.2}ms", latency.as_secs_f64() * 1e3)
+}
+
+/// Runs the benchmark against the running instance and returns the measured latencies.
+///
+/// # Arguments
+/// * `options` - The options of the benchmark.
+pub async fn run_bench(options: BenchOptions) -> Result<BenchReport> {
+    if options.concurrency == 0 {
+        bail!("The concurrency of the benchmark must be at least 1");
+    }
+
+    // the relative paths are resolved against the target, which must end with a slash
+    let mut target = options.target.clone();
+    if !target.path().ends_with('/') {
+        target.set_path(&format!("{}/", target.path()));
+    }
+
+    let client = Client::new();
+    let products = sample_products(&client, &target).await?;
+    if products.is_empty() {
+        warn!("The target has no products, all product gets will be answered with 404");
+    }
+
+    info!(
+        "Running the {:?} benchmark against {} for {}s with {} clients...",
+        options.profile,
+        target,
+        options.duration.as_secs(),
+        options.concurrency
+    );
+
+    let start = Instant::now();
+    let deadline = start + options.duration;
+    let mut clients = Vec::with_capacity(options.concurrency);
+    for _ in 0..options.concurrency {
+        let client = BenchClient {
+            client: client.clone(),
+            target: target.clone(),
+            products: products.clone(),
+            rng: StdRng::from_os_rng(),
+            generator: ProductGenerator::from_os_rng(),
+        };
+
+        clients.push(tokio::spawn(client.run(options.profile, deadline)));
+    }
+
+    let mut samples = Vec::new();
+    for client in clients {
+        samples.extend(client.await?);
+    }
+
+    Ok(BenchReport::new(&samples, start.elapsed()))
+}
+
+/// Fetches the products whose ids are used for the product gets.
+///
+/// # Arguments
+/// * `client` - The http client.
+/// * `target` - The base url of the instance.
+async fn sample_products(client: &Client, target: &Url) -> Result<Vec<ProductInfo>> {
+    let query = ProductQuery {
+        offset: 0,
+        limit: SAMPLE_PRODUCTS,
+        filter: SearchFilter::NoFilter,
+        sorting: None,
+        market: None,
+        producer: None,
+        quantity_type: None,
+        labels: None,
+        max_nova_group: None,
+        min_eco_score_grade: None,
+        exclude_allergens: Vec::new(),
+        data_issues: Vec::new(),
+        fields: None,
+        request_status: None,
+        cursor: None,
+    };
+
+    let response = client
+        .post(target.join("user/product/query")?)
+        .json(&query)
+        .send()
+        .await?;
+    if !response.status().is_success() {
+        bail!(
+            "Failed to fetch the products of {}: {}",
+            target,
+            response.status()
+        );
+    }
+
+    let response: ProductQueryResponse = response.json().await?;
+    debug!(
+        "Fetched {} products for the benchmark",
+        response.products.len()
+    );
+
+    Ok(response
+        .products
+        .into_iter()
+        .map(|product| product.info)
+        .collect())
+}
+
+/// A single client of the benchmark sending one request after another.
+struct BenchClient {
+    client: Client,
+    target: Url,
+    products: Vec<ProductInfo>,
+    rng: StdRng,
+    generator: ProductGenerator,
+}
+
+impl BenchClient {
+    /// Sends requests until the deadline and returns the measured samples.
+    ///
+    /// # Arguments
+    /// * `profile` - The mix of operations.
+    /// * `deadline` - The end of the benchmark.
+    async fn run(mut self, profile: BenchProfile, deadline: Instant) -> Vec<Sample> {
+        let mut samples = Vec::new();
+        while Instant::now() < deadline {
+            let operation = profile.choose(&mut self.rng);
+
+            let start = Instant::now();
+            let failed = match self.send(operation).await {
+                Ok(status) => !is_expected_status(operation, status),
+                Err(err) => {
+                    debug!("{} failed: {}", operation, err);
+                    true
+                }
+            };
+
+            samples.push(Sample {
+                operation,
+                latency: start.elapsed(),
+                failed,
+            });
+        }
+
+        samples
+    }
+
+    /// Sends the request of the given operation and returns the status of the response.
+    ///
+    /// # Arguments
+    /// * `operation` - The operation to send.
+    async fn send(&mut self, operation: Operation) -> Result<StatusCode> {
+        let request = match operation {
+            Operation::GetProduct => {
+                let id = self.random_product_id();
+                self.client
+                    .get(self.target.join(&format!("user/product/{}", id))?)
+            }
+            Operation::QueryProducts => {
+                let query = ProductQuery {
+                    offset: 0,
+                    limit: QUERY_LIMIT,
+                    filter: self.random_filter(),
+                    sorting: None,
+                    market: None,
+                    producer: None,
+                    quantity_type: None,
+                    labels: None,
+                    max_nova_group: None,
+                    min_eco_score_grade: None,
+                    exclude_allergens: Vec::new(),
+                    data_issues: Vec::new(),
+                    fields: None,
+                    request_status: None,
+                    cursor: None,
+                };
+                self.client
+                    .post(self.target.join("user/product/query")?)
+                    .json(&query)
+            }
+            Operation::SubmitRequest => {
+                let product = self.random_product();
+                self.client
+                    .post(self.target.join("user/product_request")?)
+                    .json(&product)
+            }
+        };
+
+        let response = request.send().await?;
+        let status = response.status();
+
+        // the body is part of the latency
+        response.bytes().await?;
+
+        Ok(status)
+    }
+
+    /// Returns the id of a known product or a random id if the target has no products.
+    fn random_product_id(&mut self) -> ProductID {
+        if self.products.is_empty() {
+            format!("bench-{}", self.rng.random::<u32>())
+        } else {
+            let index = self.rng.random_range(0..self.products.len());
+            self.products[index].id.clone()
+        }
+    }
+
+    /// Returns a search filter for the first word of the name of a known product.
+    fn random_filter(&mut self) -> SearchFilter {
+        if self.products.is_empty() {
+            return SearchFilter::NoFilter;
+        }
+
+        let index = self.rng.random_range(0..self.products.len());
+        match self.products[index].name.split_whitespace().next() {
+            Some(word) => SearchFilter::Search(word.to_string()),
+            None => SearchFilter::NoFilter,
+        }
+    }
+
+    /// Returns a new product for submitting a product request.
+    fn random_product(&mut self) -> ProductDescription {
+        self.generator.generate()
+    }
+}
+
+/// Returns true if the status is an expected answer to the operation. Gets of unknown
+/// products are expected to be answered with 404.
+///
+/// # Arguments
+/// * `operation` - The sent operation.
+/// * `status` - The status of the response.
+fn is_expected_status(operation: Operation, status: StatusCode) -> bool {
+    status.is_success() || (operation == Operation::GetProduct && status == StatusCode::NOT_FOUND)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_percentile() {
+        let latencies: Vec<Duration> = (1..=100).map(Duration::from_millis).collect();
+
+        assert_eq!(percentile(&latencies, 50.0), Duration::from_millis(50));
+        assert_eq!(percentile(&latencies, 90.0), Duration::from_millis(90));
+        assert_eq!(percentile(&latencies, 99.0), Duration::from_millis(99));
+        assert_eq!(percentile(&latencies, 100.0), Duration::from_millis(100));
+        assert_eq!(percentile(&latencies, 0.0), Duration::from_millis(1));
+        assert_eq!(percentile(&[], 50.0), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_profile_mix() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let mut counts = BTreeMap::new();
+        for _ in 0..10_000 {
+            *counts
+                .entry(BenchProfile::ReadHeavy.choose(&mut rng))
+                .or_insert(0) += 1;
+        }
+
+        // the shares are close to the weights 70/25/5
+        assert!((6500..7500).contains(&counts[&Operation::GetProduct]));
+        assert!((2000..3000).contains(&counts[&Operation::QueryProducts]));
+        assert!((200..800).contains(&counts[&Operation::SubmitRequest]));
+    }
+
+    #[test]
+    fn test_report() {
+        let samples = [
+            Sample {
+                operation: Operation::GetProduct,
+                latency: Duration::from_millis(10),
+                failed: false,
+            },
+            Sample {
+                operation: Operation::GetProduct,
+                latency: Duration::from_millis(30),
+                failed: true,
+            },
+            Sample {
+                operation: Operation::SubmitRequest,
+                latency: Duration::from_millis(20),
+                failed: false,
+            },
+        ];
+
+        let report = BenchReport::new(&samples, Duration::from_secs(2));
+        assert_eq!(report.total.requests, 3);
+        assert_eq!(report.total.errors, 1);
+        assert_eq!(report.total.throughput, 1.5);
+        assert_eq!(report.total.p50, Duration::from_millis(20));
+        assert_eq!(report.total.max, Duration::from_millis(30));
+
+        let gets = &report.operations[&Operation::GetProduct];
+        assert_eq!(gets.requests, 2);
+        assert_eq!(gets.errors, 1);
+        assert!(!report.operations.contains_key(&Operation::QueryProducts));
+    }
+}
@@ -0,0 +1,72 @@
+use std::{
+    fs::File,
+    io::{stdout, BufWriter, Write},
+    path::PathBuf,
+};
+
+use anyhow::{Context, Result};
+use clap::ValueEnum;
+use futures::StreamExt;
+use log::info;
+use product_db::{csv_line, product_csv_row, ReadBackend, PRODUCT_CSV_COLUMNS};
+
+/// The number of exported products after which the progress is logged.
+const PROGRESS_INTERVAL: u64 = 10000;
+
+/// The format of an exported product catalog.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ExportFormat {
+    /// The comma-separated product catalog as described in `doc/product-csv.md`.
+    Csv,
+}
+
+/// The options of the `export` command.
+pub struct ExportOptions {
+    /// The path to the exported file or `-` for writing it to stdout.
+    pub path: PathBuf,
+    /// The format of the export.
+    pub format: ExportFormat,
+}
+
+/// Exports all products of the database without their images and returns the number of
+/// exported products. The products are streamed page by page, i.e., also large catalogs can
+/// be exported.
+///
+/// # Arguments
+/// * `db` - The backend the products are read from.
+/// * `options` - The options of the export.
+pub async fn export_catalog<B: ReadBackend>(db: &B, options: &ExportOptions) -> Result<u64> {
+    let writer: Box<dyn Write> = if options.path.as_os_str() == "-" {
+        Box::new(stdout().lock())
+    } else {
+        let file = File::create(&options.path)
+            .with_context(|| format!("Failed to create '{}'", options.path.display()))?;
+        Box::new(file)
+    };
+    let mut writer = BufWriter::new(writer);
+    info!(
+        "Exporting the products as {:?} to '{}'",
+        options.format,
+        options.path.display()
+    );
+
+    let mut header = PRODUCT_CSV_COLUMNS.join(",");
+    header.push_str("\r\n");
+    writer.write_all(header.as_bytes())?;
+
+    let mut exported = 0;
+    let mut products = std::pin::pin!(db.export_products());
+    while let Some(product) = products.next().await {
+        writer.write_all(csv_line(&product_csv_row(&product?)).as_bytes())?;
+
+        exported += 1;
+        if exported % PROGRESS_INTERVAL == 0 {
+            info!("Exported {} products...", exported);
+        }
+    }
+    writer.flush()?;
+
+    info!("Exported {} products", exported);
+
+    Ok(exported)
+}
@@ -0,0 +1,790 @@
+use std::{
+    collections::HashMap,
+    fmt::Display,
+    fs::File,
+    io::{stdin, BufRead, BufReader},
+    path::{Path, PathBuf},
+};
+
+use anyhow::{anyhow, bail, Context, Result};
+use clap::ValueEnum;
+use log::{debug, info};
+use product_db::{
+    Allergen, CsvReader, DietaryLabels, EcoScore, EcoScoreGrade, Nutrients, Packaging,
+    ProductCsvColumns, ProductDescription, ProductInfo, QuantityType, Weight, WriteBackend,
+};
+use serde_json::Value;
+
+/// The source stored as the origin of the imported Eco-Scores.
+const ECO_SCORE_SOURCE: &str = "Open Food Facts";
+
+/// The portion in grams or ml of the products without a serving size.
+const DEFAULT_PORTION: f32 = 100.0;
+
+/// The number of kJ that correspond to 1 kcal.
+const KJ_PER_KCAL: f32 = 4.184;
+
+/// The fields of the dump that are imported. The nutrients are given per 100g or 100ml in
+/// grams, i.e., also the vitamins and minerals.
+const FIELDS: &[&str] = &[
+    "code",
+    "product_name",
+    "brands",
+    "countries_tags",
+    "completeness",
+    "quantity",
+    "product_quantity",
+    "serving_quantity",
+    "labels_tags",
+    "allergens_tags",
+    "nova_group",
+    "ecoscore_grade",
+    "ecoscore_score",
+    "energy-kcal_100g",
+    "energy_100g",
+    "proteins_100g",
+    "fat_100g",
+    "carbohydrates_100g",
+    "sugars_100g",
+    "salt_100g",
+    "vitamin-a_100g",
+    "vitamin-c_100g",
+    "vitamin-d_100g",
+    "iron_100g",
+    "calcium_100g",
+    "magnesium_100g",
+    "sodium_100g",
+    "zinc_100g",
+];
+
+/// The format of an Open Food Facts dump.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum DumpFormat {
+    /// The tab-separated CSV export with one product per line, e.g.
+    /// `en.openfoodfacts.org.products.csv`, or a product catalog exported by the `export`
+    /// command, which is detected by its header.
+    Csv,
+    /// The JSONL export with one product document per line, e.g.
+    /// `openfoodfacts-products.jsonl`.
+    Jsonl,
+}
+
+impl DumpFormat {
+    /// Returns the format of the dump derived from its file extension, if known.
+    ///
+    /// # Arguments
+    /// * `path` - The path to the dump.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()? {
+            "csv" | "tsv" => Some(DumpFormat::Csv),
+            "jsonl" | "json" => Some(DumpFormat::Jsonl),
+            _ => None,
+        }
+    }
+}
+
+/// The options of the `import` command.
+pub struct ImportOptions {
+    /// The path to the dump or `-` for reading it from stdin, e.g. `zcat dump.jsonl.gz |`.
+    pub path: PathBuf,
+    /// The format of the dump, derived from the file extension if not set.
+    pub format: Option<DumpFormat>,
+    /// The countries the imported products must be sold in, e.g. `germany` or `en:germany`.
+    /// All products are imported if empty.
+    pub countries: Vec<String>,
+    /// The minimal completeness from 0 to 1 of the imported products as rated by Open Food
+    /// Facts.
+    pub min_completeness: f32,
+    /// The number of products that are added in a single transaction.
+    pub batch_size: usize,
+}
+
+/// The summary of an import.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ImportReport {
+    /// The number of products in the dump.
+    pub read: u64,
+    /// The number of products that have been filtered by country or completeness.
+    pub filtered: u64,
+    /// The number of products that could not be mapped, e.g. due to a missing name or energy.
+    pub invalid: u64,
+    /// The number of products that have been added to the database.
+    pub added: u64,
+    /// The number of products that already existed in the database.
+    pub existing: u64,
+    /// The number of existing products that have been updated by an imported catalog.
+    pub updated: u64,
+}
+
+impl Display for ImportReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "read={}, filtered={}, invalid={}, added={}, existing={}, updated={}",
+            self.read, self.filtered, self.invalid, self.added, self.existing, self.updated
+        )
+    }
+}
+
+/// The imported fields of a single product of the dump. The tags are comma-separated.
+#[derive(Debug, Clone, Default, PartialEq)]
+struct OffRecord {
+    values: HashMap<&'static str, String>,
+}
+
+impl OffRecord {
+    /// Parses a line of the JSONL dump. The nutrients are read from the `nutriments` object.
+    ///
+    /// # Arguments
+    /// * `line` - The JSON document of the product.
+    fn from_json(line: &str) -> Result<Self> {
+        let document: Value = serde_json::from_str(line)?;
+        let nutriments = document.get("nutriments");
+
+        let mut values = HashMap::new();
+        for &field in FIELDS {
+            let value = document
+                .get(field)
+                .or_else(|| nutriments.and_then(|n| n.get(field)));
+            let value = match value {
+                Some(Value::String(value)) => value.clone(),
+                Some(Value::Number(value)) => value.to_string(),
+                Some(Value::Array(tags)) => tags
+                    .iter()
+                    .filter_map(Value::as_str)
+                    .collect::<Vec<_>>()
+                    .join(","),
+                _ => continue,
+            };
+            values.insert(field, value);
+        }
+
+        Ok(Self { values })
+    }
+
+    /// Returns the trimmed text of the given field if it is not empty.
+    ///
+    /// # Arguments
+    /// * `field` - The name of the field.
+    fn text(&self, field: &str) -> Option<&str> {
+        self.values
+            .get(field)
+            .map(|value| value.trim())
+            .filter(|value| !value.is_empty())
+    }
+
+    /// Returns the finite number of the given field.
+    ///
+    /// # Arguments
+    /// * `field` - The name of the field.
+    fn number(&self, field: &str) -> Option<f32> {
+        self.text(field)?
+            .parse::<f32>()
+            .ok()
+            .filter(|value| value.is_finite())
+    }
+
+    /// Returns the tags of the given field, e.g. `en:germany`.
+    ///
+    /// # Arguments
+    /// * `field` - The name of the field.
+    fn tags(&self, field: &str) -> impl Iterator<Item = &str> {
+        self.text(field)
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|tag| !tag.is_empty())
+    }
+
+    /// Returns true if the product is sold in one of the given countries and is at least as
+    /// complete as required.
+    ///
+    /// # Arguments
+    /// * `countries` - The country tags with language prefix, all countries if empty.
+    /// * `min_completeness` - The minimal completeness from 0 to 1.
+    fn matches(&self, countries: &[String], min_completeness: f32) -> bool {
+        if !countries.is_empty()
+            && !self
+                .tags("countries_tags")
+                .any(|tag| countries.iter().any(|country| country == tag))
+        {
+            return false;
+        }
+
+        min_completeness <= 0.0
+            || self
+                .number("completeness")
+                .is_some_and(|completeness| completeness >= min_completeness)
+    }
+
+    /// Maps the record to a product. Returns `None` if the product has no code, name or
+    /// energy.
+    fn to_product(&self) -> Option<ProductDescription> {
+        let id = self.text("code")?.to_string();
+        let name = self.text("product_name")?.to_string();
+        let producer = self
+            .text("brands")
+            .and_then(|brands| brands.split(',').next())
+            .map(|brand| brand.trim().to_string())
+            .filter(|brand| !brand.is_empty());
+
+        let quantity_type = match self.text("quantity") {
+            Some(quantity) if is_volume(quantity) => QuantityType::Volume,
+            _ => QuantityType::Weight,
+        };
+        let portion = self
+            .number("serving_quantity")
+            .filter(|portion| *portion > 0.0)
+            .unwrap_or(DEFAULT_PORTION);
+
+        let nova_group = self
+            .number("nova_group")
+            .map(|group| group as i32)
+            .filter(|group| (1..=4).contains(group));
+
+        let packaging = self
+            .number("product_quantity")
+            .filter(|quantity| *quantity > 0.0)
+            .map(|quantity| Packaging {
+                net_quantity: Some(quantity),
+                units: None,
+                material: None,
+                deposit: false,
+            });
+
+        let eco_score = self.eco_score_grade().map(|grade| EcoScore {
+            grade,
+            value: self
+                .number("ecoscore_score")
+                .map(|score| score.round() as i32)
+                .filter(|score| (0..=100).contains(score)),
+            source: Some(ECO_SCORE_SOURCE.to_string()),
+        });
+
+        Some(ProductDescription {
+            info: ProductInfo {
+                id,
+                name,
+                producer,
+                quantity_type,
+                portion,
+                volume_weight_ratio: None,
+                market: None,
+                labels: self.labels(),
+                nova_group,
+                allergens: self.allergens(),
+            },
+            preview: None,
+            full_image: None,
+            nutrients: self.nutrients()?,
+            packaging,
+            eco_score,
+        })
+    }
+
+    /// Returns the nutrients per 100g or 100ml. Returns `None` if the energy is unknown.
+    fn nutrients(&self) -> Option<Nutrients> {
+        let kcal = self
+            .number("energy-kcal_100g")
+            .or_else(|| self.number("energy_100g").map(|kj| kj / KJ_PER_KCAL))
+            .filter(|kcal| *kcal >= 0.0)?;
+        let gram = |field: &str| {
+            self.number(field)
+                .filter(|gram| *gram >= 0.0)
+                .map(Weight::new_from_gram)
+        };
+
+        Some(Nutrients {
+            kcal,
+            protein: gram("proteins_100g"),
+            fat: gram("fat_100g"),
+            carbohydrates: gram("carbohydrates_100g"),
+            sugar: gram("sugars_100g"),
+            salt: gram("salt_100g"),
+            vitamin_a: gram("vitamin-a_100g"),
+            vitamin_c: gram("vitamin-c_100g"),
+            vitamin_d: gram("vitamin-d_100g"),
+            iron: gram("iron_100g"),
+            calcium: gram("calcium_100g"),
+            magnesium: gram("magnesium_100g"),
+            sodium: gram("sodium_100g"),
+            zinc: gram("zinc_100g"),
+        })
+    }
+
+    /// Returns the dietary labels. The labels that are not tagged are unknown.
+    fn labels(&self) -> DietaryLabels {
+        let mut labels = DietaryLabels::default();
+        for tag in self.tags("labels_tags") {
+            match tag {
+                "en:vegan" => labels.vegan = Some(true),
+                "en:vegetarian" => labels.vegetarian = Some(true),
+                "en:organic" | "en:eu-organic" => labels.organic = Some(true),
+                "en:gluten-free" | "en:no-gluten" => labels.gluten_free = Some(true),
+                "en:lactose-free" | "en:no-lactose" => labels.lactose_free = Some(true),
+                _ => {}
+            }
+        }
+
+        labels
+    }
+
+    /// Returns the allergens of the product. Unknown allergen tags are skipped.
+    fn allergens(&self) -> Vec<Allergen> {
+        let mut allergens = Vec::new();
+        for tag in self.tags("allergens_tags") {
+            let allergen = match tag {
+                "en:gluten" => Allergen::Gluten,
+                "en:crustaceans" => Allergen::Crustaceans,
+                "en:eggs" => Allergen::Eggs,
+                "en:fish" => Allergen::Fish,
+                "en:peanuts" => Allergen::Peanuts,
+                "en:soybeans" => Allergen::Soybeans,
+                "en:milk" => Allergen::Milk,
+                "en:nuts" => Allergen::Nuts,
+                "en:celery" => Allergen::Celery,
+                "en:mustard" => Allergen::Mustard,
+                "en:sesame-seeds" => Allergen::Sesame,
+                "en:sulphur-dioxide-and-sulphites" => Allergen::Sulphites,
+                "en:lupin" => Allergen::Lupin,
+                "en:molluscs" => Allergen::Molluscs,
+                _ => continue,
+            };
+
+            if !allergens.contains(&allergen) {
+                allergens.push(allergen);
+            }
+        }
+
+        allergens
+    }
+
+    /// Returns the grade of the Eco-Score, if known.
+    fn eco_score_grade(&self) -> Option<EcoScoreGrade> {
+        match self.text("ecoscore_grade")? {
+            "a" | "a-plus" => Some(EcoScoreGrade::A),
+            "b" => Some(EcoScoreGrade::B),
+            "c" => Some(EcoScoreGrade::C),
+            "d" => Some(EcoScoreGrade::D),
+            "e" | "f" => Some(EcoScoreGrade::E),
+            _ => None,
+        }
+    }
+}
+
+/// The columns of the CSV dump, which are given by its header.
+struct CsvColumns {
+    columns: Vec<(usize, &'static str)>,
+}
+
+impl CsvColumns {
+    /// Returns the columns of the imported fields.
+    ///
+    /// # Arguments
+    /// * `header` - The tab-separated header of the dump.
+    fn new(header: &str) -> Result<Self> {
+        let columns: Vec<(usize, &'static str)> = header
+            .split('\t')
+            .enumerate()
+            .filter_map(|(index, name)| {
+                FIELDS
+                    .iter()
+                    .find(|field| **field == name.trim())
+                    .map(|field| (index, *field))
+            })
+            .collect();
+
+        if !columns.iter().any(|(_, field)| *field == "code") {
+            bail!("The header of the CSV dump has no 'code' column");
+        }
+
+        Ok(Self { columns })
+    }
+
+    /// Parses a tab-separated line of the dump.
+    ///
+    /// # Arguments
+    /// * `line` - The line of the product.
+    fn record(&self, line: &str) -> OffRecord {
+        let values: Vec<&str> = line.split('\t').collect();
+        let values = self
+            .columns
+            .iter()
+            .filter_map(|(index, field)| values.get(*index).map(|v| (*field, v.to_string())))
+            .collect();
+
+        OffRecord { values }
+    }
+}
+
+/// Returns true if the quantity of the package is a volume, e.g. `1.5 l` or `330 ml`.
+///
+/// # Arguments
+/// * `quantity` - The quantity of the package as stated by Open Food Facts.
+fn is_volume(quantity: &str) -> bool {
+    let unit: String = quantity
+        .trim()
+        .trim_start_matches(|c: char| c.is_ascii_digit() || c == '.' || c == ',' || c == ' ')
+        .chars()
+        .take_while(|c| c.is_alphabetic())
+        .collect::<String>()
+        .to_lowercase();
+
+    matches!(unit.as_str(), "ml" | "cl" | "dl" | "l")
+}
+
+/// Returns the country tag of the given country, e.g. `en:germany` for `germany` or
+/// `Germany`.
+///
+/// # Arguments
+/// * `country` - The country with or without language prefix.
+fn country_tag(country: &str) -> String {
+    let country = country.trim().to_lowercase().replace(' ', "-");
+    if country.contains(':') {
+        country
+    } else {
+        format!("en:{}", country)
+    }
+}
+
+/// Imports the products of an Open Food Facts dump into the database. The dump is read line
+/// by line and the products are added in batches, such that dumps with millions of products
+/// can be imported. Existing products are skipped, i.e., an aborted import can be restarted.
+/// A product catalog exported by the `export` command is imported as well, see
+/// [`import_catalog`].
+///
+/// # Arguments
+/// * `db` - The backend the products are added to.
+/// * `options` - The options of the import.
+pub async fn import_dump<B: WriteBackend>(db: &B, options: &ImportOptions) -> Result<ImportReport> {
+    let format = match options.format {
+        Some(format) => format,
+        None => DumpFormat::from_path(&options.path).ok_or_else(|| {
+            anyhow!(
+                "Unknown format of '{}', see --format",
+                options.path.display()
+            )
+        })?,
+    };
+    let mut reader: Box<dyn BufRead> = if options.path.as_os_str() == "-" {
+        Box::new(stdin().lock())
+    } else {
+        let file = File::open(&options.path)
+            .with_context(|| format!("Failed to open '{}'", options.path.display()))?;
+        Box::new(BufReader::new(file))
+    };
+
+    let countries: Vec<String> = options.countries.iter().map(|c| country_tag(c)).collect();
+    let batch_size = options.batch_size.max(1);
+    info!(
+        "Importing {:?} dump '{}' with countries={:?}, min_completeness={}, batch_size={}",
+        format,
+        options.path.display(),
+        countries,
+        options.min_completeness,
+        batch_size
+    );
+
+    // the CSV dumps are distinguished by their header
+    let mut csv_columns: Option<CsvColumns> = None;
+    if format == DumpFormat::Csv {
+        let mut header = String::new();
+        if reader.read_line(&mut header)? == 0 {
+            return Ok(ImportReport::default());
+        }
+
+        let names = CsvReader::new(header.as_bytes())
+            .record()?
+            .unwrap_or_default();
+        if ProductCsvColumns::is_catalog_header(&names) {
+            let columns = ProductCsvColumns::new(&names)?;
+            return import_catalog(db, &columns, CsvReader::new(reader), batch_size).await;
+        }
+
+        csv_columns = Some(CsvColumns::new(header.trim_end_matches(['\r', '\n']))?);
+    }
+
+    let mut report = ImportReport::default();
+    let mut batch = Vec::with_capacity(batch_size);
+    for line in reader.split(b'\n') {
+        // the dumps may contain invalid UTF-8 in single fields
+        let line = line?;
+        let line = String::from_utf8_lossy(&line);
+        let line = line.trim_end_matches('\r');
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let record = match &csv_columns {
+            Some(columns) => columns.record(line),
+            None => match OffRecord::from_json(line) {
+                Ok(record) => record,
+                Err(err) => {
+                    debug!("Skipping invalid line {}: {}", report.read + 1, err);
+                    report.read += 1;
+                    report.invalid += 1;
+                    continue;
+                }
+            },
+        };
+        report.read += 1;
+
+        if !record.matches(&countries, options.min_completeness) {
+            report.filtered += 1;
+            continue;
+        }
+
+        match record.to_product() {
+            Some(product) => batch.push(product),
+            None => {
+                report.invalid += 1;
+                continue;
+            }
+        }
+
+        if batch.len() >= batch_size {
+            add_batch(db, &mut batch, &mut report).await?;
+        }
+    }
+    add_batch(db, &mut batch, &mut report).await?;
+
+    info!("Imported the dump: {}", report);
+
+    Ok(report)
+}
+
+/// Imports a product catalog exported by the `export` command, e.g. after editing it in a
+/// spreadsheet. Existing products are updated and missing products are added, whereas the
+/// images of the existing products are kept. The country and completeness filters do not
+/// apply to catalogs.
+///
+/// # Arguments
+/// * `db` - The backend the products are imported into.
+/// * `columns` - The columns given by the header of the catalog.
+/// * `reader` - The reader of the records following the header.
+/// * `batch_size` - The number of products that are added in a single transaction.
+async fn import_catalog<B: WriteBackend, R: BufRead>(
+    db: &B,
+    columns: &ProductCsvColumns,
+    mut reader: CsvReader<R>,
+    batch_size: usize,
+) -> Result<ImportReport> {
+    let mut report = ImportReport::default();
+    let mut batch = Vec::with_capacity(batch_size);
+    while let Some(record) = reader.record()? {
+        report.read += 1;
+
+        match columns.product(&record) {
+            Ok(product) => batch.push(product),
+            Err(err) => {
+                debug!("Skipping invalid record in line {}: {}", reader.line(), err);
+                report.invalid += 1;
+                continue;
+            }
+        }
+
+        if batch.len() >= batch_size {
+            upsert_batch(db, &mut batch, &mut report).await?;
+        }
+    }
+    upsert_batch(db, &mut batch, &mut report).await?;
+
+    info!("Imported the catalog: {}", report);
+
+    Ok(report)
+}
+
+/// Updates the existing products of the batch, adds the remaining ones to the database and
+/// clears the batch.
+///
+/// # Arguments
+/// * `db` - The backend the products are imported into.
+/// * `batch` - The products to update or add.
+/// * `report` - The report that is updated by the imported products.
+async fn upsert_batch<B: WriteBackend>(
+    db: &B,
+    batch: &mut Vec<ProductDescription>,
+    report: &mut ImportReport,
+) -> Result<()> {
+    let mut missing = Vec::new();
+    for product in batch.drain(..) {
+        if db.update_product(&product).await? {
+            report.updated += 1;
+        } else {
+            missing.push(product);
+        }
+    }
+
+    add_batch(db, &mut missing, report).await
+}
+
+/// Adds the products of the batch to the database and clears the batch.
+///
+/// # Arguments
+/// * `db` - The backend the products are added to.
+/// * `batch` - The products to add.
+/// * `report` - The report that is updated by the added products.
+async fn add_batch<B: WriteBackend>(
+    db: &B,
+    batch: &mut Vec<ProductDescription>,
+    report: &mut ImportReport,
+) -> Result<()> {
+    if batch.is_empty() {
+        return Ok(());
+    }
+
+    let added = db.new_products(batch).await?;
+    report.added += added;
+    report.existing += batch.len() as u64 - added;
+    batch.clear();
+
+    info!(
+        "Imported {} products, {} products read...",
+        report.added, report.read
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use product_db::{MemoryBackend, ReadBackend};
+
+    use crate::export::{export_catalog, ExportFormat, ExportOptions};
+
+    use super::*;
+
+    const CSV_HEADER: &str = "code\turl\tproduct_name\tquantity\tbrands\tcountries_tags\tallergens_tags\tserving_quantity\tnova_group\tecoscore_score\tecoscore_grade\tcompleteness\tenergy-kcal_100g\tenergy_100g\tfat_100g\tcarbohydrates_100g\tsugars_100g\tproteins_100g\tsalt_100g\tsodium_100g\tvitamin-c_100g";
+
+    #[test]
+    fn test_csv_record() {
+        let columns = CsvColumns::new(CSV_HEADER).unwrap();
+        let record = columns.record("4000417025005\thttps://example.org\tVollmilch Schokolade\t100 g\tRitter Sport,Alfred Ritter\ten:germany,en:austria\ten:milk,en:soybeans,en:milk\t16.7\t4\t18\tc\t0.8875\t560\t2343\t35\t50\t48\t7.3\t0.23\t0.092\t");
+
+        assert!(record.matches(&[], 0.0));
+        assert!(record.matches(&["en:austria".to_string()], 0.8));
+        assert!(!record.matches(&["en:france".to_string()], 0.0));
+        assert!(!record.matches(&[], 0.9));
+
+        let product = record.to_product().unwrap();
+        assert_eq!(product.info.id, "4000417025005");
+        assert_eq!(product.info.name, "Vollmilch Schokolade");
+        assert_eq!(product.info.producer.as_deref(), Some("Ritter Sport"));
+        assert_eq!(product.info.quantity_type, QuantityType::Weight);
+        assert_eq!(product.info.portion, 16.7);
+        assert_eq!(product.info.nova_group, Some(4));
+        assert_eq!(
+            product.info.allergens,
+            vec![Allergen::Milk, Allergen::Soybeans]
+        );
+        assert_eq!(product.nutrients.kcal, 560.0);
+        assert_eq!(product.nutrients.fat, Some(Weight::new_from_gram(35.0)));
+        assert_eq!(product.nutrients.vitamin_c, None);
+        assert_eq!(
+            product.eco_score,
+            Some(EcoScore {
+                grade: EcoScoreGrade::C,
+                value: Some(18),
+                source: Some(ECO_SCORE_SOURCE.to_string()),
+            })
+        );
+        assert!(product.packaging.is_none());
+    }
+
+    #[test]
+    fn test_json_record() {
+        let record = OffRecord::from_json(
+            r#"{"code":"5449000000996","product_name":"Coca-Cola","brands":"Coca-Cola","quantity":"330 ml","product_quantity":"330","countries_tags":["en:germany"],"labels_tags":["en:vegan","en:vegetarian"],"ecoscore_grade":"unknown","nutriments":{"energy_100g":180,"sugars_100g":10.6,"carbohydrates_100g":10.6,"salt_100g":0}}"#,
+        )
+        .unwrap();
+
+        assert!(record.matches(&[country_tag("Germany")], 0.0));
+        assert!(!record.matches(&[], 0.5));
+
+        let product = record.to_product().unwrap();
+        assert_eq!(product.info.quantity_type, QuantityType::Volume);
+        assert_eq!(product.info.portion, DEFAULT_PORTION);
+        assert_eq!(product.info.labels.vegan, Some(true));
+        assert_eq!(product.info.labels.organic, None);
+        assert!((product.nutrients.kcal - 180.0 / KJ_PER_KCAL).abs() < 1e-3);
+        assert_eq!(product.nutrients.salt, Some(Weight::new_from_gram(0.0)));
+        assert_eq!(product.packaging.and_then(|p| p.net_quantity), Some(330.0));
+        assert!(product.eco_score.is_none());
+
+        // products without name or energy are not imported
+        let record = OffRecord::from_json(r#"{"code":"123","product_name":"Water"}"#).unwrap();
+        assert!(record.to_product().is_none());
+        let record =
+            OffRecord::from_json(r#"{"code":"123","nutriments":{"energy-kcal_100g":0}}"#).unwrap();
+        assert!(record.to_product().is_none());
+    }
+
+    #[test]
+    fn test_is_volume() {
+        assert!(is_volume("330 ml"));
+        assert!(is_volume("1,5 L"));
+        assert!(is_volume("75cl"));
+        assert!(!is_volume("500 g"));
+        assert!(!is_volume("1 kg"));
+        assert!(!is_volume("6 x 250g"));
+    }
+
+    #[test]
+    fn test_csv_without_code() {
+        assert!(CsvColumns::new("product_name\tbrands").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_import_catalog() {
+        let db = MemoryBackend::new();
+        let record = OffRecord::from_json(
+            r#"{"code":"1","product_name":"Water","nutriments":{"energy-kcal_100g":0}}"#,
+        )
+        .unwrap();
+        let mut water = record.to_product().unwrap();
+        assert!(db.new_product(&water).await.unwrap());
+
+        // export the catalog, rename the product and add another one
+        let path = std::env::temp_dir().join(format!("catalog-{}.csv", std::process::id()));
+        let export_options = ExportOptions {
+            path: path.clone(),
+            format: ExportFormat::Csv,
+        };
+        assert_eq!(export_catalog(&db, &export_options).await.unwrap(), 1);
+
+        let catalog = std::fs::read_to_string(&path).unwrap();
+        let catalog =
+            catalog.replace("Water", "Still Water") + "2,Soda,,,volume,330,,,,,,,,,42\r\n";
+        std::fs::write(&path, catalog).unwrap();
+
+        let import_options = ImportOptions {
+            path: path.clone(),
+            format: None,
+            countries: vec!["germany".to_string()],
+            min_completeness: 0.0,
+            batch_size: 1,
+        };
+        let report = import_dump(&db, &import_options).await.unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(
+            report,
+            ImportReport {
+                read: 2,
+                added: 1,
+                updated: 1,
+                ..Default::default()
+            }
+        );
+
+        water.info.name = "Still Water".to_string();
+        let id = water.info.id.clone();
+        assert_eq!(db.get_product(&id, false).await.unwrap(), Some(water));
+        let soda = db
+            .get_product(&"2".to_string(), false)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(soda.info.quantity_type, QuantityType::Volume);
+        assert_eq!(soda.nutrients.kcal, 42.0);
+    }
+}
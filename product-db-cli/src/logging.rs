@@ -1,6 +1,7 @@
 use std::{fmt::Display, io::Write};
 
 use log::LevelFilter;
+use product_db::try_current_request_id;
 use serde_derive::Deserialize;
 
 #[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq)]
@@ -36,17 +37,23 @@ impl Display for LogLevel {
     }
 }
 
-/// Initializes the program logging
+/// Initializes the program logging. The records logged while handling a request, e.g. by the
+/// handlers or the database backend, contain the id of the request.
 pub fn initialize_logging(filter: LevelFilter) {
     env_logger::Builder::new()
         .format(|buf, record| {
+            let request_id = try_current_request_id()
+                .map(|id| format!(" [request-id={}]", id))
+                .unwrap_or_default();
+
             writeln!(
                 buf,
-                "{}:{} {} [{}] - {}",
+                "{}:{} {} [{}]{} - {}",
                 record.file().unwrap_or("unknown"),
                 record.line().unwrap_or(0),
                 chrono::Local::now().format("%Y-%m-%dT%H:%M:%S"),
                 record.level(),
+                request_id,
                 record.args()
             )
         })
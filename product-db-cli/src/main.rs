@@ -1,17 +1,51 @@
-use std::{path::PathBuf, sync::Arc};
+use std::{path::PathBuf, sync::Arc, time::Duration};
 use tokio::signal;
 
-use anyhow::Result;
-use clap::{arg, value_parser, Command};
-use log::{error, info, LevelFilter};
+use anyhow::{anyhow, Result};
+use bench::{run_bench, BenchOptions, BenchProfile};
+use clap::{arg, value_parser, ArgAction, ArgMatches, Command};
+use export::{export_catalog, ExportFormat, ExportOptions};
+use import::{import_dump, DumpFormat, ImportOptions};
+use log::{error, info, warn, LevelFilter};
 use logging::initialize_logging;
 use options::{ProgramConfig, ProgramOptions};
-use product_db::{Options, PostgresBackend, Service};
+use product_db::{
+    generate_products, json_schema, CachedSecretProvider, CredentialsSource, Options,
+    PostgresBackend, ProductGenerator, SecretProvider, SecretProviderConfig, Service,
+    JSON_SCHEMA_NAMES, POSTGRES_PASSWORD_SECRET,
+};
 
+mod bench;
+mod export;
+mod import;
 mod logging;
 mod options;
 
-/// Parses the program arguments and returns the program options.
+/// The command selected by the program arguments.
+pub enum ProgramCommand {
+    /// Runs the service with the given options and the optional secret provider.
+    Serve(Box<Options>, Option<SecretProviderConfig>),
+    /// Runs a benchmark against a running instance.
+    Bench(BenchOptions),
+    /// Inserts synthetic products into the configured database.
+    Generate(Box<Options>, Option<SecretProviderConfig>, GenerateOptions),
+    /// Imports the products of an Open Food Facts dump into the configured database.
+    Import(Box<Options>, Option<SecretProviderConfig>, ImportOptions),
+    /// Exports the products of the configured database, e.g. for editing them in a spreadsheet.
+    Export(Box<Options>, Option<SecretProviderConfig>, ExportOptions),
+}
+
+/// The options of the `generate` command.
+pub struct GenerateOptions {
+    /// The number of products to generate.
+    pub count: usize,
+    /// True if the products get generated preview and full images.
+    pub images: bool,
+    /// The seed for generating the same products again, random if not set.
+    pub seed: Option<u64>,
+}
+
+/// Parses the program arguments and returns the selected command.
 ///
 /// # Arguments
 /// * `app_name` - The name of the application.
@@ -21,7 +55,7 @@ pub fn parse_args_and_init_logging(
     app_name: &'static str,
     version: &'static str,
     about: &'static str,
-) -> Result<Options> {
+) -> Result<ProgramCommand> {
     // parse program arguments
     let matches = Command::new(app_name)
         .version(version)
@@ -30,22 +64,410 @@ pub fn parse_args_and_init_logging(
             arg!(
                 -c --config <FILE> "Path to the configuration file."
             )
-            .required(true)
+            .required_unless_present("json-schema")
             .value_parser(value_parser!(PathBuf)),
         )
+        .arg(
+            arg!(
+                -p --profile <NAME> "The profile of the configuration file to use. Defaults to the PRODUCT_DB_PROFILE environment variable."
+            )
+            .required(false),
+        )
+        .arg(
+            arg!(
+                --"json-schema" <NAME> "Prints the JSON Schema of the given request or response type and exits."
+            )
+            .required(false),
+        )
+        .subcommand_negates_reqs(true)
+        .subcommand(
+            Command::new("bench")
+                .about("Sends a mix of product gets, queries and submissions to a running instance and reports latency percentiles and throughput.")
+                .arg(
+                    arg!(
+                        --target <URL> "The base url of the instance including the prefix, e.g. http://localhost:8888/v1."
+                    )
+                    .required(true)
+                    .value_parser(value_parser!(reqwest::Url)),
+                )
+                .arg(
+                    arg!(
+                        --profile <PROFILE> "The mix of operations."
+                    )
+                    .value_parser(value_parser!(BenchProfile))
+                    .default_value("read-heavy"),
+                )
+                .arg(
+                    arg!(
+                        --duration <SECONDS> "The duration of the benchmark in seconds."
+                    )
+                    .value_parser(value_parser!(u64))
+                    .default_value("30"),
+                )
+                .arg(
+                    arg!(
+                        --concurrency <CLIENTS> "The number of concurrent clients."
+                    )
+                    .value_parser(value_parser!(usize))
+                    .default_value("8"),
+                ),
+        )
+        .subcommand(
+            Command::new("generate")
+                .about("Inserts synthetic products with plausible names, producers and nutrients into the configured database, e.g. for performance tests.")
+                .arg(
+                    arg!(
+                        -n --count <COUNT> "The number of products to generate."
+                    )
+                    .required(true)
+                    .value_parser(value_parser!(usize)),
+                )
+                .arg(
+                    arg!(
+                        --images "Generates preview and full images for the products."
+                    ),
+                )
+                .arg(
+                    arg!(
+                        --seed <SEED> "The seed for generating the same products again."
+                    )
+                    .required(false)
+                    .value_parser(value_parser!(u64)),
+                ),
+        )
+        .subcommand(
+            Command::new("import")
+                .about("Imports the products of an Open Food Facts CSV or JSONL dump into the configured database in batches, skipping the existing products. A product catalog CSV of the export command updates the existing products instead.")
+                .arg(
+                    arg!(
+                        <FILE> "Path to the dump or - for reading it from stdin, e.g. from zcat."
+                    )
+                    .value_parser(value_parser!(PathBuf)),
+                )
+                .arg(
+                    arg!(
+                        --format <FORMAT> "The format of the dump. Derived from the file extension if not set."
+                    )
+                    .required(false)
+                    .value_parser(value_parser!(DumpFormat)),
+                )
+                .arg(
+                    arg!(
+                        --country <COUNTRY> "Only imports the products sold in the given country, e.g. germany. Can be repeated."
+                    )
+                    .required(false)
+                    .action(ArgAction::Append),
+                )
+                .arg(
+                    arg!(
+                        --"min-completeness" <RATIO> "The minimal completeness from 0 to 1 of the imported products."
+                    )
+                    .value_parser(value_parser!(f32))
+                    .default_value("0"),
+                )
+                .arg(
+                    arg!(
+                        --"batch-size" <COUNT> "The number of products that are added in a single transaction."
+                    )
+                    .value_parser(value_parser!(usize))
+                    .default_value("1000"),
+                ),
+        )
+        .subcommand(
+            Command::new("export")
+                .about("Exports the products of the configured database without their images, e.g. for editing them in a spreadsheet and importing them again.")
+                .arg(
+                    arg!(
+                        <FILE> "Path to the exported file or - for writing it to stdout."
+                    )
+                    .value_parser(value_parser!(PathBuf)),
+                )
+                .arg(
+                    arg!(
+                        --format <FORMAT> "The format of the export."
+                    )
+                    .value_parser(value_parser!(ExportFormat))
+                    .default_value("csv"),
+                ),
+        )
         .get_matches();
 
-    let config_path = matches.get_one::<PathBuf>("config").unwrap().clone();
+    if let Some(name) = matches.get_one::<String>("json-schema") {
+        print_json_schema(name)?;
+        std::process::exit(0);
+    }
+
+    if let Some(matches) = matches.subcommand_matches("bench") {
+        initialize_logging(LevelFilter::Info);
+        return Ok(ProgramCommand::Bench(parse_bench_options(matches)));
+    }
+
+    let config_path = matches
+        .get_one::<PathBuf>("config")
+        .cloned()
+        .ok_or_else(|| anyhow!("The configuration file is required, see --config"))?;
+    let profile = matches
+        .get_one::<String>("profile")
+        .cloned()
+        .or_else(|| std::env::var("PRODUCT_DB_PROFILE").ok());
 
     // load the configuration file, initialize logging and print the configuration
-    let program_config = ProgramConfig::try_from(ProgramOptions { config_path })?;
+    let program_config = ProgramConfig::try_from(ProgramOptions {
+        config_path,
+        profile: profile.clone(),
+    })?;
     initialize_logging(LevelFilter::from(program_config.log));
+    if let Some(profile) = &profile {
+        info!("Using configuration profile '{}'", profile);
+    }
     program_config.print_to_log();
 
-    Ok(Options {
+    let options = Options {
         endpoint: program_config.endpoint,
         postgres: program_config.postgres,
-    })
+        archive: program_config.archive,
+        mongodb: None,
+    };
+
+    if let Some(matches) = matches.subcommand_matches("generate") {
+        return Ok(ProgramCommand::Generate(
+            Box::new(options),
+            program_config.secrets,
+            parse_generate_options(matches),
+        ));
+    }
+
+    if let Some(matches) = matches.subcommand_matches("import") {
+        return Ok(ProgramCommand::Import(
+            Box::new(options),
+            program_config.secrets,
+            parse_import_options(matches),
+        ));
+    }
+
+    if let Some(matches) = matches.subcommand_matches("export") {
+        return Ok(ProgramCommand::Export(
+            Box::new(options),
+            program_config.secrets,
+            parse_export_options(matches),
+        ));
+    }
+
+    Ok(ProgramCommand::Serve(
+        Box::new(options),
+        program_config.secrets,
+    ))
+}
+
+/// Returns the benchmark options of the parsed `bench` arguments.
+///
+/// # Arguments
+/// * `matches` - The parsed arguments of the `bench` command.
+fn parse_bench_options(matches: &ArgMatches) -> BenchOptions {
+    BenchOptions {
+        target: matches.get_one::<reqwest::Url>("target").unwrap().clone(),
+        profile: *matches.get_one::<BenchProfile>("profile").unwrap(),
+        duration: Duration::from_secs(*matches.get_one::<u64>("duration").unwrap()),
+        concurrency: *matches.get_one::<usize>("concurrency").unwrap(),
+    }
+}
+
+/// Returns the options of the parsed `generate` arguments.
+///
+/// # Arguments
+/// * `matches` - The parsed arguments of the `generate` command.
+fn parse_generate_options(matches: &ArgMatches) -> GenerateOptions {
+    GenerateOptions {
+        count: *matches.get_one::<usize>("count").unwrap(),
+        images: matches.get_flag("images"),
+        seed: matches.get_one::<u64>("seed").copied(),
+    }
+}
+
+/// Returns the options of the parsed `import` arguments.
+///
+/// # Arguments
+/// * `matches` - The parsed arguments of the `import` command.
+fn parse_import_options(matches: &ArgMatches) -> ImportOptions {
+    ImportOptions {
+        path: matches.get_one::<PathBuf>("FILE").unwrap().clone(),
+        format: matches.get_one::<DumpFormat>("format").copied(),
+        countries: matches
+            .get_many::<String>("country")
+            .map(|countries| countries.cloned().collect())
+            .unwrap_or_default(),
+        min_completeness: *matches.get_one::<f32>("min-completeness").unwrap(),
+        batch_size: *matches.get_one::<usize>("batch-size").unwrap(),
+    }
+}
+
+/// Returns the options of the parsed `export` arguments.
+///
+/// # Arguments
+/// * `matches` - The parsed arguments of the `export` command.
+fn parse_export_options(matches: &ArgMatches) -> ExportOptions {
+    ExportOptions {
+        path: matches.get_one::<PathBuf>("FILE").unwrap().clone(),
+        format: *matches.get_one::<ExportFormat>("format").unwrap(),
+    }
+}
+
+/// Connects to the configured Postgres database.
+///
+/// # Arguments
+/// * `options` - The options with the Postgres config.
+/// * `secrets` - The optional secret provider for fetching the Postgres password.
+async fn connect(
+    mut options: Options,
+    secrets: Option<SecretProviderConfig>,
+) -> Result<PostgresBackend> {
+    if let Some(config) = secrets {
+        let provider = config.create()?;
+        options.postgres.password = provider.fetch_secret(POSTGRES_PASSWORD_SECRET).await?;
+    }
+
+    Ok(PostgresBackend::new(options.postgres).await?)
+}
+
+/// Generates synthetic products and inserts them into the configured database.
+///
+/// # Arguments
+/// * `options` - The options with the Postgres config.
+/// * `secrets` - The optional secret provider for fetching the Postgres password.
+/// * `generate_options` - The options of the generated products.
+async fn generate(
+    options: Options,
+    secrets: Option<SecretProviderConfig>,
+    generate_options: GenerateOptions,
+) -> Result<()> {
+    let db = connect(options, secrets).await?;
+
+    let generator = match generate_options.seed {
+        Some(seed) => ProductGenerator::new(seed),
+        None => ProductGenerator::from_os_rng(),
+    };
+    let mut generator = generator.with_images(generate_options.images);
+
+    generate_products(&db, &mut generator, generate_options.count).await?;
+
+    Ok(())
+}
+
+/// Imports the products of an Open Food Facts dump into the configured database.
+///
+/// # Arguments
+/// * `options` - The options with the Postgres config.
+/// * `secrets` - The optional secret provider for fetching the Postgres password.
+/// * `import_options` - The options of the import.
+async fn import(
+    options: Options,
+    secrets: Option<SecretProviderConfig>,
+    import_options: ImportOptions,
+) -> Result<()> {
+    let db = connect(options, secrets).await?;
+    let report = import_dump(&db, &import_options).await?;
+    println!("{}", report);
+
+    Ok(())
+}
+
+/// Exports the products of the configured database.
+///
+/// # Arguments
+/// * `options` - The options with the Postgres config.
+/// * `secrets` - The optional secret provider for fetching the Postgres password.
+/// * `export_options` - The options of the export.
+async fn export(
+    options: Options,
+    secrets: Option<SecretProviderConfig>,
+    export_options: ExportOptions,
+) -> Result<()> {
+    let db = connect(options, secrets).await?;
+    export_catalog(&db, &export_options).await?;
+
+    Ok(())
+}
+
+/// Prints the JSON Schema of the request or response type with the given name to stdout.
+///
+/// # Arguments
+/// * `name` - The name of the type.
+fn print_json_schema(name: &str) -> Result<()> {
+    let schema = json_schema(name).ok_or_else(|| {
+        anyhow!(
+            "No JSON Schema for '{}'. Available schemas: {}",
+            name,
+            JSON_SCHEMA_NAMES.join(", ")
+        )
+    })?;
+    println!("{}", serde_json::to_string_pretty(&schema)?);
+
+    Ok(())
+}
+
+/// Creates the service using the Postgres password of the given secret provider. If the
+/// password is rejected, e.g. because it has been rotated, it is fetched again once.
+/// The provider is also used for reloading the credentials at runtime.
+///
+/// # Arguments
+/// * `options` - The options for the service.
+/// * `provider` - The provider for fetching the Postgres password.
+async fn create_service_with_secrets<P: SecretProvider + 'static>(
+    mut options: Options,
+    provider: Arc<CachedSecretProvider<P>>,
+) -> Result<Service<PostgresBackend>> {
+    let source_provider = provider.clone();
+    let credentials_source: CredentialsSource = Arc::new(move || {
+        let provider = source_provider.clone();
+        Box::pin(async move {
+            // always fetch the current password, as it is only reloaded after a rotation
+            provider.invalidate(POSTGRES_PASSWORD_SECRET).await;
+            provider.fetch_secret(POSTGRES_PASSWORD_SECRET).await
+        })
+    });
+
+    options.postgres.password = provider.fetch_secret(POSTGRES_PASSWORD_SECRET).await?;
+
+    let service = Service::builder(options.clone())
+        .credentials_source(credentials_source.clone())
+        .build()
+        .await;
+
+    match service {
+        Err(err) if err.is_authentication_error() => {
+            warn!("Postgres rejected the password, fetching it again...");
+            provider.invalidate(POSTGRES_PASSWORD_SECRET).await;
+            options.postgres.password = provider.fetch_secret(POSTGRES_PASSWORD_SECRET).await?;
+
+            Ok(Service::builder(options)
+                .credentials_source(credentials_source)
+                .build()
+                .await?)
+        }
+        service => Ok(service?),
+    }
+}
+
+/// Reloads the database credentials of the service whenever SIGHUP is received.
+///
+/// # Arguments
+/// * `service` - The service whose credentials are reloaded.
+#[cfg(unix)]
+async fn reload_credentials_on_hangup(service: Arc<Service<PostgresBackend>>) {
+    let mut hangup = match signal::unix::signal(signal::unix::SignalKind::hangup()) {
+        Ok(hangup) => hangup,
+        Err(err) => {
+            error!("Failed to install SIGHUP handler: {}", err);
+            return;
+        }
+    };
+
+    while hangup.recv().await.is_some() {
+        info!("Received SIGHUP, reloading the database credentials...");
+        if let Err(err) = service.reload_credentials().await {
+            error!("Failed to reload the database credentials: {}", err);
+        }
+    }
 }
 
 /// Waits for the shutdown signal.
@@ -82,10 +504,36 @@ async fn run_program() -> Result<()> {
         env!("CARGO_PKG_DESCRIPTION"),
     );
 
-    let options = parse_args_and_init_logging(app_name, version, about)?;
+    let (options, secrets) = match parse_args_and_init_logging(app_name, version, about)? {
+        ProgramCommand::Serve(options, secrets) => (*options, secrets),
+        ProgramCommand::Bench(options) => {
+            let report = run_bench(options).await?;
+            println!("{}", report);
+            return Ok(());
+        }
+        ProgramCommand::Generate(options, secrets, generate_options) => {
+            return generate(*options, secrets, generate_options).await;
+        }
+        ProgramCommand::Import(options, secrets, import_options) => {
+            return import(*options, secrets, import_options).await;
+        }
+        ProgramCommand::Export(options, secrets, export_options) => {
+            return export(*options, secrets, export_options).await;
+        }
+    };
     info!("Product DB Version: {}", env!("CARGO_PKG_VERSION"));
 
-    let service: Arc<Service<PostgresBackend>> = Arc::new(product_db::Service::new(options).await?);
+    let service = match secrets {
+        Some(config) => {
+            let provider = CachedSecretProvider::new(config.create()?);
+            create_service_with_secrets(options, Arc::new(provider)).await?
+        }
+        None => product_db::Service::new(options).await?,
+    };
+    let service: Arc<Service<PostgresBackend>> = Arc::new(service);
+
+    #[cfg(unix)]
+    tokio::spawn(reload_credentials_on_hangup(service.clone()));
 
     // spawn task to wait for the shutdown signal
     let service_clone = service.clone();
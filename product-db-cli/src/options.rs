@@ -1,17 +1,22 @@
 use std::{io::Read, path::PathBuf};
 
 use log::info;
-use product_db::{EndpointOptions, PostgresConfig};
+use product_db::{ArchiveOptions, EndpointOptions, PostgresConfig, SecretProviderConfig};
 use serde::Deserialize;
 
 use crate::logging::LogLevel;
 
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
+
+/// The name of the section in the configuration file that contains the profiles.
+const PROFILES_SECTION: &str = "profiles";
 
 /// The program options of the CLI.
 pub struct ProgramOptions {
     /// The path to the configuration file.
     pub config_path: PathBuf,
+    /// The profile of the configuration file to use, if any.
+    pub profile: Option<String>,
 }
 
 /// The configuration for the product-db-cli program.
@@ -22,6 +27,12 @@ pub struct ProgramConfig {
     pub endpoint: EndpointOptions,
     /// The Postgres config.
     pub postgres: PostgresConfig,
+    /// The options for archiving processed product requests.
+    #[serde(default)]
+    pub archive: ArchiveOptions,
+    /// The optional secret provider for fetching the Postgres password at startup.
+    #[serde(default)]
+    pub secrets: Option<SecretProviderConfig>,
 }
 
 impl ProgramConfig {
@@ -34,7 +45,45 @@ impl ProgramConfig {
         info!("Postgres Port: {}", self.postgres.port);
         info!("Postgres User: {}", self.postgres.user);
         info!("Postgres Password: {}", self.postgres.password);
+        if let Some(password_file) = &self.postgres.password_file {
+            info!("Postgres Password File: {}", password_file.display());
+        }
         info!("Postgres Database: {}", self.postgres.dbname);
+        info!("Postgres PgBouncer Mode: {}", self.postgres.pgbouncer_mode);
+        if let Some(schema) = &self.postgres.schema {
+            info!("Postgres Schema: {}", schema);
+        }
+        info!(
+            "Postgres Application Name: {}",
+            self.postgres.connection_application_name()
+        );
+        info!(
+            "Postgres Partition Missing Products: {}",
+            self.postgres.partition_missing_products
+        );
+        if let Some(retention_months) = self.postgres.missing_products_retention_months {
+            info!(
+                "Postgres Missing Products Retention: {} months",
+                retention_months
+            );
+        }
+        info!("Postgres Price Tracking: {}", self.postgres.price_tracking);
+        info!(
+            "Postgres Market Specific Products: {}",
+            self.postgres.market_specific_products
+        );
+        info!(
+            "Postgres Unaccent Search: {}",
+            self.postgres.unaccent_search
+        );
+        info!("Postgres Search Terms: {:?}", self.postgres.search_terms);
+        info!("Postgres Image Store: {:?}", self.postgres.image_store);
+        if let Some(secrets) = &self.secrets {
+            info!("Secret Provider: {:?}", secrets);
+        }
+        if let Some(interval_secs) = self.archive.interval_secs {
+            info!("Archive Interval: {} seconds", interval_secs);
+        }
         info!("Endpoint:");
 
         if let Some(prefix) = &self.endpoint.prefix {
@@ -42,32 +91,157 @@ impl ProgramConfig {
         } else {
             info!("Address: {}", self.endpoint.address);
         }
+        if let Some(admin_address) = &self.endpoint.admin_address {
+            info!("Admin Address: {}", admin_address);
+        }
 
         info!("Allow Origin: {}", self.endpoint.allow_origin);
+        if let Some(user_allow_origin) = &self.endpoint.user_allow_origin {
+            info!("User Allow Origin: {}", user_allow_origin);
+        }
+        if let Some(admin_allow_origin) = &self.endpoint.admin_allow_origin {
+            info!("Admin Allow Origin: {}", admin_allow_origin);
+        }
+        info!("Read Only: {}", self.endpoint.read_only);
+        info!("Query Timeout: {}ms", self.endpoint.query_timeout_ms);
+        info!("Image Timeout: {}ms", self.endpoint.image_timeout_ms);
+        info!("Image Rate Limit: {:?}", self.endpoint.image_rate_limit);
+        info!(
+            "Signed Image URLs: {}",
+            self.endpoint.image_url_secret.is_some()
+        );
+        info!(
+            "Signed Image URL TTL: {}s",
+            self.endpoint.image_url_ttl_secs
+        );
+        info!("Image Quota: {:?}", self.endpoint.image_quota);
+        info!("Previews: {:?}", self.endpoint.previews);
+        info!(
+            "Nutrient Plausibility: {:?}",
+            self.endpoint.nutrient_plausibility
+        );
+        for deprecation in &self.endpoint.deprecations {
+            info!("Deprecation: {:?}", deprecation);
+        }
+        info!("Cache Control: {:?}", self.endpoint.cache_control);
+        info!("Metrics: {}", self.endpoint.metrics);
+        info!("Swagger UI: {}", self.endpoint.swagger_ui.is_some());
+        info!(
+            "Signed Admin Requests: {}",
+            self.endpoint.request_signing.is_some()
+        );
+        info!("TLS: {}", self.endpoint.tls.is_some());
+        info!(
+            "Client Certificates Required: {}",
+            self.endpoint
+                .tls
+                .as_ref()
+                .is_some_and(|tls| tls.client_ca_file.is_some())
+        );
+        info!(
+            "Admin Allowlist: {:?}",
+            self.endpoint
+                .admin_allowlist
+                .as_ref()
+                .map(|allowlist| &allowlist.networks)
+        );
+        info!(
+            "Admin API Keys: {}",
+            self.endpoint
+                .auth
+                .as_ref()
+                .map_or(0, |auth| auth.admin_api_keys.len())
+        );
+        info!(
+            "JWT Issuer: {:?}",
+            self.endpoint
+                .auth
+                .as_ref()
+                .and_then(|auth| auth.jwt.as_ref())
+                .map(|jwt| &jwt.issuer)
+        );
     }
 
-    /// Load the configuration from a reader.
+    /// Load the configuration from a reader and apply the given profile.
+    /// The settings of the profile section `[profiles.<name>]` override the settings of the
+    /// base configuration, all other settings are inherited.
     ///
     /// # Arguments
     /// - `r` - The reader to read the configuration from.
-    pub fn from_reader<R: Read>(r: R) -> Result<Self> {
+    /// - `profile` - The name of the profile to apply, if any.
+    pub fn from_reader<R: Read>(r: R, profile: Option<&str>) -> Result<Self> {
         let mut s = String::new();
 
         let mut r = r;
         r.read_to_string(&mut s)?;
 
-        let config: Self = toml::from_str(&s)?;
+        let mut base: toml::Table = toml::from_str(&s)?;
+        let profiles = base.remove(PROFILES_SECTION);
+
+        if let Some(profile) = profile {
+            let overrides = match profiles.as_ref().and_then(|profiles| profiles.get(profile)) {
+                Some(toml::Value::Table(overrides)) => overrides.clone(),
+                Some(_) => bail!("Profile '{}' must be a section", profile),
+                None => bail!("Profile '{}' is not defined in the configuration", profile),
+            };
+
+            merge_tables(&mut base, overrides);
+        }
+
+        let config: Self = toml::Value::Table(base).try_into()?;
 
         Ok(config)
     }
 
-    /// Load secrets from environment variables if defined
-    pub fn load_secrets_from_env(&mut self) {
+    /// Load secrets from the configured secret files and from environment variables if defined.
+    /// `PRODUCT_DB_PASSWORD` takes precedence over `PRODUCT_DB_PASSWORD_FILE`, which takes
+    /// precedence over the `password_file` of the configuration.
+    pub fn load_secrets(&mut self) -> Result<()> {
+        let from_env = std::env::var_os("PRODUCT_DB_PASSWORD").is_some()
+            || std::env::var_os("PRODUCT_DB_PASSWORD_FILE").is_some();
+        self.check_password_sources(from_env)?;
+
+        if let Ok(password_file) = std::env::var("PRODUCT_DB_PASSWORD_FILE") {
+            info!(
+                "Loaded secret file path PRODUCT_DB_PASSWORD_FILE from env: {}",
+                password_file
+            );
+            self.postgres.password_file = Some(PathBuf::from(password_file));
+        }
+
+        self.postgres.load_password_file()?;
+
         if let Ok(password) = std::env::var("PRODUCT_DB_PASSWORD") {
             let password = product_db::Secret::new(password);
             info!("Loaded secret PRODUCT_DB_PASSWORD from env: {}", password);
             self.postgres.password = password;
         }
+
+        Ok(())
+    }
+
+    /// Checks that exactly one of `password` and `password_file` of the Postgres config is set.
+    /// Neither of them is required if the password is supplied by the environment or by a
+    /// secret provider. An empty password is treated as not set.
+    ///
+    /// # Arguments
+    /// - `from_env` - Whether the password is supplied by the environment.
+    fn check_password_sources(&self, from_env: bool) -> Result<()> {
+        let has_password = !self.postgres.password.bytes().is_empty();
+        let has_password_file = self.postgres.password_file.is_some();
+
+        if has_password && has_password_file {
+            bail!("Only one of postgres.password and postgres.password_file must be set");
+        }
+
+        if !has_password && !has_password_file && !from_env && self.secrets.is_none() {
+            bail!(
+                "The Postgres password is missing, set postgres.password, postgres.password_file \
+                or a secret provider"
+            );
+        }
+
+        Ok(())
     }
 }
 
@@ -78,23 +252,65 @@ impl TryFrom<ProgramOptions> for ProgramConfig {
         let config_path = value.config_path.as_path();
         let r = std::fs::File::open(config_path)
             .with_context(|| format!("Failed to open file {}", config_path.display()))?;
-        let mut c = ProgramConfig::from_reader(r)?;
-        c.load_secrets_from_env();
+        let mut c = ProgramConfig::from_reader(r, value.profile.as_deref())?;
+        c.load_secrets()?;
 
         Ok(c)
     }
 }
 
+/// Merges the overrides recursively into the base table. Nested tables are merged, all other
+/// values of the overrides replace the values of the base table.
+///
+/// # Arguments
+/// - `base` - The table to merge the overrides into.
+/// - `overrides` - The table with the overriding values.
+fn merge_tables(base: &mut toml::Table, overrides: toml::Table) {
+    for (key, value) in overrides {
+        match (base.get_mut(&key), value) {
+            (Some(toml::Value::Table(base)), toml::Value::Table(overrides)) => {
+                merge_tables(base, overrides);
+            }
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
     use crate::logging::LogLevel;
 
     use super::ProgramConfig;
 
+    const PROFILE_CONFIG: &str = r#"
+log = "Info"
+
+[endpoint]
+address = "0.0.0.0:3030"
+allow_origin = "*"
+
+[postgres]
+host = "localhost"
+port = 5432
+dbname = "product_db"
+user = "postgres"
+password = "postgres"
+max_connections = 5
+
+[profiles.dev]
+log = "Debug"
+
+[profiles.prod.postgres]
+host = "db.example.com"
+max_connections = 20
+"#;
+
     #[test]
     fn test_loading_config() {
         let data = include_bytes!("../../example/config.toml");
-        let c = ProgramConfig::from_reader(data.as_slice()).unwrap();
+        let c = ProgramConfig::from_reader(data.as_slice(), None).unwrap();
 
         assert_eq!(c.log, LogLevel::Debug);
 
@@ -102,6 +318,54 @@ mod test {
         assert_eq!(c.postgres.host, "localhost");
         assert_eq!(c.postgres.port, 5432);
         assert_eq!(c.postgres.user, "postgres");
-        assert_eq!(c.postgres.password.secret(), "postgres");
+        assert_eq!(c.postgres.password.secret(), Some("postgres"));
+    }
+
+    #[test]
+    fn test_loading_profiles() {
+        let c = ProgramConfig::from_reader(PROFILE_CONFIG.as_bytes(), None).unwrap();
+        assert_eq!(c.log, LogLevel::Info);
+        assert_eq!(c.postgres.host, "localhost");
+
+        let c = ProgramConfig::from_reader(PROFILE_CONFIG.as_bytes(), Some("dev")).unwrap();
+        assert_eq!(c.log, LogLevel::Debug);
+        assert_eq!(c.postgres.host, "localhost");
+
+        let c = ProgramConfig::from_reader(PROFILE_CONFIG.as_bytes(), Some("prod")).unwrap();
+        assert_eq!(c.log, LogLevel::Info);
+        assert_eq!(c.postgres.host, "db.example.com");
+        assert_eq!(c.postgres.max_connections, 20);
+        assert_eq!(c.postgres.dbname, "product_db");
+        assert_eq!(c.endpoint.address, "0.0.0.0:3030");
+
+        assert!(ProgramConfig::from_reader(PROFILE_CONFIG.as_bytes(), Some("staging")).is_err());
+    }
+
+    #[test]
+    fn test_password_sources() {
+        let c = ProgramConfig::from_reader(PROFILE_CONFIG.as_bytes(), None).unwrap();
+        assert!(c.check_password_sources(false).is_ok());
+
+        // both the password and the password file
+        let config = PROFILE_CONFIG.replace(
+            "password = \"postgres\"",
+            "password = \"postgres\"\npassword_file = \"/run/secrets/postgres_password\"",
+        );
+        let c = ProgramConfig::from_reader(config.as_bytes(), None).unwrap();
+        assert!(c.check_password_sources(false).is_err());
+        assert!(c.check_password_sources(true).is_err());
+
+        // neither the password nor the password file
+        let config = PROFILE_CONFIG.replace("password = \"postgres\"\n", "");
+        let c = ProgramConfig::from_reader(config.as_bytes(), None).unwrap();
+        assert!(c.check_password_sources(false).is_err());
+        assert!(c.check_password_sources(true).is_ok());
+
+        let config = format!(
+            "{}\n[secrets]\nprovider = \"vault\"\naddress = \"https://vault:8200\"\npath = \"db\"\n",
+            config.split("[profiles.dev]").next().unwrap()
+        );
+        let c = ProgramConfig::from_reader(config.as_bytes(), None).unwrap();
+        assert!(c.check_password_sources(false).is_ok());
     }
 }
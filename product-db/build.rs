@@ -0,0 +1,20 @@
+fn main() {
+    #[cfg(feature = "protobuf")]
+    compile_protos();
+}
+
+/// Generates the Rust types of the protobuf messages using the vendored protoc binary.
+#[cfg(feature = "protobuf")]
+fn compile_protos() {
+    const PROTO: &str = "proto/product_db/v1/product_db.proto";
+
+    println!("cargo:rerun-if-changed={}", PROTO);
+
+    let protoc = protoc_bin_vendored::protoc_bin_path().expect("protoc is vendored");
+    let protoc_include = protoc_bin_vendored::include_path().expect("protoc is vendored");
+
+    prost_build::Config::new()
+        .protoc_executable(protoc)
+        .compile_protos(&[PROTO], &[std::path::Path::new("proto"), &protoc_include])
+        .expect("Failed to compile the protobuf definitions");
+}
@@ -0,0 +1,55 @@
+use std::fmt::Display;
+
+use serde::{Deserialize, Serialize};
+
+/// The allergens that must be declared on food labels, i.e., the 14 major allergens of the
+/// EU food information regulation.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+#[cfg_attr(
+    feature = "service",
+    derive(sqlx::Type),
+    sqlx(type_name = "Allergen", rename_all = "lowercase")
+)]
+#[serde(rename_all = "lowercase")]
+pub enum Allergen {
+    /// Cereals containing gluten, e.g. wheat, rye, barley or oats.
+    Gluten,
+    Crustaceans,
+    Eggs,
+    Fish,
+    Peanuts,
+    Soybeans,
+    Milk,
+    /// Tree nuts, e.g. almonds, hazelnuts or walnuts.
+    Nuts,
+    Celery,
+    Mustard,
+    Sesame,
+    /// Sulphur dioxide and sulphites.
+    Sulphites,
+    Lupin,
+    Molluscs,
+}
+
+impl Display for Allergen {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Allergen::Gluten => write!(f, "gluten"),
+            Allergen::Crustaceans => write!(f, "crustaceans"),
+            Allergen::Eggs => write!(f, "eggs"),
+            Allergen::Fish => write!(f, "fish"),
+            Allergen::Peanuts => write!(f, "peanuts"),
+            Allergen::Soybeans => write!(f, "soybeans"),
+            Allergen::Milk => write!(f, "milk"),
+            Allergen::Nuts => write!(f, "nuts"),
+            Allergen::Celery => write!(f, "celery"),
+            Allergen::Mustard => write!(f, "mustard"),
+            Allergen::Sesame => write!(f, "sesame"),
+            Allergen::Sulphites => write!(f, "sulphites"),
+            Allergen::Lupin => write!(f, "lupin"),
+            Allergen::Molluscs => write!(f, "molluscs"),
+        }
+    }
+}
@@ -0,0 +1,714 @@
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use axum::{
+    extract::{OriginalUri, Request, State},
+    http::{header, HeaderMap, StatusCode},
+    middleware::Next,
+    response::Response,
+};
+use jsonwebtoken::{decode, decode_header, jwk::JwkSet, DecodingKey, Validation};
+use log::{debug, error, info, warn};
+use tokio::sync::{Mutex, RwLock};
+
+use crate::{layers::problem_response, AuthOptions, Error, JwtOptions, Result, Secret};
+
+/// The header with the API key of the client.
+pub const API_KEY_HEADER: &str = "x-api-key";
+
+/// The scheme of the `Authorization` header that carries the API key or the JWT.
+const BEARER_PREFIX: &str = "Bearer ";
+
+/// The minimal time between two fetches of the JWKS, which limits the fetches caused by tokens
+/// with unknown key ids.
+const MIN_JWKS_REFRESH_INTERVAL: Duration = Duration::from_secs(30);
+
+/// The timeout for connecting to the OIDC provider when fetching the JWKS.
+const JWKS_CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The timeout of the whole request for fetching the JWKS, which bounds the time the requests
+/// with tokens wait for the keys.
+const JWKS_REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Authenticates the clients of the admin endpoints by their API keys.
+#[derive(Clone)]
+pub struct ApiKeyAuthenticator {
+    api_keys: Vec<Secret>,
+}
+
+impl ApiKeyAuthenticator {
+    /// Creates a new authenticator.
+    ///
+    /// # Arguments
+    /// - `api_keys` - The accepted API keys.
+    pub fn new(api_keys: Vec<Secret>) -> Self {
+        Self { api_keys }
+    }
+
+    /// Returns the API key of the request, which is either passed via the `X-API-Key` header
+    /// or as bearer token of the `Authorization` header.
+    ///
+    /// # Arguments
+    /// - `headers` - The headers of the request.
+    pub fn api_key(headers: &HeaderMap) -> Option<Secret> {
+        if let Some(key) = headers.get(API_KEY_HEADER) {
+            return Some(Secret::from_bytes(key.as_bytes().to_vec()));
+        }
+
+        bearer_token(headers).map(|token| Secret::new(token.to_string()))
+    }
+
+    /// Returns true if the given key is one of the accepted API keys. All keys are compared in
+    /// constant time.
+    ///
+    /// # Arguments
+    /// - `key` - The API key presented by the client.
+    pub fn is_valid(&self, key: &Secret) -> bool {
+        !key.bytes().is_empty()
+            && self
+                .api_keys
+                .iter()
+                .fold(false, |valid, api_key| (api_key == key) | valid)
+    }
+}
+
+/// The keys of the JWKS together with the time they have been fetched.
+#[derive(Default)]
+struct CachedKeys {
+    keys: Vec<(Option<String>, DecodingKey)>,
+    fetched_at: Option<Instant>,
+}
+
+impl CachedKeys {
+    /// Returns the key with the given id. Tokens without key id are verified with the only key
+    /// of the set.
+    ///
+    /// # Arguments
+    /// - `kid` - The key id of the token header.
+    fn find(&self, kid: Option<&str>) -> Option<DecodingKey> {
+        match kid {
+            Some(kid) => self
+                .keys
+                .iter()
+                .find(|(id, _)| id.as_deref() == Some(kid))
+                .map(|(_, key)| key.clone()),
+            None if self.keys.len() == 1 => Some(self.keys[0].1.clone()),
+            None => None,
+        }
+    }
+
+    /// Returns true if the keys have been fetched longer than the given duration ago.
+    ///
+    /// # Arguments
+    /// - `max_age` - The maximal age of the keys.
+    fn is_older_than(&self, max_age: Duration) -> bool {
+        self.fetched_at.is_none_or(|at| at.elapsed() >= max_age)
+    }
+}
+
+/// The reasons for rejecting a bearer token.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TokenRejection {
+    /// The token is malformed, expired, not signed by the issuer or not intended for the service.
+    Invalid(String),
+
+    /// The keys for verifying the token could not be fetched.
+    KeysUnavailable,
+}
+
+/// Verifies JWTs issued by an OIDC provider, e.g. Keycloak or Auth0, and maps their role claims
+/// to the access of the user and admin endpoints. The keys are fetched from the JWKS url of the
+/// provider and fetched again if a token refers to an unknown key, e.g. after a key rotation.
+pub struct JwtVerifier {
+    options: JwtOptions,
+    client: reqwest::Client,
+    keys: RwLock<CachedKeys>,
+    fetch: Mutex<()>,
+}
+
+impl JwtVerifier {
+    /// Creates a new verifier. The keys are fetched with the first token.
+    ///
+    /// # Arguments
+    /// - `options` - The options for verifying the tokens.
+    pub fn new(options: JwtOptions) -> Self {
+        Self {
+            options,
+            client: reqwest::Client::builder()
+                .connect_timeout(JWKS_CONNECT_TIMEOUT)
+                .timeout(JWKS_REQUEST_TIMEOUT)
+                .build()
+                .expect("Failed to create the HTTP client for the JWKS"),
+            keys: RwLock::new(CachedKeys::default()),
+            fetch: Mutex::new(()),
+        }
+    }
+
+    /// Returns the role that grants access to the user endpoints, if required.
+    pub fn user_role(&self) -> Option<&str> {
+        self.options.user_role.as_deref()
+    }
+
+    /// Returns the role that grants access to the admin endpoints.
+    pub fn admin_role(&self) -> &str {
+        &self.options.admin_role
+    }
+
+    /// Verifies the signature, the issuer, the audience and the expiry of the given token and
+    /// returns its roles.
+    ///
+    /// # Arguments
+    /// - `token` - The encoded JWT.
+    pub async fn verify(&self, token: &str) -> std::result::Result<Vec<String>, TokenRejection> {
+        let token_header = decode_header(token)
+            .map_err(|e| TokenRejection::Invalid(format!("Malformed token: {}", e)))?;
+        if !self.options.algorithms.contains(&token_header.alg) {
+            return Err(TokenRejection::Invalid(format!(
+                "The algorithm {:?} is not accepted",
+                token_header.alg
+            )));
+        }
+
+        let key = self
+            .decoding_key(token_header.kid.as_deref())
+            .await?
+            .ok_or_else(|| {
+                TokenRejection::Invalid(format!(
+                    "The key {:?} of the token is unknown",
+                    token_header.kid
+                ))
+            })?;
+
+        let mut validation = Validation::new(token_header.alg);
+        validation.set_issuer(&[&self.options.issuer]);
+        validation.set_audience(&[&self.options.audience]);
+
+        let claims = decode::<serde_json::Value>(token, &key, &validation)
+            .map_err(|e| TokenRejection::Invalid(format!("Invalid token: {}", e)))?
+            .claims;
+
+        Ok(roles(&claims, &self.options.roles_claim))
+    }
+
+    /// Returns the key with the given id. The keys are fetched again if they are stale or the
+    /// key is unknown, but not more often than [`MIN_JWKS_REFRESH_INTERVAL`]. Only one request
+    /// fetches the keys at a time and the cached keys are not locked while fetching, i.e., the
+    /// tokens with known keys are still verified.
+    ///
+    /// # Arguments
+    /// - `kid` - The key id of the token header.
+    async fn decoding_key(
+        &self,
+        kid: Option<&str>,
+    ) -> std::result::Result<Option<DecodingKey>, TokenRejection> {
+        let max_age = self.options.jwks_refresh_interval();
+        {
+            let keys = self.keys.read().await;
+            if !keys.is_older_than(max_age) {
+                if let Some(key) = keys.find(kid) {
+                    return Ok(Some(key));
+                }
+            }
+            if !keys.is_older_than(MIN_JWKS_REFRESH_INTERVAL) {
+                return Ok(keys.find(kid));
+            }
+        }
+
+        // the other requests wait for the keys fetched by the first one
+        let _fetch = self.fetch.lock().await;
+
+        // another request may have fetched the keys in the meantime
+        {
+            let keys = self.keys.read().await;
+            if !keys.is_older_than(MIN_JWKS_REFRESH_INTERVAL) {
+                return Ok(keys.find(kid));
+            }
+        }
+
+        match self.fetch_keys().await {
+            Ok(fetched) => {
+                let mut keys = self.keys.write().await;
+                *keys = CachedKeys {
+                    keys: fetched,
+                    fetched_at: Some(Instant::now()),
+                };
+                Ok(keys.find(kid))
+            }
+            Err(err) => {
+                // stale keys are still better than none, e.g. during an outage of the provider
+                error!("Failed to fetch the JWKS: {}", err);
+                let keys = self.keys.read().await;
+                if keys.fetched_at.is_none() {
+                    return Err(TokenRejection::KeysUnavailable);
+                }
+                Ok(keys.find(kid))
+            }
+        }
+    }
+
+    /// Fetches the keys from the JWKS url. Keys that cannot be used for verifying, e.g. due to
+    /// unsupported parameters, are skipped.
+    async fn fetch_keys(&self) -> Result<Vec<(Option<String>, DecodingKey)>> {
+        debug!("Fetching the JWKS from {}", self.options.jwks_url);
+
+        let response = self
+            .client
+            .get(&self.options.jwks_url)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| Error::JwksError(format!("Failed to request the JWKS: {}", e)))?;
+        let jwks: JwkSet = response
+            .json()
+            .await
+            .map_err(|e| Error::JwksError(format!("Failed to parse the JWKS: {}", e)))?;
+
+        let keys: Vec<_> = jwks
+            .keys
+            .iter()
+            .filter_map(|jwk| match DecodingKey::from_jwk(jwk) {
+                Ok(key) => Some((jwk.common.key_id.clone(), key)),
+                Err(err) => {
+                    warn!("Skipping key {:?} of the JWKS: {}", jwk.common.key_id, err);
+                    None
+                }
+            })
+            .collect();
+
+        info!(
+            "Fetched {} keys from the JWKS at {}",
+            keys.len(),
+            self.options.jwks_url
+        );
+
+        Ok(keys)
+    }
+}
+
+/// Returns the roles of the given claims. The claim may be nested, e.g. "realm_access.roles"
+/// of Keycloak, and contain an array of roles or a space separated list like the "scope" claim.
+///
+/// # Arguments
+/// - `claims` - The claims of the token.
+/// - `roles_claim` - The dot separated path of the roles claim.
+fn roles(claims: &serde_json::Value, roles_claim: &str) -> Vec<String> {
+    let value = roles_claim
+        .split('.')
+        .try_fold(claims, |value, name| value.get(name));
+
+    match value {
+        Some(serde_json::Value::Array(roles)) => roles
+            .iter()
+            .filter_map(|role| role.as_str())
+            .map(str::to_string)
+            .collect(),
+        Some(serde_json::Value::String(roles)) => {
+            roles.split_whitespace().map(str::to_string).collect()
+        }
+        _ => Vec::new(),
+    }
+}
+
+/// Returns the bearer token of the `Authorization` header, if any.
+///
+/// # Arguments
+/// - `headers` - The headers of the request.
+fn bearer_token(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix(BEARER_PREFIX))
+        .map(str::trim)
+}
+
+/// The access that is checked by the [`auth_guard`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Access {
+    /// The access to the user endpoints, which requires the user or the admin role.
+    User,
+
+    /// The access to the admin endpoints, which requires an API key or the admin role.
+    Admin,
+}
+
+/// Authenticates the clients by their API keys or by the JWTs of an OIDC provider.
+pub struct Authenticator {
+    api_keys: ApiKeyAuthenticator,
+    jwt: Option<JwtVerifier>,
+}
+
+impl Authenticator {
+    /// Creates the authenticator from the auth options.
+    ///
+    /// # Arguments
+    /// - `options` - The options for authenticating the clients.
+    pub fn from_options(options: &AuthOptions) -> Self {
+        Self {
+            api_keys: ApiKeyAuthenticator::new(options.admin_api_keys.clone()),
+            jwt: options.jwt.clone().map(JwtVerifier::new),
+        }
+    }
+
+    /// Returns true if the user endpoints require a token with the user role.
+    pub fn guards_user_endpoints(&self) -> bool {
+        self.jwt
+            .as_ref()
+            .is_some_and(|jwt| jwt.user_role().is_some())
+    }
+
+    /// Checks whether the request grants the given access. Returns the response for rejecting
+    /// the request otherwise.
+    ///
+    /// # Arguments
+    /// - `access` - The access requested by the client.
+    /// - `headers` - The headers of the request.
+    async fn check(
+        &self,
+        access: Access,
+        headers: &HeaderMap,
+    ) -> std::result::Result<(), Response> {
+        if access == Access::Admin {
+            if let Some(key) = ApiKeyAuthenticator::api_key(headers) {
+                if self.api_keys.is_valid(&key) {
+                    return Ok(());
+                }
+            }
+        }
+
+        let (jwt, token) = match (&self.jwt, bearer_token(headers)) {
+            (Some(jwt), Some(token)) if !headers.contains_key(API_KEY_HEADER) => (jwt, token),
+            (_, None) if !headers.contains_key(API_KEY_HEADER) => {
+                return Err(problem_response(
+                    StatusCode::UNAUTHORIZED,
+                    "The request must be authenticated".to_string(),
+                ))
+            }
+            _ => {
+                return Err(problem_response(
+                    StatusCode::UNAUTHORIZED,
+                    "The API key of the request is invalid".to_string(),
+                ))
+            }
+        };
+
+        let roles = match jwt.verify(token).await {
+            Ok(roles) => roles,
+            Err(TokenRejection::Invalid(reason)) => {
+                debug!("Rejected token: {}", reason);
+                return Err(problem_response(
+                    StatusCode::UNAUTHORIZED,
+                    "The token of the request is invalid or has expired".to_string(),
+                ));
+            }
+            Err(TokenRejection::KeysUnavailable) => {
+                return Err(problem_response(
+                    StatusCode::SERVICE_UNAVAILABLE,
+                    "The keys for verifying the token are unavailable".to_string(),
+                ));
+            }
+        };
+
+        let granted = roles.iter().any(|role| {
+            role == jwt.admin_role()
+                || (access == Access::User && Some(role.as_str()) == jwt.user_role())
+        });
+        if granted {
+            Ok(())
+        } else {
+            Err(problem_response(
+                StatusCode::FORBIDDEN,
+                "The token does not grant access to the endpoint".to_string(),
+            ))
+        }
+    }
+}
+
+/// Middleware that rejects requests without valid credentials for the given access with 401
+/// and requests whose token lacks the required role with 403.
+pub async fn auth_guard(
+    State((authenticator, access)): State<(Arc<Authenticator>, Access)>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let uri = request
+        .extensions()
+        .get::<OriginalUri>()
+        .map(|uri| uri.0.clone())
+        .unwrap_or_else(|| request.uri().clone());
+
+    match authenticator.check(access, request.headers()).await {
+        Ok(()) => {
+            debug!("Authenticated {} {}", request.method(), uri);
+            next.run(request).await
+        }
+        Err(response) => {
+            warn!(
+                "Rejected request {} {} with {}",
+                request.method(),
+                uri,
+                response.status()
+            );
+            response
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use axum::{http::HeaderValue, routing::get, Json, Router};
+    use chrono::Utc;
+    use jsonwebtoken::{encode, Algorithm, EncodingKey, Header};
+
+    use super::*;
+
+    const JWT_SECRET: &[u8] = b"jwt-test-secret";
+
+    /// The base64url encoding of [`JWT_SECRET`] as used in the JWKS.
+    const JWT_SECRET_BASE64: &str = "and0LXRlc3Qtc2VjcmV0";
+
+    fn token(kid: &str, audience: &str, roles: serde_json::Value, expires_in: i64) -> String {
+        let mut header = Header::new(Algorithm::HS256);
+        header.kid = Some(kid.to_string());
+        let claims = serde_json::json!({
+            "iss": "https://issuer.example.com",
+            "aud": audience,
+            "exp": Utc::now().timestamp() + expires_in,
+            "realm_access": { "roles": roles },
+        });
+
+        encode(&header, &claims, &EncodingKey::from_secret(JWT_SECRET)).unwrap()
+    }
+
+    fn bearer(token: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::AUTHORIZATION,
+            HeaderValue::from_str(&format!("Bearer {}", token)).unwrap(),
+        );
+        headers
+    }
+
+    #[test]
+    fn test_api_key() {
+        let authenticator = ApiKeyAuthenticator::new(vec![
+            Secret::new("first".to_string()),
+            Secret::new("second".to_string()),
+        ]);
+
+        let mut headers = HeaderMap::new();
+        assert!(ApiKeyAuthenticator::api_key(&headers).is_none());
+
+        headers.insert(
+            header::AUTHORIZATION,
+            HeaderValue::from_static("Bearer second"),
+        );
+        let key = ApiKeyAuthenticator::api_key(&headers).unwrap();
+        assert!(authenticator.is_valid(&key));
+
+        // the dedicated header takes precedence over the authorization header
+        headers.insert(API_KEY_HEADER, HeaderValue::from_static("third"));
+        let key = ApiKeyAuthenticator::api_key(&headers).unwrap();
+        assert!(!authenticator.is_valid(&key));
+
+        headers.insert(API_KEY_HEADER, HeaderValue::from_static("first"));
+        let key = ApiKeyAuthenticator::api_key(&headers).unwrap();
+        assert!(authenticator.is_valid(&key));
+
+        // other schemes of the authorization header are ignored
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::AUTHORIZATION,
+            HeaderValue::from_static("Basic Zmlyc3Q="),
+        );
+        assert!(ApiKeyAuthenticator::api_key(&headers).is_none());
+
+        assert!(!authenticator.is_valid(&Secret::new(String::new())));
+        assert!(!ApiKeyAuthenticator::new(Vec::new()).is_valid(&Secret::new("first".to_string())));
+    }
+
+    #[test]
+    fn test_roles() {
+        let claims = serde_json::json!({
+            "scope": "openid admin",
+            "realm_access": { "roles": ["user", 42, "admin"] },
+        });
+
+        assert_eq!(roles(&claims, "scope"), vec!["openid", "admin"]);
+        assert_eq!(roles(&claims, "realm_access.roles"), vec!["user", "admin"]);
+        assert!(roles(&claims, "roles").is_empty());
+        assert!(roles(&claims, "scope.roles").is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_jwt_authenticator() {
+        // minimal OIDC provider that serves a single symmetric key
+        let app = Router::new().route(
+            "/jwks",
+            get(|| async {
+                Json(serde_json::json!({
+                    "keys": [{
+                        "kty": "oct",
+                        "kid": "key-1",
+                        "alg": "HS256",
+                        "k": JWT_SECRET_BASE64,
+                    }]
+                }))
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let jwks_url = format!("http://{}/jwks", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let options = AuthOptions {
+            admin_api_keys: vec![Secret::new("api-key".to_string())],
+            jwt: Some(JwtOptions {
+                issuer: "https://issuer.example.com".to_string(),
+                audience: "product-db".to_string(),
+                jwks_url,
+                algorithms: vec![Algorithm::HS256],
+                roles_claim: "realm_access.roles".to_string(),
+                admin_role: "admin".to_string(),
+                user_role: Some("user".to_string()),
+                jwks_refresh_secs: 3600,
+            }),
+        };
+        let authenticator = Authenticator::from_options(&options);
+        assert!(authenticator.guards_user_endpoints());
+
+        let status = |result: std::result::Result<(), Response>| match result {
+            Ok(()) => StatusCode::OK,
+            Err(response) => response.status(),
+        };
+
+        // the admin role grants access to all endpoints, the user role only to the user ones
+        let admin = bearer(&token(
+            "key-1",
+            "product-db",
+            serde_json::json!(["admin"]),
+            60,
+        ));
+        let user = bearer(&token(
+            "key-1",
+            "product-db",
+            serde_json::json!(["user"]),
+            60,
+        ));
+        let other = bearer(&token(
+            "key-1",
+            "product-db",
+            serde_json::json!(["other"]),
+            60,
+        ));
+        assert_eq!(
+            status(authenticator.check(Access::Admin, &admin).await),
+            StatusCode::OK
+        );
+        assert_eq!(
+            status(authenticator.check(Access::User, &admin).await),
+            StatusCode::OK
+        );
+        assert_eq!(
+            status(authenticator.check(Access::Admin, &user).await),
+            StatusCode::FORBIDDEN
+        );
+        assert_eq!(
+            status(authenticator.check(Access::User, &user).await),
+            StatusCode::OK
+        );
+        assert_eq!(
+            status(authenticator.check(Access::User, &other).await),
+            StatusCode::FORBIDDEN
+        );
+
+        // expired tokens, tokens of other audiences and unknown keys are rejected
+        let expired = bearer(&token(
+            "key-1",
+            "product-db",
+            serde_json::json!(["admin"]),
+            -600,
+        ));
+        let audience = bearer(&token("key-1", "other", serde_json::json!(["admin"]), 60));
+        let unknown = bearer(&token(
+            "key-2",
+            "product-db",
+            serde_json::json!(["admin"]),
+            60,
+        ));
+        for headers in [&expired, &audience, &unknown, &bearer("garbage")] {
+            assert_eq!(
+                status(authenticator.check(Access::Admin, headers).await),
+                StatusCode::UNAUTHORIZED
+            );
+        }
+        assert_eq!(
+            status(authenticator.check(Access::User, &HeaderMap::new()).await),
+            StatusCode::UNAUTHORIZED
+        );
+
+        // the API keys are accepted on the admin endpoints only
+        let mut api_key = HeaderMap::new();
+        api_key.insert(API_KEY_HEADER, HeaderValue::from_static("api-key"));
+        assert_eq!(
+            status(authenticator.check(Access::Admin, &api_key).await),
+            StatusCode::OK
+        );
+        assert_eq!(
+            status(authenticator.check(Access::User, &api_key).await),
+            StatusCode::UNAUTHORIZED
+        );
+        assert_eq!(
+            status(authenticator.check(Access::Admin, &bearer("api-key")).await),
+            StatusCode::OK
+        );
+    }
+
+    #[tokio::test]
+    async fn test_jwks_single_fetch() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        // slow OIDC provider that counts the fetches of the JWKS
+        let fetches = Arc::new(AtomicUsize::new(0));
+        let app = Router::new().route(
+            "/jwks",
+            get({
+                let fetches = fetches.clone();
+                move || async move {
+                    fetches.fetch_add(1, Ordering::SeqCst);
+                    tokio::time::sleep(Duration::from_millis(200)).await;
+                    Json(serde_json::json!({
+                        "keys": [{
+                            "kty": "oct",
+                            "kid": "key-1",
+                            "alg": "HS256",
+                            "k": JWT_SECRET_BASE64,
+                        }]
+                    }))
+                }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let jwks_url = format!("http://{}/jwks", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let verifier = JwtVerifier::new(JwtOptions {
+            issuer: "https://issuer.example.com".to_string(),
+            audience: "product-db".to_string(),
+            jwks_url,
+            algorithms: vec![Algorithm::HS256],
+            roles_claim: "realm_access.roles".to_string(),
+            admin_role: "admin".to_string(),
+            user_role: None,
+            jwks_refresh_secs: 3600,
+        });
+
+        // the concurrent requests wait for the keys fetched by the first one
+        let token = token("key-1", "product-db", serde_json::json!(["admin"]), 60);
+        let results = futures::future::join_all((0..5).map(|_| verifier.verify(&token))).await;
+        assert!(results
+            .iter()
+            .all(|roles| roles.as_deref() == Ok(&["admin".to_string()][..])));
+        assert_eq!(fetches.load(Ordering::SeqCst), 1);
+    }
+}
@@ -0,0 +1,26 @@
+//! Writes the TypeScript declarations of the JSON API to a `.d.ts` file.
+//!
+//! Usage: `cargo run -p product-db --features typescript --bin generate-typescript [OUTPUT]`
+//!
+//! The output defaults to `product-db.d.ts` in the current directory.
+
+use std::{env, fs, process::ExitCode};
+
+const DEFAULT_OUTPUT: &str = "product-db.d.ts";
+
+fn main() -> ExitCode {
+    let output = env::args()
+        .nth(1)
+        .unwrap_or_else(|| DEFAULT_OUTPUT.to_string());
+
+    match fs::write(&output, product_db::typescript_declarations()) {
+        Ok(()) => {
+            println!("Wrote the TypeScript declarations to {}", output);
+            ExitCode::SUCCESS
+        }
+        Err(err) => {
+            eprintln!("Failed to write {}: {}", output, err);
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,253 @@
+use axum::{
+    body::Bytes,
+    extract::{FromRequest, FromRequestParts, Request},
+    http::{header, request::Parts, HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use log::{debug, error};
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::layers::problem_response;
+
+/// The content type of MessagePack bodies.
+const MSGPACK_CONTENT_TYPE: &str = "application/msgpack";
+
+/// The content type of CBOR bodies.
+const CBOR_CONTENT_TYPE: &str = "application/cbor";
+
+/// The formats in which request and response bodies can be encoded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BodyFormat {
+    #[default]
+    Json,
+    MessagePack,
+    Cbor,
+}
+
+impl BodyFormat {
+    /// Returns the format of the given media type or None if the media type is not supported.
+    ///
+    /// # Arguments
+    /// - `media_type` - The media type without parameters, e.g. "application/msgpack".
+    fn from_media_type(media_type: &str) -> Option<Self> {
+        match media_type.trim().to_ascii_lowercase().as_str() {
+            "application/json" | "application/*" | "*/*" => Some(BodyFormat::Json),
+            "application/msgpack" | "application/x-msgpack" | "application/vnd.msgpack" => {
+                Some(BodyFormat::MessagePack)
+            }
+            "application/cbor" => Some(BodyFormat::Cbor),
+            _ => None,
+        }
+    }
+
+    /// Returns the format of the request body according to the Content-Type header. Bodies
+    /// without or with an unknown content type are handled as JSON.
+    ///
+    /// # Arguments
+    /// - `headers` - The headers of the request.
+    fn from_content_type(headers: &HeaderMap) -> Self {
+        headers
+            .get(header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.split(';').next())
+            .and_then(Self::from_media_type)
+            .unwrap_or_default()
+    }
+
+    /// Returns the preferred format of the response according to the Accept header. The media
+    /// ranges are ordered by their quality, JSON is used if none of them is supported.
+    ///
+    /// # Arguments
+    /// - `headers` - The headers of the request.
+    pub fn from_accept(headers: &HeaderMap) -> Self {
+        let mut candidates: Vec<(f32, BodyFormat)> = headers
+            .get_all(header::ACCEPT)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .filter_map(|range| {
+                let mut parts = range.split(';');
+                let format = Self::from_media_type(parts.next()?)?;
+                let quality = parts
+                    .filter_map(|param| param.trim().strip_prefix("q="))
+                    .find_map(|q| q.trim().parse::<f32>().ok())
+                    .unwrap_or(1.0);
+
+                (quality > 0.0).then_some((quality, format))
+            })
+            .collect();
+
+        // the sort is stable, i.e., the order of the header is kept for equal qualities
+        candidates.sort_by(|lhs, rhs| rhs.0.total_cmp(&lhs.0));
+        candidates
+            .first()
+            .map(|(_, format)| *format)
+            .unwrap_or_default()
+    }
+
+    /// Returns the content type of bodies in this format.
+    fn content_type(&self) -> &'static str {
+        match self {
+            BodyFormat::Json => "application/json",
+            BodyFormat::MessagePack => MSGPACK_CONTENT_TYPE,
+            BodyFormat::Cbor => CBOR_CONTENT_TYPE,
+        }
+    }
+}
+
+impl<S: Send + Sync> FromRequestParts<S> for BodyFormat {
+    type Rejection = std::convert::Infallible;
+
+    /// Extracts the preferred format of the response from the Accept header.
+    async fn from_request_parts(
+        parts: &mut Parts,
+        _state: &S,
+    ) -> std::result::Result<Self, Self::Rejection> {
+        Ok(Self::from_accept(&parts.headers))
+    }
+}
+
+/// Extractor for request bodies that are encoded as JSON, MessagePack or CBOR depending on the
+/// Content-Type header.
+#[derive(Debug, Clone)]
+pub struct Negotiated<T>(pub T);
+
+impl<T, S> FromRequest<S> for Negotiated<T>
+where
+    T: DeserializeOwned,
+    S: Send + Sync,
+{
+    type Rejection = Response;
+
+    async fn from_request(req: Request, state: &S) -> std::result::Result<Self, Self::Rejection> {
+        let format = BodyFormat::from_content_type(req.headers());
+        if format == BodyFormat::Json {
+            return Json::<T>::from_request(req, state)
+                .await
+                .map(|Json(value)| Negotiated(value))
+                .map_err(IntoResponse::into_response);
+        }
+
+        let body = Bytes::from_request(req, state)
+            .await
+            .map_err(IntoResponse::into_response)?;
+
+        let value = match format {
+            BodyFormat::MessagePack => rmp_serde::from_slice(&body).map_err(|e| e.to_string()),
+            BodyFormat::Cbor => ciborium::from_reader(body.as_ref()).map_err(|e| e.to_string()),
+            BodyFormat::Json => unreachable!("JSON bodies are handled above"),
+        };
+
+        value.map(Negotiated).map_err(|e| {
+            debug!("Failed to decode the {} body: {}", format.content_type(), e);
+
+            problem_response(
+                StatusCode::BAD_REQUEST,
+                format!("Failed to decode the {} body: {}", format.content_type(), e),
+            )
+        })
+    }
+}
+
+/// Response whose body is encoded in the format requested via the Accept header, see
+/// [`BodyFormat::from_accept`].
+#[derive(Debug, Clone)]
+pub struct Encoded<T>(pub BodyFormat, pub T);
+
+impl<T: Serialize> IntoResponse for Encoded<T> {
+    fn into_response(self) -> Response {
+        let Encoded(format, value) = self;
+
+        let body = match format {
+            BodyFormat::Json => serde_json::to_vec(&value).map_err(|e| e.to_string()),
+            BodyFormat::MessagePack => rmp_serde::to_vec_named(&value).map_err(|e| e.to_string()),
+            BodyFormat::Cbor => {
+                let mut body = Vec::new();
+                ciborium::into_writer(&value, &mut body)
+                    .map(|_| body)
+                    .map_err(|e| e.to_string())
+            }
+        };
+
+        match body {
+            Ok(body) => (
+                [
+                    (
+                        header::CONTENT_TYPE,
+                        HeaderValue::from_static(format.content_type()),
+                    ),
+                    (header::VARY, HeaderValue::from_static("accept")),
+                ],
+                body,
+            )
+                .into_response(),
+            Err(e) => {
+                error!("Failed to encode the {} body: {}", format.content_type(), e);
+
+                problem_response(
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    format!("Failed to encode the {} body: {}", format.content_type(), e),
+                )
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Returns the headers with the given Accept header.
+    ///
+    /// # Arguments
+    /// - `accept` - The value of the Accept header.
+    fn accept(accept: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::ACCEPT, HeaderValue::from_str(accept).unwrap());
+        headers
+    }
+
+    #[test]
+    fn test_from_accept() {
+        assert_eq!(BodyFormat::from_accept(&HeaderMap::new()), BodyFormat::Json);
+        assert_eq!(BodyFormat::from_accept(&accept("*/*")), BodyFormat::Json);
+        assert_eq!(
+            BodyFormat::from_accept(&accept("application/msgpack")),
+            BodyFormat::MessagePack
+        );
+        assert_eq!(
+            BodyFormat::from_accept(&accept("application/cbor, application/json")),
+            BodyFormat::Cbor
+        );
+        assert_eq!(
+            BodyFormat::from_accept(&accept("application/json;q=0.5, application/cbor;q=0.9")),
+            BodyFormat::Cbor
+        );
+        assert_eq!(
+            BodyFormat::from_accept(&accept("application/msgpack;q=0, text/html")),
+            BodyFormat::Json
+        );
+    }
+
+    #[test]
+    fn test_from_content_type() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(BodyFormat::from_content_type(&headers), BodyFormat::Json);
+
+        headers.insert(
+            header::CONTENT_TYPE,
+            HeaderValue::from_static("application/x-msgpack"),
+        );
+        assert_eq!(
+            BodyFormat::from_content_type(&headers),
+            BodyFormat::MessagePack
+        );
+
+        headers.insert(
+            header::CONTENT_TYPE,
+            HeaderValue::from_static("application/cbor; charset=binary"),
+        );
+        assert_eq!(BodyFormat::from_content_type(&headers), BodyFormat::Cbor);
+    }
+}
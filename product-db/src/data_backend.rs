@@ -1,18 +1,41 @@
-use std::{
-    fmt::{self, Display, Formatter},
-    future::Future,
-};
+use std::fmt::{self, Display, Formatter};
+#[cfg(feature = "service")]
+use std::future::Future;
 
+#[cfg(feature = "service")]
+use futures::{stream, Stream, StreamExt};
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    MissingProduct, Options, ProductDescription, ProductID, ProductImage, ProductRequest, Result,
+    resolve_product_id, Allergen, DietaryLabels, EcoScoreGrade, ProductField, ProductID,
+    QuantityType, RequestOutcome, RequestStatus,
+};
+#[cfg(feature = "service")]
+use crate::{
+    AdditionalImage, ArchivedProductRequest, ImageStorageUsage, MissingProduct,
+    MissingProductSummary, Options, PriceObservation, ProductAvailability, ProductDescription,
+    ProductImage, ProductRequest, Recipe, Result, Secret, Store,
 };
 
 pub type DBId = i32;
 
+/// The maximal length of a product id.
+pub const MAX_PRODUCT_ID_LENGTH: usize = 64;
+
+/// The maximal number of additional images of a product or product request.
+pub const MAX_ADDITIONAL_IMAGES: usize = 8;
+
+/// The number of products queried at once by [`ReadBackend::export_products`].
+#[cfg(feature = "service")]
+const EXPORT_PRODUCTS_PAGE_SIZE: i32 = 200;
+
+/// The maximal number of missing products that can be reported at once.
+pub const MAX_MISSING_PRODUCTS_BATCH_SIZE: usize = 500;
+
 /// The sorting order for the query results.
 #[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub enum SortingOrder {
     #[serde(rename = "asc")]
     Ascending,
@@ -32,6 +55,8 @@ impl Display for SortingOrder {
 
 /// The query parameters for querying the missing products.
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct MissingProductQuery {
     /// The offset of the query results.
     #[serde(default)]
@@ -44,8 +69,70 @@ pub struct MissingProductQuery {
     pub order: SortingOrder,
 }
 
+/// The query parameters for searching the archived product requests.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct ArchiveQuery {
+    /// The offset of the query results.
+    #[serde(default)]
+    pub offset: i32,
+    /// The limit of the query results.
+    pub limit: i32,
+    /// The product id to filter the results for (optional).
+    #[serde(default)]
+    pub product_id: Option<ProductID>,
+    /// The outcome to filter the results for (optional).
+    #[serde(default)]
+    pub outcome: Option<RequestOutcome>,
+    /// If the results are in ascending or descending order of the archived date.
+    pub order: SortingOrder,
+}
+
+/// The query parameters for searching the observed prices.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct PriceQuery {
+    /// The offset of the query results.
+    #[serde(default)]
+    pub offset: i32,
+    /// The limit of the query results.
+    pub limit: i32,
+    /// The product id to filter the results for (optional).
+    #[serde(default)]
+    pub product_id: Option<ProductID>,
+    /// The store to filter the results for (optional).
+    #[serde(default)]
+    pub store: Option<String>,
+    /// The currency to filter the results for (optional).
+    #[serde(default)]
+    pub currency: Option<String>,
+    /// If the results are in ascending or descending order of the observation date.
+    pub order: SortingOrder,
+}
+
+/// The query parameters for searching the stores.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct StoreQuery {
+    /// The offset of the query results.
+    #[serde(default)]
+    pub offset: i32,
+    /// The limit of the query results.
+    pub limit: i32,
+    /// The retail chain to filter the results for (optional).
+    #[serde(default)]
+    pub chain: Option<String>,
+    /// If the results are in ascending or descending order of the name of the store.
+    pub order: SortingOrder,
+}
+
 /// The sorting field for the query results.
 #[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub enum SortingField {
     /// The date when the product was reported. (Only applicable for product requests)
     #[serde(rename = "reported_date")]
@@ -62,6 +149,10 @@ pub enum SortingField {
     /// The similarity of the search result. (Only applicable if search string is provided)
     #[serde(rename = "similarity")]
     Similarity,
+
+    /// The NOVA group of the product.
+    #[serde(rename = "nova_group")]
+    NovaGroup,
 }
 
 impl Display for SortingField {
@@ -71,12 +162,17 @@ impl Display for SortingField {
             SortingField::Name => write!(f, "name"),
             SortingField::ProductID => write!(f, "product_id"),
             SortingField::Similarity => write!(f, "similarity"),
+            SortingField::NovaGroup => write!(f, "nova_group"),
         }
     }
 }
 
-/// The sorting parameters for the query results.
+/// The sorting parameters for the query results. Ties are broken by a unique key in the same
+/// order, e.g. the product id, so the pages of a query are disjoint and without gaps even if
+/// many results share the sorted value.
 #[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct Sorting {
     /// The order of the sorting.
     pub order: SortingOrder,
@@ -87,6 +183,8 @@ pub struct Sorting {
 
 /// The search filter for the query results.
 #[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub enum SearchFilter {
     #[default]
     #[serde(rename = "no_filter")]
@@ -99,6 +197,11 @@ pub enum SearchFilter {
     /// The product id to filter the results for.
     #[serde(rename = "product_id")]
     ProductID(ProductID),
+
+    /// The store to filter the results for, i.e., only products that have last been seen at
+    /// the store are returned.
+    #[serde(rename = "store_id")]
+    Store(DBId),
 }
 
 impl SearchFilter {
@@ -110,10 +213,33 @@ impl SearchFilter {
             _ => None,
         }
     }
+
+    /// Replaces a GS1 Digital Link URI of a product id filter by the GTIN it encodes.
+    pub fn resolve_product_id(&mut self) {
+        if let SearchFilter::ProductID(product_id) = self {
+            *product_id = resolve_product_id(product_id);
+        }
+    }
+}
+
+/// An issue of the data quality of a product, e.g. for finding the products that need curation.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum ProductDataIssue {
+    /// The product has neither a preview nor a full image.
+    MissingImage,
+    /// At least one of the macro nutrients, i.e., protein, fat and carbohydrates, is unknown.
+    MissingMacroNutrients,
+    /// The producer of the product is unknown.
+    MissingProducer,
 }
 
 /// The query parameters for querying the products.
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct ProductQuery {
     /// The offset of the query results.
     #[serde(default)]
@@ -126,24 +252,104 @@ pub struct ProductQuery {
     /// The sorting parameters for the query results (optional).
     #[serde(default)]
     pub sorting: Option<Sorting>,
+    /// The market to filter the results for (optional).
+    #[serde(default)]
+    pub market: Option<String>,
+    /// The producer to filter the results for (optional), matched case-insensitively against
+    /// the whole producer, e.g. "alpro" for the products of Alpro. A `*` matches any
+    /// characters, e.g. "alpro*" for all Alpro brands. Products without producer are excluded.
+    #[serde(default)]
+    pub producer: Option<String>,
+    /// The quantity type to filter the results for (optional), e.g. the beverages measured by
+    /// volume.
+    #[serde(default)]
+    pub quantity_type: Option<QuantityType>,
+    /// The dietary labels to filter the results for (optional). Only the labels that are set
+    /// are checked, e.g. `vegan: true` returns the products labeled as vegan.
+    #[serde(default)]
+    pub labels: Option<DietaryLabels>,
+    /// The maximal NOVA group of the results (optional), e.g. 1 for only unprocessed or
+    /// minimally processed products. Products without NOVA group are excluded.
+    #[serde(default)]
+    pub max_nova_group: Option<i32>,
+    /// The minimal Eco-Score grade of the results (optional), e.g. B for the products graded
+    /// A or B. Products without Eco-Score are excluded.
+    #[serde(default)]
+    pub min_eco_score_grade: Option<EcoScoreGrade>,
+    /// The allergens the results must not contain, e.g. nuts and gluten. Products without
+    /// declared allergens are included.
+    #[serde(default)]
+    pub exclude_allergens: Vec<Allergen>,
+    /// The data quality issues the results must have (optional), e.g. `["missing_image"]` for
+    /// the products without any image. Products with any of the issues are returned.
+    #[serde(default)]
+    pub data_issues: Vec<ProductDataIssue>,
+    /// The fields to return for each product (optional), e.g. `["id", "name", "kcal"]` for a
+    /// list view. All fields are returned if not set. Only the product query endpoint of the
+    /// service projects the results, the backend always returns full products.
+    #[serde(default)]
+    pub fields: Option<Vec<ProductField>>,
+    /// The review status to filter the product requests for (optional), e.g. the rejected
+    /// requests. Ignored when querying products.
+    #[serde(default)]
+    pub request_status: Option<RequestStatus>,
+    /// The cursor of the page to return (optional), i.e., the `next_cursor` of the previous
+    /// page of a sorted product query. The products after the last one of the previous page
+    /// are returned and the offset is ignored, which avoids skipping all previous results on
+    /// large catalogs. Ignored when querying product requests.
+    #[serde(default)]
+    pub cursor: Option<String>,
 }
 
-pub trait DataBackend: Send + Sync + Sized {
-    /// Creates a new instance of the data backend.
-    ///
-    /// # Arguments
-    /// - `options` - The options for the data backend.
-    fn new(options: &Options) -> impl Future<Output = Result<Self>> + Send;
+/// The health of the connection to the database.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum HealthStatus {
+    /// The database answers queries.
+    Healthy,
+    /// The database is reachable, but requests are delayed, e.g. due to an exhausted
+    /// connection pool.
+    Degraded,
+    /// The database cannot be used, e.g. due to rejected credentials.
+    Unavailable,
+}
 
-    /// Reports a missing product and returns an internal id in the database.
-    ///
-    /// # Arguments
-    /// - `missing_product` - The missing product to report.
-    fn report_missing_product(
-        &self,
-        missing_product: MissingProduct,
-    ) -> impl Future<Output = Result<DBId>> + Send;
+/// The result of a health check of the data backend.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct HealthReport {
+    /// The health of the connection to the database.
+    pub status: HealthStatus,
+    /// The reason for a degraded or unavailable database, e.g. "connection pool exhausted".
+    pub details: Option<String>,
+    /// The time in milliseconds for acquiring a connection and executing the check.
+    pub latency_ms: u64,
+}
+
+impl HealthReport {
+    /// Returns true if the database answers queries, i.e., the status is not unavailable.
+    pub fn is_available(&self) -> bool {
+        self.status != HealthStatus::Unavailable
+    }
+}
+
+/// A page of products, each with its similarity to the search string if sorted by it, together
+/// with the total number of products matching the query.
+#[cfg(feature = "service")]
+pub type CountedProducts = (Vec<(ProductDescription, Option<f32>)>, u64);
 
+/// A page of product requests with their ids together with the total number of product
+/// requests matching the query.
+#[cfg(feature = "service")]
+pub type CountedProductRequests = (Vec<(DBId, ProductRequest)>, u64);
+
+/// The read operations of a data backend, e.g. for serving the queries from a read replica.
+/// Besides the queries, the operations for managing the connection are part of this trait.
+#[cfg(feature = "service")]
+pub trait ReadBackend: Send + Sync {
     /// Queries for missing products and returns the list of missing products.
     ///
     /// # Arguments
@@ -153,11 +359,29 @@ pub trait DataBackend: Send + Sync + Sized {
         query: &MissingProductQuery,
     ) -> impl Future<Output = Result<Vec<(DBId, MissingProduct)>>> + Send;
 
-    /// Deletes the reported missing product from the database.
+    /// Counts the missing products matching the given query, e.g. for showing the total before
+    /// fetching any page. The offset, the limit and the order of the query are ignored.
     ///
     /// # Arguments
-    /// - `id` - The internal id of the missing product
-    fn delete_reported_missing_product(&self, id: DBId) -> impl Future<Output = Result<()>> + Send;
+    /// - `query` - The query parameters for the missing products.
+    fn count_missing_products(
+        &self,
+        query: &MissingProductQuery,
+    ) -> impl Future<Output = Result<u64>> + Send;
+
+    /// Groups the reports of the missing products by the product id and returns the number of
+    /// reports together with the dates of the first and the last report. The missing products
+    /// are sorted by the number of reports in descending order, e.g. the first 50 are the most
+    /// requested ones, ties are broken by the product id.
+    ///
+    /// # Arguments
+    /// - `offset` - The offset of the results.
+    /// - `limit` - The maximal number of results.
+    fn summarize_missing_products(
+        &self,
+        offset: i32,
+        limit: i32,
+    ) -> impl Future<Output = Result<Vec<MissingProductSummary>>> + Send;
 
     /// Retrieves the details about the missing product with the given id.
     ///
@@ -168,14 +392,15 @@ pub trait DataBackend: Send + Sync + Sized {
         id: DBId,
     ) -> impl Future<Output = Result<Option<MissingProduct>>> + Send;
 
-    /// Requests a new product to be added to the database and returns the internal id.
+    /// Retrieves the photo of the reported missing product with the given id, e.g. of its
+    /// packaging or barcode. Returns `None` if the report does not exist or has no photo.
     ///
     /// # Arguments
-    /// - `requested_product` - The information about the product that is requested to be added.
-    fn request_new_product(
+    /// - `id` - The internal id of the missing product
+    fn get_missing_product_photo(
         &self,
-        requested_product: &ProductRequest,
-    ) -> impl Future<Output = Result<DBId>> + Send;
+        id: DBId,
+    ) -> impl Future<Output = Result<Option<ProductImage>>> + Send;
 
     /// Retrieves the details about the product request with the given id.
     /// Returns `None` if the product request does not exist.
@@ -199,24 +424,30 @@ pub trait DataBackend: Send + Sync + Sized {
         id: DBId,
     ) -> impl Future<Output = Result<Option<ProductImage>>> + Send;
 
-    /// Deletes the requested product from the database.
+    /// Retrieves the additional images of the product request with the given id in the order
+    /// they have been added. Returns `None` if the product request does not exist.
     ///
     /// # Arguments
-    /// - `id` - The internal id of the requested product
-    fn delete_requested_product(&self, id: DBId) -> impl Future<Output = Result<()>> + Send;
+    /// - `id` - The internal id of the requested product.
+    fn get_product_request_images(
+        &self,
+        id: DBId,
+    ) -> impl Future<Output = Result<Option<Vec<AdditionalImage>>>> + Send;
 
-    /// Adds a new product to the database and returns true on success and false if for example
-    /// the product already exists.
+    /// Searches the archived product requests.
     ///
     /// # Arguments
-    /// - `product_desc` - The description about the product to be added.
-    fn new_product(
+    /// - `query` - The query parameters for the archived product requests.
+    fn query_archived_product_requests(
         &self,
-        product_desc: &ProductDescription,
-    ) -> impl Future<Output = Result<bool>> + Send;
+        query: &ArchiveQuery,
+    ) -> impl Future<Output = Result<Vec<ArchivedProductRequest>>> + Send;
 
     /// Retrieves the details about the product with the given id.
     /// Returns `None` if the product does not exist.
+    /// GTINs are matched by their canonical GTIN-14, i.e., an UPC-A, an EAN-13 with leading
+    /// zero or an id with stripped leading zeros find the same product. An exact match of the
+    /// id is preferred.
     /// Note: The photo of the product is not included in the response.
     ///
     /// # Arguments
@@ -228,6 +459,17 @@ pub trait DataBackend: Send + Sync + Sized {
         with_preview: bool,
     ) -> impl Future<Output = Result<Option<ProductDescription>>> + Send;
 
+    /// Retrieves the details about the products with the given ids in a single query.
+    /// Unknown ids are skipped, i.e., the result may contain fewer products than ids.
+    /// Note: The photos of the products are not included in the response.
+    ///
+    /// # Arguments
+    /// - `ids` - The public ids of the products.
+    fn get_products(
+        &self,
+        ids: &[ProductID],
+    ) -> impl Future<Output = Result<Vec<ProductDescription>>> + Send;
+
     /// Retrieves the full product image related to the given product id.
     ///
     /// # Arguments
@@ -237,11 +479,22 @@ pub trait DataBackend: Send + Sync + Sized {
         id: &ProductID,
     ) -> impl Future<Output = Result<Option<ProductImage>>> + Send;
 
-    /// Deletes the product from the database.
+    /// Retrieves the additional images of the product with the given id in the order they have
+    /// been added. Returns `None` if the product does not exist.
     ///
     /// # Arguments
     /// - `id` - The public id of the product.
-    fn delete_product(&self, id: &ProductID) -> impl Future<Output = Result<()>> + Send;
+    fn get_product_images(
+        &self,
+        id: &ProductID,
+    ) -> impl Future<Output = Result<Option<Vec<AdditionalImage>>>> + Send;
+
+    /// Retrieves the recipe with the given id including its image.
+    /// Returns `None` if the recipe does not exist.
+    ///
+    /// # Arguments
+    /// - `id` - The internal id of the recipe.
+    fn get_recipe(&self, id: DBId) -> impl Future<Output = Result<Option<Recipe>>> + Send;
 
     /// Queries for product requests and returns the list of product requests.
     ///
@@ -264,4 +517,449 @@ pub trait DataBackend: Send + Sync + Sized {
         query: &ProductQuery,
         with_preview: bool,
     ) -> impl Future<Output = Result<Vec<ProductDescription>>> + Send;
+
+    /// Returns all products ordered by their id as a stream, e.g. for exporting the catalog.
+    /// The products are queried page by page without their images, i.e., the memory does not
+    /// grow with the size of the catalog. The stream ends after the first error.
+    fn export_products(&self) -> impl Stream<Item = Result<ProductDescription>> + Send + '_ {
+        stream::unfold(Some(0), move |offset| async move {
+            let offset = offset?;
+            let query = ProductQuery {
+                offset,
+                limit: EXPORT_PRODUCTS_PAGE_SIZE,
+                filter: SearchFilter::NoFilter,
+                sorting: Some(Sorting {
+                    order: SortingOrder::Ascending,
+                    field: SortingField::ProductID,
+                }),
+                market: None,
+                producer: None,
+                quantity_type: None,
+                labels: None,
+                max_nova_group: None,
+                min_eco_score_grade: None,
+                exclude_allergens: Vec::new(),
+                data_issues: Vec::new(),
+                fields: None,
+                request_status: None,
+                cursor: None,
+            };
+
+            match self.query_products(&query, false).await {
+                Ok(page) if page.is_empty() => None,
+                Ok(page) => {
+                    let next_offset = offset + page.len() as i32;
+                    let page: Vec<_> = page.into_iter().map(Ok).collect();
+                    Some((stream::iter(page), Some(next_offset)))
+                }
+                Err(err) => Some((stream::iter(vec![Err(err)]), None)),
+            }
+        })
+        .flatten()
+    }
+
+    /// Queries for products like [`ReadBackend::query_products`] and returns every product
+    /// together with its similarity to the search string of the query, from 0 to 1. Fails with
+    /// [`crate::Error::InvalidSortingError`] if the query has no search string.
+    ///
+    /// # Arguments
+    /// - `query` - The query parameters for the products.
+    /// - `with_preview` - Whether to include the preview photo of the product in the response.
+    fn query_scored_products(
+        &self,
+        query: &ProductQuery,
+        with_preview: bool,
+    ) -> impl Future<Output = Result<Vec<(ProductDescription, f32)>>> + Send;
+
+    /// Counts the products matching the filters of the given query, e.g. for showing the total
+    /// before fetching any page. The offset, the limit, the sorting and the selected fields of
+    /// the query are ignored.
+    ///
+    /// # Arguments
+    /// - `query` - The query parameters for the products.
+    fn count_products(&self, query: &ProductQuery) -> impl Future<Output = Result<u64>> + Send;
+
+    /// Counts the product requests matching the filters of the given query, see
+    /// [`ReadBackend::count_products`].
+    ///
+    /// # Arguments
+    /// - `query` - The query parameters for the product requests.
+    fn count_product_requests(
+        &self,
+        query: &ProductQuery,
+    ) -> impl Future<Output = Result<u64>> + Send;
+
+    /// Queries for products like [`ReadBackend::query_products`] and returns them together with
+    /// the total number of products matching the filters of the query, e.g. for rendering the
+    /// pagination. If sorted by the similarity, every product has its similarity to the search
+    /// string like in [`ReadBackend::query_scored_products`]. The default implementation counts
+    /// in a separate query, i.e., the total may disagree with the page under concurrent writes.
+    ///
+    /// # Arguments
+    /// - `query` - The query parameters for the products.
+    /// - `with_preview` - Whether to include the preview photo of the product in the response.
+    fn query_counted_products(
+        &self,
+        query: &ProductQuery,
+        with_preview: bool,
+    ) -> impl Future<Output = Result<CountedProducts>> + Send {
+        async move {
+            let scored = query
+                .sorting
+                .is_some_and(|sorting| sorting.field == SortingField::Similarity);
+            let products = if scored {
+                self.query_scored_products(query, with_preview)
+                    .await?
+                    .into_iter()
+                    .map(|(product, score)| (product, Some(score)))
+                    .collect()
+            } else {
+                self.query_products(query, with_preview)
+                    .await?
+                    .into_iter()
+                    .map(|product| (product, None))
+                    .collect()
+            };
+
+            let count = self.count_products(query).await?;
+            Ok((products, count))
+        }
+    }
+
+    /// Queries for product requests like [`ReadBackend::query_product_requests`] and returns
+    /// them together with the total number of product requests matching the filters of the
+    /// query, see [`ReadBackend::query_counted_products`].
+    ///
+    /// # Arguments
+    /// - `query` - The query parameters for the product requests.
+    /// - `with_preview` - Whether to include the preview photo of the product in the response.
+    fn query_counted_product_requests(
+        &self,
+        query: &ProductQuery,
+        with_preview: bool,
+    ) -> impl Future<Output = Result<CountedProductRequests>> + Send {
+        async move {
+            let requests = self.query_product_requests(query, with_preview).await?;
+            let count = self.count_product_requests(query).await?;
+            Ok((requests, count))
+        }
+    }
+
+    /// Searches the observed prices.
+    ///
+    /// # Arguments
+    /// - `query` - The query parameters for the observed prices.
+    fn query_prices(
+        &self,
+        query: &PriceQuery,
+    ) -> impl Future<Output = Result<Vec<(DBId, PriceObservation)>>> + Send;
+
+    /// Returns the cheapest price observed for the product in the given currency.
+    /// Returns `None` if no price has been observed.
+    ///
+    /// # Arguments
+    /// - `product_id` - The id of the product.
+    /// - `currency` - The ISO 4217 code of the currency.
+    fn cheapest_price(
+        &self,
+        product_id: &ProductID,
+        currency: &str,
+    ) -> impl Future<Output = Result<Option<PriceObservation>>> + Send;
+
+    /// Returns the store with the given id or `None` if it does not exist.
+    ///
+    /// # Arguments
+    /// - `id` - The id of the store.
+    fn get_store(&self, id: DBId) -> impl Future<Output = Result<Option<Store>>> + Send;
+
+    /// Queries the stores, e.g. the stores of a retail chain.
+    ///
+    /// # Arguments
+    /// - `query` - The query parameters for the stores.
+    fn query_stores(
+        &self,
+        query: &StoreQuery,
+    ) -> impl Future<Output = Result<Vec<(DBId, Store)>>> + Send;
+
+    /// Returns the last observed availability of the product in all stores ordered by the id
+    /// of the store.
+    ///
+    /// # Arguments
+    /// - `product_id` - The id of the product.
+    fn get_product_availability(
+        &self,
+        product_id: &ProductID,
+    ) -> impl Future<Output = Result<Vec<ProductAvailability>>> + Send;
+
+    /// Returns the storage used by all stored images, i.e., of the products, the product
+    /// requests and the recipes, grouped by their content type and ordered by it.
+    fn image_storage_usage(&self) -> impl Future<Output = Result<Vec<ImageStorageUsage>>> + Send;
+
+    /// Reconnects to the database using the given password, e.g. after the credentials have
+    /// been rotated.
+    ///
+    /// # Arguments
+    /// - `password` - The new password for connecting to the database.
+    fn reconnect(&self, password: Secret) -> impl Future<Output = Result<()>> + Send;
+
+    /// Checks the connection to the database by acquiring a connection and executing a trivial
+    /// query. Failures are reported as degraded or unavailable status instead of an error.
+    fn health_check(&self) -> impl Future<Output = HealthReport> + Send;
+
+    /// Executes a trivial query like `select 1`, e.g. for readiness probes. In contrast to
+    /// [`ReadBackend::health_check`], failures are returned as error.
+    fn ping(&self) -> impl Future<Output = Result<()>> + Send;
+
+    /// Records the current state of the backend in the metrics, e.g. the utilization of the
+    /// connection pool. Called before the metrics are rendered.
+    fn record_metrics(&self);
+}
+
+/// The write operations of a data backend, i.e., all operations that modify the data.
+#[cfg(feature = "service")]
+pub trait WriteBackend: Send + Sync {
+    /// Reports a missing product and returns an internal id in the database.
+    ///
+    /// # Arguments
+    /// - `missing_product` - The missing product to report.
+    fn report_missing_product(
+        &self,
+        missing_product: MissingProduct,
+    ) -> impl Future<Output = Result<DBId>> + Send;
+
+    /// Reports several missing products in a single transaction, e.g. of an offline scanning
+    /// session, and returns their internal ids in the order of the given reports. Either all or
+    /// none of the reports are stored.
+    ///
+    /// # Arguments
+    /// - `missing_products` - The missing products to report.
+    fn report_missing_products(
+        &self,
+        missing_products: Vec<MissingProduct>,
+    ) -> impl Future<Output = Result<Vec<DBId>>> + Send;
+
+    /// Deletes the reported missing product from the database.
+    ///
+    /// # Arguments
+    /// - `id` - The internal id of the missing product
+    fn delete_reported_missing_product(&self, id: DBId) -> impl Future<Output = Result<()>> + Send;
+
+    /// Sets the photo of the reported missing product, replacing an existing photo. Returns
+    /// false if the report does not exist.
+    ///
+    /// # Arguments
+    /// - `id` - The internal id of the missing product
+    /// - `photo` - The photo of the missing product, e.g. of its packaging or barcode.
+    fn set_missing_product_photo(
+        &self,
+        id: DBId,
+        photo: &ProductImage,
+    ) -> impl Future<Output = Result<bool>> + Send;
+
+    /// Requests a new product to be added to the database and returns the internal id.
+    ///
+    /// # Arguments
+    /// - `requested_product` - The information about the product that is requested to be added.
+    fn request_new_product(
+        &self,
+        requested_product: &ProductRequest,
+    ) -> impl Future<Output = Result<DBId>> + Send;
+
+    /// Deletes the requested product from the database.
+    ///
+    /// # Arguments
+    /// - `id` - The internal id of the requested product
+    fn delete_requested_product(&self, id: DBId) -> impl Future<Output = Result<()>> + Send;
+
+    /// Moves the processed product request into the archive, i.e., the request is deleted and
+    /// a summary is kept together with its original id and outcome. Returns false if the
+    /// product request does not exist.
+    ///
+    /// # Arguments
+    /// - `id` - The internal id of the requested product
+    /// - `outcome` - The outcome of the product request
+    fn archive_product_request(
+        &self,
+        id: DBId,
+        outcome: RequestOutcome,
+    ) -> impl Future<Output = Result<bool>> + Send;
+
+    /// Moves all product requests whose product has been added to the database into the
+    /// archive as approved and returns the number of archived requests.
+    fn archive_approved_product_requests(&self) -> impl Future<Output = Result<u64>> + Send;
+
+    /// Marks the product request as rejected together with the comment of the reviewer, e.g.
+    /// why it has been declined. The request is kept and can still be approved or archived.
+    /// Returns false if the product request does not exist.
+    ///
+    /// # Arguments
+    /// - `id` - The internal id of the requested product
+    /// - `comment` - The comment of the reviewer, if any
+    fn reject_product_request(
+        &self,
+        id: DBId,
+        comment: Option<&str>,
+    ) -> impl Future<Output = Result<bool>> + Send;
+
+    /// Adds the requested product to the products and moves the request into the archive as
+    /// approved in a single transaction, i.e., the description and the images of the request
+    /// are kept and the original date of the request is preserved in the archive. Returns the
+    /// id of the added product or None if the product request does not exist. Fails with
+    /// [`Error::ProductAlreadyExists`] if a product with the same id already exists.
+    ///
+    /// # Arguments
+    /// - `id` - The internal id of the requested product
+    fn approve_product_request(
+        &self,
+        id: DBId,
+    ) -> impl Future<Output = Result<Option<ProductID>>> + Send;
+
+    /// Adds a new product to the database and returns true on success and false if for example
+    /// the product already exists.
+    ///
+    /// # Arguments
+    /// - `product_desc` - The description about the product to be added.
+    fn new_product(
+        &self,
+        product_desc: &ProductDescription,
+    ) -> impl Future<Output = Result<bool>> + Send;
+
+    /// Adds the given products to the database in a single transaction, e.g. when importing a
+    /// catalog. Products that already exist are skipped. Returns the number of added products.
+    ///
+    /// # Arguments
+    /// - `products` - The descriptions of the products to be added.
+    fn new_products(
+        &self,
+        products: &[ProductDescription],
+    ) -> impl Future<Output = Result<u64>> + Send;
+
+    /// Appends the given additional images to the product request. Returns false if the product
+    /// request does not exist.
+    ///
+    /// # Arguments
+    /// - `id` - The internal id of the requested product.
+    /// - `images` - The images to append.
+    fn add_product_request_images(
+        &self,
+        id: DBId,
+        images: &[AdditionalImage],
+    ) -> impl Future<Output = Result<bool>> + Send;
+
+    /// Appends the given additional images to the product. Returns false if the product does
+    /// not exist.
+    ///
+    /// # Arguments
+    /// - `id` - The public id of the product.
+    /// - `images` - The images to append.
+    fn add_product_images(
+        &self,
+        id: &ProductID,
+        images: &[AdditionalImage],
+    ) -> impl Future<Output = Result<bool>> + Send;
+
+    /// Updates the description of the product with the id and the market of the given
+    /// description in place, e.g. to correct its name or nutrients. The market itself is not
+    /// changed. The preview and full image are only replaced if they are given, i.e., missing
+    /// images keep the stored ones. The additional images are kept. Returns false if the
+    /// product does not exist in the market.
+    ///
+    /// # Arguments
+    /// - `product_desc` - The new description of the product.
+    fn update_product(
+        &self,
+        product_desc: &ProductDescription,
+    ) -> impl Future<Output = Result<bool>> + Send;
+
+    /// Deletes the product from the database.
+    ///
+    /// # Arguments
+    /// - `id` - The public id of the product.
+    fn delete_product(&self, id: &ProductID) -> impl Future<Output = Result<()>> + Send;
+
+    /// Replaces the preview image of the product, e.g. by a preview regenerated from its full
+    /// image. Returns false if the product does not exist.
+    ///
+    /// # Arguments
+    /// - `id` - The public id of the product.
+    /// - `preview` - The new preview image.
+    fn update_product_preview(
+        &self,
+        id: &ProductID,
+        preview: &ProductImage,
+    ) -> impl Future<Output = Result<bool>> + Send;
+
+    /// Replaces the preview image of the product request. Returns false if the product request
+    /// does not exist.
+    ///
+    /// # Arguments
+    /// - `id` - The internal id of the requested product.
+    /// - `preview` - The new preview image.
+    fn update_product_request_preview(
+        &self,
+        id: DBId,
+        preview: &ProductImage,
+    ) -> impl Future<Output = Result<bool>> + Send;
+
+    /// Adds a new recipe to the database and returns its internal id.
+    ///
+    /// # Arguments
+    /// - `recipe` - The recipe to be added.
+    fn new_recipe(&self, recipe: &Recipe) -> impl Future<Output = Result<DBId>> + Send;
+
+    /// Replaces the recipe with the given id including its ingredients and image. Returns
+    /// false if the recipe does not exist.
+    ///
+    /// # Arguments
+    /// - `id` - The internal id of the recipe.
+    /// - `recipe` - The new content of the recipe.
+    fn update_recipe(&self, id: DBId, recipe: &Recipe)
+        -> impl Future<Output = Result<bool>> + Send;
+
+    /// Deletes the recipe from the database.
+    ///
+    /// # Arguments
+    /// - `id` - The internal id of the recipe.
+    fn delete_recipe(&self, id: DBId) -> impl Future<Output = Result<()>> + Send;
+
+    /// Stores a price of a product observed in a store and returns its internal id.
+    ///
+    /// # Arguments
+    /// - `price` - The observed price.
+    fn submit_price(&self, price: &PriceObservation) -> impl Future<Output = Result<DBId>> + Send;
+
+    /// Adds a new store and returns its id.
+    ///
+    /// # Arguments
+    /// - `store` - The store to add.
+    fn new_store(&self, store: &Store) -> impl Future<Output = Result<DBId>> + Send;
+
+    /// Deletes the store with the given id together with the availability of the products in
+    /// the store. The missing product reports referencing the store are kept without store.
+    ///
+    /// # Arguments
+    /// - `id` - The id of the store.
+    fn delete_store(&self, id: DBId) -> impl Future<Output = Result<()>> + Send;
+
+    /// Records if the product has been seen or has been missing at the store. Replaces the
+    /// previous observation of the product at the store.
+    /// Returns `false` if the store does not exist.
+    ///
+    /// # Arguments
+    /// - `availability` - The observed availability of the product.
+    fn report_availability(
+        &self,
+        availability: &ProductAvailability,
+    ) -> impl Future<Output = Result<bool>> + Send;
+}
+
+/// A data backend that supports both the read and the write operations and can be created
+/// from the options of the service.
+#[cfg(feature = "service")]
+pub trait DataBackend: ReadBackend + WriteBackend + Sized {
+    /// Creates a new instance of the data backend.
+    ///
+    /// # Arguments
+    /// - `options` - The options for the data backend.
+    fn new(options: &Options) -> impl Future<Output = Result<Self>> + Send;
 }
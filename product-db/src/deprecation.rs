@@ -0,0 +1,281 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{MatchedPath, Request, State},
+    http::{header, HeaderName, HeaderValue, Method},
+    middleware::Next,
+    response::Response,
+};
+use log::error;
+use metrics::counter;
+
+use crate::{metrics::DEPRECATED_REQUESTS, DeprecationOptions, Error, Result};
+
+/// The header announcing the deprecation of a resource as defined in RFC 9745.
+pub const DEPRECATION_HEADER: &str = "deprecation";
+
+/// The header announcing the removal of a resource as defined in RFC 8594.
+pub const SUNSET_HEADER: &str = "sunset";
+
+/// A deprecated route or parameter with the headers of its responses.
+#[derive(Debug, Clone)]
+struct Deprecation {
+    /// The route template including the prefix of the endpoint.
+    route: String,
+
+    /// The deprecated method of the route, all methods if undefined.
+    method: Option<Method>,
+
+    /// The deprecated query parameter, the whole route if undefined.
+    parameter: Option<String>,
+
+    /// The headers that are added to the responses.
+    headers: Vec<(HeaderName, HeaderValue)>,
+}
+
+impl Deprecation {
+    /// Creates the deprecation from its options.
+    ///
+    /// # Arguments
+    /// - `options` - The options of the deprecation.
+    /// - `prefix` - The prefix of the endpoint, if any.
+    fn from_options(options: &DeprecationOptions, prefix: Option<&str>) -> Result<Self> {
+        let method = options
+            .method
+            .as_deref()
+            .map(|method| {
+                Method::from_bytes(method.to_ascii_uppercase().as_bytes())
+                    .map_err(|e| Self::config_error(format!("Invalid method '{}': {}", method, e)))
+            })
+            .transpose()?;
+
+        let mut headers = vec![(
+            HeaderName::from_static(DEPRECATION_HEADER),
+            HeaderValue::try_from(format!("@{}", options.date.timestamp()))
+                .map_err(|e| Self::config_error(e.to_string()))?,
+        )];
+        if let Some(sunset) = options.sunset {
+            if sunset < options.date {
+                return Err(Self::config_error(format!(
+                    "The sunset of {} is before its deprecation",
+                    options.route
+                )));
+            }
+
+            let sunset = sunset.format("%a, %d %b %Y %H:%M:%S GMT").to_string();
+            headers.push((
+                HeaderName::from_static(SUNSET_HEADER),
+                HeaderValue::try_from(sunset).map_err(|e| Self::config_error(e.to_string()))?,
+            ));
+        }
+        if let Some(link) = options.link.as_deref() {
+            let link = format!("<{}>; rel=\"deprecation\"", link);
+            headers.push((
+                header::LINK,
+                HeaderValue::try_from(link).map_err(|e| {
+                    Self::config_error(format!("Invalid link of {}: {}", options.route, e))
+                })?,
+            ));
+        }
+
+        Ok(Self {
+            route: format!("{}{}", prefix.unwrap_or(""), options.route),
+            method,
+            parameter: options.parameter.clone(),
+            headers,
+        })
+    }
+
+    /// Returns true if the request with the given route, method and query is affected.
+    ///
+    /// # Arguments
+    /// - `route` - The route template matched by the request.
+    /// - `method` - The method of the request.
+    /// - `query` - The query string of the request, if any.
+    fn matches(&self, route: &str, method: &Method, query: Option<&str>) -> bool {
+        if self.route != route || self.method.as_ref().is_some_and(|m| m != method) {
+            return false;
+        }
+
+        match self.parameter.as_deref() {
+            Some(parameter) => query
+                .unwrap_or("")
+                .split('&')
+                .any(|pair| pair.split_once('=').map_or(pair, |(name, _)| name) == parameter),
+            None => true,
+        }
+    }
+
+    /// Logs and returns the configuration error with the given message.
+    ///
+    /// # Arguments
+    /// - `message` - The description of the invalid option.
+    fn config_error(message: String) -> Error {
+        error!("Invalid deprecation: {}", message);
+        Error::ConfigError(format!("Invalid deprecation: {}", message))
+    }
+}
+
+/// The deprecated routes and parameters of the endpoint.
+#[derive(Debug, Clone)]
+pub struct Deprecations {
+    deprecations: Vec<Deprecation>,
+}
+
+impl Deprecations {
+    /// Creates the deprecations from the options. The routes of the options are relative to
+    /// the prefix of the endpoint.
+    ///
+    /// # Arguments
+    /// - `options` - The configured deprecations.
+    /// - `prefix` - The prefix of the endpoint, if any.
+    pub fn from_options(options: &[DeprecationOptions], prefix: Option<&str>) -> Result<Self> {
+        let deprecations = options
+            .iter()
+            .map(|options| Deprecation::from_options(options, prefix))
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self { deprecations })
+    }
+}
+
+/// Middleware that adds the Deprecation, Sunset and Link headers to the responses of
+/// deprecated routes and parameters and counts their usage in the metrics. Must be applied to
+/// the routes, as the deprecations are identified by the matched route template.
+pub async fn deprecation_headers(
+    State(deprecations): State<Arc<Deprecations>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_string());
+    let method = request.method().clone();
+    let deprecation = route.and_then(|route| {
+        deprecations
+            .deprecations
+            .iter()
+            .find(|d| d.matches(&route, &method, request.uri().query()))
+    });
+
+    let Some(deprecation) = deprecation else {
+        return next.run(request).await;
+    };
+
+    counter!(
+        DEPRECATED_REQUESTS,
+        "method" => method.to_string(),
+        "route" => deprecation.route.clone(),
+        "parameter" => deprecation.parameter.clone().unwrap_or_default()
+    )
+    .increment(1);
+
+    let mut response = next.run(request).await;
+    for (name, value) in deprecation.headers.iter() {
+        response.headers_mut().append(name, value.clone());
+    }
+
+    response
+}
+
+#[cfg(test)]
+mod test {
+    use axum::{body::Body, middleware, routing::get, Router};
+    use chrono::{TimeZone, Utc};
+    use tower::ServiceExt;
+
+    use super::*;
+
+    fn options(route: &str) -> DeprecationOptions {
+        DeprecationOptions {
+            route: route.to_string(),
+            method: None,
+            parameter: None,
+            date: Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap(),
+            sunset: None,
+            link: None,
+        }
+    }
+
+    async fn response_headers(app: &Router, uri: &str) -> axum::http::HeaderMap {
+        let request = Request::builder().uri(uri).body(Body::empty()).unwrap();
+        app.clone()
+            .oneshot(request)
+            .await
+            .unwrap()
+            .headers()
+            .clone()
+    }
+
+    #[tokio::test]
+    async fn test_deprecation_headers() {
+        let deprecations = Deprecations::from_options(
+            &[
+                DeprecationOptions {
+                    sunset: Some(Utc.with_ymd_and_hms(2025, 7, 1, 12, 0, 0).unwrap()),
+                    link: Some("https://example.com/migration".to_string()),
+                    ..options("/v1/user/product/{id}")
+                },
+                DeprecationOptions {
+                    parameter: Some("with_preview".to_string()),
+                    ..options("/v1/user/product_request/{id}")
+                },
+            ],
+            Some("/api"),
+        )
+        .unwrap();
+
+        let app = Router::new()
+            .route("/api/v1/user/product/{id}", get(|| async { "product" }))
+            .route(
+                "/api/v1/user/product_request/{id}",
+                get(|| async { "request" }),
+            )
+            .layer(middleware::from_fn_with_state(
+                Arc::new(deprecations),
+                deprecation_headers,
+            ));
+
+        // the deprecated route is answered with all headers
+        let headers = response_headers(&app, "/api/v1/user/product/123").await;
+        assert_eq!(headers[DEPRECATION_HEADER], "@1735689600");
+        assert_eq!(headers[SUNSET_HEADER], "Tue, 01 Jul 2025 12:00:00 GMT");
+        assert_eq!(
+            headers[header::LINK],
+            "<https://example.com/migration>; rel=\"deprecation\""
+        );
+
+        // only the requests with the deprecated parameter are marked
+        let headers =
+            response_headers(&app, "/api/v1/user/product_request/1?with_preview=true").await;
+        assert_eq!(headers[DEPRECATION_HEADER], "@1735689600");
+        assert!(!headers.contains_key(SUNSET_HEADER));
+        let headers = response_headers(&app, "/api/v1/user/product_request/1").await;
+        assert!(!headers.contains_key(DEPRECATION_HEADER));
+    }
+
+    #[test]
+    fn test_invalid_deprecations() {
+        let invalid_method = DeprecationOptions {
+            method: Some("GET POST".to_string()),
+            ..options("/v1/user/product/{id}")
+        };
+        assert!(Deprecations::from_options(&[invalid_method], None).is_err());
+
+        let early_sunset = DeprecationOptions {
+            sunset: Some(Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap()),
+            ..options("/v1/user/product/{id}")
+        };
+        assert!(Deprecations::from_options(&[early_sunset], None).is_err());
+
+        let method = DeprecationOptions {
+            method: Some("post".to_string()),
+            ..options("/v1/user/product/query")
+        };
+        let deprecations = Deprecations::from_options(&[method], None).unwrap();
+        let deprecation = &deprecations.deprecations[0];
+        assert!(deprecation.matches("/v1/user/product/query", &Method::POST, None));
+        assert!(!deprecation.matches("/v1/user/product/query", &Method::GET, None));
+    }
+}
@@ -0,0 +1,915 @@
+use futures::future::BoxFuture;
+
+use crate::{
+    AdditionalImage, ArchiveQuery, ArchivedProductRequest, CountedProductRequests, CountedProducts,
+    DBId, DataBackend, HealthReport, ImageStorageUsage, MissingProduct, MissingProductQuery,
+    MissingProductSummary, Options, PostgresBackend, PriceObservation, PriceQuery,
+    ProductAvailability, ProductDescription, ProductID, ProductImage, ProductQuery, ProductRequest,
+    ReadBackend, Recipe, RequestOutcome, Result, Secret, Store, StoreQuery, WriteBackend,
+};
+
+/// Object-safe variant of the [`DataBackend`] trait using boxed futures.
+/// Every [`DataBackend`] implements this trait automatically, which allows using
+/// `dyn DynDataBackend` whenever the concrete backend is only known at runtime.
+///
+/// See [`ReadBackend`] and [`WriteBackend`] for the documentation of the individual operations.
+pub trait DynDataBackend: Send + Sync {
+    fn report_missing_product(
+        &self,
+        missing_product: MissingProduct,
+    ) -> BoxFuture<'_, Result<DBId>>;
+
+    fn report_missing_products(
+        &self,
+        missing_products: Vec<MissingProduct>,
+    ) -> BoxFuture<'_, Result<Vec<DBId>>>;
+
+    fn query_missing_products<'a>(
+        &'a self,
+        query: &'a MissingProductQuery,
+    ) -> BoxFuture<'a, Result<Vec<(DBId, MissingProduct)>>>;
+
+    fn count_missing_products<'a>(
+        &'a self,
+        query: &'a MissingProductQuery,
+    ) -> BoxFuture<'a, Result<u64>>;
+
+    fn summarize_missing_products(
+        &self,
+        offset: i32,
+        limit: i32,
+    ) -> BoxFuture<'_, Result<Vec<MissingProductSummary>>>;
+
+    fn delete_reported_missing_product(&self, id: DBId) -> BoxFuture<'_, Result<()>>;
+
+    fn get_missing_product(&self, id: DBId) -> BoxFuture<'_, Result<Option<MissingProduct>>>;
+
+    fn get_missing_product_photo(&self, id: DBId) -> BoxFuture<'_, Result<Option<ProductImage>>>;
+
+    fn set_missing_product_photo<'a>(
+        &'a self,
+        id: DBId,
+        photo: &'a ProductImage,
+    ) -> BoxFuture<'a, Result<bool>>;
+
+    fn request_new_product<'a>(
+        &'a self,
+        requested_product: &'a ProductRequest,
+    ) -> BoxFuture<'a, Result<DBId>>;
+
+    fn get_product_request(
+        &self,
+        id: DBId,
+        with_preview: bool,
+    ) -> BoxFuture<'_, Result<Option<ProductRequest>>>;
+
+    fn get_product_request_image(&self, id: DBId) -> BoxFuture<'_, Result<Option<ProductImage>>>;
+
+    fn get_product_request_images(
+        &self,
+        id: DBId,
+    ) -> BoxFuture<'_, Result<Option<Vec<AdditionalImage>>>>;
+
+    fn add_product_request_images<'a>(
+        &'a self,
+        id: DBId,
+        images: &'a [AdditionalImage],
+    ) -> BoxFuture<'a, Result<bool>>;
+
+    fn delete_requested_product(&self, id: DBId) -> BoxFuture<'_, Result<()>>;
+
+    fn archive_product_request(
+        &self,
+        id: DBId,
+        outcome: RequestOutcome,
+    ) -> BoxFuture<'_, Result<bool>>;
+
+    fn reject_product_request<'a>(
+        &'a self,
+        id: DBId,
+        comment: Option<&'a str>,
+    ) -> BoxFuture<'a, Result<bool>>;
+
+    fn archive_approved_product_requests(&self) -> BoxFuture<'_, Result<u64>>;
+
+    fn approve_product_request(&self, id: DBId) -> BoxFuture<'_, Result<Option<ProductID>>>;
+
+    fn update_product<'a>(
+        &'a self,
+        product_desc: &'a ProductDescription,
+    ) -> BoxFuture<'a, Result<bool>>;
+
+    fn query_archived_product_requests<'a>(
+        &'a self,
+        query: &'a ArchiveQuery,
+    ) -> BoxFuture<'a, Result<Vec<ArchivedProductRequest>>>;
+
+    fn new_product<'a>(
+        &'a self,
+        product_desc: &'a ProductDescription,
+    ) -> BoxFuture<'a, Result<bool>>;
+
+    fn new_products<'a>(&'a self, products: &'a [ProductDescription])
+        -> BoxFuture<'a, Result<u64>>;
+
+    fn get_product<'a>(
+        &'a self,
+        id: &'a ProductID,
+        with_preview: bool,
+    ) -> BoxFuture<'a, Result<Option<ProductDescription>>>;
+
+    fn get_products<'a>(
+        &'a self,
+        ids: &'a [ProductID],
+    ) -> BoxFuture<'a, Result<Vec<ProductDescription>>>;
+
+    fn get_product_image<'a>(
+        &'a self,
+        id: &'a ProductID,
+    ) -> BoxFuture<'a, Result<Option<ProductImage>>>;
+
+    fn get_product_images<'a>(
+        &'a self,
+        id: &'a ProductID,
+    ) -> BoxFuture<'a, Result<Option<Vec<AdditionalImage>>>>;
+
+    fn add_product_images<'a>(
+        &'a self,
+        id: &'a ProductID,
+        images: &'a [AdditionalImage],
+    ) -> BoxFuture<'a, Result<bool>>;
+
+    fn delete_product<'a>(&'a self, id: &'a ProductID) -> BoxFuture<'a, Result<()>>;
+
+    fn update_product_preview<'a>(
+        &'a self,
+        id: &'a ProductID,
+        preview: &'a ProductImage,
+    ) -> BoxFuture<'a, Result<bool>>;
+
+    fn update_product_request_preview<'a>(
+        &'a self,
+        id: DBId,
+        preview: &'a ProductImage,
+    ) -> BoxFuture<'a, Result<bool>>;
+
+    fn new_recipe<'a>(&'a self, recipe: &'a Recipe) -> BoxFuture<'a, Result<DBId>>;
+
+    fn get_recipe(&self, id: DBId) -> BoxFuture<'_, Result<Option<Recipe>>>;
+
+    fn update_recipe<'a>(&'a self, id: DBId, recipe: &'a Recipe) -> BoxFuture<'a, Result<bool>>;
+
+    fn delete_recipe(&self, id: DBId) -> BoxFuture<'_, Result<()>>;
+
+    fn query_product_requests<'a>(
+        &'a self,
+        query: &'a ProductQuery,
+        with_preview: bool,
+    ) -> BoxFuture<'a, Result<Vec<(DBId, ProductRequest)>>>;
+
+    fn query_products<'a>(
+        &'a self,
+        query: &'a ProductQuery,
+        with_preview: bool,
+    ) -> BoxFuture<'a, Result<Vec<ProductDescription>>>;
+
+    fn query_scored_products<'a>(
+        &'a self,
+        query: &'a ProductQuery,
+        with_preview: bool,
+    ) -> BoxFuture<'a, Result<Vec<(ProductDescription, f32)>>>;
+
+    fn count_products<'a>(&'a self, query: &'a ProductQuery) -> BoxFuture<'a, Result<u64>>;
+
+    fn count_product_requests<'a>(&'a self, query: &'a ProductQuery) -> BoxFuture<'a, Result<u64>>;
+
+    fn query_counted_products<'a>(
+        &'a self,
+        query: &'a ProductQuery,
+        with_preview: bool,
+    ) -> BoxFuture<'a, Result<CountedProducts>>;
+
+    fn query_counted_product_requests<'a>(
+        &'a self,
+        query: &'a ProductQuery,
+        with_preview: bool,
+    ) -> BoxFuture<'a, Result<CountedProductRequests>>;
+
+    fn submit_price<'a>(&'a self, price: &'a PriceObservation) -> BoxFuture<'a, Result<DBId>>;
+
+    fn query_prices<'a>(
+        &'a self,
+        query: &'a PriceQuery,
+    ) -> BoxFuture<'a, Result<Vec<(DBId, PriceObservation)>>>;
+
+    fn cheapest_price<'a>(
+        &'a self,
+        product_id: &'a ProductID,
+        currency: &'a str,
+    ) -> BoxFuture<'a, Result<Option<PriceObservation>>>;
+
+    fn new_store<'a>(&'a self, store: &'a Store) -> BoxFuture<'a, Result<DBId>>;
+
+    fn get_store(&self, id: DBId) -> BoxFuture<'_, Result<Option<Store>>>;
+
+    fn query_stores<'a>(
+        &'a self,
+        query: &'a StoreQuery,
+    ) -> BoxFuture<'a, Result<Vec<(DBId, Store)>>>;
+
+    fn delete_store(&self, id: DBId) -> BoxFuture<'_, Result<()>>;
+
+    fn report_availability<'a>(
+        &'a self,
+        availability: &'a ProductAvailability,
+    ) -> BoxFuture<'a, Result<bool>>;
+
+    fn get_product_availability<'a>(
+        &'a self,
+        product_id: &'a ProductID,
+    ) -> BoxFuture<'a, Result<Vec<ProductAvailability>>>;
+
+    fn image_storage_usage(&self) -> BoxFuture<'_, Result<Vec<ImageStorageUsage>>>;
+
+    fn reconnect(&self, password: Secret) -> BoxFuture<'_, Result<()>>;
+
+    fn health_check(&self) -> BoxFuture<'_, HealthReport>;
+
+    fn ping(&self) -> BoxFuture<'_, Result<()>>;
+
+    fn record_metrics(&self);
+}
+
+impl<B: DataBackend> DynDataBackend for B {
+    fn report_missing_product(
+        &self,
+        missing_product: MissingProduct,
+    ) -> BoxFuture<'_, Result<DBId>> {
+        Box::pin(WriteBackend::report_missing_product(self, missing_product))
+    }
+
+    fn report_missing_products(
+        &self,
+        missing_products: Vec<MissingProduct>,
+    ) -> BoxFuture<'_, Result<Vec<DBId>>> {
+        Box::pin(WriteBackend::report_missing_products(
+            self,
+            missing_products,
+        ))
+    }
+
+    fn query_missing_products<'a>(
+        &'a self,
+        query: &'a MissingProductQuery,
+    ) -> BoxFuture<'a, Result<Vec<(DBId, MissingProduct)>>> {
+        Box::pin(ReadBackend::query_missing_products(self, query))
+    }
+
+    fn count_missing_products<'a>(
+        &'a self,
+        query: &'a MissingProductQuery,
+    ) -> BoxFuture<'a, Result<u64>> {
+        Box::pin(ReadBackend::count_missing_products(self, query))
+    }
+
+    fn summarize_missing_products(
+        &self,
+        offset: i32,
+        limit: i32,
+    ) -> BoxFuture<'_, Result<Vec<MissingProductSummary>>> {
+        Box::pin(ReadBackend::summarize_missing_products(self, offset, limit))
+    }
+
+    fn delete_reported_missing_product(&self, id: DBId) -> BoxFuture<'_, Result<()>> {
+        Box::pin(WriteBackend::delete_reported_missing_product(self, id))
+    }
+
+    fn get_missing_product(&self, id: DBId) -> BoxFuture<'_, Result<Option<MissingProduct>>> {
+        Box::pin(ReadBackend::get_missing_product(self, id))
+    }
+
+    fn get_missing_product_photo(&self, id: DBId) -> BoxFuture<'_, Result<Option<ProductImage>>> {
+        Box::pin(ReadBackend::get_missing_product_photo(self, id))
+    }
+
+    fn set_missing_product_photo<'a>(
+        &'a self,
+        id: DBId,
+        photo: &'a ProductImage,
+    ) -> BoxFuture<'a, Result<bool>> {
+        Box::pin(WriteBackend::set_missing_product_photo(self, id, photo))
+    }
+
+    fn request_new_product<'a>(
+        &'a self,
+        requested_product: &'a ProductRequest,
+    ) -> BoxFuture<'a, Result<DBId>> {
+        Box::pin(WriteBackend::request_new_product(self, requested_product))
+    }
+
+    fn get_product_request(
+        &self,
+        id: DBId,
+        with_preview: bool,
+    ) -> BoxFuture<'_, Result<Option<ProductRequest>>> {
+        Box::pin(ReadBackend::get_product_request(self, id, with_preview))
+    }
+
+    fn get_product_request_image(&self, id: DBId) -> BoxFuture<'_, Result<Option<ProductImage>>> {
+        Box::pin(ReadBackend::get_product_request_image(self, id))
+    }
+
+    fn get_product_request_images(
+        &self,
+        id: DBId,
+    ) -> BoxFuture<'_, Result<Option<Vec<AdditionalImage>>>> {
+        Box::pin(ReadBackend::get_product_request_images(self, id))
+    }
+
+    fn add_product_request_images<'a>(
+        &'a self,
+        id: DBId,
+        images: &'a [AdditionalImage],
+    ) -> BoxFuture<'a, Result<bool>> {
+        Box::pin(WriteBackend::add_product_request_images(self, id, images))
+    }
+
+    fn delete_requested_product(&self, id: DBId) -> BoxFuture<'_, Result<()>> {
+        Box::pin(WriteBackend::delete_requested_product(self, id))
+    }
+
+    fn archive_product_request(
+        &self,
+        id: DBId,
+        outcome: RequestOutcome,
+    ) -> BoxFuture<'_, Result<bool>> {
+        Box::pin(WriteBackend::archive_product_request(self, id, outcome))
+    }
+
+    fn reject_product_request<'a>(
+        &'a self,
+        id: DBId,
+        comment: Option<&'a str>,
+    ) -> BoxFuture<'a, Result<bool>> {
+        Box::pin(WriteBackend::reject_product_request(self, id, comment))
+    }
+
+    fn archive_approved_product_requests(&self) -> BoxFuture<'_, Result<u64>> {
+        Box::pin(WriteBackend::archive_approved_product_requests(self))
+    }
+
+    fn approve_product_request(&self, id: DBId) -> BoxFuture<'_, Result<Option<ProductID>>> {
+        Box::pin(WriteBackend::approve_product_request(self, id))
+    }
+
+    fn update_product<'a>(
+        &'a self,
+        product_desc: &'a ProductDescription,
+    ) -> BoxFuture<'a, Result<bool>> {
+        Box::pin(WriteBackend::update_product(self, product_desc))
+    }
+
+    fn query_archived_product_requests<'a>(
+        &'a self,
+        query: &'a ArchiveQuery,
+    ) -> BoxFuture<'a, Result<Vec<ArchivedProductRequest>>> {
+        Box::pin(ReadBackend::query_archived_product_requests(self, query))
+    }
+
+    fn new_product<'a>(
+        &'a self,
+        product_desc: &'a ProductDescription,
+    ) -> BoxFuture<'a, Result<bool>> {
+        Box::pin(WriteBackend::new_product(self, product_desc))
+    }
+
+    fn new_products<'a>(
+        &'a self,
+        products: &'a [ProductDescription],
+    ) -> BoxFuture<'a, Result<u64>> {
+        Box::pin(WriteBackend::new_products(self, products))
+    }
+
+    fn get_product<'a>(
+        &'a self,
+        id: &'a ProductID,
+        with_preview: bool,
+    ) -> BoxFuture<'a, Result<Option<ProductDescription>>> {
+        Box::pin(ReadBackend::get_product(self, id, with_preview))
+    }
+
+    fn get_products<'a>(
+        &'a self,
+        ids: &'a [ProductID],
+    ) -> BoxFuture<'a, Result<Vec<ProductDescription>>> {
+        Box::pin(ReadBackend::get_products(self, ids))
+    }
+
+    fn get_product_image<'a>(
+        &'a self,
+        id: &'a ProductID,
+    ) -> BoxFuture<'a, Result<Option<ProductImage>>> {
+        Box::pin(ReadBackend::get_product_image(self, id))
+    }
+
+    fn get_product_images<'a>(
+        &'a self,
+        id: &'a ProductID,
+    ) -> BoxFuture<'a, Result<Option<Vec<AdditionalImage>>>> {
+        Box::pin(ReadBackend::get_product_images(self, id))
+    }
+
+    fn add_product_images<'a>(
+        &'a self,
+        id: &'a ProductID,
+        images: &'a [AdditionalImage],
+    ) -> BoxFuture<'a, Result<bool>> {
+        Box::pin(WriteBackend::add_product_images(self, id, images))
+    }
+
+    fn delete_product<'a>(&'a self, id: &'a ProductID) -> BoxFuture<'a, Result<()>> {
+        Box::pin(WriteBackend::delete_product(self, id))
+    }
+
+    fn update_product_preview<'a>(
+        &'a self,
+        id: &'a ProductID,
+        preview: &'a ProductImage,
+    ) -> BoxFuture<'a, Result<bool>> {
+        Box::pin(WriteBackend::update_product_preview(self, id, preview))
+    }
+
+    fn update_product_request_preview<'a>(
+        &'a self,
+        id: DBId,
+        preview: &'a ProductImage,
+    ) -> BoxFuture<'a, Result<bool>> {
+        Box::pin(WriteBackend::update_product_request_preview(
+            self, id, preview,
+        ))
+    }
+
+    fn new_recipe<'a>(&'a self, recipe: &'a Recipe) -> BoxFuture<'a, Result<DBId>> {
+        Box::pin(WriteBackend::new_recipe(self, recipe))
+    }
+
+    fn get_recipe(&self, id: DBId) -> BoxFuture<'_, Result<Option<Recipe>>> {
+        Box::pin(ReadBackend::get_recipe(self, id))
+    }
+
+    fn update_recipe<'a>(&'a self, id: DBId, recipe: &'a Recipe) -> BoxFuture<'a, Result<bool>> {
+        Box::pin(WriteBackend::update_recipe(self, id, recipe))
+    }
+
+    fn delete_recipe(&self, id: DBId) -> BoxFuture<'_, Result<()>> {
+        Box::pin(WriteBackend::delete_recipe(self, id))
+    }
+
+    fn query_product_requests<'a>(
+        &'a self,
+        query: &'a ProductQuery,
+        with_preview: bool,
+    ) -> BoxFuture<'a, Result<Vec<(DBId, ProductRequest)>>> {
+        Box::pin(ReadBackend::query_product_requests(
+            self,
+            query,
+            with_preview,
+        ))
+    }
+
+    fn query_products<'a>(
+        &'a self,
+        query: &'a ProductQuery,
+        with_preview: bool,
+    ) -> BoxFuture<'a, Result<Vec<ProductDescription>>> {
+        Box::pin(ReadBackend::query_products(self, query, with_preview))
+    }
+
+    fn query_scored_products<'a>(
+        &'a self,
+        query: &'a ProductQuery,
+        with_preview: bool,
+    ) -> BoxFuture<'a, Result<Vec<(ProductDescription, f32)>>> {
+        Box::pin(ReadBackend::query_scored_products(
+            self,
+            query,
+            with_preview,
+        ))
+    }
+
+    fn count_products<'a>(&'a self, query: &'a ProductQuery) -> BoxFuture<'a, Result<u64>> {
+        Box::pin(ReadBackend::count_products(self, query))
+    }
+
+    fn count_product_requests<'a>(&'a self, query: &'a ProductQuery) -> BoxFuture<'a, Result<u64>> {
+        Box::pin(ReadBackend::count_product_requests(self, query))
+    }
+
+    fn query_counted_products<'a>(
+        &'a self,
+        query: &'a ProductQuery,
+        with_preview: bool,
+    ) -> BoxFuture<'a, Result<CountedProducts>> {
+        Box::pin(ReadBackend::query_counted_products(
+            self,
+            query,
+            with_preview,
+        ))
+    }
+
+    fn query_counted_product_requests<'a>(
+        &'a self,
+        query: &'a ProductQuery,
+        with_preview: bool,
+    ) -> BoxFuture<'a, Result<CountedProductRequests>> {
+        Box::pin(ReadBackend::query_counted_product_requests(
+            self,
+            query,
+            with_preview,
+        ))
+    }
+
+    fn submit_price<'a>(&'a self, price: &'a PriceObservation) -> BoxFuture<'a, Result<DBId>> {
+        Box::pin(WriteBackend::submit_price(self, price))
+    }
+
+    fn query_prices<'a>(
+        &'a self,
+        query: &'a PriceQuery,
+    ) -> BoxFuture<'a, Result<Vec<(DBId, PriceObservation)>>> {
+        Box::pin(ReadBackend::query_prices(self, query))
+    }
+
+    fn cheapest_price<'a>(
+        &'a self,
+        product_id: &'a ProductID,
+        currency: &'a str,
+    ) -> BoxFuture<'a, Result<Option<PriceObservation>>> {
+        Box::pin(ReadBackend::cheapest_price(self, product_id, currency))
+    }
+
+    fn new_store<'a>(&'a self, store: &'a Store) -> BoxFuture<'a, Result<DBId>> {
+        Box::pin(WriteBackend::new_store(self, store))
+    }
+
+    fn get_store(&self, id: DBId) -> BoxFuture<'_, Result<Option<Store>>> {
+        Box::pin(ReadBackend::get_store(self, id))
+    }
+
+    fn query_stores<'a>(
+        &'a self,
+        query: &'a StoreQuery,
+    ) -> BoxFuture<'a, Result<Vec<(DBId, Store)>>> {
+        Box::pin(ReadBackend::query_stores(self, query))
+    }
+
+    fn delete_store(&self, id: DBId) -> BoxFuture<'_, Result<()>> {
+        Box::pin(WriteBackend::delete_store(self, id))
+    }
+
+    fn report_availability<'a>(
+        &'a self,
+        availability: &'a ProductAvailability,
+    ) -> BoxFuture<'a, Result<bool>> {
+        Box::pin(WriteBackend::report_availability(self, availability))
+    }
+
+    fn get_product_availability<'a>(
+        &'a self,
+        product_id: &'a ProductID,
+    ) -> BoxFuture<'a, Result<Vec<ProductAvailability>>> {
+        Box::pin(ReadBackend::get_product_availability(self, product_id))
+    }
+
+    fn image_storage_usage(&self) -> BoxFuture<'_, Result<Vec<ImageStorageUsage>>> {
+        Box::pin(ReadBackend::image_storage_usage(self))
+    }
+
+    fn reconnect(&self, password: Secret) -> BoxFuture<'_, Result<()>> {
+        Box::pin(ReadBackend::reconnect(self, password))
+    }
+
+    fn health_check(&self) -> BoxFuture<'_, HealthReport> {
+        Box::pin(ReadBackend::health_check(self))
+    }
+
+    fn ping(&self) -> BoxFuture<'_, Result<()>> {
+        Box::pin(ReadBackend::ping(self))
+    }
+
+    fn record_metrics(&self) {
+        ReadBackend::record_metrics(self)
+    }
+}
+
+/// A type-erased data backend wrapping any [`DynDataBackend`].
+/// Allows using the [`crate::Service`] with a backend that is chosen at runtime.
+pub struct BoxedDataBackend {
+    inner: Box<dyn DynDataBackend>,
+}
+
+impl BoxedDataBackend {
+    /// Creates a new boxed data backend from the given backend.
+    ///
+    /// # Arguments
+    /// * `backend` - The backend to wrap.
+    pub fn new<B: DynDataBackend + 'static>(backend: B) -> Self {
+        Self {
+            inner: Box::new(backend),
+        }
+    }
+}
+
+impl DataBackend for BoxedDataBackend {
+    async fn new(options: &Options) -> Result<Self> {
+        // Postgres is currently the only backend that can be configured
+        let backend = PostgresBackend::new(options.postgres.clone()).await?;
+        Ok(Self::new(backend))
+    }
+}
+
+impl ReadBackend for BoxedDataBackend {
+    async fn query_missing_products(
+        &self,
+        query: &MissingProductQuery,
+    ) -> Result<Vec<(DBId, MissingProduct)>> {
+        self.inner.query_missing_products(query).await
+    }
+
+    async fn count_missing_products(&self, query: &MissingProductQuery) -> Result<u64> {
+        self.inner.count_missing_products(query).await
+    }
+
+    async fn summarize_missing_products(
+        &self,
+        offset: i32,
+        limit: i32,
+    ) -> Result<Vec<MissingProductSummary>> {
+        self.inner.summarize_missing_products(offset, limit).await
+    }
+
+    async fn get_missing_product(&self, id: DBId) -> Result<Option<MissingProduct>> {
+        self.inner.get_missing_product(id).await
+    }
+
+    async fn get_missing_product_photo(&self, id: DBId) -> Result<Option<ProductImage>> {
+        self.inner.get_missing_product_photo(id).await
+    }
+
+    async fn get_product_request(
+        &self,
+        id: DBId,
+        with_preview: bool,
+    ) -> Result<Option<ProductRequest>> {
+        self.inner.get_product_request(id, with_preview).await
+    }
+
+    async fn get_product_request_image(&self, id: DBId) -> Result<Option<ProductImage>> {
+        self.inner.get_product_request_image(id).await
+    }
+
+    async fn get_product_request_images(&self, id: DBId) -> Result<Option<Vec<AdditionalImage>>> {
+        self.inner.get_product_request_images(id).await
+    }
+
+    async fn query_archived_product_requests(
+        &self,
+        query: &ArchiveQuery,
+    ) -> Result<Vec<ArchivedProductRequest>> {
+        self.inner.query_archived_product_requests(query).await
+    }
+
+    async fn get_product(
+        &self,
+        id: &ProductID,
+        with_preview: bool,
+    ) -> Result<Option<ProductDescription>> {
+        self.inner.get_product(id, with_preview).await
+    }
+
+    async fn get_products(&self, ids: &[ProductID]) -> Result<Vec<ProductDescription>> {
+        self.inner.get_products(ids).await
+    }
+
+    async fn get_product_image(&self, id: &ProductID) -> Result<Option<ProductImage>> {
+        self.inner.get_product_image(id).await
+    }
+
+    async fn get_product_images(&self, id: &ProductID) -> Result<Option<Vec<AdditionalImage>>> {
+        self.inner.get_product_images(id).await
+    }
+
+    async fn get_recipe(&self, id: DBId) -> Result<Option<Recipe>> {
+        self.inner.get_recipe(id).await
+    }
+
+    async fn query_product_requests(
+        &self,
+        query: &ProductQuery,
+        with_preview: bool,
+    ) -> Result<Vec<(DBId, ProductRequest)>> {
+        self.inner.query_product_requests(query, with_preview).await
+    }
+
+    async fn query_products(
+        &self,
+        query: &ProductQuery,
+        with_preview: bool,
+    ) -> Result<Vec<ProductDescription>> {
+        self.inner.query_products(query, with_preview).await
+    }
+
+    async fn query_scored_products(
+        &self,
+        query: &ProductQuery,
+        with_preview: bool,
+    ) -> Result<Vec<(ProductDescription, f32)>> {
+        self.inner.query_scored_products(query, with_preview).await
+    }
+
+    async fn count_products(&self, query: &ProductQuery) -> Result<u64> {
+        self.inner.count_products(query).await
+    }
+
+    async fn count_product_requests(&self, query: &ProductQuery) -> Result<u64> {
+        self.inner.count_product_requests(query).await
+    }
+
+    async fn query_counted_products(
+        &self,
+        query: &ProductQuery,
+        with_preview: bool,
+    ) -> Result<CountedProducts> {
+        self.inner.query_counted_products(query, with_preview).await
+    }
+
+    async fn query_counted_product_requests(
+        &self,
+        query: &ProductQuery,
+        with_preview: bool,
+    ) -> Result<CountedProductRequests> {
+        self.inner
+            .query_counted_product_requests(query, with_preview)
+            .await
+    }
+
+    async fn query_prices(&self, query: &PriceQuery) -> Result<Vec<(DBId, PriceObservation)>> {
+        self.inner.query_prices(query).await
+    }
+
+    async fn cheapest_price(
+        &self,
+        product_id: &ProductID,
+        currency: &str,
+    ) -> Result<Option<PriceObservation>> {
+        self.inner.cheapest_price(product_id, currency).await
+    }
+
+    async fn get_store(&self, id: DBId) -> Result<Option<Store>> {
+        self.inner.get_store(id).await
+    }
+
+    async fn query_stores(&self, query: &StoreQuery) -> Result<Vec<(DBId, Store)>> {
+        self.inner.query_stores(query).await
+    }
+
+    async fn get_product_availability(
+        &self,
+        product_id: &ProductID,
+    ) -> Result<Vec<ProductAvailability>> {
+        self.inner.get_product_availability(product_id).await
+    }
+
+    async fn image_storage_usage(&self) -> Result<Vec<ImageStorageUsage>> {
+        self.inner.image_storage_usage().await
+    }
+
+    async fn reconnect(&self, password: Secret) -> Result<()> {
+        self.inner.reconnect(password).await
+    }
+
+    async fn health_check(&self) -> HealthReport {
+        self.inner.health_check().await
+    }
+
+    async fn ping(&self) -> Result<()> {
+        self.inner.ping().await
+    }
+
+    fn record_metrics(&self) {
+        self.inner.record_metrics()
+    }
+}
+
+impl WriteBackend for BoxedDataBackend {
+    async fn report_missing_product(&self, missing_product: MissingProduct) -> Result<DBId> {
+        self.inner.report_missing_product(missing_product).await
+    }
+
+    async fn report_missing_products(
+        &self,
+        missing_products: Vec<MissingProduct>,
+    ) -> Result<Vec<DBId>> {
+        self.inner.report_missing_products(missing_products).await
+    }
+
+    async fn delete_reported_missing_product(&self, id: DBId) -> Result<()> {
+        self.inner.delete_reported_missing_product(id).await
+    }
+
+    async fn set_missing_product_photo(&self, id: DBId, photo: &ProductImage) -> Result<bool> {
+        self.inner.set_missing_product_photo(id, photo).await
+    }
+
+    async fn request_new_product(&self, requested_product: &ProductRequest) -> Result<DBId> {
+        self.inner.request_new_product(requested_product).await
+    }
+
+    async fn delete_requested_product(&self, id: DBId) -> Result<()> {
+        self.inner.delete_requested_product(id).await
+    }
+
+    async fn archive_product_request(&self, id: DBId, outcome: RequestOutcome) -> Result<bool> {
+        self.inner.archive_product_request(id, outcome).await
+    }
+
+    async fn reject_product_request(&self, id: DBId, comment: Option<&str>) -> Result<bool> {
+        self.inner.reject_product_request(id, comment).await
+    }
+
+    async fn archive_approved_product_requests(&self) -> Result<u64> {
+        self.inner.archive_approved_product_requests().await
+    }
+
+    async fn approve_product_request(&self, id: DBId) -> Result<Option<ProductID>> {
+        self.inner.approve_product_request(id).await
+    }
+
+    async fn update_product(&self, product_desc: &ProductDescription) -> Result<bool> {
+        self.inner.update_product(product_desc).await
+    }
+
+    async fn new_product(&self, product_desc: &ProductDescription) -> Result<bool> {
+        self.inner.new_product(product_desc).await
+    }
+
+    async fn new_products(&self, products: &[ProductDescription]) -> Result<u64> {
+        self.inner.new_products(products).await
+    }
+
+    async fn add_product_request_images(
+        &self,
+        id: DBId,
+        images: &[AdditionalImage],
+    ) -> Result<bool> {
+        self.inner.add_product_request_images(id, images).await
+    }
+
+    async fn add_product_images(&self, id: &ProductID, images: &[AdditionalImage]) -> Result<bool> {
+        self.inner.add_product_images(id, images).await
+    }
+
+    async fn delete_product(&self, id: &ProductID) -> Result<()> {
+        self.inner.delete_product(id).await
+    }
+
+    async fn update_product_preview(&self, id: &ProductID, preview: &ProductImage) -> Result<bool> {
+        self.inner.update_product_preview(id, preview).await
+    }
+
+    async fn update_product_request_preview(
+        &self,
+        id: DBId,
+        preview: &ProductImage,
+    ) -> Result<bool> {
+        self.inner.update_product_request_preview(id, preview).await
+    }
+
+    async fn new_recipe(&self, recipe: &Recipe) -> Result<DBId> {
+        self.inner.new_recipe(recipe).await
+    }
+
+    async fn update_recipe(&self, id: DBId, recipe: &Recipe) -> Result<bool> {
+        self.inner.update_recipe(id, recipe).await
+    }
+
+    async fn delete_recipe(&self, id: DBId) -> Result<()> {
+        self.inner.delete_recipe(id).await
+    }
+
+    async fn submit_price(&self, price: &PriceObservation) -> Result<DBId> {
+        self.inner.submit_price(price).await
+    }
+
+    async fn new_store(&self, store: &Store) -> Result<DBId> {
+        self.inner.new_store(store).await
+    }
+
+    async fn delete_store(&self, id: DBId) -> Result<()> {
+        self.inner.delete_store(id).await
+    }
+
+    async fn report_availability(&self, availability: &ProductAvailability) -> Result<bool> {
+        self.inner.report_availability(availability).await
+    }
+}
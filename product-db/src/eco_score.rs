@@ -0,0 +1,126 @@
+use std::fmt::Display;
+
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "service")]
+use crate::{Error, Result};
+
+/// The maximal length of the source of an Eco-Score.
+pub const MAX_ECO_SCORE_SOURCE_LENGTH: usize = 64;
+
+/// The grade of the Eco-Score from A (low environmental impact) to E (high environmental impact).
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+#[cfg_attr(
+    feature = "service",
+    derive(sqlx::Type),
+    sqlx(type_name = "EcoScoreGrade")
+)]
+pub enum EcoScoreGrade {
+    A,
+    B,
+    C,
+    D,
+    E,
+}
+
+impl Display for EcoScoreGrade {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EcoScoreGrade::A => write!(f, "A"),
+            EcoScoreGrade::B => write!(f, "B"),
+            EcoScoreGrade::C => write!(f, "C"),
+            EcoScoreGrade::D => write!(f, "D"),
+            EcoScoreGrade::E => write!(f, "E"),
+        }
+    }
+}
+
+/// The environmental impact of a product, e.g. for showing it next to the nutrients.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct EcoScore {
+    /// The grade of the Eco-Score.
+    pub grade: EcoScoreGrade,
+
+    /// The numeric score from 0 (high environmental impact) to 100 (low environmental impact).
+    #[serde(default)]
+    pub value: Option<i32>,
+
+    /// The source of the score, e.g. "Open Food Facts".
+    #[serde(default)]
+    pub source: Option<String>,
+}
+
+/// Checks that the value of the Eco-Score is between 0 and 100 and that its source has a
+/// valid length.
+///
+/// # Arguments
+/// - `eco_score` - The Eco-Score to check.
+#[cfg(feature = "service")]
+pub fn check_eco_score(eco_score: &EcoScore) -> Result<()> {
+    if let Some(value) = eco_score.value {
+        if !(0..=100).contains(&value) {
+            return Err(Error::InvalidEcoScore(format!(
+                "the value {} is not between 0 and 100",
+                value
+            )));
+        }
+    }
+
+    if let Some(source) = eco_score.source.as_ref() {
+        if source.trim().is_empty() || source.chars().count() > MAX_ECO_SCORE_SOURCE_LENGTH {
+            return Err(Error::InvalidEcoScore(format!(
+                "the source must have between 1 and {} characters",
+                MAX_ECO_SCORE_SOURCE_LENGTH
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(all(test, feature = "service"))]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_check_eco_score() {
+        let eco_score = EcoScore {
+            grade: EcoScoreGrade::B,
+            value: Some(72),
+            source: Some("Open Food Facts".to_string()),
+        };
+        assert!(check_eco_score(&eco_score).is_ok());
+
+        let invalid = [
+            EcoScore {
+                value: Some(101),
+                ..eco_score.clone()
+            },
+            EcoScore {
+                value: Some(-1),
+                ..eco_score.clone()
+            },
+            EcoScore {
+                source: Some(" ".to_string()),
+                ..eco_score.clone()
+            },
+            EcoScore {
+                source: Some("x".repeat(MAX_ECO_SCORE_SOURCE_LENGTH + 1)),
+                ..eco_score.clone()
+            },
+        ];
+        for eco_score in invalid {
+            assert!(matches!(
+                check_eco_score(&eco_score),
+                Err(Error::InvalidEcoScore(_))
+            ));
+        }
+
+        // the grades are ordered from the lowest to the highest environmental impact
+        assert!(EcoScoreGrade::A < EcoScoreGrade::E);
+    }
+}
@@ -1,7 +1,7 @@
 use serde_yaml::Error as YamlError;
 use thiserror::Error;
 
-use crate::SortingField;
+use crate::{ProductID, SortingField};
 
 #[derive(Error, Debug)]
 pub enum Error {
@@ -20,6 +20,71 @@ pub enum Error {
     #[error("Invalid sorting: {0} is not supported")]
     InvalidSortingError(SortingField),
 
+    #[error("Invalid reference amount: {0}, must be a positive amount in g or ml")]
+    InvalidReferenceAmount(f32),
+
+    #[error("Invalid portion: {0}")]
+    InvalidPortion(String),
+
+    #[error("Invalid recipe: {0}")]
+    InvalidRecipe(String),
+
+    #[error("Invalid price: {0}")]
+    InvalidPrice(String),
+
+    #[error("Price tracking is disabled, see price_tracking in the Postgres config")]
+    PriceTrackingDisabled,
+
+    #[error("Invalid store: {0}")]
+    InvalidStore(String),
+
+    #[error("Invalid market '{0}', expected an ISO 3166-1 alpha-2 country code")]
+    InvalidMarket(String),
+
+    #[error("Invalid packaging: {0}")]
+    InvalidPackaging(String),
+
+    #[error("Invalid NOVA group {0}, expected a group from 1 to 4")]
+    InvalidNovaGroup(i32),
+
+    #[error("Invalid Eco-Score: {0}")]
+    InvalidEcoScore(String),
+
+    #[error("Implausible nutrients: {0}")]
+    ImplausibleNutrients(String),
+
+    #[error("Invalid export: {0}")]
+    InvalidExport(String),
+
+    #[error("Invalid cursor: {0}")]
+    InvalidCursor(String),
+
+    #[error(
+        "The images of the product have {size} bytes, which exceeds the limit of {limit} bytes"
+    )]
+    ProductImagesTooLarge { size: u64, limit: u64 },
+
+    #[error("The image storage quota of {limit} bytes is exhausted, {used} bytes are in use")]
+    ImageQuotaExceeded { used: u64, limit: u64 },
+
+    #[error("The image of {size} bytes exceeds the limit of {limit} bytes")]
+    ImageTooLarge { size: u64, limit: u64 },
+
+    #[error("Unsupported image format '{0}', expected JPEG, PNG, WebP, GIF or AVIF")]
+    UnsupportedImageFormat(String),
+
+    #[error("The image data does not match its content type '{0}'")]
+    ImageContentTypeMismatch(String),
+
+    #[error("The product has {count} additional images, which exceeds the limit of {limit}")]
+    TooManyImages { count: usize, limit: usize },
+
+    #[error("Invalid image: {0}")]
+    InvalidImage(String),
+
+    #[error("The product {0} already exists")]
+    ProductAlreadyExists(ProductID),
+
     #[error("Network error: {0}")]
     NetworkError(#[from] tokio::io::Error),
 
@@ -29,9 +94,53 @@ pub enum Error {
     #[error("SQLx DB error: {0}")]
     DBError(#[from] Box<sqlx::Error>),
 
+    #[cfg(feature = "mongodb")]
+    #[error("MongoDB error: {0}")]
+    MongoDBError(#[from] Box<mongodb::error::Error>),
+
+    #[error("Incompatible database schema: database schema v{found}, code requires v{expected} - run migrations")]
+    IncompatibleSchemaVersion { found: i32, expected: i32 },
+
+    #[error("The data backend is read-only")]
+    ReadOnlyBackend,
+
+    #[error("Database unavailable: {0}")]
+    DatabaseUnavailable(String),
+
+    #[error("Secret provider error: {0}")]
+    SecretProviderError(String),
+
+    #[error("Image store error: {0}")]
+    ImageStoreError(String),
+
+    #[error("JWKS error: {0}")]
+    JwksError(String),
+
+    #[error("Invalid CSV: {0}")]
+    InvalidCsv(String),
+
     #[error("Internal error: {0}")]
     InternalError(String),
 }
 
+impl Error {
+    /// Returns true if the error has been caused by rejected database credentials, e.g., due
+    /// to a rotated password.
+    pub fn is_authentication_error(&self) -> bool {
+        match self {
+            // 28000: invalid_authorization_specification, 28P01: invalid_password
+            Error::DBError(err) => err
+                .as_database_error()
+                .and_then(|err| err.code())
+                .is_some_and(|code| code == "28000" || code == "28P01"),
+            #[cfg(feature = "mongodb")]
+            Error::MongoDBError(err) => {
+                matches!(*err.kind, mongodb::error::ErrorKind::Authentication { .. })
+            }
+            _ => false,
+        }
+    }
+}
+
 /// The result type used in this crate.
 pub type Result<T> = std::result::Result<T, Error>;
@@ -0,0 +1,593 @@
+use std::borrow::Cow;
+
+use chrono::{DateTime, Datelike, SecondsFormat, Timelike, Utc};
+use rust_xlsxwriter::{Format, Workbook};
+use serde_json::Value;
+
+use crate::{
+    project_product, DBId, Error, MissingProduct, ProductDescription, ProductField, Result,
+};
+
+/// The content type of the CSV exports.
+pub const CSV_CONTENT_TYPE: &str = "text/csv; charset=utf-8";
+
+/// The content type of the XLSX exports.
+pub const XLSX_CONTENT_TYPE: &str =
+    "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet";
+
+/// The content type of the ZIP archive exports.
+pub const ZIP_CONTENT_TYPE: &str = "application/zip";
+
+/// The columns of the missing products export.
+pub const MISSING_PRODUCTS_COLUMNS: [&str; 4] = ["id", "product_id", "date", "store_id"];
+
+/// The maximum number of rows of the CSV and XLSX exports, without the header line. Larger
+/// exports are rejected and must be narrowed by the query.
+pub const MAX_EXPORT_ROWS: u64 = 100_000;
+
+/// The maximum number of rows of a worksheet, including the header line.
+const XLSX_MAX_ROWS: usize = 1_048_576;
+
+/// The maximum width of the columns of a worksheet in characters.
+const XLSX_MAX_COLUMN_WIDTH: usize = 80;
+
+/// The signatures of the records of a ZIP archive.
+const ZIP_LOCAL_FILE_SIGNATURE: u32 = 0x04034b50;
+const ZIP_CENTRAL_DIRECTORY_SIGNATURE: u32 = 0x02014b50;
+const ZIP_END_OF_CENTRAL_DIRECTORY_SIGNATURE: u32 = 0x06054b50;
+
+/// The version 2.0 of the ZIP specification, which is sufficient for stored files.
+const ZIP_VERSION: u16 = 20;
+
+/// The flag of the ZIP entries for UTF-8 encoded file names.
+const ZIP_UTF8_FLAG: u16 = 0x0800;
+
+/// Returns the row of a missing product, i.e., the id, the product id, the reported date in
+/// RFC 3339 and the store id, which is null if unknown.
+///
+/// # Arguments
+/// - `id` - The id of the missing product.
+/// - `missing_product` - The missing product.
+pub fn missing_product_row(id: DBId, missing_product: &MissingProduct) -> Vec<Value> {
+    vec![
+        Value::from(id),
+        Value::from(missing_product.product_id.as_str()),
+        Value::from(
+            missing_product
+                .date
+                .to_rfc3339_opts(SecondsFormat::Secs, true),
+        ),
+        Value::from(missing_product.store_id),
+    ]
+}
+
+/// Returns the fields of the products that are exported for the selected fields of a query.
+/// Without selection, all fields are exported. The nutrient block is split into the individual
+/// nutrients and the preview image is dropped, as images do not fit into a cell.
+///
+/// # Arguments
+/// - `fields` - The selected fields of the query, if any.
+pub fn export_fields(fields: Option<&[ProductField]>) -> Vec<ProductField> {
+    let fields = fields.unwrap_or(&ProductField::ALL);
+
+    let mut export_fields: Vec<ProductField> = Vec::new();
+    for field in fields {
+        let expanded: &[ProductField] = match field {
+            ProductField::Preview => &[],
+            // the individual nutrients, i.e., kcal to zinc
+            ProductField::Nutrients => &ProductField::ALL[11..25],
+            field => std::slice::from_ref(field),
+        };
+
+        for field in expanded {
+            if !export_fields.contains(field) {
+                export_fields.push(*field);
+            }
+        }
+    }
+
+    export_fields
+}
+
+/// Returns the row of a product with the values of the given fields in the same order.
+///
+/// # Arguments
+/// - `product` - The product.
+/// - `fields` - The exported fields.
+pub fn product_row(product: &ProductDescription, fields: &[ProductField]) -> Vec<Value> {
+    let mut projection = project_product(product, fields);
+
+    fields
+        .iter()
+        .map(|field| projection.remove(field.name()).unwrap_or_default())
+        .collect()
+}
+
+/// Returns the text of a cell, e.g. "vegan: true, organic: false" for an object. Null values
+/// are empty.
+///
+/// # Arguments
+/// - `value` - The value of the cell.
+fn cell_text(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        Value::Bool(_) | Value::Number(_) => value.to_string(),
+        Value::Array(values) => values.iter().map(cell_text).collect::<Vec<_>>().join(", "),
+        Value::Object(values) => values
+            .iter()
+            .filter(|(_, value)| !value.is_null())
+            .map(|(key, value)| format!("{}: {}", key, cell_text(value)))
+            .collect::<Vec<_>>()
+            .join(", "),
+    }
+}
+
+/// Returns the value as a field of a CSV line. Fields with separators, quotes or line breaks
+/// are quoted. Values starting like a formula, e.g. "=1+1", are prefixed with an apostrophe,
+/// as spreadsheet applications would evaluate them otherwise.
+///
+/// # Arguments
+/// - `value` - The value of the field.
+fn csv_field(value: &str) -> Cow<'_, str> {
+    let value = if value.starts_with(['=', '+', '-', '@', '\t', '\r']) {
+        Cow::Owned(format!("'{}", value))
+    } else {
+        Cow::Borrowed(value)
+    };
+
+    if value.contains([',', '"', '\n', '\r']) {
+        Cow::Owned(format!("\"{}\"", value.replace('"', "\"\"")))
+    } else {
+        value
+    }
+}
+
+/// Returns the values as a line of a CSV document including the line break, see RFC 4180.
+///
+/// # Arguments
+/// - `row` - The values of the line.
+pub fn csv_line(row: &[Value]) -> String {
+    let fields: Vec<String> = row
+        .iter()
+        .map(|value| match value {
+            // negative numbers are no formulas
+            Value::Number(n) => n.to_string(),
+            value => csv_field(&cell_text(value)).into_owned(),
+        })
+        .collect();
+
+    let mut line = fields.join(",");
+    line.push_str("\r\n");
+    line
+}
+
+/// Returns the header line of a CSV document including the line break.
+///
+/// # Arguments
+/// - `header` - The names of the columns.
+pub fn csv_header(header: &[&str]) -> String {
+    let mut line = header.join(",");
+    line.push_str("\r\n");
+    line
+}
+
+/// Returns the rows as CSV with a header line, see RFC 4180.
+///
+/// # Arguments
+/// - `header` - The names of the columns.
+/// - `rows` - The values of the rows.
+pub fn csv_document(header: &[&str], rows: &[Vec<Value>]) -> String {
+    let mut out = csv_header(header);
+
+    for row in rows {
+        out.push_str(&csv_line(row));
+    }
+
+    out
+}
+
+/// Writes rows into a XLSX workbook with a single worksheet. The header line is bold and
+/// frozen, the numbers and booleans are stored as such and all other values as text, i.e.,
+/// they are never evaluated as formulas. The rows are flushed to a temporary file once
+/// written, i.e., the memory does not grow with the number of rows.
+pub struct XlsxWriter {
+    workbook: Workbook,
+    num_rows: u32,
+    column_widths: Vec<usize>,
+}
+
+impl XlsxWriter {
+    /// Creates the workbook and writes the header line.
+    ///
+    /// # Arguments
+    /// - `sheet_name` - The name of the worksheet.
+    /// - `header` - The names of the columns.
+    pub fn new(sheet_name: &str, header: &[&str]) -> Result<Self> {
+        let mut workbook = Workbook::new();
+        let worksheet = workbook.add_worksheet_with_constant_memory();
+        worksheet.set_name(sheet_name).map_err(xlsx_error)?;
+
+        let bold = Format::new().set_bold();
+        for (col, name) in header.iter().enumerate() {
+            worksheet
+                .write_string_with_format(0, col as u16, *name, &bold)
+                .map_err(xlsx_error)?;
+        }
+        worksheet.set_freeze_panes(1, 0).map_err(xlsx_error)?;
+
+        Ok(Self {
+            workbook,
+            num_rows: 1,
+            column_widths: header.iter().map(|name| name.chars().count()).collect(),
+        })
+    }
+
+    /// Writes the values as the next row. Fails if the worksheet is full.
+    ///
+    /// # Arguments
+    /// - `values` - The values of the row.
+    pub fn write_row(&mut self, values: &[Value]) -> Result<()> {
+        if self.num_rows as usize >= XLSX_MAX_ROWS {
+            return Err(Error::InvalidExport(format!(
+                "a worksheet holds at most {} rows",
+                XLSX_MAX_ROWS - 1
+            )));
+        }
+
+        let row = self.num_rows;
+        let worksheet = self.workbook.worksheet_from_index(0).map_err(xlsx_error)?;
+        for (col, value) in values.iter().enumerate() {
+            let text = match value {
+                Value::Null => continue,
+                Value::Bool(b) => {
+                    worksheet
+                        .write_boolean(row, col as u16, *b)
+                        .map_err(xlsx_error)?;
+                    b.to_string()
+                }
+                Value::Number(n) => {
+                    worksheet
+                        .write_number(row, col as u16, n.as_f64().unwrap_or_default())
+                        .map_err(xlsx_error)?;
+                    n.to_string()
+                }
+                value => {
+                    let text = cell_text(value);
+                    worksheet
+                        .write_string(row, col as u16, &text)
+                        .map_err(xlsx_error)?;
+                    text
+                }
+            };
+
+            if col >= self.column_widths.len() {
+                self.column_widths.resize(col + 1, 0);
+            }
+            self.column_widths[col] = self.column_widths[col].max(text.chars().count());
+        }
+        self.num_rows += 1;
+
+        Ok(())
+    }
+
+    /// Returns the workbook, whose columns fit the widest values.
+    pub fn finish(mut self) -> Result<Vec<u8>> {
+        let worksheet = self.workbook.worksheet_from_index(0).map_err(xlsx_error)?;
+        for (col, width) in self.column_widths.iter().enumerate() {
+            let width = (*width).clamp(1, XLSX_MAX_COLUMN_WIDTH);
+            worksheet
+                .set_column_width(col as u16, width as f64 + 1.0)
+                .map_err(xlsx_error)?;
+        }
+
+        self.workbook.save_to_buffer().map_err(xlsx_error)
+    }
+}
+
+/// Returns the rows as XLSX workbook with a single worksheet, see [`XlsxWriter`].
+///
+/// # Arguments
+/// - `sheet_name` - The name of the worksheet.
+/// - `header` - The names of the columns.
+/// - `rows` - The values of the rows.
+pub fn xlsx_document(sheet_name: &str, header: &[&str], rows: &[Vec<Value>]) -> Result<Vec<u8>> {
+    let mut writer = XlsxWriter::new(sheet_name, header)?;
+    for row in rows {
+        writer.write_row(row)?;
+    }
+
+    writer.finish()
+}
+
+/// Converts an error of writing a workbook.
+///
+/// # Arguments
+/// - `e` - The error of the XLSX writer.
+fn xlsx_error(e: rust_xlsxwriter::XlsxError) -> Error {
+    Error::InternalError(format!("Failed to write the XLSX export: {}", e))
+}
+
+/// Returns the file extension for an image of the given content type, e.g. "jpg" for
+/// "image/jpeg". Unknown content types get the extension "bin".
+///
+/// # Arguments
+/// - `content_type` - The content type of the image.
+pub fn image_file_extension(content_type: &str) -> &'static str {
+    match content_type {
+        "image/jpeg" => "jpg",
+        "image/png" => "png",
+        "image/webp" => "webp",
+        "image/gif" => "gif",
+        "image/avif" => "avif",
+        _ => "bin",
+    }
+}
+
+/// An entry of the central directory of a ZIP archive.
+#[derive(Debug, Clone)]
+struct ZipEntry {
+    name: String,
+    crc32: u32,
+    size: u32,
+    time: u16,
+    date: u16,
+    offset: u32,
+}
+
+/// Writes a ZIP archive piece by piece, e.g. for streaming it to the client while the entries
+/// are loaded. The files are stored uncompressed, as the images are compressed already, and
+/// the archive is limited to 4 GiB and 65535 entries, i.e., no ZIP64 extensions are written.
+#[derive(Debug, Default)]
+pub struct ZipStreamWriter {
+    entries: Vec<ZipEntry>,
+    offset: u64,
+}
+
+impl ZipStreamWriter {
+    /// Creates a writer for an empty archive.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the bytes of a file in the archive, i.e., its local header and its data.
+    ///
+    /// # Arguments
+    /// - `name` - The path of the file within the archive, e.g. "12/request.json".
+    /// - `data` - The content of the file.
+    /// - `modified` - The modification date of the file.
+    pub fn file(&mut self, name: &str, data: &[u8], modified: DateTime<Utc>) -> Result<Vec<u8>> {
+        let too_large =
+            || Error::InvalidExport("the archive exceeds 4 GiB or 65535 files".to_string());
+        if self.entries.len() >= u16::MAX as usize {
+            return Err(too_large());
+        }
+
+        let size = u32::try_from(data.len()).map_err(|_| too_large())?;
+        let offset = u32::try_from(self.offset).map_err(|_| too_large())?;
+        let (time, date) = dos_date_time(modified);
+        let entry = ZipEntry {
+            name: name.to_string(),
+            crc32: crc32fast::hash(data),
+            size,
+            time,
+            date,
+            offset,
+        };
+
+        let mut out = Vec::with_capacity(30 + name.len() + data.len());
+        out.extend_from_slice(&ZIP_LOCAL_FILE_SIGNATURE.to_le_bytes());
+        out.extend_from_slice(&ZIP_VERSION.to_le_bytes());
+        write_entry_fields(&mut out, &entry);
+        out.extend_from_slice(&0u16.to_le_bytes()); // extra field length
+        out.extend_from_slice(name.as_bytes());
+        out.extend_from_slice(data);
+
+        self.offset += out.len() as u64;
+        self.entries.push(entry);
+
+        Ok(out)
+    }
+
+    /// Returns the bytes of the central directory, which completes the archive.
+    pub fn finish(self) -> Result<Vec<u8>> {
+        let too_large = || Error::InvalidExport("the archive exceeds 4 GiB".to_string());
+        let directory_offset = u32::try_from(self.offset).map_err(|_| too_large())?;
+
+        let mut out = Vec::new();
+        for entry in self.entries.iter() {
+            out.extend_from_slice(&ZIP_CENTRAL_DIRECTORY_SIGNATURE.to_le_bytes());
+            out.extend_from_slice(&ZIP_VERSION.to_le_bytes()); // version made by
+            out.extend_from_slice(&ZIP_VERSION.to_le_bytes()); // version needed to extract
+            write_entry_fields(&mut out, entry);
+            out.extend_from_slice(&0u16.to_le_bytes()); // extra field length
+            out.extend_from_slice(&0u16.to_le_bytes()); // file comment length
+            out.extend_from_slice(&0u16.to_le_bytes()); // disk number start
+            out.extend_from_slice(&0u16.to_le_bytes()); // internal file attributes
+            out.extend_from_slice(&0u32.to_le_bytes()); // external file attributes
+            out.extend_from_slice(&entry.offset.to_le_bytes());
+            out.extend_from_slice(entry.name.as_bytes());
+        }
+        let directory_size = u32::try_from(out.len()).map_err(|_| too_large())?;
+        directory_offset
+            .checked_add(directory_size)
+            .ok_or_else(too_large)?;
+
+        let entries = self.entries.len() as u16;
+        out.extend_from_slice(&ZIP_END_OF_CENTRAL_DIRECTORY_SIGNATURE.to_le_bytes());
+        out.extend_from_slice(&0u16.to_le_bytes()); // number of this disk
+        out.extend_from_slice(&0u16.to_le_bytes()); // disk with the central directory
+        out.extend_from_slice(&entries.to_le_bytes());
+        out.extend_from_slice(&entries.to_le_bytes());
+        out.extend_from_slice(&directory_size.to_le_bytes());
+        out.extend_from_slice(&directory_offset.to_le_bytes());
+        out.extend_from_slice(&0u16.to_le_bytes()); // comment length
+
+        Ok(out)
+    }
+}
+
+/// Writes the fields shared by the local header and the central directory, i.e., from the
+/// general purpose flags up to the file name length.
+///
+/// # Arguments
+/// - `out` - The buffer to write the fields to.
+/// - `entry` - The entry of the file.
+fn write_entry_fields(out: &mut Vec<u8>, entry: &ZipEntry) {
+    out.extend_from_slice(&ZIP_UTF8_FLAG.to_le_bytes());
+    out.extend_from_slice(&0u16.to_le_bytes()); // compression method "stored"
+    out.extend_from_slice(&entry.time.to_le_bytes());
+    out.extend_from_slice(&entry.date.to_le_bytes());
+    out.extend_from_slice(&entry.crc32.to_le_bytes());
+    out.extend_from_slice(&entry.size.to_le_bytes()); // compressed size
+    out.extend_from_slice(&entry.size.to_le_bytes()); // uncompressed size
+    out.extend_from_slice(&(entry.name.len() as u16).to_le_bytes());
+}
+
+/// Returns the time and the date in the MS-DOS format of ZIP archives, which starts in 1980
+/// and has a resolution of two seconds.
+///
+/// # Arguments
+/// - `date` - The date to convert.
+fn dos_date_time(date: DateTime<Utc>) -> (u16, u16) {
+    if date.year() < 1980 {
+        return (0, (1 << 5) | 1);
+    }
+
+    let time = (date.hour() << 11) | (date.minute() << 5) | (date.second() / 2);
+    let day = (((date.year() - 1980).min(127) as u32) << 9) | (date.month() << 5) | date.day();
+
+    (time as u16, day as u16)
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::{Cursor, Read};
+
+    use super::*;
+
+    #[test]
+    fn test_csv_document() {
+        let date = DateTime::parse_from_rfc3339("2024-03-01T10:00:00Z")
+            .unwrap()
+            .to_utc();
+        let missing_product = |product_id: &str, store_id: Option<DBId>| MissingProduct {
+            product_id: product_id.to_string(),
+            date,
+            store_id,
+        };
+
+        let rows = vec![
+            missing_product_row(1, &missing_product("4006381333931", None)),
+            missing_product_row(2, &missing_product("a,\"b\"", Some(7))),
+            missing_product_row(3, &missing_product("=HYPERLINK(\"x\")", None)),
+        ];
+        assert_eq!(
+            csv_document(&MISSING_PRODUCTS_COLUMNS, &rows),
+            "id,product_id,date,store_id\r\n\
+             1,4006381333931,2024-03-01T10:00:00Z,\r\n\
+             2,\"a,\"\"b\"\"\",2024-03-01T10:00:00Z,7\r\n\
+             3,\"'=HYPERLINK(\"\"x\"\")\",2024-03-01T10:00:00Z,\r\n"
+        );
+
+        // an empty export only has the header
+        assert_eq!(
+            csv_document(&MISSING_PRODUCTS_COLUMNS, &[]),
+            "id,product_id,date,store_id\r\n"
+        );
+    }
+
+    #[test]
+    fn test_product_rows() {
+        let product_data = include_str!("../../test_data/products.json");
+        let products: Vec<ProductDescription> = serde_json::from_str(product_data).unwrap();
+
+        // the nutrients are split and the preview is dropped
+        let fields = export_fields(Some(&[
+            ProductField::Id,
+            ProductField::Preview,
+            ProductField::Nutrients,
+            ProductField::Kcal,
+        ]));
+        assert_eq!(fields[0], ProductField::Id);
+        assert_eq!(fields[1], ProductField::Kcal);
+        assert_eq!(fields.len(), 15);
+        assert!(!fields.contains(&ProductField::Preview));
+        assert!(!fields.contains(&ProductField::Nutrients));
+        assert!(fields.contains(&ProductField::Zinc));
+
+        let all_fields = export_fields(None);
+        assert_eq!(all_fields.len(), ProductField::ALL.len() - 2);
+
+        let row = product_row(&products[0], &[ProductField::Name, ProductField::Id]);
+        assert_eq!(
+            row,
+            vec![
+                Value::from(products[0].info.name.as_str()),
+                Value::from(products[0].info.id.as_str())
+            ]
+        );
+
+        // the nested values are readable text
+        let value = serde_json::json!({ "gluten_free": false, "organic": null, "vegan": true });
+        assert_eq!(cell_text(&value), "gluten_free: false, vegan: true");
+        assert_eq!(
+            cell_text(&serde_json::json!(["milk", "nuts"])),
+            "milk, nuts"
+        );
+
+        // the workbook is a zip archive
+        let rows: Vec<Vec<Value>> = products
+            .iter()
+            .map(|p| product_row(p, &all_fields))
+            .collect();
+        let header: Vec<&str> = all_fields.iter().map(|field| field.name()).collect();
+        let xlsx = xlsx_document("Products", &header, &rows).unwrap();
+        assert!(xlsx.starts_with(b"PK"));
+    }
+
+    #[test]
+    fn test_zip_stream_writer() {
+        let date = DateTime::parse_from_rfc3339("2024-03-01T10:00:00Z")
+            .unwrap()
+            .to_utc();
+
+        let mut writer = ZipStreamWriter::new();
+        let mut archive = writer.file("1/request.json", b"{}", date).unwrap();
+        archive.extend(
+            writer
+                .file("1/preview.jpg", &[0xff, 0xd8, 0xff], date)
+                .unwrap(),
+        );
+        archive.extend(writer.finish().unwrap());
+
+        let mut zip = zip::ZipArchive::new(Cursor::new(archive)).unwrap();
+        assert_eq!(zip.len(), 2);
+
+        let mut content = Vec::new();
+        let mut file = zip.by_name("1/preview.jpg").unwrap();
+        file.read_to_end(&mut content).unwrap();
+        assert_eq!(content, vec![0xff, 0xd8, 0xff]);
+        let modified = file.last_modified().unwrap();
+        assert_eq!(
+            (modified.year(), modified.month(), modified.day()),
+            (2024, 3, 1)
+        );
+        assert_eq!(modified.hour(), 10);
+        drop(file);
+
+        let mut content = String::new();
+        zip.by_name("1/request.json")
+            .unwrap()
+            .read_to_string(&mut content)
+            .unwrap();
+        assert_eq!(content, "{}");
+
+        // an empty archive only has the end of the central directory
+        let empty = ZipStreamWriter::new().finish().unwrap();
+        assert_eq!(zip::ZipArchive::new(Cursor::new(empty)).unwrap().len(), 0);
+
+        assert_eq!(image_file_extension("image/jpeg"), "jpg");
+        assert_eq!(image_file_extension("application/octet-stream"), "bin");
+    }
+}
@@ -0,0 +1,518 @@
+use log::{debug, info};
+use png::{BitDepth, ColorType, Encoder};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::{
+    gtin::gs1_check_digit, DataBackend, Nutrients, ProductDescription, ProductID, ProductImage,
+    ProductInfo, QuantityType, Result as ProductDBResult, Weight,
+};
+
+/// The edge length of the generated preview images in pixels.
+const PREVIEW_SIZE: u32 = 64;
+
+/// The edge length of the generated full images in pixels.
+const FULL_IMAGE_SIZE: u32 = 512;
+
+/// The number of inserted products after which the progress is logged.
+const PROGRESS_INTERVAL: usize = 1000;
+
+/// The maximal sum of protein, fat and carbohydrates per 100g.
+const MAX_MACRONUTRIENTS: f32 = 95.0;
+
+/// The adjectives the product names are composed of.
+const ADJECTIVES: &[&str] = &[
+    "Organic",
+    "Classic",
+    "Wholegrain",
+    "Light",
+    "Crunchy",
+    "Fresh",
+    "Premium",
+    "Homestyle",
+    "Original",
+    "Rustic",
+    "Creamy",
+    "Golden",
+];
+
+/// The fictional producers of the generated products.
+const PRODUCERS: &[&str] = &[
+    "Green Valley",
+    "Alpine Farms",
+    "Sunrise Foods",
+    "Nordic Harvest",
+    "Miller & Sons",
+    "Golden Field",
+    "Bluewater",
+    "Maple Ridge",
+    "Riverside Dairy",
+    "Hillside Bakery",
+];
+
+/// A category of generated products with plausible ranges of the macronutrients per 100g.
+struct Category {
+    nouns: &'static [&'static str],
+    quantity_type: QuantityType,
+    portion: (f32, f32),
+    protein: (f32, f32),
+    fat: (f32, f32),
+    carbohydrates: (f32, f32),
+    /// The share of sugar in the carbohydrates.
+    sugar_share: (f32, f32),
+    salt: (f32, f32),
+    /// The base color of the generated images.
+    color: [u8; 3],
+}
+
+/// The categories of the generated products.
+const CATEGORIES: &[Category] = &[
+    Category {
+        nouns: &["Milk", "Yogurt", "Cheese", "Quark", "Butter", "Cream"],
+        quantity_type: QuantityType::Weight,
+        portion: (100.0, 500.0),
+        protein: (3.0, 25.0),
+        fat: (1.5, 35.0),
+        carbohydrates: (0.0, 12.0),
+        sugar_share: (0.6, 1.0),
+        salt: (0.1, 2.0),
+        color: [235, 235, 250],
+    },
+    Category {
+        nouns: &[
+            "Bread",
+            "Rolls",
+            "Toast",
+            "Croissants",
+            "Bagels",
+            "Crispbread",
+        ],
+        quantity_type: QuantityType::Weight,
+        portion: (250.0, 1000.0),
+        protein: (7.0, 13.0),
+        fat: (1.0, 20.0),
+        carbohydrates: (40.0, 70.0),
+        sugar_share: (0.02, 0.15),
+        salt: (0.8, 1.8),
+        color: [205, 160, 100],
+    },
+    Category {
+        nouns: &[
+            "Crisps",
+            "Cookies",
+            "Crackers",
+            "Chocolate",
+            "Pretzels",
+            "Nut Mix",
+        ],
+        quantity_type: QuantityType::Weight,
+        portion: (75.0, 300.0),
+        protein: (4.0, 20.0),
+        fat: (15.0, 50.0),
+        carbohydrates: (30.0, 65.0),
+        sugar_share: (0.05, 0.8),
+        salt: (0.1, 3.0),
+        color: [230, 90, 60],
+    },
+    Category {
+        nouns: &[
+            "Orange Juice",
+            "Apple Juice",
+            "Lemonade",
+            "Iced Tea",
+            "Cola",
+            "Smoothie",
+        ],
+        quantity_type: QuantityType::Volume,
+        portion: (250.0, 1500.0),
+        protein: (0.0, 1.0),
+        fat: (0.0, 0.5),
+        carbohydrates: (0.0, 14.0),
+        sugar_share: (0.9, 1.0),
+        salt: (0.0, 0.05),
+        color: [250, 180, 40],
+    },
+    Category {
+        nouns: &["Muesli", "Oats", "Cornflakes", "Granola", "Porridge"],
+        quantity_type: QuantityType::Weight,
+        portion: (375.0, 1000.0),
+        protein: (7.0, 14.0),
+        fat: (1.0, 18.0),
+        carbohydrates: (55.0, 80.0),
+        sugar_share: (0.01, 0.35),
+        salt: (0.0, 1.2),
+        color: [190, 170, 80],
+    },
+    Category {
+        nouns: &[
+            "Tomatoes",
+            "Chickpeas",
+            "Sweetcorn",
+            "Kidney Beans",
+            "Lentil Soup",
+        ],
+        quantity_type: QuantityType::Weight,
+        portion: (200.0, 800.0),
+        protein: (1.0, 9.0),
+        fat: (0.1, 3.0),
+        carbohydrates: (3.0, 20.0),
+        sugar_share: (0.05, 0.5),
+        salt: (0.2, 1.2),
+        color: [200, 40, 40],
+    },
+    Category {
+        nouns: &[
+            "Pizza",
+            "Spinach",
+            "Fish Fingers",
+            "Vegetable Mix",
+            "Lasagne",
+        ],
+        quantity_type: QuantityType::Weight,
+        portion: (300.0, 1000.0),
+        protein: (2.0, 15.0),
+        fat: (0.3, 14.0),
+        carbohydrates: (2.0, 30.0),
+        sugar_share: (0.05, 0.3),
+        salt: (0.1, 1.6),
+        color: [70, 140, 210],
+    },
+];
+
+/// Generates realistic fake products, e.g. for seeding large catalogs in performance tests.
+/// The products are composed of plausible names, producers and nutrients of common food
+/// categories. The ids are EAN-13 codes of the in-store number range (prefix 2), which do not
+/// collide with the codes of real products.
+pub struct ProductGenerator {
+    rng: StdRng,
+    with_images: bool,
+}
+
+impl ProductGenerator {
+    /// Creates a new generator producing the same products for the same seed.
+    ///
+    /// # Arguments
+    /// - `seed` - The seed of the random number generator.
+    pub fn new(seed: u64) -> Self {
+        Self {
+            rng: StdRng::seed_from_u64(seed),
+            with_images: false,
+        }
+    }
+
+    /// Creates a new generator seeded from the operating system.
+    pub fn from_os_rng() -> Self {
+        Self {
+            rng: StdRng::from_os_rng(),
+            with_images: false,
+        }
+    }
+
+    /// Sets whether the products get generated preview and full images.
+    ///
+    /// # Arguments
+    /// - `with_images` - True if the images are generated.
+    pub fn with_images(mut self, with_images: bool) -> Self {
+        self.with_images = with_images;
+        self
+    }
+
+    /// Generates the next product.
+    pub fn generate(&mut self) -> ProductDescription {
+        let category = &CATEGORIES[self.rng.random_range(0..CATEGORIES.len())];
+
+        let noun = category.nouns[self.rng.random_range(0..category.nouns.len())];
+        let adjective = ADJECTIVES[self.rng.random_range(0..ADJECTIVES.len())];
+
+        // a few products are sold without a known producer
+        let producer = if self.rng.random_bool(0.9) {
+            Some(PRODUCERS[self.rng.random_range(0..PRODUCERS.len())].to_string())
+        } else {
+            None
+        };
+
+        let volume_weight_ratio = match category.quantity_type {
+            QuantityType::Volume => Some(round(self.rng.random_range(1.0..1.06), 2)),
+            QuantityType::Weight => None,
+        };
+
+        // the portions are rounded to the typical package sizes
+        let portion = (self.sample(category.portion) / 25.0).round() * 25.0;
+
+        let info = ProductInfo {
+            id: self.generate_id(),
+            name: format!("{} {}", adjective, noun),
+            producer,
+            quantity_type: category.quantity_type,
+            portion,
+            volume_weight_ratio,
+            market: None,
+            labels: Default::default(),
+            nova_group: None,
+            allergens: Vec::new(),
+        };
+
+        let nutrients = self.generate_nutrients(category);
+
+        let (preview, full_image) = if self.with_images {
+            let color = self.jitter_color(category.color);
+            let pattern = self.rng.random_range(0..3);
+            (
+                Some(render_image(PREVIEW_SIZE, color, pattern)),
+                Some(render_image(FULL_IMAGE_SIZE, color, pattern)),
+            )
+        } else {
+            (None, None)
+        };
+
+        ProductDescription {
+            info,
+            preview,
+            full_image,
+            nutrients,
+            packaging: None,
+            eco_score: None,
+        }
+    }
+
+    /// Generates an EAN-13 code of the in-store number range.
+    fn generate_id(&mut self) -> ProductID {
+        let mut digits = vec![2];
+        digits.extend((0..11).map(|_| self.rng.random_range(0..10)));
+        digits.push(gs1_check_digit(&digits));
+
+        digits.iter().map(|digit| digit.to_string()).collect()
+    }
+
+    /// Generates the nutrients per 100g of a product of the given category. The energy is
+    /// derived from the macronutrients.
+    ///
+    /// # Arguments
+    /// - `category` - The category of the product.
+    fn generate_nutrients(&mut self, category: &Category) -> Nutrients {
+        let protein = self.sample(category.protein);
+        let fat = self.sample(category.fat);
+        let carbohydrates = self.sample(category.carbohydrates);
+
+        // the macronutrients of 100g must leave room for water, fiber and minerals
+        let scale = (MAX_MACRONUTRIENTS / (protein + fat + carbohydrates)).min(1.0);
+        let protein = round(protein * scale, 1);
+        let fat = round(fat * scale, 1);
+        let carbohydrates = round(carbohydrates * scale, 1);
+        let sugar = round(carbohydrates * self.sample(category.sugar_share), 1);
+        let salt = round(self.sample(category.salt), 2);
+
+        // Atwater factors, i.e., 4 kcal/g for protein and carbohydrates and 9 kcal/g for fat
+        let kcal = round(4.0 * protein + 9.0 * fat + 4.0 * carbohydrates, 0);
+
+        Nutrients {
+            kcal,
+            protein: Some(Weight::new_from_gram(protein)),
+            fat: Some(Weight::new_from_gram(fat)),
+            carbohydrates: Some(Weight::new_from_gram(carbohydrates)),
+            sugar: Some(Weight::new_from_gram(sugar)),
+            salt: Some(Weight::new_from_gram(salt)),
+            vitamin_a: self.optional_milligram(0.01, 0.9),
+            vitamin_c: self.optional_milligram(1.0, 60.0),
+            vitamin_d: self
+                .rng
+                .random_bool(0.2)
+                .then(|| Weight::new_from_microgram(round(self.rng.random_range(0.1..5.0), 1))),
+            iron: self.optional_milligram(0.1, 5.0),
+            calcium: self.optional_milligram(5.0, 800.0),
+            magnesium: self.optional_milligram(5.0, 150.0),
+            // salt consists of 40% sodium
+            sodium: Some(Weight::new_from_milligram(round(salt * 400.0, 0))),
+            zinc: self.optional_milligram(0.1, 4.0),
+        }
+    }
+
+    /// Returns a weight in the given range of milligrams for a fifth of the products.
+    ///
+    /// # Arguments
+    /// - `min` - The minimal weight in milligrams.
+    /// - `max` - The maximal weight in milligrams.
+    fn optional_milligram(&mut self, min: f32, max: f32) -> Option<Weight> {
+        self.rng
+            .random_bool(0.2)
+            .then(|| Weight::new_from_milligram(round(self.rng.random_range(min..max), 2)))
+    }
+
+    /// Samples a value of the given range, which is more likely in the middle of the range.
+    ///
+    /// # Arguments
+    /// - `range` - The minimal and the maximal value.
+    fn sample(&mut self, (min, max): (f32, f32)) -> f32 {
+        let t = (self.rng.random::<f32>() + self.rng.random::<f32>()) / 2.0;
+        min + t * (max - min)
+    }
+
+    /// Returns the given color with slightly varied channels.
+    ///
+    /// # Arguments
+    /// - `color` - The base color.
+    fn jitter_color(&mut self, color: [u8; 3]) -> [u8; 3] {
+        color.map(|channel| (channel as i16 + self.rng.random_range(-25..=25)).clamp(0, 255) as u8)
+    }
+}
+
+impl Iterator for ProductGenerator {
+    type Item = ProductDescription;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(self.generate())
+    }
+}
+
+/// Generates the given number of products and inserts them via the backend. Returns the
+/// number of inserted products, which is smaller than the count if generated ids already
+/// exist in the database.
+///
+/// # Arguments
+/// - `db` - The backend the products are inserted into.
+/// - `generator` - The generator of the products.
+/// - `count` - The number of products to generate.
+pub async fn generate_products<DB: DataBackend>(
+    db: &DB,
+    generator: &mut ProductGenerator,
+    count: usize,
+) -> ProductDBResult<usize> {
+    info!("Generating {} products...", count);
+
+    let mut inserted = 0;
+    for (index, product) in generator.take(count).enumerate() {
+        if db.new_product(&product).await? {
+            inserted += 1;
+        } else {
+            debug!(
+                "Skipped generated product with existing id {}",
+                product.info.id
+            );
+        }
+
+        if (index + 1) % PROGRESS_INTERVAL == 0 {
+            info!("Generated {} of {} products", index + 1, count);
+        }
+    }
+
+    info!("Generated {} products, {} inserted", count, inserted);
+
+    Ok(inserted)
+}
+
+/// Rounds the value to the given number of decimals.
+///
+/// # Arguments
+/// - `value` - The value to round.
+/// - `decimals` - The number of decimals.
+fn round(value: f32, decimals: i32) -> f32 {
+    let factor = 10f32.powi(decimals);
+    (value * factor).round() / factor
+}
+
+/// Renders a square PNG image of the given color with a simple pattern.
+///
+/// # Arguments
+/// - `size` - The edge length in pixels.
+/// - `color` - The color of the pattern.
+/// - `pattern` - The pattern, i.e., 0 for stripes, 1 for a circle and 2 for a frame.
+fn render_image(size: u32, color: [u8; 3], pattern: u32) -> ProductImage {
+    let background = color.map(|channel| channel / 2 + 127);
+
+    let mut pixels = Vec::with_capacity((size * size * 3) as usize);
+    for y in 0..size {
+        for x in 0..size {
+            let foreground = match pattern {
+                0 => ((x + y) / (size / 8).max(1)).is_multiple_of(2),
+                1 => {
+                    let (dx, dy) = (x as i64 - size as i64 / 2, y as i64 - size as i64 / 2);
+                    dx * dx + dy * dy < (size as i64 * size as i64) / 9
+                }
+                _ => {
+                    let border = size / 8;
+                    x < border || y < border || x >= size - border || y >= size - border
+                }
+            };
+
+            pixels.extend_from_slice(if foreground { &color } else { &background });
+        }
+    }
+
+    let mut data = Vec::new();
+    let mut encoder = Encoder::new(&mut data, size, size);
+    encoder.set_color(ColorType::Rgb);
+    encoder.set_depth(BitDepth::Eight);
+    encoder
+        .write_header()
+        .and_then(|mut writer| writer.write_image_data(&pixels))
+        .expect("encoding into memory does not fail");
+
+    ProductImage {
+        content_type: "image/png".to_string(),
+        data,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_generate_is_deterministic() {
+        let lhs: Vec<_> = ProductGenerator::new(7).take(10).collect();
+        let rhs: Vec<_> = ProductGenerator::new(7).take(10).collect();
+        assert_eq!(lhs, rhs);
+
+        let other: Vec<_> = ProductGenerator::new(8).take(10).collect();
+        assert_ne!(lhs, other);
+    }
+
+    #[test]
+    fn test_generated_products_are_plausible() {
+        for product in ProductGenerator::new(42).take(500) {
+            let info = &product.info;
+            assert_eq!(info.id.len(), 13);
+            assert!(info.id.starts_with('2'));
+
+            let digits: Vec<u8> = info.id.bytes().map(|b| b - b'0').collect();
+            assert_eq!(gs1_check_digit(&digits[..12]), digits[12]);
+
+            assert!(!info.name.is_empty());
+            assert!(info.portion > 0.0);
+            assert_eq!(
+                info.volume_weight_ratio.is_some(),
+                info.quantity_type == QuantityType::Volume
+            );
+
+            let nutrients = &product.nutrients;
+            let protein = nutrients.protein.unwrap().gram();
+            let fat = nutrients.fat.unwrap().gram();
+            let carbohydrates = nutrients.carbohydrates.unwrap().gram();
+            let sugar = nutrients.sugar.unwrap().gram();
+            assert!(protein + fat + carbohydrates <= 100.0);
+            assert!(sugar <= carbohydrates);
+            assert!(
+                (nutrients.kcal - (4.0 * protein + 9.0 * fat + 4.0 * carbohydrates)).abs() < 1.0
+            );
+
+            assert!(product.preview.is_none());
+            assert!(product.full_image.is_none());
+        }
+    }
+
+    #[test]
+    fn test_generated_images() {
+        let product = ProductGenerator::new(1).with_images(true).generate();
+
+        for (image, size) in [
+            (product.preview.unwrap(), PREVIEW_SIZE),
+            (product.full_image.unwrap(), FULL_IMAGE_SIZE),
+        ] {
+            assert_eq!(image.content_type, "image/png");
+
+            let decoder = png::Decoder::new(image.data.as_slice());
+            let reader = decoder.read_info().unwrap();
+            assert_eq!(reader.info().width, size);
+            assert_eq!(reader.info().height, size);
+        }
+    }
+}
@@ -0,0 +1,197 @@
+use crate::ProductID;
+
+/// The number of digits of a GTIN-14, the longest Global Trade Item Number.
+pub const GTIN_LENGTH: usize = 14;
+
+/// The path segments of a GS1 Digital Link URI that precede the GTIN, i.e., the application
+/// identifier 01 and its convenience alias.
+const DIGITAL_LINK_GTIN_KEYS: [&str; 2] = ["01", "gtin"];
+
+/// Returns the canonical GTIN-14 of the given product id, i.e., the digits zero-padded to 14
+/// digits, or `None` if the id is not a GTIN. This way an UPC-A (12 digits), an EAN-13 and
+/// an id whose leading zeros have been stripped, e.g. by a spreadsheet, refer to the same
+/// product.
+///
+/// # Arguments
+/// - `id` - The product id to normalize.
+pub fn normalize_gtin(id: &str) -> Option<String> {
+    let digits = id.trim();
+    if digits.is_empty()
+        || digits.len() > GTIN_LENGTH
+        || !digits.bytes().all(|c| c.is_ascii_digit())
+    {
+        return None;
+    }
+
+    Some(format!("{:0>width$}", digits, width = GTIN_LENGTH))
+}
+
+/// Returns the GTIN encoded in the given GS1 Digital Link URI, e.g.
+/// `https://id.gs1.org/01/09506000134352`, or `None` if the value is not a Digital Link with a
+/// valid GTIN. The domain and any path before the GTIN are arbitrary and the further
+/// application identifiers, e.g. the batch or the serial number, as well as the query are
+/// ignored. The GTIN-14 of the URI is shortened to the GTIN-8, UPC-A or EAN-13 it pads, as
+/// printed as barcode and stored as product id.
+///
+/// # Arguments
+/// - `uri` - The value to parse.
+pub fn parse_digital_link(uri: &str) -> Option<String> {
+    let uri = uri.trim();
+    let (scheme, rest) = uri.split_once("://")?;
+    if !scheme.eq_ignore_ascii_case("http") && !scheme.eq_ignore_ascii_case("https") {
+        return None;
+    }
+
+    let rest = rest.split(['?', '#']).next().unwrap_or_default();
+    let (_, path) = rest.split_once('/')?;
+    let mut segments = path.split('/');
+    segments.find(|segment| DIGITAL_LINK_GTIN_KEYS.contains(segment))?;
+    let gtin = normalize_gtin(segments.next()?)?;
+    if !has_valid_check_digit(&gtin) {
+        return None;
+    }
+
+    let length = if gtin.starts_with("000000") {
+        8
+    } else if gtin.starts_with("00") {
+        12
+    } else if gtin.starts_with('0') {
+        13
+    } else {
+        GTIN_LENGTH
+    };
+
+    Some(gtin[GTIN_LENGTH - length..].to_string())
+}
+
+/// Returns the product id referenced by the given value, i.e., the GTIN if the value is a GS1
+/// Digital Link URI, as encoded in the QR codes on newer packaging, and the unchanged value
+/// otherwise.
+///
+/// # Arguments
+/// - `id` - The product id or Digital Link URI to resolve.
+pub fn resolve_product_id(id: &str) -> ProductID {
+    parse_digital_link(id).unwrap_or_else(|| id.to_string())
+}
+
+/// Returns true if the last digit of the given GTIN-14 is its GS1 check digit.
+///
+/// # Arguments
+/// - `gtin` - The canonical GTIN-14 to check.
+fn has_valid_check_digit(gtin: &str) -> bool {
+    let digits: Vec<u8> = gtin.bytes().map(|c| c - b'0').collect();
+    let Some((check, digits)) = digits.split_last() else {
+        return false;
+    };
+
+    gs1_check_digit(digits) == *check
+}
+
+/// Returns the GS1 check digit of the given digits of a GTIN of any length, e.g. the first 12
+/// digits of an EAN-13 code.
+///
+/// # Arguments
+/// - `digits` - The digits of the code without the check digit.
+pub(crate) fn gs1_check_digit(digits: &[u8]) -> u8 {
+    // the digits are weighted alternately by 3 and 1, starting with 3 left of the check digit
+    let sum: u32 = digits
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(i, &d)| d as u32 * if i % 2 == 0 { 3 } else { 1 })
+        .sum();
+
+    ((10 - sum % 10) % 10) as u8
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_gs1_check_digit() {
+        // 4006381333931 is a valid EAN-13 code
+        assert_eq!(gs1_check_digit(&[4, 0, 0, 6, 3, 8, 1, 3, 3, 3, 9, 3]), 1);
+        assert_eq!(gs1_check_digit(&[0; 12]), 0);
+        // the GTIN-14 of the same product has the same check digit
+        assert_eq!(gs1_check_digit(&[0, 4, 0, 0, 6, 3, 8, 1, 3, 3, 3, 9, 3]), 1);
+    }
+
+    #[test]
+    fn test_normalize_gtin() {
+        // EAN-13, UPC-A with leading zero stripped and the GTIN-14 are the same product
+        let gtin = Some("04001724819806".to_string());
+        assert_eq!(normalize_gtin("4001724819806"), gtin);
+        assert_eq!(normalize_gtin("04001724819806"), gtin);
+        assert_eq!(normalize_gtin(" 4001724819806 "), gtin);
+
+        assert_eq!(
+            normalize_gtin("036000291452"),
+            Some("00036000291452".to_string())
+        );
+        assert_eq!(
+            normalize_gtin("36000291452"),
+            Some("00036000291452".to_string())
+        );
+        assert_eq!(
+            normalize_gtin("42094722"),
+            Some("00000042094722".to_string())
+        );
+
+        // no GTIN
+        assert_eq!(normalize_gtin(""), None);
+        assert_eq!(normalize_gtin("123456789012345"), None);
+        assert_eq!(normalize_gtin("76a85ac9-6f22-4ad8-88b6-6afe0c21371f"), None);
+    }
+
+    #[test]
+    fn test_parse_digital_link() {
+        assert_eq!(
+            parse_digital_link("https://id.gs1.org/01/09506000134352"),
+            Some("9506000134352".to_string())
+        );
+
+        // custom domain and path, further identifiers and query are ignored
+        assert_eq!(
+            parse_digital_link(
+                "https://example.com/products/01/09506000134352/10/ABC123/21/12345?17=261231"
+            ),
+            Some("9506000134352".to_string())
+        );
+        assert_eq!(
+            parse_digital_link("HTTP://id.gs1.org/gtin/00036000291452#label"),
+            Some("036000291452".to_string())
+        );
+        assert_eq!(
+            parse_digital_link("https://id.gs1.org/01/00000042094722"),
+            Some("42094722".to_string())
+        );
+        assert_eq!(
+            parse_digital_link("https://id.gs1.org/01/19506000134359"),
+            Some("19506000134359".to_string())
+        );
+
+        // no Digital Link or invalid GTIN
+        assert_eq!(parse_digital_link("4001724819806"), None);
+        assert_eq!(
+            parse_digital_link("https://id.gs1.org/01/09506000134353"),
+            None
+        );
+        assert_eq!(parse_digital_link("https://id.gs1.org/10/ABC123"), None);
+        assert_eq!(
+            parse_digital_link("ftp://id.gs1.org/01/09506000134352"),
+            None
+        );
+        assert_eq!(parse_digital_link("https://id.gs1.org"), None);
+    }
+
+    #[test]
+    fn test_resolve_product_id() {
+        assert_eq!(
+            resolve_product_id("https://id.gs1.org/01/04001724819806"),
+            "4001724819806"
+        );
+        assert_eq!(resolve_product_id("4001724819806"), "4001724819806");
+        assert_eq!(resolve_product_id("custom-id"), "custom-id");
+    }
+}
@@ -0,0 +1,326 @@
+use std::convert::Infallible;
+
+use axum::{
+    body::{Body, Bytes},
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+};
+use futures::stream;
+use log::debug;
+use sha2::{Digest, Sha256};
+
+/// The size of the chunks in which the requested range of a resource is streamed.
+const RANGE_CHUNK_SIZE: usize = 64 * 1024;
+
+/// A single range of bytes of a resource with inclusive start and end offsets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ByteRange {
+    /// The offset of the first byte of the range.
+    pub start: u64,
+
+    /// The offset of the last byte of the range.
+    pub end: u64,
+}
+
+/// The result of evaluating a range header against a resource.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RangeRequest {
+    /// The full resource must be returned, e.g., because the range header is invalid or
+    /// requests multiple ranges.
+    Full,
+
+    /// Only the given part of the resource must be returned.
+    Partial(ByteRange),
+
+    /// The requested range does not overlap with the resource.
+    Unsatisfiable,
+}
+
+/// Evaluates the value of a range header (RFC 7233) for a resource of the given length.
+/// Only single byte ranges are supported, all other range requests are answered with the full
+/// resource.
+///
+/// # Arguments
+/// - `value` - The value of the range header.
+/// - `length` - The length of the resource in bytes.
+pub fn parse_range_header(value: &str, length: u64) -> RangeRequest {
+    let spec = match value.trim().strip_prefix("bytes=") {
+        Some(spec) => spec.trim(),
+        None => return RangeRequest::Full,
+    };
+
+    // multiple ranges are not supported, the full resource is returned instead
+    if spec.contains(',') {
+        return RangeRequest::Full;
+    }
+
+    let (start, end) = match spec.split_once('-') {
+        Some((start, end)) => (start.trim(), end.trim()),
+        None => return RangeRequest::Full,
+    };
+
+    if start.is_empty() {
+        // suffix range, i.e., the last n bytes of the resource
+        let suffix: u64 = match end.parse() {
+            Ok(suffix) => suffix,
+            Err(_) => return RangeRequest::Full,
+        };
+
+        if suffix == 0 || length == 0 {
+            return RangeRequest::Unsatisfiable;
+        }
+
+        return RangeRequest::Partial(ByteRange {
+            start: length - suffix.min(length),
+            end: length - 1,
+        });
+    }
+
+    let start: u64 = match start.parse() {
+        Ok(start) => start,
+        Err(_) => return RangeRequest::Full,
+    };
+
+    let end: Option<u64> = if end.is_empty() {
+        None
+    } else {
+        match end.parse() {
+            Ok(end) if end >= start => Some(end),
+            _ => return RangeRequest::Full,
+        }
+    };
+
+    if start >= length {
+        return RangeRequest::Unsatisfiable;
+    }
+
+    RangeRequest::Partial(ByteRange {
+        start,
+        end: end.map_or(length - 1, |end| end.min(length - 1)),
+    })
+}
+
+/// Returns the entity tag of the given data, i.e., the quoted SHA-256 hash of the data. Unlike
+/// the std hasher, the hash is stable across releases and replicas of the service.
+///
+/// # Arguments
+/// - `data` - The data to compute the entity tag for.
+pub fn entity_tag(data: &[u8]) -> String {
+    format!("\"{}\"", hex::encode(Sha256::digest(data)))
+}
+
+/// Returns the body streaming the given range of the data in chunks of [`RANGE_CHUNK_SIZE`]
+/// without copying the data.
+///
+/// # Arguments
+/// - `data` - The data of the resource.
+/// - `range` - The range of the data to stream.
+fn range_body(data: Bytes, range: ByteRange) -> Body {
+    let slice = data.slice(range.start as usize..=range.end as usize);
+    let chunks = (0..slice.len())
+        .step_by(RANGE_CHUNK_SIZE)
+        .map(move |offset| {
+            let end = (offset + RANGE_CHUNK_SIZE).min(slice.len());
+            Ok::<_, Infallible>(slice.slice(offset..end))
+        });
+
+    Body::from_stream(stream::iter(chunks))
+}
+
+/// Checks whether the value of an `If-None-Match` header (RFC 7232) matches the given entity
+/// tag, i.e., whether it is "*" or lists the tag. Weak tags are compared by their opaque tag.
+///
+/// # Arguments
+/// - `value` - The value of the `If-None-Match` header.
+/// - `etag` - The entity tag of the resource.
+pub fn if_none_match(value: &str, etag: &str) -> bool {
+    let etag = etag.trim_start_matches("W/");
+
+    value
+        .split(',')
+        .map(str::trim)
+        .any(|candidate| candidate == "*" || candidate.trim_start_matches("W/") == etag)
+}
+
+/// Creates the response for a binary resource like an image, respecting the `If-None-Match`,
+/// `Range` and `If-Range` headers of the request. Requests for an unchanged resource are
+/// answered with 304 and partial requests with 206, whose body streams only the requested range.
+///
+/// # Arguments
+/// - `request_headers` - The headers of the request.
+/// - `content_type` - The content type of the resource.
+/// - `data` - The data of the resource.
+pub fn ranged_response(
+    request_headers: &HeaderMap,
+    content_type: HeaderValue,
+    data: Vec<u8>,
+) -> Response {
+    let length = data.len() as u64;
+    let etag = entity_tag(&data);
+
+    let mut headers = HeaderMap::new();
+    headers.insert(header::CONTENT_TYPE, content_type);
+    headers.insert(header::ACCEPT_RANGES, HeaderValue::from_static("bytes"));
+    if let Ok(etag) = HeaderValue::from_str(&etag) {
+        headers.insert(header::ETAG, etag);
+    }
+
+    // the client already has the current version of the resource
+    let not_modified = request_headers
+        .get(header::IF_NONE_MATCH)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| if_none_match(value, &etag));
+    if not_modified {
+        debug!("Responding with not modified for {}", etag);
+        headers.remove(header::CONTENT_TYPE);
+
+        return (StatusCode::NOT_MODIFIED, headers).into_response();
+    }
+
+    // the range is ignored if the resource has changed in the meantime
+    let range = request_headers
+        .get(header::RANGE)
+        .and_then(|range| range.to_str().ok());
+    let if_range_matches = request_headers
+        .get(header::IF_RANGE)
+        .is_none_or(|if_range| if_range.as_bytes() == etag.as_bytes());
+
+    let range = match range {
+        Some(range) if if_range_matches => parse_range_header(range, length),
+        _ => RangeRequest::Full,
+    };
+
+    match range {
+        RangeRequest::Full => (StatusCode::OK, headers, data).into_response(),
+        RangeRequest::Partial(range) => {
+            debug!(
+                "Responding with bytes {}-{} of {}",
+                range.start, range.end, length
+            );
+
+            if let Ok(content_range) =
+                HeaderValue::from_str(&format!("bytes {}-{}/{}", range.start, range.end, length))
+            {
+                headers.insert(header::CONTENT_RANGE, content_range);
+            }
+
+            let body = range_body(Bytes::from(data), range);
+
+            (StatusCode::PARTIAL_CONTENT, headers, body).into_response()
+        }
+        RangeRequest::Unsatisfiable => {
+            if let Ok(content_range) = HeaderValue::from_str(&format!("bytes */{}", length)) {
+                headers.insert(header::CONTENT_RANGE, content_range);
+            }
+
+            (StatusCode::RANGE_NOT_SATISFIABLE, headers).into_response()
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_range_header() {
+        assert_eq!(
+            parse_range_header("bytes=0-9", 100),
+            RangeRequest::Partial(ByteRange { start: 0, end: 9 })
+        );
+        assert_eq!(
+            parse_range_header("bytes=90-", 100),
+            RangeRequest::Partial(ByteRange { start: 90, end: 99 })
+        );
+        assert_eq!(
+            parse_range_header("bytes=90-200", 100),
+            RangeRequest::Partial(ByteRange { start: 90, end: 99 })
+        );
+        assert_eq!(
+            parse_range_header("bytes=-10", 100),
+            RangeRequest::Partial(ByteRange { start: 90, end: 99 })
+        );
+        assert_eq!(
+            parse_range_header("bytes=-200", 100),
+            RangeRequest::Partial(ByteRange { start: 0, end: 99 })
+        );
+
+        assert_eq!(
+            parse_range_header("bytes=100-", 100),
+            RangeRequest::Unsatisfiable
+        );
+        assert_eq!(
+            parse_range_header("bytes=-0", 100),
+            RangeRequest::Unsatisfiable
+        );
+
+        assert_eq!(parse_range_header("bytes=0-1,5-6", 100), RangeRequest::Full);
+        assert_eq!(parse_range_header("bytes=9-0", 100), RangeRequest::Full);
+        assert_eq!(parse_range_header("items=0-9", 100), RangeRequest::Full);
+        assert_eq!(parse_range_header("bytes=a-b", 100), RangeRequest::Full);
+    }
+
+    #[test]
+    fn test_if_none_match() {
+        let etag = entity_tag(b"image");
+        assert!(if_none_match(&etag, &etag));
+        assert!(if_none_match("*", &etag));
+        assert!(if_none_match(&format!("\"other\", {}", etag), &etag));
+        assert!(if_none_match(&format!("W/{}", etag), &etag));
+
+        assert!(!if_none_match("\"other\"", &etag));
+        assert!(!if_none_match("", &etag));
+        assert!(!if_none_match(&entity_tag(b"other image"), &etag));
+    }
+
+    #[test]
+    fn test_ranged_response_not_modified() {
+        let data = b"image".to_vec();
+        let content_type = HeaderValue::from_static("image/png");
+
+        let mut request_headers = HeaderMap::new();
+        let response = ranged_response(&request_headers, content_type.clone(), data.clone());
+        assert_eq!(response.status(), StatusCode::OK);
+        let etag = response.headers().get(header::ETAG).unwrap().clone();
+
+        request_headers.insert(header::IF_NONE_MATCH, etag.clone());
+        request_headers.insert(header::RANGE, HeaderValue::from_static("bytes=0-1"));
+        let response = ranged_response(&request_headers, content_type.clone(), data.clone());
+        assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+        assert_eq!(response.headers().get(header::ETAG), Some(&etag));
+
+        // a changed resource is returned again
+        let response = ranged_response(&request_headers, content_type, b"new image".to_vec());
+        assert_eq!(response.status(), StatusCode::PARTIAL_CONTENT);
+    }
+
+    #[tokio::test]
+    async fn test_ranged_response_partial() {
+        let data = (0..3 * RANGE_CHUNK_SIZE)
+            .map(|i| (i % 251) as u8)
+            .collect::<Vec<_>>();
+        let start = RANGE_CHUNK_SIZE / 2;
+        let end = 2 * RANGE_CHUNK_SIZE + 10;
+
+        let mut request_headers = HeaderMap::new();
+        request_headers.insert(
+            header::RANGE,
+            HeaderValue::from_str(&format!("bytes={}-{}", start, end)).unwrap(),
+        );
+        let response = ranged_response(
+            &request_headers,
+            HeaderValue::from_static("image/png"),
+            data.clone(),
+        );
+        assert_eq!(response.status(), StatusCode::PARTIAL_CONTENT);
+        assert_eq!(
+            response.headers().get(header::ETAG).unwrap(),
+            entity_tag(&data).as_str()
+        );
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert_eq!(body.as_ref(), &data[start..=end]);
+    }
+}
@@ -0,0 +1,479 @@
+use std::sync::Arc;
+
+use jpeg_decoder::PixelFormat;
+use png::{BitDepth, ColorType, Encoder, Transformations};
+
+use crate::{Error, PreviewGenerator, PreviewOptions, ProductImage, Result};
+
+/// The content type of the generated previews.
+pub const PREVIEW_CONTENT_TYPE: &str = "image/png";
+
+/// The maximal number of pixels of a decoded image, which protects against decompression bombs.
+const MAX_PIXELS: u64 = 50_000_000;
+
+/// The content types of the images accepted if the content types are verified.
+pub const SUPPORTED_IMAGE_CONTENT_TYPES: [&str; 5] = [
+    "image/jpeg",
+    "image/png",
+    "image/webp",
+    "image/gif",
+    "image/avif",
+];
+
+/// An image with 8-bit RGBA pixels, stored row by row.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct RgbaImage {
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>,
+}
+
+/// Decodes the given PNG or JPEG image.
+///
+/// # Arguments
+/// - `image` - The encoded image.
+fn decode(image: &ProductImage) -> Result<RgbaImage> {
+    match image.content_type.as_str() {
+        "image/png" => decode_png(&image.data),
+        "image/jpeg" | "image/jpg" => decode_jpeg(&image.data),
+        content_type => Err(Error::InvalidImage(format!(
+            "Unsupported content type '{}', expected image/png or image/jpeg",
+            content_type
+        ))),
+    }
+}
+
+/// Checks that the image does not exceed [`MAX_PIXELS`].
+///
+/// # Arguments
+/// - `width` - The width of the image in pixels.
+/// - `height` - The height of the image in pixels.
+fn check_dimensions(width: u32, height: u32) -> Result<()> {
+    if width == 0 || height == 0 {
+        return Err(Error::InvalidImage("The image is empty".to_string()));
+    }
+    if width as u64 * height as u64 > MAX_PIXELS {
+        return Err(Error::InvalidImage(format!(
+            "The image has {}x{} pixels, which exceeds the limit of {} pixels",
+            width, height, MAX_PIXELS
+        )));
+    }
+
+    Ok(())
+}
+
+/// Decodes a PNG image with any color type and bit depth.
+///
+/// # Arguments
+/// - `data` - The encoded image.
+fn decode_png(data: &[u8]) -> Result<RgbaImage> {
+    let invalid = |e: png::DecodingError| Error::InvalidImage(format!("Invalid PNG: {}", e));
+
+    let mut decoder = png::Decoder::new(data);
+    decoder.set_transformations(Transformations::normalize_to_color8());
+    let mut reader = decoder.read_info().map_err(invalid)?;
+    let info = reader.info();
+    check_dimensions(info.width, info.height)?;
+
+    let mut buffer = vec![0; reader.output_buffer_size()];
+    let frame = reader.next_frame(&mut buffer).map_err(invalid)?;
+    let buffer = &buffer[..frame.buffer_size()];
+
+    let pixels = match frame.color_type {
+        ColorType::Rgba => buffer.to_vec(),
+        ColorType::Rgb => buffer
+            .chunks_exact(3)
+            .flat_map(|p| [p[0], p[1], p[2], 255])
+            .collect(),
+        ColorType::GrayscaleAlpha => buffer
+            .chunks_exact(2)
+            .flat_map(|p| [p[0], p[0], p[0], p[1]])
+            .collect(),
+        ColorType::Grayscale => buffer.iter().flat_map(|&l| [l, l, l, 255]).collect(),
+        ColorType::Indexed => {
+            return Err(Error::InvalidImage(
+                "Invalid PNG: unexpanded palette".to_string(),
+            ))
+        }
+    };
+
+    Ok(RgbaImage {
+        width: frame.width,
+        height: frame.height,
+        pixels,
+    })
+}
+
+/// Decodes a grayscale or RGB JPEG image.
+///
+/// # Arguments
+/// - `data` - The encoded image.
+fn decode_jpeg(data: &[u8]) -> Result<RgbaImage> {
+    let invalid = |e: jpeg_decoder::Error| Error::InvalidImage(format!("Invalid JPEG: {}", e));
+
+    let mut decoder = jpeg_decoder::Decoder::new(data);
+    decoder.read_info().map_err(invalid)?;
+    let info = decoder
+        .info()
+        .ok_or_else(|| Error::InvalidImage("Invalid JPEG: missing header".to_string()))?;
+    let (width, height) = (info.width as u32, info.height as u32);
+    check_dimensions(width, height)?;
+
+    let buffer = decoder.decode().map_err(invalid)?;
+    let pixels = match info.pixel_format {
+        PixelFormat::RGB24 => buffer
+            .chunks_exact(3)
+            .flat_map(|p| [p[0], p[1], p[2], 255])
+            .collect(),
+        PixelFormat::L8 => buffer.iter().flat_map(|&l| [l, l, l, 255]).collect(),
+        pixel_format => {
+            return Err(Error::InvalidImage(format!(
+                "Unsupported JPEG pixel format {:?}",
+                pixel_format
+            )))
+        }
+    };
+
+    Ok(RgbaImage {
+        width,
+        height,
+        pixels,
+    })
+}
+
+/// Returns the dimensions of the image scaled down to fit into the maximal dimensions while
+/// keeping the aspect ratio. Images that already fit are not scaled up.
+///
+/// # Arguments
+/// - `width` - The width of the image in pixels.
+/// - `height` - The height of the image in pixels.
+/// - `options` - The maximal dimensions.
+fn preview_dimensions(width: u32, height: u32, options: &PreviewOptions) -> (u32, u32) {
+    let scale = (options.max_width as f64 / width as f64)
+        .min(options.max_height as f64 / height as f64)
+        .min(1.0);
+
+    (
+        ((width as f64 * scale).round() as u32).max(1),
+        ((height as f64 * scale).round() as u32).max(1),
+    )
+}
+
+/// Scales the image down to the given dimensions by averaging the source pixels covered by each
+/// target pixel, which avoids the aliasing of sampling single pixels.
+///
+/// # Arguments
+/// - `image` - The source image.
+/// - `width` - The width of the scaled image, at most the width of the source image.
+/// - `height` - The height of the scaled image, at most the height of the source image.
+fn downscale(image: &RgbaImage, width: u32, height: u32) -> RgbaImage {
+    let (src_width, src_height) = (image.width as usize, image.height as usize);
+    let mut pixels = Vec::with_capacity(width as usize * height as usize * 4);
+
+    for y in 0..height as usize {
+        let y0 = y * src_height / height as usize;
+        let y1 = ((y + 1) * src_height / height as usize).max(y0 + 1);
+
+        for x in 0..width as usize {
+            let x0 = x * src_width / width as usize;
+            let x1 = ((x + 1) * src_width / width as usize).max(x0 + 1);
+
+            // the colors are weighted by their alpha, such that transparent pixels do not darken
+            let mut sum = [0u64; 4];
+            for row in y0..y1 {
+                let start = (row * src_width + x0) * 4;
+                for p in image.pixels[start..start + (x1 - x0) * 4].chunks_exact(4) {
+                    let alpha = p[3] as u64;
+                    sum[0] += p[0] as u64 * alpha;
+                    sum[1] += p[1] as u64 * alpha;
+                    sum[2] += p[2] as u64 * alpha;
+                    sum[3] += alpha;
+                }
+            }
+
+            let count = ((y1 - y0) * (x1 - x0)) as u64;
+            let color = |c: u64| (c + sum[3] / 2).checked_div(sum[3]).unwrap_or(0) as u8;
+            pixels.extend_from_slice(&[
+                color(sum[0]),
+                color(sum[1]),
+                color(sum[2]),
+                ((sum[3] + count / 2) / count) as u8,
+            ]);
+        }
+    }
+
+    RgbaImage {
+        width,
+        height,
+        pixels,
+    }
+}
+
+/// Encodes the image as PNG. Opaque images are stored without alpha channel.
+///
+/// # Arguments
+/// - `image` - The image to encode.
+fn encode_png(image: &RgbaImage) -> Result<Vec<u8>> {
+    let opaque = image.pixels.chunks_exact(4).all(|p| p[3] == 255);
+    let (color_type, pixels) = if opaque {
+        let rgb = image
+            .pixels
+            .chunks_exact(4)
+            .flat_map(|p| [p[0], p[1], p[2]])
+            .collect();
+        (ColorType::Rgb, rgb)
+    } else {
+        (ColorType::Rgba, image.pixels.clone())
+    };
+
+    let mut data = Vec::new();
+    let mut encoder = Encoder::new(&mut data, image.width, image.height);
+    encoder.set_color(color_type);
+    encoder.set_depth(BitDepth::Eight);
+    encoder
+        .write_header()
+        .and_then(|mut writer| writer.write_image_data(&pixels))
+        .map_err(|e| Error::InternalError(format!("Failed to encode the preview: {}", e)))?;
+
+    Ok(data)
+}
+
+/// Generates the preview of the given full image as PNG, which fits into the maximal dimensions
+/// of the options. Fails if the full image is neither a PNG nor a JPEG or cannot be decoded.
+///
+/// # Arguments
+/// - `full_image` - The full image of the product.
+/// - `options` - The maximal dimensions of the preview.
+pub fn generate_preview(
+    full_image: &ProductImage,
+    options: &PreviewOptions,
+) -> Result<ProductImage> {
+    let image = decode(full_image)?;
+    let (width, height) = preview_dimensions(image.width, image.height, options);
+    let preview = if (width, height) == (image.width, image.height) {
+        image
+    } else {
+        downscale(&image, width, height)
+    };
+
+    Ok(ProductImage {
+        content_type: PREVIEW_CONTENT_TYPE.to_string(),
+        data: encode_png(&preview)?,
+    })
+}
+
+/// Returns the preview generator that scales the full images down to the dimensions of the
+/// options, see [`generate_preview`].
+///
+/// # Arguments
+/// - `options` - The maximal dimensions of the previews.
+pub fn preview_generator(options: PreviewOptions) -> PreviewGenerator {
+    Arc::new(move |full_image| generate_preview(full_image, &options))
+}
+
+/// Detects the content type of the given image data by its magic bytes. Returns None if the data
+/// is neither a JPEG, PNG, WebP, GIF nor AVIF image.
+///
+/// # Arguments
+/// - `data` - The encoded image.
+pub fn detect_image_content_type(data: &[u8]) -> Option<&'static str> {
+    let bytes = |range: std::ops::Range<usize>| data.get(range).unwrap_or_default();
+
+    if data.starts_with(&[0xff, 0xd8, 0xff]) {
+        Some("image/jpeg")
+    } else if data.starts_with(b"\x89PNG\r\n\x1a\n") {
+        Some("image/png")
+    } else if data.starts_with(b"GIF87a") || data.starts_with(b"GIF89a") {
+        Some("image/gif")
+    } else if bytes(0..4) == b"RIFF" && bytes(8..12) == b"WEBP" {
+        Some("image/webp")
+    } else if bytes(4..8) == b"ftyp" && matches!(bytes(8..12), b"avif" | b"avis") {
+        Some("image/avif")
+    } else {
+        None
+    }
+}
+
+/// Checks that the given image is of one of the [`SUPPORTED_IMAGE_CONTENT_TYPES`] and that its
+/// data matches its content type. The non-standard "image/jpg" is treated as "image/jpeg".
+///
+/// # Arguments
+/// - `image` - The image to check.
+pub fn verify_image_content_type(image: &ProductImage) -> Result<()> {
+    let content_type = match image.content_type.as_str() {
+        "image/jpg" => "image/jpeg",
+        content_type => content_type,
+    };
+    if !SUPPORTED_IMAGE_CONTENT_TYPES.contains(&content_type) {
+        return Err(Error::UnsupportedImageFormat(image.content_type.clone()));
+    }
+
+    if detect_image_content_type(&image.data) != Some(content_type) {
+        return Err(Error::ImageContentTypeMismatch(image.content_type.clone()));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Returns a PNG image with a gradient of the given size and color type.
+    fn png_image(width: u32, height: u32, color_type: ColorType) -> ProductImage {
+        let channels = match color_type {
+            ColorType::Grayscale => 1,
+            ColorType::Rgb => 3,
+            _ => 4,
+        };
+        let pixels: Vec<u8> = (0..width * height * channels)
+            .map(|i| (i % 256) as u8)
+            .collect();
+
+        let mut data = Vec::new();
+        let mut encoder = Encoder::new(&mut data, width, height);
+        encoder.set_color(color_type);
+        encoder.set_depth(BitDepth::Eight);
+        encoder
+            .write_header()
+            .and_then(|mut writer| writer.write_image_data(&pixels))
+            .unwrap();
+
+        ProductImage {
+            content_type: "image/png".to_string(),
+            data,
+        }
+    }
+
+    #[test]
+    fn test_preview_dimensions() {
+        let options = PreviewOptions {
+            max_width: 256,
+            max_height: 128,
+        };
+        assert_eq!(preview_dimensions(1024, 768, &options), (171, 128));
+        assert_eq!(preview_dimensions(4000, 100, &options), (256, 6));
+        assert_eq!(preview_dimensions(100, 50, &options), (100, 50));
+        assert_eq!(preview_dimensions(10000, 1, &options), (256, 1));
+    }
+
+    #[test]
+    fn test_generate_preview() {
+        let options = PreviewOptions {
+            max_width: 64,
+            max_height: 64,
+        };
+
+        for color_type in [ColorType::Rgb, ColorType::Rgba, ColorType::Grayscale] {
+            let preview = generate_preview(&png_image(300, 200, color_type), &options).unwrap();
+            assert_eq!(preview.content_type, PREVIEW_CONTENT_TYPE);
+
+            let preview = decode(&preview).unwrap();
+            assert_eq!((preview.width, preview.height), (64, 43));
+            assert_eq!(preview.pixels.len(), 64 * 43 * 4);
+        }
+
+        // small images are not scaled up
+        let preview = generate_preview(&png_image(20, 10, ColorType::Rgb), &options).unwrap();
+        let preview = decode(&preview).unwrap();
+        assert_eq!((preview.width, preview.height), (20, 10));
+    }
+
+    #[test]
+    fn test_downscale() {
+        // a 2x2 block of black and white pixels averages to gray
+        let image = RgbaImage {
+            width: 2,
+            height: 2,
+            pixels: vec![
+                0, 0, 0, 255, 255, 255, 255, 255, 255, 255, 255, 255, 0, 0, 0, 255,
+            ],
+        };
+        assert_eq!(downscale(&image, 1, 1).pixels, vec![128, 128, 128, 255]);
+
+        // transparent pixels do not change the color
+        let image = RgbaImage {
+            width: 2,
+            height: 1,
+            pixels: vec![200, 100, 50, 255, 0, 0, 0, 0],
+        };
+        assert_eq!(downscale(&image, 1, 1).pixels, vec![200, 100, 50, 128]);
+    }
+
+    #[test]
+    fn test_invalid_images() {
+        let options = PreviewOptions::default();
+        let image = ProductImage {
+            content_type: "image/png".to_string(),
+            data: vec![1, 2, 3],
+        };
+        assert!(generate_preview(&image, &options).is_err());
+
+        let image = ProductImage {
+            content_type: "image/jpeg".to_string(),
+            data: vec![0xff, 0xd8, 0xff],
+        };
+        assert!(generate_preview(&image, &options).is_err());
+
+        let image = ProductImage {
+            content_type: "image/webp".to_string(),
+            data: vec![1, 2, 3],
+        };
+        assert!(generate_preview(&image, &options).is_err());
+    }
+
+    #[test]
+    fn test_detect_image_content_type() {
+        let png = png_image(2, 2, ColorType::Rgb);
+        assert_eq!(detect_image_content_type(&png.data), Some("image/png"));
+        assert_eq!(
+            detect_image_content_type(&[0xff, 0xd8, 0xff, 0xe0, 0, 0x10]),
+            Some("image/jpeg")
+        );
+        assert_eq!(
+            detect_image_content_type(b"GIF89a\x01\x00"),
+            Some("image/gif")
+        );
+        assert_eq!(
+            detect_image_content_type(b"RIFF\x24\x00\x00\x00WEBPVP8 "),
+            Some("image/webp")
+        );
+        assert_eq!(
+            detect_image_content_type(b"\x00\x00\x00\x1cftypavif\x00\x00\x00\x00"),
+            Some("image/avif")
+        );
+        assert_eq!(detect_image_content_type(b"RIFF"), None);
+        assert_eq!(detect_image_content_type(b"<svg></svg>"), None);
+        assert_eq!(detect_image_content_type(&[]), None);
+    }
+
+    #[test]
+    fn test_verify_image_content_type() {
+        let png = png_image(2, 2, ColorType::Rgb);
+        assert!(verify_image_content_type(&png).is_ok());
+
+        let jpeg = ProductImage {
+            content_type: "image/jpg".to_string(),
+            data: vec![0xff, 0xd8, 0xff, 0xe0],
+        };
+        assert!(verify_image_content_type(&jpeg).is_ok());
+
+        let mismatch = ProductImage {
+            content_type: "image/jpeg".to_string(),
+            data: png.data.clone(),
+        };
+        assert!(matches!(
+            verify_image_content_type(&mismatch),
+            Err(Error::ImageContentTypeMismatch(_))
+        ));
+
+        let svg = ProductImage {
+            content_type: "image/svg+xml".to_string(),
+            data: b"<svg></svg>".to_vec(),
+        };
+        assert!(matches!(
+            verify_image_content_type(&svg),
+            Err(Error::UnsupportedImageFormat(_))
+        ));
+    }
+}
@@ -0,0 +1,143 @@
+use std::time::Duration;
+
+use chrono::{DateTime, TimeZone, Utc};
+use hmac::{Hmac, Mac};
+use log::debug;
+use sha2::Sha256;
+
+use crate::{EndpointOptions, ProductID, Secret};
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// A signed and expiring token that grants access to the full image of a single product.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignedImageToken {
+    /// The token to be used in the public image url.
+    pub token: String,
+
+    /// The point in time after which the token is no longer accepted.
+    pub expires: DateTime<Utc>,
+}
+
+/// Creates and verifies the tokens of pre-signed image urls. A token consists of the hex encoded
+/// product id, the expiry as unix timestamp and the HMAC-SHA256 signature of both.
+#[derive(Clone)]
+pub struct ImageUrlSigner {
+    secret: Secret,
+    ttl: Duration,
+}
+
+impl ImageUrlSigner {
+    /// Creates a new signer.
+    ///
+    /// # Arguments
+    /// - `secret` - The secret key used for signing the tokens.
+    /// - `ttl` - The duration for which the created tokens are valid.
+    pub fn new(secret: Secret, ttl: Duration) -> Self {
+        Self { secret, ttl }
+    }
+
+    /// Creates the signer from the endpoint options or returns None if signed image urls are
+    /// not configured.
+    ///
+    /// # Arguments
+    /// - `options` - The options for the endpoint.
+    pub fn from_options(options: &EndpointOptions) -> Option<Self> {
+        options
+            .image_url_secret
+            .as_ref()
+            .map(|secret| Self::new(secret.clone(), options.image_url_ttl()))
+    }
+
+    /// Creates a new token for the image of the given product that expires after the
+    /// configured duration.
+    ///
+    /// # Arguments
+    /// - `product_id` - The id of the product whose image is made accessible.
+    /// - `now` - The current point in time.
+    pub fn sign(&self, product_id: &ProductID, now: DateTime<Utc>) -> SignedImageToken {
+        let expires = now.timestamp() + self.ttl.as_secs() as i64;
+        let payload = format!("{}.{}", hex::encode(product_id), expires);
+        let signature = hex::encode(self.mac(&payload).finalize().into_bytes());
+
+        SignedImageToken {
+            token: format!("{}.{}", payload, signature),
+            expires: Utc.timestamp_opt(expires, 0).single().unwrap_or(now),
+        }
+    }
+
+    /// Verifies the given token and returns the product id if the signature is valid and the
+    /// token has not expired yet.
+    ///
+    /// # Arguments
+    /// - `token` - The token to verify.
+    /// - `now` - The current point in time.
+    pub fn verify(&self, token: &str, now: DateTime<Utc>) -> Option<ProductID> {
+        let (payload, signature) = token.rsplit_once('.')?;
+        let (product_id, expires) = payload.split_once('.')?;
+
+        let signature = hex::decode(signature).ok()?;
+        if self.mac(payload).verify_slice(&signature).is_err() {
+            debug!("Rejected image token due to an invalid signature");
+            return None;
+        }
+
+        let expires: i64 = expires.parse().ok()?;
+        if expires < now.timestamp() {
+            debug!("Rejected image token that expired at {}", expires);
+            return None;
+        }
+
+        String::from_utf8(hex::decode(product_id).ok()?).ok()
+    }
+
+    /// Returns the MAC of the given payload.
+    ///
+    /// # Arguments
+    /// - `payload` - The payload to sign.
+    fn mac(&self, payload: &str) -> HmacSha256 {
+        let mut mac =
+            HmacSha256::new_from_slice(self.secret.bytes()).expect("HMAC accepts keys of any size");
+        mac.update(payload.as_bytes());
+        mac
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_sign_and_verify() {
+        let signer = ImageUrlSigner::new(Secret::new("key".to_string()), Duration::from_secs(60));
+        let now = Utc::now();
+        let product_id = "4001724819806".to_string();
+
+        let signed = signer.sign(&product_id, now);
+        assert_eq!(signer.verify(&signed.token, now), Some(product_id.clone()));
+        assert_eq!(
+            signer.verify(&signed.token, now + chrono::Duration::seconds(61)),
+            None
+        );
+
+        // tokens of other keys are rejected
+        let other = ImageUrlSigner::new(Secret::new("other".to_string()), Duration::from_secs(60));
+        assert_eq!(other.verify(&signed.token, now), None);
+
+        // tampering with the product id or the expiry invalidates the signature
+        let (_, rest) = signed.token.split_once('.').unwrap();
+        let tampered = format!("{}.{}", hex::encode("other-product"), rest);
+        assert_eq!(signer.verify(&tampered, now), None);
+
+        let parts: Vec<&str> = signed.token.split('.').collect();
+        let tampered = format!(
+            "{}.{}.{}",
+            parts[0],
+            signed.expires.timestamp() + 3600,
+            parts[2]
+        );
+        assert_eq!(signer.verify(&tampered, now), None);
+
+        assert_eq!(signer.verify("garbage", now), None);
+    }
+}
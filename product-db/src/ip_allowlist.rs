@@ -0,0 +1,205 @@
+use std::{net::IpAddr, net::SocketAddr, sync::Arc};
+
+use axum::{
+    extract::{ConnectInfo, OriginalUri, Request, State},
+    http::{HeaderMap, StatusCode},
+    middleware::Next,
+    response::Response,
+};
+use ipnet::IpNet;
+use log::{debug, warn};
+
+use crate::{layers::problem_response, AdminAllowlistOptions};
+
+/// The header in which reverse proxies append the address of their peer.
+const X_FORWARDED_FOR: &str = "x-forwarded-for";
+
+/// Restricts requests to clients from a set of allowed networks.
+#[derive(Debug, Clone)]
+pub struct IpAllowlist {
+    networks: Vec<IpNet>,
+    trusted_proxy_depth: usize,
+}
+
+impl IpAllowlist {
+    /// Creates a new allowlist.
+    ///
+    /// # Arguments
+    /// - `networks` - The allowed networks.
+    /// - `trusted_proxy_depth` - The number of trusted reverse proxies in front of the service.
+    pub fn new(networks: Vec<IpNet>, trusted_proxy_depth: usize) -> Self {
+        Self {
+            networks,
+            trusted_proxy_depth,
+        }
+    }
+
+    /// Creates the allowlist from the options.
+    ///
+    /// # Arguments
+    /// - `options` - The options of the allowlist.
+    pub fn from_options(options: &AdminAllowlistOptions) -> Self {
+        Self::new(options.networks.clone(), options.trusted_proxy_depth)
+    }
+
+    /// Returns the address of the client. Without trusted proxies, this is the address of the
+    /// peer. Otherwise, it is the address the outermost trusted proxy appended to the
+    /// `X-Forwarded-For` header. Returns None if the header does not contain an address for
+    /// each trusted proxy.
+    ///
+    /// # Arguments
+    /// - `peer` - The address of the peer of the connection.
+    /// - `headers` - The headers of the request.
+    pub fn client_address(&self, peer: IpAddr, headers: &HeaderMap) -> Option<IpAddr> {
+        client_address(peer, headers, self.trusted_proxy_depth)
+    }
+
+    /// Returns true if the given address is within one of the allowed networks.
+    ///
+    /// # Arguments
+    /// - `address` - The address of the client.
+    pub fn is_allowed(&self, address: IpAddr) -> bool {
+        // IPv4 clients on dual-stack sockets are reported as IPv4-mapped IPv6 addresses
+        let address = match address {
+            IpAddr::V6(v6) => v6
+                .to_ipv4_mapped()
+                .map(IpAddr::V4)
+                .unwrap_or(IpAddr::V6(v6)),
+            address => address,
+        };
+
+        self.networks
+            .iter()
+            .any(|network| network.contains(&address))
+    }
+}
+
+/// Returns the address of the client. Without trusted proxies, this is the address of the peer.
+/// Otherwise, it is the address the outermost trusted proxy appended to the `X-Forwarded-For`
+/// header. Returns None if the header does not contain an address for each trusted proxy.
+///
+/// # Arguments
+/// - `peer` - The address of the peer of the connection.
+/// - `headers` - The headers of the request.
+/// - `trusted_proxy_depth` - The number of trusted reverse proxies in front of the service.
+pub(crate) fn client_address(
+    peer: IpAddr,
+    headers: &HeaderMap,
+    trusted_proxy_depth: usize,
+) -> Option<IpAddr> {
+    if trusted_proxy_depth == 0 {
+        return Some(peer);
+    }
+
+    // multiple headers are treated as a single comma separated list
+    let forwarded: Vec<&str> = headers
+        .get_all(X_FORWARDED_FOR)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(str::trim)
+        .collect();
+
+    let index = forwarded.len().checked_sub(trusted_proxy_depth)?;
+    forwarded[index].parse().ok()
+}
+
+/// Middleware that rejects requests of clients outside the allowed networks with 403. The
+/// peer address is taken from the [`ConnectInfo`] of the connection, i.e., routers embedded
+/// into other applications must be served with
+/// `into_make_service_with_connect_info::<SocketAddr>()`.
+pub async fn ip_allowlist_guard(
+    State(allowlist): State<Arc<IpAllowlist>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let uri = request
+        .extensions()
+        .get::<OriginalUri>()
+        .map(|uri| uri.0.clone())
+        .unwrap_or_else(|| request.uri().clone());
+
+    let Some(ConnectInfo(peer)) = request.extensions().get::<ConnectInfo<SocketAddr>>() else {
+        warn!(
+            "Rejected {} {} due to unknown peer address",
+            request.method(),
+            uri
+        );
+        return problem_response(
+            StatusCode::FORBIDDEN,
+            "The address of the client is unknown".to_string(),
+        );
+    };
+
+    match allowlist.client_address(peer.ip(), request.headers()) {
+        Some(address) if allowlist.is_allowed(address) => {
+            debug!("Accepted {} {} from {}", request.method(), uri, address);
+            next.run(request).await
+        }
+        address => {
+            warn!(
+                "Rejected {} {} from {} (peer {}) outside the allowed networks",
+                request.method(),
+                uri,
+                address.map_or_else(|| "unknown".to_string(), |a| a.to_string()),
+                peer
+            );
+            problem_response(
+                StatusCode::FORBIDDEN,
+                "The admin endpoints are not accessible from this address".to_string(),
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use axum::http::HeaderValue;
+
+    use super::*;
+
+    #[test]
+    fn test_is_allowed() {
+        let allowlist = IpAllowlist::new(
+            vec!["10.0.0.0/8".parse().unwrap(), "::1/128".parse().unwrap()],
+            0,
+        );
+
+        assert!(allowlist.is_allowed("10.1.2.3".parse().unwrap()));
+        assert!(allowlist.is_allowed("::1".parse().unwrap()));
+        assert!(allowlist.is_allowed("::ffff:10.1.2.3".parse().unwrap()));
+        assert!(!allowlist.is_allowed("192.168.0.1".parse().unwrap()));
+        assert!(!allowlist.is_allowed("::2".parse().unwrap()));
+    }
+
+    #[test]
+    fn test_client_address() {
+        let peer: IpAddr = "127.0.0.1".parse().unwrap();
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            X_FORWARDED_FOR,
+            HeaderValue::from_static("1.1.1.1, 10.0.0.1, 10.0.0.2"),
+        );
+
+        // without trusted proxies, the header is ignored
+        let allowlist = IpAllowlist::new(Vec::new(), 0);
+        assert_eq!(allowlist.client_address(peer, &headers), Some(peer));
+
+        // the addresses in front of the trusted proxies can be spoofed by the client
+        let allowlist = IpAllowlist::new(Vec::new(), 1);
+        assert_eq!(
+            allowlist.client_address(peer, &headers),
+            Some("10.0.0.2".parse().unwrap())
+        );
+        let allowlist = IpAllowlist::new(Vec::new(), 3);
+        assert_eq!(
+            allowlist.client_address(peer, &headers),
+            Some("1.1.1.1".parse().unwrap())
+        );
+
+        // the header must contain an address for each trusted proxy
+        let allowlist = IpAllowlist::new(Vec::new(), 4);
+        assert_eq!(allowlist.client_address(peer, &headers), None);
+        assert_eq!(allowlist.client_address(peer, &HeaderMap::new()), None);
+    }
+}
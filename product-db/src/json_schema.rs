@@ -0,0 +1,114 @@
+use schemars::schema_for;
+use serde_json::Value;
+
+use crate::service_json::*;
+use crate::*;
+
+/// Defines the list of the available JSON Schemas and the function for creating them by name.
+macro_rules! json_schemas {
+    ($($name:ident),* $(,)?) => {
+        /// The names of the types for which a JSON Schema is available.
+        pub const JSON_SCHEMA_NAMES: &[&str] = &[$(stringify!($name)),*];
+
+        /// Returns the JSON Schema of the request or response type with the given name or None
+        /// if there is no such type.
+        ///
+        /// # Arguments
+        /// - `name` - The name of the type, see [`JSON_SCHEMA_NAMES`].
+        pub fn json_schema(name: &str) -> Option<Value> {
+            match name {
+                $(stringify!($name) => Some(schema_for!($name).to_value()),)*
+                _ => None,
+            }
+        }
+    };
+}
+
+json_schemas!(
+    // the domain types
+    ProductDescription,
+    ProductRequest,
+    MissingProduct,
+    ArchivedProductRequest,
+    Recipe,
+    PriceObservation,
+    Store,
+    // the request bodies
+    ProductSubmission,
+    ProductQuery,
+    MissingProductQuery,
+    ArchiveQuery,
+    MissingProductReportRequest,
+    MissingProductsBatchReportRequest,
+    MaintenanceModeRequest,
+    ArchiveProductRequestRequest,
+    RejectProductRequestRequest,
+    NutrientAggregationRequest,
+    PriceQuery,
+    StoreQuery,
+    AvailabilityReportRequest,
+    // the responses
+    ProductRequestResponse,
+    MissingProductReportResponse,
+    MissingProductsBatchReportResponse,
+    OnlyMessageResponse,
+    GetProductRequestResponse,
+    ProductRequestQueryResponse,
+    MissingProductsQueryResponse,
+    MissingProductsSummaryResponse,
+    GetReportedMissingProductResponse,
+    GetProductResponse,
+    ProductQueryResponse,
+    ProductProjectionQueryResponse,
+    CountResponse,
+    NutrientAggregationResponse,
+    NewRecipeResponse,
+    GetRecipeResponse,
+    PriceSubmissionResponse,
+    PriceQueryResponse,
+    CheapestPriceResponse,
+    NewStoreResponse,
+    GetStoreResponse,
+    StoreQueryResponse,
+    ProductAvailabilityResponse,
+    MaintenanceModeResponse,
+    ImageRegenerationResponse,
+    ImageStorageUsageResponse,
+    ArchiveQueryResponse,
+    ApproveProductRequestResponse,
+    AdditionalImagesResponse,
+    SelfTestResponse,
+    SignedImageUrlResponse,
+    ProblemDocument,
+);
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_json_schema() {
+        for name in JSON_SCHEMA_NAMES {
+            assert!(json_schema(name).is_some(), "missing schema for {}", name);
+        }
+        assert_eq!(json_schema("Unknown"), None);
+
+        let schema = json_schema("ProductDescription").unwrap();
+        assert_eq!(schema["title"], "ProductDescription");
+        assert_eq!(
+            schema["$defs"]["ProductImage"]["properties"]["data"]["type"],
+            "string"
+        );
+        assert!(schema["$defs"]["Nutrients"]["properties"]["vitaminA"].is_object());
+
+        // the schema validates the serialized form of the types
+        let product: ProductDescription =
+            serde_json::from_str(include_str!("../../test_data/products.json"))
+                .map(|products: Vec<ProductDescription>| products[0].clone())
+                .unwrap();
+        let value = serde_json::to_value(&product).unwrap();
+        for required in schema["required"].as_array().unwrap() {
+            assert!(value.get(required.as_str().unwrap()).is_some());
+        }
+    }
+}
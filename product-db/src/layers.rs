@@ -0,0 +1,466 @@
+use std::{any::Any, time::Duration};
+
+use axum::{
+    body::Body,
+    extract::{Request, State},
+    http::{header, HeaderValue, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde_json::Value;
+use tracing::{error, field, info_span, Instrument};
+
+use crate::{service_json::ProblemDocument, CacheControlOptions, Error, Result};
+
+/// The content type of problem documents as defined in RFC 7807.
+const PROBLEM_CONTENT_TYPE: &str = "application/problem+json";
+
+/// The header that carries the id of the request.
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// The maximal size of the body of an error response that is extended by the request id.
+const MAX_ERROR_BODY_SIZE: usize = 64 * 1024;
+
+/// The Cache-Control directive for responses that must not be stored by any cache, e.g. the
+/// images of the admin endpoint.
+pub const PRIVATE_CACHE_CONTROL: &str = "private, no-store";
+
+tokio::task_local! {
+    /// The id of the request that is currently handled by the task.
+    static REQUEST_ID: String;
+}
+
+/// Returns the id of the request that is currently handled or "unknown" if it is not available.
+pub fn current_request_id() -> String {
+    try_current_request_id().unwrap_or_else(|| "unknown".to_string())
+}
+
+/// Returns the id of the request that is currently handled by the task, if any, e.g. for adding
+/// it to the log records of the handlers and the data backend.
+pub fn try_current_request_id() -> Option<String> {
+    REQUEST_ID.try_with(|id| id.clone()).ok()
+}
+
+/// Creates a response with a problem document for the given status code.
+///
+/// # Arguments
+/// - `status` - The status code of the response.
+/// - `detail` - The human readable explanation of the problem.
+pub fn problem_response(status: StatusCode, detail: String) -> Response {
+    let problem = ProblemDocument {
+        problem_type: "about:blank".to_string(),
+        title: status.canonical_reason().unwrap_or_default().to_string(),
+        status: status.as_u16(),
+        detail,
+        request_id: try_current_request_id(),
+    };
+
+    let mut response = (status, Json(problem)).into_response();
+    response.headers_mut().insert(
+        header::CONTENT_TYPE,
+        HeaderValue::from_static(PROBLEM_CONTENT_TYPE),
+    );
+
+    response
+}
+
+/// Middleware that makes the id of the request available to the inner layers and handlers,
+/// see [`current_request_id`]. The request is handled in a `request` span carrying its id,
+/// i.e., the spans of the data backend are nested in it, and the id is added to the JSON body
+/// of error responses.
+pub async fn request_id_scope(request: Request, next: Next) -> Response {
+    let request_id = request
+        .headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|id| id.to_str().ok())
+        .unwrap_or("unknown")
+        .to_string();
+
+    let span = info_span!(
+        "request",
+        request_id = %request_id,
+        method = %request.method(),
+        path = %request.uri().path(),
+        status = field::Empty
+    );
+    let response = REQUEST_ID
+        .scope(
+            request_id.clone(),
+            next.run(request).instrument(span.clone()),
+        )
+        .await;
+    span.record("status", response.status().as_u16());
+
+    echo_request_id(response, &request_id).await
+}
+
+/// Adds the request id to the JSON object in the body of an error response, such that clients
+/// can refer to it when reporting the failure. Other responses are returned unchanged.
+///
+/// # Arguments
+/// - `response` - The response of the request.
+/// - `request_id` - The id of the request.
+async fn echo_request_id(response: Response, request_id: &str) -> Response {
+    let is_json = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|content_type| content_type.to_str().ok())
+        .is_some_and(|content_type| {
+            content_type.starts_with("application/json")
+                || content_type.starts_with(PROBLEM_CONTENT_TYPE)
+        });
+    if !is_json || !(response.status().is_client_error() || response.status().is_server_error()) {
+        return response;
+    }
+
+    let (mut parts, body) = response.into_parts();
+    let body = match axum::body::to_bytes(body, MAX_ERROR_BODY_SIZE).await {
+        Ok(body) => body,
+        Err(err) => {
+            error!(
+                "Failed to read the error response [request-id={}]: {}",
+                request_id, err
+            );
+            parts.headers.remove(header::CONTENT_LENGTH);
+            return Response::from_parts(parts, Body::empty());
+        }
+    };
+
+    let body = match serde_json::from_slice::<Value>(&body) {
+        Ok(Value::Object(mut object)) if !object.contains_key("request_id") => {
+            object.insert("request_id".to_string(), Value::from(request_id));
+            parts.headers.remove(header::CONTENT_LENGTH);
+            Body::from(Value::Object(object).to_string())
+        }
+        _ => Body::from(body),
+    };
+
+    Response::from_parts(parts, body)
+}
+
+/// Handler for panics that occurred while handling a request. Logs the panic together with the
+/// request id and responds with a 500 problem document.
+///
+/// # Arguments
+/// - `err` - The payload of the panic.
+pub fn handle_panic(err: Box<dyn Any + Send + 'static>) -> Response {
+    let details = if let Some(s) = err.downcast_ref::<String>() {
+        s.as_str()
+    } else if let Some(s) = err.downcast_ref::<&str>() {
+        s
+    } else {
+        "unknown panic payload"
+    };
+
+    let request_id = current_request_id();
+    error!(
+        "Panic while handling request [request-id={}]: {}",
+        request_id, details
+    );
+
+    problem_response(
+        StatusCode::INTERNAL_SERVER_ERROR,
+        format!(
+            "An internal error occurred while handling the request [request-id={}]",
+            request_id
+        ),
+    )
+}
+
+/// The parsed Cache-Control headers for the different kinds of responses.
+#[derive(Debug, Clone, Default)]
+pub struct CachePolicy {
+    /// The header for responses with a single product or product request as JSON.
+    pub product: Option<HeaderValue>,
+
+    /// The header for the results of queries.
+    pub query: Option<HeaderValue>,
+
+    /// The header for images.
+    pub image: Option<HeaderValue>,
+}
+
+impl CachePolicy {
+    /// Creates the cache policy from the configured directives.
+    ///
+    /// # Arguments
+    /// - `options` - The configured Cache-Control directives.
+    pub fn from_options(options: &CacheControlOptions) -> Result<Self> {
+        Ok(Self {
+            product: Self::parse_directive(options.product.as_deref())?,
+            query: Self::parse_directive(options.query.as_deref())?,
+            image: Self::parse_directive(options.image.as_deref())?,
+        })
+    }
+
+    /// Parses a single Cache-Control directive.
+    ///
+    /// # Arguments
+    /// - `directive` - The directive to parse, if any.
+    fn parse_directive(directive: Option<&str>) -> Result<Option<HeaderValue>> {
+        directive
+            .map(|directive| {
+                HeaderValue::from_str(directive).map_err(|e| {
+                    error!("Invalid Cache-Control directive '{}': {}", directive, e);
+
+                    Error::ConfigError(format!(
+                        "Invalid Cache-Control directive '{}': {}",
+                        directive, e
+                    ))
+                })
+            })
+            .transpose()
+    }
+}
+
+/// Middleware that sets the given Cache-Control header on successful and not modified
+/// responses, unless the handler already set one. Error responses are never cached.
+pub async fn cache_control(
+    State(cache_control): State<Option<HeaderValue>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let mut response = next.run(request).await;
+
+    if let Some(cache_control) = cache_control {
+        let status = response.status();
+        if (status.is_success() || status == StatusCode::NOT_MODIFIED)
+            && !response.headers().contains_key(header::CACHE_CONTROL)
+        {
+            response
+                .headers_mut()
+                .insert(header::CACHE_CONTROL, cache_control);
+        }
+    }
+
+    response
+}
+
+/// Middleware that aborts the request with 504 if it is not handled within the given timeout.
+pub async fn timeout_guard(
+    State(timeout): State<Duration>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let method = request.method().clone();
+    let uri = request.uri().clone();
+
+    match tokio::time::timeout(timeout, next.run(request)).await {
+        Ok(response) => response,
+        Err(_) => {
+            error!(
+                "{} {} exceeded the timeout of {}ms",
+                method,
+                uri,
+                timeout.as_millis()
+            );
+
+            problem_response(
+                StatusCode::GATEWAY_TIMEOUT,
+                format!(
+                    "The request could not be handled within {}ms",
+                    timeout.as_millis()
+                ),
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use axum::{body::Body, middleware, routing::get, Router};
+    use tower::ServiceExt;
+    use tower_http::{
+        catch_panic::CatchPanicLayer,
+        request_id::{MakeRequestUuid, PropagateRequestIdLayer, SetRequestIdLayer},
+    };
+
+    use crate::service_json::OnlyMessageResponse;
+
+    use super::*;
+
+    async fn panicking_handler() -> &'static str {
+        panic!("handler panicked")
+    }
+
+    #[tokio::test]
+    async fn test_catch_panic() {
+        let app = Router::new()
+            .route("/panic", get(panicking_handler))
+            .layer(CatchPanicLayer::custom(handle_panic))
+            .layer(middleware::from_fn(request_id_scope))
+            .layer(PropagateRequestIdLayer::x_request_id())
+            .layer(SetRequestIdLayer::x_request_id(MakeRequestUuid));
+
+        let request = Request::builder()
+            .uri("/panic")
+            .header(REQUEST_ID_HEADER, "test-request")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(
+            response.headers().get(REQUEST_ID_HEADER).unwrap(),
+            "test-request"
+        );
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let problem: ProblemDocument = serde_json::from_slice(&body).unwrap();
+        assert_eq!(problem.status, 500);
+        assert!(problem.detail.contains("test-request"));
+        assert_eq!(problem.request_id.as_deref(), Some("test-request"));
+    }
+
+    #[tokio::test]
+    async fn test_echo_request_id() {
+        let app = Router::new()
+            .route(
+                "/invalid",
+                get(|| async {
+                    (
+                        StatusCode::BAD_REQUEST,
+                        Json(OnlyMessageResponse {
+                            message: "Invalid product".to_string(),
+                        }),
+                    )
+                }),
+            )
+            .route(
+                "/valid",
+                get(|| async {
+                    Json(OnlyMessageResponse {
+                        message: "Product added".to_string(),
+                    })
+                }),
+            )
+            .route(
+                "/text",
+                get(|| async { (StatusCode::NOT_FOUND, "Product not found") }),
+            )
+            .layer(middleware::from_fn(request_id_scope))
+            .layer(PropagateRequestIdLayer::x_request_id())
+            .layer(SetRequestIdLayer::x_request_id(MakeRequestUuid));
+
+        let get_body = |uri: &'static str| {
+            let app = app.clone();
+            async move {
+                let request = Request::builder()
+                    .uri(uri)
+                    .header(REQUEST_ID_HEADER, "test-request")
+                    .body(Body::empty())
+                    .unwrap();
+                let response = app.oneshot(request).await.unwrap();
+                assert_eq!(
+                    response.headers().get(REQUEST_ID_HEADER).unwrap(),
+                    "test-request"
+                );
+
+                let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+                    .await
+                    .unwrap();
+                String::from_utf8(body.to_vec()).unwrap()
+            }
+        };
+
+        let body: Value = serde_json::from_str(&get_body("/invalid").await).unwrap();
+        assert_eq!(
+            body,
+            serde_json::json!({"message": "Invalid product", "request_id": "test-request"})
+        );
+
+        let body: Value = serde_json::from_str(&get_body("/valid").await).unwrap();
+        assert_eq!(body, serde_json::json!({"message": "Product added"}));
+
+        assert_eq!(get_body("/text").await, "Product not found");
+    }
+
+    #[tokio::test]
+    async fn test_cache_control() {
+        let policy = CachePolicy::from_options(&CacheControlOptions {
+            image: Some("public, max-age=2592000, immutable".to_string()),
+            ..Default::default()
+        })
+        .unwrap();
+        assert!(policy.product.is_none());
+
+        let app = Router::new()
+            .route("/image", get(|| async { "image" }))
+            .route(
+                "/missing",
+                get(|| async { (StatusCode::NOT_FOUND, "missing") }),
+            )
+            .route("/unchanged", get(|| async { StatusCode::NOT_MODIFIED }))
+            .route_layer(middleware::from_fn_with_state(policy.image, cache_control));
+
+        let request = Request::builder()
+            .uri("/image")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(
+            response.headers().get(header::CACHE_CONTROL).unwrap(),
+            "public, max-age=2592000, immutable"
+        );
+
+        let request = Request::builder()
+            .uri("/unchanged")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(
+            response.headers().get(header::CACHE_CONTROL).unwrap(),
+            "public, max-age=2592000, immutable"
+        );
+
+        let request = Request::builder()
+            .uri("/missing")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        assert!(response.headers().get(header::CACHE_CONTROL).is_none());
+
+        let invalid = CacheControlOptions {
+            query: Some("no-store\n".to_string()),
+            ..Default::default()
+        };
+        assert!(CachePolicy::from_options(&invalid).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_timeout_guard() {
+        let app = Router::new()
+            .route(
+                "/slow",
+                get(|| async {
+                    tokio::time::sleep(Duration::from_millis(500)).await;
+                    "slow"
+                }),
+            )
+            .route("/fast", get(|| async { "fast" }))
+            .route_layer(middleware::from_fn_with_state(
+                Duration::from_millis(50),
+                timeout_guard,
+            ));
+
+        let request = Request::builder().uri("/fast").body(Body::empty()).unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let request = Request::builder().uri("/slow").body(Body::empty()).unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::GATEWAY_TIMEOUT);
+        assert_eq!(
+            response.headers().get(header::CONTENT_TYPE).unwrap(),
+            PROBLEM_CONTENT_TYPE
+        );
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let problem: ProblemDocument = serde_json::from_slice(&body).unwrap();
+        assert_eq!(problem.status, 504);
+    }
+}
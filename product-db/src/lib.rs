@@ -1,24 +1,172 @@
+mod allergen;
+#[cfg(feature = "service")]
+mod auth;
+#[cfg(feature = "service")]
+mod content_negotiation;
 mod data_backend;
+#[cfg(feature = "service")]
+mod deprecation;
+#[cfg(feature = "service")]
+mod dyn_backend;
+mod eco_score;
+#[cfg(feature = "service")]
 mod error;
+#[cfg(feature = "service")]
+mod export;
+#[cfg(feature = "generator")]
+mod generator;
+mod gtin;
+#[cfg(feature = "service")]
+mod http_range;
+#[cfg(feature = "service")]
+mod image_processing;
+#[cfg(feature = "service")]
+mod image_store;
+#[cfg(feature = "service")]
+mod image_url;
+#[cfg(feature = "service")]
+mod ip_allowlist;
+#[cfg(feature = "json-schema")]
+mod json_schema;
+#[cfg(feature = "service")]
+mod layers;
+#[cfg(feature = "service")]
+mod memory_backend;
+#[cfg(feature = "service")]
+mod metrics;
+#[cfg(feature = "service")]
+mod mirrored_backend;
+#[cfg(feature = "mongodb")]
+mod mongo;
+mod nutrition;
+#[cfg(feature = "service")]
+mod openapi;
+#[cfg(feature = "service")]
 mod options;
+mod packaging;
+#[cfg(feature = "service")]
+mod pagination;
+#[cfg(feature = "service")]
+mod partitioning;
+mod plausibility;
+#[cfg(feature = "service")]
 mod postgres;
+mod price;
+#[cfg(feature = "service")]
+mod product_csv;
+mod projection;
+#[cfg(feature = "protobuf")]
+pub mod proto;
+#[cfg(feature = "service")]
+mod rate_limit;
+#[cfg(feature = "service")]
+mod read_only_backend;
+mod recipe;
+#[cfg(feature = "service")]
+mod request_signing;
+#[cfg(feature = "service")]
 mod secret;
+#[cfg(feature = "service")]
+mod secret_provider;
+mod self_test;
+#[cfg(feature = "service")]
 mod service;
+#[cfg(feature = "service")]
+mod service_builder;
 pub mod service_json;
+#[cfg(feature = "service")]
 mod sql_types;
-
-use std::fmt::Display;
+mod store;
+#[cfg(feature = "service")]
+mod tls;
+#[cfg(feature = "typescript")]
+mod typescript;
+mod units;
+
+use std::{
+    fmt::Display,
+    ops::{Add, Mul, Sub},
+};
 
 use ::serde::{Deserialize, Serialize};
 use chrono::{DateTime, Utc};
 use serde_with::{base64::Base64, serde_as};
 
+pub use allergen::*;
+#[cfg(feature = "service")]
+pub use auth::*;
+#[cfg(feature = "service")]
+pub use content_negotiation::*;
 pub use data_backend::*;
+#[cfg(feature = "service")]
+pub use deprecation::*;
+#[cfg(feature = "service")]
+pub use dyn_backend::*;
+pub use eco_score::*;
+#[cfg(feature = "service")]
 pub use error::*;
+#[cfg(feature = "service")]
+pub use export::*;
+#[cfg(feature = "generator")]
+pub use generator::*;
+pub use gtin::*;
+#[cfg(feature = "service")]
+pub use image_processing::*;
+#[cfg(feature = "service")]
+pub use image_store::*;
+#[cfg(feature = "service")]
+pub use image_url::*;
+#[cfg(feature = "service")]
+pub use ip_allowlist::*;
+#[cfg(feature = "json-schema")]
+pub use json_schema::*;
+#[cfg(feature = "service")]
+pub use layers::{current_request_id, try_current_request_id, REQUEST_ID_HEADER};
+#[cfg(feature = "service")]
+pub use memory_backend::*;
+#[cfg(feature = "service")]
+pub use metrics::*;
+#[cfg(feature = "service")]
+pub use mirrored_backend::*;
+#[cfg(feature = "mongodb")]
+pub use mongo::*;
+pub use nutrition::*;
+#[cfg(feature = "service")]
+pub use openapi::*;
+#[cfg(feature = "service")]
 pub use options::*;
+pub use packaging::*;
+#[cfg(feature = "service")]
+pub use pagination::*;
+pub use plausibility::*;
+#[cfg(feature = "service")]
 pub use postgres::*;
+pub use price::*;
+#[cfg(feature = "service")]
+pub use product_csv::*;
+pub use projection::*;
+#[cfg(feature = "service")]
+pub use rate_limit::*;
+#[cfg(feature = "service")]
+pub use read_only_backend::*;
+pub use recipe::*;
+#[cfg(feature = "service")]
+pub use request_signing::*;
+#[cfg(feature = "service")]
 pub use secret::*;
+#[cfg(feature = "service")]
+pub use secret_provider::*;
+pub use self_test::*;
+#[cfg(feature = "service")]
 pub use service::*;
+#[cfg(feature = "service")]
+pub use service_builder::*;
+pub use store::*;
+#[cfg(feature = "service")]
+pub use tls::*;
+#[cfg(feature = "typescript")]
+pub use typescript::*;
+pub use units::*;
 
 /// The id of a single product
 pub type ProductID = String;
@@ -27,6 +175,8 @@ pub type ProductID = String;
 /// This is the full information about a product consisting of the product id, name, producer,
 /// nutrients, and images.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct ProductDescription {
     /// The general information about the product.
     pub info: ProductInfo,
@@ -39,10 +189,20 @@ pub struct ProductDescription {
 
     /// The nutrients of the product.
     pub nutrients: Nutrients,
+
+    /// The packaging of the product, e.g. for shopping lists and sustainability scores.
+    #[serde(default)]
+    pub packaging: Option<Packaging>,
+
+    /// The environmental impact of the product.
+    #[serde(default)]
+    pub eco_score: Option<EcoScore>,
 }
 
 /// The information about a product.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct ProductInfo {
     /// The id of the product. Can be EAN, GTIN, or any other unique identifier.
     pub id: ProductID,
@@ -65,6 +225,50 @@ pub struct ProductInfo {
     /// The ratio between volume and weight, i.e. volume(ml) = weight(g) * volume_weight_ratio
     /// Is only defined if the quantity type is volume
     pub volume_weight_ratio: Option<f32>,
+
+    /// The market the product is sold in as ISO 3166-1 alpha-2 country code, e.g. "DE".
+    /// Products of the same brand may have a different recipe per market.
+    #[serde(default)]
+    pub market: Option<String>,
+
+    /// The dietary labels of the product, e.g. vegan or gluten-free.
+    #[serde(default)]
+    pub labels: DietaryLabels,
+
+    /// The NOVA group of the product from 1 (unprocessed or minimally processed) to 4
+    /// (ultra-processed).
+    #[serde(default)]
+    pub nova_group: Option<i32>,
+
+    /// The allergens contained in the product.
+    #[serde(default)]
+    pub allergens: Vec<Allergen>,
+}
+
+/// The dietary labels of a product. Every label is either true, false or unknown if not set.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct DietaryLabels {
+    /// The product contains no animal products.
+    #[serde(default)]
+    pub vegan: Option<bool>,
+
+    /// The product contains no meat or fish.
+    #[serde(default)]
+    pub vegetarian: Option<bool>,
+
+    /// The product is certified organic.
+    #[serde(default)]
+    pub organic: Option<bool>,
+
+    /// The product contains no gluten.
+    #[serde(default)]
+    pub gluten_free: Option<bool>,
+
+    /// The product contains no lactose.
+    #[serde(default)]
+    pub lactose_free: Option<bool>,
 }
 
 impl Display for ProductInfo {
@@ -81,7 +285,10 @@ impl Display for ProductInfo {
 
 /// A image of the product. Can be a preview or full image of the product.
 #[serde_as]
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, sqlx::FromRow)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "service", derive(sqlx::FromRow))]
 pub struct ProductImage {
     #[serde(rename = "contentType")]
     /// The content type of the preview image.
@@ -89,31 +296,230 @@ pub struct ProductImage {
 
     /// The base64 encoded image.
     #[serde_as(as = "Base64")]
+    #[cfg_attr(feature = "typescript", ts(type = "string"))]
+    #[cfg_attr(feature = "json-schema", schemars(with = "String"))]
     pub data: Vec<u8>,
 }
 
+/// The role of an additional image of a product, i.e., what the image shows.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+#[cfg_attr(
+    feature = "service",
+    derive(sqlx::Type),
+    sqlx(type_name = "ImageRole", rename_all = "lowercase")
+)]
+#[serde(rename_all = "snake_case")]
+pub enum ImageRole {
+    /// The front of the packaging.
+    Front,
+    /// The nutrition table.
+    Nutrition,
+    /// The list of ingredients.
+    Ingredients,
+    /// Any other side of the packaging, e.g. with the recycling information.
+    Packaging,
+    /// Anything else.
+    Other,
+}
+
+impl Display for ImageRole {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ImageRole::Front => write!(f, "front"),
+            ImageRole::Nutrition => write!(f, "nutrition"),
+            ImageRole::Ingredients => write!(f, "ingredients"),
+            ImageRole::Packaging => write!(f, "packaging"),
+            ImageRole::Other => write!(f, "other"),
+        }
+    }
+}
+
+/// An additional image of a product or product request besides its preview and full image,
+/// e.g. a photo of the nutrition table or of the ingredient list.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct AdditionalImage {
+    /// What the image shows.
+    pub role: ImageRole,
+
+    /// The image.
+    pub image: ProductImage,
+}
+
+/// The storage used by the images of a single content type.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct ImageStorageUsage {
+    /// The content type of the images, e.g. "image/jpeg".
+    pub content_type: String,
+
+    /// The number of stored images.
+    #[cfg_attr(feature = "typescript", ts(type = "number"))]
+    pub images: u64,
+
+    /// The total size of the stored images in bytes.
+    #[cfg_attr(feature = "typescript", ts(type = "number"))]
+    pub bytes: u64,
+}
+
 /// A request to add a new product to the database.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct ProductRequest {
     /// The information about the product.
     pub product_description: ProductDescription,
 
     /// The date when the product has been requested to be added.
     pub date: DateTime<Utc>,
+
+    /// The review status of the product request.
+    #[serde(default)]
+    pub status: RequestStatus,
+
+    /// The comment of the reviewer, e.g. why the product request has been rejected.
+    #[serde(default)]
+    pub review_comment: Option<String>,
+}
+
+/// The review status of a product request.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+#[cfg_attr(
+    feature = "service",
+    derive(sqlx::Type),
+    sqlx(type_name = "RequestStatus", rename_all = "lowercase")
+)]
+pub enum RequestStatus {
+    /// The product request has not been reviewed yet.
+    #[default]
+    #[serde(rename = "pending")]
+    Pending,
+
+    /// The product request has been approved.
+    #[serde(rename = "approved")]
+    Approved,
+
+    /// The product request has been rejected, e.g. because of missing or wrong information.
+    #[serde(rename = "rejected")]
+    Rejected,
+}
+
+impl Display for RequestStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RequestStatus::Pending => write!(f, "pending"),
+            RequestStatus::Approved => write!(f, "approved"),
+            RequestStatus::Rejected => write!(f, "rejected"),
+        }
+    }
+}
+
+/// The outcome of a processed product request.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+#[cfg_attr(
+    feature = "service",
+    derive(sqlx::Type),
+    sqlx(type_name = "RequestOutcome", rename_all = "lowercase")
+)]
+pub enum RequestOutcome {
+    /// The requested product has been added to the database.
+    #[serde(rename = "approved")]
+    Approved,
+
+    /// The requested product has not been added to the database.
+    #[serde(rename = "rejected")]
+    Rejected,
+}
+
+impl Display for RequestOutcome {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RequestOutcome::Approved => write!(f, "approved"),
+            RequestOutcome::Rejected => write!(f, "rejected"),
+        }
+    }
+}
+
+/// A processed product request that has been moved to the archive.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "service", derive(sqlx::FromRow))]
+pub struct ArchivedProductRequest {
+    /// The original id of the product request.
+    pub id: DBId,
+
+    /// The id of the requested product.
+    pub product_id: ProductID,
+
+    /// The name of the requested product.
+    pub name: String,
+
+    /// The producer of the requested product.
+    pub producer: Option<String>,
+
+    /// The date when the product has been requested to be added.
+    pub date: DateTime<Utc>,
+
+    /// The date when the product request has been archived.
+    pub archived_date: DateTime<Utc>,
+
+    /// The outcome of the product request.
+    pub outcome: RequestOutcome,
+
+    /// The comment of the reviewer, e.g. why the product request has been rejected.
+    pub review_comment: Option<String>,
 }
 
 /// A missing product report.
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, sqlx::FromRow)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "service", derive(sqlx::FromRow))]
 pub struct MissingProduct {
     /// The id of the missing product.
     pub product_id: ProductID,
 
     /// The date when the product has been reported as missing.
     pub date: DateTime<Utc>,
+
+    /// The store where the product has been scanned (optional).
+    #[serde(default)]
+    pub store_id: Option<DBId>,
+}
+
+/// The reports of a missing product grouped by its id, e.g. for finding the most requested
+/// missing products.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct MissingProductSummary {
+    /// The id of the missing product.
+    pub product_id: ProductID,
+
+    /// The number of reports of the missing product.
+    #[cfg_attr(feature = "typescript", ts(type = "number"))]
+    pub reports: u64,
+
+    /// The date of the first report of the missing product.
+    pub first_reported: DateTime<Utc>,
+
+    /// The date of the last report of the missing product.
+    pub last_reported: DateTime<Utc>,
 }
 
 /// The nutrients of a single product expressed for a reference quantity of 100g.
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Default, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct Nutrients {
     pub kcal: f32,
 
@@ -140,8 +546,77 @@ pub struct Nutrients {
     pub zinc: Option<Weight>,
 }
 
+impl Nutrients {
+    /// Returns the nutrients per 100g or 100ml of nutrients that are stated for the given
+    /// reference amount, e.g. for a serving of 30g.
+    ///
+    /// # Arguments
+    /// - `reference_amount` - The amount in g or ml the nutrients are stated for.
+    pub fn normalized(&self, reference_amount: f32) -> Self {
+        self.scale(100.0 / reference_amount)
+    }
+
+    /// Returns the nutrients multiplied by the given factor, e.g. the nutrients of a portion
+    /// of 250g are the nutrients per 100g scaled by 2.5. Missing nutrients remain missing.
+    ///
+    /// # Arguments
+    /// - `factor` - The factor to multiply the nutrients with.
+    pub fn scale(&self, factor: f32) -> Self {
+        let scale = |weight: Option<Weight>| weight.map(|w| w * factor);
+
+        Self {
+            kcal: self.kcal * factor,
+            protein: scale(self.protein),
+            fat: scale(self.fat),
+            carbohydrates: scale(self.carbohydrates),
+            sugar: scale(self.sugar),
+            salt: scale(self.salt),
+            vitamin_a: scale(self.vitamin_a),
+            vitamin_c: scale(self.vitamin_c),
+            vitamin_d: scale(self.vitamin_d),
+            iron: scale(self.iron),
+            calcium: scale(self.calcium),
+            magnesium: scale(self.magnesium),
+            sodium: scale(self.sodium),
+            zinc: scale(self.zinc),
+        }
+    }
+}
+
+impl Add for Nutrients {
+    type Output = Self;
+
+    /// Adds up the nutrients, e.g. for the total of a meal. A nutrient that is missing on one
+    /// side counts as zero, i.e., the sum is only missing if it is missing on both sides.
+    fn add(self, rhs: Self) -> Self {
+        let add = |lhs: Option<Weight>, rhs: Option<Weight>| match (lhs, rhs) {
+            (Some(lhs), Some(rhs)) => Some(lhs + rhs),
+            (lhs, rhs) => lhs.or(rhs),
+        };
+
+        Self {
+            kcal: self.kcal + rhs.kcal,
+            protein: add(self.protein, rhs.protein),
+            fat: add(self.fat, rhs.fat),
+            carbohydrates: add(self.carbohydrates, rhs.carbohydrates),
+            sugar: add(self.sugar, rhs.sugar),
+            salt: add(self.salt, rhs.salt),
+            vitamin_a: add(self.vitamin_a, rhs.vitamin_a),
+            vitamin_c: add(self.vitamin_c, rhs.vitamin_c),
+            vitamin_d: add(self.vitamin_d, rhs.vitamin_d),
+            iron: add(self.iron, rhs.iron),
+            calcium: add(self.calcium, rhs.calcium),
+            magnesium: add(self.magnesium, rhs.magnesium),
+            sodium: add(self.sodium, rhs.sodium),
+            zinc: add(self.zinc, rhs.zinc),
+        }
+    }
+}
+
 /// Weight unit
 #[derive(Clone, Copy, Debug, PartialEq, PartialOrd, Serialize, Deserialize)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct Weight {
     /// The weight value expressed in gram
     pub value: f32,
@@ -180,6 +655,30 @@ impl Weight {
     }
 }
 
+impl Add for Weight {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self::new_from_gram(self.value + rhs.value)
+    }
+}
+
+impl Sub for Weight {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        Self::new_from_gram(self.value - rhs.value)
+    }
+}
+
+impl Mul<f32> for Weight {
+    type Output = Self;
+
+    fn mul(self, factor: f32) -> Self {
+        Self::new_from_gram(self.value * factor)
+    }
+}
+
 /// Volume unit
 #[derive(Clone, Copy, Debug, PartialEq, PartialOrd, Serialize, Deserialize)]
 pub struct Volume {
@@ -205,6 +704,36 @@ impl Volume {
     }
 }
 
+impl Add for Volume {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self {
+            value: self.value + rhs.value,
+        }
+    }
+}
+
+impl Sub for Volume {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        Self {
+            value: self.value - rhs.value,
+        }
+    }
+}
+
+impl Mul<f32> for Volume {
+    type Output = Self;
+
+    fn mul(self, factor: f32) -> Self {
+        Self {
+            value: self.value * factor,
+        }
+    }
+}
+
 #[derive(Clone, Copy, Debug, PartialEq, PartialOrd, Serialize, Deserialize)]
 pub struct QuantityInnerValue {
     pub value: f32,
@@ -231,10 +760,14 @@ impl QuantityInner {
 }
 
 /// The quantity in which the product details are expressed
-#[derive(
-    Debug, sqlx::Type, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash,
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+#[cfg_attr(
+    feature = "service",
+    derive(sqlx::Type),
+    sqlx(type_name = "QuantityType", rename_all = "lowercase")
 )]
-#[sqlx(type_name = "QuantityType", rename_all = "lowercase")]
 pub enum QuantityType {
     #[serde(rename = "weight")]
     Weight,
@@ -273,4 +806,62 @@ mod test {
             }
         }
     }
+
+    #[test]
+    fn test_normalized_nutrients() {
+        let product_data = include_str!("../../test_data/products.json");
+        let products: Vec<ProductDescription> = serde_json::from_str(product_data).unwrap();
+        let nutrients = &products[0].nutrients;
+
+        // nutrients per serving of 30g
+        let normalized = nutrients.normalized(30.0);
+        assert!((normalized.kcal - nutrients.kcal * 100.0 / 30.0).abs() < 1e-3);
+        assert_eq!(normalized.protein.is_some(), nutrients.protein.is_some());
+        if let (Some(lhs), Some(rhs)) = (normalized.protein, nutrients.protein) {
+            assert!((lhs.gram() - rhs.gram() * 100.0 / 30.0).abs() < 1e-3);
+        }
+        assert_eq!(normalized.zinc.is_some(), nutrients.zinc.is_some());
+
+        // nutrients per 100g are unchanged
+        assert_eq!(&nutrients.normalized(100.0), nutrients);
+    }
+
+    #[test]
+    fn test_nutrient_arithmetic() {
+        let weight = Weight::new_from_gram(2.0) + Weight::new_from_milligram(500.0);
+        assert_eq!(weight, Weight::new_from_gram(2.5));
+        assert_eq!(
+            weight - Weight::new_from_gram(0.5),
+            Weight::new_from_gram(2.0)
+        );
+        assert_eq!(weight * 2.0, Weight::new_from_gram(5.0));
+
+        let volume = Volume::new_from_millilitre(250.0) + Volume::new_from_millilitre(250.0);
+        assert_eq!(volume.millilitre(), 500.0);
+        assert_eq!(
+            (volume - Volume::new_from_millilitre(100.0)).millilitre(),
+            400.0
+        );
+        assert_eq!((volume * 0.5).millilitre(), 250.0);
+
+        let mut lhs: Nutrients = serde_json::from_str(r#"{"kcal": 100.0}"#).unwrap();
+        lhs.protein = Some(Weight::new_from_gram(10.0));
+        lhs.fat = Some(Weight::new_from_gram(4.0));
+        let mut rhs: Nutrients = serde_json::from_str(r#"{"kcal": 50.0}"#).unwrap();
+        rhs.protein = Some(Weight::new_from_gram(2.0));
+        rhs.sugar = Some(Weight::new_from_gram(1.0));
+
+        // a nutrient missing on one side counts as zero
+        let total = lhs.clone() + rhs;
+        assert_eq!(total.kcal, 150.0);
+        assert_eq!(total.protein, Some(Weight::new_from_gram(12.0)));
+        assert_eq!(total.fat, Some(Weight::new_from_gram(4.0)));
+        assert_eq!(total.sugar, Some(Weight::new_from_gram(1.0)));
+        assert_eq!(total.salt, None);
+
+        let scaled = lhs.scale(2.5);
+        assert_eq!(scaled.kcal, 250.0);
+        assert_eq!(scaled.protein, Some(Weight::new_from_gram(25.0)));
+        assert_eq!(scaled.salt, None);
+    }
 }
@@ -0,0 +1,1896 @@
+use std::{
+    cmp::Ordering,
+    collections::{HashMap, HashSet},
+    sync::{RwLock, RwLockReadGuard, RwLockWriteGuard},
+};
+
+use chrono::{DateTime, Utc};
+use log::{debug, info};
+
+use crate::{
+    normalize_gtin, AdditionalImage, ArchiveQuery, ArchivedProductRequest, CursorValue, DBId,
+    DataBackend, Error, HealthReport, HealthStatus, ImageStorageUsage, MissingProduct,
+    MissingProductQuery, MissingProductSummary, Options, PriceObservation, PriceQuery,
+    ProductAvailability, ProductCursor, ProductDataIssue, ProductDescription, ProductID,
+    ProductImage, ProductQuery, ProductRequest, ReadBackend, Recipe, RequestOutcome, RequestStatus,
+    Result, SearchFilter, SearchTermMode, Secret, Sorting, SortingField, SortingOrder, Store,
+    StoreQuery, WriteBackend,
+};
+
+/// The maximum limit for the query results.
+const LIMIT_MAX: i32 = 200;
+
+/// In-memory implementation of the data backend, e.g. for unit-testing code that depends on
+/// the [`crate::Service`] without a database or for demos. The data is lost when the backend
+/// is dropped. The query results are deterministic, i.e., unsorted queries are ordered by the
+/// internal ids and ties of sorted queries are broken like in the Postgres backend.
+#[derive(Default)]
+pub struct MemoryBackend {
+    state: RwLock<MemoryState>,
+}
+
+/// The data of the in-memory backend.
+#[derive(Default)]
+struct MemoryState {
+    products: HashMap<ProductID, StoredProduct>,
+    product_requests: HashMap<DBId, StoredProductRequest>,
+    archived_product_requests: HashMap<DBId, ArchivedProductRequest>,
+    missing_products: HashMap<DBId, StoredMissingProduct>,
+    recipes: HashMap<DBId, Recipe>,
+    prices: HashMap<DBId, PriceObservation>,
+    stores: HashMap<DBId, Store>,
+    availability: HashMap<(DBId, ProductID), ProductAvailability>,
+
+    /// The last assigned ids, which are never reused.
+    last_missing_product_id: DBId,
+    last_product_request_id: DBId,
+    last_recipe_id: DBId,
+    last_price_id: DBId,
+    last_store_id: DBId,
+}
+
+/// A product or requested product including its images.
+#[derive(Clone)]
+struct StoredProduct {
+    description: ProductDescription,
+    images: Vec<AdditionalImage>,
+}
+
+/// A product request as stored in the backend.
+struct StoredProductRequest {
+    product: StoredProduct,
+    date: DateTime<Utc>,
+    status: RequestStatus,
+    review_comment: Option<String>,
+}
+
+impl StoredProductRequest {
+    /// Returns the product request with or without the preview.
+    ///
+    /// # Arguments
+    /// * `with_preview` - Whether to include the preview.
+    fn describe(&self, with_preview: bool) -> ProductRequest {
+        ProductRequest {
+            product_description: self.product.describe(with_preview),
+            date: self.date,
+            status: self.status,
+            review_comment: self.review_comment.clone(),
+        }
+    }
+
+    /// Checks whether the product request has the review status of the given query, if any.
+    ///
+    /// # Arguments
+    /// * `query` - The query with the review status to filter for.
+    fn has_status(&self, query: &ProductQuery) -> bool {
+        query
+            .request_status
+            .is_none_or(|status| status == self.status)
+    }
+}
+
+/// A missing product report as stored in the backend.
+struct StoredMissingProduct {
+    missing_product: MissingProduct,
+    photo: Option<ProductImage>,
+}
+
+/// A product matching a query together with the values it is sorted by.
+struct Candidate<'a, K> {
+    key: K,
+    product: &'a StoredProduct,
+    date: Option<DateTime<Utc>>,
+    score: Option<f32>,
+}
+
+impl StoredProduct {
+    /// Creates the stored product of the given description.
+    ///
+    /// # Arguments
+    /// * `description` - The description of the product.
+    fn new(description: &ProductDescription) -> Self {
+        Self {
+            description: description.clone(),
+            images: Vec::new(),
+        }
+    }
+
+    /// Returns the description of the product without its full image, which is served
+    /// separately, and with its preview only if requested.
+    ///
+    /// # Arguments
+    /// * `with_preview` - Whether to include the preview image.
+    fn describe(&self, with_preview: bool) -> ProductDescription {
+        let mut description = self.description.clone();
+        description.full_image = None;
+        if !with_preview {
+            description.preview = None;
+        }
+
+        description
+    }
+
+    /// Returns all images of the product, e.g. for computing the storage usage.
+    fn all_images(&self) -> impl Iterator<Item = &ProductImage> {
+        self.description
+            .preview
+            .iter()
+            .chain(self.description.full_image.iter())
+            .chain(self.images.iter().map(|image| &image.image))
+    }
+}
+
+/// Returns the next id of a sequence.
+///
+/// # Arguments
+/// * `last_id` - The last assigned id of the sequence.
+fn next_id(last_id: &mut DBId) -> DBId {
+    *last_id += 1;
+    *last_id
+}
+
+/// Returns the page of the given sorted items.
+///
+/// # Arguments
+/// * `items` - The sorted items.
+/// * `offset` - The offset of the page.
+/// * `limit` - The limit of the page, which is capped at [`LIMIT_MAX`].
+fn page<T>(items: impl IntoIterator<Item = T>, offset: i32, limit: i32) -> Vec<T> {
+    items
+        .into_iter()
+        .skip(offset.max(0) as usize)
+        .take(limit.clamp(0, LIMIT_MAX) as usize)
+        .collect()
+}
+
+/// Applies the sorting order to an ordering in ascending order.
+///
+/// # Arguments
+/// * `ordering` - The ordering in ascending order.
+/// * `order` - The sorting order.
+fn apply_order(ordering: Ordering, order: SortingOrder) -> Ordering {
+    match order {
+        SortingOrder::Ascending => ordering,
+        SortingOrder::Descending => ordering.reverse(),
+    }
+}
+
+/// Returns the name and producer of the product in lower case as matched by the search.
+///
+/// # Arguments
+/// * `product` - The description of the product.
+fn name_producer(product: &ProductDescription) -> String {
+    format!(
+        "{} {}",
+        product.info.name,
+        product.info.producer.as_deref().unwrap_or_default()
+    )
+    .to_lowercase()
+}
+
+/// Returns the trigrams of the words of the given text like the `pg_trgm` extension of
+/// Postgres, i.e., every word is padded by two spaces in front and one space at the end.
+///
+/// # Arguments
+/// * `text` - The text to split into trigrams.
+fn trigrams(text: &str) -> HashSet<[char; 3]> {
+    let mut trigrams = HashSet::new();
+    for word in text
+        .to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+    {
+        let padded: Vec<char> = "  ".chars().chain(word.chars()).chain([' ']).collect();
+        for window in padded.windows(3) {
+            trigrams.insert([window[0], window[1], window[2]]);
+        }
+    }
+
+    trigrams
+}
+
+/// Returns the similarity from 0 to 1 of the given texts based on their shared trigrams.
+///
+/// # Arguments
+/// * `lhs` - The first text.
+/// * `rhs` - The second text.
+fn similarity(lhs: &str, rhs: &str) -> f32 {
+    let lhs = trigrams(lhs);
+    let rhs = trigrams(rhs);
+    let shared = lhs.intersection(&rhs).count();
+    let total = lhs.len() + rhs.len() - shared;
+    if total == 0 {
+        0.0
+    } else {
+        shared as f32 / total as f32
+    }
+}
+
+/// Returns true if the product has any of the given data quality issues.
+///
+/// # Arguments
+/// * `product` - The description of the product.
+/// * `data_issues` - The data quality issues.
+fn has_data_issue(product: &ProductDescription, data_issues: &[ProductDataIssue]) -> bool {
+    data_issues.iter().any(|issue| match issue {
+        ProductDataIssue::MissingImage => product.preview.is_none() && product.full_image.is_none(),
+        ProductDataIssue::MissingMacroNutrients => {
+            let nutrients = &product.nutrients;
+            nutrients.protein.is_none()
+                || nutrients.fat.is_none()
+                || nutrients.carbohydrates.is_none()
+        }
+        ProductDataIssue::MissingProducer => product
+            .info
+            .producer
+            .as_deref()
+            .is_none_or(|producer| producer.trim().is_empty()),
+    })
+}
+
+/// Returns true if the producer matches the pattern of a producer filter case-insensitively,
+/// where a `*` of the pattern matches any characters.
+///
+/// # Arguments
+/// * `pattern` - The pattern of the producer filter.
+/// * `producer` - The producer of the product.
+fn producer_matches(pattern: &str, producer: &str) -> bool {
+    let pattern = pattern.to_lowercase();
+    let producer = producer.to_lowercase();
+    let mut parts = pattern.split('*');
+
+    // the parts before the first and after the last wildcard are anchored
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = producer.strip_prefix(first) else {
+        return false;
+    };
+    let mut parts: Vec<&str> = parts.collect();
+    let Some(last) = parts.pop() else {
+        return rest.is_empty();
+    };
+    for part in parts {
+        match rest.find(part) {
+            Some(i) => rest = &rest[i + part.len()..],
+            None => return false,
+        }
+    }
+
+    rest.ends_with(last)
+}
+
+/// Returns true if the product of the candidate comes after the cursor in the order of its
+/// sorting, where products without sorted value come last like in [`sort_candidates`].
+///
+/// # Arguments
+/// * `candidate` - The candidate product.
+/// * `cursor` - The cursor of the last product of the previous page.
+fn is_after_cursor(candidate: &Candidate<'_, &ProductID>, cursor: &ProductCursor) -> bool {
+    let description = &candidate.product.description;
+    let value = ProductCursor::after(cursor.sorting, description, candidate.score).value;
+    let ordering = match (&value, &cursor.value) {
+        (Some(CursorValue::Integer(lhs)), Some(CursorValue::Integer(rhs))) => lhs.cmp(rhs),
+        (Some(CursorValue::Number(lhs)), Some(CursorValue::Number(rhs))) => lhs.total_cmp(rhs),
+        (Some(CursorValue::Text(lhs)), Some(CursorValue::Text(rhs))) => lhs.cmp(rhs),
+        (lhs, rhs) => lhs.is_none().cmp(&rhs.is_none()),
+    };
+
+    apply_order(
+        ordering.then_with(|| description.info.id.cmp(&cursor.product_id)),
+        cursor.sorting.order,
+    ) == Ordering::Greater
+}
+
+/// Returns the offset of the product query, which is replaced by the cursor if defined.
+///
+/// # Arguments
+/// * `query` - The product query.
+fn product_offset(query: &ProductQuery) -> i32 {
+    if query.cursor.is_some() {
+        0
+    } else {
+        query.offset
+    }
+}
+
+/// Sorts the candidates of a query. Unsorted queries are ordered by the keys of the candidates
+/// and ties are broken by the keys in the same order.
+///
+/// # Arguments
+/// * `candidates` - The candidates matching the query.
+/// * `sorting` - The sorting of the query, if any.
+fn sort_candidates<K: Ord>(candidates: &mut [Candidate<'_, K>], sorting: Option<&Sorting>) {
+    let Some(sorting) = sorting else {
+        candidates.sort_by(|lhs, rhs| lhs.key.cmp(&rhs.key));
+        return;
+    };
+
+    candidates.sort_by(|lhs, rhs| {
+        let (lhs_info, rhs_info) = (&lhs.product.description.info, &rhs.product.description.info);
+        let ordering = match sorting.field {
+            SortingField::ReportedDate => lhs.date.cmp(&rhs.date),
+            SortingField::Name => lhs_info.name.cmp(&rhs_info.name),
+            SortingField::ProductID => lhs_info.id.cmp(&rhs_info.id),
+            SortingField::Similarity => lhs
+                .score
+                .unwrap_or_default()
+                .total_cmp(&rhs.score.unwrap_or_default()),
+            // the products without NOVA group come last in ascending order like in Postgres
+            SortingField::NovaGroup => (lhs_info.nova_group.is_none(), lhs_info.nova_group)
+                .cmp(&(rhs_info.nova_group.is_none(), rhs_info.nova_group)),
+        };
+
+        apply_order(ordering.then_with(|| lhs.key.cmp(&rhs.key)), sorting.order)
+    });
+}
+
+impl MemoryBackend {
+    /// Creates a new empty in-memory backend.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the data for reading.
+    fn read(&self) -> RwLockReadGuard<'_, MemoryState> {
+        self.state
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Returns the data for writing.
+    fn write(&self) -> RwLockWriteGuard<'_, MemoryState> {
+        self.state
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl MemoryState {
+    /// Returns true if the product matches all filters of the query.
+    ///
+    /// # Arguments
+    /// * `query` - The query parameters for the products.
+    /// * `product` - The description of the product.
+    fn matches(&self, query: &ProductQuery, product: &ProductDescription) -> bool {
+        let info = &product.info;
+
+        let filter_matches = match &query.filter {
+            SearchFilter::NoFilter => true,
+            SearchFilter::Search(search_string) => {
+                let search_string = search_string.to_lowercase();
+                let name_producer = name_producer(product);
+                SearchTermMode::default()
+                    .terms(&search_string)
+                    .iter()
+                    .all(|term| name_producer.contains(term))
+            }
+            SearchFilter::ProductID(product_id) => info.id == *product_id,
+            SearchFilter::Store(store_id) => self
+                .availability
+                .get(&(*store_id, info.id.clone()))
+                .is_some_and(|availability| availability.available),
+        };
+        if !filter_matches {
+            return false;
+        }
+
+        if query
+            .market
+            .as_ref()
+            .is_some_and(|market| info.market.as_ref() != Some(market))
+        {
+            return false;
+        }
+
+        // the products without producer are excluded
+        if let Some(pattern) = query.producer.as_deref() {
+            if info
+                .producer
+                .as_deref()
+                .is_none_or(|producer| !producer_matches(pattern, producer))
+            {
+                return false;
+            }
+        }
+
+        if query
+            .quantity_type
+            .is_some_and(|quantity_type| info.quantity_type != quantity_type)
+        {
+            return false;
+        }
+
+        if let Some(labels) = query.labels.as_ref() {
+            let labels = [
+                (labels.vegan, info.labels.vegan),
+                (labels.vegetarian, info.labels.vegetarian),
+                (labels.organic, info.labels.organic),
+                (labels.gluten_free, info.labels.gluten_free),
+                (labels.lactose_free, info.labels.lactose_free),
+            ];
+            if labels
+                .iter()
+                .any(|(expected, actual)| expected.is_some() && expected != actual)
+            {
+                return false;
+            }
+        }
+
+        if query
+            .exclude_allergens
+            .iter()
+            .any(|allergen| info.allergens.contains(allergen))
+        {
+            return false;
+        }
+
+        // the products without NOVA group are excluded
+        if let Some(max_nova_group) = query.max_nova_group {
+            if info
+                .nova_group
+                .is_none_or(|nova_group| nova_group > max_nova_group)
+            {
+                return false;
+            }
+        }
+
+        // the better grades are smaller and the products without Eco-Score are excluded
+        if let Some(min_eco_score_grade) = query.min_eco_score_grade {
+            if product
+                .eco_score
+                .as_ref()
+                .is_none_or(|eco_score| eco_score.grade > min_eco_score_grade)
+            {
+                return false;
+            }
+        }
+
+        query.data_issues.is_empty() || has_data_issue(product, &query.data_issues)
+    }
+
+    /// Returns the sorted products matching the query without applying the offset and limit.
+    ///
+    /// # Arguments
+    /// * `query` - The query parameters for the products.
+    fn find_products(&self, query: &ProductQuery) -> Result<Vec<Candidate<'_, &ProductID>>> {
+        let search_string = query.filter.search_string();
+        if let Some(sorting) = query.sorting.as_ref() {
+            let valid = match sorting.field {
+                SortingField::ReportedDate => false,
+                SortingField::Similarity => search_string.is_some(),
+                _ => true,
+            };
+            if !valid {
+                return Err(Error::InvalidSortingError(sorting.field));
+            }
+        }
+
+        let mut candidates: Vec<Candidate<'_, &ProductID>> = self
+            .products
+            .iter()
+            .filter(|(_, product)| self.matches(query, &product.description))
+            .map(|(id, product)| Candidate {
+                key: id,
+                product,
+                date: None,
+                score: search_string.map(|s| similarity(&name_producer(&product.description), s)),
+            })
+            .collect();
+        sort_candidates(&mut candidates, query.sorting.as_ref());
+
+        if let Some(cursor) = query.cursor.as_deref() {
+            let cursor = ProductCursor::decode(cursor, query.sorting.as_ref())?;
+            candidates.retain(|candidate| is_after_cursor(candidate, &cursor));
+        }
+
+        Ok(candidates)
+    }
+
+    /// Moves the product request into the archive and returns the request.
+    ///
+    /// # Arguments
+    /// * `id` - The id of the product request.
+    /// * `outcome` - The outcome of the product request.
+    fn archive_request(
+        &mut self,
+        id: DBId,
+        outcome: RequestOutcome,
+    ) -> Option<StoredProductRequest> {
+        let request = self.product_requests.remove(&id)?;
+        let info = &request.product.description.info;
+        self.archived_product_requests.insert(
+            id,
+            ArchivedProductRequest {
+                id,
+                product_id: info.id.clone(),
+                name: info.name.clone(),
+                producer: info.producer.clone(),
+                date: request.date,
+                archived_date: Utc::now(),
+                outcome,
+                review_comment: request.review_comment.clone(),
+            },
+        );
+
+        Some(request)
+    }
+}
+
+impl DataBackend for MemoryBackend {
+    async fn new(_options: &Options) -> Result<Self> {
+        Ok(Self::new())
+    }
+}
+
+impl ReadBackend for MemoryBackend {
+    async fn query_missing_products(
+        &self,
+        query: &MissingProductQuery,
+    ) -> Result<Vec<(DBId, MissingProduct)>> {
+        debug!("Query missing products: {:?}", query);
+
+        let state = self.read();
+        let mut missing_products: Vec<(DBId, &MissingProduct)> = state
+            .missing_products
+            .iter()
+            .map(|(id, m)| (*id, &m.missing_product))
+            .filter(|(_, m)| {
+                query
+                    .product_id
+                    .as_ref()
+                    .is_none_or(|id| m.product_id == *id)
+            })
+            .collect();
+        missing_products.sort_by(|(lhs_id, lhs), (rhs_id, rhs)| {
+            apply_order(
+                lhs.date.cmp(&rhs.date).then(lhs_id.cmp(rhs_id)),
+                query.order,
+            )
+        });
+
+        Ok(page(
+            missing_products.into_iter().map(|(id, m)| (id, m.clone())),
+            query.offset,
+            query.limit,
+        ))
+    }
+
+    async fn count_missing_products(&self, query: &MissingProductQuery) -> Result<u64> {
+        debug!("Count missing products: {:?}", query);
+
+        let state = self.read();
+        let count = state
+            .missing_products
+            .values()
+            .filter(|m| {
+                query
+                    .product_id
+                    .as_ref()
+                    .is_none_or(|id| m.missing_product.product_id == *id)
+            })
+            .count();
+
+        Ok(count as u64)
+    }
+
+    async fn summarize_missing_products(
+        &self,
+        offset: i32,
+        limit: i32,
+    ) -> Result<Vec<MissingProductSummary>> {
+        debug!("Summarize missing products");
+
+        let state = self.read();
+        let mut summaries: HashMap<&ProductID, MissingProductSummary> = HashMap::new();
+        for m in state.missing_products.values() {
+            let m = &m.missing_product;
+            summaries
+                .entry(&m.product_id)
+                .and_modify(|s| {
+                    s.reports += 1;
+                    s.first_reported = s.first_reported.min(m.date);
+                    s.last_reported = s.last_reported.max(m.date);
+                })
+                .or_insert_with(|| MissingProductSummary {
+                    product_id: m.product_id.clone(),
+                    reports: 1,
+                    first_reported: m.date,
+                    last_reported: m.date,
+                });
+        }
+
+        let mut summaries: Vec<MissingProductSummary> = summaries.into_values().collect();
+        summaries.sort_by(|lhs, rhs| {
+            rhs.reports
+                .cmp(&lhs.reports)
+                .then_with(|| lhs.product_id.cmp(&rhs.product_id))
+        });
+
+        Ok(page(summaries, offset, limit))
+    }
+
+    async fn get_missing_product(&self, id: DBId) -> Result<Option<MissingProduct>> {
+        debug!("Get missing product with id: {}", id);
+
+        let state = self.read();
+        Ok(state
+            .missing_products
+            .get(&id)
+            .map(|m| m.missing_product.clone()))
+    }
+
+    async fn get_missing_product_photo(&self, id: DBId) -> Result<Option<ProductImage>> {
+        debug!("Get photo of missing product with id: {}", id);
+
+        let state = self.read();
+        Ok(state
+            .missing_products
+            .get(&id)
+            .and_then(|m| m.photo.clone()))
+    }
+
+    async fn get_product_request(
+        &self,
+        id: DBId,
+        with_preview: bool,
+    ) -> Result<Option<ProductRequest>> {
+        debug!(
+            "Get product request with id: {} [Preview={}]",
+            id, with_preview
+        );
+
+        let state = self.read();
+        Ok(state
+            .product_requests
+            .get(&id)
+            .map(|request| request.describe(with_preview)))
+    }
+
+    async fn get_product_request_image(&self, id: DBId) -> Result<Option<ProductImage>> {
+        debug!("Get product image for product request id: {}", id);
+
+        let state = self.read();
+        Ok(state
+            .product_requests
+            .get(&id)
+            .and_then(|request| request.product.description.full_image.clone()))
+    }
+
+    async fn get_product_request_images(&self, id: DBId) -> Result<Option<Vec<AdditionalImage>>> {
+        debug!("Get additional images for product request id: {}", id);
+
+        let state = self.read();
+        Ok(state
+            .product_requests
+            .get(&id)
+            .map(|request| request.product.images.clone()))
+    }
+
+    async fn query_archived_product_requests(
+        &self,
+        query: &ArchiveQuery,
+    ) -> Result<Vec<ArchivedProductRequest>> {
+        debug!("Query archived product requests: {:?}", query);
+
+        let state = self.read();
+        let mut requests: Vec<&ArchivedProductRequest> = state
+            .archived_product_requests
+            .values()
+            .filter(|r| {
+                query
+                    .product_id
+                    .as_ref()
+                    .is_none_or(|id| r.product_id == *id)
+            })
+            .filter(|r| query.outcome.is_none_or(|outcome| r.outcome == outcome))
+            .collect();
+        requests.sort_by(|lhs, rhs| {
+            apply_order(
+                lhs.archived_date
+                    .cmp(&rhs.archived_date)
+                    .then(lhs.id.cmp(&rhs.id)),
+                query.order,
+            )
+        });
+
+        Ok(page(
+            requests.into_iter().cloned(),
+            query.offset,
+            query.limit,
+        ))
+    }
+
+    async fn get_product(
+        &self,
+        id: &ProductID,
+        with_preview: bool,
+    ) -> Result<Option<ProductDescription>> {
+        debug!("Get product with id: {} [Preview={}]", id, with_preview);
+
+        // match GTINs by their canonical GTIN-14 and prefer the exact match of the id and the
+        // product without market
+        let state = self.read();
+        let gtin = normalize_gtin(id);
+        let product = state
+            .products
+            .values()
+            .filter(|p| {
+                p.description.info.id == *id
+                    || (gtin.is_some() && normalize_gtin(&p.description.info.id) == gtin)
+            })
+            .min_by_key(|p| {
+                let info = &p.description.info;
+                (info.id != *id, info.market.is_some(), info.id.clone())
+            });
+
+        Ok(product.map(|p| p.describe(with_preview)))
+    }
+
+    async fn get_products(&self, ids: &[ProductID]) -> Result<Vec<ProductDescription>> {
+        debug!("Get {} products", ids.len());
+
+        let state = self.read();
+        let ids: HashSet<&ProductID> = ids.iter().collect();
+        let mut products: Vec<(&ProductID, &StoredProduct)> = state
+            .products
+            .iter()
+            .filter(|(id, _)| ids.contains(id))
+            .collect();
+        products.sort_by_key(|(id, _)| *id);
+
+        Ok(products
+            .into_iter()
+            .map(|(_, product)| product.describe(false))
+            .collect())
+    }
+
+    async fn get_product_image(&self, id: &ProductID) -> Result<Option<ProductImage>> {
+        debug!("Get product image for product id: {}", id);
+
+        let state = self.read();
+        Ok(state
+            .products
+            .get(id)
+            .and_then(|product| product.description.full_image.clone()))
+    }
+
+    async fn get_product_images(&self, id: &ProductID) -> Result<Option<Vec<AdditionalImage>>> {
+        debug!("Get additional images for product id: {}", id);
+
+        let state = self.read();
+        Ok(state.products.get(id).map(|product| product.images.clone()))
+    }
+
+    async fn get_recipe(&self, id: DBId) -> Result<Option<Recipe>> {
+        debug!("Get recipe with id: {}", id);
+
+        let state = self.read();
+        Ok(state.recipes.get(&id).cloned())
+    }
+
+    async fn query_product_requests(
+        &self,
+        query: &ProductQuery,
+        with_preview: bool,
+    ) -> Result<Vec<(DBId, ProductRequest)>> {
+        debug!("Query product requests: {:?}", query);
+
+        let search_string = query.filter.search_string();
+        if let Some(sorting) = query.sorting.as_ref() {
+            if sorting.field == SortingField::Similarity && search_string.is_none() {
+                return Err(Error::InvalidSortingError(sorting.field));
+            }
+        }
+
+        let state = self.read();
+        let mut candidates: Vec<Candidate<'_, DBId>> = state
+            .product_requests
+            .iter()
+            .filter(|(_, request)| {
+                request.has_status(query) && state.matches(query, &request.product.description)
+            })
+            .map(|(id, request)| Candidate {
+                key: *id,
+                product: &request.product,
+                date: Some(request.date),
+                score: search_string
+                    .map(|s| similarity(&name_producer(&request.product.description), s)),
+            })
+            .collect();
+        sort_candidates(&mut candidates, query.sorting.as_ref());
+
+        Ok(page(candidates, query.offset, query.limit)
+            .into_iter()
+            .map(|candidate| {
+                (
+                    candidate.key,
+                    state.product_requests[&candidate.key].describe(with_preview),
+                )
+            })
+            .collect())
+    }
+
+    async fn query_products(
+        &self,
+        query: &ProductQuery,
+        with_preview: bool,
+    ) -> Result<Vec<ProductDescription>> {
+        debug!("Query products: {:?}", query);
+
+        let state = self.read();
+        let candidates = state.find_products(query)?;
+
+        Ok(page(candidates, product_offset(query), query.limit)
+            .into_iter()
+            .map(|candidate| candidate.product.describe(with_preview))
+            .collect())
+    }
+
+    async fn query_scored_products(
+        &self,
+        query: &ProductQuery,
+        with_preview: bool,
+    ) -> Result<Vec<(ProductDescription, f32)>> {
+        debug!("Query scored products: {:?}", query);
+
+        if query.filter.search_string().is_none() {
+            return Err(Error::InvalidSortingError(SortingField::Similarity));
+        }
+
+        let state = self.read();
+        let candidates = state.find_products(query)?;
+
+        Ok(page(candidates, product_offset(query), query.limit)
+            .into_iter()
+            .map(|candidate| {
+                (
+                    candidate.product.describe(with_preview),
+                    candidate.score.unwrap_or_default(),
+                )
+            })
+            .collect())
+    }
+
+    async fn count_products(&self, query: &ProductQuery) -> Result<u64> {
+        debug!("Count products: {:?}", query);
+
+        let state = self.read();
+        let count = state
+            .products
+            .values()
+            .filter(|product| state.matches(query, &product.description))
+            .count();
+
+        Ok(count as u64)
+    }
+
+    async fn count_product_requests(&self, query: &ProductQuery) -> Result<u64> {
+        debug!("Count product requests: {:?}", query);
+
+        let state = self.read();
+        let count = state
+            .product_requests
+            .values()
+            .filter(|request| {
+                request.has_status(query) && state.matches(query, &request.product.description)
+            })
+            .count();
+
+        Ok(count as u64)
+    }
+
+    async fn query_prices(&self, query: &PriceQuery) -> Result<Vec<(DBId, PriceObservation)>> {
+        debug!("Query prices: {:?}", query);
+
+        let state = self.read();
+        let mut prices: Vec<(&DBId, &PriceObservation)> = state
+            .prices
+            .iter()
+            .filter(|(_, p)| {
+                query
+                    .product_id
+                    .as_ref()
+                    .is_none_or(|id| p.product_id == *id)
+            })
+            .filter(|(_, p)| query.store.as_ref().is_none_or(|store| p.store == *store))
+            .filter(|(_, p)| {
+                query
+                    .currency
+                    .as_ref()
+                    .is_none_or(|currency| p.currency == *currency)
+            })
+            .collect();
+        prices.sort_by(|(lhs_id, lhs), (rhs_id, rhs)| {
+            apply_order(
+                lhs.observed_at
+                    .cmp(&rhs.observed_at)
+                    .then(lhs_id.cmp(rhs_id)),
+                query.order,
+            )
+        });
+
+        Ok(page(
+            prices.into_iter().map(|(id, p)| (*id, p.clone())),
+            query.offset,
+            query.limit,
+        ))
+    }
+
+    async fn cheapest_price(
+        &self,
+        product_id: &ProductID,
+        currency: &str,
+    ) -> Result<Option<PriceObservation>> {
+        debug!(
+            "Get cheapest price of product {} in {}",
+            product_id, currency
+        );
+
+        // the most recent observation wins among equal prices
+        let state = self.read();
+        let price = state
+            .prices
+            .iter()
+            .filter(|(_, p)| p.product_id == *product_id && p.currency == currency)
+            .min_by(|(lhs_id, lhs), (rhs_id, rhs)| {
+                lhs.price
+                    .cmp(&rhs.price)
+                    .then(rhs.observed_at.cmp(&lhs.observed_at))
+                    .then(lhs_id.cmp(rhs_id))
+            });
+
+        Ok(price.map(|(_, p)| p.clone()))
+    }
+
+    async fn get_store(&self, id: DBId) -> Result<Option<Store>> {
+        debug!("Get store with id: {}", id);
+
+        let state = self.read();
+        Ok(state.stores.get(&id).cloned())
+    }
+
+    async fn query_stores(&self, query: &StoreQuery) -> Result<Vec<(DBId, Store)>> {
+        debug!("Query stores: {:?}", query);
+
+        let state = self.read();
+        let mut stores: Vec<(&DBId, &Store)> = state
+            .stores
+            .iter()
+            .filter(|(_, s)| query.chain.is_none() || s.chain == query.chain)
+            .collect();
+        stores.sort_by(|(lhs_id, lhs), (rhs_id, rhs)| {
+            apply_order(
+                lhs.name.cmp(&rhs.name).then(lhs_id.cmp(rhs_id)),
+                query.order,
+            )
+        });
+
+        Ok(page(
+            stores.into_iter().map(|(id, s)| (*id, s.clone())),
+            query.offset,
+            query.limit,
+        ))
+    }
+
+    async fn get_product_availability(
+        &self,
+        product_id: &ProductID,
+    ) -> Result<Vec<ProductAvailability>> {
+        debug!("Get availability of product {}", product_id);
+
+        let state = self.read();
+        let mut availability: Vec<ProductAvailability> = state
+            .availability
+            .values()
+            .filter(|a| a.product_id == *product_id)
+            .cloned()
+            .collect();
+        availability.sort_by_key(|a| a.store_id);
+
+        Ok(availability)
+    }
+
+    async fn image_storage_usage(&self) -> Result<Vec<ImageStorageUsage>> {
+        debug!("Get image storage usage");
+
+        let state = self.read();
+        let images = state
+            .products
+            .values()
+            .flat_map(StoredProduct::all_images)
+            .chain(
+                state
+                    .product_requests
+                    .values()
+                    .flat_map(|r| r.product.all_images()),
+            )
+            .chain(
+                state
+                    .missing_products
+                    .values()
+                    .filter_map(|m| m.photo.as_ref()),
+            )
+            .chain(state.recipes.values().filter_map(|r| r.image.as_ref()));
+
+        let mut usage: HashMap<&str, ImageStorageUsage> = HashMap::new();
+        for image in images {
+            let entry =
+                usage
+                    .entry(image.content_type.as_str())
+                    .or_insert_with(|| ImageStorageUsage {
+                        content_type: image.content_type.clone(),
+                        images: 0,
+                        bytes: 0,
+                    });
+            entry.images += 1;
+            entry.bytes += image.data.len() as u64;
+        }
+
+        let mut usage: Vec<ImageStorageUsage> = usage.into_values().collect();
+        usage.sort_by(|lhs, rhs| lhs.content_type.cmp(&rhs.content_type));
+
+        Ok(usage)
+    }
+
+    async fn reconnect(&self, _password: Secret) -> Result<()> {
+        Ok(())
+    }
+
+    async fn health_check(&self) -> HealthReport {
+        HealthReport {
+            status: HealthStatus::Healthy,
+            details: None,
+            latency_ms: 0,
+        }
+    }
+
+    async fn ping(&self) -> Result<()> {
+        Ok(())
+    }
+
+    fn record_metrics(&self) {}
+}
+
+impl WriteBackend for MemoryBackend {
+    async fn report_missing_product(&self, missing_product: MissingProduct) -> Result<DBId> {
+        info!(
+            "Report missing product with id: {} with timestamp {}",
+            missing_product.product_id, missing_product.date
+        );
+
+        let ids = self.report_missing_products(vec![missing_product]).await?;
+
+        Ok(ids[0])
+    }
+
+    async fn report_missing_products(
+        &self,
+        missing_products: Vec<MissingProduct>,
+    ) -> Result<Vec<DBId>> {
+        info!("Report {} missing products", missing_products.len());
+
+        let mut state = self.write();
+        let mut ids = Vec::with_capacity(missing_products.len());
+        for missing_product in missing_products {
+            let id = next_id(&mut state.last_missing_product_id);
+            state.missing_products.insert(
+                id,
+                StoredMissingProduct {
+                    missing_product,
+                    photo: None,
+                },
+            );
+            ids.push(id);
+        }
+
+        Ok(ids)
+    }
+
+    async fn delete_reported_missing_product(&self, id: DBId) -> Result<()> {
+        info!("Delete reported missing product with id: {}", id);
+
+        self.write().missing_products.remove(&id);
+
+        Ok(())
+    }
+
+    async fn set_missing_product_photo(&self, id: DBId, photo: &ProductImage) -> Result<bool> {
+        info!("Set photo of missing product with id: {}", id);
+
+        let mut state = self.write();
+        let Some(missing_product) = state.missing_products.get_mut(&id) else {
+            debug!("No missing product with id: {}", id);
+            return Ok(false);
+        };
+        missing_product.photo = Some(photo.clone());
+
+        Ok(true)
+    }
+
+    async fn request_new_product(&self, requested_product: &ProductRequest) -> Result<DBId> {
+        let product_desc = &requested_product.product_description;
+        info!("Request new product with name: {}", product_desc.info.name);
+
+        let mut state = self.write();
+        let id = next_id(&mut state.last_product_request_id);
+        state.product_requests.insert(
+            id,
+            StoredProductRequest {
+                product: StoredProduct::new(product_desc),
+                date: requested_product.date,
+                status: requested_product.status,
+                review_comment: requested_product.review_comment.clone(),
+            },
+        );
+
+        Ok(id)
+    }
+
+    async fn delete_requested_product(&self, id: DBId) -> Result<()> {
+        info!("Delete requested product with id: {}", id);
+
+        self.write().product_requests.remove(&id);
+
+        Ok(())
+    }
+
+    async fn archive_product_request(&self, id: DBId, outcome: RequestOutcome) -> Result<bool> {
+        info!("Archive product request with id: {} as {}", id, outcome);
+
+        Ok(self.write().archive_request(id, outcome).is_some())
+    }
+
+    async fn reject_product_request(&self, id: DBId, comment: Option<&str>) -> Result<bool> {
+        info!("Reject product request with id: {}", id);
+
+        let mut state = self.write();
+        let Some(request) = state.product_requests.get_mut(&id) else {
+            debug!("No product request with id: {}", id);
+            return Ok(false);
+        };
+        request.status = RequestStatus::Rejected;
+        request.review_comment = comment.map(str::to_string);
+
+        Ok(true)
+    }
+
+    async fn archive_approved_product_requests(&self) -> Result<u64> {
+        debug!("Archive product requests of added products...");
+
+        let mut state = self.write();
+        let approved: Vec<DBId> = state
+            .product_requests
+            .iter()
+            .filter(|(_, r)| state.products.contains_key(&r.product.description.info.id))
+            .map(|(id, _)| *id)
+            .collect();
+        for id in approved.iter() {
+            state.archive_request(*id, RequestOutcome::Approved);
+        }
+
+        info!(
+            "Archived {} product requests of added products",
+            approved.len()
+        );
+
+        Ok(approved.len() as u64)
+    }
+
+    async fn approve_product_request(&self, id: DBId) -> Result<Option<ProductID>> {
+        info!("Approve product request with id: {}", id);
+
+        let mut state = self.write();
+        let Some(request) = state.product_requests.get(&id) else {
+            debug!("No product request with id: {}", id);
+            return Ok(None);
+        };
+
+        let product_id = request.product.description.info.id.clone();
+        if state.products.contains_key(&product_id) {
+            info!(
+                "Product with id {} already exists in the database",
+                product_id
+            );
+            return Err(Error::ProductAlreadyExists(product_id));
+        }
+
+        // the product takes over the images of the request
+        if let Some(request) = state.archive_request(id, RequestOutcome::Approved) {
+            state.products.insert(product_id.clone(), request.product);
+        }
+
+        Ok(Some(product_id))
+    }
+
+    async fn new_product(&self, product_desc: &ProductDescription) -> Result<bool> {
+        info!("New product with id: {}", product_desc.info.id);
+
+        let mut state = self.write();
+        if state.products.contains_key(&product_desc.info.id) {
+            info!(
+                "Product with id {} already exists in the database",
+                product_desc.info.id
+            );
+            return Ok(false);
+        }
+
+        state.products.insert(
+            product_desc.info.id.clone(),
+            StoredProduct::new(product_desc),
+        );
+
+        Ok(true)
+    }
+
+    async fn new_products(&self, products: &[ProductDescription]) -> Result<u64> {
+        info!("New {} products", products.len());
+
+        // skip the existing products and the repeated ones within the batch
+        let mut state = self.write();
+        let mut num_added = 0;
+        for product_desc in products {
+            if state.products.contains_key(&product_desc.info.id) {
+                debug!(
+                    "Product with id {} already exists in the database",
+                    product_desc.info.id
+                );
+                continue;
+            }
+
+            state.products.insert(
+                product_desc.info.id.clone(),
+                StoredProduct::new(product_desc),
+            );
+            num_added += 1;
+        }
+
+        info!("Added {} of {} new products", num_added, products.len());
+
+        Ok(num_added)
+    }
+
+    async fn add_product_request_images(
+        &self,
+        id: DBId,
+        images: &[AdditionalImage],
+    ) -> Result<bool> {
+        info!("Add {} images to product request {}", images.len(), id);
+
+        let mut state = self.write();
+        let Some(request) = state.product_requests.get_mut(&id) else {
+            debug!("No product request with id: {}", id);
+            return Ok(false);
+        };
+        request.product.images.extend_from_slice(images);
+
+        Ok(true)
+    }
+
+    async fn add_product_images(&self, id: &ProductID, images: &[AdditionalImage]) -> Result<bool> {
+        info!("Add {} images to product {}", images.len(), id);
+
+        let mut state = self.write();
+        let Some(product) = state.products.get_mut(id) else {
+            debug!("No product with id: {}", id);
+            return Ok(false);
+        };
+        product.images.extend_from_slice(images);
+
+        Ok(true)
+    }
+
+    async fn update_product(&self, product_desc: &ProductDescription) -> Result<bool> {
+        let id = &product_desc.info.id;
+        info!("Update product with id: {}", id);
+
+        // the product is identified by its id and market, which are not changed
+        let mut state = self.write();
+        let Some(product) = state
+            .products
+            .get_mut(id)
+            .filter(|product| product.description.info.market == product_desc.info.market)
+        else {
+            debug!("No product with id: {}", id);
+            return Ok(false);
+        };
+
+        // the images are only replaced if given and the additional images are kept
+        let old = std::mem::replace(&mut product.description, product_desc.clone());
+        if product.description.preview.is_none() {
+            product.description.preview = old.preview;
+        }
+        if product.description.full_image.is_none() {
+            product.description.full_image = old.full_image;
+        }
+
+        Ok(true)
+    }
+
+    async fn delete_product(&self, id: &ProductID) -> Result<()> {
+        info!("Delete product with id: {}", id);
+
+        self.write().products.remove(id);
+
+        Ok(())
+    }
+
+    async fn update_product_preview(&self, id: &ProductID, preview: &ProductImage) -> Result<bool> {
+        info!("Update preview of product with id: {}", id);
+
+        let mut state = self.write();
+        let Some(product) = state.products.get_mut(id) else {
+            debug!("No product with id: {}", id);
+            return Ok(false);
+        };
+        product.description.preview = Some(preview.clone());
+
+        Ok(true)
+    }
+
+    async fn update_product_request_preview(
+        &self,
+        id: DBId,
+        preview: &ProductImage,
+    ) -> Result<bool> {
+        info!("Update preview of product request with id: {}", id);
+
+        let mut state = self.write();
+        let Some(request) = state.product_requests.get_mut(&id) else {
+            debug!("No product request with id: {}", id);
+            return Ok(false);
+        };
+        request.product.description.preview = Some(preview.clone());
+
+        Ok(true)
+    }
+
+    async fn new_recipe(&self, recipe: &Recipe) -> Result<DBId> {
+        info!("New recipe '{}'", recipe.name);
+
+        let mut state = self.write();
+        let id = next_id(&mut state.last_recipe_id);
+        state.recipes.insert(id, recipe.clone());
+
+        Ok(id)
+    }
+
+    async fn update_recipe(&self, id: DBId, recipe: &Recipe) -> Result<bool> {
+        info!("Update recipe with id: {}", id);
+
+        let mut state = self.write();
+        let Some(old_recipe) = state.recipes.get_mut(&id) else {
+            debug!("No recipe with id: {}", id);
+            return Ok(false);
+        };
+        *old_recipe = recipe.clone();
+
+        Ok(true)
+    }
+
+    async fn delete_recipe(&self, id: DBId) -> Result<()> {
+        info!("Delete recipe with id: {}", id);
+
+        self.write().recipes.remove(&id);
+
+        Ok(())
+    }
+
+    async fn submit_price(&self, price: &PriceObservation) -> Result<DBId> {
+        debug!(
+            "Submit price {} {} of product {} at {}",
+            price.price, price.currency, price.product_id, price.store
+        );
+
+        let mut state = self.write();
+        let id = next_id(&mut state.last_price_id);
+        state.prices.insert(id, price.clone());
+
+        Ok(id)
+    }
+
+    async fn new_store(&self, store: &Store) -> Result<DBId> {
+        info!("New store '{}'", store.name);
+
+        let mut state = self.write();
+        let id = next_id(&mut state.last_store_id);
+        state.stores.insert(id, store.clone());
+
+        Ok(id)
+    }
+
+    async fn delete_store(&self, id: DBId) -> Result<()> {
+        info!("Delete store with id: {}", id);
+
+        // the availability is deleted and the reports lose their store
+        let mut state = self.write();
+        state.stores.remove(&id);
+        state
+            .availability
+            .retain(|(store_id, _), _| *store_id != id);
+        for missing_product in state.missing_products.values_mut() {
+            if missing_product.missing_product.store_id == Some(id) {
+                missing_product.missing_product.store_id = None;
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn report_availability(&self, availability: &ProductAvailability) -> Result<bool> {
+        debug!(
+            "Report product {} as {} at store {}",
+            availability.product_id,
+            if availability.available {
+                "available"
+            } else {
+                "missing"
+            },
+            availability.store_id
+        );
+
+        let mut state = self.write();
+        if !state.stores.contains_key(&availability.store_id) {
+            debug!("No store with id: {}", availability.store_id);
+            return Ok(false);
+        }
+
+        state.availability.insert(
+            (availability.store_id, availability.product_id.clone()),
+            availability.clone(),
+        );
+
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{QuantityType, SearchFilter};
+
+    fn products() -> Vec<ProductDescription> {
+        let product_data = include_str!("../../test_data/products.json");
+        serde_json::from_str(product_data).unwrap()
+    }
+
+    fn product_query(filter: SearchFilter, sorting: Option<Sorting>) -> ProductQuery {
+        ProductQuery {
+            offset: 0,
+            limit: 10,
+            filter,
+            sorting,
+            market: None,
+            producer: None,
+            quantity_type: None,
+            labels: None,
+            max_nova_group: None,
+            min_eco_score_grade: None,
+            exclude_allergens: Vec::new(),
+            data_issues: Vec::new(),
+            fields: None,
+            request_status: None,
+            cursor: None,
+        }
+    }
+
+    fn ids(products: &[ProductDescription]) -> Vec<&str> {
+        products.iter().map(|p| p.info.id.as_str()).collect()
+    }
+
+    #[test]
+    fn test_similarity() {
+        assert_eq!(similarity("alpro", "alpro"), 1.0);
+        assert_eq!(similarity("alpro", "banane"), 0.0);
+        assert_eq!(similarity("", ""), 0.0);
+
+        let partial = similarity("alpro natur", "alpro");
+        assert!(partial > 0.0 && partial < 1.0);
+        assert!(similarity("alpro natur", "natur") > similarity("alpro natur", "nat"));
+    }
+
+    #[tokio::test]
+    async fn test_query_products() {
+        let backend = MemoryBackend::new();
+        assert_eq!(backend.new_products(&products()).await.unwrap(), 6);
+        assert!(!backend.new_product(&products()[0]).await.unwrap());
+
+        // unsorted queries are ordered by the product id
+        let query = product_query(SearchFilter::NoFilter, None);
+        let result = backend.query_products(&query, false).await.unwrap();
+        assert_eq!(
+            ids(&result),
+            vec![
+                "4061458061490",
+                "42094722",
+                "4260026433480",
+                "5411188080213",
+                "5411188124689",
+                "76a85ac9-6f22-4ad8-88b6-6afe0c21371f"
+            ]
+        );
+        assert!(result.iter().all(|p| p.preview.is_none()));
+        assert_eq!(backend.count_products(&query).await.unwrap(), 6);
+        let (counted, total_count) = backend.query_counted_products(&query, false).await.unwrap();
+        assert_eq!(counted.len(), result.len());
+        assert_eq!(total_count, 6);
+
+        // the products without NOVA group come last and ties are broken by the product id
+        let query = product_query(
+            SearchFilter::NoFilter,
+            Some(Sorting {
+                order: SortingOrder::Ascending,
+                field: SortingField::NovaGroup,
+            }),
+        );
+        let result = backend.query_products(&query, true).await.unwrap();
+        assert_eq!(
+            ids(&result),
+            vec![
+                "4061458061490",
+                "4260026433480",
+                "76a85ac9-6f22-4ad8-88b6-6afe0c21371f",
+                "5411188080213",
+                "42094722",
+                "5411188124689"
+            ]
+        );
+        assert!(result[0].preview.is_some());
+        assert!(result.iter().all(|p| p.full_image.is_none()));
+
+        let query = ProductQuery {
+            offset: 2,
+            limit: 3,
+            ..query
+        };
+        let page = backend.query_products(&query, false).await.unwrap();
+        assert_eq!(ids(&page), ids(&result[2..5]));
+
+        let query = ProductQuery {
+            max_nova_group: Some(1),
+            data_issues: vec![ProductDataIssue::MissingImage],
+            ..product_query(SearchFilter::NoFilter, None)
+        };
+        let result = backend.query_products(&query, false).await.unwrap();
+        assert_eq!(ids(&result), vec!["76a85ac9-6f22-4ad8-88b6-6afe0c21371f"]);
+        assert_eq!(backend.count_products(&query).await.unwrap(), 1);
+
+        // the producer is matched case-insensitively against the whole producer
+        let query = ProductQuery {
+            producer: Some("ALPRO".to_string()),
+            quantity_type: Some(QuantityType::Volume),
+            ..product_query(SearchFilter::NoFilter, None)
+        };
+        let result = backend.query_products(&query, false).await.unwrap();
+        assert_eq!(ids(&result), vec!["5411188124689"]);
+        let query = ProductQuery {
+            producer: Some("*brüggen*".to_string()),
+            ..product_query(SearchFilter::NoFilter, None)
+        };
+        let result = backend.query_products(&query, false).await.unwrap();
+        assert_eq!(ids(&result), vec!["4061458061490"]);
+        let query = ProductQuery {
+            producer: Some("alp".to_string()),
+            ..product_query(SearchFilter::NoFilter, None)
+        };
+        assert_eq!(backend.count_products(&query).await.unwrap(), 0);
+    }
+
+    #[test]
+    fn test_producer_matches() {
+        assert!(producer_matches("alpro", "Alpro"));
+        assert!(!producer_matches("alp", "Alpro"));
+        assert!(producer_matches("alp*", "Alpro"));
+        assert!(producer_matches("*molkerei", "Gläserne Molkerei"));
+        assert!(producer_matches(
+            "k*brüggen*aldi",
+            "Knusperone, H. & J. Brüggen, Aldi"
+        ));
+        assert!(!producer_matches("a*o*o", "Alpro"));
+        assert!(producer_matches("*", ""));
+        assert!(!producer_matches("", "Alpro"));
+    }
+
+    #[tokio::test]
+    async fn test_query_products_by_cursor() {
+        let backend = MemoryBackend::new();
+        backend.new_products(&products()).await.unwrap();
+
+        for (field, order) in [
+            (SortingField::Name, SortingOrder::Ascending),
+            (SortingField::NovaGroup, SortingOrder::Ascending),
+            (SortingField::NovaGroup, SortingOrder::Descending),
+        ] {
+            let sorting = Sorting { order, field };
+            let query = product_query(SearchFilter::NoFilter, Some(sorting));
+            let expected = backend.query_products(&query, false).await.unwrap();
+
+            // the pages following the cursors of the previous pages yield the same order
+            let mut pages = Vec::new();
+            let mut cursor = None;
+            loop {
+                let query = ProductQuery {
+                    limit: 4,
+                    cursor: cursor.take(),
+                    ..query.clone()
+                };
+                let page = backend.query_products(&query, false).await.unwrap();
+                let Some(last) = page.last() else {
+                    break;
+                };
+                cursor = Some(ProductCursor::after(sorting, last, None).encode());
+                pages.extend(page);
+            }
+            assert_eq!(ids(&pages), ids(&expected), "{:?}", sorting);
+
+            // the total count ignores the cursor
+            let query = ProductQuery { cursor, ..query };
+            assert_eq!(backend.count_products(&query).await.unwrap(), 6);
+        }
+
+        // the cursor must match the sorting of the query
+        let sorting = Sorting {
+            order: SortingOrder::Ascending,
+            field: SortingField::Name,
+        };
+        let query = ProductQuery {
+            cursor: Some(ProductCursor::after(sorting, &products()[0], None).encode()),
+            ..product_query(SearchFilter::NoFilter, None)
+        };
+        assert!(matches!(
+            backend.query_products(&query, false).await,
+            Err(Error::InvalidCursor(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_search_products() {
+        let backend = MemoryBackend::new();
+        backend.new_products(&products()).await.unwrap();
+
+        let query = product_query(
+            SearchFilter::Search("ALPRO natur".to_string()),
+            Some(Sorting {
+                order: SortingOrder::Descending,
+                field: SortingField::Similarity,
+            }),
+        );
+        let result = backend.query_scored_products(&query, false).await.unwrap();
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].0.info.id, "5411188080213");
+        assert!(result[0].1 > 0.5);
+
+        let query = product_query(
+            SearchFilter::Search("alpro".to_string()),
+            Some(Sorting {
+                order: SortingOrder::Ascending,
+                field: SortingField::Name,
+            }),
+        );
+        let result = backend.query_products(&query, false).await.unwrap();
+        assert_eq!(ids(&result), vec!["5411188124689", "5411188080213"]);
+
+        // the similarity requires a search string and products have no reported date
+        let invalid = [SortingField::Similarity, SortingField::ReportedDate];
+        for field in invalid {
+            let query = product_query(
+                SearchFilter::NoFilter,
+                Some(Sorting {
+                    order: SortingOrder::Ascending,
+                    field,
+                }),
+            );
+            assert!(matches!(
+                backend.query_products(&query, false).await,
+                Err(Error::InvalidSortingError(f)) if f == field
+            ));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_product_requests() {
+        let backend = MemoryBackend::new();
+        let products = products();
+
+        let mut request_ids = Vec::new();
+        for (i, product) in products.iter().take(3).enumerate() {
+            let request = ProductRequest {
+                product_description: product.clone(),
+                date: DateTime::from_timestamp(1_700_000_000 - i as i64, 0).unwrap(),
+                status: RequestStatus::Pending,
+                review_comment: None,
+            };
+            request_ids.push(backend.request_new_product(&request).await.unwrap());
+        }
+        assert_eq!(request_ids, vec![1, 2, 3]);
+
+        let query = product_query(
+            SearchFilter::NoFilter,
+            Some(Sorting {
+                order: SortingOrder::Ascending,
+                field: SortingField::ReportedDate,
+            }),
+        );
+        let result = backend.query_product_requests(&query, false).await.unwrap();
+        let result_ids: Vec<DBId> = result.iter().map(|(id, _)| *id).collect();
+        assert_eq!(result_ids, vec![3, 2, 1]);
+        assert_eq!(backend.count_product_requests(&query).await.unwrap(), 3);
+
+        // a rejected request is kept with its review comment
+        assert!(backend
+            .reject_product_request(3, Some("Duplicate"))
+            .await
+            .unwrap());
+        assert!(!backend.reject_product_request(42, None).await.unwrap());
+        let mut rejected_query = query.clone();
+        rejected_query.request_status = Some(RequestStatus::Rejected);
+        let result = backend
+            .query_product_requests(&rejected_query, false)
+            .await
+            .unwrap();
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].0, 3);
+        assert_eq!(result[0].1.status, RequestStatus::Rejected);
+        assert_eq!(result[0].1.review_comment.as_deref(), Some("Duplicate"));
+        rejected_query.request_status = Some(RequestStatus::Pending);
+        assert_eq!(
+            backend
+                .count_product_requests(&rejected_query)
+                .await
+                .unwrap(),
+            2
+        );
+
+        // the approved product takes over the images of the request
+        assert_eq!(
+            backend.approve_product_request(1).await.unwrap(),
+            Some(products[0].info.id.clone())
+        );
+        assert_eq!(backend.approve_product_request(1).await.unwrap(), None);
+        let product = backend
+            .get_product(&products[0].info.id, true)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(product.preview, products[0].preview);
+
+        backend.new_product(&products[1]).await.unwrap();
+        assert!(matches!(
+            backend.approve_product_request(2).await,
+            Err(Error::ProductAlreadyExists(_))
+        ));
+        assert_eq!(
+            backend.archive_approved_product_requests().await.unwrap(),
+            1
+        );
+        assert!(backend
+            .archive_product_request(3, RequestOutcome::Rejected)
+            .await
+            .unwrap());
+
+        let query = ArchiveQuery {
+            offset: 0,
+            limit: 10,
+            product_id: None,
+            outcome: Some(RequestOutcome::Approved),
+            order: SortingOrder::Ascending,
+        };
+        let archived = backend
+            .query_archived_product_requests(&query)
+            .await
+            .unwrap();
+        let archived_ids: Vec<DBId> = archived.iter().map(|r| r.id).collect();
+        assert_eq!(archived_ids.len(), 2);
+        assert!(archived_ids.contains(&1) && archived_ids.contains(&2));
+
+        // the review comment is kept in the archive
+        let query = ArchiveQuery {
+            outcome: Some(RequestOutcome::Rejected),
+            ..query
+        };
+        let archived = backend
+            .query_archived_product_requests(&query)
+            .await
+            .unwrap();
+        assert_eq!(archived.len(), 1);
+        assert_eq!(archived[0].review_comment.as_deref(), Some("Duplicate"));
+    }
+
+    #[tokio::test]
+    async fn test_summarize_missing_products() {
+        let backend = MemoryBackend::new();
+        let report = |product_id: &str, secs: i64| MissingProduct {
+            product_id: product_id.to_string(),
+            date: DateTime::from_timestamp(secs, 0).unwrap(),
+            store_id: None,
+        };
+        backend
+            .report_missing_products(vec![
+                report("b", 300),
+                report("a", 100),
+                report("b", 200),
+                report("c", 500),
+                report("a", 400),
+                report("b", 600),
+            ])
+            .await
+            .unwrap();
+
+        let summaries = backend.summarize_missing_products(0, 10).await.unwrap();
+        let counts: Vec<(&str, u64)> = summaries
+            .iter()
+            .map(|s| (s.product_id.as_str(), s.reports))
+            .collect();
+        assert_eq!(counts, vec![("b", 3), ("a", 2), ("c", 1)]);
+        assert_eq!(summaries[0].first_reported.timestamp(), 200);
+        assert_eq!(summaries[0].last_reported.timestamp(), 600);
+
+        let summaries = backend.summarize_missing_products(1, 1).await.unwrap();
+        assert_eq!(summaries.len(), 1);
+        assert_eq!(summaries[0].product_id, "a");
+    }
+
+    #[tokio::test]
+    async fn test_stores() {
+        let backend = MemoryBackend::new();
+        backend.new_products(&products()).await.unwrap();
+
+        let store = Store {
+            name: "Corner shop".to_string(),
+            chain: None,
+        };
+        let store_id = backend.new_store(&store).await.unwrap();
+        for (product_id, available) in [("42094722", true), ("5411188080213", false)] {
+            let availability = ProductAvailability {
+                store_id,
+                product_id: product_id.to_string(),
+                available,
+                date: Utc::now(),
+            };
+            assert!(backend.report_availability(&availability).await.unwrap());
+        }
+
+        let query = product_query(SearchFilter::Store(store_id), None);
+        let result = backend.query_products(&query, false).await.unwrap();
+        assert_eq!(ids(&result), vec!["42094722"]);
+
+        let missing_product = MissingProduct {
+            product_id: "123".to_string(),
+            date: Utc::now(),
+            store_id: Some(store_id),
+        };
+        let id = backend
+            .report_missing_product(missing_product)
+            .await
+            .unwrap();
+
+        // the reports lose their store and the availability is deleted with the store
+        backend.delete_store(store_id).await.unwrap();
+        let missing_product = backend.get_missing_product(id).await.unwrap().unwrap();
+        assert_eq!(missing_product.store_id, None);
+        assert!(backend
+            .get_product_availability(&"42094722".to_string())
+            .await
+            .unwrap()
+            .is_empty());
+        assert_eq!(backend.query_products(&query, false).await.unwrap(), vec![]);
+    }
+}
@@ -0,0 +1,149 @@
+use std::{sync::OnceLock, time::Instant};
+
+use axum::{
+    extract::{MatchedPath, Request},
+    middleware::Next,
+    response::Response,
+};
+use log::{info, warn};
+use metrics::{counter, describe_counter, describe_gauge, describe_histogram, histogram, Unit};
+use metrics_exporter_prometheus::{Matcher, PrometheusBuilder, PrometheusHandle};
+
+/// The number of open connections of the database pool.
+pub const POOL_CONNECTIONS: &str = "product_db_pool_connections";
+
+/// The number of idle connections of the database pool.
+pub const POOL_IDLE_CONNECTIONS: &str = "product_db_pool_idle_connections";
+
+/// The maximal number of connections of the database pool.
+pub const POOL_MAX_CONNECTIONS: &str = "product_db_pool_max_connections";
+
+/// The number of operations waiting for a connection of the database pool.
+pub const POOL_WAITING_ACQUIRES: &str = "product_db_pool_waiting_acquires";
+
+/// The time for acquiring a connection of the database pool.
+pub const POOL_ACQUIRE_DURATION: &str = "product_db_pool_acquire_duration_seconds";
+
+/// The time for handling requests, labeled by method and route template.
+pub const HTTP_REQUEST_DURATION: &str = "product_db_http_request_duration_seconds";
+
+/// The number of requests answered with an error status, labeled by method, route template and
+/// status code.
+pub const HTTP_REQUEST_ERRORS: &str = "product_db_http_request_errors_total";
+
+/// The number of requests to deprecated routes and parameters, labeled by method, route
+/// template and parameter.
+pub const DEPRECATED_REQUESTS: &str = "product_db_deprecated_requests_total";
+
+/// The number of writes that failed or had a different result on the secondary backend of a
+/// [`crate::MirroredBackend`], labeled by the write operation.
+pub const MIRROR_DIVERGENCES: &str = "product_db_mirror_divergences_total";
+
+/// The route label of requests that did not match any route.
+const UNMATCHED_ROUTE: &str = "unmatched";
+
+/// The buckets of the duration histograms in seconds.
+const DURATION_BUCKETS: &[f64] = &[
+    0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+/// The handle of the Prometheus recorder, None if another recorder has been installed.
+static PROMETHEUS_HANDLE: OnceLock<Option<PrometheusHandle>> = OnceLock::new();
+
+/// Returns the handle for rendering the metrics in the Prometheus text format. The Prometheus
+/// recorder is installed as global recorder on the first call. Returns None if the
+/// application has installed another recorder already.
+pub fn prometheus_handle() -> Option<PrometheusHandle> {
+    PROMETHEUS_HANDLE
+        .get_or_init(|| {
+            let builder = PrometheusBuilder::new()
+                .set_buckets_for_metric(Matcher::Suffix("_seconds".to_string()), DURATION_BUCKETS)
+                .expect("the duration buckets are not empty");
+
+            match builder.install_recorder() {
+                Ok(handle) => {
+                    info!("Installed the Prometheus metrics recorder");
+                    describe_metrics();
+                    Some(handle)
+                }
+                Err(e) => {
+                    warn!("Failed to install the Prometheus metrics recorder: {}", e);
+                    None
+                }
+            }
+        })
+        .clone()
+}
+
+/// Registers the descriptions of the metrics of the service.
+fn describe_metrics() {
+    describe_gauge!(
+        POOL_CONNECTIONS,
+        "The number of open connections of the database pool"
+    );
+    describe_gauge!(
+        POOL_IDLE_CONNECTIONS,
+        "The number of idle connections of the database pool"
+    );
+    describe_gauge!(
+        POOL_MAX_CONNECTIONS,
+        "The maximal number of connections of the database pool"
+    );
+    describe_gauge!(
+        POOL_WAITING_ACQUIRES,
+        "The number of operations waiting for a connection of the database pool"
+    );
+    describe_histogram!(
+        POOL_ACQUIRE_DURATION,
+        Unit::Seconds,
+        "The time for acquiring a connection of the database pool"
+    );
+    describe_histogram!(
+        HTTP_REQUEST_DURATION,
+        Unit::Seconds,
+        "The time for handling requests by method and route"
+    );
+    describe_counter!(
+        HTTP_REQUEST_ERRORS,
+        "The number of requests answered with an error status by method, route and status"
+    );
+    describe_counter!(
+        DEPRECATED_REQUESTS,
+        "The number of requests to deprecated routes and parameters by method, route and parameter"
+    );
+    describe_counter!(
+        MIRROR_DIVERGENCES,
+        "The number of writes diverging on the secondary backend of a mirrored backend by operation"
+    );
+}
+
+/// Middleware that records the duration of the requests and counts the error responses. The
+/// requests are labeled by the route template, e.g. "/v1/user/product/{id}", instead of the
+/// raw path to keep the number of label values bounded.
+pub async fn record_request_metrics(request: Request, next: Next) -> Response {
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_string())
+        .unwrap_or_else(|| UNMATCHED_ROUTE.to_string());
+    let method = request.method().to_string();
+
+    let start = Instant::now();
+    let response = next.run(request).await;
+    let duration = start.elapsed();
+
+    let status = response.status();
+    histogram!(HTTP_REQUEST_DURATION, "method" => method.clone(), "route" => route.clone())
+        .record(duration);
+    if status.is_client_error() || status.is_server_error() {
+        counter!(
+            HTTP_REQUEST_ERRORS,
+            "method" => method,
+            "route" => route,
+            "status" => status.as_str().to_string()
+        )
+        .increment(1);
+    }
+
+    response
+}
@@ -0,0 +1,554 @@
+use std::{
+    fmt::Debug,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+use log::warn;
+use metrics::counter;
+
+use crate::{
+    metrics::MIRROR_DIVERGENCES, AdditionalImage, ArchiveQuery, ArchivedProductRequest,
+    CountedProductRequests, CountedProducts, DBId, DataBackend, Error, HealthReport,
+    ImageStorageUsage, MissingProduct, MissingProductQuery, MissingProductSummary, Options,
+    PriceObservation, PriceQuery, ProductAvailability, ProductDescription, ProductID, ProductImage,
+    ProductQuery, ProductRequest, ReadBackend, Recipe, RequestOutcome, Result, Secret, Store,
+    StoreQuery, WriteBackend,
+};
+
+/// A data backend that serves the reads from the primary backend and applies every write to
+/// both backends, e.g. for migrating to a new storage backend or a new schema without
+/// downtime. The secondary backend is only written after the primary write succeeded and its
+/// failures do not fail the operation. Instead, failed writes and writes whose result differs
+/// from the primary are reported as divergence in the log and in the metrics. Once no
+/// divergences are reported, the secondary backend can replace the primary.
+///
+/// The internal ids of the entities are passed unchanged to the secondary backend, i.e., the
+/// entities copied to the secondary backend beforehand must keep their ids and both backends
+/// must generate the same ids for new entities, e.g. by continuing the same sequences. An id
+/// generated by the secondary backend that differs from the primary is reported as divergence.
+pub struct MirroredBackend<P: DataBackend, S: WriteBackend> {
+    primary: P,
+    secondary: S,
+    divergences: AtomicU64,
+}
+
+impl<P: DataBackend, S: WriteBackend> MirroredBackend<P, S> {
+    /// Creates a new mirrored backend from the given backends.
+    ///
+    /// # Arguments
+    /// * `primary` - The backend serving the reads and the results of the writes.
+    /// * `secondary` - The backend to which the writes are mirrored.
+    pub fn new(primary: P, secondary: S) -> Self {
+        Self {
+            primary,
+            secondary,
+            divergences: AtomicU64::new(0),
+        }
+    }
+
+    /// Returns the primary backend.
+    pub fn primary(&self) -> &P {
+        &self.primary
+    }
+
+    /// Returns the secondary backend.
+    pub fn secondary(&self) -> &S {
+        &self.secondary
+    }
+
+    /// Returns the number of writes that failed or had a different result on the secondary
+    /// backend.
+    pub fn divergences(&self) -> u64 {
+        self.divergences.load(Ordering::Relaxed)
+    }
+
+    /// Unwraps the primary and the secondary backend.
+    pub fn into_inner(self) -> (P, S) {
+        (self.primary, self.secondary)
+    }
+
+    /// Reports a divergence of the secondary backend.
+    ///
+    /// # Arguments
+    /// - `operation` - The name of the diverging write operation.
+    /// - `message` - The description of the divergence.
+    fn diverged(&self, operation: &'static str, message: String) {
+        warn!("Secondary backend diverged on {}: {}", operation, message);
+        self.divergences.fetch_add(1, Ordering::Relaxed);
+        counter!(MIRROR_DIVERGENCES, "operation" => operation).increment(1);
+    }
+
+    /// Returns the result of the mirrored write or reports a divergence if it failed.
+    ///
+    /// # Arguments
+    /// - `operation` - The name of the write operation.
+    /// - `mirrored` - The result of the write on the secondary backend.
+    fn mirrored<T>(&self, operation: &'static str, mirrored: Result<T>) -> Option<T> {
+        mirrored
+            .map_err(|e| self.diverged(operation, e.to_string()))
+            .ok()
+    }
+
+    /// Reports a divergence if the mirrored write failed or its result differs from the result
+    /// of the primary backend.
+    ///
+    /// # Arguments
+    /// - `operation` - The name of the write operation.
+    /// - `result` - The result of the write on the primary backend.
+    /// - `mirrored` - The result of the write on the secondary backend.
+    fn compare<T: PartialEq + Debug>(
+        &self,
+        operation: &'static str,
+        result: &T,
+        mirrored: Result<T>,
+    ) {
+        if let Some(mirrored) = self.mirrored(operation, mirrored) {
+            if mirrored != *result {
+                self.diverged(
+                    operation,
+                    format!("primary returned {:?}, secondary {:?}", result, mirrored),
+                );
+            }
+        }
+    }
+}
+
+impl<P: DataBackend, S: WriteBackend> DataBackend for MirroredBackend<P, S> {
+    async fn new(_options: &Options) -> Result<Self> {
+        Err(Error::InvalidConfigError(
+            "a mirrored backend cannot be created from the options, pass the backend to Service::with_backend instead".to_string(),
+        ))
+    }
+}
+
+impl<P: DataBackend, S: WriteBackend> ReadBackend for MirroredBackend<P, S> {
+    async fn query_missing_products(
+        &self,
+        query: &MissingProductQuery,
+    ) -> Result<Vec<(DBId, MissingProduct)>> {
+        self.primary.query_missing_products(query).await
+    }
+
+    async fn count_missing_products(&self, query: &MissingProductQuery) -> Result<u64> {
+        self.primary.count_missing_products(query).await
+    }
+
+    async fn summarize_missing_products(
+        &self,
+        offset: i32,
+        limit: i32,
+    ) -> Result<Vec<MissingProductSummary>> {
+        self.primary.summarize_missing_products(offset, limit).await
+    }
+
+    async fn get_missing_product(&self, id: DBId) -> Result<Option<MissingProduct>> {
+        self.primary.get_missing_product(id).await
+    }
+
+    async fn get_missing_product_photo(&self, id: DBId) -> Result<Option<ProductImage>> {
+        self.primary.get_missing_product_photo(id).await
+    }
+
+    async fn get_product_request(
+        &self,
+        id: DBId,
+        with_preview: bool,
+    ) -> Result<Option<ProductRequest>> {
+        self.primary.get_product_request(id, with_preview).await
+    }
+
+    async fn get_product_request_image(&self, id: DBId) -> Result<Option<ProductImage>> {
+        self.primary.get_product_request_image(id).await
+    }
+
+    async fn get_product_request_images(&self, id: DBId) -> Result<Option<Vec<AdditionalImage>>> {
+        self.primary.get_product_request_images(id).await
+    }
+
+    async fn query_archived_product_requests(
+        &self,
+        query: &ArchiveQuery,
+    ) -> Result<Vec<ArchivedProductRequest>> {
+        self.primary.query_archived_product_requests(query).await
+    }
+
+    async fn get_product(
+        &self,
+        id: &ProductID,
+        with_preview: bool,
+    ) -> Result<Option<ProductDescription>> {
+        self.primary.get_product(id, with_preview).await
+    }
+
+    async fn get_products(&self, ids: &[ProductID]) -> Result<Vec<ProductDescription>> {
+        self.primary.get_products(ids).await
+    }
+
+    async fn get_product_image(&self, id: &ProductID) -> Result<Option<ProductImage>> {
+        self.primary.get_product_image(id).await
+    }
+
+    async fn get_product_images(&self, id: &ProductID) -> Result<Option<Vec<AdditionalImage>>> {
+        self.primary.get_product_images(id).await
+    }
+
+    async fn get_recipe(&self, id: DBId) -> Result<Option<Recipe>> {
+        self.primary.get_recipe(id).await
+    }
+
+    async fn query_product_requests(
+        &self,
+        query: &ProductQuery,
+        with_preview: bool,
+    ) -> Result<Vec<(DBId, ProductRequest)>> {
+        self.primary
+            .query_product_requests(query, with_preview)
+            .await
+    }
+
+    async fn query_products(
+        &self,
+        query: &ProductQuery,
+        with_preview: bool,
+    ) -> Result<Vec<ProductDescription>> {
+        self.primary.query_products(query, with_preview).await
+    }
+
+    async fn query_scored_products(
+        &self,
+        query: &ProductQuery,
+        with_preview: bool,
+    ) -> Result<Vec<(ProductDescription, f32)>> {
+        self.primary
+            .query_scored_products(query, with_preview)
+            .await
+    }
+
+    async fn count_products(&self, query: &ProductQuery) -> Result<u64> {
+        self.primary.count_products(query).await
+    }
+
+    async fn count_product_requests(&self, query: &ProductQuery) -> Result<u64> {
+        self.primary.count_product_requests(query).await
+    }
+
+    async fn query_counted_products(
+        &self,
+        query: &ProductQuery,
+        with_preview: bool,
+    ) -> Result<CountedProducts> {
+        self.primary
+            .query_counted_products(query, with_preview)
+            .await
+    }
+
+    async fn query_counted_product_requests(
+        &self,
+        query: &ProductQuery,
+        with_preview: bool,
+    ) -> Result<CountedProductRequests> {
+        self.primary
+            .query_counted_product_requests(query, with_preview)
+            .await
+    }
+
+    async fn query_prices(&self, query: &PriceQuery) -> Result<Vec<(DBId, PriceObservation)>> {
+        self.primary.query_prices(query).await
+    }
+
+    async fn cheapest_price(
+        &self,
+        product_id: &ProductID,
+        currency: &str,
+    ) -> Result<Option<PriceObservation>> {
+        self.primary.cheapest_price(product_id, currency).await
+    }
+
+    async fn get_store(&self, id: DBId) -> Result<Option<Store>> {
+        self.primary.get_store(id).await
+    }
+
+    async fn query_stores(&self, query: &StoreQuery) -> Result<Vec<(DBId, Store)>> {
+        self.primary.query_stores(query).await
+    }
+
+    async fn get_product_availability(
+        &self,
+        product_id: &ProductID,
+    ) -> Result<Vec<ProductAvailability>> {
+        self.primary.get_product_availability(product_id).await
+    }
+
+    async fn image_storage_usage(&self) -> Result<Vec<ImageStorageUsage>> {
+        self.primary.image_storage_usage().await
+    }
+
+    async fn reconnect(&self, password: Secret) -> Result<()> {
+        self.primary.reconnect(password).await
+    }
+
+    async fn health_check(&self) -> HealthReport {
+        self.primary.health_check().await
+    }
+
+    async fn ping(&self) -> Result<()> {
+        self.primary.ping().await
+    }
+
+    fn record_metrics(&self) {
+        self.primary.record_metrics()
+    }
+}
+
+impl<P: DataBackend, S: WriteBackend> WriteBackend for MirroredBackend<P, S> {
+    async fn report_missing_product(&self, missing_product: MissingProduct) -> Result<DBId> {
+        let id = self
+            .primary
+            .report_missing_product(missing_product.clone())
+            .await?;
+
+        let mirrored = self.secondary.report_missing_product(missing_product).await;
+        self.compare("report_missing_product", &id, mirrored);
+
+        Ok(id)
+    }
+
+    async fn report_missing_products(
+        &self,
+        missing_products: Vec<MissingProduct>,
+    ) -> Result<Vec<DBId>> {
+        let ids = self
+            .primary
+            .report_missing_products(missing_products.clone())
+            .await?;
+
+        let mirrored = self
+            .secondary
+            .report_missing_products(missing_products)
+            .await;
+        self.compare("report_missing_products", &ids, mirrored);
+
+        Ok(ids)
+    }
+
+    async fn delete_reported_missing_product(&self, id: DBId) -> Result<()> {
+        self.primary.delete_reported_missing_product(id).await?;
+
+        let mirrored = self.secondary.delete_reported_missing_product(id).await;
+        self.mirrored("delete_reported_missing_product", mirrored);
+
+        Ok(())
+    }
+
+    async fn set_missing_product_photo(&self, id: DBId, photo: &ProductImage) -> Result<bool> {
+        let updated = self.primary.set_missing_product_photo(id, photo).await?;
+
+        let mirrored = self.secondary.set_missing_product_photo(id, photo).await;
+        self.compare("set_missing_product_photo", &updated, mirrored);
+
+        Ok(updated)
+    }
+
+    async fn request_new_product(&self, requested_product: &ProductRequest) -> Result<DBId> {
+        let id = self.primary.request_new_product(requested_product).await?;
+
+        let mirrored = self.secondary.request_new_product(requested_product).await;
+        self.compare("request_new_product", &id, mirrored);
+
+        Ok(id)
+    }
+
+    async fn delete_requested_product(&self, id: DBId) -> Result<()> {
+        self.primary.delete_requested_product(id).await?;
+
+        let mirrored = self.secondary.delete_requested_product(id).await;
+        self.mirrored("delete_requested_product", mirrored);
+
+        Ok(())
+    }
+
+    async fn archive_product_request(&self, id: DBId, outcome: RequestOutcome) -> Result<bool> {
+        let archived = self.primary.archive_product_request(id, outcome).await?;
+
+        let mirrored = self.secondary.archive_product_request(id, outcome).await;
+        self.compare("archive_product_request", &archived, mirrored);
+
+        Ok(archived)
+    }
+
+    async fn reject_product_request(&self, id: DBId, comment: Option<&str>) -> Result<bool> {
+        let rejected = self.primary.reject_product_request(id, comment).await?;
+
+        let mirrored = self.secondary.reject_product_request(id, comment).await;
+        self.compare("reject_product_request", &rejected, mirrored);
+
+        Ok(rejected)
+    }
+
+    async fn archive_approved_product_requests(&self) -> Result<u64> {
+        let archived = self.primary.archive_approved_product_requests().await?;
+
+        let mirrored = self.secondary.archive_approved_product_requests().await;
+        self.compare("archive_approved_product_requests", &archived, mirrored);
+
+        Ok(archived)
+    }
+
+    async fn approve_product_request(&self, id: DBId) -> Result<Option<ProductID>> {
+        let approved = self.primary.approve_product_request(id).await?;
+
+        let mirrored = self.secondary.approve_product_request(id).await;
+        self.compare("approve_product_request", &approved, mirrored);
+
+        Ok(approved)
+    }
+
+    async fn update_product(&self, product_desc: &ProductDescription) -> Result<bool> {
+        let updated = self.primary.update_product(product_desc).await?;
+
+        let mirrored = self.secondary.update_product(product_desc).await;
+        self.compare("update_product", &updated, mirrored);
+
+        Ok(updated)
+    }
+
+    async fn new_product(&self, product_desc: &ProductDescription) -> Result<bool> {
+        let created = self.primary.new_product(product_desc).await?;
+
+        let mirrored = self.secondary.new_product(product_desc).await;
+        self.compare("new_product", &created, mirrored);
+
+        Ok(created)
+    }
+
+    async fn new_products(&self, products: &[ProductDescription]) -> Result<u64> {
+        let added = self.primary.new_products(products).await?;
+
+        let mirrored = self.secondary.new_products(products).await;
+        self.compare("new_products", &added, mirrored);
+
+        Ok(added)
+    }
+
+    async fn add_product_request_images(
+        &self,
+        id: DBId,
+        images: &[AdditionalImage],
+    ) -> Result<bool> {
+        let added = self.primary.add_product_request_images(id, images).await?;
+
+        let mirrored = self.secondary.add_product_request_images(id, images).await;
+        self.compare("add_product_request_images", &added, mirrored);
+
+        Ok(added)
+    }
+
+    async fn add_product_images(&self, id: &ProductID, images: &[AdditionalImage]) -> Result<bool> {
+        let added = self.primary.add_product_images(id, images).await?;
+
+        let mirrored = self.secondary.add_product_images(id, images).await;
+        self.compare("add_product_images", &added, mirrored);
+
+        Ok(added)
+    }
+
+    async fn delete_product(&self, id: &ProductID) -> Result<()> {
+        self.primary.delete_product(id).await?;
+
+        let mirrored = self.secondary.delete_product(id).await;
+        self.mirrored("delete_product", mirrored);
+
+        Ok(())
+    }
+
+    async fn update_product_preview(&self, id: &ProductID, preview: &ProductImage) -> Result<bool> {
+        let updated = self.primary.update_product_preview(id, preview).await?;
+
+        let mirrored = self.secondary.update_product_preview(id, preview).await;
+        self.compare("update_product_preview", &updated, mirrored);
+
+        Ok(updated)
+    }
+
+    async fn update_product_request_preview(
+        &self,
+        id: DBId,
+        preview: &ProductImage,
+    ) -> Result<bool> {
+        let updated = self
+            .primary
+            .update_product_request_preview(id, preview)
+            .await?;
+
+        let mirrored = self
+            .secondary
+            .update_product_request_preview(id, preview)
+            .await;
+        self.compare("update_product_request_preview", &updated, mirrored);
+
+        Ok(updated)
+    }
+
+    async fn new_recipe(&self, recipe: &Recipe) -> Result<DBId> {
+        let id = self.primary.new_recipe(recipe).await?;
+
+        let mirrored = self.secondary.new_recipe(recipe).await;
+        self.compare("new_recipe", &id, mirrored);
+
+        Ok(id)
+    }
+
+    async fn update_recipe(&self, id: DBId, recipe: &Recipe) -> Result<bool> {
+        let updated = self.primary.update_recipe(id, recipe).await?;
+
+        let mirrored = self.secondary.update_recipe(id, recipe).await;
+        self.compare("update_recipe", &updated, mirrored);
+
+        Ok(updated)
+    }
+
+    async fn delete_recipe(&self, id: DBId) -> Result<()> {
+        self.primary.delete_recipe(id).await?;
+
+        let mirrored = self.secondary.delete_recipe(id).await;
+        self.mirrored("delete_recipe", mirrored);
+
+        Ok(())
+    }
+
+    async fn submit_price(&self, price: &PriceObservation) -> Result<DBId> {
+        let id = self.primary.submit_price(price).await?;
+
+        let mirrored = self.secondary.submit_price(price).await;
+        self.compare("submit_price", &id, mirrored);
+
+        Ok(id)
+    }
+
+    async fn new_store(&self, store: &Store) -> Result<DBId> {
+        let id = self.primary.new_store(store).await?;
+
+        let mirrored = self.secondary.new_store(store).await;
+        self.compare("new_store", &id, mirrored);
+
+        Ok(id)
+    }
+
+    async fn delete_store(&self, id: DBId) -> Result<()> {
+        self.primary.delete_store(id).await?;
+
+        let mirrored = self.secondary.delete_store(id).await;
+        self.mirrored("delete_store", mirrored);
+
+        Ok(())
+    }
+
+    async fn report_availability(&self, availability: &ProductAvailability) -> Result<bool> {
+        let reported = self.primary.report_availability(availability).await?;
+
+        let mirrored = self.secondary.report_availability(availability).await;
+        self.compare("report_availability", &reported, mirrored);
+
+        Ok(reported)
+    }
+}
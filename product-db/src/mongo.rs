@@ -0,0 +1,2891 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::RwLock,
+    time::{Duration, Instant},
+};
+
+use chrono::{DateTime, Utc};
+use futures::TryStreamExt;
+use log::{debug, error, info, warn};
+use metrics::gauge;
+use mongodb::{
+    bson::{
+        self, doc, oid::ObjectId, serde_helpers::chrono_datetime_as_bson_datetime,
+        spec::BinarySubtype, Binary, Bson, Document,
+    },
+    error::{CommandError, ErrorKind, WriteError, WriteFailure},
+    options::{ClientOptions, IndexOptions, ReturnDocument},
+    Client, Collection, Database, IndexModel,
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::{
+    metrics::POOL_MAX_CONNECTIONS, normalize_gtin, AdditionalImage, ArchiveQuery,
+    ArchivedProductRequest, DBId, DataBackend, EcoScore, Error, HealthReport, HealthStatus,
+    ImageRole, ImageStorageUsage, MissingProduct, MissingProductQuery, MissingProductSummary,
+    MongoConfig, Nutrients, Options, Packaging, Portion, PriceObservation, PriceQuery,
+    ProductAvailability, ProductDataIssue, ProductDescription, ProductID, ProductImage,
+    ProductInfo, ProductQuery, ProductRequest, ReadBackend, Recipe, RequestOutcome, RequestStatus,
+    Result as ProductDBResult, SearchFilter, Secret, SortingField, SortingOrder, Store, StoreQuery,
+    WriteBackend,
+};
+
+/// The maximum limit for the query results.
+const LIMIT_MAX: i32 = 200;
+
+/// The maximal duration of a health check of the database.
+const HEALTH_CHECK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The application name reported to MongoDB, e.g. in the server logs.
+const APPLICATION_NAME: &str = "product-db";
+
+/// The error code of MongoDB for a violated unique index.
+const DUPLICATE_KEY_ERROR: i32 = 11000;
+
+/// The error codes of MongoDB for a missing collection and a missing index.
+const NAMESPACE_NOT_FOUND_ERROR: i32 = 26;
+const INDEX_NOT_FOUND_ERROR: i32 = 27;
+
+/// The collections of the product database.
+const PRODUCTS: &str = "products";
+const PRODUCT_REQUESTS: &str = "product_requests";
+const ARCHIVED_PRODUCT_REQUESTS: &str = "archived_product_requests";
+const MISSING_PRODUCTS: &str = "reported_missing_products";
+const IMAGES: &str = "images";
+const RECIPES: &str = "recipes";
+const PRICES: &str = "prices";
+const STORES: &str = "stores";
+const PRODUCT_AVAILABILITY: &str = "product_availability";
+const COUNTERS: &str = "counters";
+
+/// MongoDB based implementation of the data backend. Every product is stored as a single
+/// document containing its description, its nutrients and the references onto its images.
+/// The internal ids, e.g. of the missing products, are taken from counters in the database.
+pub struct MongoBackend {
+    /// The configuration for the MongoDB connection.
+    config: MongoConfig,
+
+    /// The database, which is replaced when the credentials are reloaded.
+    database: RwLock<Database>,
+}
+
+/// An image stored in its own collection and referenced by the products, the product requests
+/// and the recipes.
+#[derive(Debug, Serialize, Deserialize)]
+struct ImageDocument {
+    #[serde(rename = "_id")]
+    id: ObjectId,
+    content_type: String,
+    data: Binary,
+}
+
+/// A product as stored in the database. The product requests embed the same document.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ProductDocument {
+    info: ProductInfo,
+    /// The canonical GTIN-14 of the product id, if the id is a GTIN.
+    gtin: Option<String>,
+    nutrients: Nutrients,
+    packaging: Option<Packaging>,
+    eco_score: Option<EcoScore>,
+    preview: Option<ObjectId>,
+    photo: Option<ObjectId>,
+    /// The additional images in the order they have been added.
+    #[serde(default)]
+    images: Vec<AdditionalImageDocument>,
+    /// The text score of the search, only defined if projected by the query.
+    #[serde(default, skip_serializing)]
+    score: Option<f64>,
+}
+
+/// The reference onto an additional image of a product or product request.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct AdditionalImageDocument {
+    role: ImageRole,
+    image: ObjectId,
+}
+
+impl ProductDocument {
+    /// Returns the ids of all images of the product, e.g. for deleting them together with the
+    /// product.
+    fn image_ids(&self) -> Vec<Option<ObjectId>> {
+        [self.preview, self.photo]
+            .into_iter()
+            .chain(self.images.iter().map(|image| Some(image.image)))
+            .collect()
+    }
+
+    /// Returns the filter matching the product with the same id and market, where a product
+    /// without market only matches products without market.
+    fn filter(info: &ProductInfo) -> Document {
+        doc! { "info.id": info.id.as_str(), "info.market": info.market.as_deref() }
+    }
+
+    /// Converts the document into the description of the product without the full image.
+    ///
+    /// # Arguments
+    /// * `preview` - The preview image of the product, if loaded.
+    fn into_description(self, preview: Option<ProductImage>) -> ProductDescription {
+        ProductDescription {
+            info: self.info,
+            preview,
+            full_image: None,
+            nutrients: self.nutrients,
+            packaging: self.packaging,
+            eco_score: self.eco_score,
+        }
+    }
+}
+
+/// A requested product as stored in the database.
+#[derive(Debug, Serialize, Deserialize)]
+struct RequestDocument {
+    #[serde(rename = "_id")]
+    id: DBId,
+    #[serde(with = "chrono_datetime_as_bson_datetime")]
+    date: DateTime<Utc>,
+    product: ProductDocument,
+    #[serde(default)]
+    status: RequestStatus,
+    #[serde(default)]
+    review_comment: Option<String>,
+}
+
+/// An archived product request as stored in the database.
+#[derive(Debug, Serialize, Deserialize)]
+struct ArchivedRequestDocument {
+    #[serde(rename = "_id")]
+    id: DBId,
+    product_id: ProductID,
+    name: String,
+    producer: Option<String>,
+    #[serde(with = "chrono_datetime_as_bson_datetime")]
+    date: DateTime<Utc>,
+    #[serde(with = "chrono_datetime_as_bson_datetime")]
+    archived_date: DateTime<Utc>,
+    outcome: RequestOutcome,
+    #[serde(default)]
+    review_comment: Option<String>,
+}
+
+/// A reported missing product as stored in the database.
+#[derive(Debug, Serialize, Deserialize)]
+struct MissingProductDocument {
+    #[serde(rename = "_id")]
+    id: DBId,
+    product_id: ProductID,
+    #[serde(with = "chrono_datetime_as_bson_datetime")]
+    date: DateTime<Utc>,
+    store_id: Option<DBId>,
+    #[serde(default)]
+    photo: Option<ObjectId>,
+}
+
+/// A recipe as stored in the database.
+#[derive(Debug, Serialize, Deserialize)]
+struct RecipeDocument {
+    #[serde(rename = "_id")]
+    id: DBId,
+    name: String,
+    servings: i32,
+    ingredients: Vec<Portion>,
+    image: Option<ObjectId>,
+}
+
+/// An observed price as stored in the database.
+#[derive(Debug, Serialize, Deserialize)]
+struct PriceDocument {
+    #[serde(rename = "_id")]
+    id: DBId,
+    product_id: ProductID,
+    store: String,
+    price: i64,
+    currency: String,
+    #[serde(with = "chrono_datetime_as_bson_datetime")]
+    observed_at: DateTime<Utc>,
+}
+
+/// A store as stored in the database.
+#[derive(Debug, Serialize, Deserialize)]
+struct StoreDocument {
+    #[serde(rename = "_id")]
+    id: DBId,
+    name: String,
+    chain: Option<String>,
+}
+
+/// The last observed availability of a product in a store as stored in the database.
+#[derive(Debug, Serialize, Deserialize)]
+struct AvailabilityDocument {
+    store_id: DBId,
+    product_id: ProductID,
+    available: bool,
+    #[serde(with = "chrono_datetime_as_bson_datetime")]
+    date: DateTime<Utc>,
+}
+
+/// The storage used by the images of a content type as grouped by the aggregation.
+#[derive(Debug, Deserialize)]
+struct ImageUsageDocument {
+    #[serde(rename = "_id")]
+    content_type: String,
+    images: i64,
+    bytes: i64,
+}
+
+/// The reports of a missing product as grouped by the aggregation.
+#[derive(Debug, Deserialize)]
+struct MissingProductSummaryDocument {
+    #[serde(rename = "_id")]
+    product_id: ProductID,
+    reports: i64,
+    #[serde(with = "chrono_datetime_as_bson_datetime")]
+    first_reported: DateTime<Utc>,
+    #[serde(with = "chrono_datetime_as_bson_datetime")]
+    last_reported: DateTime<Utc>,
+}
+
+/// The counter of the internal ids of a collection.
+#[derive(Debug, Serialize, Deserialize)]
+struct CounterDocument {
+    #[serde(rename = "_id")]
+    id: String,
+    seq: DBId,
+}
+
+/// Converts an error of the driver into an error of the product database.
+///
+/// # Arguments
+/// * `e` - The error of the driver.
+fn db_error(e: mongodb::error::Error) -> Error {
+    Error::MongoDBError(Box::new(e))
+}
+
+/// Returns true if the error has been caused by a violated unique index.
+///
+/// # Arguments
+/// * `e` - The error of the driver.
+fn is_duplicate_key_error(e: &mongodb::error::Error) -> bool {
+    matches!(
+        *e.kind,
+        ErrorKind::Write(WriteFailure::WriteError(WriteError {
+            code: DUPLICATE_KEY_ERROR,
+            ..
+        }))
+    )
+}
+
+/// Returns true if the error has been caused by dropping an index that does not exist.
+///
+/// # Arguments
+/// * `e` - The error of the driver.
+fn is_index_not_found_error(e: &mongodb::error::Error) -> bool {
+    matches!(
+        *e.kind,
+        ErrorKind::Command(CommandError {
+            code: NAMESPACE_NOT_FOUND_ERROR | INDEX_NOT_FOUND_ERROR,
+            ..
+        })
+    )
+}
+
+/// Converts the given value into BSON, e.g. an enum for a filter.
+///
+/// # Arguments
+/// * `value` - The value to convert.
+fn to_bson<T: Serialize>(value: &T) -> ProductDBResult<Bson> {
+    bson::to_bson(value).map_err(|e| Error::InternalError(format!("Failed to encode BSON: {}", e)))
+}
+
+/// Returns the sort direction of the given order.
+///
+/// # Arguments
+/// * `order` - The sorting order.
+fn direction(order: SortingOrder) -> i32 {
+    match order {
+        SortingOrder::Ascending => 1,
+        SortingOrder::Descending => -1,
+    }
+}
+
+/// Converts the text score of MongoDB, which is not bounded, into a similarity from 0 to 1 that
+/// keeps the order of the scores.
+///
+/// # Arguments
+/// * `score` - The text score of a document.
+fn similarity(score: f64) -> f32 {
+    let score = score.max(0.0);
+    (score / (1.0 + score)) as f32
+}
+
+/// Returns the conditions on the product documents for the data quality issues, i.e., a
+/// product matches if it has any of the issues.
+///
+/// # Arguments
+/// * `prefix` - The prefix of the fields of the product, e.g. "product." for the requests.
+/// * `data_issues` - The data quality issues to filter the products for.
+fn data_issues_filter(prefix: &str, data_issues: &[ProductDataIssue]) -> Document {
+    let field = |name: &str| format!("{}{}", prefix, name);
+
+    let conditions: Vec<Document> = data_issues
+        .iter()
+        .map(|issue| match issue {
+            ProductDataIssue::MissingImage => {
+                doc! { field("preview"): Bson::Null, field("photo"): Bson::Null }
+            }
+            ProductDataIssue::MissingMacroNutrients => doc! {
+                "$or": [
+                    { field("nutrients.protein"): Bson::Null },
+                    { field("nutrients.fat"): Bson::Null },
+                    { field("nutrients.carbohydrates"): Bson::Null },
+                ]
+            },
+            ProductDataIssue::MissingProducer => {
+                doc! { field("info.producer"): { "$in": [Bson::Null, ""] } }
+            }
+        })
+        .collect();
+
+    doc! { "$or": conditions }
+}
+
+/// Returns the anchored regular expression for the pattern of a producer filter, where a `*`
+/// matches any characters and all other characters match literally.
+///
+/// # Arguments
+/// * `pattern` - The pattern of the producer filter.
+fn producer_regex(pattern: &str) -> String {
+    let mut regex = String::from("^");
+    for c in pattern.chars() {
+        match c {
+            '*' => regex.push_str(".*"),
+            '\\' | '^' | '$' | '.' | '|' | '?' | '+' | '(' | ')' | '[' | ']' | '{' | '}' => {
+                regex.push('\\');
+                regex.push(c);
+            }
+            _ => regex.push(c),
+        }
+    }
+    regex.push('$');
+
+    regex
+}
+
+/// Adds the condition for the review status of the given query, if any, to the filter of the
+/// product requests. Requests stored without status are pending.
+///
+/// # Arguments
+/// * `filter` - The filter of the product requests.
+/// * `query` - The query parameters for the product requests.
+fn request_filter(mut filter: Document, query: &ProductQuery) -> Document {
+    match query.request_status {
+        Some(RequestStatus::Pending) => {
+            filter.insert("status", doc! { "$in": ["pending", Bson::Null] });
+        }
+        Some(status) => {
+            filter.insert("status", status.to_string());
+        }
+        None => {}
+    }
+
+    filter
+}
+
+/// Returns the sort document for the products of the given query or `None` if the query is
+/// unsorted. Ties are broken by the given unique fields in the same order.
+///
+/// # Arguments
+/// * `query` - The query parameters for the products.
+/// * `prefix` - The prefix of the fields of the product, e.g. "product." for the requests.
+/// * `date_field` - The field of the reported date, if the documents have one.
+/// * `unique_fields` - The fields that break ties.
+fn product_sort(
+    query: &ProductQuery,
+    prefix: &str,
+    date_field: Option<&str>,
+    unique_fields: &[&str],
+) -> ProductDBResult<Option<Document>> {
+    let Some(sorting) = query.sorting.as_ref() else {
+        return Ok(None);
+    };
+    let direction = direction(sorting.order);
+
+    let mut sort = Document::new();
+    match sorting.field {
+        SortingField::Similarity => {
+            if query.filter.search_string().is_none() {
+                return Err(Error::InvalidSortingError(sorting.field));
+            }
+
+            // the text score is always sorted in descending order
+            sort.insert("score", doc! { "$meta": "textScore" });
+        }
+        SortingField::ReportedDate => match date_field {
+            Some(date_field) => {
+                sort.insert(date_field, direction);
+            }
+            None => return Err(Error::InvalidSortingError(sorting.field)),
+        },
+        SortingField::Name => {
+            sort.insert(format!("{}info.name", prefix), direction);
+        }
+        SortingField::ProductID => {
+            sort.insert(format!("{}info.id", prefix), direction);
+        }
+        SortingField::NovaGroup => {
+            sort.insert(format!("{}info.nova_group", prefix), direction);
+        }
+    }
+
+    for field in unique_fields {
+        sort.insert(*field, direction);
+    }
+
+    Ok(Some(sort))
+}
+
+/// Finds a page of the documents of the collection.
+///
+/// # Arguments
+/// * `collection` - The collection to query.
+/// * `filter` - The filter of the documents.
+/// * `sort` - The order of the documents, if any.
+/// * `projection` - The projection of the documents, if any.
+/// * `offset` - The offset of the page.
+/// * `limit` - The limit of the page, which is capped at [`LIMIT_MAX`].
+async fn find_page<T>(
+    collection: &Collection<T>,
+    filter: Document,
+    sort: Option<Document>,
+    projection: Option<Document>,
+    offset: i32,
+    limit: i32,
+) -> ProductDBResult<Vec<T>>
+where
+    T: DeserializeOwned + Unpin + Send + Sync,
+{
+    // a limit of zero means no limit for MongoDB
+    let limit = limit.min(LIMIT_MAX);
+    if limit <= 0 {
+        return Ok(Vec::new());
+    }
+
+    let mut find = collection
+        .find(filter)
+        .skip(offset.max(0) as u64)
+        .limit(limit as i64);
+    if let Some(sort) = sort {
+        find = find.sort(sort);
+    }
+    if let Some(projection) = projection {
+        find = find.projection(projection);
+    }
+
+    let cursor = find.await.map_err(|e| {
+        error!(
+            "Failed to query the collection {}: {}",
+            collection.name(),
+            e
+        );
+        db_error(e)
+    })?;
+
+    cursor.try_collect().await.map_err(db_error)
+}
+
+impl MongoBackend {
+    /// Creates a new MongoBackend instance and the indexes of the collections, if not done yet.
+    ///
+    /// # Arguments
+    /// * `config` - The configuration for the MongoDB connection.
+    pub async fn new(config: MongoConfig) -> ProductDBResult<Self> {
+        let database = Self::connect(&config, &config.password).await?;
+        Self::create_indexes(&database).await?;
+
+        Ok(Self {
+            config,
+            database: RwLock::new(database),
+        })
+    }
+
+    /// Replaces the client by a new one using the given password, e.g. after the credentials
+    /// have been rotated.
+    ///
+    /// # Arguments
+    /// * `password` - The new password of the user.
+    pub async fn reconnect(&self, password: Secret) -> ProductDBResult<()> {
+        info!("Reconnecting to MongoDB with new credentials...");
+
+        let database = Self::connect(&self.config, &password).await?;
+        *self
+            .database
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = database;
+
+        info!("Reconnecting to MongoDB with new credentials...DONE");
+
+        Ok(())
+    }
+
+    /// Sends a ping to the database, e.g. for readiness probes. The ping is bounded by
+    /// [`HEALTH_CHECK_TIMEOUT`].
+    pub async fn ping(&self) -> ProductDBResult<()> {
+        let database = self.database();
+        match tokio::time::timeout(
+            HEALTH_CHECK_TIMEOUT,
+            database.run_command(doc! { "ping": 1 }),
+        )
+        .await
+        {
+            Ok(Ok(_)) => Ok(()),
+            Ok(Err(e)) => Err(db_error(e)),
+            Err(_) => Err(Error::DatabaseUnavailable(
+                "no response within the timeout".to_string(),
+            )),
+        }
+    }
+
+    /// Checks the connection to the database by sending a ping.
+    pub async fn health_check(&self) -> HealthReport {
+        let database = self.database();
+        let start = Instant::now();
+
+        let result = tokio::time::timeout(
+            HEALTH_CHECK_TIMEOUT,
+            database.run_command(doc! { "ping": 1 }),
+        )
+        .await;
+
+        let (status, details) = match result {
+            Ok(Ok(_)) => (HealthStatus::Healthy, None),
+            Ok(Err(e)) => {
+                let e = db_error(e);
+                if e.is_authentication_error() {
+                    (
+                        HealthStatus::Unavailable,
+                        Some(format!("authentication failed: {}", e)),
+                    )
+                } else {
+                    (HealthStatus::Unavailable, Some(e.to_string()))
+                }
+            }
+            Err(_) => (
+                HealthStatus::Degraded,
+                Some("no response within the timeout".to_string()),
+            ),
+        };
+
+        let report = HealthReport {
+            status,
+            details,
+            latency_ms: start.elapsed().as_millis() as u64,
+        };
+        match report.status {
+            HealthStatus::Healthy => debug!("Database health check OK in {}ms", report.latency_ms),
+            _ => warn!(
+                "Database health check: {:?} ({})",
+                report.status,
+                report.details.as_deref().unwrap_or_default()
+            ),
+        }
+
+        report
+    }
+
+    /// Records the configured size of the connection pool in the metrics. The driver does not
+    /// expose the utilization of its connection pools.
+    pub fn record_metrics(&self) {
+        if let Some(max_connections) = self.config.max_connections {
+            gauge!(POOL_MAX_CONNECTIONS).set(max_connections as f64);
+        }
+    }
+
+    /// Creates the client for the given configuration and returns the configured database.
+    ///
+    /// # Arguments
+    /// * `config` - The configuration for the MongoDB connection.
+    /// * `password` - The password of the user.
+    async fn connect(config: &MongoConfig, password: &Secret) -> ProductDBResult<Database> {
+        info!("Creating MongoDB client...");
+
+        let mut options = ClientOptions::parse(&config.uri).await.map_err(|e| {
+            error!("Failed to parse the MongoDB connection string: {}", e);
+            db_error(e)
+        })?;
+        options.app_name = Some(APPLICATION_NAME.to_string());
+        if let Some(user) = &config.user {
+            // keep the authentication options of the connection string, e.g. the auth source
+            let mut credential = options.credential.take().unwrap_or_default();
+            credential.username = Some(user.clone());
+            let password = password.secret().ok_or_else(|| {
+                Error::ConfigError("The MongoDB password is not valid UTF-8".to_string())
+            })?;
+            credential.password = Some(password.to_string());
+            options.credential = Some(credential);
+        }
+        if let Some(max_connections) = config.max_connections {
+            options.max_pool_size = Some(max_connections);
+        }
+
+        let client = Client::with_options(options).map_err(|e| {
+            error!("Failed to create MongoDB client: {}", e);
+            db_error(e)
+        })?;
+
+        info!("Creating MongoDB client...DONE");
+
+        Ok(client.database(&config.dbname))
+    }
+
+    /// Creates the indexes of the collections, if they do not exist yet. The text indexes for
+    /// the search do not use any language, i.e., the words are neither stemmed nor are stop
+    /// words ignored.
+    ///
+    /// # Arguments
+    /// * `database` - The database of the collections.
+    async fn create_indexes(database: &Database) -> ProductDBResult<()> {
+        debug!("Creating the indexes of the collections...");
+
+        let unique = || IndexOptions::builder().unique(true).build();
+        let text = |name: &str| {
+            IndexOptions::builder()
+                .name(name.to_string())
+                .default_language("none".to_string())
+                .build()
+        };
+
+        let indexes: [(&str, Vec<IndexModel>); 7] = [
+            (
+                PRODUCTS,
+                vec![
+                    IndexModel::builder()
+                        .keys(doc! { "info.id": 1, "info.market": 1 })
+                        .options(unique())
+                        .build(),
+                    IndexModel::builder().keys(doc! { "gtin": 1 }).build(),
+                    IndexModel::builder()
+                        .keys(doc! { "info.name": "text", "info.producer": "text" })
+                        .options(text("products_search"))
+                        .build(),
+                ],
+            ),
+            (
+                PRODUCT_REQUESTS,
+                vec![
+                    IndexModel::builder()
+                        .keys(doc! { "product.info.id": 1 })
+                        .build(),
+                    IndexModel::builder()
+                        .keys(doc! { "product.info.name": "text", "product.info.producer": "text" })
+                        .options(text("product_requests_search"))
+                        .build(),
+                ],
+            ),
+            (
+                ARCHIVED_PRODUCT_REQUESTS,
+                vec![IndexModel::builder()
+                    .keys(doc! { "archived_date": 1, "_id": 1 })
+                    .build()],
+            ),
+            (
+                MISSING_PRODUCTS,
+                vec![
+                    IndexModel::builder()
+                        .keys(doc! { "date": 1, "_id": 1 })
+                        .build(),
+                    IndexModel::builder().keys(doc! { "product_id": 1 }).build(),
+                ],
+            ),
+            (
+                PRICES,
+                vec![IndexModel::builder()
+                    .keys(doc! { "product_id": 1, "currency": 1, "price": 1 })
+                    .build()],
+            ),
+            (
+                STORES,
+                vec![IndexModel::builder()
+                    .keys(doc! { "chain": 1, "name": 1 })
+                    .build()],
+            ),
+            (
+                PRODUCT_AVAILABILITY,
+                vec![
+                    IndexModel::builder()
+                        .keys(doc! { "store_id": 1, "product_id": 1 })
+                        .options(unique())
+                        .build(),
+                    IndexModel::builder()
+                        .keys(doc! { "product_id": 1, "store_id": 1 })
+                        .build(),
+                ],
+            ),
+        ];
+
+        // the product id alone was unique before products became market-specific
+        match database
+            .collection::<Document>(PRODUCTS)
+            .drop_index("info.id_1")
+            .await
+        {
+            Ok(()) => info!("Dropped the unique index on the product id"),
+            Err(e) if is_index_not_found_error(&e) => {}
+            Err(e) => {
+                error!("Failed to drop the unique index on the product id: {}", e);
+                return Err(db_error(e));
+            }
+        }
+
+        for (collection, models) in indexes {
+            if let Err(e) = database
+                .collection::<Document>(collection)
+                .create_indexes(models)
+                .await
+            {
+                error!("Failed to create the indexes of {}: {}", collection, e);
+                return Err(db_error(e));
+            }
+        }
+
+        debug!("Creating the indexes of the collections...DONE");
+
+        Ok(())
+    }
+
+    /// Returns the current database.
+    fn database(&self) -> Database {
+        self.database
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
+    }
+
+    /// Returns the collection with the given name of the current database.
+    ///
+    /// # Arguments
+    /// * `name` - The name of the collection.
+    fn collection<T: Send + Sync>(&self, name: &str) -> Collection<T> {
+        self.database().collection(name)
+    }
+
+    /// Reserves the given number of consecutive ids of the sequence and returns the first one.
+    ///
+    /// # Arguments
+    /// * `sequence` - The name of the sequence, i.e., the name of the collection.
+    /// * `count` - The number of ids to reserve.
+    async fn reserve_ids(&self, sequence: &str, count: i32) -> ProductDBResult<DBId> {
+        let counter = self
+            .collection::<CounterDocument>(COUNTERS)
+            .find_one_and_update(doc! { "_id": sequence }, doc! { "$inc": { "seq": count } })
+            .upsert(true)
+            .return_document(ReturnDocument::After)
+            .await
+            .map_err(|e| {
+                error!("Failed to reserve ids of {}: {}", sequence, e);
+                db_error(e)
+            })?
+            .ok_or_else(|| Error::InternalError(format!("No counter for {}", sequence)))?;
+
+        Ok(counter.seq - count + 1)
+    }
+
+    /// Stores the image and returns its id. Returns `None` if there is no image.
+    ///
+    /// # Arguments
+    /// * `image` - The image to store.
+    async fn insert_image(
+        &self,
+        image: Option<&ProductImage>,
+    ) -> ProductDBResult<Option<ObjectId>> {
+        let Some(image) = image else {
+            return Ok(None);
+        };
+
+        let document = ImageDocument {
+            id: ObjectId::new(),
+            content_type: image.content_type.clone(),
+            data: Binary {
+                subtype: BinarySubtype::Generic,
+                bytes: image.data.clone(),
+            },
+        };
+
+        self.collection::<ImageDocument>(IMAGES)
+            .insert_one(&document)
+            .await
+            .map_err(|e| {
+                error!("Failed to store image: {}", e);
+                db_error(e)
+            })?;
+
+        Ok(Some(document.id))
+    }
+
+    /// Loads the images with the given ids in a single query.
+    ///
+    /// # Arguments
+    /// * `ids` - The ids of the images.
+    async fn load_images(
+        &self,
+        ids: impl IntoIterator<Item = ObjectId>,
+    ) -> ProductDBResult<HashMap<ObjectId, ProductImage>> {
+        let ids: Vec<ObjectId> = ids.into_iter().collect();
+        if ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        let images: Vec<ImageDocument> = self
+            .collection::<ImageDocument>(IMAGES)
+            .find(doc! { "_id": { "$in": ids } })
+            .await
+            .map_err(db_error)?
+            .try_collect()
+            .await
+            .map_err(db_error)?;
+
+        Ok(images
+            .into_iter()
+            .map(|image| {
+                (
+                    image.id,
+                    ProductImage {
+                        content_type: image.content_type,
+                        data: image.data.bytes,
+                    },
+                )
+            })
+            .collect())
+    }
+
+    /// Loads the image with the given id, if any.
+    ///
+    /// # Arguments
+    /// * `id` - The id of the image.
+    async fn load_image(&self, id: Option<ObjectId>) -> ProductDBResult<Option<ProductImage>> {
+        Ok(self.load_images(id).await?.into_values().next())
+    }
+
+    /// Deletes the images with the given ids.
+    ///
+    /// # Arguments
+    /// * `ids` - The ids of the images.
+    async fn delete_images(
+        &self,
+        ids: impl IntoIterator<Item = Option<ObjectId>>,
+    ) -> ProductDBResult<()> {
+        let ids: Vec<ObjectId> = ids.into_iter().flatten().collect();
+        if ids.is_empty() {
+            return Ok(());
+        }
+
+        self.collection::<ImageDocument>(IMAGES)
+            .delete_many(doc! { "_id": { "$in": ids } })
+            .await
+            .map_err(|e| {
+                error!("Failed to delete images: {}", e);
+                db_error(e)
+            })?;
+
+        Ok(())
+    }
+
+    /// Loads the additional images of the product in the order they have been added.
+    ///
+    /// # Arguments
+    /// * `product` - The document of the product.
+    async fn load_additional_images(
+        &self,
+        product: &ProductDocument,
+    ) -> ProductDBResult<Vec<AdditionalImage>> {
+        let mut images = self
+            .load_images(product.images.iter().map(|image| image.image))
+            .await?;
+
+        Ok(product
+            .images
+            .iter()
+            .filter_map(|image| {
+                images.remove(&image.image).map(|data| AdditionalImage {
+                    role: image.role,
+                    image: data,
+                })
+            })
+            .collect())
+    }
+
+    /// Stores the additional images and appends them to the first document matching the
+    /// filter. The stored images are deleted again if no document matches. Returns true if a
+    /// document matched.
+    ///
+    /// # Arguments
+    /// * `collection` - The collection of the document.
+    /// * `filter` - The filter of the document.
+    /// * `field` - The field of the additional images in the document.
+    /// * `images` - The images to append.
+    async fn append_additional_images<T: Send + Sync>(
+        &self,
+        collection: &Collection<T>,
+        filter: Document,
+        field: &str,
+        images: &[AdditionalImage],
+    ) -> ProductDBResult<bool> {
+        let mut documents = Vec::with_capacity(images.len());
+        for image in images {
+            if let Some(id) = self.insert_image(Some(&image.image)).await? {
+                documents.push(AdditionalImageDocument {
+                    role: image.role,
+                    image: id,
+                });
+            }
+        }
+        let ids: Vec<Option<ObjectId>> = documents.iter().map(|d| Some(d.image)).collect();
+
+        let result = collection
+            .update_one(
+                filter,
+                doc! { "$push": { field: { "$each": to_bson(&documents)? } } },
+            )
+            .await;
+        match result {
+            Ok(result) if result.matched_count > 0 => Ok(true),
+            Ok(_) => {
+                self.delete_images(ids).await?;
+                Ok(false)
+            }
+            Err(e) => {
+                error!("Failed to add additional images: {}", e);
+                self.delete_images(ids).await?;
+                Err(db_error(e))
+            }
+        }
+    }
+
+    /// Stores the images of the product and returns the document of the product referencing
+    /// them.
+    ///
+    /// # Arguments
+    /// * `desc` - The description of the product.
+    async fn create_product_document(
+        &self,
+        desc: &ProductDescription,
+    ) -> ProductDBResult<ProductDocument> {
+        let preview = self.insert_image(desc.preview.as_ref()).await?;
+        let photo = self.insert_image(desc.full_image.as_ref()).await?;
+
+        Ok(ProductDocument {
+            info: desc.info.clone(),
+            gtin: normalize_gtin(&desc.info.id),
+            nutrients: desc.nutrients.clone(),
+            packaging: desc.packaging.clone(),
+            eco_score: desc.eco_score.clone(),
+            preview,
+            photo,
+            images: Vec::new(),
+            score: None,
+        })
+    }
+
+    /// Converts the product documents into descriptions, including their previews if requested.
+    ///
+    /// # Arguments
+    /// * `products` - The documents of the products.
+    /// * `with_preview` - Whether to load the preview images of the products.
+    async fn load_descriptions(
+        &self,
+        products: Vec<ProductDocument>,
+        with_preview: bool,
+    ) -> ProductDBResult<Vec<ProductDescription>> {
+        let mut previews = if with_preview {
+            self.load_images(products.iter().filter_map(|p| p.preview))
+                .await?
+        } else {
+            HashMap::new()
+        };
+
+        Ok(products
+            .into_iter()
+            .map(|p| {
+                let preview = p.preview.and_then(|id| previews.remove(&id));
+                p.into_description(preview)
+            })
+            .collect())
+    }
+
+    /// Returns the ids of the products that have last been seen at the given store.
+    ///
+    /// # Arguments
+    /// * `store_id` - The id of the store.
+    async fn available_products(&self, store_id: DBId) -> ProductDBResult<Vec<Bson>> {
+        self.collection::<AvailabilityDocument>(PRODUCT_AVAILABILITY)
+            .distinct(
+                "product_id",
+                doc! { "store_id": store_id, "available": true },
+            )
+            .await
+            .map_err(db_error)
+    }
+
+    /// Returns the filter for the products of the given query.
+    ///
+    /// # Arguments
+    /// * `query` - The query parameters for the products.
+    /// * `prefix` - The prefix of the fields of the product, e.g. "product." for the requests.
+    async fn product_filter(
+        &self,
+        query: &ProductQuery,
+        prefix: &str,
+    ) -> ProductDBResult<Document> {
+        let field = |name: &str| format!("{}{}", prefix, name);
+        let mut conditions: Vec<Document> = Vec::new();
+
+        match &query.filter {
+            SearchFilter::NoFilter => {}
+            SearchFilter::Search(search_string) => {
+                conditions.push(doc! { "$text": { "$search": search_string.as_str() } });
+            }
+            SearchFilter::ProductID(product_id) => {
+                conditions.push(doc! { field("info.id"): product_id.as_str() });
+            }
+            SearchFilter::Store(store_id) => {
+                let product_ids = self.available_products(*store_id).await?;
+                conditions.push(doc! { field("info.id"): { "$in": product_ids } });
+            }
+        }
+
+        if let Some(market) = query.market.as_ref() {
+            conditions.push(doc! { field("info.market"): market.as_str() });
+        }
+
+        if let Some(producer) = query.producer.as_deref() {
+            let regex = producer_regex(producer);
+            conditions.push(doc! { field("info.producer"): { "$regex": regex, "$options": "i" } });
+        }
+
+        if let Some(quantity_type) = query.quantity_type {
+            let quantity_type = to_bson(&quantity_type)?;
+            conditions.push(doc! { field("info.quantity_type"): quantity_type });
+        }
+
+        if let Some(labels) = query.labels.as_ref() {
+            let labels = [
+                ("vegan", labels.vegan),
+                ("vegetarian", labels.vegetarian),
+                ("organic", labels.organic),
+                ("gluten_free", labels.gluten_free),
+                ("lactose_free", labels.lactose_free),
+            ];
+            for (label, value) in labels {
+                if let Some(value) = value {
+                    conditions.push(doc! { field(&format!("info.labels.{}", label)): value });
+                }
+            }
+        }
+
+        if !query.exclude_allergens.is_empty() {
+            let allergens = to_bson(&query.exclude_allergens)?;
+            conditions.push(doc! { field("info.allergens"): { "$nin": allergens } });
+        }
+
+        if let Some(max_nova_group) = query.max_nova_group {
+            conditions.push(doc! { field("info.nova_group"): { "$lte": max_nova_group } });
+        }
+
+        if let Some(min_eco_score_grade) = query.min_eco_score_grade {
+            // the grades are stored as letters, i.e., the better grades are smaller
+            let grade = to_bson(&min_eco_score_grade)?;
+            conditions.push(doc! { field("eco_score.grade"): { "$lte": grade } });
+        }
+
+        if !query.data_issues.is_empty() {
+            conditions.push(data_issues_filter(prefix, &query.data_issues));
+        }
+
+        if conditions.is_empty() {
+            Ok(Document::new())
+        } else {
+            Ok(doc! { "$and": conditions })
+        }
+    }
+
+    /// Queries the products and, if requested, their similarity to the search string of the
+    /// query, which must be defined in this case.
+    ///
+    /// # Arguments
+    /// * `query` - The query parameters for the products.
+    /// * `with_preview` - Whether to include the preview image of the product in the response.
+    /// * `with_score` - Whether to return the similarity of the products to the search string.
+    async fn fetch_products(
+        &self,
+        query: &ProductQuery,
+        with_preview: bool,
+        with_score: bool,
+    ) -> ProductDBResult<Vec<(ProductDescription, Option<f32>)>> {
+        if with_score && query.filter.search_string().is_none() {
+            return Err(Error::InvalidSortingError(SortingField::Similarity));
+        }
+        if query.cursor.is_some() {
+            return Err(Error::InvalidCursor(
+                "the MongoDB backend only supports the offset".to_string(),
+            ));
+        }
+
+        let filter = self.product_filter(query, "").await?;
+        let sort = product_sort(query, "", None, &["info.id", "info.market"])?;
+        let projection = with_score.then(|| doc! { "score": { "$meta": "textScore" } });
+
+        let products = find_page(
+            &self.collection::<ProductDocument>(PRODUCTS),
+            filter,
+            sort,
+            projection,
+            query.offset,
+            query.limit,
+        )
+        .await?;
+
+        let scores: Vec<Option<f32>> = products.iter().map(|p| p.score.map(similarity)).collect();
+        let products = self.load_descriptions(products, with_preview).await?;
+
+        Ok(products.into_iter().zip(scores).collect())
+    }
+
+    /// Moves the product request into the archive and deletes its images.
+    ///
+    /// # Arguments
+    /// * `request` - The product request to archive.
+    /// * `outcome` - The outcome of the product request.
+    async fn archive_request(
+        &self,
+        request: RequestDocument,
+        outcome: RequestOutcome,
+    ) -> ProductDBResult<()> {
+        self.move_request_to_archive(&request, outcome).await?;
+        self.delete_images(request.product.image_ids()).await
+    }
+
+    /// Moves the product request into the archive without deleting its images, e.g. if they
+    /// have been taken over by a product.
+    ///
+    /// # Arguments
+    /// * `request` - The product request to archive.
+    /// * `outcome` - The outcome of the product request.
+    async fn move_request_to_archive(
+        &self,
+        request: &RequestDocument,
+        outcome: RequestOutcome,
+    ) -> ProductDBResult<()> {
+        let archived = ArchivedRequestDocument {
+            id: request.id,
+            product_id: request.product.info.id.clone(),
+            name: request.product.info.name.clone(),
+            producer: request.product.info.producer.clone(),
+            date: request.date,
+            archived_date: Utc::now(),
+            outcome,
+            review_comment: request.review_comment.clone(),
+        };
+
+        // the request is only deleted once it has been archived
+        let archive = self.collection::<ArchivedRequestDocument>(ARCHIVED_PRODUCT_REQUESTS);
+        archive.insert_one(&archived).await.map_err(|e| {
+            error!("Failed to archive product request {}: {}", request.id, e);
+            db_error(e)
+        })?;
+
+        if let Err(e) = self
+            .collection::<RequestDocument>(PRODUCT_REQUESTS)
+            .delete_one(doc! { "_id": request.id })
+            .await
+        {
+            error!("Failed to delete product request {}: {}", request.id, e);
+
+            // the request stays pending, i.e., it is removed from the archive again
+            if let Err(e) = archive.delete_one(doc! { "_id": request.id }).await {
+                error!(
+                    "Failed to remove archived product request {}: {}",
+                    request.id, e
+                );
+            }
+            return Err(db_error(e));
+        }
+
+        Ok(())
+    }
+}
+
+impl DataBackend for MongoBackend {
+    async fn new(options: &Options) -> ProductDBResult<Self> {
+        let config = options.mongodb.clone().ok_or_else(|| {
+            Error::InvalidConfigError("the MongoDB backend requires the mongodb config".to_string())
+        })?;
+
+        Self::new(config).await
+    }
+}
+
+impl ReadBackend for MongoBackend {
+    async fn reconnect(&self, password: Secret) -> ProductDBResult<()> {
+        Self::reconnect(self, password).await
+    }
+
+    async fn health_check(&self) -> HealthReport {
+        Self::health_check(self).await
+    }
+
+    async fn ping(&self) -> ProductDBResult<()> {
+        Self::ping(self).await
+    }
+
+    fn record_metrics(&self) {
+        Self::record_metrics(self)
+    }
+
+    async fn query_missing_products(
+        &self,
+        query: &MissingProductQuery,
+    ) -> ProductDBResult<Vec<(DBId, MissingProduct)>> {
+        debug!("Query missing products: {:?}", query);
+
+        let filter = match query.product_id.as_ref() {
+            Some(product_id) => doc! { "product_id": product_id.as_str() },
+            None => Document::new(),
+        };
+        let direction = direction(query.order);
+
+        let missing_products = find_page(
+            &self.collection::<MissingProductDocument>(MISSING_PRODUCTS),
+            filter,
+            Some(doc! { "date": direction, "_id": direction }),
+            None,
+            query.offset,
+            query.limit,
+        )
+        .await?;
+
+        Ok(missing_products
+            .into_iter()
+            .map(|m| {
+                (
+                    m.id,
+                    MissingProduct {
+                        product_id: m.product_id,
+                        date: m.date,
+                        store_id: m.store_id,
+                    },
+                )
+            })
+            .collect())
+    }
+
+    async fn count_missing_products(&self, query: &MissingProductQuery) -> ProductDBResult<u64> {
+        debug!("Count missing products: {:?}", query);
+
+        let filter = match query.product_id.as_ref() {
+            Some(product_id) => doc! { "product_id": product_id.as_str() },
+            None => Document::new(),
+        };
+
+        self.collection::<MissingProductDocument>(MISSING_PRODUCTS)
+            .count_documents(filter)
+            .await
+            .map_err(db_error)
+    }
+
+    async fn summarize_missing_products(
+        &self,
+        offset: i32,
+        limit: i32,
+    ) -> ProductDBResult<Vec<MissingProductSummary>> {
+        debug!("Summarize missing products");
+
+        // a limit of zero means no limit for MongoDB
+        let limit = limit.min(LIMIT_MAX);
+        if limit <= 0 {
+            return Ok(Vec::new());
+        }
+
+        let pipeline = [
+            doc! {
+                "$group": {
+                    "_id": "$product_id",
+                    "reports": { "$sum": 1 },
+                    "first_reported": { "$min": "$date" },
+                    "last_reported": { "$max": "$date" },
+                }
+            },
+            doc! { "$sort": { "reports": -1, "_id": 1 } },
+            doc! { "$skip": offset.max(0) as i64 },
+            doc! { "$limit": limit as i64 },
+        ];
+
+        let summaries: Vec<MissingProductSummaryDocument> = self
+            .collection::<MissingProductDocument>(MISSING_PRODUCTS)
+            .aggregate(pipeline)
+            .with_type::<MissingProductSummaryDocument>()
+            .await
+            .map_err(|e| {
+                error!("Failed to summarize the missing products: {}", e);
+                db_error(e)
+            })?
+            .try_collect()
+            .await
+            .map_err(db_error)?;
+
+        Ok(summaries
+            .into_iter()
+            .map(|s| MissingProductSummary {
+                product_id: s.product_id,
+                reports: s.reports as u64,
+                first_reported: s.first_reported,
+                last_reported: s.last_reported,
+            })
+            .collect())
+    }
+
+    async fn get_missing_product(&self, id: DBId) -> ProductDBResult<Option<MissingProduct>> {
+        debug!("Get missing product with id: {}", id);
+
+        let missing_product = self
+            .collection::<MissingProductDocument>(MISSING_PRODUCTS)
+            .find_one(doc! { "_id": id })
+            .await
+            .map_err(|e| {
+                error!("Failed to get missing product: {}", e);
+                db_error(e)
+            })?;
+
+        Ok(missing_product.map(|m| MissingProduct {
+            product_id: m.product_id,
+            date: m.date,
+            store_id: m.store_id,
+        }))
+    }
+
+    async fn get_missing_product_photo(&self, id: DBId) -> ProductDBResult<Option<ProductImage>> {
+        debug!("Get photo of missing product with id: {}", id);
+
+        let missing_product = self
+            .collection::<MissingProductDocument>(MISSING_PRODUCTS)
+            .find_one(doc! { "_id": id })
+            .await
+            .map_err(db_error)?;
+
+        match missing_product {
+            Some(missing_product) => self.load_image(missing_product.photo).await,
+            None => Ok(None),
+        }
+    }
+
+    async fn get_product_request(
+        &self,
+        id: DBId,
+        with_preview: bool,
+    ) -> ProductDBResult<Option<ProductRequest>> {
+        debug!(
+            "Get product request with id: {} [Preview={}]",
+            id, with_preview
+        );
+
+        let request = self
+            .collection::<RequestDocument>(PRODUCT_REQUESTS)
+            .find_one(doc! { "_id": id })
+            .await
+            .map_err(|e| {
+                error!("Failed to get product request: {}", e);
+                db_error(e)
+            })?;
+
+        let Some(request) = request else {
+            debug!("No product request with id: {}", id);
+            return Ok(None);
+        };
+
+        let preview = if with_preview {
+            self.load_image(request.product.preview).await?
+        } else {
+            None
+        };
+
+        Ok(Some(ProductRequest {
+            product_description: request.product.into_description(preview),
+            date: request.date,
+            status: request.status,
+            review_comment: request.review_comment,
+        }))
+    }
+
+    async fn get_product_request_image(&self, id: DBId) -> ProductDBResult<Option<ProductImage>> {
+        debug!("Get product image for product request id: {}", id);
+
+        let request = self
+            .collection::<RequestDocument>(PRODUCT_REQUESTS)
+            .find_one(doc! { "_id": id })
+            .await
+            .map_err(db_error)?;
+
+        match request {
+            Some(request) => self.load_image(request.product.photo).await,
+            None => Ok(None),
+        }
+    }
+
+    async fn get_product_request_images(
+        &self,
+        id: DBId,
+    ) -> ProductDBResult<Option<Vec<AdditionalImage>>> {
+        debug!("Get additional images for product request id: {}", id);
+
+        let request = self
+            .collection::<RequestDocument>(PRODUCT_REQUESTS)
+            .find_one(doc! { "_id": id })
+            .await
+            .map_err(db_error)?;
+
+        match request {
+            Some(request) => Ok(Some(self.load_additional_images(&request.product).await?)),
+            None => Ok(None),
+        }
+    }
+
+    async fn query_archived_product_requests(
+        &self,
+        query: &ArchiveQuery,
+    ) -> ProductDBResult<Vec<ArchivedProductRequest>> {
+        debug!("Query archived product requests: {:?}", query);
+
+        let mut filter = Document::new();
+        if let Some(product_id) = query.product_id.as_ref() {
+            filter.insert("product_id", product_id.as_str());
+        }
+        if let Some(outcome) = query.outcome {
+            filter.insert("outcome", to_bson(&outcome)?);
+        }
+        let direction = direction(query.order);
+
+        let requests = find_page(
+            &self.collection::<ArchivedRequestDocument>(ARCHIVED_PRODUCT_REQUESTS),
+            filter,
+            Some(doc! { "archived_date": direction, "_id": direction }),
+            None,
+            query.offset,
+            query.limit,
+        )
+        .await?;
+
+        Ok(requests
+            .into_iter()
+            .map(|r| ArchivedProductRequest {
+                id: r.id,
+                product_id: r.product_id,
+                name: r.name,
+                producer: r.producer,
+                date: r.date,
+                archived_date: r.archived_date,
+                outcome: r.outcome,
+                review_comment: r.review_comment,
+            })
+            .collect())
+    }
+
+    async fn get_product(
+        &self,
+        id: &ProductID,
+        with_preview: bool,
+    ) -> ProductDBResult<Option<ProductDescription>> {
+        debug!("Get product with id: {} [Preview={}]", id, with_preview);
+
+        // match GTINs by their canonical GTIN-14
+        let mut conditions = vec![doc! { "info.id": id.as_str() }];
+        if let Some(gtin) = normalize_gtin(id) {
+            conditions.push(doc! { "gtin": gtin });
+        }
+
+        let products: Vec<ProductDocument> = self
+            .collection::<ProductDocument>(PRODUCTS)
+            .find(doc! { "$or": conditions })
+            .await
+            .map_err(|e| {
+                error!("Failed to get product: {}", e);
+                db_error(e)
+            })?
+            .try_collect()
+            .await
+            .map_err(db_error)?;
+
+        // prefer the exact match of the id and the product without market
+        let product = products
+            .into_iter()
+            .min_by_key(|p| (p.info.id != *id, p.info.market.is_some()));
+
+        match product {
+            Some(product) => Ok(self
+                .load_descriptions(vec![product], with_preview)
+                .await?
+                .pop()),
+            None => {
+                debug!("No product with id: {}", id);
+                Ok(None)
+            }
+        }
+    }
+
+    async fn get_products(&self, ids: &[ProductID]) -> ProductDBResult<Vec<ProductDescription>> {
+        debug!("Get {} products", ids.len());
+
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let products: Vec<ProductDocument> = self
+            .collection::<ProductDocument>(PRODUCTS)
+            .find(doc! { "info.id": { "$in": ids } })
+            .await
+            .map_err(|e| {
+                error!("Failed to get products: {}", e);
+                db_error(e)
+            })?
+            .try_collect()
+            .await
+            .map_err(db_error)?;
+
+        if products.len() < ids.len() {
+            debug!("Found {} of {} products", products.len(), ids.len());
+        }
+
+        self.load_descriptions(products, false).await
+    }
+
+    async fn get_product_image(&self, id: &ProductID) -> ProductDBResult<Option<ProductImage>> {
+        debug!("Get product image for product id: {}", id);
+
+        let product = self
+            .collection::<ProductDocument>(PRODUCTS)
+            .find_one(doc! { "info.id": id.as_str() })
+            // prefer the product without market
+            .sort(doc! { "info.market": 1 })
+            .await
+            .map_err(db_error)?;
+
+        match product {
+            Some(product) => self.load_image(product.photo).await,
+            None => {
+                debug!("No product image with id: {}", id);
+                Ok(None)
+            }
+        }
+    }
+
+    async fn get_product_images(
+        &self,
+        id: &ProductID,
+    ) -> ProductDBResult<Option<Vec<AdditionalImage>>> {
+        debug!("Get additional images for product id: {}", id);
+
+        let product = self
+            .collection::<ProductDocument>(PRODUCTS)
+            .find_one(doc! { "info.id": id.as_str() })
+            // prefer the product without market
+            .sort(doc! { "info.market": 1 })
+            .await
+            .map_err(db_error)?;
+
+        match product {
+            Some(product) => Ok(Some(self.load_additional_images(&product).await?)),
+            None => Ok(None),
+        }
+    }
+
+    async fn get_recipe(&self, id: DBId) -> ProductDBResult<Option<Recipe>> {
+        debug!("Get recipe with id: {}", id);
+
+        let recipe = self
+            .collection::<RecipeDocument>(RECIPES)
+            .find_one(doc! { "_id": id })
+            .await
+            .map_err(|e| {
+                error!("Failed to get recipe {}: {}", id, e);
+                db_error(e)
+            })?;
+
+        let Some(recipe) = recipe else {
+            debug!("No recipe with id: {}", id);
+            return Ok(None);
+        };
+
+        let image = self.load_image(recipe.image).await?;
+
+        Ok(Some(Recipe {
+            name: recipe.name,
+            servings: recipe.servings,
+            ingredients: recipe.ingredients,
+            image,
+        }))
+    }
+
+    async fn query_product_requests(
+        &self,
+        query: &ProductQuery,
+        with_preview: bool,
+    ) -> ProductDBResult<Vec<(DBId, ProductRequest)>> {
+        debug!("Query product requests: {:?}", query);
+
+        let filter = request_filter(self.product_filter(query, "product.").await?, query);
+        let sort = product_sort(query, "product.", Some("date"), &["_id"])?;
+
+        let requests = find_page(
+            &self.collection::<RequestDocument>(PRODUCT_REQUESTS),
+            filter,
+            sort,
+            None,
+            query.offset,
+            query.limit,
+        )
+        .await?;
+
+        let reviews: Vec<(DBId, DateTime<Utc>, RequestStatus, Option<String>)> = requests
+            .iter()
+            .map(|r| (r.id, r.date, r.status, r.review_comment.clone()))
+            .collect();
+        let products = self
+            .load_descriptions(
+                requests.into_iter().map(|r| r.product).collect(),
+                with_preview,
+            )
+            .await?;
+
+        Ok(reviews
+            .into_iter()
+            .zip(products)
+            .map(
+                |((id, date, status, review_comment), product_description)| {
+                    (
+                        id,
+                        ProductRequest {
+                            product_description,
+                            date,
+                            status,
+                            review_comment,
+                        },
+                    )
+                },
+            )
+            .collect())
+    }
+
+    async fn query_products(
+        &self,
+        query: &ProductQuery,
+        with_preview: bool,
+    ) -> ProductDBResult<Vec<ProductDescription>> {
+        debug!("Query products: {:?}", query);
+
+        let products = self.fetch_products(query, with_preview, false).await?;
+
+        Ok(products.into_iter().map(|(product, _)| product).collect())
+    }
+
+    async fn query_scored_products(
+        &self,
+        query: &ProductQuery,
+        with_preview: bool,
+    ) -> ProductDBResult<Vec<(ProductDescription, f32)>> {
+        debug!("Query scored products: {:?}", query);
+
+        let products = self.fetch_products(query, with_preview, true).await?;
+
+        Ok(products
+            .into_iter()
+            .map(|(product, score)| (product, score.unwrap_or_default()))
+            .collect())
+    }
+
+    async fn count_products(&self, query: &ProductQuery) -> ProductDBResult<u64> {
+        debug!("Count products: {:?}", query);
+
+        let filter = self.product_filter(query, "").await?;
+
+        self.collection::<ProductDocument>(PRODUCTS)
+            .count_documents(filter)
+            .await
+            .map_err(db_error)
+    }
+
+    async fn count_product_requests(&self, query: &ProductQuery) -> ProductDBResult<u64> {
+        debug!("Count product requests: {:?}", query);
+
+        let filter = request_filter(self.product_filter(query, "product.").await?, query);
+
+        self.collection::<RequestDocument>(PRODUCT_REQUESTS)
+            .count_documents(filter)
+            .await
+            .map_err(db_error)
+    }
+
+    async fn query_prices(
+        &self,
+        query: &PriceQuery,
+    ) -> ProductDBResult<Vec<(DBId, PriceObservation)>> {
+        debug!("Query prices: {:?}", query);
+
+        let mut filter = Document::new();
+        if let Some(product_id) = query.product_id.as_ref() {
+            filter.insert("product_id", product_id.as_str());
+        }
+        if let Some(store) = query.store.as_ref() {
+            filter.insert("store", store.as_str());
+        }
+        if let Some(currency) = query.currency.as_ref() {
+            filter.insert("currency", currency.as_str());
+        }
+        let direction = direction(query.order);
+
+        let prices = find_page(
+            &self.collection::<PriceDocument>(PRICES),
+            filter,
+            Some(doc! { "observed_at": direction, "_id": direction }),
+            None,
+            query.offset,
+            query.limit,
+        )
+        .await?;
+
+        Ok(prices
+            .into_iter()
+            .map(|p| {
+                (
+                    p.id,
+                    PriceObservation {
+                        product_id: p.product_id,
+                        store: p.store,
+                        price: p.price,
+                        currency: p.currency,
+                        observed_at: p.observed_at,
+                    },
+                )
+            })
+            .collect())
+    }
+
+    async fn cheapest_price(
+        &self,
+        product_id: &ProductID,
+        currency: &str,
+    ) -> ProductDBResult<Option<PriceObservation>> {
+        debug!(
+            "Get cheapest price of product {} in {}",
+            product_id, currency
+        );
+
+        // the most recent observation wins among equal prices
+        let price = self
+            .collection::<PriceDocument>(PRICES)
+            .find_one(doc! { "product_id": product_id.as_str(), "currency": currency })
+            .sort(doc! { "price": 1, "observed_at": -1 })
+            .await
+            .map_err(|e| {
+                error!(
+                    "Failed to get the cheapest price of product {}: {}",
+                    product_id, e
+                );
+                db_error(e)
+            })?;
+
+        Ok(price.map(|p| PriceObservation {
+            product_id: p.product_id,
+            store: p.store,
+            price: p.price,
+            currency: p.currency,
+            observed_at: p.observed_at,
+        }))
+    }
+
+    async fn get_store(&self, id: DBId) -> ProductDBResult<Option<Store>> {
+        debug!("Get store with id: {}", id);
+
+        let store = self
+            .collection::<StoreDocument>(STORES)
+            .find_one(doc! { "_id": id })
+            .await
+            .map_err(|e| {
+                error!("Failed to get store {}: {}", id, e);
+                db_error(e)
+            })?;
+
+        Ok(store.map(|s| Store {
+            name: s.name,
+            chain: s.chain,
+        }))
+    }
+
+    async fn query_stores(&self, query: &StoreQuery) -> ProductDBResult<Vec<(DBId, Store)>> {
+        debug!("Query stores: {:?}", query);
+
+        let filter = match query.chain.as_ref() {
+            Some(chain) => doc! { "chain": chain.as_str() },
+            None => Document::new(),
+        };
+        let direction = direction(query.order);
+
+        let stores = find_page(
+            &self.collection::<StoreDocument>(STORES),
+            filter,
+            Some(doc! { "name": direction, "_id": direction }),
+            None,
+            query.offset,
+            query.limit,
+        )
+        .await?;
+
+        Ok(stores
+            .into_iter()
+            .map(|s| {
+                (
+                    s.id,
+                    Store {
+                        name: s.name,
+                        chain: s.chain,
+                    },
+                )
+            })
+            .collect())
+    }
+
+    async fn get_product_availability(
+        &self,
+        product_id: &ProductID,
+    ) -> ProductDBResult<Vec<ProductAvailability>> {
+        debug!("Get availability of product {}", product_id);
+
+        let availability: Vec<AvailabilityDocument> = self
+            .collection::<AvailabilityDocument>(PRODUCT_AVAILABILITY)
+            .find(doc! { "product_id": product_id.as_str() })
+            .sort(doc! { "store_id": 1 })
+            .await
+            .map_err(|e| {
+                error!(
+                    "Failed to get the availability of product {}: {}",
+                    product_id, e
+                );
+                db_error(e)
+            })?
+            .try_collect()
+            .await
+            .map_err(db_error)?;
+
+        Ok(availability
+            .into_iter()
+            .map(|a| ProductAvailability {
+                store_id: a.store_id,
+                product_id: a.product_id,
+                available: a.available,
+                date: a.date,
+            })
+            .collect())
+    }
+
+    async fn image_storage_usage(&self) -> ProductDBResult<Vec<ImageStorageUsage>> {
+        debug!("Get image storage usage");
+
+        let pipeline = [
+            doc! {
+                "$group": {
+                    "_id": "$content_type",
+                    "images": { "$sum": 1 },
+                    "bytes": { "$sum": { "$binarySize": "$data" } },
+                }
+            },
+            doc! { "$sort": { "_id": 1 } },
+        ];
+
+        let usage: Vec<ImageUsageDocument> = self
+            .collection::<ImageDocument>(IMAGES)
+            .aggregate(pipeline)
+            .with_type::<ImageUsageDocument>()
+            .await
+            .map_err(|e| {
+                error!("Failed to get the image storage usage: {}", e);
+                db_error(e)
+            })?
+            .try_collect()
+            .await
+            .map_err(db_error)?;
+
+        Ok(usage
+            .into_iter()
+            .map(|u| ImageStorageUsage {
+                content_type: u.content_type,
+                images: u.images as u64,
+                bytes: u.bytes as u64,
+            })
+            .collect())
+    }
+}
+
+impl WriteBackend for MongoBackend {
+    async fn report_missing_product(
+        &self,
+        missing_product: MissingProduct,
+    ) -> ProductDBResult<DBId> {
+        info!(
+            "Report missing product with id: {} with timestamp {}",
+            missing_product.product_id, missing_product.date
+        );
+
+        let ids = self.report_missing_products(vec![missing_product]).await?;
+
+        Ok(ids[0])
+    }
+
+    async fn report_missing_products(
+        &self,
+        missing_products: Vec<MissingProduct>,
+    ) -> ProductDBResult<Vec<DBId>> {
+        info!("Report {} missing products", missing_products.len());
+
+        if missing_products.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let first_id = self
+            .reserve_ids(MISSING_PRODUCTS, missing_products.len() as i32)
+            .await?;
+        let documents: Vec<MissingProductDocument> = missing_products
+            .into_iter()
+            .zip(first_id..)
+            .map(|(m, id)| MissingProductDocument {
+                id,
+                product_id: m.product_id,
+                date: m.date,
+                store_id: m.store_id,
+                photo: None,
+            })
+            .collect();
+        let ids: Vec<DBId> = documents.iter().map(|m| m.id).collect();
+
+        let collection = self.collection::<MissingProductDocument>(MISSING_PRODUCTS);
+        if let Err(e) = collection.insert_many(&documents).await {
+            error!("Failed to report missing products: {}", e);
+
+            // either all or none of the reports are stored
+            if let Err(e) = collection
+                .delete_many(doc! { "_id": { "$in": ids.clone() } })
+                .await
+            {
+                error!("Failed to remove the partially stored reports: {}", e);
+            }
+
+            return Err(db_error(e));
+        }
+
+        info!("Reported {} missing products", ids.len());
+
+        Ok(ids)
+    }
+
+    async fn delete_reported_missing_product(&self, id: DBId) -> ProductDBResult<()> {
+        info!("Delete reported missing product with id: {}", id);
+
+        let missing_product = self
+            .collection::<MissingProductDocument>(MISSING_PRODUCTS)
+            .find_one_and_delete(doc! { "_id": id })
+            .await
+            .map_err(|e| {
+                error!("Failed to delete reported missing product: {}", e);
+                db_error(e)
+            })?;
+        if let Some(missing_product) = missing_product {
+            self.delete_images([missing_product.photo]).await?;
+        }
+
+        info!("Deleted reported missing product with id: {}", id);
+
+        Ok(())
+    }
+
+    async fn set_missing_product_photo(
+        &self,
+        id: DBId,
+        photo: &ProductImage,
+    ) -> ProductDBResult<bool> {
+        info!("Set photo of missing product with id: {}", id);
+
+        let new_photo = self.insert_image(Some(photo)).await?;
+        let old_missing_product = self
+            .collection::<MissingProductDocument>(MISSING_PRODUCTS)
+            .find_one_and_update(doc! { "_id": id }, doc! { "$set": { "photo": new_photo } })
+            .await;
+
+        // the previous photo is only referenced by the report
+        match old_missing_product {
+            Ok(Some(old_missing_product)) => {
+                self.delete_images([old_missing_product.photo]).await?;
+                info!("Set photo of missing product with id: {}", id);
+                Ok(true)
+            }
+            Ok(None) => {
+                debug!("No missing product with id: {}", id);
+                self.delete_images([new_photo]).await?;
+                Ok(false)
+            }
+            Err(e) => {
+                error!("Failed to set photo of missing product {}: {}", id, e);
+                self.delete_images([new_photo]).await?;
+                Err(db_error(e))
+            }
+        }
+    }
+
+    async fn request_new_product(
+        &self,
+        requested_product: &ProductRequest,
+    ) -> ProductDBResult<DBId> {
+        let product_desc = &requested_product.product_description;
+        info!("Request new product with name: {}", product_desc.info.name);
+
+        let id = self.reserve_ids(PRODUCT_REQUESTS, 1).await?;
+        let request = RequestDocument {
+            id,
+            date: requested_product.date,
+            product: self.create_product_document(product_desc).await?,
+            status: requested_product.status,
+            review_comment: requested_product.review_comment.clone(),
+        };
+
+        if let Err(e) = self
+            .collection::<RequestDocument>(PRODUCT_REQUESTS)
+            .insert_one(&request)
+            .await
+        {
+            error!("Failed to request new product: {}", e);
+            self.delete_images([request.product.preview, request.product.photo])
+                .await?;
+            return Err(db_error(e));
+        }
+
+        info!(
+            "Requested new product with name: {} as {}",
+            product_desc.info.name, id
+        );
+
+        Ok(id)
+    }
+
+    async fn delete_requested_product(&self, id: DBId) -> ProductDBResult<()> {
+        info!("Delete requested product with id: {}", id);
+
+        let request = self
+            .collection::<RequestDocument>(PRODUCT_REQUESTS)
+            .find_one_and_delete(doc! { "_id": id })
+            .await
+            .map_err(|e| {
+                error!("Failed to delete requested product: {}", e);
+                db_error(e)
+            })?;
+
+        if let Some(request) = request {
+            self.delete_images(request.product.image_ids()).await?;
+        }
+
+        info!("Deleted requested product with id: {}", id);
+
+        Ok(())
+    }
+
+    async fn archive_product_request(
+        &self,
+        id: DBId,
+        outcome: RequestOutcome,
+    ) -> ProductDBResult<bool> {
+        info!("Archive product request with id: {} as {}", id, outcome);
+
+        let request = self
+            .collection::<RequestDocument>(PRODUCT_REQUESTS)
+            .find_one(doc! { "_id": id })
+            .await
+            .map_err(db_error)?;
+
+        let Some(request) = request else {
+            debug!("No product request with id: {}", id);
+            return Ok(false);
+        };
+
+        self.archive_request(request, outcome).await?;
+        info!("Archived product request with id: {} as {}", id, outcome);
+
+        Ok(true)
+    }
+
+    async fn reject_product_request(
+        &self,
+        id: DBId,
+        comment: Option<&str>,
+    ) -> ProductDBResult<bool> {
+        info!("Reject product request with id: {}", id);
+
+        let result = self
+            .collection::<RequestDocument>(PRODUCT_REQUESTS)
+            .update_one(
+                doc! { "_id": id },
+                doc! { "$set": {
+                    "status": RequestStatus::Rejected.to_string(),
+                    "review_comment": comment,
+                } },
+            )
+            .await
+            .map_err(|e| {
+                error!("Failed to reject product request {}: {}", id, e);
+                db_error(e)
+            })?;
+
+        if result.matched_count == 0 {
+            debug!("No product request with id: {}", id);
+            return Ok(false);
+        }
+
+        info!("Rejected product request with id: {}", id);
+
+        Ok(true)
+    }
+
+    async fn archive_approved_product_requests(&self) -> ProductDBResult<u64> {
+        debug!("Archive product requests of added products...");
+
+        let requests = self.collection::<RequestDocument>(PRODUCT_REQUESTS);
+        let requested = requests
+            .distinct("product.info.id", Document::new())
+            .await
+            .map_err(db_error)?;
+        let added = self
+            .collection::<ProductDocument>(PRODUCTS)
+            .distinct("info.id", doc! { "info.id": { "$in": requested } })
+            .await
+            .map_err(db_error)?;
+
+        let approved: Vec<RequestDocument> = requests
+            .find(doc! { "product.info.id": { "$in": added } })
+            .await
+            .map_err(db_error)?
+            .try_collect()
+            .await
+            .map_err(db_error)?;
+
+        let num_archived = approved.len() as u64;
+        for request in approved {
+            self.archive_request(request, RequestOutcome::Approved)
+                .await?;
+        }
+
+        info!(
+            "Archived {} product requests of added products",
+            num_archived
+        );
+
+        Ok(num_archived)
+    }
+
+    async fn approve_product_request(&self, id: DBId) -> ProductDBResult<Option<ProductID>> {
+        info!("Approve product request with id: {}", id);
+
+        let request = self
+            .collection::<RequestDocument>(PRODUCT_REQUESTS)
+            .find_one(doc! { "_id": id })
+            .await
+            .map_err(db_error)?;
+
+        let Some(request) = request else {
+            debug!("No product request with id: {}", id);
+            return Ok(None);
+        };
+
+        // the product takes over the images of the request
+        let product_id = request.product.info.id.clone();
+        let products = self.collection::<ProductDocument>(PRODUCTS);
+        if let Err(e) = products.insert_one(&request.product).await {
+            if is_duplicate_key_error(&e) {
+                info!(
+                    "Product with id {} already exists in the database",
+                    product_id
+                );
+                return Err(Error::ProductAlreadyExists(product_id));
+            }
+
+            error!("Failed to add product of product request {}: {}", id, e);
+            return Err(db_error(e));
+        }
+
+        // without a transaction, the product is removed again if the request is not archived,
+        // while the images stay referenced by the pending request
+        if let Err(e) = self
+            .move_request_to_archive(&request, RequestOutcome::Approved)
+            .await
+        {
+            if let Err(e) = products
+                .delete_one(ProductDocument::filter(&request.product.info))
+                .await
+            {
+                error!("Failed to remove product {}: {}", product_id, e);
+            }
+            return Err(e);
+        }
+        info!("Approved product request {} as product {}", id, product_id);
+
+        Ok(Some(product_id))
+    }
+
+    async fn new_product(&self, product_desc: &ProductDescription) -> ProductDBResult<bool> {
+        info!("New product with id: {}", product_desc.info.id);
+
+        let products = self.collection::<ProductDocument>(PRODUCTS);
+        let exists = products
+            .count_documents(ProductDocument::filter(&product_desc.info))
+            .await
+            .map_err(db_error)?
+            > 0;
+        if exists {
+            info!(
+                "Product with id {} and market {:?} already exists in the database",
+                product_desc.info.id, product_desc.info.market
+            );
+            return Ok(false);
+        }
+
+        let product = self.create_product_document(product_desc).await?;
+        if let Err(e) = products.insert_one(&product).await {
+            // the images are only referenced by the product
+            self.delete_images([product.preview, product.photo]).await?;
+
+            // another instance added the product concurrently
+            if is_duplicate_key_error(&e) {
+                info!(
+                    "Product with id {} already exists in the database",
+                    product_desc.info.id
+                );
+                return Ok(false);
+            }
+
+            error!(
+                "Failed to add product with id {}: {}",
+                product_desc.info.id, e
+            );
+            return Err(db_error(e));
+        }
+
+        info!("New product {} added", product_desc.info.id);
+
+        Ok(true)
+    }
+
+    async fn new_products(&self, products: &[ProductDescription]) -> ProductDBResult<u64> {
+        info!("New {} products", products.len());
+
+        let ids: Vec<&str> = products.iter().map(|p| p.info.id.as_str()).collect();
+        let collection = self.collection::<ProductDocument>(PRODUCTS);
+        let existing: HashSet<(String, Option<String>)> = collection
+            .clone_with_type::<Document>()
+            .find(doc! { "info.id": { "$in": &ids } })
+            .projection(doc! { "info.id": 1, "info.market": 1 })
+            .await
+            .map_err(db_error)?
+            .try_collect::<Vec<Document>>()
+            .await
+            .map_err(db_error)?
+            .iter()
+            .filter_map(|p| {
+                let info = p.get_document("info").ok()?;
+                let market = info.get_str("market").ok().map(str::to_string);
+                Some((info.get_str("id").ok()?.to_string(), market))
+            })
+            .collect();
+
+        // skip the existing products and the repeated ones within the batch
+        let mut added: HashSet<(String, Option<String>)> = HashSet::new();
+        let mut documents = Vec::new();
+        for product_desc in products {
+            let key = (
+                product_desc.info.id.clone(),
+                product_desc.info.market.clone(),
+            );
+            if existing.contains(&key) || !added.insert(key) {
+                debug!(
+                    "Product with id {} and market {:?} already exists in the database",
+                    product_desc.info.id, product_desc.info.market
+                );
+                continue;
+            }
+
+            documents.push(self.create_product_document(product_desc).await?);
+        }
+
+        if documents.is_empty() {
+            info!("Added 0 of {} new products", products.len());
+            return Ok(0);
+        }
+
+        if let Err(e) = collection.insert_many(&documents).await {
+            error!("Failed to add new products: {}", e);
+
+            // either all or none of the products are stored
+            let filters: Vec<Document> = documents
+                .iter()
+                .map(|p| ProductDocument::filter(&p.info))
+                .collect();
+            if let Err(e) = collection.delete_many(doc! { "$or": filters }).await {
+                error!("Failed to remove the partially stored products: {}", e);
+            }
+            self.delete_images(documents.iter().flat_map(|p| [p.preview, p.photo]))
+                .await?;
+
+            return Err(db_error(e));
+        }
+
+        info!(
+            "Added {} of {} new products",
+            documents.len(),
+            products.len()
+        );
+
+        Ok(documents.len() as u64)
+    }
+
+    async fn add_product_request_images(
+        &self,
+        id: DBId,
+        images: &[AdditionalImage],
+    ) -> ProductDBResult<bool> {
+        info!("Add {} images to product request {}", images.len(), id);
+
+        self.append_additional_images(
+            &self.collection::<RequestDocument>(PRODUCT_REQUESTS),
+            doc! { "_id": id },
+            "product.images",
+            images,
+        )
+        .await
+    }
+
+    async fn add_product_images(
+        &self,
+        id: &ProductID,
+        images: &[AdditionalImage],
+    ) -> ProductDBResult<bool> {
+        info!("Add {} images to product {}", images.len(), id);
+
+        self.append_additional_images(
+            &self.collection::<ProductDocument>(PRODUCTS),
+            doc! { "info.id": id.as_str() },
+            "images",
+            images,
+        )
+        .await
+    }
+
+    async fn update_product(&self, product_desc: &ProductDescription) -> ProductDBResult<bool> {
+        let id = &product_desc.info.id;
+        info!("Update product with id: {}", id);
+
+        let new_preview = self.insert_image(product_desc.preview.as_ref()).await?;
+        let new_photo = self.insert_image(product_desc.full_image.as_ref()).await?;
+
+        let mut update = doc! {
+            "info": to_bson(&product_desc.info)?,
+            "gtin": normalize_gtin(id),
+            "nutrients": to_bson(&product_desc.nutrients)?,
+            "packaging": to_bson(&product_desc.packaging)?,
+            "eco_score": to_bson(&product_desc.eco_score)?,
+        };
+        if new_preview.is_some() {
+            update.insert("preview", new_preview);
+        }
+        if new_photo.is_some() {
+            update.insert("photo", new_photo);
+        }
+
+        let old_product = self
+            .collection::<ProductDocument>(PRODUCTS)
+            .find_one_and_update(
+                ProductDocument::filter(&product_desc.info),
+                doc! { "$set": update },
+            )
+            .await;
+
+        // the replaced images are only referenced by the product
+        match old_product {
+            Ok(Some(old_product)) => {
+                let replaced = [
+                    new_preview.and(old_product.preview),
+                    new_photo.and(old_product.photo),
+                ];
+                self.delete_images(replaced).await?;
+                info!("Updated product with id: {}", id);
+                Ok(true)
+            }
+            Ok(None) => {
+                debug!("No product with id: {}", id);
+                self.delete_images([new_preview, new_photo]).await?;
+                Ok(false)
+            }
+            Err(e) => {
+                error!("Failed to update product {}: {}", id, e);
+                self.delete_images([new_preview, new_photo]).await?;
+                Err(db_error(e))
+            }
+        }
+    }
+
+    async fn delete_product(&self, id: &ProductID) -> ProductDBResult<()> {
+        info!("Delete product with id: {}", id);
+
+        // the products of all markets are deleted
+        let collection = self.collection::<ProductDocument>(PRODUCTS);
+        let products: Vec<ProductDocument> = collection
+            .find(doc! { "info.id": id.as_str() })
+            .await
+            .map_err(db_error)?
+            .try_collect()
+            .await
+            .map_err(db_error)?;
+        collection
+            .delete_many(doc! { "info.id": id.as_str() })
+            .await
+            .map_err(|e| {
+                error!("Failed to delete product: {}", e);
+                db_error(e)
+            })?;
+
+        self.delete_images(products.iter().flat_map(ProductDocument::image_ids))
+            .await?;
+
+        info!("Deleted product with id: {}", id);
+
+        Ok(())
+    }
+
+    async fn update_product_preview(
+        &self,
+        id: &ProductID,
+        preview: &ProductImage,
+    ) -> ProductDBResult<bool> {
+        info!("Update preview of product with id: {}", id);
+
+        // every market has its own product and thus its own copy of the preview
+        let collection = self.collection::<ProductDocument>(PRODUCTS);
+        let markets: Vec<Bson> = collection
+            .distinct("info.market", doc! { "info.id": id.as_str() })
+            .await
+            .map_err(db_error)?;
+
+        let mut updated = false;
+        for market in markets {
+            let new_preview = self.insert_image(Some(preview)).await?;
+            let old_product = collection
+                .find_one_and_update(
+                    doc! { "info.id": id.as_str(), "info.market": market },
+                    doc! { "$set": { "preview": new_preview } },
+                )
+                .await;
+
+            // the previous preview is only referenced by the product
+            match old_product {
+                Ok(Some(old_product)) => {
+                    self.delete_images([old_product.preview]).await?;
+                    updated = true;
+                }
+                Ok(None) => self.delete_images([new_preview]).await?,
+                Err(e) => {
+                    error!("Failed to update preview of product {}: {}", id, e);
+                    self.delete_images([new_preview]).await?;
+                    return Err(db_error(e));
+                }
+            }
+        }
+
+        if updated {
+            info!("Updated preview of product with id: {}", id);
+        } else {
+            debug!("No product with id: {}", id);
+        }
+
+        Ok(updated)
+    }
+
+    async fn update_product_request_preview(
+        &self,
+        id: DBId,
+        preview: &ProductImage,
+    ) -> ProductDBResult<bool> {
+        info!("Update preview of product request with id: {}", id);
+
+        let new_preview = self.insert_image(Some(preview)).await?;
+        let old_request = self
+            .collection::<RequestDocument>(PRODUCT_REQUESTS)
+            .find_one_and_update(
+                doc! { "_id": id },
+                doc! { "$set": { "product.preview": new_preview } },
+            )
+            .await;
+
+        match old_request {
+            Ok(Some(old_request)) => {
+                self.delete_images([old_request.product.preview]).await?;
+                info!("Updated preview of product request with id: {}", id);
+                Ok(true)
+            }
+            Ok(None) => {
+                debug!("No product request with id: {}", id);
+                self.delete_images([new_preview]).await?;
+                Ok(false)
+            }
+            Err(e) => {
+                error!("Failed to update preview of product request {}: {}", id, e);
+                self.delete_images([new_preview]).await?;
+                Err(db_error(e))
+            }
+        }
+    }
+
+    async fn new_recipe(&self, recipe: &Recipe) -> ProductDBResult<DBId> {
+        info!("New recipe '{}'", recipe.name);
+
+        let id = self.reserve_ids(RECIPES, 1).await?;
+        let document = RecipeDocument {
+            id,
+            name: recipe.name.clone(),
+            servings: recipe.servings,
+            ingredients: recipe.ingredients.clone(),
+            image: self.insert_image(recipe.image.as_ref()).await?,
+        };
+
+        if let Err(e) = self
+            .collection::<RecipeDocument>(RECIPES)
+            .insert_one(&document)
+            .await
+        {
+            error!("Failed to add recipe '{}': {}", recipe.name, e);
+            self.delete_images([document.image]).await?;
+            return Err(db_error(e));
+        }
+
+        info!("New recipe '{}' added with id: {}", recipe.name, id);
+
+        Ok(id)
+    }
+
+    async fn update_recipe(&self, id: DBId, recipe: &Recipe) -> ProductDBResult<bool> {
+        info!("Update recipe with id: {}", id);
+
+        let recipes = self.collection::<RecipeDocument>(RECIPES);
+        let old_recipe = recipes
+            .find_one(doc! { "_id": id })
+            .await
+            .map_err(db_error)?;
+        let Some(old_recipe) = old_recipe else {
+            debug!("No recipe with id: {}", id);
+            return Ok(false);
+        };
+
+        let document = RecipeDocument {
+            id,
+            name: recipe.name.clone(),
+            servings: recipe.servings,
+            ingredients: recipe.ingredients.clone(),
+            image: self.insert_image(recipe.image.as_ref()).await?,
+        };
+
+        let result = match recipes.replace_one(doc! { "_id": id }, &document).await {
+            Ok(result) => result,
+            Err(e) => {
+                error!("Failed to update recipe {}: {}", id, e);
+                self.delete_images([document.image]).await?;
+                return Err(db_error(e));
+            }
+        };
+
+        // the recipe may have been deleted concurrently
+        if result.matched_count == 0 {
+            debug!("No recipe with id: {}", id);
+            self.delete_images([document.image]).await?;
+            return Ok(false);
+        }
+
+        self.delete_images([old_recipe.image]).await?;
+        info!("Updated recipe with id: {}", id);
+
+        Ok(true)
+    }
+
+    async fn delete_recipe(&self, id: DBId) -> ProductDBResult<()> {
+        info!("Delete recipe with id: {}", id);
+
+        let recipe = self
+            .collection::<RecipeDocument>(RECIPES)
+            .find_one_and_delete(doc! { "_id": id })
+            .await
+            .map_err(|e| {
+                error!("Failed to delete recipe: {}", e);
+                db_error(e)
+            })?;
+
+        if let Some(recipe) = recipe {
+            self.delete_images([recipe.image]).await?;
+        }
+
+        info!("Deleted recipe with id: {}", id);
+
+        Ok(())
+    }
+
+    async fn submit_price(&self, price: &PriceObservation) -> ProductDBResult<DBId> {
+        debug!(
+            "Submit price {} {} of product {} at {}",
+            price.price, price.currency, price.product_id, price.store
+        );
+
+        let id = self.reserve_ids(PRICES, 1).await?;
+        let document = PriceDocument {
+            id,
+            product_id: price.product_id.clone(),
+            store: price.store.clone(),
+            price: price.price,
+            currency: price.currency.clone(),
+            observed_at: price.observed_at,
+        };
+
+        self.collection::<PriceDocument>(PRICES)
+            .insert_one(&document)
+            .await
+            .map_err(|e| {
+                error!(
+                    "Failed to submit price of product {}: {}",
+                    price.product_id, e
+                );
+                db_error(e)
+            })?;
+
+        info!(
+            "Price of product {} submitted with id: {}",
+            price.product_id, id
+        );
+
+        Ok(id)
+    }
+
+    async fn new_store(&self, store: &Store) -> ProductDBResult<DBId> {
+        info!("New store '{}'", store.name);
+
+        let id = self.reserve_ids(STORES, 1).await?;
+        let document = StoreDocument {
+            id,
+            name: store.name.clone(),
+            chain: store.chain.clone(),
+        };
+
+        self.collection::<StoreDocument>(STORES)
+            .insert_one(&document)
+            .await
+            .map_err(|e| {
+                error!("Failed to add store '{}': {}", store.name, e);
+                db_error(e)
+            })?;
+
+        info!("Added store '{}' with id: {}", store.name, id);
+
+        Ok(id)
+    }
+
+    async fn delete_store(&self, id: DBId) -> ProductDBResult<()> {
+        info!("Delete store with id: {}", id);
+
+        self.collection::<StoreDocument>(STORES)
+            .delete_one(doc! { "_id": id })
+            .await
+            .map_err(|e| {
+                error!("Failed to delete store: {}", e);
+                db_error(e)
+            })?;
+
+        // the availability is deleted and the reports lose their store
+        self.collection::<AvailabilityDocument>(PRODUCT_AVAILABILITY)
+            .delete_many(doc! { "store_id": id })
+            .await
+            .map_err(db_error)?;
+        self.collection::<MissingProductDocument>(MISSING_PRODUCTS)
+            .update_many(
+                doc! { "store_id": id },
+                doc! { "$set": { "store_id": Bson::Null } },
+            )
+            .await
+            .map_err(db_error)?;
+
+        info!("Deleted store with id: {}", id);
+
+        Ok(())
+    }
+
+    async fn report_availability(
+        &self,
+        availability: &ProductAvailability,
+    ) -> ProductDBResult<bool> {
+        debug!(
+            "Report product {} as {} at store {}",
+            availability.product_id,
+            if availability.available {
+                "available"
+            } else {
+                "missing"
+            },
+            availability.store_id
+        );
+
+        let store_exists = self
+            .collection::<StoreDocument>(STORES)
+            .count_documents(doc! { "_id": availability.store_id })
+            .await
+            .map_err(db_error)?
+            > 0;
+        if !store_exists {
+            debug!("No store with id: {}", availability.store_id);
+            return Ok(false);
+        }
+
+        self.collection::<AvailabilityDocument>(PRODUCT_AVAILABILITY)
+            .update_one(
+                doc! {
+                    "store_id": availability.store_id,
+                    "product_id": availability.product_id.as_str(),
+                },
+                doc! {
+                    "$set": {
+                        "available": availability.available,
+                        "date": bson::DateTime::from_chrono(availability.date),
+                    }
+                },
+            )
+            .upsert(true)
+            .await
+            .map_err(|e| {
+                error!("Failed to report the availability: {}", e);
+                db_error(e)
+            })?;
+
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{Sorting, SortingOrder};
+
+    #[test]
+    fn test_producer_regex() {
+        assert_eq!(producer_regex("alpro"), "^alpro$");
+        assert_eq!(producer_regex("alp*"), "^alp.*$");
+        assert_eq!(
+            producer_regex("H. & J. (Aldi)*"),
+            "^H\\. & J\\. \\(Aldi\\).*$"
+        );
+    }
+
+    #[test]
+    fn test_product_sort() {
+        let mut query = ProductQuery {
+            offset: 0,
+            limit: 10,
+            filter: SearchFilter::NoFilter,
+            sorting: None,
+            market: None,
+            producer: None,
+            quantity_type: None,
+            labels: None,
+            max_nova_group: None,
+            min_eco_score_grade: None,
+            exclude_allergens: Vec::new(),
+            data_issues: Vec::new(),
+            fields: None,
+            request_status: None,
+            cursor: None,
+        };
+        assert_eq!(product_sort(&query, "", None, &["info.id"]).unwrap(), None);
+
+        // the unique fields break ties in the same order
+        query.sorting = Some(Sorting {
+            order: SortingOrder::Descending,
+            field: SortingField::Name,
+        });
+        assert_eq!(
+            product_sort(&query, "product.", Some("date"), &["_id"]).unwrap(),
+            Some(doc! { "product.info.name": -1, "_id": -1 })
+        );
+
+        // the reported date is only defined for the product requests
+        query.sorting = Some(Sorting {
+            order: SortingOrder::Ascending,
+            field: SortingField::ReportedDate,
+        });
+        assert_eq!(
+            product_sort(&query, "product.", Some("date"), &["_id"]).unwrap(),
+            Some(doc! { "date": 1, "_id": 1 })
+        );
+        assert!(matches!(
+            product_sort(&query, "", None, &["info.id"]),
+            Err(Error::InvalidSortingError(SortingField::ReportedDate))
+        ));
+
+        // the similarity requires a search string
+        query.sorting = Some(Sorting {
+            order: SortingOrder::Descending,
+            field: SortingField::Similarity,
+        });
+        assert!(matches!(
+            product_sort(&query, "", None, &["info.id"]),
+            Err(Error::InvalidSortingError(SortingField::Similarity))
+        ));
+        query.filter = SearchFilter::Search("milk".to_string());
+        assert_eq!(
+            product_sort(&query, "", None, &["info.id"]).unwrap(),
+            Some(doc! { "score": { "$meta": "textScore" }, "info.id": -1 })
+        );
+    }
+
+    #[test]
+    fn test_similarity() {
+        assert_eq!(similarity(0.0), 0.0);
+        assert_eq!(similarity(1.0), 0.5);
+        assert!(similarity(3.0) > similarity(2.0));
+        assert!(similarity(1e9) <= 1.0);
+    }
+
+    #[test]
+    fn test_product_document() {
+        let product_data = include_str!("../../test_data/products.json");
+        let products: Vec<ProductDescription> = serde_json::from_str(product_data).unwrap();
+
+        for product in products {
+            let document = ProductDocument {
+                info: product.info.clone(),
+                gtin: normalize_gtin(&product.info.id),
+                nutrients: product.nutrients.clone(),
+                packaging: product.packaging.clone(),
+                eco_score: product.eco_score.clone(),
+                preview: product.preview.as_ref().map(|_| ObjectId::new()),
+                photo: None,
+                images: Vec::new(),
+                score: Some(1.0),
+            };
+
+            // the score is only read from the database
+            let encoded = bson::to_document(&document).unwrap();
+            assert!(!encoded.contains_key("score"));
+
+            let decoded: ProductDocument = bson::from_document(encoded).unwrap();
+            assert_eq!(decoded.score, None);
+            assert_eq!(
+                decoded.into_description(product.preview.clone()),
+                ProductDescription {
+                    full_image: None,
+                    ..product
+                }
+            );
+        }
+    }
+}
@@ -0,0 +1,181 @@
+use std::collections::HashMap;
+#[cfg(feature = "service")]
+use std::collections::HashSet;
+
+#[cfg(feature = "service")]
+use log::{debug, info};
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "service")]
+use crate::{DataBackend, Error, Result};
+use crate::{Nutrients, ProductDescription, ProductID};
+
+/// The maximal number of portions that can be aggregated at once.
+pub const MAX_AGGREGATED_PORTIONS: usize = 500;
+
+/// A portion of a product, e.g. the 250ml of milk of a meal.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct Portion {
+    /// The id of the product.
+    pub product_id: ProductID,
+    /// The amount of the product in g or ml depending on the quantity type of the product.
+    pub amount: f32,
+}
+
+/// The summed nutrients of a list of portions.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct NutrientAggregation {
+    /// The summed nutrients of all portions whose product is known.
+    pub nutrients: Nutrients,
+    /// The ids of the products that are not in the database and have not been summed up.
+    pub missing_products: Vec<ProductID>,
+}
+
+/// Sums up the nutrients of the given portions. The nutrients of the products are stated per
+/// 100g or 100ml and are scaled by the amount of the portion. Portions of products that are
+/// not in the given list are reported as missing.
+///
+/// # Arguments
+/// - `products` - The products referenced by the portions.
+/// - `portions` - The portions to sum up.
+pub fn sum_nutrients(products: &[ProductDescription], portions: &[Portion]) -> NutrientAggregation {
+    let products: HashMap<&ProductID, &Nutrients> = products
+        .iter()
+        .map(|p| (&p.info.id, &p.nutrients))
+        .collect();
+
+    let mut nutrients = Nutrients::default();
+    let mut missing_products: Vec<ProductID> = Vec::new();
+    for portion in portions {
+        match products.get(&portion.product_id) {
+            Some(product_nutrients) => {
+                nutrients = nutrients + product_nutrients.scale(portion.amount / 100.0);
+            }
+            None => {
+                if !missing_products.contains(&portion.product_id) {
+                    missing_products.push(portion.product_id.clone());
+                }
+            }
+        }
+    }
+
+    NutrientAggregation {
+        nutrients,
+        missing_products,
+    }
+}
+
+/// Sums up the nutrients of the given portions, e.g. for the total of a meal. The products are
+/// resolved in a single query of the backend.
+///
+/// # Arguments
+/// - `db` - The data backend to resolve the products with.
+/// - `portions` - The portions to sum up.
+#[cfg(feature = "service")]
+pub async fn aggregate_nutrients<DB: DataBackend>(
+    db: &DB,
+    portions: &[Portion],
+) -> Result<NutrientAggregation> {
+    if portions.len() > MAX_AGGREGATED_PORTIONS {
+        return Err(Error::InvalidPortion(format!(
+            "{} portions exceed the maximum of {}",
+            portions.len(),
+            MAX_AGGREGATED_PORTIONS
+        )));
+    }
+
+    if let Some(portion) = portions
+        .iter()
+        .find(|p| !p.amount.is_finite() || p.amount < 0.0)
+    {
+        return Err(Error::InvalidPortion(format!(
+            "{} of product {} is not a valid amount in g or ml",
+            portion.amount, portion.product_id
+        )));
+    }
+
+    let ids = unique_product_ids(portions);
+
+    debug!(
+        "Aggregating the nutrients of {} portions of {} products",
+        portions.len(),
+        ids.len()
+    );
+    let products = db.get_products(&ids).await?;
+    let aggregation = sum_nutrients(&products, portions);
+
+    info!(
+        "Aggregated the nutrients of {} portions, {} products missing",
+        portions.len(),
+        aggregation.missing_products.len()
+    );
+
+    Ok(aggregation)
+}
+
+/// Returns the ids of the products referenced by the portions without duplicates.
+///
+/// # Arguments
+/// - `portions` - The portions referencing the products.
+#[cfg(feature = "service")]
+pub(crate) fn unique_product_ids(portions: &[Portion]) -> Vec<ProductID> {
+    let mut seen = HashSet::new();
+    portions
+        .iter()
+        .filter(|p| seen.insert(&p.product_id))
+        .map(|p| p.product_id.clone())
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::Weight;
+
+    #[test]
+    fn test_sum_nutrients() {
+        let product_data = include_str!("../../test_data/products.json");
+        let products: Vec<ProductDescription> = serde_json::from_str(product_data).unwrap();
+        let first = &products[0];
+        let second = &products[1];
+
+        let portions = vec![
+            Portion {
+                product_id: first.info.id.clone(),
+                amount: 250.0,
+            },
+            Portion {
+                product_id: second.info.id.clone(),
+                amount: 50.0,
+            },
+            Portion {
+                product_id: "unknown".to_string(),
+                amount: 100.0,
+            },
+            Portion {
+                product_id: "unknown".to_string(),
+                amount: 10.0,
+            },
+        ];
+
+        let aggregation = sum_nutrients(&products, &portions);
+        assert_eq!(aggregation.missing_products, vec!["unknown".to_string()]);
+
+        let expected = first.nutrients.kcal * 2.5 + second.nutrients.kcal * 0.5;
+        assert!((aggregation.nutrients.kcal - expected).abs() < 1e-2);
+
+        let protein = |n: &Nutrients| n.protein.map(Weight::gram).unwrap_or_default();
+        let expected = protein(&first.nutrients) * 2.5 + protein(&second.nutrients) * 0.5;
+        assert!((protein(&aggregation.nutrients) - expected).abs() < 1e-3);
+
+        // nothing to sum up
+        let aggregation = sum_nutrients(&products, &[]);
+        assert_eq!(aggregation.nutrients, Nutrients::default());
+        assert!(aggregation.missing_products.is_empty());
+    }
+}
@@ -0,0 +1,131 @@
+use log::error;
+
+use crate::{Error, Result};
+
+/// The OpenAPI specification of the REST API, which is maintained in `doc/openapi.yaml`.
+pub const OPENAPI_SPEC: &str = include_str!("../../doc/openapi.yaml");
+
+/// The content type of the Swagger UI page.
+pub const HTML_CONTENT_TYPE: &str = "text/html; charset=utf-8";
+
+/// Returns the OpenAPI specification as JSON document. The server url respects the prefix of the
+/// endpoint and the version is the version of the crate.
+///
+/// # Arguments
+/// - `prefix` - The prefix of the endpoint, if any.
+pub fn openapi_document(prefix: Option<&str>) -> Result<serde_json::Value> {
+    let mut document: serde_json::Value = serde_yaml::from_str(OPENAPI_SPEC).map_err(|e| {
+        error!("Failed to parse the OpenAPI specification: {}", e);
+        Error::InternalError(format!("Failed to parse the OpenAPI specification: {}", e))
+    })?;
+
+    document["info"]["version"] = env!("CARGO_PKG_VERSION").into();
+    if let Some(servers) = document["servers"].as_array_mut() {
+        for server in servers {
+            server["url"] = format!("{}/v1", prefix.unwrap_or("")).into();
+        }
+    }
+
+    Ok(document)
+}
+
+/// Returns the page of the Swagger UI for the OpenAPI specification at the given url.
+///
+/// # Arguments
+/// - `assets_url` - The base url of the `swagger-ui-dist` assets.
+/// - `spec_url` - The url of the OpenAPI specification.
+pub fn swagger_ui_page(assets_url: &str, spec_url: &str) -> String {
+    let assets_url = assets_url.trim_end_matches('/');
+
+    format!(
+        r##"<!DOCTYPE html>
+<html lang="en">
+<head>
+  <meta charset="utf-8">
+  <title>Product Database API</title>
+  <link rel="stylesheet" href="{assets_url}/swagger-ui.css">
+</head>
+<body>
+  <div id="swagger-ui"></div>
+  <script src="{assets_url}/swagger-ui-bundle.js"></script>
+  <script>
+    window.ui = SwaggerUIBundle({{ url: "{spec_url}", dom_id: "#swagger-ui" }});
+  </script>
+</body>
+</html>
+"##
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Replaces the names of the path parameters, e.g. "/product/{id}" becomes "/product/{}".
+    fn normalize(path: &str) -> String {
+        let mut normalized = String::with_capacity(path.len());
+        let mut in_parameter = false;
+        for c in path.chars() {
+            match c {
+                '{' => {
+                    in_parameter = true;
+                    normalized.push_str("{}");
+                }
+                '}' => in_parameter = false,
+                c if !in_parameter => normalized.push(c),
+                _ => {}
+            }
+        }
+
+        normalized
+    }
+
+    #[test]
+    fn test_openapi_document() {
+        let document = openapi_document(Some("/api")).unwrap();
+        assert_eq!(document["info"]["version"], env!("CARGO_PKG_VERSION"));
+        assert_eq!(document["servers"][0]["url"], "/api/v1");
+
+        let document = openapi_document(None).unwrap();
+        assert_eq!(document["servers"][0]["url"], "/v1");
+    }
+
+    #[test]
+    fn test_routes_are_documented() {
+        let document = openapi_document(None).unwrap();
+        let paths: Vec<String> = document["paths"]
+            .as_object()
+            .unwrap()
+            .keys()
+            .map(|path| normalize(path))
+            .collect();
+
+        // the routes are relative to the nested user, admin or public routers, whereas the
+        // metrics and the probes are served outside of /v1
+        let service = include_str!("service.rs");
+        let routes: Vec<String> = service
+            .split(".route(")
+            .skip(1)
+            .filter_map(|call| call.trim_start().strip_prefix('"'))
+            .filter_map(|call| call.split('"').next())
+            .filter(|route| !["/metrics", "/healthz", "/readyz"].contains(route))
+            .map(|route| normalize(route.strip_prefix("/v1").unwrap_or(route)))
+            .collect();
+        assert!(!routes.is_empty());
+
+        for route in routes {
+            assert!(
+                paths.iter().any(|path| path.ends_with(&route)),
+                "The route {} is missing in doc/openapi.yaml",
+                route
+            );
+        }
+    }
+
+    #[test]
+    fn test_swagger_ui_page() {
+        let page = swagger_ui_page("https://cdn.example.com/swagger/", "/v1/openapi.json");
+        assert!(page.contains(r#"href="https://cdn.example.com/swagger/swagger-ui.css""#));
+        assert!(page.contains(r#"url: "/v1/openapi.json""#));
+    }
+}
@@ -1,6 +1,11 @@
+use std::{path::PathBuf, time::Duration};
+
+use chrono::{DateTime, Utc};
+use ipnet::IpNet;
+use jsonwebtoken::Algorithm;
 use serde::Deserialize;
 
-use crate::PostgresConfig;
+use crate::{PostgresConfig, Secret};
 
 /// The options for running the product database.
 #[derive(Debug, Clone)]
@@ -9,6 +14,44 @@ pub struct Options {
     pub endpoint: EndpointOptions,
     /// The Postgres config.
     pub postgres: PostgresConfig,
+    /// The options for archiving processed product requests.
+    pub archive: ArchiveOptions,
+    /// The MongoDB config, which is only used by the MongoDB backend.
+    pub mongodb: Option<MongoConfig>,
+}
+
+/// The configuration for connecting to the MongoDB database.
+#[derive(Debug, Clone, Deserialize)]
+pub struct MongoConfig {
+    /// The connection string of the deployment, e.g. "mongodb://localhost:27017".
+    pub uri: String,
+    /// The user for authenticating, if not given by the connection string.
+    #[serde(default)]
+    pub user: Option<String>,
+    /// The password of the user.
+    #[serde(default)]
+    pub password: Secret,
+    /// The name of the database that contains the collections of the product database.
+    pub dbname: String,
+    /// The maximal number of connections per server. Defaults to the default of the driver.
+    #[serde(default)]
+    pub max_connections: Option<u32>,
+}
+
+/// The options for archiving processed product requests.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ArchiveOptions {
+    /// The interval in seconds in which product requests whose product has been added to the
+    /// database are archived as approved. The scheduled archival is disabled if undefined.
+    #[serde(default)]
+    pub interval_secs: Option<u64>,
+}
+
+impl ArchiveOptions {
+    /// Returns the interval of the scheduled archival, if enabled.
+    pub fn interval(&self) -> Option<Duration> {
+        self.interval_secs.map(Duration::from_secs)
+    }
 }
 
 /// The options for the endpoint.
@@ -17,20 +60,555 @@ pub struct EndpointOptions {
     /// The address to bind the endpoint to.
     pub address: String,
 
+    /// The address to bind the admin endpoints to, e.g. "127.0.0.1:8081". If defined, the
+    /// admin endpoints are only served on this address and not on `address`.
+    #[serde(default)]
+    pub admin_address: Option<String>,
+
     /// The allowed origin for CORS requests.
     pub allow_origin: String,
 
+    /// The allowed origin for CORS requests on the user and public endpoints. Defaults to
+    /// `allow_origin` if undefined.
+    #[serde(default)]
+    pub user_allow_origin: Option<String>,
+
+    /// The allowed origin for CORS requests on the admin endpoints and the metrics, e.g. only
+    /// the origin of an internal dashboard. Defaults to `allow_origin` if undefined.
+    #[serde(default)]
+    pub admin_allow_origin: Option<String>,
+
     /// The prefix for the endpoint.
     #[serde(default)]
     pub prefix: Option<String>,
+
+    /// If true, the service starts in read-only (maintenance) mode, i.e., all mutating
+    /// endpoints are rejected until the mode is switched off again.
+    #[serde(default)]
+    pub read_only: bool,
+
+    /// The timeout in milliseconds for handling requests on the JSON endpoints, e.g. queries.
+    #[serde(default = "EndpointOptions::default_query_timeout_ms")]
+    pub query_timeout_ms: u64,
+
+    /// The timeout in milliseconds for handling requests on the image endpoints.
+    #[serde(default = "EndpointOptions::default_image_timeout_ms")]
+    pub image_timeout_ms: u64,
+
+    /// The limits for the requests of each client on the image endpoints, independent of the
+    /// other endpoints. The image endpoints are not limited if undefined.
+    #[serde(default)]
+    pub image_rate_limit: Option<RateLimitOptions>,
+
+    /// The secret key for signing public image urls. Signed image urls are disabled if no
+    /// secret is configured.
+    #[serde(default)]
+    pub image_url_secret: Option<Secret>,
+
+    /// The duration in seconds for which signed image urls are valid.
+    #[serde(default = "EndpointOptions::default_image_url_ttl_secs")]
+    pub image_url_ttl_secs: u64,
+
+    /// The limits for storing the images of submitted products and product requests.
+    #[serde(default)]
+    pub image_quota: ImageQuotaOptions,
+
+    /// The options for generating the previews of submitted products and product requests with
+    /// a full image but without preview. No previews are generated if undefined.
+    #[serde(default)]
+    pub previews: Option<PreviewOptions>,
+
+    /// The handling of implausible nutrients of submitted products and product requests.
+    #[serde(default)]
+    pub nutrient_plausibility: NutrientPlausibilityOptions,
+
+    /// The Cache-Control directives for the different kinds of responses.
+    #[serde(default)]
+    pub cache_control: CacheControlOptions,
+
+    /// The options for requiring signed requests on the admin endpoints. The admin endpoints
+    /// accept unsigned requests if undefined.
+    #[serde(default)]
+    pub request_signing: Option<RequestSigningOptions>,
+
+    /// The options for serving the endpoint via TLS. The endpoint is served via plain HTTP if
+    /// undefined.
+    #[serde(default)]
+    pub tls: Option<TlsOptions>,
+
+    /// The networks from which the admin endpoints are accessible. The admin endpoints are
+    /// accessible from everywhere if undefined.
+    #[serde(default)]
+    pub admin_allowlist: Option<AdminAllowlistOptions>,
+
+    /// The options for authenticating the clients via API keys or JWTs. The admin and the user
+    /// endpoints accept unauthenticated requests if undefined.
+    #[serde(default)]
+    pub auth: Option<AuthOptions>,
+
+    /// If true, the metrics are served in the Prometheus text format under `/metrics`, which is
+    /// part of the admin endpoints regarding the admin address.
+    #[serde(default)]
+    pub metrics: bool,
+
+    /// The options for serving the Swagger UI of the OpenAPI specification under `/v1/docs`.
+    /// The Swagger UI is disabled if undefined, while the specification is always served under
+    /// `/v1/openapi.json`.
+    #[serde(default)]
+    pub swagger_ui: Option<SwaggerUiOptions>,
+
+    /// The deprecated routes and parameters, whose responses announce the deprecation via the
+    /// Deprecation, Sunset and Link headers.
+    #[serde(default)]
+    pub deprecations: Vec<DeprecationOptions>,
+}
+
+/// The options for serving the Swagger UI.
+#[derive(Debug, Clone, Deserialize)]
+pub struct SwaggerUiOptions {
+    /// The base url of the `swagger-ui-dist` assets, e.g. of a CDN or a self-hosted copy.
+    #[serde(default = "SwaggerUiOptions::default_assets_url")]
+    pub assets_url: String,
+}
+
+impl SwaggerUiOptions {
+    fn default_assets_url() -> String {
+        "https://unpkg.com/swagger-ui-dist@5".to_string()
+    }
+}
+
+/// The options for marking a route or a query parameter as deprecated.
+#[derive(Debug, Clone, Deserialize)]
+pub struct DeprecationOptions {
+    /// The route template relative to the prefix of the endpoint, e.g. "/v1/user/product/{id}".
+    pub route: String,
+
+    /// The deprecated method of the route, e.g. "GET". All methods are deprecated if undefined.
+    #[serde(default)]
+    pub method: Option<String>,
+
+    /// The deprecated query parameter, e.g. "with_preview". If defined, only the requests with
+    /// the parameter are marked as deprecated.
+    #[serde(default)]
+    pub parameter: Option<String>,
+
+    /// The date since which the route or the parameter is deprecated.
+    pub date: DateTime<Utc>,
+
+    /// The date after which the route or the parameter may be removed, if scheduled.
+    #[serde(default)]
+    pub sunset: Option<DateTime<Utc>>,
+
+    /// The url of the documentation about the deprecation, e.g. a migration guide.
+    #[serde(default)]
+    pub link: Option<String>,
+}
+
+/// The options for restricting the admin endpoints to the clients of the given networks.
+#[derive(Debug, Clone, Deserialize)]
+pub struct AdminAllowlistOptions {
+    /// The allowed networks in CIDR notation, e.g. "10.0.0.0/8" or "::1/128".
+    pub networks: Vec<IpNet>,
+
+    /// The number of trusted reverse proxies in front of the service. If greater than zero,
+    /// the client address is taken from the `X-Forwarded-For` header, skipping the addresses
+    /// appended by the trusted proxies. Otherwise, the address of the peer is used.
+    #[serde(default)]
+    pub trusted_proxy_depth: usize,
+}
+
+/// The options for authenticating the clients of the admin and the user endpoints.
+#[derive(Debug, Clone, Deserialize)]
+pub struct AuthOptions {
+    /// The API keys that are accepted on the admin endpoints via the `X-API-Key` header or as
+    /// bearer token of the `Authorization` header.
+    #[serde(default)]
+    pub admin_api_keys: Vec<Secret>,
+
+    /// The options for accepting the JWTs of an OIDC provider as bearer tokens. Only the API
+    /// keys are accepted if undefined.
+    #[serde(default)]
+    pub jwt: Option<JwtOptions>,
+}
+
+/// The options for verifying the JWTs of an OIDC provider, e.g. Keycloak or Auth0, and mapping
+/// their roles to the access of the endpoints.
+#[derive(Debug, Clone, Deserialize)]
+pub struct JwtOptions {
+    /// The expected issuer (`iss` claim) of the tokens.
+    pub issuer: String,
+
+    /// The expected audience (`aud` claim) of the tokens.
+    pub audience: String,
+
+    /// The url of the JSON Web Key Set with the keys of the issuer.
+    pub jwks_url: String,
+
+    /// The accepted signature algorithms of the tokens.
+    #[serde(default = "JwtOptions::default_algorithms")]
+    pub algorithms: Vec<Algorithm>,
+
+    /// The dot separated path of the claim with the roles of the client, e.g.
+    /// "realm_access.roles" for Keycloak. The claim contains an array of roles or a space
+    /// separated list.
+    #[serde(default = "JwtOptions::default_roles_claim")]
+    pub roles_claim: String,
+
+    /// The role that grants access to the admin and the user endpoints.
+    #[serde(default = "JwtOptions::default_admin_role")]
+    pub admin_role: String,
+
+    /// The role that grants access to the user endpoints. The user endpoints are accessible
+    /// without a token if undefined.
+    #[serde(default)]
+    pub user_role: Option<String>,
+
+    /// The interval in seconds after which the keys are fetched again from the JWKS url.
+    #[serde(default = "JwtOptions::default_jwks_refresh_secs")]
+    pub jwks_refresh_secs: u64,
+}
+
+impl JwtOptions {
+    fn default_algorithms() -> Vec<Algorithm> {
+        vec![Algorithm::RS256]
+    }
+
+    fn default_roles_claim() -> String {
+        "roles".to_string()
+    }
+
+    fn default_admin_role() -> String {
+        "admin".to_string()
+    }
+
+    fn default_jwks_refresh_secs() -> u64 {
+        3600
+    }
+
+    /// Returns the interval after which the keys are fetched again.
+    pub fn jwks_refresh_interval(&self) -> Duration {
+        Duration::from_secs(self.jwks_refresh_secs)
+    }
+}
+
+/// The options for limiting the requests of each client. A client may send `burst` requests
+/// at once and `requests_per_second` on average. Likewise, the bandwidth is limited by the
+/// bytes of the responses, if configured.
+#[derive(Debug, Clone, Deserialize)]
+pub struct RateLimitOptions {
+    /// The average number of requests per second of a client.
+    pub requests_per_second: f64,
+
+    /// The number of requests a client may send at once.
+    #[serde(default = "RateLimitOptions::default_burst")]
+    pub burst: u32,
+
+    /// The average number of response bytes per second of a client. The bandwidth is not
+    /// limited if undefined.
+    #[serde(default)]
+    pub bytes_per_second: Option<u64>,
+
+    /// The number of response bytes a client may receive at once. Defaults to the bytes of a
+    /// single second.
+    #[serde(default)]
+    pub burst_bytes: Option<u64>,
+
+    /// The number of trusted reverse proxies in front of the service for determining the
+    /// address of the client, see [`AdminAllowlistOptions::trusted_proxy_depth`].
+    #[serde(default)]
+    pub trusted_proxy_depth: usize,
+}
+
+impl RateLimitOptions {
+    fn default_burst() -> u32 {
+        10
+    }
+}
+
+/// The default limit of the request bodies, which is the default body limit of axum.
+const DEFAULT_MAX_BODY_BYTES: u64 = 2 * 1024 * 1024;
+
+/// The size of a request body besides its base64 encoded images, e.g. the nutrients.
+const MAX_BODY_OVERHEAD_BYTES: u64 = 1024 * 1024;
+
+/// The limits for storing images, which are enforced when products or product requests are
+/// submitted. No limit applies if undefined, except for the size of a single image, which is
+/// limited to 10 MiB by default.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ImageQuotaOptions {
+    /// The maximal number of bytes of all stored images.
+    #[serde(default)]
+    pub max_total_bytes: Option<u64>,
+
+    /// The maximal number of bytes of the images of a single product, i.e., of its preview, its
+    /// full image and its additional images together.
+    #[serde(default)]
+    pub max_product_bytes: Option<u64>,
+
+    /// The maximal number of bytes of a single image. Defaults to 10 MiB.
+    #[serde(default = "ImageQuotaOptions::default_max_image_bytes")]
+    pub max_image_bytes: Option<u64>,
+
+    /// Whether only JPEG, PNG, WebP, GIF and AVIF images are accepted, whose data must match
+    /// their content type. Checked by the magic bytes at the start of the data. Enabled by
+    /// default.
+    #[serde(default = "ImageQuotaOptions::default_verify_content_types")]
+    pub verify_content_types: bool,
+}
+
+impl ImageQuotaOptions {
+    /// Returns the maximal number of bytes of the request bodies, which must fit the base64
+    /// encoded images of a submission, i.e., all images of a product if `max_product_bytes` is
+    /// set and otherwise the preview and the full image. Without limits for the images, the
+    /// default body limit of 2 MiB applies, which is also the minimum.
+    pub fn max_body_bytes(&self) -> usize {
+        let images = self
+            .max_product_bytes
+            .or(self.max_image_bytes.map(|bytes| bytes.saturating_mul(2)));
+        let max_body_bytes = match images {
+            Some(bytes) => (bytes.div_ceil(3).saturating_mul(4))
+                .saturating_add(MAX_BODY_OVERHEAD_BYTES)
+                .max(DEFAULT_MAX_BODY_BYTES),
+            None => DEFAULT_MAX_BODY_BYTES,
+        };
+
+        usize::try_from(max_body_bytes).unwrap_or(usize::MAX)
+    }
+
+    fn default_max_image_bytes() -> Option<u64> {
+        Some(10 * 1024 * 1024)
+    }
+
+    fn default_verify_content_types() -> bool {
+        true
+    }
+}
+
+impl Default for ImageQuotaOptions {
+    fn default() -> Self {
+        Self {
+            max_total_bytes: None,
+            max_product_bytes: None,
+            max_image_bytes: Self::default_max_image_bytes(),
+            verify_content_types: Self::default_verify_content_types(),
+        }
+    }
+}
+
+/// The options for generating preview images from the full images. The previews are scaled
+/// down to fit into the maximal dimensions while keeping the aspect ratio.
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq)]
+pub struct PreviewOptions {
+    /// The maximal width of the previews in pixels.
+    #[serde(default = "PreviewOptions::default_max_dimension")]
+    pub max_width: u32,
+
+    /// The maximal height of the previews in pixels.
+    #[serde(default = "PreviewOptions::default_max_dimension")]
+    pub max_height: u32,
+}
+
+impl PreviewOptions {
+    fn default_max_dimension() -> u32 {
+        256
+    }
+}
+
+impl Default for PreviewOptions {
+    fn default() -> Self {
+        Self {
+            max_width: Self::default_max_dimension(),
+            max_height: Self::default_max_dimension(),
+        }
+    }
+}
+
+/// The handling of the submissions whose nutrients violate a plausibility rule, see
+/// [`crate::nutrient_issues`].
+#[derive(Debug, Clone, Copy, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PlausibilityMode {
+    /// The nutrients are not checked.
+    Off,
+    /// The submission is accepted and the violations are returned as warnings.
+    #[default]
+    Warn,
+    /// The submission is rejected.
+    Reject,
+}
+
+/// The handling of implausible nutrients, which is separately configured for product
+/// requests, which are reviewed anyway, and for the products added by the admins.
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct NutrientPlausibilityOptions {
+    /// The handling of implausible product requests, which are flagged by default.
+    #[serde(default)]
+    pub requests: PlausibilityMode,
+
+    /// The handling of implausible products, which are rejected by default.
+    #[serde(default = "NutrientPlausibilityOptions::default_products")]
+    pub products: PlausibilityMode,
+}
+
+impl NutrientPlausibilityOptions {
+    fn default_products() -> PlausibilityMode {
+        PlausibilityMode::Reject
+    }
+}
+
+impl Default for NutrientPlausibilityOptions {
+    fn default() -> Self {
+        Self {
+            requests: PlausibilityMode::default(),
+            products: Self::default_products(),
+        }
+    }
+}
+
+/// The options for serving the endpoint via TLS.
+#[derive(Debug, Clone, Deserialize)]
+pub struct TlsOptions {
+    /// The path to the PEM encoded certificate chain of the service.
+    pub cert_file: PathBuf,
+
+    /// The path to the PEM encoded private key of the service.
+    pub key_file: PathBuf,
+
+    /// The path to the PEM encoded CA certificates for verifying client certificates. If
+    /// defined, the admin endpoints require a client certificate issued by one of the CAs,
+    /// while the user endpoints stay accessible without client certificate.
+    #[serde(default)]
+    pub client_ca_file: Option<PathBuf>,
+}
+
+/// The options for verifying the HMAC signatures of requests from trusted services.
+#[derive(Debug, Clone, Deserialize)]
+pub struct RequestSigningOptions {
+    /// The secret key shared with the trusted services for signing their requests.
+    pub secret: Secret,
+
+    /// The maximal difference in seconds between the timestamp of a signed request and the
+    /// time of the service.
+    #[serde(default = "RequestSigningOptions::default_max_clock_skew_secs")]
+    pub max_clock_skew_secs: u64,
+}
+
+impl RequestSigningOptions {
+    fn default_max_clock_skew_secs() -> u64 {
+        300
+    }
+
+    /// Returns the maximal difference between the timestamp of a signed request and the time
+    /// of the service.
+    pub fn max_clock_skew(&self) -> Duration {
+        Duration::from_secs(self.max_clock_skew_secs)
+    }
+}
+
+/// The Cache-Control directives that are set on successful responses, e.g. "no-store" or
+/// "public, max-age=2592000, immutable". No header is set for undefined directives.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct CacheControlOptions {
+    /// The directive for responses with a single product or product request as JSON.
+    #[serde(default)]
+    pub product: Option<String>,
+
+    /// The directive for the results of queries.
+    #[serde(default)]
+    pub query: Option<String>,
+
+    /// The directive for the product images. The images of product requests and reported
+    /// missing products are never cached.
+    #[serde(default)]
+    pub image: Option<String>,
+}
+
+impl EndpointOptions {
+    fn default_query_timeout_ms() -> u64 {
+        30_000
+    }
+
+    fn default_image_timeout_ms() -> u64 {
+        60_000
+    }
+
+    fn default_image_url_ttl_secs() -> u64 {
+        3_600
+    }
+
+    /// Returns the timeout for handling requests on the JSON endpoints.
+    pub fn query_timeout(&self) -> Duration {
+        Duration::from_millis(self.query_timeout_ms)
+    }
+
+    /// Returns the timeout for handling requests on the image endpoints.
+    pub fn image_timeout(&self) -> Duration {
+        Duration::from_millis(self.image_timeout_ms)
+    }
+
+    /// Returns the duration for which signed image urls are valid.
+    pub fn image_url_ttl(&self) -> Duration {
+        Duration::from_secs(self.image_url_ttl_secs)
+    }
 }
 
 impl Default for EndpointOptions {
     fn default() -> Self {
         Self {
             address: "0.0.0.0:8080".to_string(),
+            admin_address: None,
             allow_origin: "*".to_string(),
+            user_allow_origin: None,
+            admin_allow_origin: None,
             prefix: None,
+            read_only: false,
+            query_timeout_ms: Self::default_query_timeout_ms(),
+            image_timeout_ms: Self::default_image_timeout_ms(),
+            image_rate_limit: None,
+            image_url_secret: None,
+            image_url_ttl_secs: Self::default_image_url_ttl_secs(),
+            image_quota: ImageQuotaOptions::default(),
+            previews: None,
+            nutrient_plausibility: NutrientPlausibilityOptions::default(),
+            cache_control: CacheControlOptions::default(),
+            request_signing: None,
+            tls: None,
+            admin_allowlist: None,
+            auth: None,
+            metrics: false,
+            swagger_ui: None,
+            deprecations: Vec::new(),
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_max_body_bytes() {
+        // the default body limit applies without or with small image limits
+        let unlimited = ImageQuotaOptions {
+            max_image_bytes: None,
+            ..Default::default()
+        };
+        assert_eq!(unlimited.max_body_bytes(), 2 * 1024 * 1024);
+        let small = ImageQuotaOptions {
+            max_image_bytes: Some(1024),
+            ..Default::default()
+        };
+        assert_eq!(small.max_body_bytes(), 2 * 1024 * 1024);
+
+        // the preview and the full image of the default limit fit base64 encoded
+        let default = ImageQuotaOptions::default();
+        assert!(default.max_body_bytes() as u64 >= 2 * 10 * 1024 * 1024 * 4 / 3);
+
+        // the limit of all images of a product takes precedence
+        let per_product = ImageQuotaOptions {
+            max_product_bytes: Some(3 * 1024 * 1024),
+            ..Default::default()
+        };
+        assert_eq!(per_product.max_body_bytes(), 5 * 1024 * 1024);
+    }
+}
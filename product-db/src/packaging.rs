@@ -0,0 +1,134 @@
+use std::fmt::Display;
+
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "service")]
+use crate::{Error, Result};
+
+/// The main material of the packaging of a product.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+#[cfg_attr(
+    feature = "service",
+    derive(sqlx::Type),
+    sqlx(type_name = "PackagingMaterial", rename_all = "lowercase")
+)]
+#[serde(rename_all = "lowercase")]
+pub enum PackagingMaterial {
+    Plastic,
+    Glass,
+    Paper,
+    Metal,
+    /// Multiple materials that cannot be separated, e.g. beverage cartons.
+    Composite,
+    Other,
+}
+
+impl Display for PackagingMaterial {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PackagingMaterial::Plastic => write!(f, "plastic"),
+            PackagingMaterial::Glass => write!(f, "glass"),
+            PackagingMaterial::Paper => write!(f, "paper"),
+            PackagingMaterial::Metal => write!(f, "metal"),
+            PackagingMaterial::Composite => write!(f, "composite"),
+            PackagingMaterial::Other => write!(f, "other"),
+        }
+    }
+}
+
+/// The packaging of a product, e.g. a six-pack of 330ml glass bottles with deposit.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct Packaging {
+    /// The net weight or volume of the whole pack in grams or ml depending on the quantity
+    /// type of the product.
+    #[serde(default)]
+    pub net_quantity: Option<f32>,
+
+    /// The number of units in the pack, e.g. 6 for a six-pack.
+    #[serde(default)]
+    pub units: Option<i32>,
+
+    /// The main material of the packaging.
+    #[serde(default)]
+    pub material: Option<PackagingMaterial>,
+
+    /// True if there is a deposit on the packaging, e.g. for returnable bottles.
+    #[serde(default)]
+    pub deposit: bool,
+}
+
+/// Checks that the net quantity of the packaging is positive and that it has at least one unit.
+///
+/// # Arguments
+/// - `packaging` - The packaging to check.
+#[cfg(feature = "service")]
+pub fn check_packaging(packaging: &Packaging) -> Result<()> {
+    if let Some(net_quantity) = packaging.net_quantity {
+        if !net_quantity.is_finite() || net_quantity <= 0.0 {
+            return Err(Error::InvalidPackaging(format!(
+                "{} is not a valid net quantity in g or ml",
+                net_quantity
+            )));
+        }
+    }
+
+    if let Some(units) = packaging.units {
+        if units < 1 {
+            return Err(Error::InvalidPackaging(format!(
+                "{} units, the pack must contain at least one unit",
+                units
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(all(test, feature = "service"))]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_check_packaging() {
+        let packaging = Packaging {
+            net_quantity: Some(1980.0),
+            units: Some(6),
+            material: Some(PackagingMaterial::Glass),
+            deposit: true,
+        };
+        assert!(check_packaging(&packaging).is_ok());
+
+        let unknown = Packaging {
+            net_quantity: None,
+            units: None,
+            material: None,
+            deposit: false,
+        };
+        assert!(check_packaging(&unknown).is_ok());
+
+        let invalid = [
+            Packaging {
+                net_quantity: Some(0.0),
+                ..packaging.clone()
+            },
+            Packaging {
+                net_quantity: Some(f32::NAN),
+                ..packaging.clone()
+            },
+            Packaging {
+                units: Some(0),
+                ..packaging.clone()
+            },
+        ];
+        for packaging in invalid {
+            assert!(matches!(
+                check_packaging(&packaging),
+                Err(Error::InvalidPackaging(_))
+            ));
+        }
+    }
+}
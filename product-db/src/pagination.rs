@@ -0,0 +1,346 @@
+use axum::{
+    http::{header, HeaderName, HeaderValue, Uri},
+    response::{IntoResponseParts, ResponseParts},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{Error, ProductDescription, ProductID, Result, Sorting, SortingField};
+
+/// The header with the total number of results of a query.
+pub const TOTAL_COUNT_HEADER: &str = "x-total-count";
+
+/// The offset and limit of a query given as URL query parameters, e.g. as part of the links
+/// for the next and previous page. The parameters override the offset and limit of the query
+/// in the request body.
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+pub struct PageParams {
+    /// The offset of the query results.
+    #[serde(default)]
+    pub offset: Option<i32>,
+
+    /// The limit of the query results.
+    #[serde(default)]
+    pub limit: Option<i32>,
+}
+
+impl PageParams {
+    /// Overrides the given offset and limit of a query with the defined parameters.
+    ///
+    /// # Arguments
+    /// - `offset` - The offset of the query.
+    /// - `limit` - The limit of the query.
+    pub fn apply(&self, offset: &mut i32, limit: &mut i32) {
+        if let Some(value) = self.offset {
+            *offset = value;
+        }
+        if let Some(value) = self.limit {
+            *limit = value;
+        }
+    }
+}
+
+/// The RFC 8288 `Link` header with the next and previous page of a query and the
+/// `X-Total-Count` header, if the total number of results is known.
+#[derive(Debug, Clone, Default)]
+pub struct PageLinks {
+    link: Option<HeaderValue>,
+    total_count: Option<u64>,
+}
+
+impl PageLinks {
+    /// Computes the links for the page of a query. The next page exists if the total number of
+    /// results exceeds the page or, if the total is unknown, if the page is full.
+    ///
+    /// # Arguments
+    /// - `uri` - The original uri of the request.
+    /// - `offset` - The offset of the query.
+    /// - `limit` - The limit of the query.
+    /// - `returned` - The number of results on the page.
+    /// - `total_count` - The total number of results of the query, if known.
+    pub fn new(
+        uri: &Uri,
+        offset: i32,
+        limit: i32,
+        returned: usize,
+        total_count: Option<u64>,
+    ) -> Self {
+        let offset = offset.max(0) as u64;
+        let limit = limit.max(0) as u64;
+
+        let has_next = limit > 0
+            && match total_count {
+                Some(total_count) => offset + limit < total_count,
+                None => returned as u64 >= limit,
+            };
+        let has_prev = offset > 0 && limit > 0;
+
+        let mut links = Vec::new();
+        if has_next {
+            links.push(Self::link(uri, offset + limit, limit, "next"));
+        }
+        if has_prev {
+            links.push(Self::link(uri, offset.saturating_sub(limit), limit, "prev"));
+        }
+
+        Self {
+            link: HeaderValue::try_from(links.join(", "))
+                .ok()
+                .filter(|_| !links.is_empty()),
+            total_count,
+        }
+    }
+
+    /// Returns the link to the page with the given offset and limit, keeping all other query
+    /// parameters of the uri.
+    ///
+    /// # Arguments
+    /// - `uri` - The original uri of the request.
+    /// - `offset` - The offset of the page.
+    /// - `limit` - The limit of the page.
+    /// - `rel` - The relation of the page to the current page.
+    fn link(uri: &Uri, offset: u64, limit: u64, rel: &str) -> String {
+        let mut params: Vec<String> = uri
+            .query()
+            .unwrap_or_default()
+            .split('&')
+            .filter(|param| {
+                let name = param.split('=').next().unwrap_or_default();
+                !param.is_empty() && name != "offset" && name != "limit"
+            })
+            .map(str::to_string)
+            .collect();
+        params.push(format!("offset={}", offset));
+        params.push(format!("limit={}", limit));
+
+        format!("<{}?{}>; rel=\"{}\"", uri.path(), params.join("&"), rel)
+    }
+}
+
+impl IntoResponseParts for PageLinks {
+    type Error = std::convert::Infallible;
+
+    fn into_response_parts(
+        self,
+        mut res: ResponseParts,
+    ) -> std::result::Result<ResponseParts, Self::Error> {
+        if let Some(link) = self.link {
+            res.headers_mut().insert(header::LINK, link);
+        }
+        if let Some(total_count) = self.total_count {
+            res.headers_mut().insert(
+                HeaderName::from_static(TOTAL_COUNT_HEADER),
+                HeaderValue::from(total_count),
+            );
+        }
+
+        Ok(res)
+    }
+}
+
+/// The sorted value of the last product of a page, e.g. its name.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum CursorValue {
+    Integer(i32),
+    Number(f32),
+    Text(String),
+}
+
+/// The position of the last product of a page in the order of a sorted product query, i.e.,
+/// its sorted value and the product id and market breaking the ties. The next page consists
+/// of the products after the position, which the database finds by the index instead of
+/// skipping all previous results like an offset.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProductCursor {
+    /// The sorting of the query the cursor has been created for.
+    pub sorting: Sorting,
+    /// The sorted value of the last product, `None` if it is undefined, e.g. without NOVA
+    /// group.
+    pub value: Option<CursorValue>,
+    /// The id of the last product.
+    pub product_id: ProductID,
+    /// The market of the last product.
+    pub market: Option<String>,
+}
+
+impl ProductCursor {
+    /// Creates the cursor after the given product, i.e., the last product of a page.
+    ///
+    /// # Arguments
+    /// - `sorting` - The sorting of the query.
+    /// - `product` - The last product of the page.
+    /// - `score` - The similarity of the product to the search string, if sorted by it.
+    pub fn after(sorting: Sorting, product: &ProductDescription, score: Option<f32>) -> Self {
+        let info = &product.info;
+        let value = match sorting.field {
+            SortingField::Name => Some(CursorValue::Text(info.name.clone())),
+            SortingField::ProductID => Some(CursorValue::Text(info.id.clone())),
+            SortingField::NovaGroup => info.nova_group.map(CursorValue::Integer),
+            SortingField::Similarity => score.map(CursorValue::Number),
+            SortingField::ReportedDate => None,
+        };
+
+        Self {
+            sorting,
+            value,
+            product_id: info.id.clone(),
+            market: info.market.clone(),
+        }
+    }
+
+    /// Encodes the cursor as opaque string for the responses.
+    pub fn encode(&self) -> String {
+        hex::encode(serde_json::to_vec(self).unwrap_or_default())
+    }
+
+    /// Decodes the cursor of a query, which must have the same sorting as the query the cursor
+    /// has been created for.
+    ///
+    /// # Arguments
+    /// - `cursor` - The encoded cursor.
+    /// - `sorting` - The sorting of the query.
+    pub fn decode(cursor: &str, sorting: Option<&Sorting>) -> Result<Self> {
+        let cursor: Self = hex::decode(cursor)
+            .ok()
+            .and_then(|data| serde_json::from_slice(&data).ok())
+            .ok_or_else(|| Error::InvalidCursor("malformed cursor".to_string()))?;
+
+        match sorting {
+            Some(sorting) if *sorting == cursor.sorting => Ok(cursor),
+            Some(_) => Err(Error::InvalidCursor(
+                "the sorting differs from the one of the cursor".to_string(),
+            )),
+            None => Err(Error::InvalidCursor(
+                "the cursor requires a sorting".to_string(),
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::SortingOrder;
+
+    #[test]
+    fn test_page_links() {
+        let uri: Uri = "/v1/user/product/query?offset=5&format=json"
+            .parse()
+            .unwrap();
+
+        // the first page with more results
+        let links = PageLinks::new(&uri, 0, 10, 10, Some(25));
+        assert_eq!(
+            links.link.unwrap(),
+            "</v1/user/product/query?format=json&offset=10&limit=10>; rel=\"next\""
+        );
+        assert_eq!(links.total_count, Some(25));
+
+        // a page in the middle without total count
+        let links = PageLinks::new(&uri, 15, 10, 10, None);
+        assert_eq!(
+            links.link.unwrap(),
+            "</v1/user/product/query?format=json&offset=25&limit=10>; rel=\"next\", \
+             </v1/user/product/query?format=json&offset=5&limit=10>; rel=\"prev\""
+        );
+
+        // the last page
+        let links = PageLinks::new(&uri, 20, 10, 5, Some(25));
+        assert_eq!(
+            links.link.unwrap(),
+            "</v1/user/product/query?format=json&offset=10&limit=10>; rel=\"prev\""
+        );
+        let links = PageLinks::new(&uri, 20, 10, 5, None);
+        assert!(links
+            .link
+            .unwrap()
+            .to_str()
+            .unwrap()
+            .ends_with("rel=\"prev\""));
+
+        // a single page
+        let links = PageLinks::new(&uri, 0, 10, 3, None);
+        assert!(links.link.is_none());
+
+        // the url query parameters override the body
+        let mut offset = 0;
+        let mut limit = 10;
+        PageParams {
+            offset: Some(20),
+            limit: None,
+        }
+        .apply(&mut offset, &mut limit);
+        assert_eq!((offset, limit), (20, 10));
+    }
+
+    #[test]
+    fn test_product_cursor() {
+        let product_data = include_str!("../../test_data/products.json");
+        let products: Vec<ProductDescription> = serde_json::from_str(product_data).unwrap();
+        let product = &products[0];
+
+        let by_name = Sorting {
+            order: SortingOrder::Ascending,
+            field: SortingField::Name,
+        };
+        let cursor = ProductCursor::after(by_name, product, None);
+        assert_eq!(
+            cursor.value,
+            Some(CursorValue::Text(product.info.name.clone()))
+        );
+        assert_eq!(cursor.product_id, product.info.id);
+
+        // the cursor is opaque and decodes to itself
+        let encoded = cursor.encode();
+        assert!(!encoded.contains(&product.info.id));
+        assert_eq!(
+            ProductCursor::decode(&encoded, Some(&by_name)).unwrap(),
+            cursor
+        );
+
+        // the cursor only applies to queries with the same sorting
+        let descending = Sorting {
+            order: SortingOrder::Descending,
+            ..by_name
+        };
+        assert!(matches!(
+            ProductCursor::decode(&encoded, Some(&descending)),
+            Err(Error::InvalidCursor(_))
+        ));
+        assert!(matches!(
+            ProductCursor::decode(&encoded, None),
+            Err(Error::InvalidCursor(_))
+        ));
+        assert!(matches!(
+            ProductCursor::decode("not a cursor", Some(&by_name)),
+            Err(Error::InvalidCursor(_))
+        ));
+
+        // the scores and nova groups keep their types
+        let by_similarity = Sorting {
+            order: SortingOrder::Descending,
+            field: SortingField::Similarity,
+        };
+        let cursor = ProductCursor::after(by_similarity, product, Some(0.5));
+        assert_eq!(
+            ProductCursor::decode(&cursor.encode(), Some(&by_similarity))
+                .unwrap()
+                .value,
+            Some(CursorValue::Number(0.5))
+        );
+        let by_nova_group = Sorting {
+            order: SortingOrder::Ascending,
+            field: SortingField::NovaGroup,
+        };
+        let cursor = ProductCursor {
+            value: Some(CursorValue::Integer(3)),
+            ..ProductCursor::after(by_nova_group, product, None)
+        };
+        assert_eq!(
+            ProductCursor::decode(&cursor.encode(), Some(&by_nova_group))
+                .unwrap()
+                .value,
+            Some(CursorValue::Integer(3))
+        );
+    }
+}
@@ -0,0 +1,156 @@
+use chrono::{DateTime, Datelike, TimeZone, Utc};
+
+/// The name of the partitioned table.
+const PARENT_TABLE: &str = "reported_missing_products";
+
+/// The migration that converts the reported_missing_products table into a partitioned table.
+pub const PARTITION_MIGRATION: &str =
+    include_str!("../../docker/db/migrations/partition_reported_missing_products.sql");
+
+/// A monthly partition of the reported_missing_products table. The months are in UTC.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct MonthlyPartition {
+    year: i32,
+    month: u32,
+}
+
+impl MonthlyPartition {
+    /// Returns the partition that contains the given date.
+    ///
+    /// # Arguments
+    /// - `date` - The date to find the partition for.
+    pub fn containing(date: DateTime<Utc>) -> Self {
+        Self {
+            year: date.year(),
+            month: date.month(),
+        }
+    }
+
+    /// Parses the partition from its table name or returns None for other table names.
+    ///
+    /// # Arguments
+    /// - `name` - The name of the partition table, e.g. "reported_missing_products_y2025m03".
+    pub fn from_name(name: &str) -> Option<Self> {
+        let suffix = name.strip_prefix(PARENT_TABLE)?.strip_prefix("_y")?;
+        let (year, month) = suffix.split_once('m')?;
+        let year: i32 = year.parse().ok()?;
+        let month: u32 = month.parse().ok()?;
+
+        if (1..=12).contains(&month) {
+            Some(Self { year, month })
+        } else {
+            None
+        }
+    }
+
+    /// Returns the table name of the partition.
+    pub fn name(&self) -> String {
+        format!("{}_y{:04}m{:02}", PARENT_TABLE, self.year, self.month)
+    }
+
+    /// Returns the first point in time that belongs to the partition.
+    pub fn start(&self) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(self.year, self.month, 1, 0, 0, 0)
+            .single()
+            .expect("The first of a month is a valid date")
+    }
+
+    /// Returns the first point in time that belongs to the next partition.
+    pub fn end(&self) -> DateTime<Utc> {
+        self.next().start()
+    }
+
+    /// Returns the partition of the following month.
+    pub fn next(&self) -> Self {
+        if self.month == 12 {
+            Self {
+                year: self.year + 1,
+                month: 1,
+            }
+        } else {
+            Self {
+                year: self.year,
+                month: self.month + 1,
+            }
+        }
+    }
+
+    /// Returns the partition the given number of months before this one.
+    ///
+    /// # Arguments
+    /// - `months` - The number of months to go back.
+    pub fn months_before(&self, months: u32) -> Self {
+        let index = self.year * 12 + self.month as i32 - 1 - months as i32;
+        Self {
+            year: index.div_euclid(12),
+            month: index.rem_euclid(12) as u32 + 1,
+        }
+    }
+
+    /// Returns the statement that creates the partition if it does not exist yet.
+    pub fn create_statement(&self) -> String {
+        format!(
+            "create table if not exists {} partition of {} for values from ('{}') to ('{}');",
+            self.name(),
+            PARENT_TABLE,
+            self.start().to_rfc3339(),
+            self.end().to_rfc3339()
+        )
+    }
+
+    /// Returns the statement that drops the partition including its reports.
+    pub fn drop_statement(&self) -> String {
+        format!("drop table if exists {};", self.name())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_monthly_partition() {
+        let date = Utc.with_ymd_and_hms(2025, 12, 31, 23, 59, 59).unwrap();
+        let partition = MonthlyPartition::containing(date);
+
+        assert_eq!(partition.name(), "reported_missing_products_y2025m12");
+        assert_eq!(
+            partition.start(),
+            Utc.with_ymd_and_hms(2025, 12, 1, 0, 0, 0).unwrap()
+        );
+        assert_eq!(
+            partition.end(),
+            Utc.with_ymd_and_hms(2026, 1, 1, 0, 0, 0).unwrap()
+        );
+        assert_eq!(
+            partition.next().name(),
+            "reported_missing_products_y2026m01"
+        );
+        assert_eq!(
+            partition.months_before(12).name(),
+            "reported_missing_products_y2024m12"
+        );
+        assert_eq!(
+            partition.months_before(11).name(),
+            "reported_missing_products_y2025m01"
+        );
+        assert_eq!(
+            partition.months_before(14).name(),
+            "reported_missing_products_y2024m10"
+        );
+
+        assert_eq!(
+            MonthlyPartition::from_name(&partition.name()),
+            Some(partition)
+        );
+        assert_eq!(
+            MonthlyPartition::from_name("reported_missing_products"),
+            None
+        );
+        assert_eq!(
+            MonthlyPartition::from_name("reported_missing_products_y2025m13"),
+            None
+        );
+        assert_eq!(MonthlyPartition::from_name("products_y2025m01"), None);
+    }
+}
@@ -0,0 +1,214 @@
+use std::fmt::Display;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Nutrients, QuantityType, Weight};
+
+/// The deviation in gram that is tolerated due to the rounding of the stated nutrients.
+const ROUNDING_TOLERANCE: f32 = 0.5;
+
+/// The maximal density in g/ml of a product whose nutrients are stated per 100ml, e.g. honey
+/// or syrup, i.e., the macronutrients of 100ml may weigh more than 100g.
+const MAX_DENSITY: f32 = 1.5;
+
+/// The deviation in kcal of the stated energy from the energy computed from the
+/// macronutrients that is always tolerated.
+const ENERGY_TOLERANCE_KCAL: f32 = 20.0;
+
+/// The relative deviation of the stated energy from the energy computed from the
+/// macronutrients that is tolerated, e.g. due to fiber or alcohol.
+const ENERGY_TOLERANCE: f32 = 0.2;
+
+/// The amount of salt in gram that corresponds to 1g of sodium.
+const SALT_PER_SODIUM: f32 = 2.5;
+
+/// The deviation in gram of the salt from the salt computed from the sodium that is always
+/// tolerated.
+const SALT_TOLERANCE_GRAM: f32 = 0.1;
+
+/// The relative deviation of the salt from the salt computed from the sodium that is
+/// tolerated.
+const SALT_TOLERANCE: f32 = 0.2;
+
+/// A violated plausibility rule of the nutrients of a product, e.g. due to a typo or a wrong
+/// unit when the nutrients have been entered.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum NutrientIssue {
+    /// The protein, fat, carbohydrates and salt weigh more than 100g per 100g.
+    MacronutrientsExceedAmount,
+    /// The energy does not match the energy computed from the macronutrients by the Atwater
+    /// factors, i.e., 4 kcal/g for protein and carbohydrates and 9 kcal/g for fat.
+    EnergyMismatch,
+    /// The sugar exceeds the carbohydrates.
+    SugarExceedsCarbohydrates,
+    /// The sodium does not match the salt, which consists of 40% sodium.
+    SodiumMismatch,
+}
+
+impl Display for NutrientIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NutrientIssue::MacronutrientsExceedAmount => {
+                write!(f, "the macronutrients exceed the reference amount")
+            }
+            NutrientIssue::EnergyMismatch => {
+                write!(f, "the energy does not match the macronutrients")
+            }
+            NutrientIssue::SugarExceedsCarbohydrates => {
+                write!(f, "the sugar exceeds the carbohydrates")
+            }
+            NutrientIssue::SodiumMismatch => write!(f, "the sodium does not match the salt"),
+        }
+    }
+}
+
+/// Returns the violated plausibility rules of the given nutrients per 100g or 100ml. Rules
+/// whose nutrients are unknown are skipped.
+///
+/// # Arguments
+/// - `nutrients` - The nutrients per 100g or 100ml to check.
+/// - `quantity_type` - Whether the nutrients are stated per 100g or per 100ml.
+pub fn nutrient_issues(nutrients: &Nutrients, quantity_type: QuantityType) -> Vec<NutrientIssue> {
+    let gram = |weight: Option<Weight>| weight.map(|w| w.value);
+    let mut issues = Vec::new();
+
+    let max_amount = match quantity_type {
+        QuantityType::Weight => 100.0,
+        QuantityType::Volume => 100.0 * MAX_DENSITY,
+    };
+    let macronutrients: f32 = [
+        nutrients.protein,
+        nutrients.fat,
+        nutrients.carbohydrates,
+        nutrients.salt,
+    ]
+    .into_iter()
+    .filter_map(gram)
+    .sum();
+    if macronutrients > max_amount + ROUNDING_TOLERANCE {
+        issues.push(NutrientIssue::MacronutrientsExceedAmount);
+    }
+
+    if let (Some(protein), Some(fat), Some(carbohydrates)) = (
+        gram(nutrients.protein),
+        gram(nutrients.fat),
+        gram(nutrients.carbohydrates),
+    ) {
+        let kcal = 4.0 * protein + 9.0 * fat + 4.0 * carbohydrates;
+        let tolerance = ENERGY_TOLERANCE_KCAL.max(ENERGY_TOLERANCE * kcal);
+        if (nutrients.kcal - kcal).abs() > tolerance {
+            issues.push(NutrientIssue::EnergyMismatch);
+        }
+    }
+
+    if let (Some(sugar), Some(carbohydrates)) =
+        (gram(nutrients.sugar), gram(nutrients.carbohydrates))
+    {
+        if sugar > carbohydrates + ROUNDING_TOLERANCE {
+            issues.push(NutrientIssue::SugarExceedsCarbohydrates);
+        }
+    }
+
+    if let (Some(salt), Some(sodium)) = (gram(nutrients.salt), gram(nutrients.sodium)) {
+        let expected = sodium * SALT_PER_SODIUM;
+        let tolerance = SALT_TOLERANCE_GRAM.max(SALT_TOLERANCE * expected);
+        if (salt - expected).abs() > tolerance {
+            issues.push(NutrientIssue::SodiumMismatch);
+        }
+    }
+
+    issues
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn nutrients() -> Nutrients {
+        Nutrients {
+            kcal: 372.0,
+            protein: Some(Weight::new_from_gram(13.0)),
+            fat: Some(Weight::new_from_gram(7.0)),
+            carbohydrates: Some(Weight::new_from_gram(59.0)),
+            sugar: Some(Weight::new_from_gram(2.5)),
+            salt: Some(Weight::new_from_gram(1.2)),
+            vitamin_a: None,
+            vitamin_c: None,
+            vitamin_d: None,
+            iron: None,
+            calcium: None,
+            magnesium: None,
+            sodium: Some(Weight::new_from_milligram(480.0)),
+            zinc: None,
+        }
+    }
+
+    #[test]
+    fn test_nutrient_issues() {
+        assert!(nutrient_issues(&nutrients(), QuantityType::Weight).is_empty());
+
+        // unknown nutrients are not checked
+        let unknown = Nutrients {
+            kcal: 1000.0,
+            protein: None,
+            sodium: None,
+            ..nutrients()
+        };
+        assert!(nutrient_issues(&unknown, QuantityType::Weight).is_empty());
+
+        // the carbohydrates entered in mg instead of g
+        let carbohydrates = Nutrients {
+            carbohydrates: Some(Weight::new_from_gram(590.0)),
+            ..nutrients()
+        };
+        assert_eq!(
+            nutrient_issues(&carbohydrates, QuantityType::Weight),
+            vec![
+                NutrientIssue::MacronutrientsExceedAmount,
+                NutrientIssue::EnergyMismatch,
+            ]
+        );
+
+        let sugar = Nutrients {
+            sugar: Some(Weight::new_from_gram(62.0)),
+            ..nutrients()
+        };
+        assert_eq!(
+            nutrient_issues(&sugar, QuantityType::Weight),
+            vec![NutrientIssue::SugarExceedsCarbohydrates]
+        );
+
+        // the sodium stated as salt
+        let sodium = Nutrients {
+            sodium: Some(Weight::new_from_gram(1.2)),
+            ..nutrients()
+        };
+        assert_eq!(
+            nutrient_issues(&sodium, QuantityType::Weight),
+            vec![NutrientIssue::SodiumMismatch]
+        );
+    }
+
+    #[test]
+    fn test_nutrient_issues_volume() {
+        // 100ml of syrup weigh more than 100g
+        let syrup = Nutrients {
+            kcal: 420.0,
+            protein: Some(Weight::new_from_gram(0.0)),
+            fat: Some(Weight::new_from_gram(0.0)),
+            carbohydrates: Some(Weight::new_from_gram(105.0)),
+            sugar: Some(Weight::new_from_gram(105.0)),
+            salt: None,
+            sodium: None,
+            ..nutrients()
+        };
+        assert!(nutrient_issues(&syrup, QuantityType::Volume).is_empty());
+        assert_eq!(
+            nutrient_issues(&syrup, QuantityType::Weight),
+            vec![NutrientIssue::MacronutrientsExceedAmount]
+        );
+    }
+}
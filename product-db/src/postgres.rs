@@ -1,18 +1,46 @@
+use std::{
+    collections::{HashMap, HashSet},
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex, RwLock,
+    },
+    time::{Duration, Instant},
+};
+
+use chrono::{DateTime, Utc};
+
 use futures::TryStreamExt;
-use log::{debug, error, info, trace, LevelFilter};
+use log::LevelFilter;
+use metrics::{gauge, histogram};
 use serde::Deserialize;
 use sqlx::{
-    postgres::{PgConnectOptions, PgPoolOptions},
-    ConnectOptions, Database, Executor, QueryBuilder, Row,
+    pool::PoolConnection,
+    postgres::{PgArguments, PgConnectOptions, PgConnection, PgPoolOptions},
+    query::Query,
+    ConnectOptions, Connection, Database, Executor, Postgres, QueryBuilder,
 };
+use tracing::{debug, error, field, info, instrument, trace, warn, Span};
 
 use crate::{
+    metrics::{
+        POOL_ACQUIRE_DURATION, POOL_CONNECTIONS, POOL_IDLE_CONNECTIONS, POOL_MAX_CONNECTIONS,
+        POOL_WAITING_ACQUIRES,
+    },
+    new_storage_key, normalize_gtin,
+    partitioning::{MonthlyPartition, PARTITION_MIGRATION},
     sql_types::{
-        SQLMissingProduct, SQLProductDescription, SQLRequestedProduct, SQLRequestedProductWithId,
+        SQLMissingProduct, SQLMissingProductSummary, SQLPriceObservation, SQLProductDescription,
+        SQLRecipe, SQLRequestedProduct, SQLRequestedProductWithId, SQLStore,
     },
-    DBId, DataBackend, Error, MissingProduct, MissingProductQuery, Nutrients, Options,
-    ProductDescription, ProductID, ProductImage, ProductQuery, ProductRequest,
-    Result as ProductDBResult, SearchFilter, Secret, SortingField,
+    AdditionalImage, Allergen, ArchiveQuery, ArchivedProductRequest, CountedProductRequests,
+    CountedProducts, CursorValue, DBId, DataBackend, DietaryLabels, EcoScoreGrade, Error,
+    HealthReport, HealthStatus, ImageRole, ImageStorageUsage, ImageStore, ImageStoreConfig,
+    MissingProduct, MissingProductQuery, MissingProductSummary, Nutrients, Options,
+    PackagingMaterial, PriceObservation, PriceQuery, ProductAvailability, ProductCursor,
+    ProductDataIssue, ProductDescription, ProductID, ProductImage, ProductQuery, ProductRequest,
+    QuantityType, ReadBackend, Recipe, RequestOutcome, RequestStatus, Result as ProductDBResult,
+    SearchFilter, Secret, SortingField, SortingOrder, Store, StoreQuery, WriteBackend,
 };
 
 type Pool = sqlx::PgPool;
@@ -20,436 +48,4372 @@ type Pool = sqlx::PgPool;
 /// The maximum limit for the query results.
 const LIMIT_MAX: i32 = 200;
 
+/// The key of the advisory lock that serializes the migrations of multiple instances.
+pub const MIGRATION_LOCK_KEY: i64 = 0x7072_6f64_7563_7464;
+
+/// The maximal duration of a health check of the database.
+const HEALTH_CHECK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The default application name of the connections.
+const DEFAULT_APPLICATION_NAME: &str = "product-db";
+
+/// The version of the database schema the code requires.
+pub const SCHEMA_VERSION: i32 = 18;
+
+/// The maximal number of deleted images whose objects are deleted from the image store at once.
+const PURGE_BATCH_SIZE: i64 = 100;
+
+/// The migration that creates the optional table for the observed prices.
+const PRICE_TRACKING_MIGRATION: &str =
+    include_str!("../../docker/db/migrations/price_tracking.sql");
+
+/// The migration that makes the pair of product id and market the uniqueness key of products.
+const MARKET_SPECIFIC_PRODUCTS_MIGRATION: &str =
+    include_str!("../../docker/db/migrations/market_specific_products.sql");
+
+/// The migration that enables the accent- and case-insensitive search.
+const UNACCENT_SEARCH_MIGRATION: &str =
+    include_str!("../../docker/db/migrations/unaccent_search.sql");
+
+/// An optional part of the database schema, which is applied at startup if enabled in the
+/// config. The applied features are recorded in the `schema_features` table together with the
+/// schema version, i.e., a feature applied to an older schema version is applied again after
+/// migrating the schema.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SchemaFeature {
+    PartitionedMissingProducts,
+    PriceTracking,
+    MarketSpecificProducts,
+    UnaccentSearch,
+}
+
+impl SchemaFeature {
+    /// Returns the name of the feature in the `schema_features` table.
+    fn name(self) -> &'static str {
+        match self {
+            Self::PartitionedMissingProducts => "partitioned_missing_products",
+            Self::PriceTracking => "price_tracking",
+            Self::MarketSpecificProducts => "market_specific_products",
+            Self::UnaccentSearch => "unaccent_search",
+        }
+    }
+
+    /// Returns the migration that applies the feature.
+    fn migration(self) -> &'static str {
+        match self {
+            Self::PartitionedMissingProducts => PARTITION_MIGRATION,
+            Self::PriceTracking => PRICE_TRACKING_MIGRATION,
+            Self::MarketSpecificProducts => MARKET_SPECIFIC_PRODUCTS_MIGRATION,
+            Self::UnaccentSearch => UNACCENT_SEARCH_MIGRATION,
+        }
+    }
+}
+
 /// Postgres based implementation of the state backend.
 pub struct PostgresBackend {
-    /// The sql connection pool.
-    pool: Pool,
+    /// The configuration for the postgres connection.
+    config: PostgresConfig,
+
+    /// The sql connection pool, which is replaced when the credentials are reloaded.
+    pool: RwLock<Pool>,
+
+    /// True if the reported missing products are partitioned by month.
+    partitioned: bool,
+
+    /// The monthly partitions of the reported missing products that are known to exist.
+    partitions: Mutex<HashSet<MonthlyPartition>>,
+
+    /// The number of operations waiting for a connection of the pool.
+    waiting_acquires: AtomicUsize,
+
+    /// The store for the images except for the previews, if they are not stored in the bytea
+    /// column of the images.
+    image_store: Option<Arc<dyn ImageStore>>,
 }
 
-/// The configuration for connecting to the postgres database.
-#[derive(Clone, Debug, Deserialize)]
-pub struct PostgresConfig {
-    pub host: String,
-    pub port: u16,
-    pub user: String,
-    pub password: Secret,
-    pub dbname: String,
-    pub max_connections: u32,
+/// Counts an operation waiting for a connection of the pool until it is dropped, i.e., also
+/// if the operation is cancelled, e.g. by a timeout.
+struct WaitingAcquire<'a> {
+    waiting_acquires: &'a AtomicUsize,
 }
 
-impl PostgresBackend {
-    /// Create a new PostgresBackend instance.
+impl<'a> WaitingAcquire<'a> {
+    /// Starts counting the waiting operation.
     ///
     /// # Arguments
-    /// * `config` - The configuration for the postgres connection.
-    pub async fn new(config: PostgresConfig) -> ProductDBResult<Self> {
-        // create the connection pool
-        info!("Creating Postgres connection pool...");
+    /// * `waiting_acquires` - The number of waiting operations.
+    fn new(waiting_acquires: &'a AtomicUsize) -> Self {
+        let waiting = waiting_acquires.fetch_add(1, Ordering::Relaxed) + 1;
+        gauge!(POOL_WAITING_ACQUIRES).set(waiting as f64);
 
-        // get the current log level
-        let log_level = log::max_level();
+        Self { waiting_acquires }
+    }
+}
 
-        let options: PgConnectOptions = PgConnectOptions::new()
-            .host(&config.host)
-            .port(config.port)
-            .username(&config.user)
-            .password(config.password.secret())
-            .database(&config.dbname)
-            .log_statements(if log_level == log::Level::Trace {
-                LevelFilter::Trace
-            } else {
-                LevelFilter::Off
-            });
+impl Drop for WaitingAcquire<'_> {
+    fn drop(&mut self) {
+        let waiting = self.waiting_acquires.fetch_sub(1, Ordering::Relaxed) - 1;
+        gauge!(POOL_WAITING_ACQUIRES).set(waiting as f64);
+    }
+}
 
-        let pool = match PgPoolOptions::new()
-            .max_connections(config.max_connections)
-            .connect_with(options)
-            .await
-        {
-            Ok(pool) => pool,
-            Err(e) => {
-                error!("Failed to create Postgres connection pool: {}", e);
-                return Err(Error::DBError(Box::new(e)));
-            }
-        };
+/// An image as inserted into the `product_image` table, i.e., either with its data or, if the
+/// image has been uploaded to the image store, with its storage key and size.
+struct ImageRow<'a> {
+    data: &'a [u8],
+    content_type: &'a str,
+    storage_key: Option<String>,
+    storage_size: Option<i64>,
+}
 
-        info!("Creating Postgres connection pool...DONE");
+impl<'a> ImageRow<'a> {
+    /// Returns the row storing the data of the image in the bytea column.
+    ///
+    /// # Arguments
+    /// * `image` - The image to store.
+    fn inline(image: &'a ProductImage) -> Self {
+        Self {
+            data: &image.data,
+            content_type: &image.content_type,
+            storage_key: None,
+            storage_size: None,
+        }
+    }
+}
 
-        Ok(Self { pool })
+/// The keys of the images uploaded to the image store by a write operation. Unless committed,
+/// the images are deleted again in the background when dropped, i.e., if the statements
+/// referencing them failed or the operation has been cancelled.
+struct UploadedImages {
+    store: Option<Arc<dyn ImageStore>>,
+    keys: Vec<String>,
+}
+
+impl UploadedImages {
+    /// Moves the uploaded images of another operation into this one, e.g. of a single product
+    /// of a batch whose statement has succeeded.
+    ///
+    /// # Arguments
+    /// * `other` - The uploaded images to take over.
+    fn merge(&mut self, mut other: UploadedImages) {
+        self.keys.append(&mut other.keys);
+    }
+
+    /// Keeps the uploaded images as the statements referencing them have been committed.
+    fn commit(mut self) {
+        self.keys.clear();
     }
 }
 
-impl DataBackend for PostgresBackend {
-    async fn new(options: &Options) -> ProductDBResult<Self> {
-        let pg_config = options.postgres.clone();
-        Self::new(pg_config).await
+impl Drop for UploadedImages {
+    fn drop(&mut self) {
+        let Some(store) = self.store.clone() else {
+            return;
+        };
+        if self.keys.is_empty() {
+            return;
+        }
+
+        let keys = std::mem::take(&mut self.keys);
+        debug!("Discard {} uploaded images", keys.len());
+        match tokio::runtime::Handle::try_current() {
+            Ok(runtime) => {
+                runtime.spawn(async move {
+                    for key in keys {
+                        if let Err(e) = store.delete(&key).await {
+                            warn!("Failed to discard uploaded image {}: {}", key, e);
+                        }
+                    }
+                });
+            }
+            Err(_) => warn!("Failed to discard {} uploaded images", keys.len()),
+        }
     }
+}
 
-    async fn report_missing_product(
-        &self,
-        missing_product: MissingProduct,
-    ) -> ProductDBResult<DBId> {
-        info!(
-            "Report missing product with id: {} with timestamp {}",
-            missing_product.product_id, missing_product.date
-        );
+/// A key compared by the keyset pagination of the products, see
+/// [`PostgresBackend::push_cursor_filter`].
+enum CursorKey {
+    /// A column of the products, e.g. the name.
+    Column(String),
+    /// The similarity of the products to the given search string.
+    Similarity(String),
+}
 
-        let db_id: DBId = match sqlx::query_scalar("insert into reported_missing_products (product_id, date) values ($1, $2) returning id;")
-        .bind(&missing_product.product_id)
-        .bind(missing_product.date).fetch_one(&self.pool).await {
-                Ok(row) => row,
-                Err(e) => {
-                    error!("Failed to report missing product: {}", e);
-                    return Err(Error::DBError(Box::new(e)));
-                }
-            };
+/// Records the duration of a backend operation in the `duration_ms` field of its span when
+/// dropped, i.e., also if the operation fails or is cancelled.
+struct OperationTimer {
+    span: Span,
+    start: Instant,
+}
 
-        info!(
-            "Reported missing product with id: {} as {}",
-            missing_product.product_id, db_id
-        );
+impl OperationTimer {
+    /// Starts timing the operation of the current span.
+    fn start() -> Self {
+        Self {
+            span: Span::current(),
+            start: Instant::now(),
+        }
+    }
+}
 
-        Ok(db_id)
+impl Drop for OperationTimer {
+    fn drop(&mut self) {
+        self.span
+            .record("duration_ms", self.start.elapsed().as_millis() as u64);
     }
+}
 
-    async fn query_missing_products(
-        &self,
-        query: &MissingProductQuery,
-    ) -> ProductDBResult<Vec<(DBId, MissingProduct)>> {
-        let sorting_order = query.order.to_string();
+/// Records the number of rows returned or affected by a backend operation in its span.
+///
+/// # Arguments
+/// * `rows` - The number of rows.
+fn record_rows(rows: u64) {
+    Span::current().record("rows", rows);
+}
 
-        let mut query_builder =
-            QueryBuilder::new("select id, product_id, date from reported_missing_products ");
+/// Returns the variants of a search term for the accent-insensitive search, i.e., the term
+/// itself and, if it contains umlauts, the term with their German transcription, e.g. "müsli"
+/// and "muesli". The index only strips the accents, i.e., "Müsli" and "Muesli" are stored
+/// differently.
+///
+/// # Arguments
+/// * `term` - The search term.
+fn umlaut_variants(term: &str) -> Vec<String> {
+    let mut variants = vec![term.to_string()];
 
-        let mut _q: String = String::new();
-        if let Some(product_id) = query.product_id.as_ref() {
-            query_builder.push("where product_id = ");
-            query_builder.push_bind(product_id);
-        }
+    if term.contains(['ä', 'ö', 'ü', 'Ä', 'Ö', 'Ü']) {
+        let transcription = term
+            .chars()
+            .fold(String::with_capacity(term.len()), |mut s, c| {
+                match c {
+                    'ä' => s.push_str("ae"),
+                    'ö' => s.push_str("oe"),
+                    'ü' => s.push_str("ue"),
+                    'Ä' => s.push_str("Ae"),
+                    'Ö' => s.push_str("Oe"),
+                    'Ü' => s.push_str("Ue"),
+                    c => s.push(c),
+                }
+                s
+            });
+        variants.push(transcription);
+    }
 
-        query_builder.push(" order by date ");
-        query_builder.push(sorting_order.as_str());
-        Self::add_offset_and_limit(&mut query_builder, query.offset, query.limit);
+    variants
+}
 
-        let query = query_builder.build_query_as::<SQLMissingProduct>();
-        let mut rows = query.fetch(&self.pool);
-        let mut missing_products = Vec::new();
-        while let Some(row) = rows
-            .try_next()
-            .await
-            .map_err(|e| Error::DBError(Box::new(e)))?
-        {
-            missing_products.push((
-                row.id,
-                MissingProduct {
-                    product_id: row.product_id,
-                    date: row.date,
-                },
-            ));
+/// Returns the like pattern for the pattern of a producer filter, where a `*` matches any
+/// characters and the wildcards of like match literally.
+///
+/// # Arguments
+/// * `pattern` - The pattern of the producer filter.
+fn producer_like_pattern(pattern: &str) -> String {
+    let mut like = String::with_capacity(pattern.len());
+    for c in pattern.chars() {
+        match c {
+            '*' => like.push('%'),
+            '%' | '_' | '\\' => {
+                like.push('\\');
+                like.push(c);
+            }
+            _ => like.push(c),
         }
-
-        Ok(missing_products)
     }
 
-    async fn get_missing_product(&self, id: DBId) -> ProductDBResult<Option<MissingProduct>> {
-        debug!("Get missing product with id: {}", id);
+    like
+}
 
-        let query = sqlx::query_as::<_, MissingProduct>(
-            "select product_id, date from reported_missing_products where id = $1;",
-        )
-        .bind(id);
+/// How the terms of a search string are matched against the name and producer of the
+/// products and product requests.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SearchTermMode {
+    /// The search string is matched as a single literal substring, i.e., "alpro vanilla" does
+    /// not match "Vanilla Soy Drink" by "Alpro".
+    Phrase,
+    /// Every whitespace-separated term of the search string must be contained.
+    #[default]
+    All,
+    /// At least one whitespace-separated term of the search string must be contained.
+    Any,
+}
 
-        let row = match query.fetch_optional(&self.pool).await {
-            Ok(row) => row,
-            Err(e) => {
-                error!("Failed to get missing product: {}", e);
-                return Err(Error::DBError(Box::new(e)));
-            }
+impl SearchTermMode {
+    /// Returns the terms of the given search string that are matched.
+    ///
+    /// # Arguments
+    /// * `search_string` - The search string.
+    pub fn terms(self, search_string: &str) -> Vec<&str> {
+        let terms: Vec<&str> = match self {
+            SearchTermMode::Phrase => Vec::new(),
+            SearchTermMode::All | SearchTermMode::Any => search_string.split_whitespace().collect(),
         };
 
-        if let Some(row) = row {
-            debug!("Found missing product with id: {}", id);
-            trace!("Product: id={}, date={}", row.product_id, row.date);
-
-            Ok(Some(row))
+        // a search string without terms is matched literally
+        if terms.is_empty() {
+            vec![search_string]
         } else {
-            debug!("No missing product with id: {}", id);
-            Ok(None)
+            terms
         }
     }
 
-    async fn delete_reported_missing_product(&self, id: DBId) -> ProductDBResult<()> {
-        info!("Delete reported missing product with id: {}", id);
+    /// Returns the text search query for the given search string in the syntax of
+    /// `to_tsquery`, i.e., the terms as prefixes combined by the operator of the mode. The
+    /// words of a phrase must follow each other in the given order.
+    ///
+    /// # Arguments
+    /// * `search_string` - The search string.
+    pub fn text_search_query(self, search_string: &str) -> String {
+        let operator = match self {
+            SearchTermMode::Any => " | ",
+            SearchTermMode::Phrase | SearchTermMode::All => " & ",
+        };
 
-        let query = sqlx::query("delete from reported_missing_products where id = $1;").bind(id);
-        if let Err(e) = self.pool.execute(query).await {
-            error!("Failed to delete reported missing product: {}", e);
-            return Err(Error::DBError(Box::new(e)));
-        }
+        // the search strings without words yield an empty query, which matches nothing
+        self.terms(search_string)
+            .iter()
+            .map(|term| term.trim())
+            .filter(|term| !term.is_empty())
+            .map(|term| format!("'{}':*", term.replace('\\', "\\\\").replace('\'', "''")))
+            .collect::<Vec<_>>()
+            .join(operator)
+    }
+}
 
-        info!("Deleted reported missing product with id: {}", id);
+/// The configuration for connecting to the postgres database.
+#[derive(Clone, Debug, Deserialize)]
+pub struct PostgresConfig {
+    pub host: String,
+    pub port: u16,
+    pub user: String,
+    #[serde(default)]
+    pub password: Secret,
+    /// Optional path to a file containing the password, e.g. a mounted Docker secret.
+    #[serde(default)]
+    pub password_file: Option<PathBuf>,
+    pub dbname: String,
+    pub max_connections: u32,
+    /// If true, the backend is compatible with transaction-mode poolers like PgBouncer, i.e.,
+    /// prepared statements are not cached on the connections. The migration lock at startup is
+    /// transaction-level and thus needs no special handling.
+    #[serde(default)]
+    pub pgbouncer_mode: bool,
+    /// The schema that contains the tables of the product database. Defaults to the default
+    /// schema of the database, usually "public". The `search_path` is set on each new
+    /// connection, i.e., the schema cannot be combined with the PgBouncer mode, where
+    /// transactions switch between server connections. Set the `search_path` of the database
+    /// role instead in that case.
+    #[serde(default)]
+    pub schema: Option<String>,
+    /// The application name reported to Postgres, e.g. in `pg_stat_activity`.
+    /// Defaults to "product-db".
+    #[serde(default)]
+    pub application_name: Option<String>,
+    /// The id of the instance that is appended to the application name.
+    /// Defaults to the hostname or, if not available, the process id.
+    #[serde(default)]
+    pub instance_id: Option<String>,
+    /// If true, the reported missing products are partitioned by month. The existing table is
+    /// migrated at startup. The partitioning is not reverted when the option is disabled again.
+    #[serde(default)]
+    pub partition_missing_products: bool,
+    /// The number of months for which reported missing products are kept. Older partitions
+    /// are dropped. Requires the reported missing products to be partitioned.
+    #[serde(default)]
+    pub missing_products_retention_months: Option<u32>,
+    /// If true, the prices of the products observed in stores are tracked. The table for the
+    /// prices is created at startup and kept when the option is disabled again.
+    #[serde(default)]
+    pub price_tracking: bool,
+    /// If true, the pair of product id and market is the uniqueness key of the products, i.e.,
+    /// the same product id can be added once per market. Otherwise, the product id is unique.
+    /// The migration is not reverted when the option is disabled again.
+    #[serde(default)]
+    pub market_specific_products: bool,
+    /// If true, the search ignores accents, e.g. "cafe" matches "café", and umlauts in the
+    /// search string also match their German transcription, e.g. "Müsli" matches "Muesli".
+    /// Requires the `unaccent` extension, which is created at startup together with an index on
+    /// the unaccented names.
+    #[serde(default)]
+    pub unaccent_search: bool,
+    /// How the terms of the search string are matched. Defaults to all terms having to be
+    /// contained in the name or producer.
+    #[serde(default)]
+    pub search_terms: SearchTermMode,
+    /// Where the images except for the previews are stored. Defaults to the bytea column of the
+    /// images in Postgres. The previews are always stored in Postgres as they are loaded together
+    /// with the products.
+    #[serde(default)]
+    pub image_store: ImageStoreConfig,
+}
+
+impl PostgresConfig {
+    /// Loads the password from the configured password file, if any.
+    pub fn load_password_file(&mut self) -> ProductDBResult<()> {
+        if let Some(password_file) = &self.password_file {
+            let password = Secret::from_file(password_file)?;
+            if password.secret().is_none() {
+                return Err(Error::ConfigError(format!(
+                    "The password in file {} is not valid UTF-8",
+                    password_file.display()
+                )));
+            }
+
+            self.password = password;
+            info!(
+                "Loaded Postgres password from file {}: {}",
+                password_file.display(),
+                self.password
+            );
+        }
 
         Ok(())
     }
 
-    async fn request_new_product(
-        &self,
-        requested_product: &ProductRequest,
-    ) -> ProductDBResult<DBId> {
-        let product_desc = &requested_product.product_description;
-        let date = &requested_product.date;
+    /// Returns the application name for the connections including the instance id,
+    /// e.g. "product-db (host-1)".
+    pub fn connection_application_name(&self) -> String {
+        let application_name = self
+            .application_name
+            .as_deref()
+            .unwrap_or(DEFAULT_APPLICATION_NAME);
 
-        info!("Request new product with name: {}", product_desc.info.name);
+        let instance_id = self.instance_id.clone().unwrap_or_else(|| {
+            std::env::var("HOSTNAME").unwrap_or_else(|_| std::process::id().to_string())
+        });
 
-        // create the product description entry
-        let product_desc_id = self.create_product_description(product_desc).await?;
+        format!("{} ({})", application_name, instance_id)
+    }
+}
 
-        // insert the product into the requested_products table
-        let q = sqlx::query("insert into requested_products (product_description_id, date) values ($1, $2) returning id;")
-            .bind(product_desc_id)
-            .bind(date);
+impl PostgresBackend {
+    /// Create a new PostgresBackend instance.
+    ///
+    /// # Arguments
+    /// * `config` - The configuration for the postgres connection.
+    pub async fn new(config: PostgresConfig) -> ProductDBResult<Self> {
+        let pool = Self::create_pool(&config).await?;
 
-        let db_id: DBId = match self.pool.fetch_one(q).await {
-            Ok(row) => row.get(0),
-            Err(e) => {
-                error!("Failed to request new product: {}", e);
-                return Err(Error::DBError(Box::new(e)));
-            }
-        };
+        // only one instance migrates the database at a time, the others wait and find the
+        // migrations applied once they get the lock
+        let lock = MigrationLock::acquire(&pool).await?;
+        let backend = Self::migrate(config, pool).await;
+        lock.release().await;
 
-        info!(
-            "Requested new product with name: {} as {}",
-            product_desc.info.name, db_id
-        );
-        Ok(db_id)
+        backend
     }
 
-    async fn get_product_request(
-        &self,
-        id: DBId,
-        with_preview: bool,
-    ) -> ProductDBResult<Option<ProductRequest>> {
-        debug!(
-            "Get product request with id: {} [Preview={}]",
-            id, with_preview
-        );
+    /// Checks the schema version and applies the configured migrations, e.g. the partitioning
+    /// of the reported missing products. Must be called while holding the [`MigrationLock`].
+    ///
+    /// # Arguments
+    /// * `config` - The configuration for the postgres connection.
+    /// * `pool` - The connection pool to the database.
+    async fn migrate(config: PostgresConfig, pool: Pool) -> ProductDBResult<Self> {
+        Self::check_schema_version(&pool).await?;
+        let applied = Self::applied_schema_features(&pool).await?;
+        let is_applied =
+            |feature: SchemaFeature| applied.get(feature.name()).copied() == Some(SCHEMA_VERSION);
 
-        let mut query_builder = QueryBuilder::default();
-        Self::init_get_product_request_query(&mut query_builder, with_preview, false);
+        // the partitioning converts the table once, i.e., it is only recorded afterwards
+        let mut partitioned = Self::is_partitioned(&pool).await?;
+        let partitioning = SchemaFeature::PartitionedMissingProducts;
+        if config.partition_missing_products && !partitioned {
+            Self::apply_schema_feature(&pool, partitioning).await?;
+            partitioned = true;
+        } else if partitioned && !is_applied(partitioning) {
+            Self::record_schema_feature(&pool, partitioning).await?;
+        }
 
-        query_builder.push(" where r_id = $1;");
+        let features = [
+            (config.price_tracking, SchemaFeature::PriceTracking),
+            (
+                config.market_specific_products,
+                SchemaFeature::MarketSpecificProducts,
+            ),
+            (config.unaccent_search, SchemaFeature::UnaccentSearch),
+        ];
+        for (enabled, feature) in features {
+            if enabled && !is_applied(feature) {
+                Self::apply_schema_feature(&pool, feature).await?;
+            }
+        }
 
-        let query = query_builder
-            .build_query_as::<SQLRequestedProduct>()
-            .bind(id);
+        if config.missing_products_retention_months.is_some() && !partitioned {
+            error!("The retention of missing products requires partitioning");
+            return Err(Error::InvalidConfigError(
+                "missing_products_retention_months requires partition_missing_products".to_string(),
+            ));
+        }
 
-        let row = query.fetch_optional(&self.pool).await.map_err(|e| {
-            error!("Failed to get product request: {}", e);
-            Error::DBError(Box::new(e))
-        })?;
+        let image_store = config.image_store.create()?;
 
-        if row.is_none() {
-            debug!("No product request with id: {}", id);
+        let backend = Self {
+            config,
+            pool: RwLock::new(pool),
+            partitioned,
+            partitions: Mutex::new(HashSet::new()),
+            waiting_acquires: AtomicUsize::new(0),
+            image_store,
+        };
+
+        if partitioned {
+            // the set of known partitions is empty, i.e., the retention is applied at startup
+            backend.maintain_partitions(Utc::now()).await?;
+            let partitions = backend.list_partitions().await?;
+            backend.known_partitions().extend(partitions);
         }
 
-        Ok(row.map(|r| {
-            if !with_preview {
-                trace!(
-                    "Skip preview image decoding for product request with id: {}",
-                    id
-                );
-            }
+        Ok(backend)
+    }
 
-            let request: ProductRequest = r.into();
+    /// Drops the partitions of the reported missing products that are older than the
+    /// configured retention and returns the number of dropped partitions.
+    ///
+    /// # Arguments
+    /// * `now` - The current point in time.
+    pub async fn apply_missing_products_retention(
+        &self,
+        now: DateTime<Utc>,
+    ) -> ProductDBResult<usize> {
+        let retention_months = match self.config.missing_products_retention_months {
+            Some(retention_months) if self.partitioned => retention_months,
+            _ => return Ok(0),
+        };
 
-            request
-        }))
-    }
+        let cutoff = MonthlyPartition::containing(now)
+            .months_before(retention_months)
+            .start();
+        debug!("Dropping missing product partitions before {}", cutoff);
 
-    async fn get_product_request_image(&self, id: DBId) -> ProductDBResult<Option<ProductImage>> {
-        debug!("Get product image for product request id: {}", id);
+        let mut num_dropped = 0;
+        for partition in self.list_partitions().await? {
+            if partition.end() > cutoff {
+                continue;
+            }
 
-        let query = sqlx::query_as::<_, ProductImage>(
-            "select content_type, data from requested_products_full_image where r_id = $1;",
-        )
-        .bind(id);
+            info!("Dropping expired partition {}", partition.name());
+            let mut connection = self.acquire().await?;
+            if let Err(e) = connection
+                .execute(sqlx::raw_sql(&partition.drop_statement()))
+                .await
+            {
+                error!("Failed to drop partition {}: {}", partition.name(), e);
+                return Err(Error::DBError(Box::new(e)));
+            }
 
-        let row = query.fetch_optional(&self.pool).await.map_err(|e| {
-            error!(
-                "Failed to get product image for product request {}: {}",
-                id, e
-            );
-            Error::DBError(Box::new(e))
-        })?;
+            self.known_partitions().remove(&partition);
+            num_dropped += 1;
+        }
 
-        if let Some(row) = row {
-            Ok(Some(row))
-        } else {
-            debug!("No missing product with id: {}", id);
-            Ok(None)
+        // the photos of the dropped reports are deleted, their images via the trigger
+        if num_dropped > 0 {
+            let mut connection = self.acquire().await?;
+            sqlx::query!(
+                "delete from missing_product_photos p where not exists (
+                    select 1 from reported_missing_products r where r.id = p.missing_product_id
+                );"
+            )
+            .execute(&mut *connection)
+            .await
+            .map_err(|e| {
+                error!("Failed to delete the photos of the dropped reports: {}", e);
+                Error::DBError(Box::new(e))
+            })?;
         }
+
+        Ok(num_dropped)
     }
 
-    async fn delete_requested_product(&self, id: DBId) -> ProductDBResult<()> {
-        info!("Delete requested product with id: {}", id);
+    /// Replaces the connection pool by a new one using the given password, e.g. after the
+    /// credentials have been rotated. The old connections are drained gracefully, i.e.,
+    /// running queries are completed before the connections are closed.
+    ///
+    /// # Arguments
+    /// * `password` - The new password for the postgres connection.
+    pub async fn reconnect(&self, password: Secret) -> ProductDBResult<()> {
+        info!("Reconnecting to Postgres with new credentials...");
 
-        let q = sqlx::query("delete from requested_products where id = $1;").bind(id);
+        let mut config = self.config.clone();
+        config.password = password;
+        let pool = Self::create_pool(&config).await?;
 
-        if let Err(err) = self.pool.execute(q).await {
-            error!("Failed to delete requested product: {}", err);
-            return Err(Error::DBError(Box::new(err)));
-        }
+        let old_pool = std::mem::replace(
+            &mut *self
+                .pool
+                .write()
+                .unwrap_or_else(|poisoned| poisoned.into_inner()),
+            pool,
+        );
+        old_pool.close().await;
 
-        info!("Deleted requested product with id: {}", id);
+        info!("Reconnecting to Postgres with new credentials...DONE");
 
         Ok(())
     }
 
-    async fn new_product(&self, product_desc: &ProductDescription) -> ProductDBResult<bool> {
-        info!("New product with id: {}", product_desc.info.id);
+    /// Returns the current connection pool.
+    fn pool(&self) -> Pool {
+        self.pool
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
+    }
 
-        // create the product description entry
-        let product_desc_id = self.create_product_description(product_desc).await?;
+    /// Acquires a connection of the current pool and records the waiting operations and the
+    /// acquire latency in the metrics.
+    async fn acquire(&self) -> ProductDBResult<PoolConnection<Postgres>> {
+        let pool = self.pool();
+        let start = Instant::now();
 
-        // insert the product into the products table
-        let q = sqlx::query(
-            "insert into products (product_description_id, product_id) values ($1, $2);",
-        )
-        .bind(product_desc_id)
-        .bind(&product_desc.info.id);
+        let connection = {
+            let _waiting = WaitingAcquire::new(&self.waiting_acquires);
+            pool.acquire().await
+        };
+        histogram!(POOL_ACQUIRE_DURATION).record(start.elapsed());
 
-        if let Err(err) = self.pool.execute(q).await {
-            if let sqlx::Error::Database(ref db_err) = err {
-                if db_err.is_unique_violation() {
-                    info!(
-                        "Product with id {} already exists in the database",
-                        product_desc.info.id
-                    );
+        connection.map_err(|e| {
+            error!("Failed to acquire a database connection: {}", e);
+            Error::DBError(Box::new(e))
+        })
+    }
 
-                    // we need to cleanup the created product description entry
-                    let q = sqlx::query("delete from product_description where id = $1;")
-                        .bind(product_desc_id);
-                    if let Err(err) = self.pool.execute(q).await {
-                        error!("Failed to delete requested product: {}", err);
-                        return Err(Error::DBError(Box::new(err)));
-                    }
+    /// Returns the guard for the images uploaded to the image store by a write operation.
+    fn uploads(&self) -> UploadedImages {
+        UploadedImages {
+            store: self.image_store.clone(),
+            keys: Vec::new(),
+        }
+    }
 
-                    return Ok(false);
-                } else {
-                    error!(
-                        "Failed to add product with id {}: {}",
-                        product_desc.info.id, err
-                    );
-                    return Err(Error::DBError(Box::new(err)));
+    /// Uploads the image to the image store, if configured, and returns the row referencing it
+    /// by its storage key. Otherwise, the row contains the data of the image.
+    ///
+    /// # Arguments
+    /// * `image` - The image to store.
+    /// * `uploads` - The uploaded images of the operation, to which the image is added.
+    async fn upload_image<'a>(
+        &self,
+        image: &'a ProductImage,
+        uploads: &mut UploadedImages,
+    ) -> ProductDBResult<ImageRow<'a>> {
+        let Some(store) = &self.image_store else {
+            return Ok(ImageRow::inline(image));
+        };
+
+        let key = new_storage_key(&image.content_type);
+        store.put(&key, image).await?;
+        uploads.keys.push(key.clone());
+
+        Ok(ImageRow {
+            data: &[],
+            content_type: &image.content_type,
+            storage_key: Some(key),
+            storage_size: Some(image.data.len() as i64),
+        })
+    }
+
+    /// Returns the image of a row of the `product_image` table, whose data is loaded from the
+    /// image store if the row references it by its storage key.
+    ///
+    /// # Arguments
+    /// * `content_type` - The content type of the image.
+    /// * `data` - The data of the image, empty if the image is in the image store.
+    /// * `storage_key` - The storage key of the image, if it is in the image store.
+    async fn load_image(
+        &self,
+        content_type: String,
+        data: Vec<u8>,
+        storage_key: Option<String>,
+    ) -> ProductDBResult<ProductImage> {
+        let Some(key) = storage_key else {
+            return Ok(ProductImage { content_type, data });
+        };
+
+        let Some(store) = &self.image_store else {
+            error!(
+                "Image {} is in the image store, but none is configured",
+                key
+            );
+            return Err(Error::ImageStoreError(format!(
+                "Image {} is in the image store, but none is configured",
+                key
+            )));
+        };
+
+        let data = store.get(&key).await?;
+        Ok(ProductImage { content_type, data })
+    }
+
+    /// Deletes the deleted images from the image store. Their storage keys are collected by a
+    /// trigger, i.e., also if the images are deleted by a cascade. Failures are only logged and
+    /// the images are deleted by the next purge.
+    async fn purge_deleted_images(&self) {
+        let Some(store) = &self.image_store else {
+            return;
+        };
+
+        loop {
+            let Ok(mut connection) = self.acquire().await else {
+                return;
+            };
+            let mut transaction = match connection.begin().await {
+                Ok(transaction) => transaction,
+                Err(e) => {
+                    warn!("Failed to start transaction for purging images: {}", e);
+                    return;
+                }
+            };
+
+            // the keys stay claimed until the transaction ends, i.e., concurrent purges of other
+            // instances skip them and a failed purge leaves them for the next one
+            let q = sqlx::query_scalar!(
+                "select storage_key from deleted_stored_images
+                limit $1 for update skip locked;",
+                PURGE_BATCH_SIZE
+            );
+            let keys = match q.fetch_all(&mut *transaction).await {
+                Ok(keys) => keys,
+                Err(e) => {
+                    warn!("Failed to claim the deleted images: {}", e);
+                    return;
                 }
+            };
+
+            let mut deleted = Vec::with_capacity(keys.len());
+            for key in &keys {
+                match store.delete(key).await {
+                    Ok(()) => deleted.push(key.clone()),
+                    Err(e) => warn!("Failed to delete image {} from the image store: {}", key, e),
+                }
+            }
+            debug!(
+                "Deleted {} of {} images from the image store",
+                deleted.len(),
+                keys.len()
+            );
+
+            // only the keys of the images deleted from the store are removed
+            let q = sqlx::query!(
+                "delete from deleted_stored_images where storage_key = any($1::varchar[]);",
+                &deleted
+            );
+            if let Err(e) = q.execute(&mut *transaction).await {
+                error!("Failed to remove the purged images: {}", e);
+                return;
+            }
+            if let Err(e) = transaction.commit().await {
+                error!("Failed to commit the purge of the images: {}", e);
+                return;
+            }
+
+            if deleted.len() < keys.len() || (keys.len() as i64) < PURGE_BATCH_SIZE {
+                return;
+            }
+        }
+    }
+
+    /// Records the utilization of the connection pool in the metrics.
+    pub fn record_metrics(&self) {
+        let pool = self.pool();
+
+        gauge!(POOL_CONNECTIONS).set(pool.size() as f64);
+        gauge!(POOL_IDLE_CONNECTIONS).set(pool.num_idle() as f64);
+        gauge!(POOL_MAX_CONNECTIONS).set(self.config.max_connections as f64);
+        gauge!(POOL_WAITING_ACQUIRES).set(self.waiting_acquires.load(Ordering::Relaxed) as f64);
+    }
+
+    /// Creates a new connection pool for the given configuration.
+    ///
+    /// # Arguments
+    /// * `config` - The configuration for the postgres connection.
+    async fn create_pool(config: &PostgresConfig) -> ProductDBResult<Pool> {
+        // create the connection pool
+        info!("Creating Postgres connection pool...");
+
+        // get the current log level
+        let log_level = log::max_level();
+
+        let password = config.password.secret().ok_or_else(|| {
+            Error::ConfigError("The Postgres password is not valid UTF-8".to_string())
+        })?;
+
+        let mut options: PgConnectOptions = PgConnectOptions::new()
+            .host(&config.host)
+            .port(config.port)
+            .username(&config.user)
+            .password(password)
+            .database(&config.dbname)
+            .application_name(&config.connection_application_name())
+            .log_statements(if log_level == log::Level::Trace {
+                LevelFilter::Trace
             } else {
-                error!(
-                    "Failed to add product with id {}: {}",
-                    product_desc.info.id, err
-                );
-                return Err(Error::DBError(Box::new(err)));
+                LevelFilter::Off
+            });
+
+        if config.pgbouncer_mode {
+            // named prepared statements are bound to the server connection, which changes
+            // between transactions when running behind a transaction-mode pooler
+            info!("PgBouncer mode enabled, disabling the prepared statement cache");
+            options = options.statement_cache_capacity(0);
+        }
+
+        let mut pool_options = PgPoolOptions::new().max_connections(config.max_connections);
+
+        if let Some(schema) = &config.schema {
+            Self::check_schema_name(schema)?;
+
+            // poolers like PgBouncer reject the search_path as startup parameter and a session
+            // setting would leak to other clients of the server connection
+            if config.pgbouncer_mode {
+                error!("The Postgres schema is not supported in PgBouncer mode");
+                return Err(Error::InvalidConfigError(
+                    "The Postgres schema is not supported in PgBouncer mode, set the search_path \
+                    of the database role instead"
+                        .to_string(),
+                ));
             }
+
+            // the public schema contains the pg_trgm extension used for the search
+            info!("Using Postgres schema '{}'", schema);
+            let set_search_path = format!("set search_path to {}, public;", schema);
+            pool_options = pool_options.after_connect(move |connection, _| {
+                let set_search_path = set_search_path.clone();
+                Box::pin(async move {
+                    connection.execute(sqlx::raw_sql(&set_search_path)).await?;
+                    Ok(())
+                })
+            });
         }
 
-        info!("New product {} added", product_desc.info.id);
+        let pool = match pool_options.connect_with(options).await {
+            Ok(pool) => pool,
+            Err(e) => {
+                error!("Failed to create Postgres connection pool: {}", e);
+                return Err(Error::DBError(Box::new(e)));
+            }
+        };
 
-        Ok(true)
+        info!("Creating Postgres connection pool...DONE");
+
+        Ok(pool)
     }
 
-    async fn get_product(
-        &self,
-        id: &ProductID,
-        with_preview: bool,
-    ) -> ProductDBResult<Option<ProductDescription>> {
-        debug!("Get product with id: {} [Preview={}]", id, with_preview);
+    /// Returns true if the reported missing products table is partitioned.
+    ///
+    /// # Arguments
+    /// * `pool` - The connection pool to the database.
+    async fn is_partitioned(pool: &Pool) -> ProductDBResult<bool> {
+        sqlx::query_scalar!(
+            r#"select c.relkind = 'p' as "partitioned!" from pg_class c
+            where c.oid = 'reported_missing_products'::regclass;"#
+        )
+        .fetch_one(pool)
+        .await
+        .map_err(|e| {
+            error!(
+                "Failed to check the partitioning of missing products: {}",
+                e
+            );
+            Error::DBError(Box::new(e))
+        })
+    }
 
-        let mut query_builder = QueryBuilder::default();
-        Self::init_get_product_query(&mut query_builder, with_preview);
-        query_builder.push(" where product_id = $1;");
-        let query = query_builder
-            .build_query_as::<SQLProductDescription>()
-            .bind(id);
+    /// Returns the applied optional schema features together with the schema version they have
+    /// been applied to.
+    ///
+    /// # Arguments
+    /// * `pool` - The connection pool to the database.
+    async fn applied_schema_features(pool: &Pool) -> ProductDBResult<HashMap<String, i32>> {
+        let rows = sqlx::query!("select name, version from schema_features;")
+            .fetch_all(pool)
+            .await
+            .map_err(|e| {
+                error!("Failed to read the applied schema features: {}", e);
+                Error::DBError(Box::new(e))
+            })?;
 
-        let row = query.fetch_optional(&self.pool).await.map_err(|e| {
-            error!("Failed to get product request: {}", e);
+        Ok(rows
+            .into_iter()
+            .map(|row| (row.name, row.version))
+            .collect())
+    }
+
+    /// Applies the migration of the optional schema feature and records it for the current
+    /// schema version in the same transaction. The migrations can be applied repeatedly, except
+    /// for the partitioning, which must only be applied to an unpartitioned table.
+    ///
+    /// # Arguments
+    /// * `pool` - The connection pool to the database.
+    /// * `feature` - The feature to apply.
+    async fn apply_schema_feature(pool: &Pool, feature: SchemaFeature) -> ProductDBResult<()> {
+        info!("Applying schema feature {}...", feature.name());
+
+        let mut tx = pool.begin().await.map_err(|e| {
+            error!("Failed to start the transaction: {}", e);
             Error::DBError(Box::new(e))
         })?;
 
-        if row.is_none() {
-            debug!("No product request with id: {}", id);
+        if let Err(e) = tx.execute(sqlx::raw_sql(feature.migration())).await {
+            error!("Failed to apply schema feature {}: {}", feature.name(), e);
+            return Err(Error::DBError(Box::new(e)));
         }
+        Self::insert_schema_feature(&mut tx, feature).await?;
 
-        Ok(row.map(|r| {
-            if !with_preview {
-                trace!(
-                    "Skip preview image decoding for product request with id: {}",
-                    id
-                );
-            }
+        tx.commit().await.map_err(|e| {
+            error!("Failed to commit schema feature {}: {}", feature.name(), e);
+            Error::DBError(Box::new(e))
+        })?;
 
-            let request: ProductDescription = r.into();
+        info!("Applying schema feature {}...DONE", feature.name());
 
-            request
-        }))
+        Ok(())
     }
 
-    async fn get_product_image(&self, id: &ProductID) -> ProductDBResult<Option<ProductImage>> {
-        debug!("Get product image for product id: {}", id);
+    /// Records the optional schema feature as applied to the current schema version without
+    /// applying its migration, e.g. for a table partitioned before the features were recorded.
+    ///
+    /// # Arguments
+    /// * `pool` - The connection pool to the database.
+    /// * `feature` - The applied feature.
+    async fn record_schema_feature(pool: &Pool, feature: SchemaFeature) -> ProductDBResult<()> {
+        let mut connection = pool.acquire().await.map_err(|e| {
+            error!("Failed to acquire a connection: {}", e);
+            Error::DBError(Box::new(e))
+        })?;
 
-        let query =
-            sqlx::query_as::<_, ProductImage>("select pi.content_type, pi.data from product_image pi join product_description p on p.photo = pi.id where p.product_id = $1;")
-                .bind(id);
+        Self::insert_schema_feature(&mut connection, feature).await
+    }
 
-        let row = query.fetch_optional(&self.pool).await.map_err(|e| {
-            error!("Failed to get product image for id={}: {}", id, e);
+    /// Inserts or updates the record of the optional schema feature for the current schema
+    /// version.
+    ///
+    /// # Arguments
+    /// * `connection` - The connection to the database.
+    /// * `feature` - The applied feature.
+    async fn insert_schema_feature(
+        connection: &mut PgConnection,
+        feature: SchemaFeature,
+    ) -> ProductDBResult<()> {
+        sqlx::query!(
+            "insert into schema_features(name, version) values ($1, $2)
+            on conflict (name) do update set version = excluded.version, applied_at = now();",
+            feature.name(),
+            SCHEMA_VERSION
+        )
+        .execute(connection)
+        .await
+        .map_err(|e| {
+            error!("Failed to record schema feature {}: {}", feature.name(), e);
             Error::DBError(Box::new(e))
         })?;
 
-        if row.is_none() {
-            debug!("No product image with id: {}", id);
+        Ok(())
+    }
+
+    /// Returns an error if the price tracking is disabled in the config.
+    fn check_price_tracking(&self) -> ProductDBResult<()> {
+        if self.config.price_tracking {
+            Ok(())
+        } else {
+            Err(Error::PriceTrackingDisabled)
         }
+    }
 
-        Ok(row)
+    /// Returns the existing monthly partitions of the reported missing products.
+    async fn list_partitions(&self) -> ProductDBResult<Vec<MonthlyPartition>> {
+        let mut connection = self.acquire().await?;
+        let names = sqlx::query_scalar!(
+            r#"select c.relname::text as "name!" from pg_inherits i
+            join pg_class c on c.oid = i.inhrelid
+            where i.inhparent = 'reported_missing_products'::regclass;"#
+        )
+        .fetch_all(&mut *connection)
+        .await
+        .map_err(|e| {
+            error!("Failed to list the partitions of missing products: {}", e);
+            Error::DBError(Box::new(e))
+        })?;
+
+        Ok(names
+            .iter()
+            .filter_map(|name| MonthlyPartition::from_name(name))
+            .collect())
     }
 
-    async fn delete_product(&self, id: &ProductID) -> ProductDBResult<()> {
-        info!("Delete product with id: {}", id);
+    /// Returns the set of partitions that are known to exist.
+    fn known_partitions(&self) -> std::sync::MutexGuard<'_, HashSet<MonthlyPartition>> {
+        self.partitions
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
 
-        let q = sqlx::query("delete from products where product_id = $1;").bind(id);
+    /// Creates the given partition unless it is known to exist.
+    ///
+    /// # Arguments
+    /// * `partition` - The partition to create.
+    async fn ensure_partition(&self, partition: MonthlyPartition) -> ProductDBResult<()> {
+        if self.known_partitions().contains(&partition) {
+            return Ok(());
+        }
 
-        if let Err(err) = self.pool.execute(q).await {
-            error!("Failed to delete product: {}", err);
-            return Err(Error::DBError(Box::new(err)));
+        info!("Creating partition {}", partition.name());
+        let mut connection = self.acquire().await?;
+        match connection
+            .execute(sqlx::raw_sql(&partition.create_statement()))
+            .await
+        {
+            Ok(_) => {}
+            // another instance created the partition concurrently
+            Err(sqlx::Error::Database(db_err))
+                if matches!(db_err.code().as_deref(), Some("42P07") | Some("23505")) => {}
+            Err(e) => {
+                error!("Failed to create partition {}: {}", partition.name(), e);
+                return Err(Error::DBError(Box::new(e)));
+            }
         }
 
-        info!("Deleted product with id: {}", id);
+        self.known_partitions().insert(partition);
 
         Ok(())
     }
 
-    async fn query_product_requests(
+    /// Makes sure the partitions for the current and the next month exist. When a new month
+    /// has started, the retention is applied.
+    ///
+    /// # Arguments
+    /// * `now` - The current point in time.
+    async fn maintain_partitions(&self, now: DateTime<Utc>) -> ProductDBResult<()> {
+        let current = MonthlyPartition::containing(now);
+        if self.known_partitions().contains(&current) {
+            return Ok(());
+        }
+
+        self.ensure_partition(current).await?;
+        self.ensure_partition(current.next()).await?;
+        self.apply_missing_products_retention(now).await?;
+
+        Ok(())
+    }
+
+    /// Checks that the given schema name is a plain SQL identifier.
+    ///
+    /// # Arguments
+    /// * `schema` - The name of the schema to check.
+    fn check_schema_name(schema: &str) -> ProductDBResult<()> {
+        let mut chars = schema.chars();
+        let valid = chars
+            .next()
+            .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+            && chars.all(|c| c.is_ascii_alphanumeric() || c == '_');
+
+        if !valid {
+            error!("Invalid Postgres schema name '{}'", schema);
+            return Err(Error::InvalidConfigError(format!(
+                "Invalid Postgres schema name '{}'",
+                schema
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Acquires a connection from the pool and executes a trivial query. The check is bounded
+    /// by [`HEALTH_CHECK_TIMEOUT`] instead of the acquire timeout of the pool.
+    pub async fn health_check(&self) -> HealthReport {
+        let pool = self.pool();
+        let start = Instant::now();
+
+        let result = Self::select_one(&pool).await;
+
+        let report = Self::health_report(result, start.elapsed());
+        match report.status {
+            HealthStatus::Healthy => debug!("Database health check OK in {}ms", report.latency_ms),
+            _ => warn!(
+                "Database health check: {:?} ({}), {}/{} connections open, {} idle",
+                report.status,
+                report.details.as_deref().unwrap_or_default(),
+                pool.size(),
+                self.config.max_connections,
+                pool.num_idle()
+            ),
+        }
+
+        report
+    }
+
+    /// Acquires a connection from the pool and executes `select 1`, e.g. for readiness probes.
+    /// The ping is bounded by [`HEALTH_CHECK_TIMEOUT`] instead of the acquire timeout of the pool.
+    pub async fn ping(&self) -> ProductDBResult<()> {
+        Self::select_one(&self.pool()).await.map_err(|e| {
+            debug!("Database ping failed: {}", e);
+            Error::DBError(Box::new(e))
+        })
+    }
+
+    /// Acquires a connection from the pool and executes `select 1` within
+    /// [`HEALTH_CHECK_TIMEOUT`]. A timeout is reported as exhausted pool.
+    ///
+    /// # Arguments
+    /// * `pool` - The connection pool to the database.
+    async fn select_one(pool: &Pool) -> std::result::Result<(), sqlx::Error> {
+        let check = async {
+            let mut connection = pool.acquire().await?;
+            sqlx::query("select 1").execute(&mut *connection).await?;
+            Ok(())
+        };
+
+        match tokio::time::timeout(HEALTH_CHECK_TIMEOUT, check).await {
+            Ok(result) => result,
+            Err(_) => Err(sqlx::Error::PoolTimedOut),
+        }
+    }
+
+    /// Returns the health report for the result of a health check.
+    ///
+    /// # Arguments
+    /// * `result` - The result of acquiring a connection and executing the check.
+    /// * `latency` - The duration of the health check.
+    fn health_report(
+        result: std::result::Result<(), sqlx::Error>,
+        latency: Duration,
+    ) -> HealthReport {
+        let (status, details) = match result {
+            Ok(()) => (HealthStatus::Healthy, None),
+            Err(sqlx::Error::PoolTimedOut) => (
+                HealthStatus::Degraded,
+                Some("connection pool exhausted".to_string()),
+            ),
+            Err(sqlx::Error::PoolClosed) => (
+                HealthStatus::Unavailable,
+                Some("connection pool closed".to_string()),
+            ),
+            Err(e) => {
+                let e = Error::DBError(Box::new(e));
+                if e.is_authentication_error() {
+                    (
+                        HealthStatus::Unavailable,
+                        Some(format!("authentication failed: {}", e)),
+                    )
+                } else {
+                    (HealthStatus::Unavailable, Some(e.to_string()))
+                }
+            }
+        };
+
+        HealthReport {
+            status,
+            details,
+            latency_ms: latency.as_millis() as u64,
+        }
+    }
+
+    /// Checks that the version of the database schema matches the version the code requires.
+    ///
+    /// # Arguments
+    /// * `pool` - The connection pool to the database.
+    async fn check_schema_version(pool: &Pool) -> ProductDBResult<()> {
+        debug!("Checking database schema version...");
+
+        let found: Option<i32> =
+            match sqlx::query_scalar("select max(version) from schema_version;")
+                .fetch_one(pool)
+                .await
+            {
+                Ok(version) => version,
+                Err(sqlx::Error::Database(db_err)) if db_err.code().as_deref() == Some("42P01") => {
+                    // the schema_version table does not exist, i.e., the schema predates versioning
+                    None
+                }
+                Err(e) => {
+                    error!("Failed to read the database schema version: {}", e);
+                    return Err(Error::DBError(Box::new(e)));
+                }
+            };
+
+        let found = found.unwrap_or(0);
+        if found != SCHEMA_VERSION {
+            error!(
+                "Database schema version is v{}, but code requires v{}",
+                found, SCHEMA_VERSION
+            );
+            return Err(Error::IncompatibleSchemaVersion {
+                found,
+                expected: SCHEMA_VERSION,
+            });
+        }
+
+        debug!("Checking database schema version...v{} OK", found);
+
+        Ok(())
+    }
+}
+
+/// Transaction-level advisory lock that serializes the migrations of multiple instances
+/// starting simultaneously. The lock is taken by a transaction that stays open on a connection
+/// detached from the pool and is released when the transaction ends. In contrast to a
+/// session-level lock, this also holds behind a transaction-mode pooler like PgBouncer, which
+/// keeps the server connection assigned to the client only for the duration of a transaction.
+/// If the instance fails, the transaction is rolled back when its connection is closed.
+struct MigrationLock {
+    connection: PgConnection,
+}
+
+impl MigrationLock {
+    /// Acquires the lock and waits if another instance holds it.
+    ///
+    /// # Arguments
+    /// * `pool` - The connection pool to the database.
+    async fn acquire(pool: &Pool) -> ProductDBResult<Self> {
+        let mut connection = pool
+            .acquire()
+            .await
+            .map_err(|e| {
+                error!(
+                    "Failed to acquire a connection for the migration lock: {}",
+                    e
+                );
+                Error::DBError(Box::new(e))
+            })?
+            .detach();
+
+        connection
+            .execute(sqlx::raw_sql("begin;"))
+            .await
+            .map_err(|e| {
+                error!(
+                    "Failed to begin the transaction of the migration lock: {}",
+                    e
+                );
+                Error::DBError(Box::new(e))
+            })?;
+
+        let locked: bool = sqlx::query_scalar("select pg_try_advisory_xact_lock($1);")
+            .bind(MIGRATION_LOCK_KEY)
+            .fetch_one(&mut connection)
+            .await
+            .map_err(|e| {
+                error!("Failed to acquire the migration lock: {}", e);
+                Error::DBError(Box::new(e))
+            })?;
+
+        if !locked {
+            info!("Waiting for another instance to finish the migrations...");
+            sqlx::query("select pg_advisory_xact_lock($1);")
+                .bind(MIGRATION_LOCK_KEY)
+                .execute(&mut connection)
+                .await
+                .map_err(|e| {
+                    error!("Failed to acquire the migration lock: {}", e);
+                    Error::DBError(Box::new(e))
+                })?;
+            info!("Waiting for another instance to finish the migrations...DONE");
+        }
+
+        debug!("Acquired the migration lock");
+
+        Ok(Self { connection })
+    }
+
+    /// Releases the lock by ending its transaction and closes the connection.
+    async fn release(mut self) {
+        // the lock is released with the end of the transaction, i.e., closing the connection
+        // rolls it back if the commit fails
+        if let Err(e) = self.connection.execute(sqlx::raw_sql("commit;")).await {
+            warn!(
+                "Failed to commit the transaction of the migration lock: {}",
+                e
+            );
+        }
+
+        if let Err(e) = self.connection.close().await {
+            warn!(
+                "Failed to close the connection of the migration lock: {}",
+                e
+            );
+        }
+
+        debug!("Released the migration lock");
+    }
+}
+
+impl DataBackend for PostgresBackend {
+    async fn new(options: &Options) -> ProductDBResult<Self> {
+        let pg_config = options.postgres.clone();
+        Self::new(pg_config).await
+    }
+}
+
+impl ReadBackend for PostgresBackend {
+    async fn reconnect(&self, password: Secret) -> ProductDBResult<()> {
+        Self::reconnect(self, password).await
+    }
+
+    async fn health_check(&self) -> HealthReport {
+        Self::health_check(self).await
+    }
+
+    async fn ping(&self) -> ProductDBResult<()> {
+        Self::ping(self).await
+    }
+
+    fn record_metrics(&self) {
+        Self::record_metrics(self)
+    }
+
+    #[instrument(
+        skip_all,
+        fields(
+            offset = query.offset,
+            limit = query.limit,
+            rows = field::Empty,
+            duration_ms = field::Empty
+        )
+    )]
+    async fn query_missing_products(
+        &self,
+        query: &MissingProductQuery,
+    ) -> ProductDBResult<Vec<(DBId, MissingProduct)>> {
+        let _timer = OperationTimer::start();
+        let sorting_order = query.order.to_string();
+
+        let mut query_builder = QueryBuilder::new(
+            "select id, product_id, date, store_id from reported_missing_products ",
+        );
+
+        let mut _q: String = String::new();
+        Self::push_missing_product_filter(&mut query_builder, query.product_id.as_ref());
+
+        query_builder.push(" order by date ");
+        query_builder.push(sorting_order.as_str());
+        query_builder.push(", id ");
+        query_builder.push(sorting_order.as_str());
+        Self::add_offset_and_limit(&mut query_builder, query.offset, query.limit);
+
+        let query = query_builder.build_query_as::<SQLMissingProduct>();
+        let mut connection = self.acquire().await?;
+        let mut rows = query.fetch(&mut *connection);
+        let mut missing_products = Vec::new();
+        while let Some(row) = rows
+            .try_next()
+            .await
+            .map_err(|e| Error::DBError(Box::new(e)))?
+        {
+            missing_products.push((
+                row.id,
+                MissingProduct {
+                    product_id: row.product_id,
+                    date: row.date,
+                    store_id: row.store_id,
+                },
+            ));
+        }
+
+        record_rows(missing_products.len() as u64);
+        Ok(missing_products)
+    }
+
+    #[instrument(skip_all, fields(rows = field::Empty, duration_ms = field::Empty))]
+    async fn count_missing_products(&self, query: &MissingProductQuery) -> ProductDBResult<u64> {
+        let _timer = OperationTimer::start();
+        debug!("Count missing products: {:?}", query);
+
+        let mut query_builder =
+            QueryBuilder::new("select count(*) from reported_missing_products ");
+        Self::push_missing_product_filter(&mut query_builder, query.product_id.as_ref());
+
+        let mut connection = self.acquire().await?;
+        let count: i64 = query_builder
+            .build_query_scalar()
+            .fetch_one(&mut *connection)
+            .await
+            .map_err(|e| Error::DBError(Box::new(e)))?;
+
+        record_rows(1);
+        Ok(count as u64)
+    }
+
+    #[instrument(skip_all, fields(rows = field::Empty, duration_ms = field::Empty))]
+    async fn summarize_missing_products(
+        &self,
+        offset: i32,
+        limit: i32,
+    ) -> ProductDBResult<Vec<MissingProductSummary>> {
+        let _timer = OperationTimer::start();
+        debug!("Summarize missing products");
+
+        let mut query_builder = QueryBuilder::new(
+            "select product_id, count(*) as reports, min(date) as first_reported,
+            max(date) as last_reported from reported_missing_products
+            group by product_id order by reports desc, product_id asc",
+        );
+        Self::add_offset_and_limit(&mut query_builder, offset, limit);
+
+        let mut connection = self.acquire().await?;
+        let summaries: Vec<MissingProductSummary> = query_builder
+            .build_query_as::<SQLMissingProductSummary>()
+            .fetch_all(&mut *connection)
+            .await
+            .map_err(|e| {
+                error!("Failed to summarize the missing products: {}", e);
+                Error::DBError(Box::new(e))
+            })?
+            .into_iter()
+            .map(MissingProductSummary::from)
+            .collect();
+
+        record_rows(summaries.len() as u64);
+        Ok(summaries)
+    }
+
+    #[instrument(skip_all, fields(id = id, rows = field::Empty, duration_ms = field::Empty))]
+    async fn get_missing_product(&self, id: DBId) -> ProductDBResult<Option<MissingProduct>> {
+        let _timer = OperationTimer::start();
+        debug!("Get missing product with id: {}", id);
+
+        let query = sqlx::query_as!(
+            MissingProduct,
+            "select product_id, date, store_id from reported_missing_products where id = $1;",
+            id
+        );
+
+        let mut connection = self.acquire().await?;
+        let row = match query.fetch_optional(&mut *connection).await {
+            Ok(row) => row,
+            Err(e) => {
+                error!("Failed to get missing product: {}", e);
+                return Err(Error::DBError(Box::new(e)));
+            }
+        };
+        record_rows(row.is_some().into());
+
+        if let Some(row) = row {
+            debug!("Found missing product with id: {}", id);
+            trace!("Product: id={}, date={}", row.product_id, row.date);
+
+            Ok(Some(row))
+        } else {
+            debug!("No missing product with id: {}", id);
+            Ok(None)
+        }
+    }
+
+    #[instrument(skip_all, fields(id = id, rows = field::Empty, duration_ms = field::Empty))]
+    async fn get_missing_product_photo(&self, id: DBId) -> ProductDBResult<Option<ProductImage>> {
+        let _timer = OperationTimer::start();
+        debug!("Get photo of missing product with id: {}", id);
+
+        let query = sqlx::query!(
+            "select i.content_type, i.data, i.storage_key from missing_product_photos p
+            join product_image i on i.id = p.image where p.missing_product_id = $1;",
+            id
+        );
+
+        let mut connection = self.acquire().await?;
+        let row = query.fetch_optional(&mut *connection).await.map_err(|e| {
+            error!("Failed to get photo of missing product {}: {}", id, e);
+            Error::DBError(Box::new(e))
+        })?;
+        drop(connection);
+        record_rows(row.is_some().into());
+
+        match row {
+            Some(row) => Ok(Some(
+                self.load_image(row.content_type, row.data, row.storage_key)
+                    .await?,
+            )),
+            None => Ok(None),
+        }
+    }
+
+    #[instrument(
+        skip_all,
+        fields(
+            id = id,
+            with_preview = with_preview,
+            rows = field::Empty,
+            duration_ms = field::Empty
+        )
+    )]
+    async fn get_product_request(
+        &self,
+        id: DBId,
+        with_preview: bool,
+    ) -> ProductDBResult<Option<ProductRequest>> {
+        let _timer = OperationTimer::start();
+        debug!(
+            "Get product request with id: {} [Preview={}]",
+            id, with_preview
+        );
+
+        let mut query_builder = QueryBuilder::default();
+        Self::init_get_product_request_query(&mut query_builder, with_preview, false);
+
+        query_builder.push(" where r_id = $1;");
+
+        let query = query_builder
+            .build_query_as::<SQLRequestedProduct>()
+            .bind(id);
+
+        let mut connection = self.acquire().await?;
+        let row = query.fetch_optional(&mut *connection).await.map_err(|e| {
+            error!("Failed to get product request: {}", e);
+            Error::DBError(Box::new(e))
+        })?;
+        record_rows(row.is_some().into());
+
+        if row.is_none() {
+            debug!("No product request with id: {}", id);
+        }
+
+        Ok(row.map(|r| {
+            if !with_preview {
+                trace!(
+                    "Skip preview image decoding for product request with id: {}",
+                    id
+                );
+            }
+
+            let request: ProductRequest = r.into();
+
+            request
+        }))
+    }
+
+    #[instrument(skip_all, fields(id = id, rows = field::Empty, duration_ms = field::Empty))]
+    async fn get_product_request_image(&self, id: DBId) -> ProductDBResult<Option<ProductImage>> {
+        let _timer = OperationTimer::start();
+        debug!("Get product image for product request id: {}", id);
+
+        let query = sqlx::query!(
+            r#"select content_type as "content_type!", data as "data!", storage_key from requested_products_full_image where r_id = $1;"#,
+            id
+        );
+
+        let mut connection = self.acquire().await?;
+        let row = query.fetch_optional(&mut *connection).await.map_err(|e| {
+            error!(
+                "Failed to get product image for product request {}: {}",
+                id, e
+            );
+            Error::DBError(Box::new(e))
+        })?;
+        drop(connection);
+        record_rows(row.is_some().into());
+
+        if let Some(row) = row {
+            Ok(Some(
+                self.load_image(row.content_type, row.data, row.storage_key)
+                    .await?,
+            ))
+        } else {
+            debug!("No missing product with id: {}", id);
+            Ok(None)
+        }
+    }
+
+    #[instrument(skip_all, fields(id = id, rows = field::Empty, duration_ms = field::Empty))]
+    async fn get_product_request_images(
+        &self,
+        id: DBId,
+    ) -> ProductDBResult<Option<Vec<AdditionalImage>>> {
+        let _timer = OperationTimer::start();
+        debug!("Get additional images for product request id: {}", id);
+
+        let Some(description_id) = self.product_request_description_id(id).await? else {
+            debug!("No product request with id: {}", id);
+            record_rows(0);
+            return Ok(None);
+        };
+
+        let images = self.get_additional_images(description_id).await?;
+        record_rows(images.len() as u64);
+
+        Ok(Some(images))
+    }
+
+    #[instrument(
+        skip_all,
+        fields(
+            offset = query.offset,
+            limit = query.limit,
+            rows = field::Empty,
+            duration_ms = field::Empty
+        )
+    )]
+    async fn query_archived_product_requests(
+        &self,
+        query: &ArchiveQuery,
+    ) -> ProductDBResult<Vec<ArchivedProductRequest>> {
+        let _timer = OperationTimer::start();
+        debug!("Query archived product requests: {:?}", query);
+
+        let mut query_builder = QueryBuilder::new(
+            "select id, product_id, name, producer, date, archived_date, outcome, review_comment
+            from archived_product_requests where true",
+        );
+
+        if let Some(product_id) = query.product_id.as_ref() {
+            query_builder.push(" and product_id = ");
+            query_builder.push_bind(product_id);
+        }
+
+        if let Some(outcome) = query.outcome {
+            query_builder.push(" and outcome = ");
+            query_builder.push_bind(outcome);
+        }
+
+        query_builder.push(" order by archived_date ");
+        query_builder.push(query.order.to_string());
+        query_builder.push(", id ");
+        query_builder.push(query.order.to_string());
+        Self::add_offset_and_limit(&mut query_builder, query.offset, query.limit);
+
+        let query = query_builder.build_query_as::<ArchivedProductRequest>();
+        let mut connection = self.acquire().await?;
+        let requests = query.fetch_all(&mut *connection).await.map_err(|e| {
+            error!("Failed to query archived product requests: {}", e);
+            Error::DBError(Box::new(e))
+        })?;
+
+        record_rows(requests.len() as u64);
+        Ok(requests)
+    }
+
+    #[instrument(
+        skip_all,
+        fields(
+            product_id = %id,
+            with_preview = with_preview,
+            rows = field::Empty,
+            duration_ms = field::Empty
+        )
+    )]
+    async fn get_product(
+        &self,
+        id: &ProductID,
+        with_preview: bool,
+    ) -> ProductDBResult<Option<ProductDescription>> {
+        let _timer = OperationTimer::start();
+        debug!("Get product with id: {} [Preview={}]", id, with_preview);
+
+        let mut query_builder = QueryBuilder::default();
+        Self::init_get_product_query(&mut query_builder, with_preview);
+        // match GTINs by their canonical GTIN-14, prefer the exact match of the id and the
+        // product without market if the product is sold in several markets
+        query_builder.push(
+            " where (product_id = $1 or gtin = $2) \
+            order by product_id = $1 desc, market nulls first limit 1;",
+        );
+        let query = query_builder
+            .build_query_as::<SQLProductDescription>()
+            .bind(id)
+            .bind(normalize_gtin(id));
+
+        let mut connection = self.acquire().await?;
+        let row = query.fetch_optional(&mut *connection).await.map_err(|e| {
+            error!("Failed to get product request: {}", e);
+            Error::DBError(Box::new(e))
+        })?;
+        record_rows(row.is_some().into());
+
+        if row.is_none() {
+            debug!("No product request with id: {}", id);
+        }
+
+        Ok(row.map(|r| {
+            if !with_preview {
+                trace!(
+                    "Skip preview image decoding for product request with id: {}",
+                    id
+                );
+            }
+
+            let request: ProductDescription = r.into();
+
+            request
+        }))
+    }
+
+    #[instrument(
+        skip_all,
+        fields(count = ids.len(), rows = field::Empty, duration_ms = field::Empty)
+    )]
+    async fn get_products(&self, ids: &[ProductID]) -> ProductDBResult<Vec<ProductDescription>> {
+        let _timer = OperationTimer::start();
+        debug!("Get {} products", ids.len());
+
+        if ids.is_empty() {
+            record_rows(0);
+            return Ok(Vec::new());
+        }
+
+        let mut query_builder = QueryBuilder::default();
+        Self::init_get_product_query(&mut query_builder, false);
+        query_builder.push(" where product_id = any($1);");
+        let query = query_builder
+            .build_query_as::<SQLProductDescription>()
+            .bind(ids);
+
+        let mut connection = self.acquire().await?;
+        let rows = query.fetch_all(&mut *connection).await.map_err(|e| {
+            error!("Failed to get products: {}", e);
+            Error::DBError(Box::new(e))
+        })?;
+        record_rows(rows.len() as u64);
+
+        if rows.len() < ids.len() {
+            debug!("Found {} of {} products", rows.len(), ids.len());
+        }
+
+        Ok(rows.into_iter().map(|r| r.into()).collect())
+    }
+
+    #[instrument(
+        skip_all,
+        fields(product_id = %id, rows = field::Empty, duration_ms = field::Empty)
+    )]
+    async fn get_product_image(&self, id: &ProductID) -> ProductDBResult<Option<ProductImage>> {
+        let _timer = OperationTimer::start();
+        debug!("Get product image for product id: {}", id);
+
+        let query = sqlx::query!(
+            "select pi.content_type, pi.data, pi.storage_key from product_image pi join product_description p on p.photo = pi.id where p.product_id = $1;",
+            id
+        );
+
+        let mut connection = self.acquire().await?;
+        let row = query.fetch_optional(&mut *connection).await.map_err(|e| {
+            error!("Failed to get product image for id={}: {}", id, e);
+            Error::DBError(Box::new(e))
+        })?;
+        drop(connection);
+        record_rows(row.is_some().into());
+
+        match row {
+            Some(row) => Ok(Some(
+                self.load_image(row.content_type, row.data, row.storage_key)
+                    .await?,
+            )),
+            None => {
+                debug!("No product image with id: {}", id);
+                Ok(None)
+            }
+        }
+    }
+
+    #[instrument(
+        skip_all,
+        fields(product_id = %id, rows = field::Empty, duration_ms = field::Empty)
+    )]
+    async fn get_product_images(
+        &self,
+        id: &ProductID,
+    ) -> ProductDBResult<Option<Vec<AdditionalImage>>> {
+        let _timer = OperationTimer::start();
+        debug!("Get additional images for product id: {}", id);
+
+        let Some(description_id) = self.product_description_id(id).await? else {
+            debug!("No product with id: {}", id);
+            record_rows(0);
+            return Ok(None);
+        };
+
+        let images = self.get_additional_images(description_id).await?;
+        record_rows(images.len() as u64);
+
+        Ok(Some(images))
+    }
+
+    #[instrument(skip_all, fields(id = id, rows = field::Empty, duration_ms = field::Empty))]
+    async fn get_recipe(&self, id: DBId) -> ProductDBResult<Option<Recipe>> {
+        let _timer = OperationTimer::start();
+        debug!("Get recipe with id: {}", id);
+
+        let query = sqlx::query_as!(
+            SQLRecipe,
+            r#"select r.name, r.servings,
+                array(select i.product_id from recipe_ingredients i
+                    where i.recipe_id = r.id order by i.position) as "product_ids!",
+                array(select i.amount from recipe_ingredients i
+                    where i.recipe_id = r.id order by i.position) as "amounts!",
+                pi.data as "image_data?", pi.content_type as "image_content_type?",
+                pi.storage_key as "image_storage_key?"
+            from recipes r left join product_image pi on pi.id = r.image
+            where r.id = $1;"#,
+            id
+        );
+
+        let mut connection = self.acquire().await?;
+        let row = query.fetch_optional(&mut *connection).await.map_err(|e| {
+            error!("Failed to get recipe {}: {}", id, e);
+            Error::DBError(Box::new(e))
+        })?;
+        drop(connection);
+        record_rows(row.is_some().into());
+
+        let Some(mut row) = row else {
+            debug!("No recipe with id: {}", id);
+            return Ok(None);
+        };
+
+        if let Some(storage_key) = row.image_storage_key.take() {
+            let content_type = row.image_content_type.clone().unwrap_or_default();
+            let image = self
+                .load_image(content_type, Vec::new(), Some(storage_key))
+                .await?;
+            row.image_data = Some(image.data);
+        }
+
+        Ok(Some(row.into()))
+    }
+
+    #[instrument(
+        skip_all,
+        fields(
+            offset = query.offset,
+            limit = query.limit,
+            rows = field::Empty,
+            duration_ms = field::Empty
+        )
+    )]
+    async fn query_prices(
+        &self,
+        query: &PriceQuery,
+    ) -> ProductDBResult<Vec<(DBId, PriceObservation)>> {
+        let _timer = OperationTimer::start();
+        self.check_price_tracking()?;
+        debug!("Query prices: {:?}", query);
+
+        let mut query_builder = QueryBuilder::new(
+            "select id, product_id, store, price, currency, observed_at from prices where true",
+        );
+
+        if let Some(product_id) = query.product_id.as_ref() {
+            query_builder.push(" and product_id = ");
+            query_builder.push_bind(product_id);
+        }
+
+        if let Some(store) = query.store.as_ref() {
+            query_builder.push(" and store = ");
+            query_builder.push_bind(store);
+        }
+
+        if let Some(currency) = query.currency.as_ref() {
+            query_builder.push(" and currency = ");
+            query_builder.push_bind(currency);
+        }
+
+        query_builder.push(" order by observed_at ");
+        query_builder.push(query.order.to_string());
+        query_builder.push(", id ");
+        query_builder.push(query.order.to_string());
+        Self::add_offset_and_limit(&mut query_builder, query.offset, query.limit);
+
+        let query = query_builder.build_query_as::<SQLPriceObservation>();
+        let mut connection = self.acquire().await?;
+        let prices = query.fetch_all(&mut *connection).await.map_err(|e| {
+            error!("Failed to query prices: {}", e);
+            Error::DBError(Box::new(e))
+        })?;
+
+        record_rows(prices.len() as u64);
+        Ok(prices.into_iter().map(|p| p.into()).collect())
+    }
+
+    #[instrument(
+        skip_all,
+        fields(
+            product_id = %product_id,
+            currency = currency,
+            rows = field::Empty,
+            duration_ms = field::Empty
+        )
+    )]
+    async fn cheapest_price(
+        &self,
+        product_id: &ProductID,
+        currency: &str,
+    ) -> ProductDBResult<Option<PriceObservation>> {
+        let _timer = OperationTimer::start();
+        self.check_price_tracking()?;
+        debug!(
+            "Get cheapest price of product {} in {}",
+            product_id, currency
+        );
+
+        // the most recent observation wins among equal prices
+        let query = sqlx::query_as!(
+            PriceObservation,
+            "select product_id, store, price, currency, observed_at from prices
+            where product_id = $1 and currency = $2
+            order by price asc, observed_at desc limit 1;",
+            product_id,
+            currency
+        );
+
+        let mut connection = self.acquire().await?;
+        let row = query.fetch_optional(&mut *connection).await.map_err(|e| {
+            error!(
+                "Failed to get the cheapest price of product {}: {}",
+                product_id, e
+            );
+            Error::DBError(Box::new(e))
+        })?;
+        record_rows(row.is_some().into());
+
+        if row.is_none() {
+            debug!("No price of product {} in {}", product_id, currency);
+        }
+
+        Ok(row)
+    }
+
+    #[instrument(skip_all, fields(id = id, rows = field::Empty, duration_ms = field::Empty))]
+    async fn get_store(&self, id: DBId) -> ProductDBResult<Option<Store>> {
+        let _timer = OperationTimer::start();
+        debug!("Get store with id: {}", id);
+
+        let query = sqlx::query_as!(Store, "select name, chain from stores where id = $1;", id);
+
+        let mut connection = self.acquire().await?;
+        let row = query.fetch_optional(&mut *connection).await.map_err(|e| {
+            error!("Failed to get store {}: {}", id, e);
+            Error::DBError(Box::new(e))
+        })?;
+        record_rows(row.is_some().into());
+
+        if row.is_none() {
+            debug!("No store with id: {}", id);
+        }
+
+        Ok(row)
+    }
+
+    #[instrument(
+        skip_all,
+        fields(
+            offset = query.offset,
+            limit = query.limit,
+            rows = field::Empty,
+            duration_ms = field::Empty
+        )
+    )]
+    async fn query_stores(&self, query: &StoreQuery) -> ProductDBResult<Vec<(DBId, Store)>> {
+        let _timer = OperationTimer::start();
+        debug!("Query stores: {:?}", query);
+
+        let mut query_builder = QueryBuilder::new("select id, name, chain from stores where true");
+
+        if let Some(chain) = query.chain.as_ref() {
+            query_builder.push(" and chain = ");
+            query_builder.push_bind(chain);
+        }
+
+        query_builder.push(" order by name ");
+        query_builder.push(query.order.to_string());
+        query_builder.push(", id ");
+        query_builder.push(query.order.to_string());
+        Self::add_offset_and_limit(&mut query_builder, query.offset, query.limit);
+
+        let query = query_builder.build_query_as::<SQLStore>();
+        let mut connection = self.acquire().await?;
+        let stores = query.fetch_all(&mut *connection).await.map_err(|e| {
+            error!("Failed to query stores: {}", e);
+            Error::DBError(Box::new(e))
+        })?;
+
+        record_rows(stores.len() as u64);
+        Ok(stores.into_iter().map(|s| s.into()).collect())
+    }
+
+    #[instrument(
+        skip_all,
+        fields(product_id = %product_id, rows = field::Empty, duration_ms = field::Empty)
+    )]
+    async fn get_product_availability(
+        &self,
+        product_id: &ProductID,
+    ) -> ProductDBResult<Vec<ProductAvailability>> {
+        let _timer = OperationTimer::start();
+        debug!("Get availability of product {}", product_id);
+
+        let query = sqlx::query_as!(
+            ProductAvailability,
+            "select store_id, product_id, available, date from product_availability
+            where product_id = $1 order by store_id;",
+            product_id
+        );
+
+        let mut connection = self.acquire().await?;
+        let availability = query.fetch_all(&mut *connection).await.map_err(|e| {
+            error!(
+                "Failed to get the availability of product {}: {}",
+                product_id, e
+            );
+            Error::DBError(Box::new(e))
+        })?;
+
+        record_rows(availability.len() as u64);
+        Ok(availability)
+    }
+
+    #[instrument(skip_all, fields(rows = field::Empty, duration_ms = field::Empty))]
+    async fn image_storage_usage(&self) -> ProductDBResult<Vec<ImageStorageUsage>> {
+        let _timer = OperationTimer::start();
+        debug!("Get image storage usage");
+
+        let query = sqlx::query!(
+            r#"select content_type, count(*) as "images!", sum(coalesce(storage_size, octet_length(data)))::int8 as "bytes!"
+            from product_image group by content_type order by content_type;"#
+        );
+
+        let mut connection = self.acquire().await?;
+        let rows = query.fetch_all(&mut *connection).await.map_err(|e| {
+            error!("Failed to get the image storage usage: {}", e);
+            Error::DBError(Box::new(e))
+        })?;
+
+        record_rows(rows.len() as u64);
+        Ok(rows
+            .into_iter()
+            .map(|row| ImageStorageUsage {
+                content_type: row.content_type,
+                images: row.images as u64,
+                bytes: row.bytes as u64,
+            })
+            .collect())
+    }
+
+    #[instrument(
+        skip_all,
+        fields(
+            offset = query.offset,
+            limit = query.limit,
+            with_preview = with_preview,
+            rows = field::Empty,
+            duration_ms = field::Empty
+        )
+    )]
+    async fn query_product_requests(
+        &self,
+        query: &ProductQuery,
+        with_preview: bool,
+    ) -> ProductDBResult<Vec<(DBId, ProductRequest)>> {
+        let _timer = OperationTimer::start();
+        debug!("Query product requests: {:?}", query);
+
+        let (result, _) = self
+            .fetch_product_requests(query, with_preview, false)
+            .await?;
+
+        record_rows(result.len() as u64);
+        Ok(result)
+    }
+
+    #[instrument(
+        skip_all,
+        fields(
+            offset = query.offset,
+            limit = query.limit,
+            with_preview = with_preview,
+            rows = field::Empty,
+            duration_ms = field::Empty
+        )
+    )]
+    async fn query_products(
+        &self,
+        query: &ProductQuery,
+        with_preview: bool,
+    ) -> ProductDBResult<Vec<ProductDescription>> {
+        let _timer = OperationTimer::start();
+        debug!("Query products: {:?}", query);
+
+        let (products, _) = self
+            .fetch_products(query, with_preview, false, false)
+            .await?;
+
+        record_rows(products.len() as u64);
+        Ok(products.into_iter().map(|(product, _)| product).collect())
+    }
+
+    #[instrument(
+        skip_all,
+        fields(
+            offset = query.offset,
+            limit = query.limit,
+            with_preview = with_preview,
+            rows = field::Empty,
+            duration_ms = field::Empty
+        )
+    )]
+    async fn query_scored_products(
+        &self,
+        query: &ProductQuery,
+        with_preview: bool,
+    ) -> ProductDBResult<Vec<(ProductDescription, f32)>> {
+        let _timer = OperationTimer::start();
+        debug!("Query scored products: {:?}", query);
+
+        let (products, _) = self
+            .fetch_products(query, with_preview, true, false)
+            .await?;
+
+        record_rows(products.len() as u64);
+        Ok(products
+            .into_iter()
+            .map(|(product, score)| (product, score.unwrap_or_default()))
+            .collect())
+    }
+
+    #[instrument(skip_all, fields(rows = field::Empty, duration_ms = field::Empty))]
+    async fn count_products(&self, query: &ProductQuery) -> ProductDBResult<u64> {
+        let _timer = OperationTimer::start();
+        debug!("Count products: {:?}", query);
+
+        let search_string = query.filter.search_string().map(|s| s.to_lowercase());
+
+        let mut query_builder = QueryBuilder::new("select count(*) from products_full");
+        self.push_product_filters(&mut query_builder, query, search_string.as_deref());
+
+        let mut connection = self.acquire().await?;
+        let count: i64 = query_builder
+            .build_query_scalar()
+            .fetch_one(&mut *connection)
+            .await
+            .map_err(|e| Error::DBError(Box::new(e)))?;
+
+        record_rows(1);
+        Ok(count as u64)
+    }
+
+    #[instrument(skip_all, fields(rows = field::Empty, duration_ms = field::Empty))]
+    async fn count_product_requests(&self, query: &ProductQuery) -> ProductDBResult<u64> {
+        let _timer = OperationTimer::start();
+        debug!("Count product requests: {:?}", query);
+
+        let mut query_builder = QueryBuilder::new("select count(*) from requested_products_full");
+        self.push_product_request_filters(&mut query_builder, query);
+
+        let mut connection = self.acquire().await?;
+        let count: i64 = query_builder
+            .build_query_scalar()
+            .fetch_one(&mut *connection)
+            .await
+            .map_err(|e| Error::DBError(Box::new(e)))?;
+
+        record_rows(1);
+        Ok(count as u64)
+    }
+
+    #[instrument(
+        skip_all,
+        fields(
+            offset = query.offset,
+            limit = query.limit,
+            with_preview = with_preview,
+            rows = field::Empty,
+            duration_ms = field::Empty
+        )
+    )]
+    async fn query_counted_products(
+        &self,
+        query: &ProductQuery,
+        with_preview: bool,
+    ) -> ProductDBResult<CountedProducts> {
+        let _timer = OperationTimer::start();
+        debug!("Query counted products: {:?}", query);
+
+        let scored = query
+            .sorting
+            .is_some_and(|sorting| sorting.field == SortingField::Similarity);
+        let (products, total_count) = self
+            .fetch_products(query, with_preview, scored, true)
+            .await?;
+
+        // an empty page has no row with the total, e.g. if the offset is after the last product
+        let total_count = match total_count {
+            Some(total_count) => total_count,
+            None if query.offset == 0 && query.cursor.is_none() => 0,
+            None => self.count_products(query).await?,
+        };
+
+        record_rows(products.len() as u64);
+        Ok((products, total_count))
+    }
+
+    #[instrument(
+        skip_all,
+        fields(
+            offset = query.offset,
+            limit = query.limit,
+            with_preview = with_preview,
+            rows = field::Empty,
+            duration_ms = field::Empty
+        )
+    )]
+    async fn query_counted_product_requests(
+        &self,
+        query: &ProductQuery,
+        with_preview: bool,
+    ) -> ProductDBResult<CountedProductRequests> {
+        let _timer = OperationTimer::start();
+        debug!("Query counted product requests: {:?}", query);
+
+        let (requests, total_count) = self
+            .fetch_product_requests(query, with_preview, true)
+            .await?;
+
+        // an empty page has no row with the total, e.g. if the offset is after the last request
+        let total_count = match total_count {
+            Some(total_count) => total_count,
+            None if query.offset == 0 => 0,
+            None => self.count_product_requests(query).await?,
+        };
+
+        record_rows(requests.len() as u64);
+        Ok((requests, total_count))
+    }
+}
+
+impl WriteBackend for PostgresBackend {
+    #[instrument(
+        skip_all,
+        fields(
+            product_id = %missing_product.product_id,
+            id = field::Empty,
+            duration_ms = field::Empty
+        )
+    )]
+    async fn report_missing_product(
+        &self,
+        missing_product: MissingProduct,
+    ) -> ProductDBResult<DBId> {
+        let _timer = OperationTimer::start();
+        info!(
+            "Report missing product with id: {} with timestamp {}",
+            missing_product.product_id, missing_product.date
+        );
+
+        if self.partitioned {
+            self.maintain_partitions(Utc::now()).await?;
+            self.ensure_partition(MonthlyPartition::containing(missing_product.date))
+                .await?;
+        }
+
+        let mut connection = self.acquire().await?;
+        let db_id: DBId = match sqlx::query_scalar!(
+            "insert into reported_missing_products (product_id, date, store_id)
+            values ($1, $2, $3) returning id;",
+            missing_product.product_id,
+            missing_product.date,
+            missing_product.store_id
+        )
+        .fetch_one(&mut *connection)
+        .await
+        {
+            Ok(row) => row,
+            Err(e) => {
+                error!("Failed to report missing product: {}", e);
+                return Err(Error::DBError(Box::new(e)));
+            }
+        };
+
+        Span::current().record("id", db_id);
+        info!(
+            "Reported missing product with id: {} as {}",
+            missing_product.product_id, db_id
+        );
+
+        Ok(db_id)
+    }
+
+    #[instrument(
+        skip_all,
+        fields(
+            count = missing_products.len(),
+            rows = field::Empty,
+            duration_ms = field::Empty
+        )
+    )]
+    async fn report_missing_products(
+        &self,
+        missing_products: Vec<MissingProduct>,
+    ) -> ProductDBResult<Vec<DBId>> {
+        let _timer = OperationTimer::start();
+        info!("Report {} missing products", missing_products.len());
+
+        if missing_products.is_empty() {
+            record_rows(0);
+            return Ok(Vec::new());
+        }
+
+        if self.partitioned {
+            self.maintain_partitions(Utc::now()).await?;
+            let partitions: HashSet<MonthlyPartition> = missing_products
+                .iter()
+                .map(|m| MonthlyPartition::containing(m.date))
+                .collect();
+            for partition in partitions {
+                self.ensure_partition(partition).await?;
+            }
+        }
+
+        let mut connection = self.acquire().await?;
+        let mut transaction = connection.begin().await.map_err(|e| {
+            error!("Failed to start transaction for missing products: {}", e);
+            Error::DBError(Box::new(e))
+        })?;
+
+        let mut ids: Vec<DBId> = Vec::with_capacity(missing_products.len());
+        for missing_product in missing_products.iter() {
+            let db_id: DBId = sqlx::query_scalar!(
+                "insert into reported_missing_products (product_id, date, store_id)
+            values ($1, $2, $3) returning id;",
+                missing_product.product_id,
+                missing_product.date,
+                missing_product.store_id
+            )
+            .fetch_one(&mut *transaction)
+            .await
+            .map_err(|e| {
+                error!(
+                    "Failed to report missing product {}: {}",
+                    missing_product.product_id, e
+                );
+                Error::DBError(Box::new(e))
+            })?;
+
+            ids.push(db_id);
+        }
+
+        transaction.commit().await.map_err(|e| {
+            error!("Failed to commit the missing products: {}", e);
+            Error::DBError(Box::new(e))
+        })?;
+        record_rows(ids.len() as u64);
+
+        info!("Reported {} missing products", ids.len());
+
+        Ok(ids)
+    }
+
+    #[instrument(skip_all, fields(id = id, rows = field::Empty, duration_ms = field::Empty))]
+    async fn delete_reported_missing_product(&self, id: DBId) -> ProductDBResult<()> {
+        let _timer = OperationTimer::start();
+        info!("Delete reported missing product with id: {}", id);
+
+        // the deletion of the photo also deletes its image via the trigger
+        let mut connection = self.acquire().await?;
+        sqlx::query!(
+            "delete from missing_product_photos where missing_product_id = $1;",
+            id
+        )
+        .execute(&mut *connection)
+        .await
+        .map_err(|e| {
+            error!("Failed to delete photo of reported missing product: {}", e);
+            Error::DBError(Box::new(e))
+        })?;
+
+        let query = sqlx::query!("delete from reported_missing_products where id = $1;", id);
+        match query.execute(&mut *connection).await {
+            Ok(result) => record_rows(result.rows_affected()),
+            Err(e) => {
+                error!("Failed to delete reported missing product: {}", e);
+                return Err(Error::DBError(Box::new(e)));
+            }
+        }
+        drop(connection);
+        self.purge_deleted_images().await;
+
+        info!("Deleted reported missing product with id: {}", id);
+
+        Ok(())
+    }
+
+    #[instrument(skip_all, fields(id = id, rows = field::Empty, duration_ms = field::Empty))]
+    async fn set_missing_product_photo(
+        &self,
+        id: DBId,
+        photo: &ProductImage,
+    ) -> ProductDBResult<bool> {
+        let _timer = OperationTimer::start();
+        info!("Set photo of missing product with id: {}", id);
+
+        let mut uploads = self.uploads();
+        let photo = self.upload_image(photo, &mut uploads).await?;
+
+        let mut connection = self.acquire().await?;
+        let mut transaction = connection.begin().await.map_err(|e| {
+            error!(
+                "Failed to start transaction for missing product photo: {}",
+                e
+            );
+            Error::DBError(Box::new(e))
+        })?;
+
+        // the previous photo is replaced, its image is deleted via the trigger
+        sqlx::query!(
+            "delete from missing_product_photos where missing_product_id = $1;",
+            id
+        )
+        .execute(&mut *transaction)
+        .await
+        .map_err(|e| {
+            error!("Failed to delete photo of missing product {}: {}", id, e);
+            Error::DBError(Box::new(e))
+        })?;
+
+        let result = sqlx::query!(
+            "with new_image as (
+                insert into product_image (data, content_type, storage_key, storage_size)
+                values ($2, $3, $4, $5) returning id
+            )
+            insert into missing_product_photos (missing_product_id, image)
+            select r.id, new_image.id from reported_missing_products r, new_image
+            where r.id = $1;",
+            id,
+            photo.data,
+            photo.content_type,
+            photo.storage_key,
+            photo.storage_size
+        )
+        .execute(&mut *transaction)
+        .await
+        .map_err(|e| {
+            error!("Failed to set photo of missing product {}: {}", id, e);
+            Error::DBError(Box::new(e))
+        })?;
+        record_rows(result.rows_affected());
+
+        // the inserted image is discarded together with the transaction
+        if result.rows_affected() == 0 {
+            debug!("No missing product with id: {}", id);
+            return Ok(false);
+        }
+
+        transaction.commit().await.map_err(|e| {
+            error!("Failed to commit the missing product photo: {}", e);
+            Error::DBError(Box::new(e))
+        })?;
+        uploads.commit();
+        drop(connection);
+        self.purge_deleted_images().await;
+
+        info!("Set photo of missing product with id: {}", id);
+
+        Ok(true)
+    }
+
+    #[instrument(
+        skip_all,
+        fields(
+            product_id = %requested_product.product_description.info.id,
+            id = field::Empty,
+            duration_ms = field::Empty
+        )
+    )]
+    async fn request_new_product(
+        &self,
+        requested_product: &ProductRequest,
+    ) -> ProductDBResult<DBId> {
+        let _timer = OperationTimer::start();
+        let product_desc = &requested_product.product_description;
+        let date = &requested_product.date;
+
+        info!("Request new product with name: {}", product_desc.info.name);
+
+        let mut uploads = self.uploads();
+        let full_image = match product_desc.full_image.as_ref() {
+            Some(full_image) => Some(self.upload_image(full_image, &mut uploads).await?),
+            None => None,
+        };
+
+        // the nutrients and images are rolled back together with the transaction if any of the
+        // inserts fails, i.e., no orphaned rows are left behind
+        let mut connection = self.acquire().await?;
+        let mut transaction = connection.begin().await.map_err(|e| {
+            error!(
+                "Failed to start transaction for requesting new product: {}",
+                e
+            );
+            Error::DBError(Box::new(e))
+        })?;
+
+        // create the product description entry
+        let product_desc_id =
+            Self::create_product_description(&mut transaction, product_desc, full_image.as_ref())
+                .await?;
+
+        // insert the product into the requested_products table
+        let q = sqlx::query_scalar!(
+            "insert into requested_products (product_description_id, date, status, review_comment)
+            values ($1, $2, $3, $4) returning id;",
+            product_desc_id,
+            date,
+            requested_product.status as RequestStatus,
+            requested_product.review_comment
+        );
+
+        let db_id: DBId = match q.fetch_one(&mut *transaction).await {
+            Ok(db_id) => db_id,
+            Err(e) => {
+                error!("Failed to request new product: {}", e);
+                return Err(Error::DBError(Box::new(e)));
+            }
+        };
+
+        transaction.commit().await.map_err(|e| {
+            error!("Failed to commit the requested product: {}", e);
+            Error::DBError(Box::new(e))
+        })?;
+        uploads.commit();
+
+        info!(
+            "Requested new product with name: {} as {}",
+            product_desc.info.name, db_id
+        );
+        Ok(db_id)
+    }
+
+    #[instrument(
+        skip_all,
+        fields(id = id, outcome = %outcome, rows = field::Empty, duration_ms = field::Empty)
+    )]
+    async fn archive_product_request(
+        &self,
+        id: DBId,
+        outcome: RequestOutcome,
+    ) -> ProductDBResult<bool> {
+        let _timer = OperationTimer::start();
+        info!("Archive product request with id: {} as {}", id, outcome);
+
+        // the deletion of the request also deletes its product description via the trigger
+        let q = sqlx::query!(
+            "with archived as (
+                delete from requested_products r using product_description p
+                where r.id = $1 and p.id = r.product_description_id
+                returning r.id, p.product_id, p.name, p.producer, r.date, r.review_comment
+            )
+            insert into archived_product_requests
+                (id, product_id, name, producer, date, archived_date, outcome, review_comment)
+            select id, product_id, name, producer, date, now(), $2, review_comment from archived;",
+            id,
+            outcome as RequestOutcome
+        );
+
+        let mut connection = self.acquire().await?;
+        let archived = match q.execute(&mut *connection).await {
+            Ok(result) => {
+                record_rows(result.rows_affected());
+                result.rows_affected() > 0
+            }
+            Err(e) => {
+                error!("Failed to archive product request {}: {}", id, e);
+                return Err(Error::DBError(Box::new(e)));
+            }
+        };
+        drop(connection);
+        self.purge_deleted_images().await;
+
+        if archived {
+            info!("Archived product request with id: {} as {}", id, outcome);
+        } else {
+            debug!("No product request with id: {}", id);
+        }
+
+        Ok(archived)
+    }
+
+    #[instrument(skip_all, fields(rows = field::Empty, duration_ms = field::Empty))]
+    async fn archive_approved_product_requests(&self) -> ProductDBResult<u64> {
+        let _timer = OperationTimer::start();
+        debug!("Archive product requests of added products...");
+
+        let q = sqlx::query!(
+            "with archived as (
+                delete from requested_products r using product_description p
+                where p.id = r.product_description_id
+                    and exists (select 1 from products pr where pr.product_id = p.product_id)
+                returning r.id, p.product_id, p.name, p.producer, r.date, r.review_comment
+            )
+            insert into archived_product_requests
+                (id, product_id, name, producer, date, archived_date, outcome, review_comment)
+            select id, product_id, name, producer, date, now(), 'approved'::RequestOutcome,
+                review_comment
+            from archived;"
+        );
+
+        let mut connection = self.acquire().await?;
+        let num_archived = match q.execute(&mut *connection).await {
+            Ok(result) => result.rows_affected(),
+            Err(e) => {
+                error!(
+                    "Failed to archive product requests of added products: {}",
+                    e
+                );
+                return Err(Error::DBError(Box::new(e)));
+            }
+        };
+        drop(connection);
+        self.purge_deleted_images().await;
+
+        record_rows(num_archived);
+        info!(
+            "Archived {} product requests of added products",
+            num_archived
+        );
+
+        Ok(num_archived)
+    }
+
+    #[instrument(skip_all, fields(id = id, rows = field::Empty, duration_ms = field::Empty))]
+    async fn reject_product_request(
+        &self,
+        id: DBId,
+        comment: Option<&str>,
+    ) -> ProductDBResult<bool> {
+        let _timer = OperationTimer::start();
+        info!("Reject product request with id: {}", id);
+
+        let q = sqlx::query!(
+            "update requested_products set status = 'rejected'::RequestStatus, review_comment = $2
+            where id = $1;",
+            id,
+            comment
+        );
+
+        let mut connection = self.acquire().await?;
+        let num_rejected = match q.execute(&mut *connection).await {
+            Ok(result) => result.rows_affected(),
+            Err(err) => {
+                error!("Failed to reject product request {}: {}", id, err);
+                return Err(Error::DBError(Box::new(err)));
+            }
+        };
+        record_rows(num_rejected);
+
+        if num_rejected == 0 {
+            debug!("No product request with id: {}", id);
+            return Ok(false);
+        }
+
+        info!("Rejected product request {}", id);
+
+        Ok(true)
+    }
+
+    #[instrument(skip_all, fields(id = id, rows = field::Empty, duration_ms = field::Empty))]
+    async fn approve_product_request(&self, id: DBId) -> ProductDBResult<Option<ProductID>> {
+        let _timer = OperationTimer::start();
+        info!("Approve product request with id: {}", id);
+
+        let mut connection = self.acquire().await?;
+        let mut transaction = connection.begin().await.map_err(|e| {
+            error!(
+                "Failed to start transaction for approving product request: {}",
+                e
+            );
+            Error::DBError(Box::new(e))
+        })?;
+
+        let q = sqlx::query_scalar!(
+            "select p.product_id from requested_products r
+            join product_description p on p.id = r.product_description_id
+            where r.id = $1 for update of r;",
+            id
+        );
+        let product_id = match q.fetch_optional(&mut *transaction).await {
+            Ok(Some(product_id)) => product_id,
+            Ok(None) => {
+                debug!("No product request with id: {}", id);
+                record_rows(0);
+                return Ok(None);
+            }
+            Err(e) => {
+                error!("Failed to get product request {}: {}", id, e);
+                return Err(Error::DBError(Box::new(e)));
+            }
+        };
+
+        // the product takes over the description of the request, which is therefore kept when
+        // the request is deleted
+        let q = sqlx::query!(
+            "insert into products (product_description_id, product_id, market)
+            select p.id, p.product_id, p.market from requested_products r
+            join product_description p on p.id = r.product_description_id
+            where r.id = $1;",
+            id
+        );
+        if let Err(err) = q.execute(&mut *transaction).await {
+            if let sqlx::Error::Database(ref db_err) = err {
+                if db_err.is_unique_violation() {
+                    info!(
+                        "Product with id {} already exists in the database",
+                        product_id
+                    );
+                    record_rows(0);
+                    return Err(Error::ProductAlreadyExists(product_id));
+                }
+            }
+
+            error!("Failed to add product of product request {}: {}", id, err);
+            return Err(Error::DBError(Box::new(err)));
+        }
+
+        let q = sqlx::query!(
+            "with archived as (
+                delete from requested_products r using product_description p
+                where r.id = $1 and p.id = r.product_description_id
+                returning r.id, p.product_id, p.name, p.producer, r.date, r.review_comment
+            )
+            insert into archived_product_requests
+                (id, product_id, name, producer, date, archived_date, outcome, review_comment)
+            select id, product_id, name, producer, date, now(), 'approved'::RequestOutcome,
+                review_comment
+            from archived;",
+            id
+        );
+        if let Err(e) = q.execute(&mut *transaction).await {
+            error!("Failed to archive product request {}: {}", id, e);
+            return Err(Error::DBError(Box::new(e)));
+        }
+
+        transaction.commit().await.map_err(|e| {
+            error!(
+                "Failed to commit the approval of product request {}: {}",
+                id, e
+            );
+            Error::DBError(Box::new(e))
+        })?;
+        record_rows(1);
+
+        info!("Approved product request {} as product {}", id, product_id);
+
+        Ok(Some(product_id))
+    }
+
+    #[instrument(skip_all, fields(id = id, rows = field::Empty, duration_ms = field::Empty))]
+    async fn delete_requested_product(&self, id: DBId) -> ProductDBResult<()> {
+        let _timer = OperationTimer::start();
+        info!("Delete requested product with id: {}", id);
+
+        let q = sqlx::query!("delete from requested_products where id = $1;", id);
+
+        let mut connection = self.acquire().await?;
+        match q.execute(&mut *connection).await {
+            Ok(result) => record_rows(result.rows_affected()),
+            Err(err) => {
+                error!("Failed to delete requested product: {}", err);
+                return Err(Error::DBError(Box::new(err)));
+            }
+        }
+        drop(connection);
+        self.purge_deleted_images().await;
+
+        info!("Deleted requested product with id: {}", id);
+
+        Ok(())
+    }
+
+    #[instrument(
+        skip_all,
+        fields(product_id = %product_desc.info.id, rows = field::Empty, duration_ms = field::Empty)
+    )]
+    async fn new_product(&self, product_desc: &ProductDescription) -> ProductDBResult<bool> {
+        let _timer = OperationTimer::start();
+        info!("New product with id: {}", product_desc.info.id);
+
+        let mut uploads = self.uploads();
+        let full_image = match product_desc.full_image.as_ref() {
+            Some(full_image) => Some(self.upload_image(full_image, &mut uploads).await?),
+            None => None,
+        };
+
+        let info = &product_desc.info;
+        let q = Self::insert_product_query(product_desc, full_image.as_ref());
+
+        let mut connection = self.acquire().await?;
+        if let Err(err) = q.execute(&mut *connection).await {
+            if let sqlx::Error::Database(ref db_err) = err {
+                if db_err.is_unique_violation() {
+                    info!("Product with id {} already exists in the database", info.id);
+                    record_rows(0);
+                    return Ok(false);
+                }
+            }
+
+            error!("Failed to add product with id {}: {}", info.id, err);
+            return Err(Error::DBError(Box::new(err)));
+        }
+
+        uploads.commit();
+        record_rows(1);
+        info!("New product {} added", product_desc.info.id);
+
+        Ok(true)
+    }
+
+    #[instrument(skip_all, fields(rows = field::Empty, duration_ms = field::Empty))]
+    async fn new_products(&self, products: &[ProductDescription]) -> ProductDBResult<u64> {
+        let _timer = OperationTimer::start();
+        info!("New {} products", products.len());
+
+        // the full images are uploaded before the transaction holds the connection
+        let mut uploads = self.uploads();
+        let mut full_images = Vec::with_capacity(products.len());
+        for product_desc in products {
+            let mut product_uploads = self.uploads();
+            let full_image = match product_desc.full_image.as_ref() {
+                Some(full_image) => {
+                    Some(self.upload_image(full_image, &mut product_uploads).await?)
+                }
+                None => None,
+            };
+            full_images.push((full_image, product_uploads));
+        }
+
+        let mut connection = self.acquire().await?;
+        let mut transaction = connection.begin().await.map_err(|e| {
+            error!("Failed to start transaction for new products: {}", e);
+            Error::DBError(Box::new(e))
+        })?;
+
+        let mut added = 0;
+        for (product_desc, (full_image, product_uploads)) in products.iter().zip(full_images) {
+            // Every product is inserted within a savepoint, such that an existing product only
+            // rolls back its own statement instead of the whole transaction.
+            let mut savepoint = transaction.begin().await.map_err(|e| {
+                error!("Failed to create savepoint for new product: {}", e);
+                Error::DBError(Box::new(e))
+            })?;
+
+            let q = Self::insert_product_query(product_desc, full_image.as_ref());
+            match q.execute(&mut *savepoint).await {
+                Ok(_) => {}
+                Err(sqlx::Error::Database(db_err)) if db_err.is_unique_violation() => {
+                    debug!(
+                        "Product with id {} already exists in the database",
+                        product_desc.info.id
+                    );
+                    savepoint.rollback().await.map_err(|e| {
+                        error!("Failed to roll back savepoint for new product: {}", e);
+                        Error::DBError(Box::new(e))
+                    })?;
+                    continue;
+                }
+                Err(err) => {
+                    error!(
+                        "Failed to add product with id {}: {}",
+                        product_desc.info.id, err
+                    );
+                    return Err(Error::DBError(Box::new(err)));
+                }
+            }
+
+            savepoint.commit().await.map_err(|e| {
+                error!("Failed to release savepoint for new product: {}", e);
+                Error::DBError(Box::new(e))
+            })?;
+            uploads.merge(product_uploads);
+            added += 1;
+        }
+
+        transaction.commit().await.map_err(|e| {
+            error!("Failed to commit the new products: {}", e);
+            Error::DBError(Box::new(e))
+        })?;
+        uploads.commit();
+        record_rows(added);
+
+        info!("Added {} of {} new products", added, products.len());
+
+        Ok(added)
+    }
+
+    #[instrument(
+        skip_all,
+        fields(id = id, images = images.len(), rows = field::Empty, duration_ms = field::Empty)
+    )]
+    async fn add_product_request_images(
+        &self,
+        id: DBId,
+        images: &[AdditionalImage],
+    ) -> ProductDBResult<bool> {
+        let _timer = OperationTimer::start();
+        info!("Add {} images to product request {}", images.len(), id);
+
+        let Some(description_id) = self.product_request_description_id(id).await? else {
+            info!("No product request with id: {}", id);
+            record_rows(0);
+            return Ok(false);
+        };
+
+        self.add_additional_images(description_id, images).await?;
+        record_rows(images.len() as u64);
+
+        Ok(true)
+    }
+
+    #[instrument(
+        skip_all,
+        fields(product_id = %id, images = images.len(), rows = field::Empty, duration_ms = field::Empty)
+    )]
+    async fn add_product_images(
+        &self,
+        id: &ProductID,
+        images: &[AdditionalImage],
+    ) -> ProductDBResult<bool> {
+        let _timer = OperationTimer::start();
+        info!("Add {} images to product {}", images.len(), id);
+
+        let Some(description_id) = self.product_description_id(id).await? else {
+            info!("No product with id: {}", id);
+            record_rows(0);
+            return Ok(false);
+        };
+
+        self.add_additional_images(description_id, images).await?;
+        record_rows(images.len() as u64);
+
+        Ok(true)
+    }
+
+    #[instrument(
+        skip_all,
+        fields(product_id = %product_desc.info.id, rows = field::Empty, duration_ms = field::Empty)
+    )]
+    async fn update_product(&self, product_desc: &ProductDescription) -> ProductDBResult<bool> {
+        let _timer = OperationTimer::start();
+        let id = &product_desc.info.id;
+        info!("Update product with id: {}", id);
+
+        let mut uploads = self.uploads();
+        let full_image = match product_desc.full_image.as_ref() {
+            Some(full_image) => Some(self.upload_image(full_image, &mut uploads).await?),
+            None => None,
+        };
+
+        let mut connection = self.acquire().await?;
+        let mut transaction = connection.begin().await.map_err(|e| {
+            error!("Failed to start transaction for updating product: {}", e);
+            Error::DBError(Box::new(e))
+        })?;
+
+        // the product is identified by its id and market, which are not changed
+        let q = sqlx::query_scalar!(
+            "select product_description_id from products
+            where product_id = $1 and market is not distinct from $2 for update;",
+            id,
+            product_desc.info.market
+        );
+        let description_id = match q.fetch_optional(&mut *transaction).await {
+            Ok(Some(description_id)) => description_id,
+            Ok(None) => {
+                debug!("No product with id: {}", id);
+                record_rows(0);
+                return Ok(false);
+            }
+            Err(e) => {
+                error!("Failed to get product {}: {}", id, e);
+                return Err(Error::DBError(Box::new(e)));
+            }
+        };
+
+        Self::update_description_query(description_id, product_desc)
+            .execute(&mut *transaction)
+            .await
+            .map_err(|e| {
+                error!("Failed to update product {}: {}", id, e);
+                Error::DBError(Box::new(e))
+            })?;
+
+        if let Some(preview) = product_desc.preview.as_ref() {
+            Self::replace_preview(&mut transaction, description_id, preview).await?;
+        }
+        if let Some(full_image) = full_image.as_ref() {
+            Self::replace_full_image(&mut transaction, description_id, full_image).await?;
+        }
+
+        transaction.commit().await.map_err(|e| {
+            error!("Failed to commit the update of product {}: {}", id, e);
+            Error::DBError(Box::new(e))
+        })?;
+        uploads.commit();
+        drop(connection);
+        self.purge_deleted_images().await;
+        record_rows(1);
+
+        info!("Updated product with id: {}", id);
+
+        Ok(true)
+    }
+
+    #[instrument(
+        skip_all,
+        fields(product_id = %id, rows = field::Empty, duration_ms = field::Empty)
+    )]
+    async fn delete_product(&self, id: &ProductID) -> ProductDBResult<()> {
+        let _timer = OperationTimer::start();
+        info!("Delete product with id: {}", id);
+
+        let q = sqlx::query!("delete from products where product_id = $1;", id);
+
+        let mut connection = self.acquire().await?;
+        match q.execute(&mut *connection).await {
+            Ok(result) => record_rows(result.rows_affected()),
+            Err(err) => {
+                error!("Failed to delete product: {}", err);
+                return Err(Error::DBError(Box::new(err)));
+            }
+        }
+        drop(connection);
+        self.purge_deleted_images().await;
+
+        info!("Deleted product with id: {}", id);
+
+        Ok(())
+    }
+
+    #[instrument(
+        skip_all,
+        fields(product_id = %id, rows = field::Empty, duration_ms = field::Empty)
+    )]
+    async fn update_product_preview(
+        &self,
+        id: &ProductID,
+        preview: &ProductImage,
+    ) -> ProductDBResult<bool> {
+        let _timer = OperationTimer::start();
+        info!("Update preview of product with id: {}", id);
+
+        // with market specific products, every market has its own description and preview
+        let q = sqlx::query_scalar!(
+            "select product_description_id from products where product_id = $1;",
+            id
+        );
+
+        let mut connection = self.acquire().await?;
+        let description_ids = q.fetch_all(&mut *connection).await.map_err(|e| {
+            error!("Failed to get the descriptions of product {}: {}", id, e);
+            Error::DBError(Box::new(e))
+        })?;
+
+        for description_id in description_ids.iter() {
+            Self::replace_preview(&mut connection, *description_id, preview).await?;
+        }
+        record_rows(description_ids.len() as u64);
+
+        if description_ids.is_empty() {
+            debug!("No product with id: {}", id);
+        } else {
+            info!("Updated preview of product with id: {}", id);
+        }
+
+        Ok(!description_ids.is_empty())
+    }
+
+    #[instrument(skip_all, fields(id = id, rows = field::Empty, duration_ms = field::Empty))]
+    async fn update_product_request_preview(
+        &self,
+        id: DBId,
+        preview: &ProductImage,
+    ) -> ProductDBResult<bool> {
+        let _timer = OperationTimer::start();
+        info!("Update preview of product request with id: {}", id);
+
+        let q = sqlx::query_scalar!(
+            "select product_description_id from requested_products where id = $1;",
+            id
+        );
+
+        let mut connection = self.acquire().await?;
+        let description_id = q.fetch_optional(&mut *connection).await.map_err(|e| {
+            error!(
+                "Failed to get the description of product request {}: {}",
+                id, e
+            );
+            Error::DBError(Box::new(e))
+        })?;
+        record_rows(description_id.is_some().into());
+
+        match description_id {
+            Some(description_id) => {
+                Self::replace_preview(&mut connection, description_id, preview).await?;
+                info!("Updated preview of product request with id: {}", id);
+                Ok(true)
+            }
+            None => {
+                debug!("No product request with id: {}", id);
+                Ok(false)
+            }
+        }
+    }
+
+    #[instrument(
+        skip_all,
+        fields(id = field::Empty, rows = field::Empty, duration_ms = field::Empty)
+    )]
+    async fn new_recipe(&self, recipe: &Recipe) -> ProductDBResult<DBId> {
+        let _timer = OperationTimer::start();
+        info!("New recipe '{}'", recipe.name);
+
+        let (product_ids, amounts): (Vec<ProductID>, Vec<f32>) = recipe
+            .ingredients
+            .iter()
+            .map(|i| (i.product_id.clone(), i.amount))
+            .unzip();
+        let mut uploads = self.uploads();
+        let image = match recipe.image.as_ref() {
+            Some(image) => Some(self.upload_image(image, &mut uploads).await?),
+            None => None,
+        };
+
+        // Insert the image, the recipe and its ingredients in a single statement, i.e., a single
+        // round trip.
+        let q = sqlx::query_scalar!(
+            r#"with new_image as (
+                insert into product_image (data, content_type, storage_key, storage_size)
+                select $3::bytea, $4::varchar, $7::varchar, $8::int8
+                where $3::bytea is not null returning id
+            ), new_recipe as (
+                insert into recipes (name, servings, image)
+                values ($1, $2, (select id from new_image)) returning id
+            ), new_ingredients as (
+                insert into recipe_ingredients (recipe_id, position, product_id, amount)
+                select new_recipe.id, i.position, i.product_id, i.amount
+                from new_recipe,
+                    unnest($5::varchar[], $6::real[]) with ordinality as i(product_id, amount, position)
+            )
+            select id as "id!" from new_recipe;"#,
+            recipe.name,
+            recipe.servings,
+            image.as_ref().map(|i| i.data),
+            image.as_ref().map(|i| i.content_type),
+            &product_ids,
+            &amounts,
+            image.as_ref().and_then(|i| i.storage_key.as_deref()),
+            image.as_ref().and_then(|i| i.storage_size)
+        );
+
+        let mut connection = self.acquire().await?;
+        let id = q.fetch_one(&mut *connection).await.map_err(|e| {
+            error!("Failed to add recipe '{}': {}", recipe.name, e);
+            Error::DBError(Box::new(e))
+        })?;
+        uploads.commit();
+        record_rows(1);
+        Span::current().record("id", id);
+
+        info!("New recipe '{}' added with id: {}", recipe.name, id);
+
+        Ok(id)
+    }
+
+    #[instrument(skip_all, fields(id = id, rows = field::Empty, duration_ms = field::Empty))]
+    async fn update_recipe(&self, id: DBId, recipe: &Recipe) -> ProductDBResult<bool> {
+        let _timer = OperationTimer::start();
+        info!("Update recipe with id: {}", id);
+
+        let (product_ids, amounts): (Vec<ProductID>, Vec<f32>) = recipe
+            .ingredients
+            .iter()
+            .map(|i| (i.product_id.clone(), i.amount))
+            .unzip();
+        let mut uploads = self.uploads();
+        let image = match recipe.image.as_ref() {
+            Some(image) => Some(self.upload_image(image, &mut uploads).await?),
+            None => None,
+        };
+
+        // Replace the recipe, its ingredients and its image in a single statement. All parts of
+        // the statement see the same snapshot, i.e., only the previous ingredients and the
+        // previous image are deleted.
+        let q = sqlx::query!(
+            "with old_recipe as (
+                select id, image from recipes where id = $1 for update
+            ), new_image as (
+                insert into product_image (data, content_type, storage_key, storage_size)
+                select $4::bytea, $5::varchar, $8::varchar, $9::int8
+                where $4::bytea is not null and exists (select 1 from old_recipe) returning id
+            ), updated as (
+                update recipes set name = $2, servings = $3, image = (select id from new_image)
+                where id in (select id from old_recipe) returning id
+            ), old_ingredients as (
+                delete from recipe_ingredients where recipe_id in (select id from old_recipe)
+            ), new_ingredients as (
+                insert into recipe_ingredients (recipe_id, position, product_id, amount)
+                select updated.id, i.position, i.product_id, i.amount
+                from updated,
+                    unnest($6::varchar[], $7::real[]) with ordinality as i(product_id, amount, position)
+            ), old_image as (
+                delete from product_image where id in (select image from old_recipe)
+            )
+            select id from updated;",
+            id,
+            recipe.name,
+            recipe.servings,
+            image.as_ref().map(|i| i.data),
+            image.as_ref().map(|i| i.content_type),
+            &product_ids,
+            &amounts,
+            image.as_ref().and_then(|i| i.storage_key.as_deref()),
+            image.as_ref().and_then(|i| i.storage_size)
+        );
+
+        let mut connection = self.acquire().await?;
+        let row = q.fetch_optional(&mut *connection).await.map_err(|e| {
+            error!("Failed to update recipe {}: {}", id, e);
+            Error::DBError(Box::new(e))
+        })?;
+        drop(connection);
+        record_rows(row.is_some().into());
+
+        if row.is_some() {
+            uploads.commit();
+            self.purge_deleted_images().await;
+            info!("Updated recipe with id: {}", id);
+        } else {
+            debug!("No recipe with id: {}", id);
+        }
+
+        Ok(row.is_some())
+    }
+
+    #[instrument(skip_all, fields(id = id, rows = field::Empty, duration_ms = field::Empty))]
+    async fn delete_recipe(&self, id: DBId) -> ProductDBResult<()> {
+        let _timer = OperationTimer::start();
+        info!("Delete recipe with id: {}", id);
+
+        // the ingredients are deleted by the cascade and the image by the trigger
+        let q = sqlx::query!("delete from recipes where id = $1;", id);
+
+        let mut connection = self.acquire().await?;
+        match q.execute(&mut *connection).await {
+            Ok(result) => record_rows(result.rows_affected()),
+            Err(err) => {
+                error!("Failed to delete recipe: {}", err);
+                return Err(Error::DBError(Box::new(err)));
+            }
+        }
+        drop(connection);
+        self.purge_deleted_images().await;
+
+        info!("Deleted recipe with id: {}", id);
+
+        Ok(())
+    }
+
+    #[instrument(
+        skip_all,
+        fields(product_id = %price.product_id, id = field::Empty, duration_ms = field::Empty)
+    )]
+    async fn submit_price(&self, price: &PriceObservation) -> ProductDBResult<DBId> {
+        let _timer = OperationTimer::start();
+        self.check_price_tracking()?;
+        debug!(
+            "Submit price {} {} of product {} at {}",
+            price.price, price.currency, price.product_id, price.store
+        );
+
+        let q = sqlx::query_scalar!(
+            "insert into prices (product_id, store, price, currency, observed_at)
+            values ($1, $2, $3, $4, $5) returning id;",
+            price.product_id,
+            price.store,
+            price.price,
+            price.currency,
+            price.observed_at
+        );
+
+        let mut connection = self.acquire().await?;
+        let id = q.fetch_one(&mut *connection).await.map_err(|e| {
+            error!(
+                "Failed to submit price of product {}: {}",
+                price.product_id, e
+            );
+            Error::DBError(Box::new(e))
+        })?;
+        Span::current().record("id", id);
+
+        info!(
+            "Price of product {} submitted with id: {}",
+            price.product_id, id
+        );
+
+        Ok(id)
+    }
+
+    #[instrument(skip_all, fields(id = field::Empty, duration_ms = field::Empty))]
+    async fn new_store(&self, store: &Store) -> ProductDBResult<DBId> {
+        let _timer = OperationTimer::start();
+        info!("New store '{}'", store.name);
+
+        let q = sqlx::query_scalar!(
+            "insert into stores (name, chain) values ($1, $2) returning id;",
+            store.name,
+            store.chain
+        );
+
+        let mut connection = self.acquire().await?;
+        let id = q.fetch_one(&mut *connection).await.map_err(|e| {
+            error!("Failed to add store '{}': {}", store.name, e);
+            Error::DBError(Box::new(e))
+        })?;
+        Span::current().record("id", id);
+
+        info!("Added store '{}' with id: {}", store.name, id);
+
+        Ok(id)
+    }
+
+    #[instrument(skip_all, fields(id = id, rows = field::Empty, duration_ms = field::Empty))]
+    async fn delete_store(&self, id: DBId) -> ProductDBResult<()> {
+        let _timer = OperationTimer::start();
+        info!("Delete store with id: {}", id);
+
+        // the availability is deleted by the cascade and the reports lose their store
+        let q = sqlx::query!("delete from stores where id = $1;", id);
+
+        let mut connection = self.acquire().await?;
+        match q.execute(&mut *connection).await {
+            Ok(result) => record_rows(result.rows_affected()),
+            Err(err) => {
+                error!("Failed to delete store: {}", err);
+                return Err(Error::DBError(Box::new(err)));
+            }
+        }
+
+        info!("Deleted store with id: {}", id);
+
+        Ok(())
+    }
+
+    #[instrument(
+        skip_all,
+        fields(
+            store_id = availability.store_id,
+            product_id = %availability.product_id,
+            rows = field::Empty,
+            duration_ms = field::Empty
+        )
+    )]
+    async fn report_availability(
+        &self,
+        availability: &ProductAvailability,
+    ) -> ProductDBResult<bool> {
+        let _timer = OperationTimer::start();
+        debug!(
+            "Report product {} as {} at store {}",
+            availability.product_id,
+            if availability.available {
+                "available"
+            } else {
+                "missing"
+            },
+            availability.store_id
+        );
+
+        // nothing is inserted if the store does not exist
+        let q = sqlx::query!(
+            "insert into product_availability (store_id, product_id, available, date)
+            select id, $2, $3, $4 from stores where id = $1
+            on conflict (store_id, product_id)
+            do update set available = excluded.available, date = excluded.date;",
+            availability.store_id,
+            availability.product_id,
+            availability.available,
+            availability.date
+        );
+
+        let mut connection = self.acquire().await?;
+        let rows_affected = match q.execute(&mut *connection).await {
+            Ok(result) => result.rows_affected(),
+            Err(err) => {
+                error!("Failed to report the availability: {}", err);
+                return Err(Error::DBError(Box::new(err)));
+            }
+        };
+        record_rows(rows_affected);
+
+        if rows_affected == 0 {
+            debug!("No store with id: {}", availability.store_id);
+        }
+
+        Ok(rows_affected > 0)
+    }
+}
+
+impl PostgresBackend {
+    /// Create a new entry for the nutrients in the database.
+    ///
+    /// # Arguments
+    /// * `connection` - The connection to the database, e.g. a transaction.
+    /// * `nutrients` - The nutrients to create an entry for.
+    async fn create_nutrients_entry(
+        connection: &mut PgConnection,
+        nutrients: &Nutrients,
+    ) -> ProductDBResult<DBId> {
+        debug!("Create new entry for nutrients: {:?}", nutrients);
+
+        let q = sqlx::query_scalar!(
+            "insert into nutrients (
+            kcal,
+            protein_grams,
+            fat_grams,
+            carbohydrates_grams,
+            sugar_grams,
+            salt_grams,
+            vitamin_a_mg,
+            vitamin_c_mg,
+            vitamin_d_mug,
+            iron_mg,
+            calcium_mg,
+            magnesium_mg,
+            sodium_mg,
+            zinc_mg
+        ) values ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14) returning id;",
+            nutrients.kcal,
+            nutrients.protein.map(|w| w.gram()),
+            nutrients.fat.map(|w| w.gram()),
+            nutrients.carbohydrates.map(|w| w.gram()),
+            nutrients.sugar.map(|w| w.gram()),
+            nutrients.salt.map(|w| w.gram()),
+            nutrients.vitamin_a.map(|w| w.milligram()),
+            nutrients.vitamin_c.map(|w| w.milligram()),
+            nutrients.vitamin_d.map(|w| w.microgram()),
+            nutrients.iron.map(|w| w.milligram()),
+            nutrients.calcium.map(|w| w.milligram()),
+            nutrients.magnesium.map(|w| w.milligram()),
+            nutrients.sodium.map(|w| w.milligram()),
+            nutrients.zinc.map(|w| w.milligram())
+        );
+
+        let db_id: DBId = match q.fetch_one(connection).await {
+            Ok(db_id) => db_id,
+            Err(e) => {
+                error!("Failed to create new entry for nutrients: {}", e);
+                return Err(Error::DBError(Box::new(e)));
+            }
+        };
+
+        debug!("Create new entry for nutrients DONE: Id={}", db_id);
+
+        Ok(db_id)
+    }
+
+    /// Replaces the preview image of the product description and deletes the previous one in
+    /// a single statement.
+    ///
+    /// # Arguments
+    /// * `connection` - The connection to the database.
+    /// * `description_id` - The id of the product description.
+    /// * `preview` - The new preview image.
+    async fn replace_preview(
+        connection: &mut PgConnection,
+        description_id: DBId,
+        preview: &ProductImage,
+    ) -> ProductDBResult<()> {
+        debug!(
+            "Replace preview of product description {}: Size={}, content-type={}",
+            description_id,
+            preview.data.len(),
+            preview.content_type
+        );
+
+        // All parts of the statement see the same snapshot, i.e., only the previous preview is
+        // deleted. The description references the new preview once the deletion cascades.
+        let q = sqlx::query!(
+            "with old_description as (
+                select id, preview from product_description where id = $1 for update
+            ), new_preview as (
+                insert into product_image (data, content_type)
+                select $2::bytea, $3::varchar from old_description returning id
+            ), updated as (
+                update product_description set preview = (select id from new_preview)
+                where id in (select id from old_description)
+            )
+            delete from product_image where id in (select preview from old_description);",
+            description_id,
+            preview.data,
+            preview.content_type
+        );
+
+        q.execute(connection).await.map_err(|e| {
+            error!(
+                "Failed to replace preview of product description {}: {}",
+                description_id, e
+            );
+            Error::DBError(Box::new(e))
+        })?;
+
+        Ok(())
+    }
+
+    /// Replaces the full image of the product description and deletes the previous one in a
+    /// single statement, see [`Self::replace_preview`].
+    ///
+    /// # Arguments
+    /// * `connection` - The connection to the database.
+    /// * `description_id` - The id of the product description.
+    /// * `full_image` - The row of the new full image, which may reference the image store.
+    async fn replace_full_image(
+        connection: &mut PgConnection,
+        description_id: DBId,
+        full_image: &ImageRow<'_>,
+    ) -> ProductDBResult<()> {
+        debug!(
+            "Replace full image of product description {}: Size={}, content-type={}",
+            description_id,
+            full_image.data.len(),
+            full_image.content_type
+        );
+
+        let q = sqlx::query!(
+            "with old_description as (
+                select id, photo from product_description where id = $1 for update
+            ), new_photo as (
+                insert into product_image (data, content_type, storage_key, storage_size)
+                select $2::bytea, $3::varchar, $4::varchar, $5::int8 from old_description
+                returning id
+            ), updated as (
+                update product_description set photo = (select id from new_photo)
+                where id in (select id from old_description)
+            )
+            delete from product_image where id in (select photo from old_description);",
+            description_id,
+            full_image.data,
+            full_image.content_type,
+            full_image.storage_key,
+            full_image.storage_size
+        );
+
+        q.execute(connection).await.map_err(|e| {
+            error!(
+                "Failed to replace full image of product description {}: {}",
+                description_id, e
+            );
+            Error::DBError(Box::new(e))
+        })?;
+
+        Ok(())
+    }
+
+    /// Create a new entry for an image of the product in the database.
+    /// If the given image is None, no entry will be created and None will be returned.
+    ///
+    /// # Arguments
+    /// * `connection` - The connection to the database, e.g. a transaction.
+    /// * `image` - The row of the product image to store.
+    async fn create_image_entry(
+        connection: &mut PgConnection,
+        image: Option<&ImageRow<'_>>,
+    ) -> ProductDBResult<Option<DBId>> {
+        // check if an image is available and if not return None
+        let image = if let Some(image) = image {
+            image
+        } else {
+            debug!("No image available for product");
+            return Ok(None);
+        };
+
+        debug!(
+            "Create new entry for image: Size={}, content-type={}",
+            image.data.len(),
+            image.content_type
+        );
+
+        let q = sqlx::query_scalar!(
+            "insert into product_image (data, content_type, storage_key, storage_size)
+            values ($1, $2, $3, $4) returning id;",
+            image.data,
+            image.content_type,
+            image.storage_key,
+            image.storage_size
+        );
+
+        let db_id: DBId = match q.fetch_one(connection).await {
+            Ok(db_id) => db_id,
+            Err(e) => {
+                error!("Failed creating entry for image: {}", e);
+                return Err(Error::DBError(Box::new(e)));
+            }
+        };
+
+        debug!("Create new entry for image DONE: Id={}", db_id);
+
+        Ok(Some(db_id))
+    }
+
+    /// Create a new entry for the description of a product in the database. The nutrients and
+    /// images are inserted by separate statements, i.e., the connection should be a transaction
+    /// to not leave them behind if a later statement fails.
+    ///
+    /// # Arguments
+    /// * `connection` - The connection to the database, e.g. a transaction.
+    /// * `desc` - The product description to store.
+    /// * `full_image` - The row of the full image, which may reference the image store.
+    async fn create_product_description(
+        connection: &mut PgConnection,
+        desc: &ProductDescription,
+        full_image: Option<&ImageRow<'_>>,
+    ) -> ProductDBResult<DBId> {
+        debug!(
+            "Create new product description: id={}, name={}",
+            desc.info.id, desc.info.name,
+        );
+
+        // the statements of a connection are executed one after another
+        let nutrients = match Self::create_nutrients_entry(connection, &desc.nutrients).await {
+            Ok(nutrients) => nutrients,
+            Err(e) => {
+                error!("Failed to create nutrients entry: {}", e);
+                return Err(e);
+            }
+        };
+
+        let preview = desc.preview.as_ref().map(ImageRow::inline);
+        let preview = match Self::create_image_entry(connection, preview.as_ref()).await {
+            Ok(preview) => preview,
+            Err(e) => {
+                error!("Failed to create preview image entry: {}", e);
+                return Err(e);
+            }
+        };
+
+        let full_image = match Self::create_image_entry(connection, full_image).await {
+            Ok(full_image) => full_image,
+            Err(e) => {
+                error!("Failed to create full image entry: {}", e);
+                return Err(e);
+            }
+        };
+
+        // create the product description entry
+        let packaging = desc.packaging.as_ref();
+        let eco_score = desc.eco_score.as_ref();
+        let q = sqlx::query_scalar!(
+            "insert into product_description (
+            product_id,
+            name,
+            producer,
+            quantity_type,
+            portion,
+            volume_weight_ratio,
+            market,
+            packaging_net_quantity,
+            packaging_units,
+            packaging_material,
+            packaging_deposit,
+            vegan,
+            vegetarian,
+            organic,
+            gluten_free,
+            lactose_free,
+            nova_group,
+            eco_score_grade,
+            eco_score_value,
+            eco_score_source,
+            allergens,
+            preview,
+            photo,
+            nutrients
+        ) values (
+            $1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, $19,
+            $20, $21, $22, $23, $24
+        ) returning id;",
+            desc.info.id,
+            desc.info.name,
+            desc.info.producer,
+            desc.info.quantity_type as QuantityType,
+            desc.info.portion,
+            desc.info.volume_weight_ratio,
+            desc.info.market,
+            packaging.and_then(|p| p.net_quantity),
+            packaging.and_then(|p| p.units),
+            packaging.and_then(|p| p.material) as Option<PackagingMaterial>,
+            packaging.map(|p| p.deposit),
+            desc.info.labels.vegan,
+            desc.info.labels.vegetarian,
+            desc.info.labels.organic,
+            desc.info.labels.gluten_free,
+            desc.info.labels.lactose_free,
+            desc.info.nova_group,
+            eco_score.map(|e| e.grade) as Option<EcoScoreGrade>,
+            eco_score.and_then(|e| e.value),
+            eco_score.and_then(|e| e.source.as_deref()),
+            desc.info.allergens.as_slice() as &[Allergen],
+            preview,
+            full_image,
+            nutrients
+        );
+
+        let db_id: DBId = match q.fetch_one(connection).await {
+            Ok(db_id) => db_id,
+            Err(e) => {
+                error!(
+                    "Create new product description: id={}, name={}, FAILED",
+                    desc.info.id, desc.info.name
+                );
+                return Err(Error::DBError(Box::new(e)));
+            }
+        };
+
+        debug!(
+            "Create new product description: id={}, name={}, DB-Id={} DONE",
+            desc.info.id, desc.info.name, db_id
+        );
+
+        Ok(db_id)
+    }
+
+    /// Returns the id of the product description of the product request, if it exists.
+    ///
+    /// # Arguments
+    /// * `id` - The internal id of the requested product.
+    async fn product_request_description_id(&self, id: DBId) -> ProductDBResult<Option<DBId>> {
+        let q = sqlx::query_scalar!(
+            "select product_description_id from requested_products where id = $1;",
+            id
+        );
+
+        let mut connection = self.acquire().await?;
+        q.fetch_optional(&mut *connection).await.map_err(|e| {
+            error!("Failed to get product request {}: {}", id, e);
+            Error::DBError(Box::new(e))
+        })
+    }
+
+    /// Returns the id of the product description of the product, if it exists. The product
+    /// without market is preferred if the products are market-specific.
+    ///
+    /// # Arguments
+    /// * `id` - The public id of the product.
+    async fn product_description_id(&self, id: &ProductID) -> ProductDBResult<Option<DBId>> {
+        let q = sqlx::query_scalar!(
+            "select product_description_id from products where product_id = $1
+            order by market nulls first limit 1;",
+            id
+        );
+
+        let mut connection = self.acquire().await?;
+        q.fetch_optional(&mut *connection).await.map_err(|e| {
+            error!("Failed to get product {}: {}", id, e);
+            Error::DBError(Box::new(e))
+        })
+    }
+
+    /// Returns the additional images of the product description in the order they have been
+    /// added.
+    ///
+    /// # Arguments
+    /// * `description_id` - The id of the product description.
+    async fn get_additional_images(
+        &self,
+        description_id: DBId,
+    ) -> ProductDBResult<Vec<AdditionalImage>> {
+        let q = sqlx::query!(
+            r#"select a.role as "role: ImageRole", i.content_type, i.data, i.storage_key
+            from additional_images a join product_image i on i.id = a.image
+            where a.product_description_id = $1 order by a.id;"#,
+            description_id
+        );
+
+        let mut connection = self.acquire().await?;
+        let rows = q.fetch_all(&mut *connection).await.map_err(|e| {
+            error!(
+                "Failed to get additional images of product description {}: {}",
+                description_id, e
+            );
+            Error::DBError(Box::new(e))
+        })?;
+        drop(connection);
+
+        let mut images = Vec::with_capacity(rows.len());
+        for row in rows {
+            images.push(AdditionalImage {
+                role: row.role,
+                image: self
+                    .load_image(row.content_type, row.data, row.storage_key)
+                    .await?,
+            });
+        }
+
+        Ok(images)
+    }
+
+    /// Appends the additional images to the product description in a single transaction.
+    ///
+    /// # Arguments
+    /// * `description_id` - The id of the product description.
+    /// * `images` - The images to append.
+    async fn add_additional_images(
+        &self,
+        description_id: DBId,
+        images: &[AdditionalImage],
+    ) -> ProductDBResult<()> {
+        let mut uploads = self.uploads();
+        let mut rows = Vec::with_capacity(images.len());
+        for image in images {
+            rows.push((
+                image.role,
+                self.upload_image(&image.image, &mut uploads).await?,
+            ));
+        }
+
+        let mut connection = self.acquire().await?;
+        let mut transaction = connection.begin().await.map_err(|e| {
+            error!("Failed to start transaction for additional images: {}", e);
+            Error::DBError(Box::new(e))
+        })?;
+
+        for (role, image) in rows {
+            sqlx::query!(
+                "with new_image as (
+                    insert into product_image (data, content_type, storage_key, storage_size)
+                    values ($2, $3, $5, $6) returning id
+                )
+                insert into additional_images (product_description_id, role, image)
+                select $1, $4, id from new_image;",
+                description_id,
+                image.data,
+                image.content_type,
+                role as ImageRole,
+                image.storage_key,
+                image.storage_size
+            )
+            .execute(&mut *transaction)
+            .await
+            .map_err(|e| {
+                error!(
+                    "Failed to add additional image to product description {}: {}",
+                    description_id, e
+                );
+                Error::DBError(Box::new(e))
+            })?;
+        }
+
+        transaction.commit().await.map_err(|e| {
+            error!("Failed to commit the additional images: {}", e);
+            Error::DBError(Box::new(e))
+        })?;
+        uploads.commit();
+
+        Ok(())
+    }
+
+    /// Returns the statement inserting the nutrients, the images, the description and the
+    /// product in a single round trip. If the product already exists, the whole statement fails
+    /// with a unique violation and nothing is left behind.
+    ///
+    /// # Arguments
+    /// * `product_desc` - The product to insert.
+    /// * `full_image` - The row of the full image, which may reference the image store.
+    fn insert_product_query<'a>(
+        product_desc: &'a ProductDescription,
+        full_image: Option<&'a ImageRow<'a>>,
+    ) -> Query<'a, Postgres, PgArguments> {
+        let info = &product_desc.info;
+        let nutrients = &product_desc.nutrients;
+        let preview = product_desc.preview.as_ref();
+        let packaging = product_desc.packaging.as_ref();
+        let eco_score = product_desc.eco_score.as_ref();
+
+        sqlx::query!(
+            "with new_nutrients as (
+                insert into nutrients (
+                    kcal,
+                    protein_grams,
+                    fat_grams,
+                    carbohydrates_grams,
+                    sugar_grams,
+                    salt_grams,
+                    vitamin_a_mg,
+                    vitamin_c_mg,
+                    vitamin_d_mug,
+                    iron_mg,
+                    calcium_mg,
+                    magnesium_mg,
+                    sodium_mg,
+                    zinc_mg
+                ) values ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14) returning id
+            ), new_preview as (
+                insert into product_image (data, content_type)
+                select $15::bytea, $16::varchar where $15::bytea is not null returning id
+            ), new_photo as (
+                insert into product_image (data, content_type, storage_key, storage_size)
+                select $17::bytea, $18::varchar, $40::varchar, $41::int8
+                where $17::bytea is not null returning id
+            ), new_description as (
+                insert into product_description (
+                    product_id,
+                    name,
+                    producer,
+                    quantity_type,
+                    portion,
+                    volume_weight_ratio,
+                    market,
+                    packaging_net_quantity,
+                    packaging_units,
+                    packaging_material,
+                    packaging_deposit,
+                    vegan,
+                    vegetarian,
+                    organic,
+                    gluten_free,
+                    lactose_free,
+                    nova_group,
+                    eco_score_grade,
+                    eco_score_value,
+                    eco_score_source,
+                    allergens,
+                    preview,
+                    photo,
+                    nutrients
+                ) values (
+                    $19,
+                    $20,
+                    $21,
+                    $22,
+                    $23,
+                    $24,
+                    $25,
+                    $26,
+                    $27,
+                    $28,
+                    $29,
+                    $30,
+                    $31,
+                    $32,
+                    $33,
+                    $34,
+                    $35,
+                    $36,
+                    $37,
+                    $38,
+                    $39,
+                    (select id from new_preview),
+                    (select id from new_photo),
+                    (select id from new_nutrients)
+                ) returning id
+            )
+            insert into products (product_description_id, product_id, market)
+            select id, $19, $25 from new_description;",
+            nutrients.kcal,
+            nutrients.protein.map(|w| w.gram()),
+            nutrients.fat.map(|w| w.gram()),
+            nutrients.carbohydrates.map(|w| w.gram()),
+            nutrients.sugar.map(|w| w.gram()),
+            nutrients.salt.map(|w| w.gram()),
+            nutrients.vitamin_a.map(|w| w.milligram()),
+            nutrients.vitamin_c.map(|w| w.milligram()),
+            nutrients.vitamin_d.map(|w| w.microgram()),
+            nutrients.iron.map(|w| w.milligram()),
+            nutrients.calcium.map(|w| w.milligram()),
+            nutrients.magnesium.map(|w| w.milligram()),
+            nutrients.sodium.map(|w| w.milligram()),
+            nutrients.zinc.map(|w| w.milligram()),
+            preview.map(|image| image.data.as_slice()),
+            preview.map(|image| image.content_type.as_str()),
+            full_image.map(|image| image.data),
+            full_image.map(|image| image.content_type),
+            info.id,
+            info.name,
+            info.producer,
+            info.quantity_type as QuantityType,
+            info.portion,
+            info.volume_weight_ratio,
+            info.market,
+            packaging.and_then(|p| p.net_quantity),
+            packaging.and_then(|p| p.units),
+            packaging.and_then(|p| p.material) as Option<PackagingMaterial>,
+            packaging.map(|p| p.deposit),
+            info.labels.vegan,
+            info.labels.vegetarian,
+            info.labels.organic,
+            info.labels.gluten_free,
+            info.labels.lactose_free,
+            info.nova_group,
+            eco_score.map(|e| e.grade) as Option<EcoScoreGrade>,
+            eco_score.and_then(|e| e.value),
+            eco_score.and_then(|e| e.source.as_deref()),
+            info.allergens.as_slice() as &[Allergen],
+            full_image.and_then(|image| image.storage_key.as_deref()),
+            full_image.and_then(|image| image.storage_size)
+        )
+    }
+
+    /// Returns the query for updating the product description and its nutrients with the given
+    /// description. The images are not updated. The name and producer in trigram format are
+    /// recomputed by the trigger.
+    ///
+    /// # Arguments
+    /// * `description_id` - The id of the product description to update.
+    /// * `product_desc` - The new description of the product.
+    fn update_description_query(
+        description_id: DBId,
+        product_desc: &ProductDescription,
+    ) -> Query<'_, Postgres, PgArguments> {
+        let info = &product_desc.info;
+        let nutrients = &product_desc.nutrients;
+        let packaging = product_desc.packaging.as_ref();
+        let eco_score = product_desc.eco_score.as_ref();
+
+        sqlx::query!(
+            "with updated_nutrients as (
+                update nutrients set
+                    kcal = $2,
+                    protein_grams = $3,
+                    fat_grams = $4,
+                    carbohydrates_grams = $5,
+                    sugar_grams = $6,
+                    salt_grams = $7,
+                    vitamin_a_mg = $8,
+                    vitamin_c_mg = $9,
+                    vitamin_d_mug = $10,
+                    iron_mg = $11,
+                    calcium_mg = $12,
+                    magnesium_mg = $13,
+                    sodium_mg = $14,
+                    zinc_mg = $15
+                where id = (select nutrients from product_description where id = $1)
+            )
+            update product_description set
+                name = $16,
+                producer = $17,
+                name_producer = null,
+                quantity_type = $18,
+                portion = $19,
+                volume_weight_ratio = $20,
+                market = $21,
+                packaging_net_quantity = $22,
+                packaging_units = $23,
+                packaging_material = $24,
+                packaging_deposit = $25,
+                vegan = $26,
+                vegetarian = $27,
+                organic = $28,
+                gluten_free = $29,
+                lactose_free = $30,
+                nova_group = $31,
+                eco_score_grade = $32,
+                eco_score_value = $33,
+                eco_score_source = $34,
+                allergens = $35
+            where id = $1;",
+            description_id,
+            nutrients.kcal,
+            nutrients.protein.map(|w| w.gram()),
+            nutrients.fat.map(|w| w.gram()),
+            nutrients.carbohydrates.map(|w| w.gram()),
+            nutrients.sugar.map(|w| w.gram()),
+            nutrients.salt.map(|w| w.gram()),
+            nutrients.vitamin_a.map(|w| w.milligram()),
+            nutrients.vitamin_c.map(|w| w.milligram()),
+            nutrients.vitamin_d.map(|w| w.microgram()),
+            nutrients.iron.map(|w| w.milligram()),
+            nutrients.calcium.map(|w| w.milligram()),
+            nutrients.magnesium.map(|w| w.milligram()),
+            nutrients.sodium.map(|w| w.milligram()),
+            nutrients.zinc.map(|w| w.milligram()),
+            info.name,
+            info.producer,
+            info.quantity_type as QuantityType,
+            info.portion,
+            info.volume_weight_ratio,
+            info.market,
+            packaging.and_then(|p| p.net_quantity),
+            packaging.and_then(|p| p.units),
+            packaging.and_then(|p| p.material) as Option<PackagingMaterial>,
+            packaging.map(|p| p.deposit),
+            info.labels.vegan,
+            info.labels.vegetarian,
+            info.labels.organic,
+            info.labels.gluten_free,
+            info.labels.lactose_free,
+            info.nova_group,
+            eco_score.map(|e| e.grade) as Option<EcoScoreGrade>,
+            eco_score.and_then(|e| e.value),
+            eco_score.and_then(|e| e.source.as_deref()),
+            info.allergens.as_slice() as &[Allergen]
+        )
+    }
+
+    /// Add the fields of the product to the query.
+    ///
+    /// # Arguments
+    /// * `q` - The query builder to add the fields to.
+    /// * `with_preview` - Whether to include the preview image of the product in the response.
+    fn init_get_product_query<DB: Database>(q: &mut QueryBuilder<'_, DB>, with_preview: bool) {
+        Self::push_product_columns(q, with_preview);
+        Self::push_product_source(q, with_preview);
+    }
+
+    /// Adds the select clause with the fields of the product to the query.
+    ///
+    /// # Arguments
+    /// * `q` - The query builder to add the fields to.
+    /// * `with_preview` - Whether to include the preview image of the product in the response.
+    fn push_product_columns<DB: Database>(q: &mut QueryBuilder<'_, DB>, with_preview: bool) {
+        q.push(
+            "select product_id, name, producer, quantity_type, portion, volume_weight_ratio, market,
+        kcal, protein_grams, fat_grams, carbohydrates_grams,
+        sugar_grams, salt_grams,
+        vitamin_a_mg, vitamin_c_mg, vitamin_d_mug,
+        iron_mg, calcium_mg, magnesium_mg, sodium_mg, zinc_mg,
+        packaging_net_quantity, packaging_units, packaging_material, packaging_deposit,
+        vegan, vegetarian, organic, gluten_free, lactose_free, nova_group,
+        eco_score_grade, eco_score_value, eco_score_source, allergens,",
+        );
+
+        if with_preview {
+            q.push("preview, preview_content_type");
+        } else {
+            q.push("null as preview, null as preview_content_type");
+        }
+    }
+
+    /// Adds the from clause with the view of the products to the query.
+    ///
+    /// # Arguments
+    /// * `q` - The query builder to add the view to.
+    /// * `with_preview` - Whether the preview image of the product is included in the response.
+    fn push_product_source<DB: Database>(q: &mut QueryBuilder<'_, DB>, with_preview: bool) {
+        if with_preview {
+            q.push(" from products_full_with_preview");
+        } else {
+            q.push(" from products_full");
+        }
+    }
+
+    /// Initializes the query builder with a simple query to get the product request.
+    ///
+    /// # Arguments
+    /// * `q` - The query builder to initialize.
+    /// * `with_preview` - Whether to include the preview image of the product in the response.
+    /// * `with_db_id` - Whether to include the database id in the response.
+    fn init_get_product_request_query<DB: Database>(
+        q: &mut QueryBuilder<'_, DB>,
+        with_preview: bool,
+        with_db_id: bool,
+    ) {
+        Self::push_product_request_columns(q, with_preview, with_db_id);
+        Self::push_product_request_source(q, with_preview);
+    }
+
+    /// Adds the select clause with the columns of the product requests to the query.
+    ///
+    /// # Arguments
+    /// * `q` - The query builder to add the columns to.
+    /// * `with_preview` - Whether to include the preview image of the product in the response.
+    /// * `with_db_id` - Whether to include the database id in the response.
+    fn push_product_request_columns<DB: Database>(
+        q: &mut QueryBuilder<'_, DB>,
+        with_preview: bool,
+        with_db_id: bool,
+    ) {
+        q.push(
+            "select
+        product_id, date, name, producer, quantity_type, portion, volume_weight_ratio, market,
+        kcal, protein_grams, fat_grams, carbohydrates_grams,
+        sugar_grams, salt_grams,
+        vitamin_a_mg, vitamin_c_mg, vitamin_d_mug,
+        iron_mg, calcium_mg, magnesium_mg, sodium_mg, zinc_mg,
+        packaging_net_quantity, packaging_units, packaging_material, packaging_deposit,
+        vegan, vegetarian, organic, gluten_free, lactose_free, nova_group,
+        eco_score_grade, eco_score_value, eco_score_source, allergens,
+        status, review_comment,",
+        );
+
+        if with_db_id {
+            q.push("r_id,");
+        }
+
+        if with_preview {
+            q.push("preview, preview_content_type");
+        } else {
+            q.push("null as preview, null as preview_content_type");
+        }
+    }
+
+    /// Adds the from clause with the view of the product requests to the query.
+    ///
+    /// # Arguments
+    /// * `q` - The query builder to add the view to.
+    /// * `with_preview` - Whether the preview image of the product is included in the response.
+    fn push_product_request_source<DB: Database>(q: &mut QueryBuilder<'_, DB>, with_preview: bool) {
+        if with_preview {
+            q.push(" from requested_products_full_with_preview");
+        } else {
+            q.push(" from requested_products_full");
+        }
+    }
+
+    /// Adds the condition for the products that have last been seen at the given store.
+    ///
+    /// # Arguments
+    /// * `q` - The query builder to add the condition to.
+    /// * `store_id` - The id of the store.
+    fn push_store_filter(q: &mut QueryBuilder<'_, Postgres>, store_id: DBId) {
+        q.push(
+            " and product_id in (select product_id from product_availability
+            where available and store_id = ",
+        );
+        q.push_bind(store_id);
+        q.push(")");
+    }
+
+    /// Adds the condition for the products of the given market, if any.
+    ///
+    /// # Arguments
+    /// * `q` - The query builder to add the condition to.
+    /// * `market` - The market to filter the products for.
+    fn push_market_filter<'q>(q: &mut QueryBuilder<'q, Postgres>, market: Option<&'q String>) {
+        if let Some(market) = market {
+            q.push(" and market = ");
+            q.push_bind(market);
+        }
+    }
+
+    /// Adds the condition for the products of the given producer, which is matched
+    /// case-insensitively against the whole producer, where a `*` matches any characters.
+    ///
+    /// # Arguments
+    /// * `q` - The query builder to add the condition to.
+    /// * `producer` - The pattern of the producer to filter the products for.
+    fn push_producer_filter(q: &mut QueryBuilder<'_, Postgres>, producer: Option<&str>) {
+        if let Some(producer) = producer {
+            q.push(" and producer ilike ");
+            q.push_bind(producer_like_pattern(producer));
+            q.push(" escape '\\'");
+        }
+    }
+
+    /// Adds the condition for the products with the given quantity type.
+    ///
+    /// # Arguments
+    /// * `q` - The query builder to add the condition to.
+    /// * `quantity_type` - The quantity type to filter the products for.
+    fn push_quantity_type_filter(
+        q: &mut QueryBuilder<'_, Postgres>,
+        quantity_type: Option<QuantityType>,
+    ) {
+        if let Some(quantity_type) = quantity_type {
+            q.push(" and quantity_type = ");
+            q.push_bind(quantity_type);
+        }
+    }
+
+    /// Adds the conditions for the products with the given dietary labels. Only the labels that
+    /// are set are checked.
+    ///
+    /// # Arguments
+    /// * `q` - The query builder to add the conditions to.
+    /// * `labels` - The dietary labels to filter the products for.
+    fn push_labels_filter(q: &mut QueryBuilder<'_, Postgres>, labels: Option<&DietaryLabels>) {
+        let Some(labels) = labels else {
+            return;
+        };
+
+        let columns = [
+            ("vegan", labels.vegan),
+            ("vegetarian", labels.vegetarian),
+            ("organic", labels.organic),
+            ("gluten_free", labels.gluten_free),
+            ("lactose_free", labels.lactose_free),
+        ];
+        for (column, value) in columns {
+            if let Some(value) = value {
+                q.push(format!(" and {} = ", column));
+                q.push_bind(value);
+            }
+        }
+    }
+
+    /// Adds the condition that the missing products were reported for the given product id.
+    ///
+    /// # Arguments
+    /// * `q` - The query builder to add the condition to.
+    /// * `product_id` - The product id to filter the missing products for, if any.
+    fn push_missing_product_filter<'q>(
+        q: &mut QueryBuilder<'q, Postgres>,
+        product_id: Option<&'q ProductID>,
+    ) {
+        if let Some(product_id) = product_id {
+            q.push("where product_id = ");
+            q.push_bind(product_id);
+        }
+    }
+
+    /// Adds the condition that the products contain none of the given allergens. Products
+    /// without declared allergens are included.
+    ///
+    /// # Arguments
+    /// * `q` - The query builder to add the condition to.
+    /// * `exclude_allergens` - The allergens the products must not contain.
+    fn push_allergens_filter(q: &mut QueryBuilder<'_, Postgres>, exclude_allergens: &[Allergen]) {
+        if exclude_allergens.is_empty() {
+            return;
+        }
+
+        q.push(" and not (allergens && ");
+        q.push_bind(exclude_allergens.to_vec());
+        q.push(")");
+    }
+
+    /// Adds the condition that the products have at least one of the given data quality issues.
+    ///
+    /// # Arguments
+    /// * `q` - The query builder to add the condition to.
+    /// * `data_issues` - The data quality issues to filter the products for.
+    fn push_data_issues_filter(
+        q: &mut QueryBuilder<'_, Postgres>,
+        data_issues: &[ProductDataIssue],
+    ) {
+        if data_issues.is_empty() {
+            return;
+        }
+
+        let conditions: Vec<&str> = data_issues
+            .iter()
+            .map(|issue| match issue {
+                ProductDataIssue::MissingImage => "(preview is null and photo is null)",
+                ProductDataIssue::MissingMacroNutrients => {
+                    "(protein_grams is null or fat_grams is null or carbohydrates_grams is null)"
+                }
+                ProductDataIssue::MissingProducer => "coalesce(trim(producer), '') = ''",
+            })
+            .collect();
+
+        q.push(format!(" and ({})", conditions.join(" or ")));
+    }
+
+    /// Queries the product requests and, if requested, the total number of product requests
+    /// matching the filters of the query in the same statement, i.e., the total is consistent
+    /// with the page. The total is None if the page is empty.
+    ///
+    /// # Arguments
+    /// * `query` - The query parameters for the product requests.
+    /// * `with_preview` - Whether to include the preview image of the product in the response.
+    /// * `with_count` - Whether to return the total number of matching product requests.
+    async fn fetch_product_requests(
         &self,
         query: &ProductQuery,
         with_preview: bool,
-    ) -> ProductDBResult<Vec<(DBId, ProductRequest)>> {
-        debug!("Query product requests: {:?}", query);
-
+        with_count: bool,
+    ) -> ProductDBResult<(Vec<(DBId, ProductRequest)>, Option<u64>)> {
         // start building the sql query
         let mut query_builder = QueryBuilder::default();
-        Self::init_get_product_request_query(&mut query_builder, with_preview, true);
+        Self::push_product_request_columns(&mut query_builder, with_preview, true);
+        if with_count {
+            query_builder.push(", count(*) over () as total_count");
+        }
+        Self::push_product_request_source(&mut query_builder, with_preview);
 
         // add the where clause
-        match &query.filter {
-            SearchFilter::NoFilter => {}
-            SearchFilter::ProductID(product_id) => {
-                query_builder.push(" where product_id = ");
-                query_builder.push_bind(product_id);
-            }
-            SearchFilter::Search(s) => {
-                query_builder.push(" where name_producer like ");
-                query_builder.push_bind(format!("%{}%", s.to_lowercase()));
-            }
-        }
+        self.push_product_request_filters(&mut query_builder, query);
 
         // add the order by clause
         if let Some(sorting) = query.sorting.as_ref() {
@@ -459,9 +4423,7 @@ impl DataBackend for PostgresBackend {
             match sorting.field {
                 SortingField::Similarity => {
                     if let SearchFilter::Search(search_string) = &query.filter {
-                        query_builder.push("similarity(name_producer, ");
-                        query_builder.push_bind(search_string);
-                        query_builder.push(") ");
+                        self.push_similarity(&mut query_builder, search_string);
                     } else {
                         return Err(Error::InvalidSortingError(sorting.field));
                     }
@@ -476,6 +4438,10 @@ impl DataBackend for PostgresBackend {
 
             query_builder.push(" ");
             query_builder.push(sorting.order.to_string());
+
+            // the id breaks ties, i.e., the pages are disjoint even if the sorted values are equal
+            query_builder.push(", r_id ");
+            query_builder.push(sorting.order.to_string());
         }
 
         // add the limit and offset to the query
@@ -483,40 +4449,76 @@ impl DataBackend for PostgresBackend {
 
         let query = query_builder.build_query_as::<SQLRequestedProductWithId>();
 
-        let mut rows = query.fetch(&self.pool);
+        let mut connection = self.acquire().await?;
+
+        let mut rows = query.fetch(&mut *connection);
         let mut result: Vec<(DBId, ProductRequest)> = Vec::new();
+        let mut total_count = None;
         while let Some(row) = rows
             .try_next()
             .await
             .map_err(|e| Error::DBError(Box::new(e)))?
         {
+            total_count = row.desc.total_count.map(|count| count as u64);
             let db_id = row.id;
             let product_request: ProductRequest = row.into();
             result.push((db_id, product_request));
         }
 
-        Ok(result)
+        Ok((result, total_count))
     }
 
-    async fn query_products(
+    /// Queries the products and, if requested, their similarity to the search string of the
+    /// query, which must be defined in this case, and the total number of products matching the
+    /// filters of the query in the same statement, i.e., the total is consistent with the page.
+    /// The total is None if the page is empty.
+    ///
+    /// # Arguments
+    /// * `query` - The query parameters for the products.
+    /// * `with_preview` - Whether to include the preview image of the product in the response.
+    /// * `with_score` - Whether to return the similarity of the products to the search string.
+    /// * `with_count` - Whether to return the total number of matching products.
+    async fn fetch_products(
         &self,
         query: &ProductQuery,
         with_preview: bool,
-    ) -> ProductDBResult<Vec<ProductDescription>> {
-        debug!("Query products: {:?}", query);
-
-        // start building the sql query
-        let mut query_builder = QueryBuilder::default();
-        Self::init_get_product_query(&mut query_builder, with_preview);
-
+        with_score: bool,
+        with_count: bool,
+    ) -> ProductDBResult<(Vec<(ProductDescription, Option<f32>)>, Option<u64>)> {
         // create lower case search string
         let search_string = query.filter.search_string();
         let search_string = search_string.map(|s| s.to_lowercase());
 
+        // start building the sql query
+        let mut query_builder = QueryBuilder::default();
+        Self::push_product_columns(&mut query_builder, with_preview);
+        if with_score {
+            let search_string = search_string
+                .as_ref()
+                .ok_or(Error::InvalidSortingError(SortingField::Similarity))?;
+            query_builder.push(", ");
+            self.push_similarity(&mut query_builder, search_string);
+            query_builder.push("as score");
+        }
+        if with_count && query.cursor.is_some() {
+            // the window would only count the products after the cursor
+            query_builder.push(", (select count(*) from products_full");
+            self.push_product_filters(&mut query_builder, query, search_string.as_deref());
+            query_builder.push(") as total_count");
+        } else if with_count {
+            query_builder.push(", count(*) over () as total_count");
+        }
+        Self::push_product_source(&mut query_builder, with_preview);
+
         // add the where clause
-        if let Some(search_string) = search_string.as_ref() {
-            query_builder.push(" where name_producer like ");
-            query_builder.push_bind(format!("%{}%", search_string));
+        self.push_product_filters(&mut query_builder, query, search_string.as_deref());
+
+        // the products after the cursor replace the offset
+        let mut offset = query.offset;
+        if let Some(cursor) = query.cursor.as_deref() {
+            let cursor = ProductCursor::decode(cursor, query.sorting.as_ref())?;
+            self.push_cursor_filter(&mut query_builder, &cursor, search_string.as_deref())?;
+            offset = 0;
         }
 
         // add the order by clause
@@ -527,9 +4529,7 @@ impl DataBackend for PostgresBackend {
             match sorting.field {
                 SortingField::Similarity => {
                     if let Some(search_string) = search_string.as_ref() {
-                        query_builder.push("similarity(name_producer, ");
-                        query_builder.push_bind(search_string.to_lowercase());
-                        query_builder.push(") ");
+                        self.push_similarity(&mut query_builder, search_string);
                     } else {
                         return Err(Error::InvalidSortingError(sorting.field));
                     }
@@ -544,259 +4544,355 @@ impl DataBackend for PostgresBackend {
 
             query_builder.push(" ");
             query_builder.push(sorting.order.to_string());
+
+            // the product id and market break ties, i.e., the pages are disjoint even if many
+            // products share the sorted value, e.g. the same name
+            query_builder.push(", product_id ");
+            query_builder.push(sorting.order.to_string());
+            query_builder.push(", market ");
+            query_builder.push(sorting.order.to_string());
         }
 
         // add the limit and offset to the query
-        Self::add_offset_and_limit(&mut query_builder, query.offset, query.limit);
+        Self::add_offset_and_limit(&mut query_builder, offset, query.limit);
 
         let query = query_builder.build_query_as::<SQLProductDescription>();
 
-        let mut rows = query.fetch(&self.pool);
+        let mut connection = self.acquire().await?;
+
+        let mut rows = query.fetch(&mut *connection);
         let mut products = Vec::new();
+        let mut total_count = None;
         while let Some(row) = rows
             .try_next()
             .await
             .map_err(|e| Error::DBError(Box::new(e)))?
         {
+            let score = row.score;
+            total_count = row.total_count.map(|count| count as u64);
             let product: ProductDescription = row.into();
-            products.push(product);
+            products.push((product, score));
         }
 
-        Ok(products)
+        Ok((products, total_count))
     }
-}
 
-impl PostgresBackend {
-    /// Create a new entry for the nutrients in the database.
+    /// Adds the condition for the products after the cursor in the order of its sorting, i.e.,
+    /// the keyset pagination. The sorted value, the product id and the market are compared
+    /// one after the other, where null values come last in ascending and first in descending
+    /// order like in the order by clause.
     ///
     /// # Arguments
-    /// * `nutrients` - The nutrients to create an entry for.
-    async fn create_nutrients_entry(&self, nutrients: &Nutrients) -> ProductDBResult<DBId> {
-        debug!("Create new entry for nutrients: {:?}", nutrients);
-
-        let q = sqlx::query(
-            "insert into nutrients (
-            kcal,
-            protein_grams,
-            fat_grams,
-            carbohydrates_grams,
-            sugar_grams,
-            salt_grams,
-            vitamin_a_mg,
-            vitamin_c_mg,
-            vitamin_d_mug,
-            iron_mg,
-            calcium_mg,
-            magnesium_mg,
-            sodium_mg,
-            zinc_mg
-        ) values ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14) returning id;",
-        )
-        .bind(nutrients.kcal)
-        .bind(nutrients.protein.map(|w| w.gram()))
-        .bind(nutrients.fat.map(|w| w.gram()))
-        .bind(nutrients.carbohydrates.map(|w| w.gram()))
-        .bind(nutrients.sugar.map(|w| w.gram()))
-        .bind(nutrients.salt.map(|w| w.gram()))
-        .bind(nutrients.vitamin_a.map(|w| w.milligram()))
-        .bind(nutrients.vitamin_c.map(|w| w.milligram()))
-        .bind(nutrients.vitamin_d.map(|w| w.microgram()))
-        .bind(nutrients.iron.map(|w| w.milligram()))
-        .bind(nutrients.calcium.map(|w| w.milligram()))
-        .bind(nutrients.magnesium.map(|w| w.milligram()))
-        .bind(nutrients.sodium.map(|w| w.milligram()))
-        .bind(nutrients.zinc.map(|w| w.milligram()));
-
-        let row = match self.pool.fetch_one(q).await {
-            Ok(row) => row,
-            Err(e) => {
-                error!("Failed to create new entry for nutrients: {}", e);
-                return Err(Error::DBError(Box::new(e)));
+    /// * `q` - The query builder to add the condition to.
+    /// * `cursor` - The cursor of the last product of the previous page.
+    /// * `search_string` - The lower case search string of the query, if any.
+    fn push_cursor_filter(
+        &self,
+        q: &mut QueryBuilder<'_, Postgres>,
+        cursor: &ProductCursor,
+        search_string: Option<&str>,
+    ) -> ProductDBResult<()> {
+        let sorted_key = match cursor.sorting.field {
+            SortingField::Similarity => {
+                let search_string =
+                    search_string.ok_or(Error::InvalidSortingError(SortingField::Similarity))?;
+                CursorKey::Similarity(search_string.to_string())
             }
+            SortingField::ReportedDate => {
+                return Err(Error::InvalidSortingError(cursor.sorting.field));
+            }
+            field => CursorKey::Column(field.to_string()),
         };
+        let keys = [
+            (sorted_key, cursor.value.clone()),
+            (
+                CursorKey::Column("product_id".to_string()),
+                Some(CursorValue::Text(cursor.product_id.clone())),
+            ),
+            (
+                CursorKey::Column("market".to_string()),
+                cursor.market.clone().map(CursorValue::Text),
+            ),
+        ];
 
-        let db_id: DBId = row.get(0);
-        debug!("Create new entry for nutrients DONE: Id={}", db_id);
+        // after the cursor means after its first key or equal to it and after the remaining keys
+        q.push(" and ");
+        for (i, (key, value)) in keys.iter().enumerate() {
+            q.push("(");
+            self.push_after_cursor_key(q, key, value.as_ref(), cursor.sorting.order);
+            if i + 1 < keys.len() {
+                q.push(" or (");
+                self.push_cursor_key(q, key);
+                match value {
+                    Some(value) => {
+                        q.push(" = ");
+                        Self::push_cursor_value(q, value);
+                    }
+                    None => {
+                        q.push(" is null");
+                    }
+                }
+                q.push(" and ");
+            }
+        }
+        for i in 0..keys.len() {
+            q.push(if i + 1 < keys.len() { "))" } else { ")" });
+        }
 
-        Ok(db_id)
+        Ok(())
     }
 
-    /// Create a new entry for an image of the product in the database.
-    /// If the given image is None, no entry will be created and None will be returned.
+    /// Adds the condition for the values of the key after the value of the cursor.
     ///
     /// # Arguments
-    /// * `image` - The product image to store.
-    async fn create_image_entry(
+    /// * `q` - The query builder to add the condition to.
+    /// * `key` - The compared key.
+    /// * `value` - The value of the key of the cursor, `None` for null.
+    /// * `order` - The order of the sorting.
+    fn push_after_cursor_key(
         &self,
-        image: &Option<ProductImage>,
-    ) -> ProductDBResult<Option<DBId>> {
-        // check if an image is available and if not return None
-        let image = if let Some(image) = image {
-            image
-        } else {
-            debug!("No image available for product");
-            return Ok(None);
-        };
-
-        debug!(
-            "Create new entry for image: Size={}, content-type={}",
-            image.data.len(),
-            image.content_type
-        );
-
-        let q = sqlx::query(
-            "insert into product_image (data, content_type) values ($1, $2) returning id;",
-        )
-        .bind(&image.data)
-        .bind(&image.content_type);
-
-        let row = match self.pool.fetch_one(q).await {
-            Ok(row) => row,
-            Err(e) => {
-                error!("Failed creating entry for image: {}", e);
-                return Err(Error::DBError(Box::new(e)));
+        q: &mut QueryBuilder<'_, Postgres>,
+        key: &CursorKey,
+        value: Option<&CursorValue>,
+        order: SortingOrder,
+    ) {
+        match (order, value) {
+            // null values come last in ascending order
+            (SortingOrder::Ascending, None) => {
+                q.push("false");
             }
-        };
-
-        let db_id: DBId = row.get(0);
-        debug!("Create new entry for image DONE: Id={}", db_id);
-
-        Ok(Some(db_id))
+            (SortingOrder::Ascending, Some(value)) => {
+                self.push_cursor_key(q, key);
+                q.push(" > ");
+                Self::push_cursor_value(q, value);
+                q.push(" or ");
+                self.push_cursor_key(q, key);
+                q.push(" is null");
+            }
+            // null values come first in descending order
+            (SortingOrder::Descending, None) => {
+                self.push_cursor_key(q, key);
+                q.push(" is not null");
+            }
+            (SortingOrder::Descending, Some(value)) => {
+                self.push_cursor_key(q, key);
+                q.push(" < ");
+                Self::push_cursor_value(q, value);
+            }
+        }
     }
 
-    /// Create a new entry for the description of a product in the database.
+    /// Adds the expression of the given key of a cursor.
     ///
     /// # Arguments
-    /// * `desc` - The product description to store.
-    async fn create_product_description(&self, desc: &ProductDescription) -> ProductDBResult<DBId> {
-        debug!(
-            "Create new product description: id={}, name={}",
-            desc.info.id, desc.info.name,
-        );
-
-        let nutrients = self.create_nutrients_entry(&desc.nutrients);
-        let preview = self.create_image_entry(&desc.preview);
-        let full_image = self.create_image_entry(&desc.full_image);
-
-        // waiting for the elements nutrients, preview, and full_image to be created
-        let nutrients = match nutrients.await {
-            Ok(nutrients) => nutrients,
-            Err(e) => {
-                error!("Failed to create nutrients entry: {}", e);
-                return Err(e);
+    /// * `q` - The query builder to add the expression to.
+    /// * `key` - The key of the cursor.
+    fn push_cursor_key(&self, q: &mut QueryBuilder<'_, Postgres>, key: &CursorKey) {
+        match key {
+            CursorKey::Column(column) => {
+                q.push(column);
             }
-        };
+            CursorKey::Similarity(search_string) => self.push_similarity(q, search_string),
+        }
+    }
 
-        let preview = match preview.await {
-            Ok(preview) => preview,
-            Err(e) => {
-                error!("Failed to create preview image entry: {}", e);
-                return Err(e);
-            }
+    /// Binds the given value of a cursor.
+    ///
+    /// # Arguments
+    /// * `q` - The query builder to bind the value to.
+    /// * `value` - The value of the cursor.
+    fn push_cursor_value(q: &mut QueryBuilder<'_, Postgres>, value: &CursorValue) {
+        match value {
+            CursorValue::Integer(value) => q.push_bind(*value),
+            CursorValue::Number(value) => q.push_bind(*value),
+            CursorValue::Text(value) => q.push_bind(value.clone()),
         };
+    }
 
-        let full_image = match full_image.await {
-            Ok(full_image) => full_image,
-            Err(e) => {
-                error!("Failed to create full image entry: {}", e);
-                return Err(e);
-            }
+    /// Adds the filter for the products whose name and producer match the terms of the search
+    /// string according to the configured search term mode. The terms are matched by the
+    /// full-text search as word prefixes in any order, or, e.g. for typos, by the trigram
+    /// word similarity. The accents are ignored by the trigram fallback if the
+    /// accent-insensitive search is enabled, see [`umlaut_variants`].
+    ///
+    /// # Arguments
+    /// * `q` - The query builder to add the filter to.
+    /// * `search_string` - The lower case search string.
+    fn push_search_filter(&self, q: &mut QueryBuilder<'_, Postgres>, search_string: &str) {
+        let separator = match self.config.search_terms {
+            SearchTermMode::Any => " or ",
+            SearchTermMode::Phrase | SearchTermMode::All => " and ",
         };
 
-        // create the product description entry
-        let q = sqlx::query(
-            "insert into product_description (
-            product_id,
-            name,
-            producer,
-            quantity_type,
-            portion,
-            volume_weight_ratio,
-            preview,
-            photo,
-            nutrients
-        ) values ($1, $2, $3, $4, $5, $6, $7, $8, $9) returning id;",
-        )
-        .bind(&desc.info.id)
-        .bind(&desc.info.name)
-        .bind(&desc.info.producer)
-        .bind(desc.info.quantity_type)
-        .bind(desc.info.portion)
-        .bind(desc.info.volume_weight_ratio)
-        .bind(preview)
-        .bind(full_image)
-        .bind(nutrients);
-
-        let row = match self.pool.fetch_one(q).await {
-            Ok(row) => row,
-            Err(e) => {
-                error!(
-                    "Create new product description: id={}, name={}, FAILED",
-                    desc.info.id, desc.info.name
-                );
-                return Err(Error::DBError(Box::new(e)));
+        q.push(" and (");
+        self.push_text_search_match(q, search_string);
+        q.push(" or (");
+        for (i, term) in self
+            .config
+            .search_terms
+            .terms(search_string)
+            .iter()
+            .enumerate()
+        {
+            if i > 0 {
+                q.push(separator);
             }
-        };
 
-        let db_id: DBId = row.get(0);
-        debug!(
-            "Create new product description: id={}, name={}, DB-Id={} DONE",
-            desc.info.id, desc.info.name, db_id
-        );
+            if self.config.unaccent_search {
+                q.push("(");
+                for (j, variant) in umlaut_variants(term).into_iter().enumerate() {
+                    if j > 0 {
+                        q.push(" or ");
+                    }
+                    q.push("search_fold(");
+                    q.push_bind(variant);
+                    q.push(") <% search_fold(name_producer)");
+                }
+                q.push(")");
+            } else {
+                q.push_bind(term.to_string());
+                q.push(" <% name_producer");
+            }
+        }
+        q.push("))");
+    }
 
-        Ok(db_id)
+    /// Adds the condition for the products whose name and producer match the full-text search
+    /// query of the search string.
+    ///
+    /// # Arguments
+    /// * `q` - The query builder to add the condition to.
+    /// * `search_string` - The lower case search string.
+    fn push_text_search_match(&self, q: &mut QueryBuilder<'_, Postgres>, search_string: &str) {
+        q.push("search_vector(name_producer, market) @@ search_query(");
+        q.push_bind(self.config.search_terms.text_search_query(search_string));
+        q.push(")");
     }
 
-    /// Add the fields of the product to the query.
+    /// Adds the relevance of the name and producer for the search string between 0 and 1, e.g.
+    /// for sorting by the similarity. The products matching the full-text search are ranked
+    /// above the ones only found by the trigram fallback, the rank of the full-text search and
+    /// the trigram similarity order them among each other. The accents are ignored by the
+    /// trigram similarity if the accent-insensitive search is enabled.
     ///
     /// # Arguments
-    /// * `q` - The query builder to add the fields to.
-    /// * `with_preview` - Whether to include the preview image of the product in the response.
-    fn init_get_product_query<DB: Database>(q: &mut QueryBuilder<'_, DB>, with_preview: bool) {
-        // start building the sql query
-        q.push(
-            "select product_id, name, producer, quantity_type, portion, volume_weight_ratio,
-        kcal, protein_grams, fat_grams, carbohydrates_grams,
-        sugar_grams, salt_grams,
-        vitamin_a_mg, vitamin_c_mg, vitamin_d_mug,
-        iron_mg, calcium_mg, magnesium_mg, sodium_mg, zinc_mg,",
-        );
+    /// * `q` - The query builder to add the similarity to.
+    /// * `search_string` - The search string.
+    fn push_similarity(&self, q: &mut QueryBuilder<'_, Postgres>, search_string: &str) {
+        let search_string = search_string.to_lowercase();
 
-        if with_preview {
-            q.push("preview, preview_content_type from products_full_with_preview");
+        q.push("(case when ");
+        self.push_text_search_match(q, &search_string);
+        q.push(" then 0.5 + 0.25 * ts_rank(search_vector(name_producer, market), search_query(");
+        q.push_bind(self.config.search_terms.text_search_query(&search_string));
+        q.push("), 32) + 0.25 * ");
+        self.push_trigram_similarity(q, &search_string);
+        q.push(" else 0.5 * ");
+        self.push_trigram_similarity(q, &search_string);
+        q.push(" end)::real ");
+    }
+
+    /// Adds the trigram similarity of the name and producer to the search string.
+    ///
+    /// # Arguments
+    /// * `q` - The query builder to add the similarity to.
+    /// * `search_string` - The lower case search string.
+    fn push_trigram_similarity(&self, q: &mut QueryBuilder<'_, Postgres>, search_string: &str) {
+        if self.config.unaccent_search {
+            q.push("greatest(");
+            for (i, variant) in umlaut_variants(search_string).into_iter().enumerate() {
+                if i > 0 {
+                    q.push(", ");
+                }
+                q.push("similarity(search_fold(name_producer), search_fold(");
+                q.push_bind(variant);
+                q.push("))");
+            }
+            q.push(")");
         } else {
-            q.push("null as preview, null as preview_content_type from products_full");
+            q.push("similarity(name_producer, ");
+            q.push_bind(search_string.to_string());
+            q.push(")");
         }
     }
 
-    /// Initializes the query builder with a simple query to get the product request.
+    /// Adds the where clause of a product query, i.e., all its filters but the sorting, the
+    /// offset and the limit.
     ///
     /// # Arguments
-    /// * `q` - The query builder to initialize.
-    /// * `with_preview` - Whether to include the preview image of the product in the response.
-    /// * `with_db_id` - Whether to include the database id in the response.
-    fn init_get_product_request_query<DB: Database>(
-        q: &mut QueryBuilder<'_, DB>,
-        with_preview: bool,
-        with_db_id: bool,
+    /// * `q` - The query builder to add the where clause to.
+    /// * `query` - The product query whose filters are added.
+    /// * `search_string` - The lower case search string of the query, if any.
+    fn push_product_filters<'q>(
+        &self,
+        q: &mut QueryBuilder<'q, Postgres>,
+        query: &'q ProductQuery,
+        search_string: Option<&str>,
     ) {
-        q.push(
-            "select
-        product_id, date, name, producer, quantity_type, portion, volume_weight_ratio,
-        kcal, protein_grams, fat_grams, carbohydrates_grams,
-        sugar_grams, salt_grams,
-        vitamin_a_mg, vitamin_c_mg, vitamin_d_mug,
-        iron_mg, calcium_mg, magnesium_mg, sodium_mg, zinc_mg,",
-        );
-
-        if with_db_id {
-            q.push("r_id,");
+        q.push(" where true");
+        if let Some(search_string) = search_string {
+            self.push_search_filter(q, search_string);
+        } else if let SearchFilter::Store(store_id) = &query.filter {
+            Self::push_store_filter(q, *store_id);
+        }
+        Self::push_market_filter(q, query.market.as_ref());
+        Self::push_producer_filter(q, query.producer.as_deref());
+        Self::push_quantity_type_filter(q, query.quantity_type);
+        Self::push_labels_filter(q, query.labels.as_ref());
+        Self::push_allergens_filter(q, &query.exclude_allergens);
+        Self::push_data_issues_filter(q, &query.data_issues);
+        if let Some(max_nova_group) = query.max_nova_group {
+            q.push(" and nova_group <= ");
+            q.push_bind(max_nova_group);
+        }
+        if let Some(min_eco_score_grade) = query.min_eco_score_grade {
+            q.push(" and eco_score_grade <= ");
+            q.push_bind(min_eco_score_grade);
         }
+    }
 
-        if with_preview {
-            q.push("preview, preview_content_type from requested_products_full_with_preview");
-        } else {
-            q.push("null as preview, null as preview_content_type from requested_products_full");
+    /// Adds the where clause of a product request query, i.e., all its filters but the
+    /// sorting, the offset and the limit.
+    ///
+    /// # Arguments
+    /// * `q` - The query builder to add the where clause to.
+    /// * `query` - The product request query whose filters are added.
+    fn push_product_request_filters<'q>(
+        &self,
+        q: &mut QueryBuilder<'q, Postgres>,
+        query: &'q ProductQuery,
+    ) {
+        q.push(" where true");
+        match &query.filter {
+            SearchFilter::NoFilter => {}
+            SearchFilter::ProductID(product_id) => {
+                q.push(" and product_id = ");
+                q.push_bind(product_id);
+            }
+            SearchFilter::Search(s) => {
+                self.push_search_filter(q, &s.to_lowercase());
+            }
+            SearchFilter::Store(store_id) => {
+                Self::push_store_filter(q, *store_id);
+            }
+        }
+        Self::push_market_filter(q, query.market.as_ref());
+        Self::push_producer_filter(q, query.producer.as_deref());
+        Self::push_quantity_type_filter(q, query.quantity_type);
+        Self::push_labels_filter(q, query.labels.as_ref());
+        Self::push_allergens_filter(q, &query.exclude_allergens);
+        Self::push_data_issues_filter(q, &query.data_issues);
+        if let Some(max_nova_group) = query.max_nova_group {
+            q.push(" and nova_group <= ");
+            q.push_bind(max_nova_group);
+        }
+        if let Some(min_eco_score_grade) = query.min_eco_score_grade {
+            q.push(" and eco_score_grade <= ");
+            q.push_bind(min_eco_score_grade);
+        }
+        if let Some(status) = query.request_status {
+            q.push(" and status = ");
+            q.push_bind(status);
         }
     }
 
@@ -811,3 +4907,102 @@ impl PostgresBackend {
         q.push_bind(limit.min(LIMIT_MAX));
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_health_report() {
+        let latency = Duration::from_millis(12);
+
+        let report = PostgresBackend::health_report(Ok(()), latency);
+        assert_eq!(report.status, HealthStatus::Healthy);
+        assert_eq!(report.details, None);
+        assert_eq!(report.latency_ms, 12);
+
+        let report = PostgresBackend::health_report(Err(sqlx::Error::PoolTimedOut), latency);
+        assert_eq!(report.status, HealthStatus::Degraded);
+        assert_eq!(report.details.as_deref(), Some("connection pool exhausted"));
+        assert!(report.is_available());
+
+        let report = PostgresBackend::health_report(Err(sqlx::Error::PoolClosed), latency);
+        assert_eq!(report.status, HealthStatus::Unavailable);
+        assert!(!report.is_available());
+    }
+
+    #[test]
+    fn test_load_password_file() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        std::io::Write::write_all(&mut file, b"password\n").unwrap();
+
+        let mut config: PostgresConfig = serde_json::from_value(serde_json::json!({
+            "host": "localhost",
+            "port": 5432,
+            "user": "postgres",
+            "dbname": "product_db",
+            "max_connections": 1,
+            "password_file": file.path(),
+        }))
+        .unwrap();
+        config.load_password_file().unwrap();
+        assert_eq!(config.password.secret(), Some("password"));
+
+        // binary passwords are rejected
+        std::io::Write::write_all(&mut file, &[0xff, 0xfe]).unwrap();
+        assert!(config.load_password_file().is_err());
+    }
+
+    #[test]
+    fn test_umlaut_variants() {
+        assert_eq!(umlaut_variants("cafe"), vec!["cafe"]);
+        assert_eq!(umlaut_variants("muesli"), vec!["muesli"]);
+        assert_eq!(umlaut_variants("müsli"), vec!["müsli", "muesli"]);
+        assert_eq!(
+            umlaut_variants("Öl Äpfel Übung"),
+            vec!["Öl Äpfel Übung", "Oel Aepfel Uebung"]
+        );
+    }
+
+    #[test]
+    fn test_producer_like_pattern() {
+        assert_eq!(producer_like_pattern("alpro"), "alpro");
+        assert_eq!(producer_like_pattern("*brüggen*"), "%brüggen%");
+        assert_eq!(producer_like_pattern("100%_bio\\"), "100\\%\\_bio\\\\");
+    }
+
+    #[test]
+    fn test_search_terms() {
+        assert_eq!(
+            SearchTermMode::Phrase.terms("alpro  vanilla"),
+            vec!["alpro  vanilla"]
+        );
+        assert_eq!(
+            SearchTermMode::All.terms(" alpro  vanilla "),
+            vec!["alpro", "vanilla"]
+        );
+        assert_eq!(SearchTermMode::Any.terms("alpro"), vec!["alpro"]);
+        assert_eq!(SearchTermMode::All.terms("  "), vec!["  "]);
+    }
+
+    #[test]
+    fn test_text_search_query() {
+        assert_eq!(
+            SearchTermMode::Phrase.text_search_query("soy drink"),
+            "'soy drink':*"
+        );
+        assert_eq!(
+            SearchTermMode::All.text_search_query(" vanilla  alpro "),
+            "'vanilla':* & 'alpro':*"
+        );
+        assert_eq!(
+            SearchTermMode::Any.text_search_query("oat chocolate"),
+            "'oat':* | 'chocolate':*"
+        );
+        assert_eq!(SearchTermMode::Phrase.text_search_query("  "), "");
+        assert_eq!(
+            SearchTermMode::All.text_search_query("l'oréal c:\\"),
+            "'l''oréal':* & 'c:\\\\':*"
+        );
+    }
+}
@@ -0,0 +1,99 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::ProductID;
+#[cfg(feature = "service")]
+use crate::{Error, Result, MAX_STORE_NAME_LENGTH};
+
+/// A price of a product observed in a store.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "service", derive(sqlx::FromRow))]
+pub struct PriceObservation {
+    /// The id of the product.
+    pub product_id: ProductID,
+
+    /// The store where the price has been observed.
+    pub store: String,
+
+    /// The price in minor units of the currency, e.g. 199 for 1.99 EUR.
+    pub price: i64,
+
+    /// The ISO 4217 code of the currency, e.g. "EUR".
+    pub currency: String,
+
+    /// The date when the price has been observed.
+    pub observed_at: DateTime<Utc>,
+}
+
+/// Checks that the price is not negative, the store has a name and the currency is an ISO 4217
+/// code.
+///
+/// # Arguments
+/// - `price` - The observed price to check.
+#[cfg(feature = "service")]
+pub fn check_price(price: &PriceObservation) -> Result<()> {
+    if price.price < 0 {
+        return Err(Error::InvalidPrice(format!("{} is negative", price.price)));
+    }
+
+    if price.store.trim().is_empty() || price.store.chars().count() > MAX_STORE_NAME_LENGTH {
+        return Err(Error::InvalidPrice(format!(
+            "the store must have between 1 and {} characters",
+            MAX_STORE_NAME_LENGTH
+        )));
+    }
+
+    if price.currency.len() != 3 || !price.currency.chars().all(|c| c.is_ascii_uppercase()) {
+        return Err(Error::InvalidPrice(format!(
+            "'{}' is not an ISO 4217 currency code",
+            price.currency
+        )));
+    }
+
+    Ok(())
+}
+
+#[cfg(all(test, feature = "service"))]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_check_price() {
+        let price = PriceObservation {
+            product_id: "4006040009201".to_string(),
+            store: "Corner shop".to_string(),
+            price: 199,
+            currency: "EUR".to_string(),
+            observed_at: Utc::now(),
+        };
+        assert!(check_price(&price).is_ok());
+
+        let invalid = [
+            PriceObservation {
+                price: -1,
+                ..price.clone()
+            },
+            PriceObservation {
+                store: " ".to_string(),
+                ..price.clone()
+            },
+            PriceObservation {
+                store: "x".repeat(MAX_STORE_NAME_LENGTH + 1),
+                ..price.clone()
+            },
+            PriceObservation {
+                currency: "eur".to_string(),
+                ..price.clone()
+            },
+            PriceObservation {
+                currency: "EURO".to_string(),
+                ..price.clone()
+            },
+        ];
+        for price in invalid.iter() {
+            assert!(matches!(check_price(price), Err(Error::InvalidPrice(_))));
+        }
+    }
+}
@@ -0,0 +1,605 @@
+use std::{collections::HashMap, io::BufRead};
+
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+use crate::{
+    DietaryLabels, EcoScore, Error, Nutrients, Packaging, ProductDescription, ProductInfo,
+    QuantityType, Result, Weight,
+};
+
+/// The columns of the product catalog CSV, see `doc/product-csv.md`. The nutrients are given
+/// per 100g or 100ml, the energy in kcal and all other nutrients in grams.
+pub const PRODUCT_CSV_COLUMNS: [&str; 35] = [
+    "id",
+    "name",
+    "producer",
+    "market",
+    "quantity_type",
+    "portion",
+    "volume_weight_ratio",
+    "nova_group",
+    "allergens",
+    "vegan",
+    "vegetarian",
+    "organic",
+    "gluten_free",
+    "lactose_free",
+    "kcal",
+    "protein",
+    "fat",
+    "carbohydrates",
+    "sugar",
+    "salt",
+    "vitamin_a",
+    "vitamin_c",
+    "vitamin_d",
+    "iron",
+    "calcium",
+    "magnesium",
+    "sodium",
+    "zinc",
+    "packaging_net_quantity",
+    "packaging_units",
+    "packaging_material",
+    "packaging_deposit",
+    "eco_score_grade",
+    "eco_score_value",
+    "eco_score_source",
+];
+
+/// The columns that must be present in an imported catalog.
+const REQUIRED_COLUMNS: [&str; 4] = ["id", "name", "portion", "kcal"];
+
+/// The characters that are escaped with an apostrophe when written to a CSV field, as
+/// spreadsheet applications would evaluate them as formulas otherwise.
+const FORMULA_PREFIXES: [char; 6] = ['=', '+', '-', '@', '\t', '\r'];
+
+/// Returns the number as JSON value with the shortest representation of the float, e.g. 0.1
+/// instead of 0.10000000149011612.
+///
+/// # Arguments
+/// - `value` - The number.
+fn number(value: f32) -> Value {
+    value
+        .to_string()
+        .parse::<f64>()
+        .ok()
+        .and_then(serde_json::Number::from_f64)
+        .map_or(Value::Null, Value::Number)
+}
+
+/// Returns the weight in grams as JSON value, null if unknown.
+///
+/// # Arguments
+/// - `weight` - The weight, if known.
+fn grams(weight: Option<Weight>) -> Value {
+    weight.map_or(Value::Null, |weight| number(weight.value))
+}
+
+/// Returns the row of the product in the catalog CSV with the values of
+/// [`PRODUCT_CSV_COLUMNS`] in the same order. The images are not exported.
+///
+/// # Arguments
+/// - `product` - The product.
+pub fn product_csv_row(product: &ProductDescription) -> Vec<Value> {
+    let info = &product.info;
+    let nutrients = &product.nutrients;
+    let packaging = product.packaging.as_ref();
+    let eco_score = product.eco_score.as_ref();
+
+    vec![
+        Value::from(info.id.as_str()),
+        Value::from(info.name.as_str()),
+        Value::from(info.producer.as_deref()),
+        Value::from(info.market.as_deref()),
+        Value::from(info.quantity_type.to_string()),
+        number(info.portion),
+        info.volume_weight_ratio.map_or(Value::Null, number),
+        Value::from(info.nova_group),
+        Value::from(
+            info.allergens
+                .iter()
+                .map(|allergen| allergen.to_string())
+                .collect::<Vec<_>>(),
+        ),
+        Value::from(info.labels.vegan),
+        Value::from(info.labels.vegetarian),
+        Value::from(info.labels.organic),
+        Value::from(info.labels.gluten_free),
+        Value::from(info.labels.lactose_free),
+        number(nutrients.kcal),
+        grams(nutrients.protein),
+        grams(nutrients.fat),
+        grams(nutrients.carbohydrates),
+        grams(nutrients.sugar),
+        grams(nutrients.salt),
+        grams(nutrients.vitamin_a),
+        grams(nutrients.vitamin_c),
+        grams(nutrients.vitamin_d),
+        grams(nutrients.iron),
+        grams(nutrients.calcium),
+        grams(nutrients.magnesium),
+        grams(nutrients.sodium),
+        grams(nutrients.zinc),
+        packaging
+            .and_then(|p| p.net_quantity)
+            .map_or(Value::Null, number),
+        Value::from(packaging.and_then(|p| p.units)),
+        Value::from(packaging.and_then(|p| p.material).map(|m| m.to_string())),
+        Value::from(packaging.map(|p| p.deposit)),
+        Value::from(eco_score.map(|e| e.grade.to_string())),
+        Value::from(eco_score.and_then(|e| e.value)),
+        Value::from(eco_score.and_then(|e| e.source.as_deref())),
+    ]
+}
+
+/// Reads the records of a CSV document as defined by RFC 4180, i.e., separated by commas with
+/// optionally quoted fields, which may contain separators, escaped quotes and line breaks.
+pub struct CsvReader<R: BufRead> {
+    reader: R,
+    line: usize,
+}
+
+impl<R: BufRead> CsvReader<R> {
+    /// Creates a new reader.
+    ///
+    /// # Arguments
+    /// - `reader` - The reader of the CSV document.
+    pub fn new(reader: R) -> Self {
+        Self { reader, line: 0 }
+    }
+
+    /// Returns the number of the last line that has been read, starting at 1.
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /// Reads the next line into the given buffer. Returns false at the end of the document.
+    ///
+    /// # Arguments
+    /// - `buffer` - The buffer the line is appended to.
+    fn read_line(&mut self, buffer: &mut String) -> Result<bool> {
+        let read = self
+            .reader
+            .read_line(buffer)
+            .map_err(|e| Error::IO(Box::new(e)))?;
+        if read > 0 {
+            self.line += 1;
+        }
+
+        Ok(read > 0)
+    }
+
+    /// Returns the fields of the next record, None at the end of the document. Empty lines
+    /// are skipped.
+    pub fn record(&mut self) -> Result<Option<Vec<String>>> {
+        let mut line = String::new();
+        loop {
+            if !self.read_line(&mut line)? {
+                return Ok(None);
+            }
+            if !line.trim_end_matches(['\r', '\n']).is_empty() {
+                break;
+            }
+            line.clear();
+        }
+
+        let mut fields = Vec::new();
+        let mut field = String::new();
+        let mut chars: Vec<char> = line.chars().collect();
+        let mut index = 0;
+        let mut quoted = false;
+        loop {
+            let Some(&c) = chars.get(index) else {
+                if !quoted {
+                    break;
+                }
+
+                // the quoted field continues on the next line
+                let mut next = String::new();
+                if !self.read_line(&mut next)? {
+                    return Err(Error::InvalidCsv(format!(
+                        "Unterminated quoted field in line {}",
+                        self.line
+                    )));
+                }
+                chars = next.chars().collect();
+                index = 0;
+                continue;
+            };
+            index += 1;
+
+            match c {
+                '"' if quoted => {
+                    if chars.get(index) == Some(&'"') {
+                        field.push('"');
+                        index += 1;
+                    } else {
+                        quoted = false;
+                    }
+                }
+                '"' if field.is_empty() => quoted = true,
+                ',' if !quoted => fields.push(std::mem::take(&mut field)),
+                '\r' | '\n' if !quoted => {}
+                c => field.push(c),
+            }
+        }
+        fields.push(field);
+
+        Ok(Some(fields))
+    }
+}
+
+/// The positions of the columns of an imported catalog, which are given by its header. The
+/// columns may be in any order and the optional columns may be missing.
+pub struct ProductCsvColumns {
+    columns: HashMap<&'static str, usize>,
+}
+
+impl ProductCsvColumns {
+    /// Returns the columns of the given header. Unknown columns are ignored.
+    ///
+    /// # Arguments
+    /// - `header` - The names of the columns.
+    pub fn new(header: &[String]) -> Result<Self> {
+        let columns: HashMap<&'static str, usize> = header
+            .iter()
+            .enumerate()
+            .filter_map(|(index, name)| {
+                let name = name.trim().trim_start_matches('\u{feff}');
+                PRODUCT_CSV_COLUMNS
+                    .iter()
+                    .find(|column| **column == name)
+                    .map(|column| (*column, index))
+            })
+            .collect();
+
+        for column in REQUIRED_COLUMNS {
+            if !columns.contains_key(column) {
+                return Err(Error::InvalidCsv(format!(
+                    "The header has no '{}' column",
+                    column
+                )));
+            }
+        }
+
+        Ok(Self { columns })
+    }
+
+    /// Returns true if the header is the header of a product catalog, i.e., its first column
+    /// is the id of the products.
+    ///
+    /// # Arguments
+    /// - `header` - The names of the columns.
+    pub fn is_catalog_header(header: &[String]) -> bool {
+        header
+            .first()
+            .is_some_and(|name| name.trim().trim_start_matches('\u{feff}') == "id")
+    }
+
+    /// Returns the trimmed text of the given column if it is not empty. Values that have been
+    /// escaped with an apostrophe on export are unescaped.
+    ///
+    /// # Arguments
+    /// - `record` - The fields of the record.
+    /// - `column` - The name of the column.
+    fn text<'a>(&self, record: &'a [String], column: &str) -> Option<&'a str> {
+        let value = record.get(*self.columns.get(column)?)?.trim();
+        let value = match value.strip_prefix('\'') {
+            Some(escaped) if escaped.starts_with(FORMULA_PREFIXES) => escaped,
+            _ => value,
+        };
+
+        Some(value).filter(|value| !value.is_empty())
+    }
+
+    /// Parses the value of the given column, None if the column is empty.
+    ///
+    /// # Arguments
+    /// - `record` - The fields of the record.
+    /// - `column` - The name of the column.
+    fn parse<T: std::str::FromStr>(&self, record: &[String], column: &str) -> Result<Option<T>> {
+        self.text(record, column)
+            .map(|text| {
+                text.parse::<T>().map_err(|_| {
+                    Error::InvalidCsv(format!("Invalid value '{}' of column '{}'", text, column))
+                })
+            })
+            .transpose()
+    }
+
+    /// Parses the value of the given column by the serde representation of the type, e.g. of an
+    /// enum, ignoring the case. None if the column is empty.
+    ///
+    /// # Arguments
+    /// - `record` - The fields of the record.
+    /// - `column` - The name of the column.
+    fn parse_enum<T: DeserializeOwned>(
+        &self,
+        record: &[String],
+        column: &str,
+    ) -> Result<Option<T>> {
+        self.text(record, column)
+            .map(|text| enum_value(text, column))
+            .transpose()
+    }
+
+    /// Parses the boolean value of the given column, None if the column is empty.
+    ///
+    /// # Arguments
+    /// - `record` - The fields of the record.
+    /// - `column` - The name of the column.
+    fn boolean(&self, record: &[String], column: &str) -> Result<Option<bool>> {
+        self.text(record, column)
+            .map(|text| match text.to_lowercase().as_str() {
+                "true" | "yes" | "1" => Ok(true),
+                "false" | "no" | "0" => Ok(false),
+                _ => Err(Error::InvalidCsv(format!(
+                    "Invalid value '{}' of column '{}', expected true or false",
+                    text, column
+                ))),
+            })
+            .transpose()
+    }
+
+    /// Parses the weight in grams of the given column, None if the column is empty.
+    ///
+    /// # Arguments
+    /// - `record` - The fields of the record.
+    /// - `column` - The name of the column.
+    fn grams(&self, record: &[String], column: &str) -> Result<Option<Weight>> {
+        Ok(self
+            .parse::<f32>(record, column)?
+            .map(Weight::new_from_gram))
+    }
+
+    /// Maps the record to a product without images.
+    ///
+    /// # Arguments
+    /// - `record` - The fields of the record.
+    pub fn product(&self, record: &[String]) -> Result<ProductDescription> {
+        let required =
+            |column: &str| Error::InvalidCsv(format!("The column '{}' must not be empty", column));
+
+        let allergens = self
+            .text(record, "allergens")
+            .unwrap_or_default()
+            .split([',', ';'])
+            .map(str::trim)
+            .filter(|allergen| !allergen.is_empty())
+            .map(|allergen| enum_value(allergen, "allergens"))
+            .collect::<Result<Vec<_>>>()?;
+
+        let info = ProductInfo {
+            id: self
+                .text(record, "id")
+                .ok_or_else(|| required("id"))?
+                .to_string(),
+            name: self
+                .text(record, "name")
+                .ok_or_else(|| required("name"))?
+                .to_string(),
+            producer: self.text(record, "producer").map(str::to_string),
+            quantity_type: self
+                .parse_enum(record, "quantity_type")?
+                .unwrap_or(QuantityType::Weight),
+            portion: self
+                .parse(record, "portion")?
+                .ok_or_else(|| required("portion"))?,
+            volume_weight_ratio: self.parse(record, "volume_weight_ratio")?,
+            market: self.text(record, "market").map(str::to_uppercase),
+            labels: DietaryLabels {
+                vegan: self.boolean(record, "vegan")?,
+                vegetarian: self.boolean(record, "vegetarian")?,
+                organic: self.boolean(record, "organic")?,
+                gluten_free: self.boolean(record, "gluten_free")?,
+                lactose_free: self.boolean(record, "lactose_free")?,
+            },
+            nova_group: self.parse(record, "nova_group")?,
+            allergens,
+        };
+
+        let nutrients = Nutrients {
+            kcal: self
+                .parse(record, "kcal")?
+                .ok_or_else(|| required("kcal"))?,
+            protein: self.grams(record, "protein")?,
+            fat: self.grams(record, "fat")?,
+            carbohydrates: self.grams(record, "carbohydrates")?,
+            sugar: self.grams(record, "sugar")?,
+            salt: self.grams(record, "salt")?,
+            vitamin_a: self.grams(record, "vitamin_a")?,
+            vitamin_c: self.grams(record, "vitamin_c")?,
+            vitamin_d: self.grams(record, "vitamin_d")?,
+            iron: self.grams(record, "iron")?,
+            calcium: self.grams(record, "calcium")?,
+            magnesium: self.grams(record, "magnesium")?,
+            sodium: self.grams(record, "sodium")?,
+            zinc: self.grams(record, "zinc")?,
+        };
+
+        // the packaging is unknown if all of its columns are empty
+        let net_quantity = self.parse(record, "packaging_net_quantity")?;
+        let units = self.parse(record, "packaging_units")?;
+        let material = self.parse_enum(record, "packaging_material")?;
+        let deposit = self.boolean(record, "packaging_deposit")?;
+        let packaging =
+            (net_quantity.is_some() || units.is_some() || material.is_some() || deposit.is_some())
+                .then(|| Packaging {
+                    net_quantity,
+                    units,
+                    material,
+                    deposit: deposit.unwrap_or_default(),
+                });
+
+        let eco_score = self
+            .parse_enum(record, "eco_score_grade")?
+            .map(|grade| -> Result<EcoScore> {
+                Ok(EcoScore {
+                    grade,
+                    value: self.parse(record, "eco_score_value")?,
+                    source: self.text(record, "eco_score_source").map(str::to_string),
+                })
+            })
+            .transpose()?;
+
+        Ok(ProductDescription {
+            info,
+            preview: None,
+            full_image: None,
+            nutrients,
+            packaging,
+            eco_score,
+        })
+    }
+}
+
+/// Parses the text by the serde representation of the type, e.g. of an enum, ignoring the case.
+///
+/// # Arguments
+/// - `text` - The text of the value.
+/// - `column` - The name of the column for the error message.
+fn enum_value<T: DeserializeOwned>(text: &str, column: &str) -> Result<T> {
+    [text.to_string(), text.to_lowercase(), text.to_uppercase()]
+        .into_iter()
+        .find_map(|text| serde_json::from_value(Value::String(text)).ok())
+        .ok_or_else(|| {
+            Error::InvalidCsv(format!("Invalid value '{}' of column '{}'", text, column))
+        })
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::Cursor;
+
+    use crate::{
+        csv_document, Allergen, EcoScoreGrade, PackagingMaterial, ProductInfo, QuantityType,
+    };
+
+    use super::*;
+
+    fn product() -> ProductDescription {
+        ProductDescription {
+            info: ProductInfo {
+                id: "4000417025005".to_string(),
+                name: "-Schokolade, \"Vollmilch\"".to_string(),
+                producer: Some("Ritter Sport".to_string()),
+                quantity_type: QuantityType::Weight,
+                portion: 16.7,
+                volume_weight_ratio: None,
+                market: Some("DE".to_string()),
+                labels: DietaryLabels {
+                    vegetarian: Some(true),
+                    vegan: Some(false),
+                    ..Default::default()
+                },
+                nova_group: Some(4),
+                allergens: vec![Allergen::Milk, Allergen::Soybeans],
+            },
+            preview: None,
+            full_image: None,
+            nutrients: Nutrients {
+                kcal: 560.0,
+                protein: Some(Weight::new_from_gram(7.3)),
+                fat: Some(Weight::new_from_gram(35.0)),
+                carbohydrates: Some(Weight::new_from_gram(50.0)),
+                sugar: Some(Weight::new_from_gram(48.0)),
+                salt: Some(Weight::new_from_gram(0.23)),
+                vitamin_a: None,
+                vitamin_c: None,
+                vitamin_d: Some(Weight::new_from_gram(0.000002)),
+                iron: None,
+                calcium: Some(Weight::new_from_gram(0.2)),
+                magnesium: None,
+                sodium: None,
+                zinc: None,
+            },
+            packaging: Some(Packaging {
+                net_quantity: Some(100.0),
+                units: None,
+                material: Some(PackagingMaterial::Paper),
+                deposit: false,
+            }),
+            eco_score: Some(EcoScore {
+                grade: EcoScoreGrade::C,
+                value: Some(48),
+                source: None,
+            }),
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut milk = product();
+        milk.info.id = "42".to_string();
+        milk.info.name = "Milch\nfrisch".to_string();
+        milk.info.quantity_type = QuantityType::Volume;
+        milk.info.volume_weight_ratio = Some(0.97);
+        milk.info.allergens = Vec::new();
+        milk.packaging = None;
+        milk.eco_score = None;
+
+        let products = vec![product(), milk];
+        let rows: Vec<Vec<Value>> = products.iter().map(product_csv_row).collect();
+        let document = csv_document(&PRODUCT_CSV_COLUMNS, &rows);
+
+        let mut reader = CsvReader::new(Cursor::new(document));
+        let header = reader.record().unwrap().unwrap();
+        assert!(ProductCsvColumns::is_catalog_header(&header));
+        let columns = ProductCsvColumns::new(&header).unwrap();
+
+        for expected in products {
+            let record = reader.record().unwrap().unwrap();
+            assert_eq!(record.len(), PRODUCT_CSV_COLUMNS.len());
+            assert_eq!(columns.product(&record).unwrap(), expected);
+        }
+        assert!(reader.record().unwrap().is_none());
+        assert_eq!(reader.line(), 4);
+    }
+
+    #[test]
+    fn test_edited_catalog() {
+        // a catalog edited in a spreadsheet with reordered and missing columns
+        let document = "\u{feff}name,id,kcal,portion,allergens,vegan,packaging_deposit\r\n\
+            Water,1,0,250,,yes,TRUE\r\n\
+            \r\n\
+            Bread,2,250.5,50,\"Gluten; sesame\",,\r\n";
+
+        let mut reader = CsvReader::new(Cursor::new(document));
+        let header = reader.record().unwrap().unwrap();
+        assert!(!ProductCsvColumns::is_catalog_header(&header));
+        let columns = ProductCsvColumns::new(&header).unwrap();
+
+        let water = columns.product(&reader.record().unwrap().unwrap()).unwrap();
+        assert_eq!(water.info.id, "1");
+        assert_eq!(water.info.quantity_type, QuantityType::Weight);
+        assert_eq!(water.info.labels.vegan, Some(true));
+        assert!(water.packaging.unwrap().deposit);
+
+        let bread = columns.product(&reader.record().unwrap().unwrap()).unwrap();
+        assert_eq!(bread.nutrients.kcal, 250.5);
+        assert_eq!(
+            bread.info.allergens,
+            vec![Allergen::Gluten, Allergen::Sesame]
+        );
+        assert!(bread.packaging.is_none());
+        assert!(reader.record().unwrap().is_none());
+    }
+
+    #[test]
+    fn test_invalid_catalog() {
+        let header = |names: &str| names.split(',').map(str::to_string).collect::<Vec<_>>();
+        assert!(ProductCsvColumns::new(&header("id,name,kcal")).is_err());
+
+        let columns = ProductCsvColumns::new(&header("id,name,portion,kcal,nova_group")).unwrap();
+        let record = |values: &str| values.split(',').map(str::to_string).collect::<Vec<_>>();
+        assert!(columns.product(&record("1,Water,250,0,")).is_ok());
+        assert!(columns.product(&record("1,,250,0,")).is_err());
+        assert!(columns.product(&record("1,Water,250,zero,")).is_err());
+        assert!(columns.product(&record("1,Water,250,0,four")).is_err());
+
+        let mut reader = CsvReader::new(Cursor::new("id,\"name\r\n"));
+        assert!(reader.record().is_err());
+    }
+}
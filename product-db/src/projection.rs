@@ -0,0 +1,233 @@
+use std::{fmt::Display, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::ProductDescription;
+
+/// A product reduced to the selected fields, i.e., a flat map of the field names to their
+/// values.
+pub type ProductProjection = Map<String, Value>;
+
+/// The fields of a product that can be selected by a query, e.g. for list views that need
+/// neither the full nutrient block nor the producer metadata.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum ProductField {
+    Id,
+    Name,
+    Producer,
+    QuantityType,
+    Portion,
+    VolumeWeightRatio,
+    Market,
+    Labels,
+    NovaGroup,
+    Allergens,
+    /// The whole nutrient block.
+    Nutrients,
+    Kcal,
+    Protein,
+    Fat,
+    Carbohydrates,
+    Sugar,
+    Salt,
+    #[serde(rename = "vitaminA")]
+    VitaminA,
+    #[serde(rename = "vitaminC")]
+    VitaminC,
+    #[serde(rename = "vitaminD")]
+    VitaminD,
+    Iron,
+    Calcium,
+    Magnesium,
+    Sodium,
+    Zinc,
+    /// The preview image, which is only loaded from the database if selected.
+    Preview,
+    Packaging,
+    EcoScore,
+}
+
+impl ProductField {
+    /// All fields that can be selected.
+    pub const ALL: [ProductField; 28] = [
+        ProductField::Id,
+        ProductField::Name,
+        ProductField::Producer,
+        ProductField::QuantityType,
+        ProductField::Portion,
+        ProductField::VolumeWeightRatio,
+        ProductField::Market,
+        ProductField::Labels,
+        ProductField::NovaGroup,
+        ProductField::Allergens,
+        ProductField::Nutrients,
+        ProductField::Kcal,
+        ProductField::Protein,
+        ProductField::Fat,
+        ProductField::Carbohydrates,
+        ProductField::Sugar,
+        ProductField::Salt,
+        ProductField::VitaminA,
+        ProductField::VitaminC,
+        ProductField::VitaminD,
+        ProductField::Iron,
+        ProductField::Calcium,
+        ProductField::Magnesium,
+        ProductField::Sodium,
+        ProductField::Zinc,
+        ProductField::Preview,
+        ProductField::Packaging,
+        ProductField::EcoScore,
+    ];
+
+    /// Returns the name of the field as used in the JSON representation of the product.
+    pub fn name(&self) -> &'static str {
+        match self {
+            ProductField::Id => "id",
+            ProductField::Name => "name",
+            ProductField::Producer => "producer",
+            ProductField::QuantityType => "quantity_type",
+            ProductField::Portion => "portion",
+            ProductField::VolumeWeightRatio => "volume_weight_ratio",
+            ProductField::Market => "market",
+            ProductField::Labels => "labels",
+            ProductField::NovaGroup => "nova_group",
+            ProductField::Allergens => "allergens",
+            ProductField::Nutrients => "nutrients",
+            ProductField::Kcal => "kcal",
+            ProductField::Protein => "protein",
+            ProductField::Fat => "fat",
+            ProductField::Carbohydrates => "carbohydrates",
+            ProductField::Sugar => "sugar",
+            ProductField::Salt => "salt",
+            ProductField::VitaminA => "vitaminA",
+            ProductField::VitaminC => "vitaminC",
+            ProductField::VitaminD => "vitaminD",
+            ProductField::Iron => "iron",
+            ProductField::Calcium => "calcium",
+            ProductField::Magnesium => "magnesium",
+            ProductField::Sodium => "sodium",
+            ProductField::Zinc => "zinc",
+            ProductField::Preview => "preview",
+            ProductField::Packaging => "packaging",
+            ProductField::EcoScore => "eco_score",
+        }
+    }
+
+    /// Returns the section of the JSON representation of the product the field belongs to or
+    /// `None` if the field is at the top level of the product.
+    fn section(&self) -> Option<&'static str> {
+        match self {
+            ProductField::Nutrients
+            | ProductField::Preview
+            | ProductField::Packaging
+            | ProductField::EcoScore => None,
+            ProductField::Kcal
+            | ProductField::Protein
+            | ProductField::Fat
+            | ProductField::Carbohydrates
+            | ProductField::Sugar
+            | ProductField::Salt
+            | ProductField::VitaminA
+            | ProductField::VitaminC
+            | ProductField::VitaminD
+            | ProductField::Iron
+            | ProductField::Calcium
+            | ProductField::Magnesium
+            | ProductField::Sodium
+            | ProductField::Zinc => Some("nutrients"),
+            _ => Some("info"),
+        }
+    }
+}
+
+impl Display for ProductField {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for ProductField {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        ProductField::ALL
+            .into_iter()
+            .find(|field| field.name() == s)
+            .ok_or_else(|| format!("Unknown product field '{}'", s))
+    }
+}
+
+/// Reduces the product to the given fields. Fields that are not defined for the product, e.g.
+/// a missing preview, are null.
+///
+/// # Arguments
+/// - `product` - The product to reduce.
+/// - `fields` - The fields to select.
+pub fn project_product(product: &ProductDescription, fields: &[ProductField]) -> ProductProjection {
+    let value = serde_json::to_value(product).unwrap_or_default();
+
+    fields
+        .iter()
+        .map(|field| {
+            let section = match field.section() {
+                Some(section) => &value[section],
+                None => &value,
+            };
+
+            (field.name().to_string(), section[field.name()].clone())
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_project_product() {
+        let product_data = include_str!("../../test_data/products.json");
+        let products: Vec<ProductDescription> = serde_json::from_str(product_data).unwrap();
+        let product = &products[0];
+
+        let fields = [
+            ProductField::Id,
+            ProductField::Name,
+            ProductField::Kcal,
+            ProductField::VitaminC,
+            ProductField::Preview,
+        ];
+        let projection = project_product(product, &fields);
+
+        let keys: Vec<&str> = projection.keys().map(String::as_str).collect();
+        assert_eq!(keys.len(), fields.len());
+        for field in fields {
+            assert!(keys.contains(&field.name()));
+        }
+        assert_eq!(projection["id"], Value::String(product.info.id.clone()));
+        assert_eq!(projection["name"], Value::String(product.info.name.clone()));
+        assert_eq!(
+            projection["kcal"].as_f64().unwrap() as f32,
+            product.nutrients.kcal
+        );
+        assert_eq!(projection["vitaminC"], Value::Null);
+        assert_eq!(
+            projection["preview"],
+            serde_json::to_value(&product.preview).unwrap()
+        );
+
+        // the names of the fields match the serialized names
+        for field in ProductField::ALL {
+            assert_eq!(
+                serde_json::to_value(field).unwrap(),
+                Value::String(field.name().to_string())
+            );
+            assert_eq!(field.name().parse::<ProductField>(), Ok(field));
+        }
+        assert!("unknown".parse::<ProductField>().is_err());
+    }
+}
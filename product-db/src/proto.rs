@@ -0,0 +1,881 @@
+//! The protobuf messages of the domain types and the conversions from and to them. The messages
+//! are defined in `proto/product_db/v1/product_db.proto`.
+
+use chrono::{DateTime, Utc};
+use prost_types::Timestamp;
+use thiserror::Error;
+
+use crate::{
+    Allergen, DietaryLabels, EcoScore, EcoScoreGrade, MissingProduct, MissingProductQuery,
+    Nutrients, Packaging, PackagingMaterial, ProductDataIssue, ProductDescription, ProductField,
+    ProductImage, ProductInfo, ProductQuery, ProductRequest, QuantityType, RequestStatus,
+    SearchFilter, Sorting, SortingField, SortingOrder, Weight,
+};
+
+/// The generated protobuf messages of version 1.
+pub mod v1 {
+    include!(concat!(env!("OUT_DIR"), "/product_db.v1.rs"));
+}
+
+/// The error of converting a protobuf message into a domain type.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum ProtoConversionError {
+    #[error("Missing field: {0}")]
+    MissingField(&'static str),
+
+    #[error("Invalid value of field {field}: {value}")]
+    InvalidValue { field: &'static str, value: String },
+}
+
+/// The result of converting a protobuf message into a domain type.
+pub type ProtoResult<T> = std::result::Result<T, ProtoConversionError>;
+
+/// Converts the given date into a protobuf timestamp.
+///
+/// # Arguments
+/// - `date` - The date to convert.
+fn to_timestamp(date: DateTime<Utc>) -> Timestamp {
+    Timestamp {
+        seconds: date.timestamp(),
+        nanos: date.timestamp_subsec_nanos() as i32,
+    }
+}
+
+/// Converts the given protobuf timestamp into a date.
+///
+/// # Arguments
+/// - `field` - The name of the field, used for the error.
+/// - `timestamp` - The timestamp to convert.
+fn from_timestamp(field: &'static str, timestamp: Option<Timestamp>) -> ProtoResult<DateTime<Utc>> {
+    let timestamp = timestamp.ok_or(ProtoConversionError::MissingField(field))?;
+
+    u32::try_from(timestamp.nanos)
+        .ok()
+        .and_then(|nanos| DateTime::from_timestamp(timestamp.seconds, nanos))
+        .ok_or_else(|| ProtoConversionError::InvalidValue {
+            field,
+            value: timestamp.to_string(),
+        })
+}
+
+/// Returns the error for an enum value that is unknown or unspecified.
+///
+/// # Arguments
+/// - `field` - The name of the field.
+/// - `value` - The raw value of the field.
+fn invalid_enum(field: &'static str, value: i32) -> ProtoConversionError {
+    ProtoConversionError::InvalidValue {
+        field,
+        value: value.to_string(),
+    }
+}
+
+impl From<QuantityType> for v1::QuantityType {
+    fn from(value: QuantityType) -> Self {
+        match value {
+            QuantityType::Weight => v1::QuantityType::Weight,
+            QuantityType::Volume => v1::QuantityType::Volume,
+        }
+    }
+}
+
+impl From<DietaryLabels> for v1::DietaryLabels {
+    fn from(value: DietaryLabels) -> Self {
+        Self {
+            vegan: value.vegan,
+            vegetarian: value.vegetarian,
+            organic: value.organic,
+            gluten_free: value.gluten_free,
+            lactose_free: value.lactose_free,
+        }
+    }
+}
+
+impl From<v1::DietaryLabels> for DietaryLabels {
+    fn from(value: v1::DietaryLabels) -> Self {
+        Self {
+            vegan: value.vegan,
+            vegetarian: value.vegetarian,
+            organic: value.organic,
+            gluten_free: value.gluten_free,
+            lactose_free: value.lactose_free,
+        }
+    }
+}
+
+impl From<Allergen> for v1::Allergen {
+    fn from(value: Allergen) -> Self {
+        match value {
+            Allergen::Gluten => v1::Allergen::Gluten,
+            Allergen::Crustaceans => v1::Allergen::Crustaceans,
+            Allergen::Eggs => v1::Allergen::Eggs,
+            Allergen::Fish => v1::Allergen::Fish,
+            Allergen::Peanuts => v1::Allergen::Peanuts,
+            Allergen::Soybeans => v1::Allergen::Soybeans,
+            Allergen::Milk => v1::Allergen::Milk,
+            Allergen::Nuts => v1::Allergen::Nuts,
+            Allergen::Celery => v1::Allergen::Celery,
+            Allergen::Mustard => v1::Allergen::Mustard,
+            Allergen::Sesame => v1::Allergen::Sesame,
+            Allergen::Sulphites => v1::Allergen::Sulphites,
+            Allergen::Lupin => v1::Allergen::Lupin,
+            Allergen::Molluscs => v1::Allergen::Molluscs,
+        }
+    }
+}
+
+/// Converts the given protobuf enum values into allergens. Unspecified values are rejected.
+///
+/// # Arguments
+/// - `field` - The name of the field for the error message.
+/// - `values` - The raw protobuf enum values.
+fn allergens_from_proto(field: &'static str, values: Vec<i32>) -> ProtoResult<Vec<Allergen>> {
+    values
+        .into_iter()
+        .map(|value| match v1::Allergen::try_from(value) {
+            Ok(v1::Allergen::Gluten) => Ok(Allergen::Gluten),
+            Ok(v1::Allergen::Crustaceans) => Ok(Allergen::Crustaceans),
+            Ok(v1::Allergen::Eggs) => Ok(Allergen::Eggs),
+            Ok(v1::Allergen::Fish) => Ok(Allergen::Fish),
+            Ok(v1::Allergen::Peanuts) => Ok(Allergen::Peanuts),
+            Ok(v1::Allergen::Soybeans) => Ok(Allergen::Soybeans),
+            Ok(v1::Allergen::Milk) => Ok(Allergen::Milk),
+            Ok(v1::Allergen::Nuts) => Ok(Allergen::Nuts),
+            Ok(v1::Allergen::Celery) => Ok(Allergen::Celery),
+            Ok(v1::Allergen::Mustard) => Ok(Allergen::Mustard),
+            Ok(v1::Allergen::Sesame) => Ok(Allergen::Sesame),
+            Ok(v1::Allergen::Sulphites) => Ok(Allergen::Sulphites),
+            Ok(v1::Allergen::Lupin) => Ok(Allergen::Lupin),
+            Ok(v1::Allergen::Molluscs) => Ok(Allergen::Molluscs),
+            _ => Err(invalid_enum(field, value)),
+        })
+        .collect()
+}
+
+/// Converts the given allergens into the raw protobuf enum values.
+///
+/// # Arguments
+/// - `allergens` - The allergens to convert.
+fn allergens_to_proto(allergens: Vec<Allergen>) -> Vec<i32> {
+    allergens
+        .into_iter()
+        .map(|allergen| v1::Allergen::from(allergen) as i32)
+        .collect()
+}
+
+impl From<ProductDataIssue> for v1::ProductDataIssue {
+    fn from(value: ProductDataIssue) -> Self {
+        match value {
+            ProductDataIssue::MissingImage => v1::ProductDataIssue::MissingImage,
+            ProductDataIssue::MissingMacroNutrients => v1::ProductDataIssue::MissingMacroNutrients,
+            ProductDataIssue::MissingProducer => v1::ProductDataIssue::MissingProducer,
+        }
+    }
+}
+
+/// Converts the given protobuf enum values into data quality issues. Unspecified values are
+/// rejected.
+///
+/// # Arguments
+/// - `field` - The name of the field for the error message.
+/// - `values` - The raw protobuf enum values.
+fn data_issues_from_proto(
+    field: &'static str,
+    values: Vec<i32>,
+) -> ProtoResult<Vec<ProductDataIssue>> {
+    values
+        .into_iter()
+        .map(|value| match v1::ProductDataIssue::try_from(value) {
+            Ok(v1::ProductDataIssue::MissingImage) => Ok(ProductDataIssue::MissingImage),
+            Ok(v1::ProductDataIssue::MissingMacroNutrients) => {
+                Ok(ProductDataIssue::MissingMacroNutrients)
+            }
+            Ok(v1::ProductDataIssue::MissingProducer) => Ok(ProductDataIssue::MissingProducer),
+            _ => Err(invalid_enum(field, value)),
+        })
+        .collect()
+}
+
+/// Converts the given protobuf field names into the selected product fields. No fields are
+/// selected, i.e., all fields are returned, if the list is empty.
+///
+/// # Arguments
+/// - `field` - The name of the field, used for the error.
+/// - `values` - The names of the selected product fields.
+fn product_fields_from_proto(
+    field: &'static str,
+    values: Vec<String>,
+) -> ProtoResult<Option<Vec<ProductField>>> {
+    if values.is_empty() {
+        return Ok(None);
+    }
+
+    values
+        .into_iter()
+        .map(|value| {
+            value
+                .parse()
+                .map_err(|_| ProtoConversionError::InvalidValue { field, value })
+        })
+        .collect::<ProtoResult<Vec<_>>>()
+        .map(Some)
+}
+
+impl From<ProductInfo> for v1::ProductInfo {
+    fn from(value: ProductInfo) -> Self {
+        Self {
+            id: value.id,
+            name: value.name,
+            producer: value.producer,
+            quantity_type: v1::QuantityType::from(value.quantity_type) as i32,
+            portion: value.portion,
+            volume_weight_ratio: value.volume_weight_ratio,
+            market: value.market,
+            labels: Some(value.labels.into()),
+            nova_group: value.nova_group,
+            allergens: allergens_to_proto(value.allergens),
+        }
+    }
+}
+
+impl TryFrom<v1::ProductInfo> for ProductInfo {
+    type Error = ProtoConversionError;
+
+    fn try_from(value: v1::ProductInfo) -> ProtoResult<Self> {
+        let quantity_type = match v1::QuantityType::try_from(value.quantity_type) {
+            Ok(v1::QuantityType::Weight) => QuantityType::Weight,
+            Ok(v1::QuantityType::Volume) => QuantityType::Volume,
+            _ => return Err(invalid_enum("quantity_type", value.quantity_type)),
+        };
+
+        Ok(Self {
+            id: value.id,
+            name: value.name,
+            producer: value.producer,
+            quantity_type,
+            portion: value.portion,
+            volume_weight_ratio: value.volume_weight_ratio,
+            market: value.market,
+            labels: value.labels.map(Into::into).unwrap_or_default(),
+            nova_group: value.nova_group,
+            allergens: allergens_from_proto("allergens", value.allergens)?,
+        })
+    }
+}
+
+impl From<ProductImage> for v1::ProductImage {
+    fn from(value: ProductImage) -> Self {
+        Self {
+            content_type: value.content_type,
+            data: value.data,
+        }
+    }
+}
+
+impl From<v1::ProductImage> for ProductImage {
+    fn from(value: v1::ProductImage) -> Self {
+        Self {
+            content_type: value.content_type,
+            data: value.data,
+        }
+    }
+}
+
+impl From<Nutrients> for v1::Nutrients {
+    fn from(value: Nutrients) -> Self {
+        let gram = |weight: Option<Weight>| weight.map(Weight::gram);
+
+        Self {
+            kcal: value.kcal,
+            protein: gram(value.protein),
+            fat: gram(value.fat),
+            carbohydrates: gram(value.carbohydrates),
+            sugar: gram(value.sugar),
+            salt: gram(value.salt),
+            vitamin_a: gram(value.vitamin_a),
+            vitamin_c: gram(value.vitamin_c),
+            vitamin_d: gram(value.vitamin_d),
+            iron: gram(value.iron),
+            calcium: gram(value.calcium),
+            magnesium: gram(value.magnesium),
+            sodium: gram(value.sodium),
+            zinc: gram(value.zinc),
+        }
+    }
+}
+
+impl From<v1::Nutrients> for Nutrients {
+    fn from(value: v1::Nutrients) -> Self {
+        let weight = |gram: Option<f32>| gram.map(Weight::new_from_gram);
+
+        Self {
+            kcal: value.kcal,
+            protein: weight(value.protein),
+            fat: weight(value.fat),
+            carbohydrates: weight(value.carbohydrates),
+            sugar: weight(value.sugar),
+            salt: weight(value.salt),
+            vitamin_a: weight(value.vitamin_a),
+            vitamin_c: weight(value.vitamin_c),
+            vitamin_d: weight(value.vitamin_d),
+            iron: weight(value.iron),
+            calcium: weight(value.calcium),
+            magnesium: weight(value.magnesium),
+            sodium: weight(value.sodium),
+            zinc: weight(value.zinc),
+        }
+    }
+}
+
+impl From<Packaging> for v1::Packaging {
+    fn from(value: Packaging) -> Self {
+        let material = match value.material {
+            None => v1::PackagingMaterial::Unspecified,
+            Some(PackagingMaterial::Plastic) => v1::PackagingMaterial::Plastic,
+            Some(PackagingMaterial::Glass) => v1::PackagingMaterial::Glass,
+            Some(PackagingMaterial::Paper) => v1::PackagingMaterial::Paper,
+            Some(PackagingMaterial::Metal) => v1::PackagingMaterial::Metal,
+            Some(PackagingMaterial::Composite) => v1::PackagingMaterial::Composite,
+            Some(PackagingMaterial::Other) => v1::PackagingMaterial::Other,
+        };
+
+        Self {
+            net_quantity: value.net_quantity,
+            units: value.units,
+            material: material as i32,
+            deposit: value.deposit,
+        }
+    }
+}
+
+impl TryFrom<v1::Packaging> for Packaging {
+    type Error = ProtoConversionError;
+
+    fn try_from(value: v1::Packaging) -> ProtoResult<Self> {
+        let material = match v1::PackagingMaterial::try_from(value.material) {
+            Ok(v1::PackagingMaterial::Unspecified) => None,
+            Ok(v1::PackagingMaterial::Plastic) => Some(PackagingMaterial::Plastic),
+            Ok(v1::PackagingMaterial::Glass) => Some(PackagingMaterial::Glass),
+            Ok(v1::PackagingMaterial::Paper) => Some(PackagingMaterial::Paper),
+            Ok(v1::PackagingMaterial::Metal) => Some(PackagingMaterial::Metal),
+            Ok(v1::PackagingMaterial::Composite) => Some(PackagingMaterial::Composite),
+            Ok(v1::PackagingMaterial::Other) => Some(PackagingMaterial::Other),
+            Err(_) => return Err(invalid_enum("material", value.material)),
+        };
+
+        Ok(Self {
+            net_quantity: value.net_quantity,
+            units: value.units,
+            material,
+            deposit: value.deposit,
+        })
+    }
+}
+
+impl From<EcoScoreGrade> for v1::EcoScoreGrade {
+    fn from(value: EcoScoreGrade) -> Self {
+        match value {
+            EcoScoreGrade::A => v1::EcoScoreGrade::A,
+            EcoScoreGrade::B => v1::EcoScoreGrade::B,
+            EcoScoreGrade::C => v1::EcoScoreGrade::C,
+            EcoScoreGrade::D => v1::EcoScoreGrade::D,
+            EcoScoreGrade::E => v1::EcoScoreGrade::E,
+        }
+    }
+}
+
+/// Converts the given protobuf enum value into an Eco-Score grade, where the unspecified value
+/// maps to `None`.
+///
+/// # Arguments
+/// - `field` - The name of the field for the error message.
+/// - `value` - The raw protobuf enum value.
+fn eco_score_grade_from_proto(
+    field: &'static str,
+    value: i32,
+) -> ProtoResult<Option<EcoScoreGrade>> {
+    match v1::EcoScoreGrade::try_from(value) {
+        Ok(v1::EcoScoreGrade::Unspecified) => Ok(None),
+        Ok(v1::EcoScoreGrade::A) => Ok(Some(EcoScoreGrade::A)),
+        Ok(v1::EcoScoreGrade::B) => Ok(Some(EcoScoreGrade::B)),
+        Ok(v1::EcoScoreGrade::C) => Ok(Some(EcoScoreGrade::C)),
+        Ok(v1::EcoScoreGrade::D) => Ok(Some(EcoScoreGrade::D)),
+        Ok(v1::EcoScoreGrade::E) => Ok(Some(EcoScoreGrade::E)),
+        Err(_) => Err(invalid_enum(field, value)),
+    }
+}
+
+impl From<EcoScore> for v1::EcoScore {
+    fn from(value: EcoScore) -> Self {
+        Self {
+            grade: v1::EcoScoreGrade::from(value.grade) as i32,
+            value: value.value,
+            source: value.source,
+        }
+    }
+}
+
+impl TryFrom<v1::EcoScore> for EcoScore {
+    type Error = ProtoConversionError;
+
+    fn try_from(value: v1::EcoScore) -> ProtoResult<Self> {
+        Ok(Self {
+            grade: eco_score_grade_from_proto("grade", value.grade)?
+                .ok_or(ProtoConversionError::MissingField("grade"))?,
+            value: value.value,
+            source: value.source,
+        })
+    }
+}
+
+impl From<ProductDescription> for v1::ProductDescription {
+    fn from(value: ProductDescription) -> Self {
+        Self {
+            info: Some(value.info.into()),
+            preview: value.preview.map(Into::into),
+            full_image: value.full_image.map(Into::into),
+            nutrients: Some(value.nutrients.into()),
+            packaging: value.packaging.map(Into::into),
+            eco_score: value.eco_score.map(Into::into),
+        }
+    }
+}
+
+impl TryFrom<v1::ProductDescription> for ProductDescription {
+    type Error = ProtoConversionError;
+
+    fn try_from(value: v1::ProductDescription) -> ProtoResult<Self> {
+        Ok(Self {
+            info: value
+                .info
+                .ok_or(ProtoConversionError::MissingField("info"))?
+                .try_into()?,
+            preview: value.preview.map(Into::into),
+            full_image: value.full_image.map(Into::into),
+            nutrients: value
+                .nutrients
+                .ok_or(ProtoConversionError::MissingField("nutrients"))?
+                .into(),
+            packaging: value.packaging.map(TryInto::try_into).transpose()?,
+            eco_score: value.eco_score.map(TryInto::try_into).transpose()?,
+        })
+    }
+}
+
+impl From<RequestStatus> for v1::RequestStatus {
+    fn from(value: RequestStatus) -> Self {
+        match value {
+            RequestStatus::Pending => v1::RequestStatus::Pending,
+            RequestStatus::Approved => v1::RequestStatus::Approved,
+            RequestStatus::Rejected => v1::RequestStatus::Rejected,
+        }
+    }
+}
+
+/// Converts the given protobuf enum value into a review status, where the unspecified value
+/// maps to `None`.
+///
+/// # Arguments
+/// - `field` - The name of the field for the error message.
+/// - `value` - The raw protobuf enum value.
+fn request_status_from_proto(
+    field: &'static str,
+    value: i32,
+) -> ProtoResult<Option<RequestStatus>> {
+    match v1::RequestStatus::try_from(value) {
+        Ok(v1::RequestStatus::Unspecified) => Ok(None),
+        Ok(v1::RequestStatus::Pending) => Ok(Some(RequestStatus::Pending)),
+        Ok(v1::RequestStatus::Approved) => Ok(Some(RequestStatus::Approved)),
+        Ok(v1::RequestStatus::Rejected) => Ok(Some(RequestStatus::Rejected)),
+        Err(_) => Err(invalid_enum(field, value)),
+    }
+}
+
+/// Converts the given protobuf enum value into a quantity type filter, where the unspecified
+/// value maps to `None`.
+///
+/// # Arguments
+/// - `field` - The name of the field for the error message.
+/// - `value` - The raw protobuf enum value.
+fn quantity_type_from_proto(field: &'static str, value: i32) -> ProtoResult<Option<QuantityType>> {
+    match v1::QuantityType::try_from(value) {
+        Ok(v1::QuantityType::Unspecified) => Ok(None),
+        Ok(v1::QuantityType::Weight) => Ok(Some(QuantityType::Weight)),
+        Ok(v1::QuantityType::Volume) => Ok(Some(QuantityType::Volume)),
+        Err(_) => Err(invalid_enum(field, value)),
+    }
+}
+
+impl From<ProductRequest> for v1::ProductRequest {
+    fn from(value: ProductRequest) -> Self {
+        Self {
+            product_description: Some(value.product_description.into()),
+            date: Some(to_timestamp(value.date)),
+            status: v1::RequestStatus::from(value.status) as i32,
+            review_comment: value.review_comment,
+        }
+    }
+}
+
+impl TryFrom<v1::ProductRequest> for ProductRequest {
+    type Error = ProtoConversionError;
+
+    fn try_from(value: v1::ProductRequest) -> ProtoResult<Self> {
+        Ok(Self {
+            product_description: value
+                .product_description
+                .ok_or(ProtoConversionError::MissingField("product_description"))?
+                .try_into()?,
+            date: from_timestamp("date", value.date)?,
+            status: request_status_from_proto("status", value.status)?.unwrap_or_default(),
+            review_comment: value.review_comment,
+        })
+    }
+}
+
+impl From<MissingProduct> for v1::MissingProduct {
+    fn from(value: MissingProduct) -> Self {
+        Self {
+            product_id: value.product_id,
+            date: Some(to_timestamp(value.date)),
+            store_id: value.store_id,
+        }
+    }
+}
+
+impl TryFrom<v1::MissingProduct> for MissingProduct {
+    type Error = ProtoConversionError;
+
+    fn try_from(value: v1::MissingProduct) -> ProtoResult<Self> {
+        Ok(Self {
+            product_id: value.product_id,
+            date: from_timestamp("date", value.date)?,
+            store_id: value.store_id,
+        })
+    }
+}
+
+impl From<SortingOrder> for v1::SortingOrder {
+    fn from(value: SortingOrder) -> Self {
+        match value {
+            SortingOrder::Ascending => v1::SortingOrder::Ascending,
+            SortingOrder::Descending => v1::SortingOrder::Descending,
+        }
+    }
+}
+
+/// Converts the raw value of a sorting order field.
+///
+/// # Arguments
+/// - `field` - The name of the field, used for the error.
+/// - `value` - The raw value of the field.
+fn sorting_order(field: &'static str, value: i32) -> ProtoResult<SortingOrder> {
+    match v1::SortingOrder::try_from(value) {
+        Ok(v1::SortingOrder::Ascending) => Ok(SortingOrder::Ascending),
+        Ok(v1::SortingOrder::Descending) => Ok(SortingOrder::Descending),
+        _ => Err(invalid_enum(field, value)),
+    }
+}
+
+impl From<SortingField> for v1::SortingField {
+    fn from(value: SortingField) -> Self {
+        match value {
+            SortingField::ReportedDate => v1::SortingField::ReportedDate,
+            SortingField::Name => v1::SortingField::Name,
+            SortingField::ProductID => v1::SortingField::ProductId,
+            SortingField::Similarity => v1::SortingField::Similarity,
+            SortingField::NovaGroup => v1::SortingField::NovaGroup,
+        }
+    }
+}
+
+impl From<Sorting> for v1::Sorting {
+    fn from(value: Sorting) -> Self {
+        Self {
+            order: v1::SortingOrder::from(value.order) as i32,
+            field: v1::SortingField::from(value.field) as i32,
+        }
+    }
+}
+
+impl TryFrom<v1::Sorting> for Sorting {
+    type Error = ProtoConversionError;
+
+    fn try_from(value: v1::Sorting) -> ProtoResult<Self> {
+        let field = match v1::SortingField::try_from(value.field) {
+            Ok(v1::SortingField::ReportedDate) => SortingField::ReportedDate,
+            Ok(v1::SortingField::Name) => SortingField::Name,
+            Ok(v1::SortingField::ProductId) => SortingField::ProductID,
+            Ok(v1::SortingField::Similarity) => SortingField::Similarity,
+            Ok(v1::SortingField::NovaGroup) => SortingField::NovaGroup,
+            _ => return Err(invalid_enum("sorting.field", value.field)),
+        };
+
+        Ok(Self {
+            order: sorting_order("sorting.order", value.order)?,
+            field,
+        })
+    }
+}
+
+impl From<ProductQuery> for v1::ProductQuery {
+    fn from(value: ProductQuery) -> Self {
+        let filter = match value.filter {
+            SearchFilter::NoFilter => None,
+            SearchFilter::Search(search) => Some(v1::product_query::Filter::Search(search)),
+            SearchFilter::ProductID(id) => Some(v1::product_query::Filter::ProductId(id)),
+            SearchFilter::Store(id) => Some(v1::product_query::Filter::StoreId(id)),
+        };
+
+        Self {
+            offset: value.offset,
+            limit: value.limit,
+            filter,
+            sorting: value.sorting.map(Into::into),
+            market: value.market,
+            producer: value.producer,
+            quantity_type: value
+                .quantity_type
+                .map(|quantity_type| v1::QuantityType::from(quantity_type) as i32)
+                .unwrap_or_default(),
+            labels: value.labels.map(Into::into),
+            max_nova_group: value.max_nova_group,
+            min_eco_score_grade: value
+                .min_eco_score_grade
+                .map(|grade| v1::EcoScoreGrade::from(grade) as i32)
+                .unwrap_or_default(),
+            exclude_allergens: allergens_to_proto(value.exclude_allergens),
+            data_issues: value
+                .data_issues
+                .into_iter()
+                .map(|issue| v1::ProductDataIssue::from(issue) as i32)
+                .collect(),
+            fields: value
+                .fields
+                .unwrap_or_default()
+                .iter()
+                .map(ToString::to_string)
+                .collect(),
+            request_status: value
+                .request_status
+                .map(|status| v1::RequestStatus::from(status) as i32)
+                .unwrap_or_default(),
+            cursor: value.cursor,
+        }
+    }
+}
+
+impl TryFrom<v1::ProductQuery> for ProductQuery {
+    type Error = ProtoConversionError;
+
+    fn try_from(value: v1::ProductQuery) -> ProtoResult<Self> {
+        let filter = match value.filter {
+            None => SearchFilter::NoFilter,
+            Some(v1::product_query::Filter::Search(search)) => SearchFilter::Search(search),
+            Some(v1::product_query::Filter::ProductId(id)) => SearchFilter::ProductID(id),
+            Some(v1::product_query::Filter::StoreId(id)) => SearchFilter::Store(id),
+        };
+
+        Ok(Self {
+            offset: value.offset,
+            limit: value.limit,
+            filter,
+            sorting: value.sorting.map(TryInto::try_into).transpose()?,
+            market: value.market,
+            producer: value.producer,
+            quantity_type: quantity_type_from_proto("quantity_type", value.quantity_type)?,
+            labels: value.labels.map(Into::into),
+            max_nova_group: value.max_nova_group,
+            min_eco_score_grade: eco_score_grade_from_proto(
+                "min_eco_score_grade",
+                value.min_eco_score_grade,
+            )?,
+            exclude_allergens: allergens_from_proto("exclude_allergens", value.exclude_allergens)?,
+            data_issues: data_issues_from_proto("data_issues", value.data_issues)?,
+            fields: product_fields_from_proto("fields", value.fields)?,
+            request_status: request_status_from_proto("request_status", value.request_status)?,
+            cursor: value.cursor,
+        })
+    }
+}
+
+impl From<MissingProductQuery> for v1::MissingProductQuery {
+    fn from(value: MissingProductQuery) -> Self {
+        Self {
+            offset: value.offset,
+            limit: value.limit,
+            product_id: value.product_id,
+            order: v1::SortingOrder::from(value.order) as i32,
+        }
+    }
+}
+
+impl TryFrom<v1::MissingProductQuery> for MissingProductQuery {
+    type Error = ProtoConversionError;
+
+    fn try_from(value: v1::MissingProductQuery) -> ProtoResult<Self> {
+        Ok(Self {
+            offset: value.offset,
+            limit: value.limit,
+            product_id: value.product_id,
+            order: sorting_order("order", value.order)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use prost::Message;
+
+    use super::*;
+
+    #[test]
+    fn test_product_description_roundtrip() {
+        let product_data = include_str!("../../test_data/products.json");
+        let products: Vec<ProductDescription> = serde_json::from_str(product_data).unwrap();
+
+        for product in products {
+            let encoded = v1::ProductDescription::from(product.clone()).encode_to_vec();
+            let decoded = v1::ProductDescription::decode(encoded.as_slice()).unwrap();
+
+            assert_eq!(ProductDescription::try_from(decoded).unwrap(), product);
+        }
+    }
+
+    #[test]
+    fn test_product_request_roundtrip() {
+        let product_data = include_str!("../../test_data/products.json");
+        let products: Vec<ProductDescription> = serde_json::from_str(product_data).unwrap();
+
+        let request = ProductRequest {
+            product_description: products[0].clone(),
+            date: Utc::now(),
+            status: RequestStatus::Rejected,
+            review_comment: Some("The photo is blurry".to_string()),
+        };
+        let message = v1::ProductRequest::from(request.clone());
+        assert_eq!(ProductRequest::try_from(message).unwrap(), request);
+
+        let mut message = v1::ProductRequest::from(request);
+        message.date = None;
+        assert_eq!(
+            ProductRequest::try_from(message),
+            Err(ProtoConversionError::MissingField("date"))
+        );
+    }
+
+    #[test]
+    fn test_query_roundtrip() {
+        let queries = [
+            ProductQuery {
+                offset: 5,
+                limit: 10,
+                filter: SearchFilter::NoFilter,
+                sorting: None,
+                market: None,
+                producer: None,
+                quantity_type: None,
+                labels: None,
+                max_nova_group: None,
+                min_eco_score_grade: None,
+                exclude_allergens: Vec::new(),
+                data_issues: Vec::new(),
+                fields: None,
+                request_status: None,
+                cursor: None,
+            },
+            ProductQuery {
+                offset: 0,
+                limit: 20,
+                filter: SearchFilter::Search("milk".to_string()),
+                sorting: Some(Sorting {
+                    order: SortingOrder::Descending,
+                    field: SortingField::Similarity,
+                }),
+                market: None,
+                producer: None,
+                quantity_type: None,
+                labels: None,
+                max_nova_group: None,
+                min_eco_score_grade: None,
+                exclude_allergens: Vec::new(),
+                data_issues: Vec::new(),
+                fields: None,
+                request_status: None,
+                cursor: None,
+            },
+            ProductQuery {
+                offset: 0,
+                limit: 1,
+                filter: SearchFilter::ProductID("4001724819806".to_string()),
+                sorting: Some(Sorting {
+                    order: SortingOrder::Ascending,
+                    field: SortingField::ProductID,
+                }),
+                market: None,
+                producer: None,
+                quantity_type: None,
+                labels: None,
+                max_nova_group: None,
+                min_eco_score_grade: None,
+                exclude_allergens: Vec::new(),
+                data_issues: Vec::new(),
+                fields: None,
+                request_status: None,
+                cursor: None,
+            },
+            ProductQuery {
+                offset: 0,
+                limit: 10,
+                filter: SearchFilter::Store(7),
+                sorting: Some(Sorting {
+                    order: SortingOrder::Ascending,
+                    field: SortingField::NovaGroup,
+                }),
+                market: Some("DE".to_string()),
+                producer: Some("alpro*".to_string()),
+                quantity_type: Some(QuantityType::Volume),
+                labels: Some(DietaryLabels {
+                    vegan: Some(true),
+                    gluten_free: Some(false),
+                    ..Default::default()
+                }),
+                max_nova_group: Some(2),
+                min_eco_score_grade: Some(EcoScoreGrade::B),
+                exclude_allergens: vec![Allergen::Nuts, Allergen::Gluten],
+                data_issues: vec![
+                    ProductDataIssue::MissingImage,
+                    ProductDataIssue::MissingProducer,
+                ],
+                fields: Some(vec![
+                    ProductField::Id,
+                    ProductField::Name,
+                    ProductField::Kcal,
+                ]),
+                request_status: Some(RequestStatus::Rejected),
+                cursor: Some("7b7d".to_string()),
+            },
+        ];
+
+        for query in queries {
+            let message = v1::ProductQuery::from(query.clone());
+            assert_eq!(ProductQuery::try_from(message).unwrap(), query);
+        }
+
+        let query = MissingProductQuery {
+            offset: 0,
+            limit: 10,
+            product_id: None,
+            order: SortingOrder::Ascending,
+        };
+        let mut message = v1::MissingProductQuery::from(query.clone());
+        assert_eq!(
+            MissingProductQuery::try_from(message.clone()).unwrap(),
+            query
+        );
+
+        // unspecified enum values are rejected
+        message.order = v1::SortingOrder::Unspecified as i32;
+        assert!(MissingProductQuery::try_from(message).is_err());
+    }
+}
@@ -0,0 +1,289 @@
+use std::{
+    collections::HashMap,
+    net::{IpAddr, SocketAddr},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use axum::{
+    body::HttpBody,
+    extract::{ConnectInfo, OriginalUri, Request, State},
+    http::{header, HeaderValue, StatusCode},
+    middleware::Next,
+    response::Response,
+};
+use log::warn;
+
+use crate::{
+    ip_allowlist::client_address, layers::problem_response, Error, RateLimitOptions, Result,
+};
+
+/// The number of tracked clients above which the clients with a full budget are forgotten.
+const MAX_TRACKED_CLIENTS: usize = 10_000;
+
+/// The remaining budget of a client, i.e., the tokens in its request and bandwidth buckets.
+#[derive(Debug, Clone, Copy)]
+struct Budget {
+    requests: f64,
+    bytes: f64,
+    updated: Instant,
+}
+
+/// Limits the requests and the response bytes of each client with token buckets. Requests
+/// whose client address is unknown share a single budget.
+#[derive(Debug)]
+pub struct RateLimiter {
+    requests_per_second: f64,
+    burst: f64,
+    bytes_per_second: Option<f64>,
+    burst_bytes: f64,
+    trusted_proxy_depth: usize,
+    clients: Mutex<HashMap<Option<IpAddr>, Budget>>,
+}
+
+impl RateLimiter {
+    /// Creates the rate limiter from the options.
+    ///
+    /// # Arguments
+    /// - `options` - The options of the rate limiter.
+    pub fn from_options(options: &RateLimitOptions) -> Result<Self> {
+        if !options.requests_per_second.is_finite() || options.requests_per_second <= 0.0 {
+            return Err(Error::InvalidConfigError(format!(
+                "the requests per second of the rate limit must be positive, got {}",
+                options.requests_per_second
+            )));
+        }
+        if options.burst == 0 {
+            return Err(Error::InvalidConfigError(
+                "the burst of the rate limit must allow at least one request".to_string(),
+            ));
+        }
+        if options.bytes_per_second == Some(0) || options.burst_bytes == Some(0) {
+            return Err(Error::InvalidConfigError(
+                "the bandwidth of the rate limit must be positive".to_string(),
+            ));
+        }
+
+        let bytes_per_second = options.bytes_per_second.map(|bytes| bytes as f64);
+        let burst_bytes = options
+            .burst_bytes
+            .map(|bytes| bytes as f64)
+            .or(bytes_per_second)
+            .unwrap_or_default();
+
+        Ok(Self {
+            requests_per_second: options.requests_per_second,
+            burst: options.burst as f64,
+            bytes_per_second,
+            burst_bytes,
+            trusted_proxy_depth: options.trusted_proxy_depth,
+            clients: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// Takes a request from the budget of the client. Returns the duration after which the
+    /// client may retry if its budget is exhausted, either by requests or by response bytes.
+    ///
+    /// # Arguments
+    /// - `client` - The address of the client, if known.
+    /// - `now` - The current point in time.
+    pub fn acquire(
+        &self,
+        client: Option<IpAddr>,
+        now: Instant,
+    ) -> std::result::Result<(), Duration> {
+        let mut clients = self.clients.lock().unwrap();
+        if clients.len() >= MAX_TRACKED_CLIENTS {
+            clients.retain(|_, budget| !self.is_full(&self.refilled(*budget, now)));
+        }
+
+        let budget = clients
+            .entry(client)
+            .or_insert_with(|| self.full_budget(now));
+        *budget = self.refilled(*budget, now);
+
+        if budget.requests < 1.0 {
+            let missing = 1.0 - budget.requests;
+            return Err(Duration::from_secs_f64(missing / self.requests_per_second));
+        }
+        if let Some(bytes_per_second) = self.bytes_per_second {
+            if budget.bytes <= 0.0 {
+                // wait until a single byte is available again
+                let missing = 1.0 - budget.bytes;
+                return Err(Duration::from_secs_f64(missing / bytes_per_second));
+            }
+        }
+
+        budget.requests -= 1.0;
+        Ok(())
+    }
+
+    /// Charges the bytes of a response to the budget of the client. The budget may become
+    /// negative, i.e., after a large response the client has to wait until it is paid off.
+    ///
+    /// # Arguments
+    /// - `client` - The address of the client, if known.
+    /// - `bytes` - The number of bytes of the response.
+    /// - `now` - The current point in time.
+    pub fn charge(&self, client: Option<IpAddr>, bytes: u64, now: Instant) {
+        if self.bytes_per_second.is_none() {
+            return;
+        }
+
+        let mut clients = self.clients.lock().unwrap();
+        let budget = clients
+            .entry(client)
+            .or_insert_with(|| self.full_budget(now));
+        *budget = self.refilled(*budget, now);
+        budget.bytes -= bytes as f64;
+    }
+
+    /// Returns the budget of a client that has not sent any request yet.
+    ///
+    /// # Arguments
+    /// - `now` - The current point in time.
+    fn full_budget(&self, now: Instant) -> Budget {
+        Budget {
+            requests: self.burst,
+            bytes: self.burst_bytes,
+            updated: now,
+        }
+    }
+
+    /// Returns the given budget refilled by the time that has passed since its last update.
+    ///
+    /// # Arguments
+    /// - `budget` - The budget to refill.
+    /// - `now` - The current point in time.
+    fn refilled(&self, budget: Budget, now: Instant) -> Budget {
+        let elapsed = now.saturating_duration_since(budget.updated).as_secs_f64();
+        let bytes_per_second = self.bytes_per_second.unwrap_or_default();
+
+        Budget {
+            requests: (budget.requests + elapsed * self.requests_per_second).min(self.burst),
+            bytes: (budget.bytes + elapsed * bytes_per_second).min(self.burst_bytes),
+            updated: now,
+        }
+    }
+
+    /// Returns true if the budget is the one of a client that has not sent any request yet.
+    ///
+    /// # Arguments
+    /// - `budget` - The budget to check.
+    fn is_full(&self, budget: &Budget) -> bool {
+        budget.requests >= self.burst && budget.bytes >= self.burst_bytes
+    }
+}
+
+/// Middleware that rejects the requests of clients that exhausted their budget with 429 and a
+/// `Retry-After` header. The bytes of the responses are charged to the budget of the client.
+/// Does nothing if no rate limiter is configured.
+pub async fn rate_limit_guard(
+    State(limiter): State<Option<Arc<RateLimiter>>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let Some(limiter) = limiter else {
+        return next.run(request).await;
+    };
+
+    let client = request
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .and_then(|ConnectInfo(peer)| {
+            client_address(peer.ip(), request.headers(), limiter.trusted_proxy_depth)
+        });
+
+    if let Err(retry_after) = limiter.acquire(client, Instant::now()) {
+        let uri = request
+            .extensions()
+            .get::<OriginalUri>()
+            .map(|uri| uri.0.clone())
+            .unwrap_or_else(|| request.uri().clone());
+        warn!(
+            "Rejected {} {} of {:?} due to the rate limit",
+            request.method(),
+            uri,
+            client
+        );
+
+        let retry_after = retry_after.as_secs_f64().ceil().max(1.0) as u64;
+        let mut response = problem_response(
+            StatusCode::TOO_MANY_REQUESTS,
+            format!(
+                "The rate limit has been exceeded, retry after {}s",
+                retry_after
+            ),
+        );
+        response
+            .headers_mut()
+            .insert(header::RETRY_AFTER, HeaderValue::from(retry_after));
+
+        return response;
+    }
+
+    let response = next.run(request).await;
+    if let Some(bytes) = response.body().size_hint().exact() {
+        limiter.charge(client, bytes, Instant::now());
+    }
+
+    response
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_rate_limiter() {
+        let limiter = RateLimiter::from_options(&RateLimitOptions {
+            requests_per_second: 2.0,
+            burst: 2,
+            bytes_per_second: Some(1000),
+            burst_bytes: None,
+            trusted_proxy_depth: 0,
+        })
+        .unwrap();
+
+        let client: Option<IpAddr> = Some("10.1.2.3".parse().unwrap());
+        let other: Option<IpAddr> = Some("10.1.2.4".parse().unwrap());
+        let now = Instant::now();
+
+        // the burst is available at once
+        assert!(limiter.acquire(client, now).is_ok());
+        assert!(limiter.acquire(client, now).is_ok());
+        let retry_after = limiter.acquire(client, now).unwrap_err();
+        assert_eq!(retry_after, Duration::from_millis(500));
+
+        // the other clients have their own budget
+        assert!(limiter.acquire(other, now).is_ok());
+
+        // the budget is refilled over time
+        let now = now + Duration::from_millis(500);
+        assert!(limiter.acquire(client, now).is_ok());
+        assert!(limiter.acquire(client, now).is_err());
+
+        // a large response must be paid off before the next request
+        let now = now + Duration::from_secs(1);
+        limiter.charge(client, 3000, now);
+        let retry_after = limiter.acquire(client, now).unwrap_err();
+        assert!((retry_after.as_secs_f64() - 2.001).abs() < 1e-6);
+        assert!(limiter.acquire(client, now + retry_after).is_ok());
+
+        // invalid options are rejected
+        let options = RateLimitOptions {
+            requests_per_second: 0.0,
+            burst: 1,
+            bytes_per_second: None,
+            burst_bytes: None,
+            trusted_proxy_depth: 0,
+        };
+        assert!(RateLimiter::from_options(&options).is_err());
+        assert!(RateLimiter::from_options(&RateLimitOptions {
+            requests_per_second: 1.0,
+            burst: 0,
+            ..options
+        })
+        .is_err());
+    }
+}
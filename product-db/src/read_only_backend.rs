@@ -0,0 +1,336 @@
+use crate::{
+    AdditionalImage, ArchiveQuery, ArchivedProductRequest, CountedProductRequests, CountedProducts,
+    DBId, DataBackend, Error, HealthReport, ImageStorageUsage, MissingProduct, MissingProductQuery,
+    MissingProductSummary, Options, PriceObservation, PriceQuery, ProductAvailability,
+    ProductDescription, ProductID, ProductImage, ProductQuery, ProductRequest, ReadBackend, Recipe,
+    RequestOutcome, Result, Secret, Store, StoreQuery, WriteBackend,
+};
+
+/// A data backend that only serves the read operations of the wrapped [`ReadBackend`], e.g. of
+/// a read replica. All write operations fail with [`Error::ReadOnlyBackend`].
+/// See [`crate::Service::read_only`] for serving a read-only backend.
+pub struct ReadOnlyBackend<R: ReadBackend> {
+    inner: R,
+}
+
+impl<R: ReadBackend> ReadOnlyBackend<R> {
+    /// Creates a new read-only backend from the given backend.
+    ///
+    /// # Arguments
+    /// * `backend` - The backend whose read operations are served.
+    pub fn new(backend: R) -> Self {
+        Self { inner: backend }
+    }
+
+    /// Returns the wrapped backend.
+    pub fn inner(&self) -> &R {
+        &self.inner
+    }
+
+    /// Unwraps the backend.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R: ReadBackend> DataBackend for ReadOnlyBackend<R> {
+    async fn new(_options: &Options) -> Result<Self> {
+        Err(Error::InvalidConfigError(
+            "a read-only backend cannot be created from the options, pass the backend to Service::read_only instead".to_string(),
+        ))
+    }
+}
+
+impl<R: ReadBackend> ReadBackend for ReadOnlyBackend<R> {
+    async fn query_missing_products(
+        &self,
+        query: &MissingProductQuery,
+    ) -> Result<Vec<(DBId, MissingProduct)>> {
+        self.inner.query_missing_products(query).await
+    }
+
+    async fn count_missing_products(&self, query: &MissingProductQuery) -> Result<u64> {
+        self.inner.count_missing_products(query).await
+    }
+
+    async fn summarize_missing_products(
+        &self,
+        offset: i32,
+        limit: i32,
+    ) -> Result<Vec<MissingProductSummary>> {
+        self.inner.summarize_missing_products(offset, limit).await
+    }
+
+    async fn get_missing_product(&self, id: DBId) -> Result<Option<MissingProduct>> {
+        self.inner.get_missing_product(id).await
+    }
+
+    async fn get_missing_product_photo(&self, id: DBId) -> Result<Option<ProductImage>> {
+        self.inner.get_missing_product_photo(id).await
+    }
+
+    async fn get_product_request(
+        &self,
+        id: DBId,
+        with_preview: bool,
+    ) -> Result<Option<ProductRequest>> {
+        self.inner.get_product_request(id, with_preview).await
+    }
+
+    async fn get_product_request_image(&self, id: DBId) -> Result<Option<ProductImage>> {
+        self.inner.get_product_request_image(id).await
+    }
+
+    async fn get_product_request_images(&self, id: DBId) -> Result<Option<Vec<AdditionalImage>>> {
+        self.inner.get_product_request_images(id).await
+    }
+
+    async fn query_archived_product_requests(
+        &self,
+        query: &ArchiveQuery,
+    ) -> Result<Vec<ArchivedProductRequest>> {
+        self.inner.query_archived_product_requests(query).await
+    }
+
+    async fn get_product(
+        &self,
+        id: &ProductID,
+        with_preview: bool,
+    ) -> Result<Option<ProductDescription>> {
+        self.inner.get_product(id, with_preview).await
+    }
+
+    async fn get_products(&self, ids: &[ProductID]) -> Result<Vec<ProductDescription>> {
+        self.inner.get_products(ids).await
+    }
+
+    async fn get_product_image(&self, id: &ProductID) -> Result<Option<ProductImage>> {
+        self.inner.get_product_image(id).await
+    }
+
+    async fn get_product_images(&self, id: &ProductID) -> Result<Option<Vec<AdditionalImage>>> {
+        self.inner.get_product_images(id).await
+    }
+
+    async fn get_recipe(&self, id: DBId) -> Result<Option<Recipe>> {
+        self.inner.get_recipe(id).await
+    }
+
+    async fn query_product_requests(
+        &self,
+        query: &ProductQuery,
+        with_preview: bool,
+    ) -> Result<Vec<(DBId, ProductRequest)>> {
+        self.inner.query_product_requests(query, with_preview).await
+    }
+
+    async fn query_products(
+        &self,
+        query: &ProductQuery,
+        with_preview: bool,
+    ) -> Result<Vec<ProductDescription>> {
+        self.inner.query_products(query, with_preview).await
+    }
+
+    async fn query_scored_products(
+        &self,
+        query: &ProductQuery,
+        with_preview: bool,
+    ) -> Result<Vec<(ProductDescription, f32)>> {
+        self.inner.query_scored_products(query, with_preview).await
+    }
+
+    async fn count_products(&self, query: &ProductQuery) -> Result<u64> {
+        self.inner.count_products(query).await
+    }
+
+    async fn count_product_requests(&self, query: &ProductQuery) -> Result<u64> {
+        self.inner.count_product_requests(query).await
+    }
+
+    async fn query_counted_products(
+        &self,
+        query: &ProductQuery,
+        with_preview: bool,
+    ) -> Result<CountedProducts> {
+        self.inner.query_counted_products(query, with_preview).await
+    }
+
+    async fn query_counted_product_requests(
+        &self,
+        query: &ProductQuery,
+        with_preview: bool,
+    ) -> Result<CountedProductRequests> {
+        self.inner
+            .query_counted_product_requests(query, with_preview)
+            .await
+    }
+
+    async fn query_prices(&self, query: &PriceQuery) -> Result<Vec<(DBId, PriceObservation)>> {
+        self.inner.query_prices(query).await
+    }
+
+    async fn cheapest_price(
+        &self,
+        product_id: &ProductID,
+        currency: &str,
+    ) -> Result<Option<PriceObservation>> {
+        self.inner.cheapest_price(product_id, currency).await
+    }
+
+    async fn get_store(&self, id: DBId) -> Result<Option<Store>> {
+        self.inner.get_store(id).await
+    }
+
+    async fn query_stores(&self, query: &StoreQuery) -> Result<Vec<(DBId, Store)>> {
+        self.inner.query_stores(query).await
+    }
+
+    async fn get_product_availability(
+        &self,
+        product_id: &ProductID,
+    ) -> Result<Vec<ProductAvailability>> {
+        self.inner.get_product_availability(product_id).await
+    }
+
+    async fn image_storage_usage(&self) -> Result<Vec<ImageStorageUsage>> {
+        self.inner.image_storage_usage().await
+    }
+
+    async fn reconnect(&self, password: Secret) -> Result<()> {
+        self.inner.reconnect(password).await
+    }
+
+    async fn health_check(&self) -> HealthReport {
+        self.inner.health_check().await
+    }
+
+    async fn ping(&self) -> Result<()> {
+        self.inner.ping().await
+    }
+
+    fn record_metrics(&self) {
+        self.inner.record_metrics()
+    }
+}
+
+impl<R: ReadBackend> WriteBackend for ReadOnlyBackend<R> {
+    async fn report_missing_product(&self, _missing_product: MissingProduct) -> Result<DBId> {
+        Err(Error::ReadOnlyBackend)
+    }
+
+    async fn report_missing_products(
+        &self,
+        _missing_products: Vec<MissingProduct>,
+    ) -> Result<Vec<DBId>> {
+        Err(Error::ReadOnlyBackend)
+    }
+
+    async fn delete_reported_missing_product(&self, _id: DBId) -> Result<()> {
+        Err(Error::ReadOnlyBackend)
+    }
+
+    async fn set_missing_product_photo(&self, _id: DBId, _photo: &ProductImage) -> Result<bool> {
+        Err(Error::ReadOnlyBackend)
+    }
+
+    async fn request_new_product(&self, _requested_product: &ProductRequest) -> Result<DBId> {
+        Err(Error::ReadOnlyBackend)
+    }
+
+    async fn delete_requested_product(&self, _id: DBId) -> Result<()> {
+        Err(Error::ReadOnlyBackend)
+    }
+
+    async fn archive_product_request(&self, _id: DBId, _outcome: RequestOutcome) -> Result<bool> {
+        Err(Error::ReadOnlyBackend)
+    }
+
+    async fn reject_product_request(&self, _id: DBId, _comment: Option<&str>) -> Result<bool> {
+        Err(Error::ReadOnlyBackend)
+    }
+
+    async fn archive_approved_product_requests(&self) -> Result<u64> {
+        Err(Error::ReadOnlyBackend)
+    }
+
+    async fn approve_product_request(&self, _id: DBId) -> Result<Option<ProductID>> {
+        Err(Error::ReadOnlyBackend)
+    }
+
+    async fn update_product(&self, _product_desc: &ProductDescription) -> Result<bool> {
+        Err(Error::ReadOnlyBackend)
+    }
+
+    async fn new_product(&self, _product_desc: &ProductDescription) -> Result<bool> {
+        Err(Error::ReadOnlyBackend)
+    }
+
+    async fn new_products(&self, _products: &[ProductDescription]) -> Result<u64> {
+        Err(Error::ReadOnlyBackend)
+    }
+
+    async fn add_product_request_images(
+        &self,
+        _id: DBId,
+        _images: &[AdditionalImage],
+    ) -> Result<bool> {
+        Err(Error::ReadOnlyBackend)
+    }
+
+    async fn add_product_images(
+        &self,
+        _id: &ProductID,
+        _images: &[AdditionalImage],
+    ) -> Result<bool> {
+        Err(Error::ReadOnlyBackend)
+    }
+
+    async fn delete_product(&self, _id: &ProductID) -> Result<()> {
+        Err(Error::ReadOnlyBackend)
+    }
+
+    async fn update_product_preview(
+        &self,
+        _id: &ProductID,
+        _preview: &ProductImage,
+    ) -> Result<bool> {
+        Err(Error::ReadOnlyBackend)
+    }
+
+    async fn update_product_request_preview(
+        &self,
+        _id: DBId,
+        _preview: &ProductImage,
+    ) -> Result<bool> {
+        Err(Error::ReadOnlyBackend)
+    }
+
+    async fn new_recipe(&self, _recipe: &Recipe) -> Result<DBId> {
+        Err(Error::ReadOnlyBackend)
+    }
+
+    async fn update_recipe(&self, _id: DBId, _recipe: &Recipe) -> Result<bool> {
+        Err(Error::ReadOnlyBackend)
+    }
+
+    async fn delete_recipe(&self, _id: DBId) -> Result<()> {
+        Err(Error::ReadOnlyBackend)
+    }
+
+    async fn submit_price(&self, _price: &PriceObservation) -> Result<DBId> {
+        Err(Error::ReadOnlyBackend)
+    }
+
+    async fn new_store(&self, _store: &Store) -> Result<DBId> {
+        Err(Error::ReadOnlyBackend)
+    }
+
+    async fn delete_store(&self, _id: DBId) -> Result<()> {
+        Err(Error::ReadOnlyBackend)
+    }
+
+    async fn report_availability(&self, _availability: &ProductAvailability) -> Result<bool> {
+        Err(Error::ReadOnlyBackend)
+    }
+}
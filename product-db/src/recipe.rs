@@ -0,0 +1,219 @@
+use std::collections::HashMap;
+
+#[cfg(feature = "service")]
+use log::debug;
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "service")]
+use crate::{nutrition::unique_product_ids, DataBackend, Error, Result, MAX_AGGREGATED_PORTIONS};
+use crate::{
+    sum_nutrients, Nutrients, Portion, ProductDescription, ProductID, ProductImage, QuantityType,
+    Weight,
+};
+
+/// The maximal length of the name of a recipe.
+pub const MAX_RECIPE_NAME_LENGTH: usize = 64;
+
+/// A recipe composed of products, e.g. a pancake dough made of flour, milk and eggs.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct Recipe {
+    /// The name of the recipe.
+    pub name: String,
+
+    /// The number of portions the recipe yields.
+    pub servings: i32,
+
+    /// The products and their amounts in g or ml the recipe is composed of.
+    pub ingredients: Vec<Portion>,
+
+    /// An image of the recipe.
+    #[serde(default)]
+    pub image: Option<ProductImage>,
+}
+
+/// The nutrients of a recipe derived from the nutrients of its ingredients.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct RecipeNutrients {
+    /// The nutrients per 100g of the recipe.
+    pub per_100g: Nutrients,
+
+    /// The nutrients of a single portion of the recipe.
+    pub per_portion: Nutrients,
+
+    /// The total weight of the known ingredients. Volumes are converted by the volume weight
+    /// ratio of the product if defined and are taken as gram otherwise.
+    pub total_weight: Weight,
+
+    /// The ids of the ingredients that are not in the database and have not been summed up.
+    pub missing_products: Vec<ProductID>,
+}
+
+/// Computes the nutrients of the recipe from the nutrients of its ingredients.
+///
+/// # Arguments
+/// - `recipe` - The recipe to compute the nutrients for.
+/// - `products` - The products referenced by the ingredients of the recipe.
+pub fn recipe_nutrients(recipe: &Recipe, products: &[ProductDescription]) -> RecipeNutrients {
+    let aggregation = sum_nutrients(products, &recipe.ingredients);
+
+    let products: HashMap<&ProductID, &ProductDescription> =
+        products.iter().map(|p| (&p.info.id, p)).collect();
+    let total_gram: f32 = recipe
+        .ingredients
+        .iter()
+        .filter_map(|ingredient| {
+            let product = products.get(&ingredient.product_id)?;
+            match (product.info.quantity_type, product.info.volume_weight_ratio) {
+                (QuantityType::Volume, Some(ratio)) if ratio > 0.0 => {
+                    Some(ingredient.amount / ratio)
+                }
+                _ => Some(ingredient.amount),
+            }
+        })
+        .sum();
+
+    let per_100g = if total_gram > 0.0 {
+        aggregation.nutrients.scale(100.0 / total_gram)
+    } else {
+        Nutrients::default()
+    };
+    let per_portion = aggregation
+        .nutrients
+        .scale(1.0 / recipe.servings.max(1) as f32);
+
+    RecipeNutrients {
+        per_100g,
+        per_portion,
+        total_weight: Weight::new_from_gram(total_gram),
+        missing_products: aggregation.missing_products,
+    }
+}
+
+/// Checks that the recipe has a name, at least one serving and valid ingredient amounts.
+///
+/// # Arguments
+/// - `recipe` - The recipe to check.
+#[cfg(feature = "service")]
+pub fn check_recipe(recipe: &Recipe) -> Result<()> {
+    if recipe.name.trim().is_empty() || recipe.name.chars().count() > MAX_RECIPE_NAME_LENGTH {
+        return Err(Error::InvalidRecipe(format!(
+            "the name must have between 1 and {} characters",
+            MAX_RECIPE_NAME_LENGTH
+        )));
+    }
+
+    if recipe.servings < 1 {
+        return Err(Error::InvalidRecipe(format!(
+            "{} servings, the recipe must yield at least one portion",
+            recipe.servings
+        )));
+    }
+
+    if recipe.ingredients.len() > MAX_AGGREGATED_PORTIONS {
+        return Err(Error::InvalidRecipe(format!(
+            "{} ingredients exceed the maximum of {}",
+            recipe.ingredients.len(),
+            MAX_AGGREGATED_PORTIONS
+        )));
+    }
+
+    if let Some(ingredient) = recipe
+        .ingredients
+        .iter()
+        .find(|i| !i.amount.is_finite() || i.amount <= 0.0)
+    {
+        return Err(Error::InvalidRecipe(format!(
+            "{} of product {} is not a valid amount in g or ml",
+            ingredient.amount, ingredient.product_id
+        )));
+    }
+
+    Ok(())
+}
+
+/// Computes the nutrients of the recipe from the current nutrients of its ingredients, i.e.,
+/// changes of the products are reflected. The products are resolved in a single query of the
+/// backend.
+///
+/// # Arguments
+/// - `db` - The data backend to resolve the products with.
+/// - `recipe` - The recipe to compute the nutrients for.
+#[cfg(feature = "service")]
+pub async fn resolve_recipe_nutrients<DB: DataBackend>(
+    db: &DB,
+    recipe: &Recipe,
+) -> Result<RecipeNutrients> {
+    let ids = unique_product_ids(&recipe.ingredients);
+
+    debug!(
+        "Resolving the nutrients of recipe '{}' with {} products",
+        recipe.name,
+        ids.len()
+    );
+    let products = db.get_products(&ids).await?;
+
+    Ok(recipe_nutrients(recipe, &products))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_recipe_nutrients() {
+        let product_data = include_str!("../../test_data/products.json");
+        let products: Vec<ProductDescription> = serde_json::from_str(product_data).unwrap();
+        let first = &products[0];
+        let second = &products[1];
+
+        let recipe = Recipe {
+            name: "Test recipe".to_string(),
+            servings: 4,
+            ingredients: vec![
+                Portion {
+                    product_id: first.info.id.clone(),
+                    amount: 300.0,
+                },
+                Portion {
+                    product_id: second.info.id.clone(),
+                    amount: 100.0,
+                },
+                Portion {
+                    product_id: "unknown".to_string(),
+                    amount: 50.0,
+                },
+            ],
+            image: None,
+        };
+
+        let nutrients = recipe_nutrients(&recipe, &products);
+        assert_eq!(nutrients.missing_products, vec!["unknown".to_string()]);
+
+        let weight = |product: &ProductDescription, amount: f32| match (
+            product.info.quantity_type,
+            product.info.volume_weight_ratio,
+        ) {
+            (QuantityType::Volume, Some(ratio)) => amount / ratio,
+            _ => amount,
+        };
+        let total_gram = weight(first, 300.0) + weight(second, 100.0);
+        assert!((nutrients.total_weight.gram() - total_gram).abs() < 1e-3);
+
+        let total_kcal = first.nutrients.kcal * 3.0 + second.nutrients.kcal;
+        assert!((nutrients.per_portion.kcal - total_kcal / 4.0).abs() < 1e-2);
+        assert!((nutrients.per_100g.kcal - total_kcal * 100.0 / total_gram).abs() < 1e-2);
+
+        // a recipe without known ingredients has no nutrients
+        let empty = Recipe {
+            ingredients: Vec::new(),
+            ..recipe
+        };
+        let nutrients = recipe_nutrients(&empty, &products);
+        assert_eq!(nutrients.per_100g, Nutrients::default());
+        assert_eq!(nutrients.per_portion, Nutrients::default());
+    }
+}
@@ -0,0 +1,255 @@
+use std::time::Duration;
+
+use axum::{
+    body::{to_bytes, Body},
+    extract::{OriginalUri, Request, State},
+    http::StatusCode,
+    middleware::Next,
+    response::Response,
+};
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use log::{debug, warn};
+use sha2::Sha256;
+
+use crate::{layers::problem_response, RequestSigningOptions, Secret};
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// The header with the hex encoded HMAC-SHA256 signature of the request.
+pub const SIGNATURE_HEADER: &str = "x-signature";
+
+/// The header with the unix timestamp at which the request has been signed.
+pub const SIGNATURE_TIMESTAMP_HEADER: &str = "x-signature-timestamp";
+
+/// The default maximal size of the body of signed requests, which is the default body limit of
+/// axum.
+const DEFAULT_MAX_SIGNED_BODY_BYTES: usize = 2 * 1024 * 1024;
+
+/// Signs and verifies requests of trusted services. The signature is the HMAC-SHA256 of the
+/// timestamp, the method, the path including the query and the body of the request, each
+/// separated by a newline.
+#[derive(Clone)]
+pub struct RequestSigner {
+    secret: Secret,
+    max_clock_skew: Duration,
+    max_body_bytes: usize,
+}
+
+impl RequestSigner {
+    /// Creates a new signer.
+    ///
+    /// # Arguments
+    /// - `secret` - The secret key shared with the trusted services.
+    /// - `max_clock_skew` - The maximal age or advance of the timestamp of a signed request.
+    pub fn new(secret: Secret, max_clock_skew: Duration) -> Self {
+        Self {
+            secret,
+            max_clock_skew,
+            max_body_bytes: DEFAULT_MAX_SIGNED_BODY_BYTES,
+        }
+    }
+
+    /// Sets the maximal size of the body of signed requests, which must match the body limit
+    /// of the endpoints, as the body is read completely for verifying the signature.
+    ///
+    /// # Arguments
+    /// - `max_body_bytes` - The maximal number of bytes of the body.
+    pub fn with_max_body_bytes(mut self, max_body_bytes: usize) -> Self {
+        self.max_body_bytes = max_body_bytes;
+        self
+    }
+
+    /// Creates the signer from the request signing options.
+    ///
+    /// # Arguments
+    /// - `options` - The options for verifying signed requests.
+    pub fn from_options(options: &RequestSigningOptions) -> Self {
+        Self::new(options.secret.clone(), options.max_clock_skew())
+    }
+
+    /// Returns the hex encoded signature of the given request.
+    ///
+    /// # Arguments
+    /// - `timestamp` - The unix timestamp at which the request is signed.
+    /// - `method` - The method of the request, e.g. "POST".
+    /// - `path_and_query` - The path of the request including the query, e.g. "/v1/admin/product".
+    /// - `body` - The body of the request.
+    pub fn sign(&self, timestamp: i64, method: &str, path_and_query: &str, body: &[u8]) -> String {
+        hex::encode(
+            self.mac(timestamp, method, path_and_query, body)
+                .finalize()
+                .into_bytes(),
+        )
+    }
+
+    /// Returns true if the signature of the given request is valid and its timestamp is within
+    /// the allowed clock skew.
+    ///
+    /// # Arguments
+    /// - `timestamp` - The value of the timestamp header.
+    /// - `signature` - The value of the signature header.
+    /// - `method` - The method of the request.
+    /// - `path_and_query` - The path of the request including the query.
+    /// - `body` - The body of the request.
+    /// - `now` - The current point in time.
+    pub fn verify(
+        &self,
+        timestamp: &str,
+        signature: &str,
+        method: &str,
+        path_and_query: &str,
+        body: &[u8],
+        now: DateTime<Utc>,
+    ) -> bool {
+        let Ok(timestamp) = timestamp.trim().parse::<i64>() else {
+            debug!("Rejected request due to an invalid signature timestamp");
+            return false;
+        };
+
+        if timestamp.abs_diff(now.timestamp()) > self.max_clock_skew.as_secs() {
+            debug!(
+                "Rejected request with signature timestamp {} outside the allowed clock skew",
+                timestamp
+            );
+            return false;
+        }
+
+        let Ok(signature) = hex::decode(signature.trim()) else {
+            debug!("Rejected request due to a malformed signature");
+            return false;
+        };
+
+        self.mac(timestamp, method, path_and_query, body)
+            .verify_slice(&signature)
+            .is_ok()
+    }
+
+    /// Returns the MAC of the given request.
+    ///
+    /// # Arguments
+    /// - `timestamp` - The unix timestamp at which the request is signed.
+    /// - `method` - The method of the request.
+    /// - `path_and_query` - The path of the request including the query.
+    /// - `body` - The body of the request.
+    fn mac(&self, timestamp: i64, method: &str, path_and_query: &str, body: &[u8]) -> HmacSha256 {
+        let mut mac =
+            HmacSha256::new_from_slice(self.secret.bytes()).expect("HMAC accepts keys of any size");
+        mac.update(format!("{}\n{}\n{}\n", timestamp, method, path_and_query).as_bytes());
+        mac.update(body);
+        mac
+    }
+}
+
+/// Middleware that rejects requests without a valid signature of a trusted service with 401.
+pub async fn request_signature_guard(
+    State(signer): State<RequestSigner>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let (parts, body) = request.into_parts();
+
+    // the nested routers only see the stripped path, the signature covers the full path
+    let uri = parts
+        .extensions
+        .get::<OriginalUri>()
+        .map(|uri| uri.0.clone())
+        .unwrap_or_else(|| parts.uri.clone());
+    let path_and_query = uri
+        .path_and_query()
+        .map(|p| p.as_str())
+        .unwrap_or_else(|| uri.path());
+
+    let header = |name: &str| {
+        parts
+            .headers
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string)
+    };
+    let (Some(timestamp), Some(signature)) =
+        (header(SIGNATURE_TIMESTAMP_HEADER), header(SIGNATURE_HEADER))
+    else {
+        warn!("Rejected unsigned request {} {}", parts.method, uri);
+        return problem_response(
+            StatusCode::UNAUTHORIZED,
+            "The request must be signed".to_string(),
+        );
+    };
+
+    let body = match to_bytes(body, signer.max_body_bytes).await {
+        Ok(body) => body,
+        Err(err) => {
+            warn!(
+                "Failed to read the body of {} {}: {}",
+                parts.method, uri, err
+            );
+            return problem_response(
+                StatusCode::PAYLOAD_TOO_LARGE,
+                format!("Failed to read the body of the request: {}", err),
+            );
+        }
+    };
+
+    if !signer.verify(
+        &timestamp,
+        &signature,
+        parts.method.as_str(),
+        path_and_query,
+        &body,
+        Utc::now(),
+    ) {
+        warn!(
+            "Rejected request {} {} with invalid signature",
+            parts.method, uri
+        );
+        return problem_response(
+            StatusCode::UNAUTHORIZED,
+            "The signature of the request is invalid or has expired".to_string(),
+        );
+    }
+
+    next.run(Request::from_parts(parts, Body::from(body))).await
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_sign_and_verify() {
+        let signer = RequestSigner::new(Secret::new("key".to_string()), Duration::from_secs(60));
+        let now = Utc::now();
+        let timestamp = now.timestamp();
+        let body = br#"{"read_only":true}"#;
+
+        let signature = signer.sign(timestamp, "PUT", "/v1/admin/maintenance", body);
+        let ts = timestamp.to_string();
+        assert!(signer.verify(&ts, &signature, "PUT", "/v1/admin/maintenance", body, now));
+
+        // the signature covers the method, the path and the body
+        assert!(!signer.verify(&ts, &signature, "GET", "/v1/admin/maintenance", body, now));
+        assert!(!signer.verify(&ts, &signature, "PUT", "/v1/admin/product", body, now));
+        assert!(!signer.verify(&ts, &signature, "PUT", "/v1/admin/maintenance", b"{}", now));
+
+        // the timestamp must be within the clock skew and is covered by the signature
+        let later = now + chrono::Duration::seconds(61);
+        assert!(!signer.verify(&ts, &signature, "PUT", "/v1/admin/maintenance", body, later));
+        let other_ts = (timestamp + 1).to_string();
+        assert!(!signer.verify(
+            &other_ts,
+            &signature,
+            "PUT",
+            "/v1/admin/maintenance",
+            body,
+            now
+        ));
+
+        // signatures of other keys are rejected
+        let other = RequestSigner::new(Secret::new("other".to_string()), Duration::from_secs(60));
+        assert!(!other.verify(&ts, &signature, "PUT", "/v1/admin/maintenance", body, now));
+
+        assert!(!signer.verify("garbage", &signature, "PUT", "/", body, now));
+        assert!(!signer.verify(&ts, "garbage", "PUT", "/v1/admin/maintenance", body, now));
+    }
+}
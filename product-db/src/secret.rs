@@ -1,14 +1,26 @@
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 use std::{
     fmt::{Debug, Display},
+    path::Path,
     str::FromStr,
 };
 
+use log::error;
+use subtle::ConstantTimeEq;
+use zeroize::Zeroize;
+
+use crate::{Error, Result as ProductDBResult};
+
+/// The placeholder that is written instead of the secret when serializing it.
+const REDACTED: &str = "<redacted>";
+
 /// A wrapper for a secret string that can be printed to the console without revealing the secret.
+/// The memory of the secret is zeroed on drop, serializing only writes a placeholder and
+/// comparisons are done in constant time.
 #[derive(Clone, Default)]
 pub struct Secret {
-    secret: String,
+    secret: Vec<u8>,
 }
 
 impl Secret {
@@ -17,26 +29,90 @@ impl Secret {
     /// # Arguments
     /// * `secret` - The secret string.
     pub fn new(secret: String) -> Self {
+        Self {
+            secret: secret.into_bytes(),
+        }
+    }
+
+    /// Creates a new secret from the given bytes, which don't need to be valid UTF-8.
+    ///
+    /// # Arguments
+    /// * `secret` - The secret bytes.
+    pub fn from_bytes(secret: Vec<u8>) -> Self {
         Self { secret }
     }
 
-    /// Returns the secret string without disguising it.
-    pub fn secret(&self) -> &str {
+    /// Reads the secret from the given file, e.g. a mounted Docker or Kubernetes secret.
+    /// Trailing line breaks are removed.
+    ///
+    /// # Arguments
+    /// * `path` - The path to the file containing the secret.
+    pub fn from_file(path: &Path) -> ProductDBResult<Self> {
+        let mut secret = std::fs::read(path).map_err(|e| {
+            error!("Failed to read secret from file {}: {}", path.display(), e);
+            Error::IO(Box::new(e))
+        })?;
+
+        // remove trailing line breaks in place to avoid copies of the secret
+        while matches!(secret.last(), Some(b'\r' | b'\n')) {
+            secret.pop();
+        }
+
+        Ok(Self::from_bytes(secret))
+    }
+
+    /// Returns the secret string without disguising it or `None` if the secret is not valid
+    /// UTF-8, see [`Self::bytes`].
+    pub fn secret(&self) -> Option<&str> {
+        std::str::from_utf8(&self.secret).ok()
+    }
+
+    /// Returns the bytes of the secret without disguising them.
+    pub fn bytes(&self) -> &[u8] {
         &self.secret
     }
+
+    /// Returns the disguised secret, see [`disguise_secret`].
+    fn disguised(&self) -> String {
+        match std::str::from_utf8(&self.secret) {
+            Ok(secret) => disguise_secret(secret),
+            Err(_) => "*".repeat(self.secret.len()),
+        }
+    }
+}
+
+impl Drop for Secret {
+    fn drop(&mut self) {
+        self.secret.zeroize();
+    }
+}
+
+impl PartialEq for Secret {
+    fn eq(&self, other: &Self) -> bool {
+        self.secret.ct_eq(&other.secret).into()
+    }
 }
 
+impl Eq for Secret {}
+
 impl Debug for Secret {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let secret = disguise_secret(&self.secret);
-        write!(f, "Secret: {}", secret)
+        write!(f, "Secret: {}", self.disguised())
     }
 }
 
 impl Display for Secret {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let secret = disguise_secret(&self.secret);
-        write!(f, "{}", secret)
+        write!(f, "{}", self.disguised())
+    }
+}
+
+impl Serialize for Secret {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(REDACTED)
     }
 }
 
@@ -78,7 +154,7 @@ pub fn disguise_secret(secret: &str) -> String {
     // Disguise the secret.
     let mut disguised = String::new();
     disguised.push_str(&secret[..num_clean_chars]);
-    disguised.extend(std::iter::repeat('*').take(secret.len() - 2 * num_clean_chars));
+    disguised.extend(std::iter::repeat_n('*', secret.len() - 2 * num_clean_chars));
     disguised.push_str(&secret[secret.len() - num_clean_chars..]);
 
     disguised
@@ -105,7 +181,7 @@ mod test {
     #[test]
     fn test_secret_from_str() {
         let secret: Secret = "password".parse().unwrap();
-        assert_eq!(secret.secret(), "password");
+        assert_eq!(secret.secret(), Some("password"));
     }
 
     #[test]
@@ -118,7 +194,43 @@ mod test {
         }
 
         let s = serde_json::from_str::<SecretConfig>(source).unwrap();
-        assert_eq!(s.secret.secret(), "password");
+        assert_eq!(s.secret.secret(), Some("password"));
+    }
+
+    #[test]
+    fn test_serialize_secret_redacted() {
+        let secret = Secret::new("password".to_string());
+        assert_eq!(serde_json::to_string(&secret).unwrap(), "\"<redacted>\"");
+    }
+
+    #[test]
+    fn test_secret_eq() {
+        let secret = Secret::new("password".to_string());
+        assert_eq!(secret, Secret::new("password".to_string()));
+        assert_ne!(secret, Secret::new("passwort".to_string()));
+        assert_ne!(secret, Secret::new("pass".to_string()));
+    }
+
+    #[test]
+    fn test_secret_bytes() {
+        let secret = Secret::from_bytes(vec![0xff, 0xfe, 0x00, 0x01]);
+        assert_eq!(secret.bytes(), &[0xff, 0xfe, 0x00, 0x01]);
+        assert_eq!(secret.secret(), None);
+        assert_eq!(format!("{}", secret), "****");
+
+        let secret = Secret::new("password".to_string());
+        assert_eq!(secret.bytes(), b"password");
+    }
+
+    #[test]
+    fn test_secret_from_file() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        std::io::Write::write_all(&mut file, b"password\n").unwrap();
+
+        let secret = Secret::from_file(file.path()).unwrap();
+        assert_eq!(secret.secret(), Some("password"));
+
+        assert!(Secret::from_file(Path::new("/does/not/exist")).is_err());
     }
 
     #[test]
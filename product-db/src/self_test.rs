@@ -0,0 +1,266 @@
+use std::fmt::Display;
+
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "service")]
+use std::{future::Future, time::Instant};
+
+#[cfg(feature = "service")]
+use chrono::Utc;
+#[cfg(feature = "service")]
+use log::{info, warn};
+
+#[cfg(feature = "service")]
+use crate::{
+    DataBackend, DietaryLabels, MissingProduct, Nutrients, ProductDescription, ProductInfo,
+    QuantityType, Result,
+};
+
+/// The prefix of the ids of the synthetic products and missing product reports of the self-test.
+pub const SELF_TEST_PRODUCT_PREFIX: &str = "selftest-";
+
+/// An operation of the self-test against the data backend.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum SelfTestOperation {
+    /// Adding the synthetic product.
+    NewProduct,
+    /// Reading the synthetic product back.
+    GetProduct,
+    /// Deleting the synthetic product.
+    DeleteProduct,
+    /// Checking that the synthetic product is gone.
+    CheckProductDeleted,
+    /// Reporting the synthetic product as missing.
+    ReportMissingProduct,
+    /// Reading the missing product report back.
+    GetMissingProduct,
+    /// Deleting the missing product report.
+    DeleteMissingProduct,
+}
+
+impl Display for SelfTestOperation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SelfTestOperation::NewProduct => write!(f, "new_product"),
+            SelfTestOperation::GetProduct => write!(f, "get_product"),
+            SelfTestOperation::DeleteProduct => write!(f, "delete_product"),
+            SelfTestOperation::CheckProductDeleted => write!(f, "check_product_deleted"),
+            SelfTestOperation::ReportMissingProduct => write!(f, "report_missing_product"),
+            SelfTestOperation::GetMissingProduct => write!(f, "get_missing_product"),
+            SelfTestOperation::DeleteMissingProduct => write!(f, "delete_missing_product"),
+        }
+    }
+}
+
+/// The outcome of a single operation of the self-test.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct SelfTestStep {
+    /// The executed operation.
+    pub operation: SelfTestOperation,
+    /// Whether the operation succeeded and returned the expected result.
+    pub ok: bool,
+    /// The time in milliseconds the operation took.
+    pub latency_ms: f64,
+    /// The reason of the failure, if any.
+    pub error: Option<String>,
+}
+
+/// The report of a write, read and delete cycle against the data backend.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct SelfTestReport {
+    /// Whether all operations succeeded.
+    pub ok: bool,
+    /// The id of the synthetic product.
+    pub product_id: String,
+    /// The time in milliseconds the whole cycle took.
+    pub latency_ms: f64,
+    /// The outcome of the operations in the order they have been executed.
+    pub steps: Vec<SelfTestStep>,
+}
+
+impl SelfTestReport {
+    /// Returns the operations that failed.
+    pub fn failed_operations(&self) -> Vec<SelfTestOperation> {
+        self.steps
+            .iter()
+            .filter(|step| !step.ok)
+            .map(|step| step.operation)
+            .collect()
+    }
+}
+
+/// Runs a full write, read and delete cycle with a synthetic product and a synthetic missing
+/// product report against the given backend. All operations are executed even if a previous
+/// one failed, so the report shows which kinds of operations are affected, e.g. failing writes
+/// while the reads still work. The synthetic data is deleted at the end of the cycle.
+///
+/// # Arguments
+/// - `backend` - The backend to test.
+#[cfg(feature = "service")]
+pub async fn run_self_test<B: DataBackend>(backend: &B) -> SelfTestReport {
+    let product_id = format!(
+        "{}{}",
+        SELF_TEST_PRODUCT_PREFIX,
+        Utc::now().timestamp_micros()
+    );
+    let product = self_test_product(&product_id);
+    let start = Instant::now();
+    let mut steps = Vec::new();
+
+    steps.push(
+        run_step(SelfTestOperation::NewProduct, async {
+            check(
+                backend.new_product(&product).await?,
+                "product already exists",
+            )
+        })
+        .await,
+    );
+    steps.push(
+        run_step(SelfTestOperation::GetProduct, async {
+            let stored = backend.get_product(&product_id, false).await?;
+            check(
+                stored.is_some_and(|stored| stored.info.name == product.info.name),
+                "product not found",
+            )
+        })
+        .await,
+    );
+    steps.push(
+        run_step(SelfTestOperation::DeleteProduct, async {
+            backend.delete_product(&product_id).await
+        })
+        .await,
+    );
+    steps.push(
+        run_step(SelfTestOperation::CheckProductDeleted, async {
+            let stored = backend.get_product(&product_id, false).await?;
+            check(stored.is_none(), "product still exists")
+        })
+        .await,
+    );
+
+    let missing_product = MissingProduct {
+        product_id: product_id.clone(),
+        date: Utc::now(),
+        store_id: None,
+    };
+    let mut report_id = None;
+    steps.push(
+        run_step(SelfTestOperation::ReportMissingProduct, async {
+            report_id = Some(backend.report_missing_product(missing_product).await?);
+            Ok(())
+        })
+        .await,
+    );
+    steps.push(
+        run_step(SelfTestOperation::GetMissingProduct, async {
+            let Some(id) = report_id else {
+                return check(false, "no report to read");
+            };
+            let stored = backend.get_missing_product(id).await?;
+            check(
+                stored.is_some_and(|stored| stored.product_id == product_id),
+                "report not found",
+            )
+        })
+        .await,
+    );
+    steps.push(
+        run_step(SelfTestOperation::DeleteMissingProduct, async {
+            let Some(id) = report_id else {
+                return check(false, "no report to delete");
+            };
+            backend.delete_reported_missing_product(id).await
+        })
+        .await,
+    );
+
+    let report = SelfTestReport {
+        ok: steps.iter().all(|step| step.ok),
+        product_id,
+        latency_ms: start.elapsed().as_secs_f64() * 1000.0,
+        steps,
+    };
+    if report.ok {
+        info!("Self-test succeeded in {:.1}ms", report.latency_ms);
+    } else {
+        warn!("Self-test failed: {:?}", report.failed_operations());
+    }
+
+    report
+}
+
+/// Returns the synthetic product of the self-test with the given id.
+///
+/// # Arguments
+/// - `id` - The id of the synthetic product.
+#[cfg(feature = "service")]
+fn self_test_product(id: &str) -> ProductDescription {
+    ProductDescription {
+        info: ProductInfo {
+            id: id.to_string(),
+            name: "Self-test product".to_string(),
+            producer: None,
+            quantity_type: QuantityType::Weight,
+            portion: 100.0,
+            volume_weight_ratio: None,
+            market: None,
+            labels: DietaryLabels::default(),
+            nova_group: None,
+            allergens: Vec::new(),
+        },
+        preview: None,
+        full_image: None,
+        nutrients: Nutrients::default(),
+        packaging: None,
+        eco_score: None,
+    }
+}
+
+/// Returns an error with the given message if the condition does not hold.
+///
+/// # Arguments
+/// - `condition` - The expected condition.
+/// - `message` - The message of the error.
+#[cfg(feature = "service")]
+fn check(condition: bool, message: &str) -> Result<()> {
+    if condition {
+        Ok(())
+    } else {
+        Err(crate::Error::InternalError(message.to_string()))
+    }
+}
+
+/// Executes a single operation of the self-test and measures its latency.
+///
+/// # Arguments
+/// - `operation` - The executed operation.
+/// - `future` - The future executing the operation.
+#[cfg(feature = "service")]
+async fn run_step(
+    operation: SelfTestOperation,
+    future: impl Future<Output = Result<()>>,
+) -> SelfTestStep {
+    let start = Instant::now();
+    let result = future.await;
+    let latency_ms = start.elapsed().as_secs_f64() * 1000.0;
+
+    if let Err(err) = result.as_ref() {
+        warn!("Self-test operation {} failed: {}", operation, err);
+    }
+
+    SelfTestStep {
+        operation,
+        ok: result.is_ok(),
+        latency_ms,
+        error: result.err().map(|err| err.to_string()),
+    }
+}
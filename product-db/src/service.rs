@@ -1,30 +1,371 @@
-use std::sync::Arc;
+use std::{
+    future::Future,
+    net::SocketAddr,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    time::Instant,
+};
 
 use axum::{
-    extract::{Path, Query, State},
-    http::{header, HeaderMap, HeaderValue, Method, StatusCode},
-    response::IntoResponse,
-    routing::{delete, get, post},
+    body::Body,
+    extract::{DefaultBodyLimit, OriginalUri, Path, Query, Request, State},
+    http::{header, HeaderMap, HeaderName, HeaderValue, Method, StatusCode, Uri},
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
+    routing::{delete, get, post, put},
     Json, Router,
 };
-use log::{debug, error, info, warn};
-use tokio::sync::watch;
-use tower_http::cors::CorsLayer;
+use futures::{
+    future::{try_join, BoxFuture},
+    stream::{self, BoxStream},
+    StreamExt, TryStreamExt,
+};
+use metrics_exporter_prometheus::PrometheusHandle;
+use tokio::{
+    net::TcpListener,
+    sync::{mpsc, watch},
+};
+use tokio_rustls::TlsAcceptor;
+use tower_http::{
+    catch_panic::CatchPanicLayer,
+    cors::CorsLayer,
+    request_id::{MakeRequestUuid, PropagateRequestIdLayer, SetRequestIdLayer},
+};
+use tracing::{debug, error, info, warn};
 
-use crate::{service_json::*, MissingProduct, MissingProductQuery, ProductID, ProductQuery};
+use crate::{
+    aggregate_nutrients, auth_guard, check_eco_score, check_packaging, check_price, check_recipe,
+    check_store, client_certificate_guard, csv_header, csv_line, deprecation_headers,
+    export_fields,
+    http_range::ranged_response,
+    image_file_extension, ip_allowlist_guard,
+    layers::{
+        cache_control, handle_panic, problem_response, request_id_scope, timeout_guard,
+        CachePolicy, PRIVATE_CACHE_CONTROL,
+    },
+    missing_product_row, nutrient_issues, openapi_document, product_row, project_product,
+    prometheus_handle, rate_limit_guard, record_request_metrics, request_signature_guard,
+    resolve_product_id, resolve_recipe_nutrients, run_self_test,
+    service_json::*,
+    swagger_ui_page,
+    tls::serve_tls,
+    tls_acceptor, Access, ArchiveQuery, Authenticator, BodyFormat, Deprecations, Encoded,
+    ImageUrlSigner, IpAllowlist, MissingProduct, MissingProductQuery, Negotiated, NutrientIssue,
+    PageLinks, PageParams, PlausibilityMode, PriceObservation, PriceQuery, ProductAvailability,
+    ProductCursor, ProductField, ProductID, ProductQuery, RateLimiter, RequestSigner, Sorting,
+    SortingField, Store, StoreQuery, XlsxWriter, ZipStreamWriter, CSV_CONTENT_TYPE,
+    DEPRECATION_HEADER, HTML_CONTENT_TYPE, MAX_ADDITIONAL_IMAGES, MAX_EXPORT_ROWS,
+    MAX_MISSING_PRODUCTS_BATCH_SIZE, MAX_PRODUCT_ID_LENGTH, MISSING_PRODUCTS_COLUMNS,
+    SUNSET_HEADER, TOTAL_COUNT_HEADER, XLSX_CONTENT_TYPE, ZIP_CONTENT_TYPE,
+};
 
 use crate::{
-    DBId, DataBackend, EndpointOptions, Error, Options, ProductDescription, ProductRequest, Result,
+    generate_preview, verify_image_content_type, AdditionalImage, DBId, DataBackend,
+    EndpointOptions, Error, HealthReport, HealthStatus, ImageQuotaOptions, Options, PreviewOptions,
+    ProductDescription, ProductImage, ProductRequest, ReadBackend, ReadOnlyBackend, Recipe,
+    RequestOutcome, RequestStatus, Result, SearchFilter, Secret, ServiceBuilder, SortingOrder,
 };
 
+/// The number of results queried at once by the exports and the maintenance tasks, i.e., the
+/// maximum limit of the queries.
+const EXPORT_PAGE_SIZE: i32 = 200;
+
+/// The number of missing products returned by the summary if no limit is given.
+const MISSING_PRODUCTS_SUMMARY_LIMIT: i32 = 50;
+
+/// Returns the results of all pages as a stream, which queries the next page once the previous
+/// one is consumed and ends with an empty page, as the backend may cap the limit. The stream
+/// ends after the first error.
+///
+/// # Arguments
+/// - `query_page` - Queries the page at the given offset.
+fn page_stream<T, F, Fut>(query_page: F) -> BoxStream<'static, Result<T>>
+where
+    T: Send + 'static,
+    F: FnMut(i32) -> Fut + Send + 'static,
+    Fut: Future<Output = Result<Vec<T>>> + Send,
+{
+    stream::unfold(
+        (Some(0), query_page),
+        |(offset, mut query_page)| async move {
+            let offset = offset?;
+            match query_page(offset).await {
+                Ok(page) if page.is_empty() => None,
+                Ok(page) => {
+                    let next_offset = offset + page.len() as i32;
+                    let page: Vec<_> = page.into_iter().map(Ok).collect();
+                    Some((stream::iter(page), (Some(next_offset), query_page)))
+                }
+                Err(err) => Some((stream::iter(vec![Err(err)]), (None, query_page))),
+            }
+        },
+    )
+    .flatten()
+    .boxed()
+}
+
+/// Returns the products matching the sorted query as a stream like [`page_stream`], but every
+/// page continues with the keyset cursor after the last product of the previous page. Thus,
+/// the database finds each page by the index instead of skipping all previous rows, and
+/// products added or deleted during the export neither shift nor repeat the following rows.
+///
+/// # Arguments
+/// - `state` - The data backend.
+/// - `query` - The sorted query for the products.
+fn product_stream<DB: DataBackend + 'static>(
+    state: Arc<DB>,
+    query: ProductQuery,
+) -> BoxStream<'static, Result<ProductDescription>> {
+    stream::unfold(Some(query), move |query| {
+        let state = state.clone();
+        async move {
+            let mut query = query?;
+            let sorting = query.sorting?;
+
+            // the cursor of the similarity sorting needs the score of the last product
+            let page = if sorting.field == SortingField::Similarity {
+                state
+                    .query_scored_products(&query, false)
+                    .await
+                    .map(|page| {
+                        page.into_iter()
+                            .map(|(product, score)| (product, Some(score)))
+                            .collect::<Vec<_>>()
+                    })
+            } else {
+                state
+                    .query_products(&query, false)
+                    .await
+                    .map(|page| page.into_iter().map(|product| (product, None)).collect())
+            };
+
+            match page {
+                Ok(page) => {
+                    let (last, score) = page.last()?;
+                    query.cursor = Some(ProductCursor::after(sorting, last, *score).encode());
+                    let page: Vec<_> = page.into_iter().map(|(product, _)| Ok(product)).collect();
+                    Some((stream::iter(page), Some(query)))
+                }
+                Err(err) => Some((stream::iter(vec![Err(err)]), None)),
+            }
+        }
+    })
+    .flatten()
+    .boxed()
+}
+
+/// Returns the query for exporting the products or product requests matching the given query
+/// page by page. Without sorting, they are sorted by the product id, as the backends break the
+/// ties of a sorting by the product id and market or the request id, i.e., the pages are
+/// disjoint and complete. The products are paged by the keyset cursor, see
+/// [`product_stream`], while the product requests, which have no cursor, are paged by offset.
+///
+/// # Arguments
+/// - `query` - The query for the exported products or product requests.
+fn export_query(query: ProductQuery) -> ProductQuery {
+    ProductQuery {
+        offset: 0,
+        limit: EXPORT_PAGE_SIZE,
+        sorting: query.sorting.or(Some(Sorting {
+            order: SortingOrder::Ascending,
+            field: SortingField::ProductID,
+        })),
+        cursor: None,
+        ..query
+    }
+}
+
+/// Returns the rows of an export in the given format as a file download. Exports with more
+/// than [`MAX_EXPORT_ROWS`] rows are rejected. The CSV document is streamed while the rows are
+/// loaded, i.e., failures abort the download. The XLSX workbook is written on the blocking
+/// thread pool and buffered in memory until it is complete.
+///
+/// # Arguments
+/// - `format` - The file format of the export.
+/// - `name` - The name of the exported entity, e.g. "products".
+/// - `header` - The names of the columns.
+/// - `num_rows` - The number of rows to export.
+/// - `rows` - The values of the rows.
+async fn export_response(
+    format: ExportFormat,
+    name: &'static str,
+    header: Vec<&'static str>,
+    num_rows: u64,
+    rows: BoxStream<'static, Result<Vec<serde_json::Value>>>,
+) -> Response {
+    if num_rows > MAX_EXPORT_ROWS {
+        let err = Error::InvalidExport(format!(
+            "the export has {} rows, but at most {} are exported",
+            num_rows, MAX_EXPORT_ROWS
+        ));
+        return problem_response(StatusCode::BAD_REQUEST, err.to_string());
+    }
+
+    // the rows beyond the limit, e.g. added since counting, fail the export
+    let rows = rows
+        .enumerate()
+        .map(move |(index, row)| match index as u64 {
+            index if index < MAX_EXPORT_ROWS => row,
+            _ => Err(Error::InvalidExport(format!(
+                "the export has more than {} rows",
+                MAX_EXPORT_ROWS
+            ))),
+        })
+        .take(MAX_EXPORT_ROWS as usize + 1);
+
+    let (content_type, body) = match format {
+        ExportFormat::Csv => {
+            let lines = stream::once(async move { Ok(csv_header(&header)) })
+                .chain(rows.map_ok(|row| csv_line(&row)))
+                .inspect_err(move |err| error!("Failed to export {}: {}", name, err));
+            (CSV_CONTENT_TYPE, Body::from_stream(lines))
+        }
+        ExportFormat::Zip => {
+            let err = Error::InvalidExport(format!("{} cannot be exported as zip", name));
+            return problem_response(StatusCode::BAD_REQUEST, err.to_string());
+        }
+        ExportFormat::Xlsx => {
+            // the worksheet is named like the entity, e.g. "Products"
+            let mut sheet_name = name.replace('_', " ");
+            sheet_name[..1].make_ascii_uppercase();
+
+            let runtime = tokio::runtime::Handle::current();
+            let xlsx = tokio::task::spawn_blocking(move || {
+                let mut rows = rows;
+                let mut writer = XlsxWriter::new(&sheet_name, &header)?;
+                while let Some(row) = runtime.block_on(rows.next()) {
+                    writer.write_row(&row?)?;
+                }
+                writer.finish()
+            })
+            .await
+            .unwrap_or_else(|e| Err(Error::InternalError(e.to_string())));
+
+            match xlsx {
+                Ok(xlsx) => (XLSX_CONTENT_TYPE, Body::from(xlsx)),
+                Err(err) => {
+                    error!("Failed to export {}: {}", name, err);
+                    return problem_response(StatusCode::BAD_REQUEST, err.to_string());
+                }
+            }
+        }
+    };
+
+    let disposition = format!(
+        "attachment; filename=\"{}.{}\"",
+        name,
+        format.file_extension()
+    );
+
+    (
+        StatusCode::OK,
+        [
+            (header::CONTENT_TYPE, HeaderValue::from_static(content_type)),
+            (
+                header::CONTENT_DISPOSITION,
+                HeaderValue::try_from(disposition)
+                    .unwrap_or(HeaderValue::from_static("attachment")),
+            ),
+        ],
+        body,
+    )
+        .into_response()
+}
+
+/// The endpoints that are served by a router.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RouterScope {
+    /// The admin, user and public endpoints.
+    All,
+    /// The user and public endpoints.
+    User,
+    /// The admin endpoints.
+    Admin,
+}
+
+/// An extension of the router, e.g., for adding layers or routes to the service.
+pub(crate) type RouterExtension = Box<dyn Fn(Router) -> Router + Send + Sync>;
+
+/// The state of the handlers accepting product submissions: the backend, the NOVA classifier,
+/// the image quota, the nutrient plausibility mode and the options for generating previews.
+type SubmissionState<DB> = (
+    Arc<DB>,
+    Option<NovaClassifier>,
+    ImageQuotaOptions,
+    PlausibilityMode,
+    Option<PreviewOptions>,
+);
+
+/// The source for reloading the database password, e.g. a secret file or a secret manager.
+pub type CredentialsSource = Arc<dyn Fn() -> BoxFuture<'static, Result<Secret>> + Send + Sync>;
+
+/// The classifier that derives the NOVA group of a submitted product without NOVA group, e.g.
+/// from its ingredients. Returns None if the NOVA group cannot be derived.
+pub type NovaClassifier = Arc<dyn Fn(&ProductDescription) -> Option<i32> + Send + Sync>;
+
+/// The generator that derives the preview image from the full image of a product, e.g. a
+/// downscaled thumbnail. Fails if the full image cannot be decoded.
+pub type PreviewGenerator = Arc<dyn Fn(&ProductImage) -> Result<ProductImage> + Send + Sync>;
+
+/// The regeneration of the preview images together with its progress, which is shared between
+/// the routers of the service.
+#[derive(Clone)]
+struct ImageRegeneration {
+    preview_generator: Option<PreviewGenerator>,
+    status: Arc<Mutex<ImageRegenerationStatus>>,
+}
+
+/// The product or product request whose preview is regenerated from its full image.
+enum ImageOwner {
+    Product(ProductID),
+    ProductRequest(DBId),
+}
+
+/// The read-only (maintenance) mode of the service, which is shared with the guards of the
+/// mutating routes.
+#[derive(Clone)]
+struct ReadOnlyMode {
+    /// Whether the service is currently in read-only mode.
+    enabled: Arc<AtomicBool>,
+
+    /// Whether the read-only mode cannot be switched off, e.g. for a read-only backend.
+    pinned: bool,
+}
+
 /// The central service that provides access to the product database.
 pub struct Service<DB: DataBackend> {
     options: Options,
     db: Arc<DB>,
+    read_only: ReadOnlyMode,
+    extensions: Vec<RouterExtension>,
+    credentials_source: Option<CredentialsSource>,
+    nova_classifier: Option<NovaClassifier>,
+    image_regeneration: ImageRegeneration,
     stop_signal_receiver: watch::Receiver<i32>,
     stop_signal_sender: watch::Sender<i32>,
 }
 
+impl<R: ReadBackend + 'static> Service<ReadOnlyBackend<R>> {
+    /// Creates a new instance of the service that only serves the read operations of the given
+    /// backend, e.g. of a read replica. The service starts in read-only mode and the write
+    /// operations of the backend fail with [`Error::ReadOnlyBackend`].
+    ///
+    /// # Arguments
+    /// - `options` - The options for the service.
+    /// - `db` - The backend whose read operations are served.
+    pub fn read_only(mut options: Options, db: R) -> Self {
+        options.endpoint.read_only = true;
+        let mut service = Self::with_backend(options, ReadOnlyBackend::new(db));
+
+        // the backend rejects all writes, i.e., the read-only mode cannot be switched off
+        service.read_only.pinned = true;
+
+        service
+    }
+}
+
 impl<DB: DataBackend + 'static> Service<DB> {
     /// Creates a new instance of the service.
     ///
@@ -32,193 +373,3559 @@ impl<DB: DataBackend + 'static> Service<DB> {
     /// - `options` - The options for the service.
     pub async fn new(options: Options) -> Result<Self> {
         // create postgres database instance
-        let db = Arc::new(DB::new(&options).await?);
+        let db = DB::new(&options).await?;
+        Self::check_health(&db).await?;
+
+        Ok(Self::with_backend(options, db))
+    }
+
+    /// Checks the connection to the database of a newly created backend. Fails if the
+    /// database is unavailable and warns if it is degraded, e.g. the connection pool is
+    /// exhausted already.
+    ///
+    /// # Arguments
+    /// - `db` - The data backend to check.
+    pub(crate) async fn check_health(db: &DB) -> Result<()> {
+        info!("Checking the database connection...");
+        let report = db.health_check().await;
+        match report.status {
+            HealthStatus::Healthy => {
+                info!(
+                    "Checking the database connection...OK ({}ms)",
+                    report.latency_ms
+                );
+                Ok(())
+            }
+            HealthStatus::Degraded => {
+                warn!(
+                    "Checking the database connection...DEGRADED: {}",
+                    report.details.as_deref().unwrap_or_default()
+                );
+                Ok(())
+            }
+            HealthStatus::Unavailable => {
+                let details = report.details.unwrap_or_default();
+                error!("Checking the database connection...FAILED: {}", details);
+                Err(Error::DatabaseUnavailable(details))
+            }
+        }
+    }
+
+    /// Checks the connection to the database, e.g. for readiness probes.
+    pub async fn health_check(&self) -> HealthReport {
+        self.db.health_check().await
+    }
+
+    /// Creates a new instance of the service using an already created data backend.
+    ///
+    /// # Arguments
+    /// - `options` - The options for the service.
+    /// - `db` - The data backend instance to use.
+    pub fn with_backend(options: Options, db: DB) -> Self {
+        Self::from_parts(options, db, Vec::new(), None, None, None)
+    }
+
+    /// Returns a builder for the service, which allows registering additional layers and
+    /// routes before the service is started.
+    ///
+    /// # Arguments
+    /// - `options` - The options for the service.
+    pub fn builder(options: Options) -> ServiceBuilder<DB> {
+        ServiceBuilder::new(options)
+    }
+
+    /// Creates a new instance of the service from its parts.
+    ///
+    /// # Arguments
+    /// - `options` - The options for the service.
+    /// - `db` - The data backend instance to use.
+    /// - `extensions` - The extensions that are applied to the router of the service.
+    /// - `credentials_source` - The source for reloading the database password. Defaults to the
+    ///   configured password file, if any.
+    /// - `nova_classifier` - The classifier for the NOVA group of submitted products, if any.
+    /// - `preview_generator` - The generator for regenerating the preview images. Defaults to
+    ///   scaling down the full images if previews are configured.
+    pub(crate) fn from_parts(
+        options: Options,
+        db: DB,
+        extensions: Vec<RouterExtension>,
+        credentials_source: Option<CredentialsSource>,
+        nova_classifier: Option<NovaClassifier>,
+        preview_generator: Option<PreviewGenerator>,
+    ) -> Self {
+        let db = Arc::new(db);
+        let preview_generator = preview_generator.or_else(|| {
+            options
+                .endpoint
+                .previews
+                .map(crate::image_processing::preview_generator)
+        });
+
+        let credentials_source = credentials_source.or_else(|| {
+            options.postgres.password_file.clone().map(|password_file| {
+                let source: CredentialsSource = Arc::new(move || {
+                    let password_file = password_file.clone();
+                    Box::pin(async move { Secret::from_file(&password_file) })
+                });
+
+                source
+            })
+        });
 
         // create the stop signal channel with the initial value set to running=false
         let (tx, rx) = watch::channel(0);
 
-        Ok(Self {
+        let read_only = ReadOnlyMode {
+            enabled: Arc::new(AtomicBool::new(options.endpoint.read_only)),
+            pinned: false,
+        };
+        if options.endpoint.read_only {
+            warn!("Service is starting in read-only mode");
+        }
+
+        Self {
             options,
             db,
+            read_only,
+            extensions,
+            credentials_source,
+            nova_classifier,
+            image_regeneration: ImageRegeneration {
+                preview_generator,
+                status: Arc::new(Mutex::new(ImageRegenerationStatus::default())),
+            },
             stop_signal_receiver: rx,
             stop_signal_sender: tx,
+        }
+    }
+
+    /// Returns the configured router of the service including its state and layers.
+    /// The router can be nested into another axum application instead of calling [`Self::run`].
+    /// If a separate admin address is configured, the router does not contain the admin
+    /// endpoints, see [`Self::admin_router`].
+    pub fn router(&self) -> Result<Router> {
+        let scope = if self.options.endpoint.admin_address.is_some() {
+            RouterScope::User
+        } else {
+            RouterScope::All
+        };
+
+        self.scoped_router(scope)
+    }
+
+    /// Returns the router with only the admin endpoints if a separate admin address is
+    /// configured, otherwise None as the admin endpoints are part of [`Self::router`].
+    pub fn admin_router(&self) -> Result<Option<Router>> {
+        if self.options.endpoint.admin_address.is_none() {
+            return Ok(None);
+        }
+
+        self.scoped_router(RouterScope::Admin).map(Some)
+    }
+
+    /// Returns the router for the given endpoints including its state and layers. The
+    /// registered extensions are applied to the routers of all scopes.
+    ///
+    /// # Arguments
+    /// - `scope` - The endpoints that are served by the router.
+    fn scoped_router(&self, scope: RouterScope) -> Result<Router> {
+        let app = Self::setup_routes(
+            self.db.clone(),
+            &self.options.endpoint,
+            self.read_only.clone(),
+            self.credentials_source.clone(),
+            self.nova_classifier.clone(),
+            self.image_regeneration.clone(),
+            scope,
+        )?;
+
+        // apply the registered extensions in the order of their registration
+        let app = self
+            .extensions
+            .iter()
+            .fold(app, |app, extension| extension(app));
+
+        Ok(app)
+    }
+
+    /// Binds the service to the configured addresses and serves the requests until stopped.
+    pub async fn run(&self) -> Result<()> {
+        let app = self.router()?;
+        let admin_app = self.admin_router()?;
+
+        let listener = Self::bind(&self.options.endpoint.address).await?;
+        let admin_listener = match &self.options.endpoint.admin_address {
+            Some(admin_address) => Some(Self::bind(admin_address).await?),
+            None => None,
+        };
+
+        let tls_acceptor = self
+            .options
+            .endpoint
+            .tls
+            .as_ref()
+            .map(tls_acceptor)
+            .transpose()?;
+
+        if let Some(interval) = self.options.archive.interval() {
+            tokio::spawn(Self::archive_periodically(
+                self.db.clone(),
+                interval,
+                self.stop_signal_receiver.clone(),
+            ));
+        }
+
+        // start the server...
+        if tls_acceptor.is_some() {
+            info!("Starting the server with TLS...");
+        } else {
+            info!("Starting the server...");
+        }
+
+        let server = Self::serve(
+            listener,
+            app,
+            tls_acceptor.clone(),
+            self.stop_signal_receiver.clone(),
+        );
+        match admin_listener.zip(admin_app) {
+            Some((admin_listener, admin_app)) => {
+                let admin_server = Self::serve(
+                    admin_listener,
+                    admin_app,
+                    tls_acceptor,
+                    self.stop_signal_receiver.clone(),
+                );
+                try_join(server, admin_server).await?;
+            }
+            None => server.await?,
+        }
+
+        info!("Server stopped.");
+
+        Ok(())
+    }
+
+    /// Creates the listener on the given address.
+    ///
+    /// # Arguments
+    /// - `address` - The address to bind to, e.g. "0.0.0.0:8080".
+    async fn bind(address: &str) -> Result<TcpListener> {
+        info!("Start listening on '{}'...", address);
+        match TcpListener::bind(address).await {
+            Ok(listener) => {
+                info!("Start listening on '{}'...OK", address);
+                Ok(listener)
+            }
+            Err(e) => {
+                error!("Start listening on '{}'...FAILED", address);
+                error!("Failed to bind to the address {} due to {}", address, e);
+                Err(Error::NetworkError(e))
+            }
+        }
+    }
+
+    /// Serves the requests of the given listener until the stop signal is received.
+    ///
+    /// # Arguments
+    /// - `listener` - The listener to accept the connections on.
+    /// - `app` - The router for handling the requests.
+    /// - `tls_acceptor` - The acceptor for TLS connections, if TLS is enabled.
+    /// - `rx` - The receiver of the stop signal.
+    async fn serve(
+        listener: TcpListener,
+        app: Router,
+        tls_acceptor: Option<TlsAcceptor>,
+        rx: watch::Receiver<i32>,
+    ) -> Result<()> {
+        if let Some(tls_acceptor) = tls_acceptor {
+            serve_tls(listener, app, tls_acceptor, rx).await;
+
+            return Ok(());
+        }
+
+        axum::serve(
+            listener,
+            app.into_make_service_with_connect_info::<SocketAddr>(),
+        )
+        .with_graceful_shutdown(async move {
+            let mut rx = rx.clone();
+            // wait for the signal to shutdown the server
+            if let Err(err) = rx.changed().await {
+                warn!("Failed to receive the stop signal: {}", err);
+                return;
+            }
+
+            info!("Received stop signal, stopping the server...");
+        })
+        .await
+        .map_err(|e| {
+            error!("Server error: {}", e);
+            Error::NetworkError(e)
+        })
+    }
+
+    /// Handles the request for the OpenAPI specification.
+    ///
+    /// # Arguments
+    /// - `document` - The OpenAPI specification as JSON document.
+    async fn handle_openapi(
+        State(document): State<Arc<serde_json::Value>>,
+    ) -> Json<Arc<serde_json::Value>> {
+        debug!("Get OpenAPI specification");
+        Json(document)
+    }
+
+    /// Handles the request for the Swagger UI.
+    ///
+    /// # Arguments
+    /// - `page` - The rendered page of the Swagger UI.
+    async fn handle_swagger_ui(State(page): State<Arc<String>>) -> impl IntoResponse {
+        debug!("Get Swagger UI");
+
+        (
+            [(
+                header::CONTENT_TYPE,
+                HeaderValue::from_static(HTML_CONTENT_TYPE),
+            )],
+            page.to_string(),
+        )
+    }
+
+    /// Handles the request for the metrics in the Prometheus text format.
+    ///
+    /// # Arguments
+    /// - `db` - The data backend, which records its current state before rendering.
+    /// - `handle` - The handle of the Prometheus recorder.
+    async fn handle_metrics(
+        State((db, handle)): State<(Arc<DB>, PrometheusHandle)>,
+    ) -> impl IntoResponse {
+        debug!("Render metrics");
+        db.record_metrics();
+
+        (
+            [(
+                header::CONTENT_TYPE,
+                HeaderValue::from_static("text/plain; version=0.0.4"),
+            )],
+            handle.render(),
+        )
+    }
+
+    /// Handles the liveness probe, which succeeds as long as the service handles requests.
+    async fn handle_liveness() -> Json<OnlyMessageResponse> {
+        Json(OnlyMessageResponse {
+            message: "The service is alive.".to_string(),
+        })
+    }
+
+    /// Handles the readiness probe, which pings the data backend and responds with 503 if it
+    /// cannot be reached.
+    ///
+    /// # Arguments
+    /// - `db` - The data backend.
+    async fn handle_readiness(State(db): State<Arc<DB>>) -> (StatusCode, Json<HealthReport>) {
+        let start = Instant::now();
+        let result = db.ping().await;
+        let latency_ms = start.elapsed().as_millis() as u64;
+
+        match result {
+            Ok(()) => (
+                StatusCode::OK,
+                Json(HealthReport {
+                    status: HealthStatus::Healthy,
+                    details: None,
+                    latency_ms,
+                }),
+            ),
+            Err(err) => {
+                warn!("Readiness probe failed: {}", err);
+                (
+                    StatusCode::SERVICE_UNAVAILABLE,
+                    Json(HealthReport {
+                        status: HealthStatus::Unavailable,
+                        details: Some(err.to_string()),
+                        latency_ms,
+                    }),
+                )
+            }
+        }
+    }
+
+    /// Archives the product requests whose product has been added to the database in the given
+    /// interval until the service is stopped.
+    ///
+    /// # Arguments
+    /// - `db` - The data backend.
+    /// - `interval` - The interval between two archival runs.
+    /// - `rx` - The receiver for the stop signal.
+    async fn archive_periodically(
+        db: Arc<DB>,
+        interval: std::time::Duration,
+        mut rx: watch::Receiver<i32>,
+    ) {
+        info!("Archiving processed product requests every {:?}", interval);
+
+        // a stop signal or a dropped sender ends the loop before the timeout elapses
+        while tokio::time::timeout(interval, rx.changed()).await.is_err() {
+            if let Err(err) = db.archive_approved_product_requests().await {
+                error!("Scheduled archival of product requests failed: {}", err);
+            }
+        }
+
+        debug!("Scheduled archival of product requests stopped");
+    }
+
+    /// Regenerates the previews of all products and product requests with a full image and
+    /// records the progress. A failed image is logged and counted, but does not stop the
+    /// regeneration.
+    ///
+    /// # Arguments
+    /// - `db` - The data backend.
+    /// - `preview_generator` - The generator deriving the previews from the full images.
+    /// - `status` - The progress of the regeneration.
+    async fn regenerate_images(
+        db: Arc<DB>,
+        preview_generator: PreviewGenerator,
+        status: Arc<Mutex<ImageRegenerationStatus>>,
+    ) {
+        info!("Regenerating the preview images...");
+
+        // the owners are collected before any preview is replaced, so the total is known
+        let owners = match Self::collect_image_owners(&db).await {
+            Ok(owners) => owners,
+            Err(err) => {
+                error!("Regenerating the preview images...FAILED: {}", err);
+                let mut status = status.lock().unwrap();
+                status.running = false;
+                status.finished = Some(chrono::Utc::now());
+                return;
+            }
+        };
+        status.lock().unwrap().total = owners.len() as u64;
+
+        for owner in owners.iter() {
+            let result = Self::regenerate_preview(&db, &preview_generator, owner).await;
+
+            let mut status = status.lock().unwrap();
+            status.processed += 1;
+            match result {
+                Ok(true) => status.regenerated += 1,
+                Ok(false) => {}
+                Err(err) => {
+                    status.failed += 1;
+                    match owner {
+                        ImageOwner::Product(id) => {
+                            warn!(
+                                "Failed to regenerate the preview of product {}: {}",
+                                id, err
+                            )
+                        }
+                        ImageOwner::ProductRequest(id) => warn!(
+                            "Failed to regenerate the preview of product request {}: {}",
+                            id, err
+                        ),
+                    }
+                }
+            }
+        }
+
+        let mut status = status.lock().unwrap();
+        status.running = false;
+        status.finished = Some(chrono::Utc::now());
+        info!(
+            "Regenerating the preview images...DONE: {} regenerated, {} failed",
+            status.regenerated, status.failed
+        );
+    }
+
+    /// Returns all products and product requests, whose previews may be regenerated.
+    ///
+    /// # Arguments
+    /// - `db` - The data backend.
+    async fn collect_image_owners(db: &Arc<DB>) -> Result<Vec<ImageOwner>> {
+        let all = |offset| ProductQuery {
+            offset,
+            limit: EXPORT_PAGE_SIZE,
+            filter: SearchFilter::NoFilter,
+            sorting: None,
+            market: None,
+            producer: None,
+            quantity_type: None,
+            labels: None,
+            max_nova_group: None,
+            min_eco_score_grade: None,
+            exclude_allergens: Vec::new(),
+            data_issues: Vec::new(),
+            fields: None,
+            request_status: None,
+            cursor: None,
+        };
+
+        let products: Vec<_> = page_stream({
+            let db = db.clone();
+            move |offset| {
+                let db = db.clone();
+                let query = all(offset);
+                async move { db.query_products(&query, false).await }
+            }
         })
+        .try_collect()
+        .await?;
+        let requests: Vec<_> = page_stream({
+            let db = db.clone();
+            move |offset| {
+                let db = db.clone();
+                let query = all(offset);
+                async move { db.query_product_requests(&query, false).await }
+            }
+        })
+        .try_collect()
+        .await?;
+
+        Ok(products
+            .into_iter()
+            .map(|product| ImageOwner::Product(product.info.id))
+            .chain(
+                requests
+                    .into_iter()
+                    .map(|(id, _)| ImageOwner::ProductRequest(id)),
+            )
+            .collect())
+    }
+
+    /// Generates the preview of a submitted product from its full image if the product has no
+    /// preview and previews are configured. The product is kept without preview if the full
+    /// image cannot be decoded, e.g. due to an unsupported format.
+    ///
+    /// # Arguments
+    /// - `product` - The submitted product.
+    /// - `previews` - The options for generating the previews, if configured.
+    async fn with_generated_preview(
+        mut product: ProductDescription,
+        previews: Option<PreviewOptions>,
+    ) -> ProductDescription {
+        let (Some(options), None, Some(full_image)) =
+            (previews, &product.preview, &product.full_image)
+        else {
+            return product;
+        };
+
+        // decoding and scaling the image is CPU bound
+        let full_image = full_image.clone();
+        match tokio::task::spawn_blocking(move || generate_preview(&full_image, &options)).await {
+            Ok(Ok(preview)) => {
+                debug!(
+                    "Generated preview of {} bytes for product {}",
+                    preview.data.len(),
+                    product.info.id
+                );
+                product.preview = Some(preview);
+            }
+            Ok(Err(err)) => warn!(
+                "Failed to generate the preview of product {}: {}",
+                product.info.id, err
+            ),
+            Err(err) => error!("Failed to generate the preview: {}", err),
+        }
+
+        product
+    }
+
+    /// Regenerates the preview of the product or product request from its full image. Returns
+    /// false if there is no full image.
+    ///
+    /// # Arguments
+    /// - `db` - The data backend.
+    /// - `preview_generator` - The generator deriving the preview from the full image.
+    /// - `owner` - The product or product request.
+    async fn regenerate_preview(
+        db: &DB,
+        preview_generator: &PreviewGenerator,
+        owner: &ImageOwner,
+    ) -> Result<bool> {
+        let full_image = match owner {
+            ImageOwner::Product(id) => db.get_product_image(id).await?,
+            ImageOwner::ProductRequest(id) => db.get_product_request_image(*id).await?,
+        };
+        let Some(full_image) = full_image else {
+            return Ok(false);
+        };
+
+        // decoding and scaling the image is CPU bound
+        let preview_generator = preview_generator.clone();
+        let preview = tokio::task::spawn_blocking(move || preview_generator(&full_image))
+            .await
+            .map_err(|e| Error::InternalError(e.to_string()))??;
+
+        match owner {
+            ImageOwner::Product(id) => db.update_product_preview(id, &preview).await,
+            ImageOwner::ProductRequest(id) => {
+                db.update_product_request_preview(*id, &preview).await
+            }
+        }
+    }
+
+    /// Stops the service.
+    pub fn stop(&self) {
+        info!("Stopping the server...");
+        if let Err(err) = self.stop_signal_sender.send(1) {
+            error!("Failed to send the stop signal: {}", err);
+        }
+    }
+
+    /// Switches the read-only (maintenance) mode of the service on or off.
+    /// While in read-only mode, all mutating endpoints respond with 503. Fails with
+    /// [`Error::ReadOnlyBackend`] when switching off the mode of a service created by
+    /// [`Service::read_only`].
+    ///
+    /// # Arguments
+    /// - `read_only` - Whether the service should be in read-only mode.
+    pub fn set_read_only(&self, read_only: bool) -> Result<()> {
+        if self.read_only.pinned && !read_only {
+            warn!("Cannot switch off the read-only mode of a read-only backend");
+            return Err(Error::ReadOnlyBackend);
+        }
+
+        info!("Set read-only mode to {}", read_only);
+        self.read_only.enabled.store(read_only, Ordering::SeqCst);
+
+        Ok(())
+    }
+
+    /// Returns true if the service is currently in read-only (maintenance) mode.
+    pub fn is_read_only(&self) -> bool {
+        self.read_only.enabled.load(Ordering::SeqCst)
+    }
+
+    /// Reloads the database password from the credentials source and reconnects the data
+    /// backend, e.g. after a scheduled credential rotation.
+    pub async fn reload_credentials(&self) -> Result<()> {
+        Self::reload_credentials_from(&self.db, self.credentials_source.as_ref()).await
+    }
+
+    /// Reloads the database password from the given credentials source and reconnects the
+    /// data backend.
+    ///
+    /// # Arguments
+    /// - `db` - The data backend to reconnect.
+    /// - `credentials_source` - The source for the database password.
+    async fn reload_credentials_from(
+        db: &DB,
+        credentials_source: Option<&CredentialsSource>,
+    ) -> Result<()> {
+        let credentials_source = credentials_source.ok_or_else(|| {
+            Error::ConfigError("No source for reloading the credentials configured".to_string())
+        })?;
+
+        info!("Reloading the database credentials...");
+        let password = credentials_source().await?;
+        db.reconnect(password).await?;
+        info!("Reloading the database credentials...DONE");
+
+        Ok(())
+    }
+
+    /// Sets up the routes for the service and returns the app.
+    ///
+    /// # Arguments
+    /// - `db` - The data backend instance to use.
+    /// - `endpoint_options` - The options for the endpoint.
+    /// - `read_only` - The shared flag for the read-only (maintenance) mode.
+    /// - `credentials_source` - The source for reloading the database password, if any.
+    /// - `nova_classifier` - The classifier for the NOVA group of submitted products, if any.
+    /// - `image_regeneration` - The regeneration of the preview images.
+    /// - `scope` - The endpoints that are served by the app.
+    fn setup_routes(
+        db: Arc<DB>,
+        endpoint_options: &EndpointOptions,
+        read_only: ReadOnlyMode,
+        credentials_source: Option<CredentialsSource>,
+        nova_classifier: Option<NovaClassifier>,
+        image_regeneration: ImageRegeneration,
+        scope: RouterScope,
+    ) -> Result<Router> {
+        // the user and admin endpoints may allow different origins
+        let cors = Self::cors_layer(&endpoint_options.allow_origin)?;
+        let user_cors = match &endpoint_options.user_allow_origin {
+            Some(allow_origin) => Self::cors_layer(allow_origin)?,
+            None => cors.clone(),
+        };
+        let admin_cors = match &endpoint_options.admin_allow_origin {
+            Some(allow_origin) => Self::cors_layer(allow_origin)?,
+            None => cors.clone(),
+        };
+
+        let cache_policy = CachePolicy::from_options(&endpoint_options.cache_control)?;
+
+        // the image endpoints share a budget that is independent of the other endpoints
+        let image_rate_limiter = endpoint_options
+            .image_rate_limit
+            .as_ref()
+            .map(RateLimiter::from_options)
+            .transpose()?
+            .map(Arc::new);
+        if image_rate_limiter.is_some() {
+            info!("Rate limiting of the image endpoints is enabled");
+        }
+
+        let image_url_signer = ImageUrlSigner::from_options(endpoint_options).map(Arc::new);
+        if image_url_signer.is_some() {
+            info!("Signed image urls are enabled");
+        }
+
+        // the authenticator is shared by all endpoints to fetch the keys of the OIDC provider once
+        let authenticator = endpoint_options
+            .auth
+            .as_ref()
+            .map(|auth| Arc::new(Authenticator::from_options(auth)));
+
+        let mut api_routes = Router::new();
+        if scope != RouterScope::User {
+            let admin_app = Self::setup_guarded_admin_endpoint(
+                db.clone(),
+                endpoint_options,
+                &cache_policy,
+                image_rate_limiter.clone(),
+                read_only.clone(),
+                image_url_signer.clone(),
+                credentials_source,
+                nova_classifier.clone(),
+                image_regeneration,
+                authenticator.clone(),
+            );
+            api_routes = api_routes.nest("/v1/admin", Self::with_cors(admin_app, &admin_cors));
+        }
+        if endpoint_options.metrics && scope != RouterScope::User {
+            let handle = prometheus_handle().ok_or_else(|| {
+                Error::ConfigError(
+                    "Metrics cannot be served as another metrics recorder is installed".to_string(),
+                )
+            })?;
+
+            info!("Serving the metrics under /metrics");
+            let metrics_app = Router::new()
+                .route("/metrics", get(Self::handle_metrics))
+                .with_state((db.clone(), handle));
+            api_routes = api_routes.merge(Self::with_cors(metrics_app, &admin_cors));
+        }
+
+        // the probes are served on every listener and are not guarded
+        let probes_app = Router::new()
+            .route("/healthz", get(Self::handle_liveness))
+            .route("/readyz", get(Self::handle_readiness))
+            .with_state(db.clone());
+        api_routes = api_routes.merge(probes_app);
+
+        if scope != RouterScope::Admin {
+            let user_app = Self::setup_user_endpoint(
+                db.clone(),
+                endpoint_options,
+                &cache_policy,
+                image_rate_limiter.clone(),
+                read_only,
+                nova_classifier,
+            );
+            let user_app = match authenticator.filter(|a| a.guards_user_endpoints()) {
+                Some(authenticator) => {
+                    info!("Authentication is required on the user endpoints");
+                    user_app.layer(middleware::from_fn_with_state(
+                        (authenticator, Access::User),
+                        auth_guard,
+                    ))
+                }
+                None => user_app,
+            };
+            api_routes = api_routes.nest("/v1/user", Self::with_cors(user_app, &user_cors));
+
+            // the specification covers all endpoints but is served together with the user ones
+            let docs_app = Self::setup_docs_endpoint(endpoint_options)?;
+            api_routes = api_routes.merge(Self::with_cors(docs_app, &user_cors));
+
+            if let Some(image_url_signer) = image_url_signer {
+                let public_app = Self::setup_public_endpoint(
+                    db.clone(),
+                    endpoint_options,
+                    &cache_policy,
+                    image_rate_limiter,
+                    image_url_signer,
+                );
+                api_routes = api_routes.nest("/v1/public", Self::with_cors(public_app, &user_cors));
+            }
+        }
+        let app = if let Some(prefix) = &endpoint_options.prefix {
+            Router::new().nest(prefix, api_routes)
+        } else {
+            api_routes
+        };
+
+        // respond with JSON for unknown paths and unsupported methods
+        let api_base = format!("{}/v1", endpoint_options.prefix.as_deref().unwrap_or(""));
+        let not_found_app = Router::new()
+            .fallback(move |method: Method, uri: Uri| {
+                Self::handle_not_found(method, uri, api_base.clone())
+            })
+            .layer(cors);
+        let app = app
+            .merge(not_found_app)
+            .method_not_allowed_fallback(Self::handle_method_not_allowed);
+
+        // announce the deprecated routes and parameters identified by their route templates
+        let app = if endpoint_options.deprecations.is_empty() {
+            app
+        } else {
+            let deprecations = Deprecations::from_options(
+                &endpoint_options.deprecations,
+                endpoint_options.prefix.as_deref(),
+            )?;
+            info!(
+                "{} routes or parameters are marked as deprecated",
+                endpoint_options.deprecations.len()
+            );
+            app.layer(middleware::from_fn_with_state(
+                Arc::new(deprecations),
+                deprecation_headers,
+            ))
+        };
+
+        // the bodies must fit the largest accepted images, which are base64 encoded in JSON
+        let app = app.layer(DefaultBodyLimit::max(
+            endpoint_options.image_quota.max_body_bytes(),
+        ));
+
+        // catch panics of the handlers and respond with 500 instead of dropping the connection,
+        // the request id is set in the outer layers to make it available for logging the panic
+        let app = app.layer(CatchPanicLayer::custom(handle_panic));
+
+        // record the metrics outside of the panic handler to include its responses
+        let app = if endpoint_options.metrics {
+            app.layer(middleware::from_fn(record_request_metrics))
+        } else {
+            app
+        };
+
+        let app = app
+            .layer(middleware::from_fn(request_id_scope))
+            .layer(PropagateRequestIdLayer::x_request_id())
+            .layer(SetRequestIdLayer::x_request_id(MakeRequestUuid))
+            .with_state(db);
+
+        Ok(app)
+    }
+
+    /// Creates the CORS layer for the given allowed origin.
+    ///
+    /// # Arguments
+    /// - `allow_origin` - The allowed origin for CORS requests.
+    fn cors_layer(allow_origin: &str) -> Result<CorsLayer> {
+        let allow_origin = allow_origin.parse::<HeaderValue>().map_err(|e| {
+            error!("Failed to parse the allow-origin value: {}", e);
+
+            Error::ConfigError(format!("Failed to parse the allow-origin value: {}", e))
+        })?;
+
+        // the pagination headers are exposed to the scripts of the allowed origin
+        Ok(CorsLayer::new()
+            .allow_methods(vec![Method::GET, Method::POST, Method::PUT, Method::DELETE])
+            .allow_origin(allow_origin)
+            .expose_headers([
+                header::LINK,
+                HeaderName::from_static(TOTAL_COUNT_HEADER),
+                HeaderName::from_static(DEPRECATION_HEADER),
+                HeaderName::from_static(SUNSET_HEADER),
+            ]))
+    }
+
+    /// Applies the CORS layer to the routes of the router. The fallback for unsupported methods
+    /// is set beforehand, so that the layer also answers the preflight requests.
+    ///
+    /// # Arguments
+    /// - `router` - The router to apply the layer to.
+    /// - `cors` - The CORS layer for the routes.
+    fn with_cors<S>(router: Router<S>, cors: &CorsLayer) -> Router<S>
+    where
+        S: Clone + Send + Sync + 'static,
+    {
+        router
+            .method_not_allowed_fallback(Self::handle_method_not_allowed)
+            .layer(cors.clone())
+    }
+
+    /// Sets up the routes of the OpenAPI specification and, if configured, of the Swagger UI.
+    ///
+    /// # Arguments
+    /// - `endpoint_options` - The options for the endpoint.
+    fn setup_docs_endpoint(endpoint_options: &EndpointOptions) -> Result<Router<Arc<DB>>> {
+        let prefix = endpoint_options.prefix.as_deref();
+        let document = openapi_document(prefix)?;
+
+        let app = Router::new()
+            .route("/v1/openapi.json", get(Self::handle_openapi))
+            .with_state(Arc::new(document));
+
+        Ok(match &endpoint_options.swagger_ui {
+            Some(swagger_ui) => {
+                info!("Serving the Swagger UI under /v1/docs");
+                let spec_url = format!("{}/v1/openapi.json", prefix.unwrap_or(""));
+                let page = swagger_ui_page(&swagger_ui.assets_url, &spec_url);
+                app.merge(
+                    Router::new()
+                        .route("/v1/docs", get(Self::handle_swagger_ui))
+                        .with_state(Arc::new(page)),
+                )
+            }
+            None => app,
+        })
+    }
+
+    /// Sets up the admin endpoint including the guards for signed requests, client
+    /// certificates, API keys or JWTs and the IP allowlist, if configured.
+    ///
+    /// # Arguments
+    /// - `db` - The data backend instance to use.
+    /// - `endpoint_options` - The options for the endpoint.
+    /// - `cache_policy` - The Cache-Control headers for the responses.
+    /// - `image_rate_limiter` - The rate limiter of the image endpoints, if enabled.
+    /// - `read_only` - The shared flag for the read-only (maintenance) mode.
+    /// - `image_url_signer` - The signer for public image urls, if enabled.
+    /// - `credentials_source` - The source for reloading the database password, if any.
+    /// - `nova_classifier` - The classifier for the NOVA group of submitted products, if any.
+    /// - `image_regeneration` - The regeneration of the preview images.
+    /// - `authenticator` - The authenticator for API keys and JWTs, if configured.
+    #[allow(clippy::too_many_arguments)]
+    fn setup_guarded_admin_endpoint(
+        db: Arc<DB>,
+        endpoint_options: &EndpointOptions,
+        cache_policy: &CachePolicy,
+        image_rate_limiter: Option<Arc<RateLimiter>>,
+        read_only: ReadOnlyMode,
+        image_url_signer: Option<Arc<ImageUrlSigner>>,
+        credentials_source: Option<CredentialsSource>,
+        nova_classifier: Option<NovaClassifier>,
+        image_regeneration: ImageRegeneration,
+        authenticator: Option<Arc<Authenticator>>,
+    ) -> Router<Arc<DB>> {
+        let admin_app = Self::setup_admin_endpoint(
+            db.clone(),
+            endpoint_options,
+            cache_policy,
+            image_rate_limiter,
+            read_only,
+            image_url_signer,
+            nova_classifier,
+            image_regeneration,
+        )
+        .merge(
+            Router::new()
+                .route("/reload_credentials", post(Self::handle_reload_credentials))
+                .with_state((db, credentials_source)),
+        );
+        let admin_app = match &endpoint_options.request_signing {
+            Some(request_signing) => {
+                info!("Signed requests are required on the admin endpoints");
+                admin_app.layer(middleware::from_fn_with_state(
+                    RequestSigner::from_options(request_signing)
+                        .with_max_body_bytes(endpoint_options.image_quota.max_body_bytes()),
+                    request_signature_guard,
+                ))
+            }
+            None => admin_app,
+        };
+        let admin_app = match endpoint_options
+            .tls
+            .as_ref()
+            .and_then(|tls| tls.client_ca_file.as_ref())
+        {
+            Some(_) => {
+                info!("Client certificates are required on the admin endpoints");
+                admin_app.layer(middleware::from_fn(client_certificate_guard))
+            }
+            None => admin_app,
+        };
+        let admin_app = match authenticator {
+            Some(authenticator) => {
+                info!("Authentication is required on the admin endpoints");
+                admin_app.layer(middleware::from_fn_with_state(
+                    (authenticator, Access::Admin),
+                    auth_guard,
+                ))
+            }
+            None => admin_app,
+        };
+        match &endpoint_options.admin_allowlist {
+            Some(admin_allowlist) => {
+                info!(
+                    "The admin endpoints are restricted to the networks {:?}",
+                    admin_allowlist.networks
+                );
+                admin_app.layer(middleware::from_fn_with_state(
+                    Arc::new(IpAllowlist::from_options(admin_allowlist)),
+                    ip_allowlist_guard,
+                ))
+            }
+            None => admin_app,
+        }
+    }
+
+    /// Sets up the admin endpoint.
+    ///
+    /// # Arguments
+    /// - `endpoint_options` - The options for the endpoint.
+    /// - `cache_policy` - The Cache-Control headers for the responses.
+    /// - `image_rate_limiter` - The rate limiter of the image endpoints, if enabled.
+    /// - `read_only` - The shared flag for the read-only (maintenance) mode.
+    /// - `image_url_signer` - The signer for public image urls, if enabled.
+    /// - `nova_classifier` - The classifier for the NOVA group of submitted products, if any.
+    /// - `image_regeneration` - The regeneration of the preview images.
+    #[allow(clippy::too_many_arguments)]
+    fn setup_admin_endpoint(
+        db: Arc<DB>,
+        endpoint_options: &EndpointOptions,
+        cache_policy: &CachePolicy,
+        image_rate_limiter: Option<Arc<RateLimiter>>,
+        read_only: ReadOnlyMode,
+        image_url_signer: Option<Arc<ImageUrlSigner>>,
+        nova_classifier: Option<NovaClassifier>,
+        image_regeneration: ImageRegeneration,
+    ) -> Router<Arc<DB>> {
+        let mut app = Router::new();
+
+        if let Some(image_url_signer) = image_url_signer {
+            let public_image_base = format!(
+                "{}/v1/public/image",
+                endpoint_options.prefix.as_deref().unwrap_or("")
+            );
+
+            app = app.merge(
+                Router::new()
+                    .route(
+                        "/product/{id}/signed_image_url",
+                        get(Self::handle_get_signed_image_url),
+                    )
+                    .with_state((image_url_signer, public_image_base)),
+            );
+        }
+
+        // guard for all mutating routes that must be rejected in read-only mode
+        let guard = middleware::from_fn_with_state(read_only.clone(), Self::read_only_guard);
+
+        let product_cache =
+            middleware::from_fn_with_state(cache_policy.product.clone(), cache_control);
+        let query_cache = middleware::from_fn_with_state(cache_policy.query.clone(), cache_control);
+
+        // the image routes have their own timeout budget
+        let image_app = Router::new()
+            .route(
+                "/product_request/{id}/image",
+                get(Self::handle_get_product_request_image),
+            )
+            .route(
+                "/product_request/{id}/images",
+                get(Self::handle_get_product_request_images),
+            )
+            .route(
+                "/missing_products/{id}/photo",
+                get(Self::handle_get_missing_product_photo),
+            )
+            // the configured image policy is meant for the public product images, the images of
+            // requests and reports must not end up in shared caches
+            .route_layer(middleware::from_fn_with_state(
+                Some(HeaderValue::from_static(PRIVATE_CACHE_CONTROL)),
+                cache_control,
+            ))
+            .route_layer(middleware::from_fn_with_state(
+                endpoint_options.image_timeout(),
+                timeout_guard,
+            ))
+            .route_layer(middleware::from_fn_with_state(
+                image_rate_limiter,
+                rate_limit_guard,
+            ));
+
+        let maintenance_app = Router::new()
+            .route(
+                "/maintenance",
+                get(Self::handle_get_maintenance_mode).put(Self::handle_set_maintenance_mode),
+            )
+            .with_state(read_only);
+
+        // the regeneration modifies the previews, i.e., only starting it is rejected in
+        // read-only mode
+        let regeneration_app = Router::new()
+            .route(
+                "/maintenance/regenerate_images",
+                post(Self::handle_regenerate_images).layer(guard.clone()),
+            )
+            .route(
+                "/maintenance/regenerate_images",
+                get(Self::handle_get_image_regeneration),
+            )
+            .with_state((db.clone(), image_regeneration));
+
+        let image_usage_app = Router::new()
+            .route("/images/usage", get(Self::handle_image_storage_usage))
+            .with_state((db.clone(), endpoint_options.image_quota.clone()));
+
+        let submission_app = Router::new()
+            .route(
+                "/product",
+                post(Self::handle_new_product).layer(guard.clone()),
+            )
+            .route(
+                "/product/{id}",
+                put(Self::handle_update_product).layer(guard.clone()),
+            )
+            .with_state((
+                db,
+                nova_classifier,
+                endpoint_options.image_quota.clone(),
+                endpoint_options.nutrient_plausibility.products,
+                endpoint_options.previews,
+            ));
+
+        app.route(
+            "/product_request/{request_id}",
+            delete(Self::handle_delete_product_request).layer(guard.clone()),
+        )
+        .route(
+            "/product_request/{request_id}",
+            get(Self::handle_get_product_request).layer(product_cache),
+        )
+        .route(
+            "/product_request/query",
+            post(Self::handle_product_request_query).layer(query_cache.clone()),
+        )
+        .route(
+            "/product_request/export",
+            get(Self::handle_get_product_request_export).post(Self::handle_product_request_export),
+        )
+        .route(
+            "/product_request/{request_id}/archive",
+            post(Self::handle_archive_product_request).layer(guard.clone()),
+        )
+        .route(
+            "/product_request/{request_id}/approve",
+            post(Self::handle_approve_product_request).layer(guard.clone()),
+        )
+        .route(
+            "/product_request/{request_id}/reject",
+            post(Self::handle_reject_product_request).layer(guard.clone()),
+        )
+        .route(
+            "/archive/query",
+            post(Self::handle_archive_query).layer(query_cache.clone()),
+        )
+        .route(
+            "/missing_products/query",
+            post(Self::handle_missing_products_query).layer(query_cache.clone()),
+        )
+        .route(
+            "/missing_products/count",
+            post(Self::handle_missing_products_count).layer(query_cache.clone()),
+        )
+        .route(
+            "/missing_products/summary",
+            get(Self::handle_missing_products_summary),
+        )
+        .route(
+            "/missing_products/export",
+            get(Self::handle_missing_products_export),
+        )
+        .route(
+            "/missing_products/{id}",
+            get(Self::handle_get_missing_product),
+        )
+        .route(
+            "/missing_products/{id}",
+            delete(Self::handle_delete_missing_product).layer(guard.clone()),
+        )
+        .route(
+            "/product/{id}",
+            delete(Self::handle_delete_product).layer(guard.clone()),
+        )
+        .route(
+            "/product/query",
+            post(Self::handle_product_query).layer(query_cache),
+        )
+        .route(
+            "/recipe",
+            post(Self::handle_new_recipe).layer(guard.clone()),
+        )
+        .route(
+            "/recipe/{id}",
+            put(Self::handle_update_recipe)
+                .delete(Self::handle_delete_recipe)
+                .layer(guard.clone()),
+        )
+        .route(
+            "/selftest",
+            post(Self::handle_self_test).layer(guard.clone()),
+        )
+        .route("/store", post(Self::handle_new_store).layer(guard.clone()))
+        .route(
+            "/store/{id}",
+            delete(Self::handle_delete_store).layer(guard),
+        )
+        .merge(maintenance_app)
+        .merge(regeneration_app)
+        .merge(image_usage_app)
+        .merge(submission_app)
+        .route_layer(middleware::from_fn_with_state(
+            endpoint_options.query_timeout(),
+            timeout_guard,
+        ))
+        .merge(image_app)
+    }
+
+    /// Sets up the user endpoint.
+    ///
+    /// # Arguments
+    /// - `db` - The data backend instance to use.
+    /// - `endpoint_options` - The options for the endpoint.
+    /// - `cache_policy` - The Cache-Control headers for the responses.
+    /// - `image_rate_limiter` - The rate limiter of the image endpoints, if enabled.
+    /// - `read_only` - The shared flag for the read-only (maintenance) mode.
+    /// - `nova_classifier` - The classifier for the NOVA group of submitted products, if any.
+    fn setup_user_endpoint(
+        db: Arc<DB>,
+        endpoint_options: &EndpointOptions,
+        cache_policy: &CachePolicy,
+        image_rate_limiter: Option<Arc<RateLimiter>>,
+        read_only: ReadOnlyMode,
+        nova_classifier: Option<NovaClassifier>,
+    ) -> Router<Arc<DB>> {
+        let app = Router::new();
+
+        #[cfg(feature = "json-schema")]
+        let app = app.route("/schema/{name}", get(Self::handle_get_json_schema));
+
+        // guard for all mutating routes that must be rejected in read-only mode
+        let guard = middleware::from_fn_with_state(read_only, Self::read_only_guard);
+
+        let product_cache =
+            middleware::from_fn_with_state(cache_policy.product.clone(), cache_control);
+        let query_cache = middleware::from_fn_with_state(cache_policy.query.clone(), cache_control);
+
+        // the image routes have their own timeout budget
+        let image_app = Router::new()
+            .route("/product/{id}/image", get(Self::handle_get_product_image))
+            .route("/product/{id}/images", get(Self::handle_get_product_images))
+            .route_layer(middleware::from_fn_with_state(
+                cache_policy.image.clone(),
+                cache_control,
+            ))
+            .route_layer(middleware::from_fn_with_state(
+                endpoint_options.image_timeout(),
+                timeout_guard,
+            ))
+            .route_layer(middleware::from_fn_with_state(
+                image_rate_limiter,
+                rate_limit_guard,
+            ));
+
+        let report_app = Router::new()
+            .route(
+                "/missing_products",
+                post(Self::handle_report_missing_product).layer(guard.clone()),
+            )
+            .with_state((db.clone(), endpoint_options.image_quota.clone()));
+
+        let submission_app = Router::new()
+            .route(
+                "/product_request",
+                post(Self::handle_product_request).layer(guard.clone()),
+            )
+            .with_state((
+                db,
+                nova_classifier,
+                endpoint_options.image_quota.clone(),
+                endpoint_options.nutrient_plausibility.requests,
+                endpoint_options.previews,
+            ));
+
+        app.route(
+            "/missing_products/batch",
+            post(Self::handle_report_missing_products_batch).layer(guard.clone()),
+        )
+        .route(
+            "/price",
+            post(Self::handle_submit_price).layer(guard.clone()),
+        )
+        .route("/price/query", post(Self::handle_price_query))
+        .route(
+            "/product/{id}/cheapest_price",
+            get(Self::handle_get_cheapest_price),
+        )
+        .route(
+            "/product/{id}",
+            get(Self::handle_get_product).layer(product_cache),
+        )
+        .route(
+            "/product/query",
+            post(Self::handle_product_query).layer(query_cache.clone()),
+        )
+        .route(
+            "/product/count",
+            post(Self::handle_product_count).layer(query_cache),
+        )
+        .route("/product/export", post(Self::handle_product_export))
+        .route(
+            "/nutrients/aggregate",
+            post(Self::handle_aggregate_nutrients),
+        )
+        .route("/recipe/{id}", get(Self::handle_get_recipe))
+        .route("/store/{id}", get(Self::handle_get_store))
+        .route("/store/query", post(Self::handle_store_query))
+        .route(
+            "/store/{id}/availability",
+            post(Self::handle_report_availability).layer(guard),
+        )
+        .route(
+            "/product/{id}/availability",
+            get(Self::handle_get_product_availability),
+        )
+        .merge(report_app)
+        .merge(submission_app)
+        .route_layer(middleware::from_fn_with_state(
+            endpoint_options.query_timeout(),
+            timeout_guard,
+        ))
+        .merge(image_app)
+    }
+
+    /// Sets up the public endpoint that serves the images referenced by signed urls.
+    ///
+    /// # Arguments
+    /// - `db` - The data backend instance to use.
+    /// - `endpoint_options` - The options for the endpoint.
+    /// - `cache_policy` - The Cache-Control headers for the responses.
+    /// - `image_rate_limiter` - The rate limiter of the image endpoints, if enabled.
+    /// - `image_url_signer` - The signer for verifying the public image urls.
+    fn setup_public_endpoint(
+        db: Arc<DB>,
+        endpoint_options: &EndpointOptions,
+        cache_policy: &CachePolicy,
+        image_rate_limiter: Option<Arc<RateLimiter>>,
+        image_url_signer: Arc<ImageUrlSigner>,
+    ) -> Router<Arc<DB>> {
+        Router::new()
+            .route("/image/{token}", get(Self::handle_get_public_image))
+            .route_layer(middleware::from_fn_with_state(
+                cache_policy.image.clone(),
+                cache_control,
+            ))
+            .route_layer(middleware::from_fn_with_state(
+                endpoint_options.image_timeout(),
+                timeout_guard,
+            ))
+            .route_layer(middleware::from_fn_with_state(
+                image_rate_limiter,
+                rate_limit_guard,
+            ))
+            .with_state((db, image_url_signer))
+    }
+
+    /// Fallback: Handles requests for paths that do not exist.
+    ///
+    /// # Arguments
+    /// - `method` - The method of the request.
+    /// - `uri` - The uri of the request.
+    /// - `api_base` - The base path of the API used as hint in the response.
+    async fn handle_not_found(
+        method: Method,
+        uri: Uri,
+        api_base: String,
+    ) -> (StatusCode, Json<OnlyMessageResponse>) {
+        info!("No route for {} {}", method, uri);
+
+        (
+            StatusCode::NOT_FOUND,
+            Json(OnlyMessageResponse {
+                message: format!(
+                    "No endpoint found for {} {}. The endpoints are located under {}/user and {}/admin.",
+                    method,
+                    uri.path(),
+                    api_base,
+                    api_base
+                ),
+            }),
+        )
+    }
+
+    /// Fallback: Handles requests with a method that is not supported by the path.
+    ///
+    /// # Arguments
+    /// - `method` - The method of the request.
+    /// - `uri` - The uri of the request.
+    async fn handle_method_not_allowed(
+        method: Method,
+        uri: Uri,
+    ) -> (StatusCode, Json<OnlyMessageResponse>) {
+        info!("Method {} not allowed for {}", method, uri);
+
+        (
+            StatusCode::METHOD_NOT_ALLOWED,
+            Json(OnlyMessageResponse {
+                message: format!(
+                    "Method {} is not allowed for {}. See the Allow header for the supported methods.",
+                    method,
+                    uri.path()
+                ),
+            }),
+        )
+    }
+
+    /// Middleware that rejects the request with 503 if the service is in read-only mode.
+    async fn read_only_guard(
+        State(read_only): State<ReadOnlyMode>,
+        request: Request,
+        next: Next,
+    ) -> Response {
+        if read_only.enabled.load(Ordering::SeqCst) {
+            warn!(
+                "Rejected {} {} due to read-only mode",
+                request.method(),
+                request.uri()
+            );
+
+            return (
+                StatusCode::SERVICE_UNAVAILABLE,
+                Json(OnlyMessageResponse {
+                    message: "The service is in read-only mode due to maintenance. Please try again later.".to_string(),
+                }),
+            )
+                .into_response();
+        }
+
+        next.run(request).await
+    }
+
+    /// GET: Handles getting the current read-only (maintenance) mode.
+    async fn handle_get_maintenance_mode(
+        State(read_only): State<ReadOnlyMode>,
+    ) -> (StatusCode, Json<MaintenanceModeResponse>) {
+        let read_only = read_only.enabled.load(Ordering::SeqCst);
+        debug!("Get maintenance mode: read_only={}", read_only);
+
+        (
+            StatusCode::OK,
+            Json(MaintenanceModeResponse {
+                message: "Maintenance mode retrieved.".to_string(),
+                read_only,
+            }),
+        )
+    }
+
+    /// PUT: Handles switching the read-only (maintenance) mode on or off. The mode of a
+    /// read-only backend cannot be switched off and is answered with 409.
+    async fn handle_set_maintenance_mode(
+        State(read_only): State<ReadOnlyMode>,
+        Json(payload): Json<MaintenanceModeRequest>,
+    ) -> (StatusCode, Json<MaintenanceModeResponse>) {
+        if read_only.pinned && !payload.read_only {
+            warn!("Rejected switching off the read-only mode of a read-only backend");
+
+            return (
+                StatusCode::CONFLICT,
+                Json(MaintenanceModeResponse {
+                    message: "The read-only mode of a read-only backend cannot be switched off."
+                        .to_string(),
+                    read_only: true,
+                }),
+            );
+        }
+
+        info!("Set read-only mode to {}", payload.read_only);
+        read_only.enabled.store(payload.read_only, Ordering::SeqCst);
+
+        (
+            StatusCode::OK,
+            Json(MaintenanceModeResponse {
+                message: "Maintenance mode updated.".to_string(),
+                read_only: payload.read_only,
+            }),
+        )
+    }
+
+    /// POST: Handles starting the regeneration of the preview images of all products and
+    /// product requests from their full images, e.g. after the size of the previews has
+    /// changed. The regeneration runs in the background, see
+    /// [`Self::handle_get_image_regeneration`] for its progress.
+    async fn handle_regenerate_images(
+        State((state, image_regeneration)): State<(Arc<DB>, ImageRegeneration)>,
+    ) -> (StatusCode, Json<ImageRegenerationResponse>) {
+        let Some(preview_generator) = image_regeneration.preview_generator.clone() else {
+            warn!("Cannot regenerate the images without preview generator");
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(ImageRegenerationResponse {
+                    message: "No preview generator configured.".to_string(),
+                    status: image_regeneration.status.lock().unwrap().clone(),
+                }),
+            );
+        };
+
+        let status = {
+            let mut status = image_regeneration.status.lock().unwrap();
+            if status.running {
+                info!("Regeneration of the images is already running");
+                return (
+                    StatusCode::CONFLICT,
+                    Json(ImageRegenerationResponse {
+                        message: "Regeneration of the images is already running.".to_string(),
+                        status: status.clone(),
+                    }),
+                );
+            }
+
+            *status = ImageRegenerationStatus {
+                running: true,
+                started: Some(chrono::Utc::now()),
+                ..Default::default()
+            };
+            status.clone()
+        };
+
+        tokio::spawn(Self::regenerate_images(
+            state,
+            preview_generator,
+            image_regeneration.status,
+        ));
+
+        (
+            StatusCode::ACCEPTED,
+            Json(ImageRegenerationResponse {
+                message: "Regeneration of the images started.".to_string(),
+                status,
+            }),
+        )
+    }
+
+    /// GET: Handles getting the progress of the last regeneration of the preview images.
+    async fn handle_get_image_regeneration(
+        State((_, image_regeneration)): State<(Arc<DB>, ImageRegeneration)>,
+    ) -> (StatusCode, Json<ImageRegenerationResponse>) {
+        let status = image_regeneration.status.lock().unwrap().clone();
+        debug!("Get image regeneration: {:?}", status);
+
+        (
+            StatusCode::OK,
+            Json(ImageRegenerationResponse {
+                message: "Image regeneration retrieved.".to_string(),
+                status,
+            }),
+        )
+    }
+
+    /// GET: Handles reporting the storage used by the images broken down by content type.
+    async fn handle_image_storage_usage(
+        State((state, image_quota)): State<(Arc<DB>, ImageQuotaOptions)>,
+    ) -> (StatusCode, Json<ImageStorageUsageResponse>) {
+        debug!("Get image storage usage");
+
+        let (status, message, content_types) = match state.image_storage_usage().await {
+            Ok(content_types) => (
+                StatusCode::OK,
+                "Image storage usage retrieved.".to_string(),
+                content_types,
+            ),
+            Err(err) => {
+                error!("Failed to get the image storage usage: {}", err);
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    err.to_string(),
+                    Vec::new(),
+                )
+            }
+        };
+
+        (
+            status,
+            Json(ImageStorageUsageResponse {
+                message,
+                total_images: content_types.iter().map(|usage| usage.images).sum(),
+                total_bytes: content_types.iter().map(|usage| usage.bytes).sum(),
+                max_total_bytes: image_quota.max_total_bytes,
+                max_product_bytes: image_quota.max_product_bytes,
+                max_image_bytes: image_quota.max_image_bytes,
+                content_types,
+            }),
+        )
+    }
+
+    /// POST: Handles running a write, read and delete cycle against the data backend.
+    async fn handle_self_test(
+        State(state): State<Arc<DB>>,
+    ) -> (StatusCode, Json<SelfTestResponse>) {
+        debug!("Run self-test");
+
+        let report = run_self_test(state.as_ref()).await;
+        let (status, message) = if report.ok {
+            (StatusCode::OK, "Self-test succeeded.".to_string())
+        } else {
+            let failed = report
+                .failed_operations()
+                .iter()
+                .map(|operation| operation.to_string())
+                .collect::<Vec<_>>();
+            (
+                StatusCode::SERVICE_UNAVAILABLE,
+                format!("Self-test failed: {}", failed.join(", ")),
+            )
+        };
+
+        (status, Json(SelfTestResponse { message, report }))
+    }
+
+    /// POST: Handles reloading the database credentials.
+    async fn handle_reload_credentials(
+        State((state, credentials_source)): State<(Arc<DB>, Option<CredentialsSource>)>,
+    ) -> (StatusCode, Json<OnlyMessageResponse>) {
+        match Self::reload_credentials_from(&state, credentials_source.as_ref()).await {
+            Ok(()) => (
+                StatusCode::OK,
+                Json(OnlyMessageResponse {
+                    message: "Database credentials reloaded.".to_string(),
+                }),
+            ),
+            Err(err @ Error::ConfigError(_)) => {
+                warn!("Failed to reload the database credentials: {}", err);
+                (
+                    StatusCode::BAD_REQUEST,
+                    Json(OnlyMessageResponse {
+                        message: err.to_string(),
+                    }),
+                )
+            }
+            Err(err) => {
+                error!("Failed to reload the database credentials: {}", err);
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(OnlyMessageResponse {
+                        message: err.to_string(),
+                    }),
+                )
+            }
+        }
+    }
+
+    /// GET: Handles getting the JSON Schema of a request or response type.
+    #[cfg(feature = "json-schema")]
+    async fn handle_get_json_schema(Path(name): Path<String>) -> Response {
+        debug!("Get JSON Schema: {}", name);
+
+        match crate::json_schema(&name) {
+            Some(schema) => (StatusCode::OK, Json(schema)).into_response(),
+            None => (
+                StatusCode::NOT_FOUND,
+                Json(OnlyMessageResponse {
+                    message: format!(
+                        "No JSON Schema for '{}'. Available schemas: {}",
+                        name,
+                        crate::JSON_SCHEMA_NAMES.join(", ")
+                    ),
+                }),
+            )
+                .into_response(),
+        }
+    }
+
+    /// Returns the description of the submitted product with the nutrients normalized to 100g
+    /// or 100ml. The market of the product must be an ISO 3166-1 alpha-2 country code and the
+    /// packaging and the Eco-Score, if any, must be valid. A missing NOVA group is derived by the
+    /// classifier and the allergens are sorted without duplicates. Returns the normalized
+    /// description together with the additional images of the product.
+    ///
+    /// # Arguments
+    /// - `submission` - The submitted product.
+    /// - `nova_classifier` - The classifier for the NOVA group of the product, if any.
+    fn normalize_submission(
+        submission: ProductSubmission,
+        nova_classifier: Option<&NovaClassifier>,
+    ) -> Result<(ProductDescription, Vec<AdditionalImage>)> {
+        if submission.images.len() > MAX_ADDITIONAL_IMAGES {
+            return Err(Error::TooManyImages {
+                count: submission.images.len(),
+                limit: MAX_ADDITIONAL_IMAGES,
+            });
+        }
+
+        let mut product_description = submission.product_description;
+        product_description.info.id = resolve_product_id(&product_description.info.id);
+
+        if let Some(market) = product_description.info.market.as_ref() {
+            if market.len() != 2 || !market.bytes().all(|c| c.is_ascii_uppercase()) {
+                return Err(Error::InvalidMarket(market.clone()));
+            }
+        }
+
+        if let Some(packaging) = product_description.packaging.as_ref() {
+            check_packaging(packaging)?;
+        }
+
+        if let Some(eco_score) = product_description.eco_score.as_ref() {
+            check_eco_score(eco_score)?;
+        }
+
+        product_description.info.allergens.sort();
+        product_description.info.allergens.dedup();
+
+        if product_description.info.nova_group.is_none() {
+            if let Some(nova_classifier) = nova_classifier {
+                product_description.info.nova_group = nova_classifier(&product_description);
+                debug!(
+                    "Classified product {} as NOVA group {:?}",
+                    product_description.info.id, product_description.info.nova_group
+                );
+            }
+        }
+
+        if let Some(nova_group) = product_description.info.nova_group {
+            if !(1..=4).contains(&nova_group) {
+                return Err(Error::InvalidNovaGroup(nova_group));
+            }
+        }
+
+        if let Some(reference_amount) = submission.reference_amount {
+            if !reference_amount.is_finite() || reference_amount <= 0.0 {
+                return Err(Error::InvalidReferenceAmount(reference_amount));
+            }
+
+            debug!(
+                "Normalizing the nutrients of product {} from {} to 100",
+                product_description.info.id, reference_amount
+            );
+            product_description.nutrients =
+                product_description.nutrients.normalized(reference_amount);
+        }
+
+        Ok((product_description, submission.images))
+    }
+
+    /// Checks the nutrients of the submitted product for plausibility, see [nutrient_issues].
+    /// Returns the violated rules as warnings or an error if implausible products are rejected.
+    ///
+    /// # Arguments
+    /// - `product_description` - The normalized description of the submitted product.
+    /// - `mode` - The handling of implausible nutrients.
+    fn check_plausibility(
+        product_description: &ProductDescription,
+        mode: PlausibilityMode,
+    ) -> Result<Vec<NutrientIssue>> {
+        if mode == PlausibilityMode::Off {
+            return Ok(Vec::new());
+        }
+
+        let issues = nutrient_issues(
+            &product_description.nutrients,
+            product_description.info.quantity_type,
+        );
+        if issues.is_empty() {
+            return Ok(issues);
+        }
+
+        let message = issues
+            .iter()
+            .map(|issue| issue.to_string())
+            .collect::<Vec<_>>()
+            .join(", ");
+        if mode == PlausibilityMode::Reject {
+            return Err(Error::ImplausibleNutrients(message));
+        }
+
+        warn!(
+            "Product {} has implausible nutrients: {}",
+            product_description.info.id, message
+        );
+        Ok(issues)
+    }
+
+    /// Checks that the images of the given product are accepted and that storing them stays
+    /// within the configured limits, i.e., the per-product limit and the total quota of the
+    /// image storage.
+    ///
+    /// # Arguments
+    /// - `db` - The data backend for determining the current usage of the image storage.
+    /// - `image_quota` - The configured limits of the image storage.
+    /// - `product_description` - The description of the product to store.
+    /// - `images` - The additional images of the product to store.
+    async fn check_image_quota(
+        db: &DB,
+        image_quota: &ImageQuotaOptions,
+        product_description: &ProductDescription,
+        images: &[AdditionalImage],
+    ) -> Result<()> {
+        let images: Vec<&ProductImage> = [
+            product_description.preview.as_ref(),
+            product_description.full_image.as_ref(),
+        ]
+        .into_iter()
+        .flatten()
+        .chain(images.iter().map(|image| &image.image))
+        .collect();
+
+        for image in images.iter() {
+            check_image(image_quota, image)?;
+        }
+
+        let size: u64 = images.iter().map(|image| image.data.len() as u64).sum();
+        Self::check_image_size(db, image_quota, size).await
+    }
+
+    /// Checks that storing images of the given total size stays within the configured limits,
+    /// i.e., the per-product limit and the total quota of the image storage.
+    ///
+    /// # Arguments
+    /// - `db` - The data backend for determining the current usage of the image storage.
+    /// - `image_quota` - The configured limits of the image storage.
+    /// - `size` - The total size of the images to store in bytes.
+    async fn check_image_size(db: &DB, image_quota: &ImageQuotaOptions, size: u64) -> Result<()> {
+        if size == 0 {
+            return Ok(());
+        }
+
+        if let Some(limit) = image_quota.max_product_bytes {
+            if size > limit {
+                return Err(Error::ProductImagesTooLarge { size, limit });
+            }
+        }
+
+        if let Some(limit) = image_quota.max_total_bytes {
+            let used: u64 = db
+                .image_storage_usage()
+                .await?
+                .iter()
+                .map(|usage| usage.bytes)
+                .sum();
+            if used + size > limit {
+                return Err(Error::ImageQuotaExceeded { used, limit });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Stores the additional images of a newly requested product. The request is deleted again
+    /// if the images cannot be stored.
+    ///
+    /// # Arguments
+    /// - `db` - The data backend to store the images in.
+    /// - `id` - The internal id of the new product request.
+    /// - `images` - The additional images of the requested product.
+    async fn add_request_images(db: &DB, id: DBId, images: &[AdditionalImage]) -> Result<()> {
+        if images.is_empty() {
+            return Ok(());
+        }
+
+        let result = match db.add_product_request_images(id, images).await {
+            Ok(true) => return Ok(()),
+            Ok(false) => Err(Error::InternalError(format!(
+                "Product request with id={} vanished",
+                id
+            ))),
+            Err(err) => Err(err),
+        };
+
+        db.delete_requested_product(id).await?;
+        result
+    }
+
+    /// POST: Handles a requesting a new product.
+    async fn handle_product_request(
+        State((state, nova_classifier, image_quota, plausibility, previews)): State<
+            SubmissionState<DB>,
+        >,
+        Json(payload): Json<ProductSubmission>,
+    ) -> (StatusCode, Json<ProductRequestResponse>) {
+        debug!("Received product request: {:?}", payload);
+
+        let submission = Self::normalize_submission(payload, nova_classifier.as_ref()).and_then(
+            |(product_description, images)| {
+                let warnings = Self::check_plausibility(&product_description, plausibility)?;
+                Ok((product_description, images, warnings))
+            },
+        );
+        let (product_description, images, warnings) = match submission {
+            Ok(submission) => submission,
+            Err(err) => {
+                error!("Failed to receive product request: {}", err);
+                return (
+                    StatusCode::BAD_REQUEST,
+                    Json(ProductRequestResponse {
+                        message: err.to_string(),
+                        date: None,
+                        id: None,
+                        warnings: Vec::new(),
+                    }),
+                );
+            }
+        };
+        let product_description = Self::with_generated_preview(product_description, previews).await;
+
+        if let Err(err) =
+            Self::check_image_quota(&state, &image_quota, &product_description, &images).await
+        {
+            error!("Failed to receive product request: {}", err);
+            return (
+                image_quota_status(&err),
+                Json(ProductRequestResponse {
+                    message: err.to_string(),
+                    date: None,
+                    id: None,
+                    warnings: Vec::new(),
+                }),
+            );
+        }
+
+        let product_request = ProductRequest {
+            product_description,
+            date: chrono::Utc::now(),
+            status: RequestStatus::Pending,
+            review_comment: None,
+        };
+
+        let result = state.request_new_product(&product_request).await;
+        if let Ok(id) = result {
+            if let Err(err) = Self::add_request_images(&state, id, &images).await {
+                error!("Failed to store the images of product request: {}", err);
+                return (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(ProductRequestResponse {
+                        message: err.to_string(),
+                        date: None,
+                        id: None,
+                        warnings: Vec::new(),
+                    }),
+                );
+            }
+        }
+
+        match result {
+            Ok(id) => {
+                info!("Product request received successfully");
+                (
+                    StatusCode::CREATED,
+                    Json(ProductRequestResponse {
+                        message: "Product request received successfully".to_string(),
+                        date: Some(product_request.date),
+                        id: Some(id),
+                        warnings,
+                    }),
+                )
+            }
+            Err(err) => {
+                error!("Failed to receive product request: {}", err);
+                (
+                    StatusCode::BAD_REQUEST,
+                    Json(ProductRequestResponse {
+                        message: err.to_string(),
+                        date: None,
+                        id: None,
+                        warnings: Vec::new(),
+                    }),
+                )
+            }
+        }
+    }
+
+    /// Stores the photo of a newly reported missing product. The report is deleted again if the
+    /// photo cannot be stored.
+    ///
+    /// # Arguments
+    /// - `db` - The data backend to store the photo in.
+    /// - `id` - The internal id of the new report.
+    /// - `photo` - The photo of the missing product.
+    async fn add_missing_product_photo(db: &DB, id: DBId, photo: &ProductImage) -> Result<()> {
+        let result = match db.set_missing_product_photo(id, photo).await {
+            Ok(true) => return Ok(()),
+            Ok(false) => Err(Error::InternalError(format!(
+                "Reported missing product with id={} vanished",
+                id
+            ))),
+            Err(err) => Err(err),
+        };
+
+        db.delete_reported_missing_product(id).await?;
+        result
+    }
+
+    /// POST: Handles reporting a missing product.
+    async fn handle_report_missing_product(
+        State((state, image_quota)): State<(Arc<DB>, ImageQuotaOptions)>,
+        Json(payload): Json<MissingProductReportRequest>,
+    ) -> (StatusCode, Json<MissingProductReportResponse>) {
+        debug!(
+            "Received missing product report: product_id={}, store_id={:?}, photo={}",
+            payload.product_id,
+            payload.store_id,
+            payload.photo.is_some()
+        );
+
+        let date = chrono::Utc::now();
+        if let Some(photo) = payload.photo.as_ref() {
+            let size = photo.data.len() as u64;
+            let checked = match check_image(&image_quota, photo) {
+                Ok(()) => Self::check_image_size(&state, &image_quota, size).await,
+                Err(err) => Err(err),
+            };
+            if let Err(err) = checked {
+                error!("Received missing product report failed: {}", err);
+                return (
+                    image_quota_status(&err),
+                    Json(MissingProductReportResponse {
+                        message: err.to_string(),
+                        date: Some(date),
+                        id: None,
+                        warnings: Vec::new(),
+                    }),
+                );
+            }
+        }
+
+        let missing_product = MissingProduct {
+            product_id: resolve_product_id(&payload.product_id),
+            date,
+            store_id: payload.store_id,
+        };
+
+        let result = state.report_missing_product(missing_product).await;
+        if let (Ok(id), Some(photo)) = (result.as_ref(), payload.photo.as_ref()) {
+            if let Err(err) = Self::add_missing_product_photo(&state, *id, photo).await {
+                error!("Failed to store the photo of the missing product: {}", err);
+                return (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(MissingProductReportResponse {
+                        message: err.to_string(),
+                        date: Some(date),
+                        id: None,
+                        warnings: Vec::new(),
+                    }),
+                );
+            }
+        }
+
+        match result {
+            Ok(id) => {
+                info!("Received missing product report successfully");
+                (
+                    StatusCode::CREATED,
+                    Json(MissingProductReportResponse {
+                        message: "Received missing product report successfully".to_string(),
+                        date: Some(date),
+                        id: Some(id),
+                        warnings: Vec::new(),
+                    }),
+                )
+            }
+            Err(err) => {
+                error!("Received missing product report failed: {}", err);
+                (
+                    StatusCode::BAD_REQUEST,
+                    Json(MissingProductReportResponse {
+                        message: err.to_string(),
+                        date: Some(date),
+                        id: None,
+                        warnings: Vec::new(),
+                    }),
+                )
+            }
+        }
+    }
+
+    /// POST: Handles reporting several missing products at once, e.g. of an offline scanning
+    /// session. Invalid product ids are rejected individually and the others are stored in a
+    /// single transaction.
+    async fn handle_report_missing_products_batch(
+        State(state): State<Arc<DB>>,
+        Json(mut payload): Json<MissingProductsBatchReportRequest>,
+    ) -> (StatusCode, Json<MissingProductsBatchReportResponse>) {
+        debug!(
+            "Received batch of {} missing product reports",
+            payload.product_ids.len()
+        );
+
+        if payload.product_ids.is_empty()
+            || payload.product_ids.len() > MAX_MISSING_PRODUCTS_BATCH_SIZE
+        {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(MissingProductsBatchReportResponse {
+                    message: format!(
+                        "The batch must contain between 1 and {} product ids",
+                        MAX_MISSING_PRODUCTS_BATCH_SIZE
+                    ),
+                    date: None,
+                    results: Vec::new(),
+                }),
+            );
+        }
+
+        for product_id in payload.product_ids.iter_mut() {
+            *product_id = resolve_product_id(product_id);
+        }
+
+        let is_valid = |product_id: &ProductID| {
+            !product_id.trim().is_empty() && product_id.chars().count() <= MAX_PRODUCT_ID_LENGTH
+        };
+
+        let date = chrono::Utc::now();
+        let missing_products: Vec<MissingProduct> = payload
+            .product_ids
+            .iter()
+            .filter(|product_id| is_valid(product_id))
+            .map(|product_id| MissingProduct {
+                product_id: product_id.clone(),
+                date,
+                store_id: payload.store_id,
+            })
+            .collect();
+
+        let ids = match state.report_missing_products(missing_products).await {
+            Ok(ids) => ids,
+            Err(err) => {
+                error!("Received batch of missing product reports failed: {}", err);
+                return (
+                    StatusCode::BAD_REQUEST,
+                    Json(MissingProductsBatchReportResponse {
+                        message: err.to_string(),
+                        date: Some(date),
+                        results: Vec::new(),
+                    }),
+                );
+            }
+        };
+
+        let num_reported = ids.len();
+        let mut ids = ids.into_iter();
+        let results: Vec<MissingProductReportResult> = payload
+            .product_ids
+            .into_iter()
+            .map(|product_id| {
+                if is_valid(&product_id) {
+                    MissingProductReportResult {
+                        product_id,
+                        id: ids.next(),
+                        message: "Reported".to_string(),
+                    }
+                } else {
+                    MissingProductReportResult {
+                        product_id,
+                        id: None,
+                        message: format!(
+                            "Invalid product id, expected between 1 and {} characters",
+                            MAX_PRODUCT_ID_LENGTH
+                        ),
+                    }
+                }
+            })
+            .collect();
+
+        info!(
+            "Received {} of {} missing product reports successfully",
+            num_reported,
+            results.len()
+        );
+        let status = if num_reported > 0 {
+            StatusCode::CREATED
+        } else {
+            StatusCode::BAD_REQUEST
+        };
+        (
+            status,
+            Json(MissingProductsBatchReportResponse {
+                message: format!(
+                    "Received {} of {} missing product reports",
+                    num_reported,
+                    results.len()
+                ),
+                date: Some(date),
+                results,
+            }),
+        )
+    }
+
+    /// DELETE: Handles deleting a requested product.
+    async fn handle_delete_product_request(
+        State(state): State<Arc<DB>>,
+        Path(request_id): Path<DBId>,
+    ) -> (StatusCode, Json<OnlyMessageResponse>) {
+        debug!("Deleting product request with id={}", request_id);
+
+        match state.delete_requested_product(request_id).await {
+            Ok(()) => {
+                info!("Deleting product request with id={} successful", request_id);
+                (
+                    StatusCode::OK,
+                    Json(OnlyMessageResponse {
+                        message: "Product request deleted.".to_string(),
+                    }),
+                )
+            }
+            Err(err) => {
+                error!("Failed to receive product request: {}", err);
+                (
+                    StatusCode::BAD_REQUEST,
+                    Json(OnlyMessageResponse {
+                        message: err.to_string(),
+                    }),
+                )
+            }
+        }
+    }
+
+    /// Loads the additional images of an approved product request that are carried over to the
+    /// product with the same id. Returns none if the request has no additional images or the
+    /// status and message of the error response, e.g. if the product does not exist.
+    ///
+    /// # Arguments
+    /// - `db` - The data backend to load the request from.
+    /// - `request_id` - The internal id of the approved product request.
+    async fn request_images_to_carry_over(
+        db: &DB,
+        request_id: DBId,
+    ) -> std::result::Result<Option<(ProductID, Vec<AdditionalImage>)>, (StatusCode, String)> {
+        let internal_error = |err: Error| {
+            error!("Failed to load the images of the product request: {}", err);
+            (StatusCode::INTERNAL_SERVER_ERROR, err.to_string())
+        };
+        let not_found = || {
+            (
+                StatusCode::NOT_FOUND,
+                "Product request not found".to_string(),
+            )
+        };
+
+        let product_request = db
+            .get_product_request(request_id, false)
+            .await
+            .map_err(internal_error)?
+            .ok_or_else(not_found)?;
+        let images = db
+            .get_product_request_images(request_id)
+            .await
+            .map_err(internal_error)?
+            .ok_or_else(not_found)?;
+        if images.is_empty() {
+            return Ok(None);
+        }
+
+        let product_id = product_request.product_description.info.id;
+        match db.get_product(&product_id, false).await {
+            Ok(Some(_)) => Ok(Some((product_id, images))),
+            Ok(None) => Err((
+                StatusCode::CONFLICT,
+                format!(
+                    "Product with id={} does not exist, the images cannot be carried over",
+                    product_id
+                ),
+            )),
+            Err(err) => Err(internal_error(err)),
+        }
+    }
+
+    /// POST: Handles approving a product request, i.e., adding it to the products.
+    async fn handle_approve_product_request(
+        State(state): State<Arc<DB>>,
+        Path(request_id): Path<DBId>,
+    ) -> (StatusCode, Json<ApproveProductRequestResponse>) {
+        debug!("Approving product request with id={}", request_id);
+
+        let (status, message, product_id) = match state.approve_product_request(request_id).await {
+            Ok(Some(product_id)) => {
+                info!(
+                    "Approved product request with id={} as product with id={}",
+                    request_id, product_id
+                );
+                (
+                    StatusCode::OK,
+                    "Product request approved.".to_string(),
+                    Some(product_id),
+                )
+            }
+            Ok(None) => (
+                StatusCode::NOT_FOUND,
+                "Product request not found".to_string(),
+                None,
+            ),
+            Err(err @ Error::ProductAlreadyExists(_)) => {
+                (StatusCode::CONFLICT, err.to_string(), None)
+            }
+            Err(err) => {
+                error!("Failed to approve product request: {}", err);
+                (StatusCode::INTERNAL_SERVER_ERROR, err.to_string(), None)
+            }
+        };
+
+        (
+            status,
+            Json(ApproveProductRequestResponse {
+                message,
+                product_id,
+            }),
+        )
+    }
+
+    /// POST: Handles rejecting a product request, which keeps it with the review comment.
+    async fn handle_reject_product_request(
+        State(state): State<Arc<DB>>,
+        Path(request_id): Path<DBId>,
+        Json(request): Json<RejectProductRequestRequest>,
+    ) -> (StatusCode, Json<OnlyMessageResponse>) {
+        debug!("Rejecting product request with id={}", request_id);
+
+        let (status, message) = match state
+            .reject_product_request(request_id, request.comment.as_deref())
+            .await
+        {
+            Ok(true) => {
+                info!("Rejected product request with id={}", request_id);
+                (StatusCode::OK, "Product request rejected.".to_string())
+            }
+            Ok(false) => (
+                StatusCode::NOT_FOUND,
+                "Product request not found".to_string(),
+            ),
+            Err(err) => {
+                error!("Failed to reject product request: {}", err);
+                (StatusCode::INTERNAL_SERVER_ERROR, err.to_string())
+            }
+        };
+
+        (status, Json(OnlyMessageResponse { message }))
+    }
+
+    /// POST: Handles archiving a processed product request.
+    async fn handle_archive_product_request(
+        State(state): State<Arc<DB>>,
+        Path(request_id): Path<DBId>,
+        Json(request): Json<ArchiveProductRequestRequest>,
+    ) -> (StatusCode, Json<OnlyMessageResponse>) {
+        debug!(
+            "Archiving product request with id={} as {}",
+            request_id, request.outcome
+        );
+
+        let carry_over = if request.outcome == RequestOutcome::Approved && request.carry_over_images
+        {
+            match Self::request_images_to_carry_over(&state, request_id).await {
+                Ok(carry_over) => carry_over,
+                Err((status, message)) => {
+                    return (status, Json(OnlyMessageResponse { message }));
+                }
+            }
+        } else {
+            None
+        };
+
+        match state
+            .archive_product_request(request_id, request.outcome)
+            .await
+        {
+            Ok(true) => {
+                info!(
+                    "Archiving product request with id={} successful",
+                    request_id
+                );
+
+                if let Some((product_id, images)) = carry_over {
+                    match state.add_product_images(&product_id, &images).await {
+                        Ok(true) => info!(
+                            "Carried over {} images to product with id={}",
+                            images.len(),
+                            product_id
+                        ),
+                        Ok(false) => warn!(
+                            "Product with id={} vanished before carrying over its images",
+                            product_id
+                        ),
+                        Err(err) => {
+                            error!("Failed to carry over the images of the request: {}", err);
+                            return (
+                                StatusCode::INTERNAL_SERVER_ERROR,
+                                Json(OnlyMessageResponse {
+                                    message: format!(
+                                        "Product request archived, but its images could not be \
+                                         carried over: {}",
+                                        err
+                                    ),
+                                }),
+                            );
+                        }
+                    }
+                }
+
+                (
+                    StatusCode::OK,
+                    Json(OnlyMessageResponse {
+                        message: "Product request archived.".to_string(),
+                    }),
+                )
+            }
+            Ok(false) => (
+                StatusCode::NOT_FOUND,
+                Json(OnlyMessageResponse {
+                    message: "Product request not found".to_string(),
+                }),
+            ),
+            Err(err) => {
+                error!("Failed to archive product request: {}", err);
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(OnlyMessageResponse {
+                        message: err.to_string(),
+                    }),
+                )
+            }
+        }
+    }
+
+    /// POST: Handles searching the archived product requests.
+    async fn handle_archive_query(
+        State(state): State<Arc<DB>>,
+        format: BodyFormat,
+        OriginalUri(uri): OriginalUri,
+        Query(page): Query<PageParams>,
+        Negotiated(mut query): Negotiated<ArchiveQuery>,
+    ) -> (StatusCode, PageLinks, Encoded<ArchiveQueryResponse>) {
+        query.product_id = query.product_id.as_deref().map(resolve_product_id);
+        page.apply(&mut query.offset, &mut query.limit);
+        debug!("Get archive query: {:?}", query);
+
+        match state.query_archived_product_requests(&query).await {
+            Ok(result) => {
+                info!("Archive query successful: {:?}", query);
+                (
+                    StatusCode::OK,
+                    PageLinks::new(&uri, query.offset, query.limit, result.len(), None),
+                    Encoded(
+                        format,
+                        ArchiveQueryResponse {
+                            message: "Query executed successful".to_string(),
+                            archived_product_requests: result,
+                        },
+                    ),
+                )
+            }
+            Err(err) => {
+                error!("Failed to query the archive: {}", err);
+                (
+                    StatusCode::BAD_REQUEST,
+                    PageLinks::default(),
+                    Encoded(
+                        format,
+                        ArchiveQueryResponse {
+                            message: err.to_string(),
+                            archived_product_requests: Vec::new(),
+                        },
+                    ),
+                )
+            }
+        }
+    }
+
+    /// GET: Handles getting a requested product.
+    async fn handle_get_product_request(
+        State(state): State<Arc<DB>>,
+        Path(request_id): Path<DBId>,
+        query: Query<GetProductRequestQuery>,
+    ) -> (StatusCode, Json<GetProductRequestResponse>) {
+        debug!("Get product request with id={}", request_id);
+
+        match state
+            .get_product_request(request_id, query.with_preview)
+            .await
+        {
+            Ok(Some(mut product_request)) => {
+                if query.with_full_image {
+                    match state.get_product_request_image(request_id).await {
+                        Ok(Some(image)) => {
+                            product_request.product_description.full_image = Some(image);
+                        }
+                        Ok(None) => {
+                            warn!("Product request with id={} has no full image", request_id);
+                        }
+                        Err(err) => {
+                            error!("Failed to receive product request image: {}", err);
+                            return (
+                                StatusCode::BAD_REQUEST,
+                                Json(GetProductRequestResponse {
+                                    message: err.to_string(),
+                                    product_request: None,
+                                    warnings: Vec::new(),
+                                }),
+                            );
+                        }
+                    }
+                }
+
+                info!("Get product request with id={} successful", request_id);
+                let product_description = &product_request.product_description;
+                let warnings = nutrient_issues(
+                    &product_description.nutrients,
+                    product_description.info.quantity_type,
+                );
+                (
+                    StatusCode::OK,
+                    Json(GetProductRequestResponse {
+                        message: "Product request found.".to_string(),
+                        product_request: Some(product_request),
+                        warnings,
+                    }),
+                )
+            }
+            Ok(None) => {
+                info!("Product request with id={} not found", request_id);
+                (
+                    StatusCode::NOT_FOUND,
+                    Json(GetProductRequestResponse {
+                        message: format!("Product with id={} not found", request_id),
+                        product_request: None,
+                        warnings: Vec::new(),
+                    }),
+                )
+            }
+            Err(err) => {
+                error!("Failed to receive product request: {}", err);
+                (
+                    StatusCode::BAD_REQUEST,
+                    Json(GetProductRequestResponse {
+                        message: err.to_string(),
+                        product_request: None,
+                        warnings: Vec::new(),
+                    }),
+                )
+            }
+        }
+    }
+
+    /// POST: Handles executing a product request query.
+    async fn handle_product_request_query(
+        State(state): State<Arc<DB>>,
+        format: BodyFormat,
+        OriginalUri(uri): OriginalUri,
+        Query(page): Query<PageParams>,
+        Negotiated(mut query): Negotiated<ProductQuery>,
+    ) -> (StatusCode, PageLinks, Encoded<ProductRequestQueryResponse>) {
+        query.filter.resolve_product_id();
+        page.apply(&mut query.offset, &mut query.limit);
+        debug!("Get product request query [Decoded]: {:?}", query);
+
+        match state.query_counted_product_requests(&query, true).await {
+            Ok((result, count)) => {
+                info!("Product request query successful: {:?}", query);
+                (
+                    StatusCode::OK,
+                    PageLinks::new(&uri, query.offset, query.limit, result.len(), Some(count)),
+                    Encoded(
+                        format,
+                        ProductRequestQueryResponse {
+                            message: "Query executed successful".to_string(),
+                            product_requests: result,
+                            total_count: count,
+                        },
+                    ),
+                )
+            }
+            Err(err) => {
+                error!("Failed to receive product request: {}", err);
+                (
+                    StatusCode::BAD_REQUEST,
+                    PageLinks::default(),
+                    Encoded(
+                        format,
+                        ProductRequestQueryResponse {
+                            message: err.to_string(),
+                            product_requests: Vec::new(),
+                            total_count: 0,
+                        },
+                    ),
+                )
+            }
+        }
+    }
+
+    /// POST: Handles executing a product request query.
+    async fn handle_missing_products_query(
+        State(state): State<Arc<DB>>,
+        format: BodyFormat,
+        OriginalUri(uri): OriginalUri,
+        Query(page): Query<PageParams>,
+        Negotiated(mut query): Negotiated<MissingProductQuery>,
+    ) -> (StatusCode, PageLinks, Encoded<MissingProductsQueryResponse>) {
+        query.product_id = query.product_id.as_deref().map(resolve_product_id);
+        page.apply(&mut query.offset, &mut query.limit);
+        debug!("Get missing product query: {:?}", query);
+
+        let result = match state.query_missing_products(&query).await {
+            Ok(result) => state
+                .count_missing_products(&query)
+                .await
+                .map(|count| (result, count)),
+            Err(err) => Err(err),
+        };
+
+        match result {
+            Ok((result, count)) => {
+                info!("Missing products query successful: {:?}", query);
+                (
+                    StatusCode::OK,
+                    PageLinks::new(&uri, query.offset, query.limit, result.len(), Some(count)),
+                    Encoded(
+                        format,
+                        MissingProductsQueryResponse {
+                            message: "Query executed successful".to_string(),
+                            missing_products: result,
+                        },
+                    ),
+                )
+            }
+            Err(err) => {
+                error!("Failed to receive product request: {}", err);
+                (
+                    StatusCode::BAD_REQUEST,
+                    PageLinks::default(),
+                    Encoded(
+                        format,
+                        MissingProductsQueryResponse {
+                            message: err.to_string(),
+                            missing_products: Vec::new(),
+                        },
+                    ),
+                )
+            }
+        }
+    }
+
+    /// GET: Handles exporting all product requests, e.g. as ZIP archive including the images
+    /// for an offline review, see [`Self::export_product_requests`].
+    async fn handle_get_product_request_export(
+        State(state): State<Arc<DB>>,
+        Query(export): Query<ExportQuery>,
+    ) -> Response {
+        debug!("Export all product requests as {:?}", export.format);
+
+        let query = ProductQuery {
+            offset: 0,
+            limit: EXPORT_PAGE_SIZE,
+            filter: SearchFilter::NoFilter,
+            sorting: None,
+            market: None,
+            producer: None,
+            quantity_type: None,
+            labels: None,
+            max_nova_group: None,
+            min_eco_score_grade: None,
+            exclude_allergens: Vec::new(),
+            data_issues: Vec::new(),
+            fields: None,
+            request_status: None,
+            cursor: None,
+        };
+
+        Self::export_product_requests(state, export.format, query).await
+    }
+
+    /// POST: Handles exporting all product requests matching a query, e.g. for a spreadsheet,
+    /// see [`Self::export_product_requests`].
+    async fn handle_product_request_export(
+        State(state): State<Arc<DB>>,
+        Query(export): Query<ExportQuery>,
+        Negotiated(mut query): Negotiated<ProductQuery>,
+    ) -> Response {
+        query.filter.resolve_product_id();
+        debug!("Export product requests: {:?}", query);
+
+        Self::export_product_requests(state, export.format, query).await
+    }
+
+    /// Returns the export of all product requests matching the query. The offset and the limit
+    /// of the query are ignored and its fields select the columns. The ZIP archive is streamed
+    /// instead, see [`Self::product_request_archive`].
+    ///
+    /// # Arguments
+    /// - `state` - The data backend.
+    /// - `format` - The file format of the export.
+    /// - `query` - The query for the exported product requests.
+    async fn export_product_requests(
+        state: Arc<DB>,
+        format: ExportFormat,
+        query: ProductQuery,
+    ) -> Response {
+        let fields = export_fields(query.fields.as_deref());
+        let query = export_query(query);
+
+        if format == ExportFormat::Zip {
+            return Self::product_request_archive(state, query);
+        }
+
+        let num_rows = match state.count_product_requests(&query).await {
+            Ok(num_rows) => num_rows,
+            Err(err) => {
+                error!("Failed to export product requests: {}", err);
+                return problem_response(StatusCode::BAD_REQUEST, err.to_string());
+            }
+        };
+        info!("Exporting {} product requests", num_rows);
+
+        let mut header = vec!["request_id", "date"];
+        header.extend(fields.iter().map(|field| field.name()));
+
+        let rows = page_stream(move |offset| {
+            let state = state.clone();
+            let query = ProductQuery {
+                offset,
+                ..query.clone()
+            };
+            async move { state.query_product_requests(&query, false).await }
+        })
+        .map_ok(move |(id, request)| {
+            let mut row = vec![
+                serde_json::Value::from(id),
+                serde_json::Value::from(request.date.to_rfc3339()),
+            ];
+            row.extend(product_row(&request.product_description, &fields));
+            row
+        })
+        .boxed();
+
+        export_response(format, "product_requests", header, num_rows, rows).await
+    }
+
+    /// Returns the ZIP archive of the product requests matching the query. The archive is
+    /// written in the background and streamed while the pages of the requests are loaded.
+    /// Each request is stored as "<id>/request.json" together with its images, e.g.
+    /// "<id>/preview.jpg" and "<id>/full_image.jpg". Failures abort the download.
+    ///
+    /// # Arguments
+    /// - `state` - The data backend.
+    /// - `query` - The query for the exported product requests with the page size as limit.
+    fn product_request_archive(state: Arc<DB>, query: ProductQuery) -> Response {
+        let (tx, rx) = mpsc::channel::<Result<Vec<u8>>>(4);
+        tokio::spawn(async move {
+            if let Err(err) = Self::write_product_request_archive(&state, query, &tx).await {
+                error!("Failed to export product requests as zip: {}", err);
+                let _ = tx.send(Err(err)).await;
+            }
+        });
+
+        let chunks = futures::stream::unfold(rx, |mut rx| async move {
+            rx.recv().await.map(|chunk| (chunk, rx))
+        });
+
+        (
+            StatusCode::OK,
+            [
+                (
+                    header::CONTENT_TYPE,
+                    HeaderValue::from_static(ZIP_CONTENT_TYPE),
+                ),
+                (
+                    header::CONTENT_DISPOSITION,
+                    HeaderValue::from_static("attachment; filename=\"product_requests.zip\""),
+                ),
+            ],
+            Body::from_stream(chunks),
+        )
+            .into_response()
+    }
+
+    /// Writes the ZIP archive of the product requests into the channel, one chunk per request.
+    /// Stops early without error if the client has closed the download.
+    ///
+    /// # Arguments
+    /// - `state` - The data backend.
+    /// - `query` - The query for the exported product requests with the page size as limit.
+    /// - `tx` - The channel for the chunks of the archive.
+    async fn write_product_request_archive(
+        state: &DB,
+        query: ProductQuery,
+        tx: &mpsc::Sender<Result<Vec<u8>>>,
+    ) -> Result<()> {
+        let mut writer = ZipStreamWriter::new();
+        let mut offset = 0;
+        loop {
+            let page = state
+                .query_product_requests(
+                    &ProductQuery {
+                        offset,
+                        ..query.clone()
+                    },
+                    true,
+                )
+                .await?;
+            if page.is_empty() {
+                break;
+            }
+            offset += page.len() as i32;
+
+            for (id, mut request) in page {
+                let preview = request.product_description.preview.take();
+                let full_image = state.get_product_request_image(id).await?;
+                request.product_description.full_image = None;
+
+                let json = serde_json::to_vec_pretty(&serde_json::json!({
+                    "id": id,
+                    "date": request.date,
+                    "product_description": request.product_description,
+                }))
+                .map_err(|e| Error::Serialization(Box::new(e)))?;
+
+                let mut chunk =
+                    writer.file(&format!("{}/request.json", id), &json, request.date)?;
+                for (name, image) in [("preview", preview), ("full_image", full_image)] {
+                    if let Some(image) = image {
+                        let path = format!(
+                            "{}/{}.{}",
+                            id,
+                            name,
+                            image_file_extension(&image.content_type)
+                        );
+                        chunk.extend(writer.file(&path, &image.data, request.date)?);
+                    }
+                }
+
+                if tx.send(Ok(chunk)).await.is_err() {
+                    debug!("The download of the product requests has been closed");
+                    return Ok(());
+                }
+            }
+        }
+
+        info!("Exported {} product requests as zip", offset);
+        let _ = tx.send(Ok(writer.finish()?)).await;
+
+        Ok(())
+    }
+
+    /// POST: Handles counting the missing products matching a query.
+    async fn handle_missing_products_count(
+        State(state): State<Arc<DB>>,
+        format: BodyFormat,
+        Negotiated(mut query): Negotiated<MissingProductQuery>,
+    ) -> (StatusCode, Encoded<CountResponse>) {
+        query.product_id = query.product_id.as_deref().map(resolve_product_id);
+        debug!("Count missing products: {:?}", query);
+
+        match state.count_missing_products(&query).await {
+            Ok(count) => {
+                info!("Missing products count successful: {:?}", query);
+                (
+                    StatusCode::OK,
+                    Encoded(
+                        format,
+                        CountResponse {
+                            message: "Query executed successful".to_string(),
+                            count,
+                        },
+                    ),
+                )
+            }
+            Err(err) => {
+                error!("Failed to count missing products: {}", err);
+                (
+                    StatusCode::BAD_REQUEST,
+                    Encoded(
+                        format,
+                        CountResponse {
+                            message: err.to_string(),
+                            count: 0,
+                        },
+                    ),
+                )
+            }
+        }
+    }
+
+    /// GET: Handles summarizing the reports of the missing products grouped by the product id,
+    /// e.g. the 50 most requested missing products.
+    async fn handle_missing_products_summary(
+        State(state): State<Arc<DB>>,
+        OriginalUri(uri): OriginalUri,
+        Query(page): Query<PageParams>,
+    ) -> (StatusCode, PageLinks, Json<MissingProductsSummaryResponse>) {
+        let mut offset = 0;
+        let mut limit = MISSING_PRODUCTS_SUMMARY_LIMIT;
+        page.apply(&mut offset, &mut limit);
+        debug!(
+            "Summarize missing products with offset={} and limit={}",
+            offset, limit
+        );
+
+        match state.summarize_missing_products(offset, limit).await {
+            Ok(missing_products) => {
+                info!("Summarized {} missing products", missing_products.len());
+                (
+                    StatusCode::OK,
+                    PageLinks::new(&uri, offset, limit, missing_products.len(), None),
+                    Json(MissingProductsSummaryResponse {
+                        message: "Query executed successful".to_string(),
+                        missing_products,
+                    }),
+                )
+            }
+            Err(err) => {
+                error!("Failed to summarize the missing products: {}", err);
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    PageLinks::default(),
+                    Json(MissingProductsSummaryResponse {
+                        message: err.to_string(),
+                        missing_products: Vec::new(),
+                    }),
+                )
+            }
+        }
+    }
+
+    /// GET: Handles exporting all missing products matching the filters, e.g. for a
+    /// spreadsheet.
+    async fn handle_missing_products_export(
+        State(state): State<Arc<DB>>,
+        Query(mut export): Query<MissingProductsExportQuery>,
+    ) -> Response {
+        export.product_id = export.product_id.as_deref().map(resolve_product_id);
+        debug!("Export missing products: {:?}", export);
+
+        let query = MissingProductQuery {
+            offset: 0,
+            limit: EXPORT_PAGE_SIZE,
+            product_id: export.product_id.clone(),
+            order: export.order.unwrap_or(SortingOrder::Ascending),
+        };
+
+        let num_rows = match state.count_missing_products(&query).await {
+            Ok(num_rows) => num_rows,
+            Err(err) => {
+                error!("Failed to export missing products: {}", err);
+                return problem_response(StatusCode::BAD_REQUEST, err.to_string());
+            }
+        };
+        info!("Exporting {} missing products", num_rows);
+
+        let rows = page_stream(move |offset| {
+            let state = state.clone();
+            let query = MissingProductQuery {
+                offset,
+                ..query.clone()
+            };
+            async move { state.query_missing_products(&query).await }
+        })
+        .map_ok(|(id, missing_product)| missing_product_row(id, &missing_product))
+        .boxed();
+
+        export_response(
+            export.format,
+            "missing_products",
+            MISSING_PRODUCTS_COLUMNS.to_vec(),
+            num_rows,
+            rows,
+        )
+        .await
+    }
+
+    /// GET: Handles getting reported missing product.
+    async fn handle_get_missing_product(
+        State(state): State<Arc<DB>>,
+        Path(request_id): Path<DBId>,
+    ) -> (StatusCode, Json<GetReportedMissingProductResponse>) {
+        debug!("Get reported missing product with id={}", request_id);
+
+        match state.get_missing_product(request_id).await {
+            Ok(Some(missing_product)) => {
+                info!(
+                    "Get reported missing product with id={} successful",
+                    request_id
+                );
+                (
+                    StatusCode::OK,
+                    Json(GetReportedMissingProductResponse {
+                        message: "Reported missing product found.".to_string(),
+                        missing_product: Some(missing_product),
+                    }),
+                )
+            }
+            Ok(None) => {
+                info!("Reported missing product with id={} not found", request_id);
+                (
+                    StatusCode::NOT_FOUND,
+                    Json(GetReportedMissingProductResponse {
+                        message: format!(
+                            "Reported missing product with id={} not found",
+                            request_id
+                        ),
+                        missing_product: None,
+                    }),
+                )
+            }
+            Err(err) => {
+                error!("Failed to receive reported missing product: {}", err);
+                (
+                    StatusCode::BAD_REQUEST,
+                    Json(GetReportedMissingProductResponse {
+                        message: err.to_string(),
+                        missing_product: None,
+                    }),
+                )
+            }
+        }
+    }
+
+    /// DELETE: Handles deleting a reported missing product.
+    async fn handle_delete_missing_product(
+        State(state): State<Arc<DB>>,
+        Path(report_id): Path<DBId>,
+    ) -> (StatusCode, Json<OnlyMessageResponse>) {
+        debug!("Deleting reported missing product with id={}", report_id);
+
+        match state.delete_reported_missing_product(report_id).await {
+            Ok(()) => {
+                info!(
+                    "Deleting reported missing product with id={} successful",
+                    report_id
+                );
+                (
+                    StatusCode::OK,
+                    Json(OnlyMessageResponse {
+                        message: "Product request deleted.".to_string(),
+                    }),
+                )
+            }
+            Err(err) => {
+                error!("Failed to receive product request: {}", err);
+                (
+                    StatusCode::BAD_REQUEST,
+                    Json(OnlyMessageResponse {
+                        message: err.to_string(),
+                    }),
+                )
+            }
+        }
+    }
+
+    /// Stores the additional images of a newly added product. The product is deleted again if
+    /// the images cannot be stored.
+    ///
+    /// # Arguments
+    /// - `db` - The data backend to store the images in.
+    /// - `id` - The id of the new product.
+    /// - `images` - The additional images of the new product.
+    async fn add_new_product_images(
+        db: &DB,
+        id: &ProductID,
+        images: &[AdditionalImage],
+    ) -> Result<()> {
+        if images.is_empty() {
+            return Ok(());
+        }
+
+        let result = match db.add_product_images(id, images).await {
+            Ok(true) => return Ok(()),
+            Ok(false) => Err(Error::InternalError(format!(
+                "Product with id={} vanished",
+                id
+            ))),
+            Err(err) => Err(err),
+        };
+
+        db.delete_product(id).await?;
+        result
+    }
+
+    /// POST: Handles adding a new product.
+    async fn handle_new_product(
+        State((state, nova_classifier, image_quota, plausibility, previews)): State<
+            SubmissionState<DB>,
+        >,
+        Json(payload): Json<ProductSubmission>,
+    ) -> (StatusCode, Json<OnlyMessageResponse>) {
+        debug!("Created new product: {:?}", payload);
+
+        let (payload, images) = match Self::normalize_submission(payload, nova_classifier.as_ref())
+            .and_then(|(product_description, images)| {
+                Self::check_plausibility(&product_description, plausibility)?;
+                Ok((product_description, images))
+            }) {
+            Ok(submission) => submission,
+            Err(err) => {
+                error!("Failed to add new product: {}", err);
+                return (
+                    StatusCode::BAD_REQUEST,
+                    Json(OnlyMessageResponse {
+                        message: err.to_string(),
+                    }),
+                );
+            }
+        };
+        let payload = Self::with_generated_preview(payload, previews).await;
+
+        if let Err(err) = Self::check_image_quota(&state, &image_quota, &payload, &images).await {
+            error!("Failed to add new product: {}", err);
+            return (
+                image_quota_status(&err),
+                Json(OnlyMessageResponse {
+                    message: err.to_string(),
+                }),
+            );
+        }
+
+        let result = state.new_product(&payload).await;
+        if let Ok(true) = result {
+            if let Err(err) = Self::add_new_product_images(&state, &payload.info.id, &images).await
+            {
+                error!("Failed to store the images of the new product: {}", err);
+                return (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(OnlyMessageResponse {
+                        message: err.to_string(),
+                    }),
+                );
+            }
+        }
+
+        match result {
+            Ok(ret) => {
+                if ret {
+                    info!("New product created successfully");
+                    (
+                        StatusCode::CREATED,
+                        Json(OnlyMessageResponse {
+                            message: "Product successfully created".to_string(),
+                        }),
+                    )
+                } else {
+                    error!("Product already exists: {}", payload.info);
+                    (
+                        StatusCode::CONFLICT,
+                        Json(OnlyMessageResponse {
+                            message: format!("Product with id={} already exists", payload.info.id),
+                        }),
+                    )
+                }
+            }
+            Err(err) => {
+                error!("Failed to add new product: {}", err);
+                (
+                    StatusCode::BAD_REQUEST,
+                    Json(OnlyMessageResponse {
+                        message: err.to_string(),
+                    }),
+                )
+            }
+        }
+    }
+
+    /// PUT: Handles updating the description of an existing product.
+    async fn handle_update_product(
+        State((state, nova_classifier, image_quota, plausibility, previews)): State<
+            SubmissionState<DB>,
+        >,
+        Path(product_id): Path<ProductID>,
+        Json(mut payload): Json<ProductSubmission>,
+    ) -> (StatusCode, Json<OnlyMessageResponse>) {
+        debug!("Update product {}: {:?}", product_id, payload);
+
+        // the id of the path takes precedence over the id of the description
+        payload.product_description.info.id = product_id;
+        let (payload, images) = match Self::normalize_submission(payload, nova_classifier.as_ref())
+            .and_then(|(product_description, images)| {
+                Self::check_plausibility(&product_description, plausibility)?;
+                Ok((product_description, images))
+            }) {
+            Ok(submission) => submission,
+            Err(err) => {
+                error!("Failed to update product: {}", err);
+                return (
+                    StatusCode::BAD_REQUEST,
+                    Json(OnlyMessageResponse {
+                        message: err.to_string(),
+                    }),
+                );
+            }
+        };
+        let payload = Self::with_generated_preview(payload, previews).await;
+
+        if let Err(err) = Self::check_image_quota(&state, &image_quota, &payload, &images).await {
+            error!("Failed to update product: {}", err);
+            return (
+                image_quota_status(&err),
+                Json(OnlyMessageResponse {
+                    message: err.to_string(),
+                }),
+            );
+        }
+
+        match state.update_product(&payload).await {
+            Ok(true) => {
+                if !images.is_empty() {
+                    if let Err(err) = state.add_product_images(&payload.info.id, &images).await {
+                        error!("Failed to store the images of the product: {}", err);
+                        return (
+                            StatusCode::INTERNAL_SERVER_ERROR,
+                            Json(OnlyMessageResponse {
+                                message: format!(
+                                    "Product updated, but its images could not be stored: {}",
+                                    err
+                                ),
+                            }),
+                        );
+                    }
+                }
+
+                info!("Product {} updated successfully", payload.info.id);
+                (
+                    StatusCode::OK,
+                    Json(OnlyMessageResponse {
+                        message: "Product successfully updated".to_string(),
+                    }),
+                )
+            }
+            Ok(false) => (
+                StatusCode::NOT_FOUND,
+                Json(OnlyMessageResponse {
+                    message: format!("Product with id={} not found", payload.info.id),
+                }),
+            ),
+            Err(err) => {
+                error!("Failed to update product: {}", err);
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(OnlyMessageResponse {
+                        message: err.to_string(),
+                    }),
+                )
+            }
+        }
+    }
+
+    /// POST: Handles deleting a product.
+    async fn handle_delete_product(
+        State(state): State<Arc<DB>>,
+        Path(product_id): Path<ProductID>,
+    ) -> (StatusCode, Json<OnlyMessageResponse>) {
+        let product_id = resolve_product_id(&product_id);
+        debug!("Delete product: {:?}", product_id);
+
+        match state.delete_product(&product_id).await {
+            Ok(_) => {
+                info!("Product deleted successfully");
+                (
+                    StatusCode::OK,
+                    Json(OnlyMessageResponse {
+                        message: "Product deleted successfully".to_string(),
+                    }),
+                )
+            }
+            Err(err) => {
+                error!("Failed to delete product: {}", err);
+                (
+                    StatusCode::BAD_REQUEST,
+                    Json(OnlyMessageResponse {
+                        message: err.to_string(),
+                    }),
+                )
+            }
+        }
+    }
+
+    /// GET: Handles getting the specified product.
+    async fn handle_get_product(
+        State(state): State<Arc<DB>>,
+        Path(product_id): Path<ProductID>,
+        query: Query<GetProductRequestQuery>,
+    ) -> (StatusCode, Json<GetProductResponse>) {
+        let product_id = resolve_product_id(&product_id);
+        debug!("Get product with id={}", product_id);
+
+        match state.get_product(&product_id, query.with_preview).await {
+            Ok(Some(mut product_description)) => {
+                if query.with_full_image {
+                    // the id of the product may differ from the requested GTIN by leading zeros
+                    match state.get_product_image(&product_description.info.id).await {
+                        Ok(Some(image)) => {
+                            product_description.full_image = Some(image);
+                        }
+                        Ok(None) => {
+                            warn!("Product with id={} has no full image", product_id);
+                        }
+                        Err(err) => {
+                            error!("Failed to receive product image: {}", err);
+                            return (
+                                StatusCode::BAD_REQUEST,
+                                Json(GetProductResponse {
+                                    message: err.to_string(),
+                                    product: None,
+                                }),
+                            );
+                        }
+                    }
+                }
+
+                info!("Get product with id={} successful", product_id);
+                (
+                    StatusCode::OK,
+                    Json(GetProductResponse {
+                        message: "Product found.".to_string(),
+                        product: Some(product_description),
+                    }),
+                )
+            }
+            Ok(None) => {
+                info!("Product with id={} not found", product_id);
+                (
+                    StatusCode::NOT_FOUND,
+                    Json(GetProductResponse {
+                        message: format!("Product with id={} not found", product_id),
+                        product: None,
+                    }),
+                )
+            }
+            Err(err) => {
+                error!("Failed to receive product: {}", err);
+                (
+                    StatusCode::BAD_REQUEST,
+                    Json(GetProductResponse {
+                        message: err.to_string(),
+                        product: None,
+                    }),
+                )
+            }
+        }
     }
 
-    /// Returns the router for the service.
-    pub async fn run(&self) -> Result<()> {
-        let app = Self::setup_routes(self.db.clone(), &self.options.endpoint)?;
-
-        let rx = self.stop_signal_receiver.clone();
-
-        let service_addr = self.options.endpoint.address.as_str();
+    /// POST: Handles executing a product query.
+    async fn handle_product_query(
+        State(state): State<Arc<DB>>,
+        format: BodyFormat,
+        OriginalUri(uri): OriginalUri,
+        Query(page): Query<PageParams>,
+        Negotiated(mut query): Negotiated<ProductQuery>,
+    ) -> Response {
+        query.filter.resolve_product_id();
+        page.apply(&mut query.offset, &mut query.limit);
+        debug!("Get product query [Decoded]: {:?}", query);
 
-        // create the listener on the given address
-        info!("Start listening on '{}'...", service_addr);
-        let listener = match tokio::net::TcpListener::bind(service_addr).await {
-            Ok(listener) => listener,
-            Err(e) => {
-                error!("Start listening on '{}'...FAILED", service_addr);
-                error!(
-                    "Failed to bind to the address {} due to {}",
-                    service_addr, e
-                );
-                return Err(Error::NetworkError(e));
-            }
-        };
+        // the preview images are only loaded if they are returned
+        let with_preview = query
+            .fields
+            .as_ref()
+            .is_none_or(|fields| fields.contains(&ProductField::Preview));
 
-        info!("Start listening on '{}'...OK", service_addr);
+        // the similarity of the products is returned if they are sorted by it
+        let scored = query
+            .sorting
+            .is_some_and(|sorting| sorting.field == SortingField::Similarity);
 
-        // start the server...
-        info!("Starting the server...");
-        axum::serve(listener, app)
-            .with_graceful_shutdown(async move {
-                let mut rx = rx.clone();
-                // wait for the signal to shutdown the server
-                if let Err(err) = rx.changed().await {
-                    warn!("Failed to receive the stop signal: {}", err);
-                    return;
-                }
+        match state.query_counted_products(&query, with_preview).await {
+            Ok((result, count)) => {
+                let (result, scores): (Vec<_>, Vec<_>) = result.into_iter().unzip();
+                let scores: Option<Vec<f32>> =
+                    scored.then(|| scores.into_iter().map(Option::unwrap_or_default).collect());
 
-                info!("Received stop signal, stopping the server...");
-            })
-            .await
-            .map_err(|e| {
-                error!("Server error: {}", e);
-                Error::NetworkError(e)
-            })?;
+                info!("Product query successful: {:?}", query);
 
-        info!("Server stopped.");
+                // the offset links only apply to the pages without cursor
+                let links = if query.cursor.is_some() {
+                    PageLinks::new(&uri, 0, 0, result.len(), Some(count))
+                } else {
+                    PageLinks::new(&uri, query.offset, query.limit, result.len(), Some(count))
+                };
 
-        Ok(())
-    }
+                // a full page of a sorted query continues after its last product
+                let next_cursor = query
+                    .sorting
+                    .filter(|_| result.len() >= query.limit.max(1) as usize)
+                    .zip(result.last())
+                    .map(|(sorting, product)| {
+                        let score = scores.as_ref().and_then(|scores| scores.last().copied());
+                        ProductCursor::after(sorting, product, score).encode()
+                    });
 
-    /// Stops the service.
-    pub fn stop(&self) {
-        info!("Stopping the server...");
-        if let Err(err) = self.stop_signal_sender.send(1) {
-            error!("Failed to send the stop signal: {}", err);
+                match query.fields.as_ref() {
+                    Some(fields) => (
+                        StatusCode::OK,
+                        links,
+                        Encoded(
+                            format,
+                            ProductProjectionQueryResponse {
+                                message: "Query executed successful".to_string(),
+                                products: result
+                                    .iter()
+                                    .map(|product| project_product(product, fields))
+                                    .collect(),
+                                scores,
+                                total_count: count,
+                                next_cursor,
+                            },
+                        ),
+                    )
+                        .into_response(),
+                    None => (
+                        StatusCode::OK,
+                        links,
+                        Encoded(
+                            format,
+                            ProductQueryResponse {
+                                message: "Query executed successful".to_string(),
+                                products: result,
+                                scores,
+                                total_count: count,
+                                next_cursor,
+                            },
+                        ),
+                    )
+                        .into_response(),
+                }
+            }
+            Err(err) => {
+                error!("Failed to process product query: {}", err);
+                (
+                    StatusCode::BAD_REQUEST,
+                    Encoded(
+                        format,
+                        ProductQueryResponse {
+                            message: err.to_string(),
+                            products: Vec::new(),
+                            scores: None,
+                            total_count: 0,
+                            next_cursor: None,
+                        },
+                    ),
+                )
+                    .into_response()
+            }
         }
     }
 
-    /// Sets up the routes for the service and returns the app.
-    ///
-    /// # Arguments
-    /// - `db` - The data backend instance to use.
-    /// - `endpoint_options` - The options for the endpoint.
-    fn setup_routes(db: Arc<DB>, endpoint_options: &EndpointOptions) -> Result<Router> {
-        // parse the CORS-origin configuration
-        let allow_origins = endpoint_options
-            .allow_origin
-            .parse::<HeaderValue>()
-            .map_err(|e| {
-                error!("Failed to parse the allow-origin value: {}", e);
-
-                Error::ConfigError(format!("Failed to parse the allow-origin value: {}", e))
-            })?;
-
-        let cors = CorsLayer::new()
-            .allow_methods(vec![Method::GET, Method::POST, Method::DELETE])
-            .allow_origin(allow_origins);
+    /// POST: Handles exporting all products matching a query, e.g. for a spreadsheet. The
+    /// offset and the limit of the query are ignored and its fields select the columns.
+    async fn handle_product_export(
+        State(state): State<Arc<DB>>,
+        Query(export): Query<ExportQuery>,
+        Negotiated(mut query): Negotiated<ProductQuery>,
+    ) -> Response {
+        query.filter.resolve_product_id();
+        debug!("Export products: {:?}", query);
 
-        let admin_app = Self::setup_admin_endpoint();
-        let user_app = Self::setup_user_endpoint();
+        let fields = export_fields(query.fields.as_deref());
+        let query = export_query(query);
 
-        let api_routes = Router::new()
-            .nest("/v1/admin", admin_app)
-            .nest("/v1/user", user_app);
-        let app = if let Some(prefix) = &endpoint_options.prefix {
-            Router::new().nest(prefix, api_routes)
-        } else {
-            api_routes
+        let num_rows = match state.count_products(&query).await {
+            Ok(num_rows) => num_rows,
+            Err(err) => {
+                error!("Failed to export products: {}", err);
+                return problem_response(StatusCode::BAD_REQUEST, err.to_string());
+            }
         };
+        info!("Exporting {} products", num_rows);
 
-        let app = app.layer(cors).with_state(db);
+        let header = fields.iter().map(|field| field.name()).collect();
+        let rows = product_stream(state, query)
+            .map_ok(move |product| product_row(&product, &fields))
+            .boxed();
 
-        Ok(app)
+        export_response(export.format, "products", header, num_rows, rows).await
     }
 
-    /// Sets up the admin endpoint.
-    fn setup_admin_endpoint() -> Router<Arc<DB>> {
-        let app = Router::new();
+    /// POST: Handles counting the products matching a query.
+    async fn handle_product_count(
+        State(state): State<Arc<DB>>,
+        format: BodyFormat,
+        Negotiated(mut query): Negotiated<ProductQuery>,
+    ) -> (StatusCode, Encoded<CountResponse>) {
+        query.filter.resolve_product_id();
+        debug!("Count products: {:?}", query);
 
-        app.route(
-            "/product_request/{request_id}",
-            delete(Self::handle_delete_product_request),
-        )
-        .route(
-            "/product_request/{request_id}",
-            get(Self::handle_get_product_request),
-        )
-        .route(
-            "/product_request/query",
-            post(Self::handle_product_request_query),
-        )
-        .route(
-            "/product_request/{id}/image",
-            get(Self::handle_get_product_request_image),
-        )
-        .route(
-            "/missing_products/query",
-            post(Self::handle_missing_products_query),
-        )
-        .route(
-            "/missing_products/{id}",
-            get(Self::handle_get_missing_product),
-        )
-        .route(
-            "/missing_products/{id}",
-            delete(Self::handle_delete_missing_product),
-        )
-        .route("/product", post(Self::handle_new_product))
-        .route("/product/{id}", delete(Self::handle_delete_product))
+        match state.count_products(&query).await {
+            Ok(count) => {
+                info!("Product count successful: {:?}", query);
+                (
+                    StatusCode::OK,
+                    Encoded(
+                        format,
+                        CountResponse {
+                            message: "Query executed successful".to_string(),
+                            count,
+                        },
+                    ),
+                )
+            }
+            Err(err) => {
+                error!("Failed to count products: {}", err);
+                (
+                    StatusCode::BAD_REQUEST,
+                    Encoded(
+                        format,
+                        CountResponse {
+                            message: err.to_string(),
+                            count: 0,
+                        },
+                    ),
+                )
+            }
+        }
     }
 
-    /// Sets up the user endpoint.
-    fn setup_user_endpoint() -> Router<Arc<DB>> {
-        let app = Router::new();
+    /// POST: Handles summing up the nutrients of a list of portions.
+    async fn handle_aggregate_nutrients(
+        State(state): State<Arc<DB>>,
+        Json(mut payload): Json<NutrientAggregationRequest>,
+    ) -> (StatusCode, Json<NutrientAggregationResponse>) {
+        debug!("Aggregate nutrients: {:?}", payload);
 
-        app.route("/product_request", post(Self::handle_product_request))
-            .route(
-                "/missing_products",
-                post(Self::handle_report_missing_product),
-            )
-            .route("/product/{id}", get(Self::handle_get_product))
-            .route("/product/query", post(Self::handle_product_query))
-            .route("/product/{id}/image", get(Self::handle_get_product_image))
+        for portion in payload.portions.iter_mut() {
+            portion.product_id = resolve_product_id(&portion.product_id);
+        }
+
+        match aggregate_nutrients(state.as_ref(), &payload.portions).await {
+            Ok(aggregation) => (
+                StatusCode::OK,
+                Json(NutrientAggregationResponse {
+                    message: "Nutrients aggregated successfully".to_string(),
+                    aggregation: Some(aggregation),
+                }),
+            ),
+            Err(err) => {
+                error!("Failed to aggregate nutrients: {}", err);
+                (
+                    StatusCode::BAD_REQUEST,
+                    Json(NutrientAggregationResponse {
+                        message: err.to_string(),
+                        aggregation: None,
+                    }),
+                )
+            }
+        }
     }
 
-    /// POST: Handles a requesting a new product.
-    async fn handle_product_request(
+    /// POST: Handles adding a new recipe.
+    async fn handle_new_recipe(
         State(state): State<Arc<DB>>,
-        Json(payload): Json<ProductDescription>,
-    ) -> (StatusCode, Json<ProductRequestResponse>) {
-        debug!("Received product request: {:?}", payload);
+        Json(recipe): Json<Recipe>,
+    ) -> (StatusCode, Json<NewRecipeResponse>) {
+        debug!("New recipe: {}", recipe.name);
 
-        let product_request = ProductRequest {
-            product_description: payload,
-            date: chrono::Utc::now(),
+        let result = match check_recipe(&recipe) {
+            Ok(()) => state.new_recipe(&recipe).await,
+            Err(err) => Err(err),
         };
 
-        match state.request_new_product(&product_request).await {
+        match result {
             Ok(id) => {
-                info!("Product request received successfully");
+                info!("Recipe added successfully with id={}", id);
                 (
                     StatusCode::CREATED,
-                    Json(ProductRequestResponse {
-                        message: "Product request received successfully".to_string(),
-                        date: Some(product_request.date),
+                    Json(NewRecipeResponse {
+                        message: "Recipe added successfully".to_string(),
                         id: Some(id),
                     }),
                 )
             }
             Err(err) => {
-                error!("Failed to receive product request: {}", err);
+                error!("Failed to add recipe: {}", err);
                 (
                     StatusCode::BAD_REQUEST,
-                    Json(ProductRequestResponse {
+                    Json(NewRecipeResponse {
                         message: err.to_string(),
-                        date: None,
                         id: None,
                     }),
                 )
@@ -226,64 +3933,69 @@ impl<DB: DataBackend + 'static> Service<DB> {
         }
     }
 
-    /// POST: Handles reporting a missing product.
-    async fn handle_report_missing_product(
+    /// PUT: Handles replacing the specified recipe.
+    async fn handle_update_recipe(
         State(state): State<Arc<DB>>,
-        Json(payload): Json<MissingProductReportRequest>,
-    ) -> (StatusCode, Json<MissingProductReportResponse>) {
-        debug!("Received missing product report: {:?}", payload);
+        Path(id): Path<DBId>,
+        Json(recipe): Json<Recipe>,
+    ) -> (StatusCode, Json<OnlyMessageResponse>) {
+        debug!("Update recipe with id={}", id);
 
-        let date = chrono::Utc::now();
-        let missing_product = MissingProduct {
-            product_id: payload.product_id,
-            date,
+        let result = match check_recipe(&recipe) {
+            Ok(()) => state.update_recipe(id, &recipe).await,
+            Err(err) => Err(err),
         };
 
-        match state.report_missing_product(missing_product).await {
-            Ok(id) => {
-                info!("Received missing product report successfully");
+        match result {
+            Ok(true) => {
+                info!("Recipe with id={} updated successfully", id);
                 (
-                    StatusCode::CREATED,
-                    Json(MissingProductReportResponse {
-                        message: "Received missing product report successfully".to_string(),
-                        date: Some(date),
-                        id: Some(id),
+                    StatusCode::OK,
+                    Json(OnlyMessageResponse {
+                        message: "Recipe updated successfully".to_string(),
+                    }),
+                )
+            }
+            Ok(false) => {
+                info!("Recipe with id={} not found", id);
+                (
+                    StatusCode::NOT_FOUND,
+                    Json(OnlyMessageResponse {
+                        message: format!("Recipe with id={} not found", id),
                     }),
                 )
             }
             Err(err) => {
-                error!("Received missing product report failed: {}", err);
+                error!("Failed to update recipe: {}", err);
                 (
                     StatusCode::BAD_REQUEST,
-                    Json(MissingProductReportResponse {
+                    Json(OnlyMessageResponse {
                         message: err.to_string(),
-                        date: Some(date),
-                        id: None,
                     }),
                 )
             }
         }
     }
 
-    /// DELETE: Handles deleting a requested product.
-    async fn handle_delete_product_request(
+    /// DELETE: Handles deleting the specified recipe.
+    async fn handle_delete_recipe(
         State(state): State<Arc<DB>>,
-        Path(request_id): Path<DBId>,
+        Path(id): Path<DBId>,
     ) -> (StatusCode, Json<OnlyMessageResponse>) {
-        debug!("Deleting product request with id={}", request_id);
+        debug!("Delete recipe with id={}", id);
 
-        match state.delete_requested_product(request_id).await {
-            Ok(()) => {
-                info!("Deleting product request with id={} successful", request_id);
+        match state.delete_recipe(id).await {
+            Ok(_) => {
+                info!("Recipe deleted successfully");
                 (
                     StatusCode::OK,
                     Json(OnlyMessageResponse {
-                        message: "Product request deleted.".to_string(),
+                        message: "Recipe deleted successfully".to_string(),
                     }),
                 )
             }
             Err(err) => {
-                error!("Failed to receive product request: {}", err);
+                error!("Failed to delete recipe: {}", err);
                 (
                     StatusCode::BAD_REQUEST,
                     Json(OnlyMessageResponse {
@@ -294,203 +4006,247 @@ impl<DB: DataBackend + 'static> Service<DB> {
         }
     }
 
-    /// GET: Handles getting a requested product.
-    async fn handle_get_product_request(
+    /// GET: Handles getting the specified recipe together with the nutrients derived from the
+    /// current nutrients of its ingredients.
+    async fn handle_get_recipe(
         State(state): State<Arc<DB>>,
-        Path(request_id): Path<DBId>,
-        query: Query<GetProductRequestQuery>,
-    ) -> (StatusCode, Json<GetProductRequestResponse>) {
-        debug!("Get product request with id={}", request_id);
-
-        match state
-            .get_product_request(request_id, query.with_preview)
-            .await
-        {
-            Ok(Some(mut product_request)) => {
-                if query.with_full_image {
-                    match state.get_product_request_image(request_id).await {
-                        Ok(Some(image)) => {
-                            product_request.product_description.full_image = Some(image);
-                        }
-                        Ok(None) => {
-                            warn!("Product request with id={} has no full image", request_id);
-                        }
-                        Err(err) => {
-                            error!("Failed to receive product request image: {}", err);
-                            return (
-                                StatusCode::BAD_REQUEST,
-                                Json(GetProductRequestResponse {
-                                    message: err.to_string(),
-                                    product_request: None,
-                                }),
-                            );
-                        }
-                    }
-                }
+        Path(id): Path<DBId>,
+    ) -> (StatusCode, Json<GetRecipeResponse>) {
+        debug!("Get recipe with id={}", id);
 
-                info!("Get product request with id={} successful", request_id);
-                (
-                    StatusCode::OK,
-                    Json(GetProductRequestResponse {
-                        message: "Product request found.".to_string(),
-                        product_request: Some(product_request),
+        let recipe = match state.get_recipe(id).await {
+            Ok(Some(recipe)) => recipe,
+            Ok(None) => {
+                info!("Recipe with id={} not found", id);
+                return (
+                    StatusCode::NOT_FOUND,
+                    Json(GetRecipeResponse {
+                        message: format!("Recipe with id={} not found", id),
+                        recipe: None,
+                        nutrients: None,
                     }),
-                )
+                );
             }
-            Ok(None) => {
-                info!("Product request with id={} not found", request_id);
+            Err(err) => {
+                error!("Failed to receive recipe: {}", err);
+                return (
+                    StatusCode::BAD_REQUEST,
+                    Json(GetRecipeResponse {
+                        message: err.to_string(),
+                        recipe: None,
+                        nutrients: None,
+                    }),
+                );
+            }
+        };
+
+        match resolve_recipe_nutrients(state.as_ref(), &recipe).await {
+            Ok(nutrients) => {
+                info!("Get recipe with id={} successful", id);
                 (
-                    StatusCode::NOT_FOUND,
-                    Json(GetProductRequestResponse {
-                        message: format!("Product with id={} not found", request_id),
-                        product_request: None,
+                    StatusCode::OK,
+                    Json(GetRecipeResponse {
+                        message: "Recipe found.".to_string(),
+                        recipe: Some(recipe),
+                        nutrients: Some(nutrients),
                     }),
                 )
             }
             Err(err) => {
-                error!("Failed to receive product request: {}", err);
+                error!("Failed to resolve the nutrients of recipe {}: {}", id, err);
                 (
                     StatusCode::BAD_REQUEST,
-                    Json(GetProductRequestResponse {
+                    Json(GetRecipeResponse {
                         message: err.to_string(),
-                        product_request: None,
+                        recipe: None,
+                        nutrients: None,
                     }),
                 )
             }
         }
     }
 
-    /// POST: Handles executing a product request query.
-    async fn handle_product_request_query(
+    /// POST: Handles the submission of a price observed in a store.
+    async fn handle_submit_price(
         State(state): State<Arc<DB>>,
-        Json(query): Json<ProductQuery>,
-    ) -> (StatusCode, Json<ProductRequestQueryResponse>) {
-        debug!("Get product request query [Decoded]: {:?}", query);
+        Json(mut price): Json<PriceObservation>,
+    ) -> (StatusCode, Json<PriceSubmissionResponse>) {
+        price.product_id = resolve_product_id(&price.product_id);
+        debug!("Submit price of product with id={}", price.product_id);
 
-        match state.query_product_requests(&query, true).await {
-            Ok(result) => {
-                info!("Product request query successful: {:?}", query);
+        let result = match check_price(&price) {
+            Ok(()) => state.submit_price(&price).await,
+            Err(err) => Err(err),
+        };
+
+        match result {
+            Ok(id) => {
+                info!("Price submitted successfully with id={}", id);
                 (
-                    StatusCode::OK,
-                    Json(ProductRequestQueryResponse {
-                        message: "Query executed successful".to_string(),
-                        product_requests: result,
+                    StatusCode::CREATED,
+                    Json(PriceSubmissionResponse {
+                        message: "Price submitted successfully".to_string(),
+                        id: Some(id),
                     }),
                 )
             }
             Err(err) => {
-                error!("Failed to receive product request: {}", err);
+                error!("Failed to submit price: {}", err);
                 (
-                    StatusCode::BAD_REQUEST,
-                    Json(ProductRequestQueryResponse {
+                    price_error_status(&err),
+                    Json(PriceSubmissionResponse {
                         message: err.to_string(),
-                        product_requests: Vec::new(),
+                        id: None,
                     }),
                 )
             }
         }
     }
 
-    /// POST: Handles executing a product request query.
-    async fn handle_missing_products_query(
+    /// POST: Handles querying the observed prices.
+    async fn handle_price_query(
         State(state): State<Arc<DB>>,
-        Json(query): Json<MissingProductQuery>,
-    ) -> (StatusCode, Json<MissingProductsQueryResponse>) {
-        debug!("Get missing product query: {:?}", query);
+        OriginalUri(uri): OriginalUri,
+        Query(page): Query<PageParams>,
+        Json(mut query): Json<PriceQuery>,
+    ) -> (StatusCode, PageLinks, Json<PriceQueryResponse>) {
+        query.product_id = query.product_id.as_deref().map(resolve_product_id);
+        page.apply(&mut query.offset, &mut query.limit);
+        debug!("Query prices: {:?}", query);
 
-        match state.query_missing_products(&query).await {
-            Ok(result) => {
-                info!("Missing products query successful: {:?}", query);
+        match state.query_prices(&query).await {
+            Ok(prices) => {
+                info!("Query prices successful, {} results", prices.len());
                 (
                     StatusCode::OK,
-                    Json(MissingProductsQueryResponse {
-                        message: "Query executed successful".to_string(),
-                        missing_products: result,
+                    PageLinks::new(&uri, query.offset, query.limit, prices.len(), None),
+                    Json(PriceQueryResponse {
+                        message: "Prices found.".to_string(),
+                        prices,
                     }),
                 )
             }
             Err(err) => {
-                error!("Failed to receive product request: {}", err);
+                error!("Failed to query prices: {}", err);
                 (
-                    StatusCode::BAD_REQUEST,
-                    Json(MissingProductsQueryResponse {
+                    price_error_status(&err),
+                    PageLinks::default(),
+                    Json(PriceQueryResponse {
                         message: err.to_string(),
-                        missing_products: Vec::new(),
+                        prices: Vec::new(),
                     }),
                 )
             }
         }
     }
 
-    /// GET: Handles getting reported missing product.
-    async fn handle_get_missing_product(
+    /// GET: Handles getting the cheapest price seen for the specified product.
+    async fn handle_get_cheapest_price(
         State(state): State<Arc<DB>>,
-        Path(request_id): Path<DBId>,
-    ) -> (StatusCode, Json<GetReportedMissingProductResponse>) {
-        debug!("Get reported missing product with id={}", request_id);
+        Path(product_id): Path<ProductID>,
+        Query(query): Query<CheapestPriceQuery>,
+    ) -> (StatusCode, Json<CheapestPriceResponse>) {
+        let product_id = resolve_product_id(&product_id);
+        debug!(
+            "Get cheapest price of product with id={} in {}",
+            product_id, query.currency
+        );
 
-        match state.get_missing_product(request_id).await {
-            Ok(Some(missing_product)) => {
+        match state.cheapest_price(&product_id, &query.currency).await {
+            Ok(Some(price)) => {
                 info!(
-                    "Get reported missing product with id={} successful",
-                    request_id
+                    "Get cheapest price of product with id={} successful",
+                    product_id
                 );
                 (
                     StatusCode::OK,
-                    Json(GetReportedMissingProductResponse {
-                        message: "Reported missing product found.".to_string(),
-                        missing_product: Some(missing_product),
+                    Json(CheapestPriceResponse {
+                        message: "Price found.".to_string(),
+                        price: Some(price),
                     }),
                 )
             }
             Ok(None) => {
-                info!("Reported missing product with id={} not found", request_id);
+                info!("No price of product with id={} found", product_id);
                 (
                     StatusCode::NOT_FOUND,
-                    Json(GetReportedMissingProductResponse {
+                    Json(CheapestPriceResponse {
                         message: format!(
-                            "Reported missing product with id={} not found",
-                            request_id
+                            "No price in {} of product with id={} found",
+                            query.currency, product_id
                         ),
-                        missing_product: None,
+                        price: None,
                     }),
                 )
             }
             Err(err) => {
-                error!("Failed to receive reported missing product: {}", err);
+                error!("Failed to get the cheapest price: {}", err);
+                (
+                    price_error_status(&err),
+                    Json(CheapestPriceResponse {
+                        message: err.to_string(),
+                        price: None,
+                    }),
+                )
+            }
+        }
+    }
+
+    /// POST: Handles adding a new store.
+    async fn handle_new_store(
+        State(state): State<Arc<DB>>,
+        Json(store): Json<Store>,
+    ) -> (StatusCode, Json<NewStoreResponse>) {
+        debug!("New store: {}", store.name);
+
+        let result = match check_store(&store) {
+            Ok(()) => state.new_store(&store).await,
+            Err(err) => Err(err),
+        };
+
+        match result {
+            Ok(id) => {
+                info!("Store added successfully with id={}", id);
+                (
+                    StatusCode::CREATED,
+                    Json(NewStoreResponse {
+                        message: "Store added successfully".to_string(),
+                        id: Some(id),
+                    }),
+                )
+            }
+            Err(err) => {
+                error!("Failed to add store: {}", err);
                 (
                     StatusCode::BAD_REQUEST,
-                    Json(GetReportedMissingProductResponse {
+                    Json(NewStoreResponse {
                         message: err.to_string(),
-                        missing_product: None,
+                        id: None,
                     }),
                 )
             }
         }
     }
 
-    /// DELETE: Handles deleting a reported missing product.
-    async fn handle_delete_missing_product(
+    /// DELETE: Handles deleting the specified store.
+    async fn handle_delete_store(
         State(state): State<Arc<DB>>,
-        Path(report_id): Path<DBId>,
+        Path(id): Path<DBId>,
     ) -> (StatusCode, Json<OnlyMessageResponse>) {
-        debug!("Deleting reported missing product with id={}", report_id);
+        debug!("Delete store with id={}", id);
 
-        match state.delete_reported_missing_product(report_id).await {
-            Ok(()) => {
-                info!(
-                    "Deleting reported missing product with id={} successful",
-                    report_id
-                );
+        match state.delete_store(id).await {
+            Ok(_) => {
+                info!("Store deleted successfully");
                 (
                     StatusCode::OK,
                     Json(OnlyMessageResponse {
-                        message: "Product request deleted.".to_string(),
+                        message: "Store deleted successfully".to_string(),
                     }),
                 )
             }
             Err(err) => {
-                error!("Failed to receive product request: {}", err);
+                error!("Failed to delete store: {}", err);
                 (
                     StatusCode::BAD_REQUEST,
                     Json(OnlyMessageResponse {
@@ -501,186 +4257,214 @@ impl<DB: DataBackend + 'static> Service<DB> {
         }
     }
 
-    /// POST: Handles adding a new product.
-    async fn handle_new_product(
+    /// GET: Handles getting the specified store.
+    async fn handle_get_store(
         State(state): State<Arc<DB>>,
-        Json(payload): Json<ProductDescription>,
-    ) -> (StatusCode, Json<OnlyMessageResponse>) {
-        debug!("Created new product: {:?}", payload);
+        Path(id): Path<DBId>,
+    ) -> (StatusCode, Json<GetStoreResponse>) {
+        debug!("Get store with id={}", id);
 
-        match state.new_product(&payload).await {
-            Ok(ret) => {
-                if ret {
-                    info!("New product created successfully");
-                    (
-                        StatusCode::CREATED,
-                        Json(OnlyMessageResponse {
-                            message: "Product successfully created".to_string(),
-                        }),
-                    )
-                } else {
-                    error!("Product already exists: {}", payload.info);
-                    (
-                        StatusCode::CONFLICT,
-                        Json(OnlyMessageResponse {
-                            message: format!("Product with id={} already exists", payload.info.id),
-                        }),
-                    )
-                }
+        match state.get_store(id).await {
+            Ok(Some(store)) => {
+                info!("Get store with id={} successful", id);
+                (
+                    StatusCode::OK,
+                    Json(GetStoreResponse {
+                        message: "Store found.".to_string(),
+                        store: Some(store),
+                    }),
+                )
+            }
+            Ok(None) => {
+                info!("Store with id={} not found", id);
+                (
+                    StatusCode::NOT_FOUND,
+                    Json(GetStoreResponse {
+                        message: format!("Store with id={} not found", id),
+                        store: None,
+                    }),
+                )
             }
             Err(err) => {
-                error!("Failed to add new product: {}", err);
+                error!("Failed to receive store: {}", err);
                 (
                     StatusCode::BAD_REQUEST,
-                    Json(OnlyMessageResponse {
+                    Json(GetStoreResponse {
                         message: err.to_string(),
+                        store: None,
                     }),
                 )
             }
         }
     }
 
-    /// POST: Handles deleting a product.
-    async fn handle_delete_product(
+    /// POST: Handles querying the stores.
+    async fn handle_store_query(
         State(state): State<Arc<DB>>,
-        Path(product_id): Path<ProductID>,
-    ) -> (StatusCode, Json<OnlyMessageResponse>) {
-        debug!("Delete product: {:?}", product_id);
+        OriginalUri(uri): OriginalUri,
+        Query(page): Query<PageParams>,
+        Json(mut query): Json<StoreQuery>,
+    ) -> (StatusCode, PageLinks, Json<StoreQueryResponse>) {
+        page.apply(&mut query.offset, &mut query.limit);
+        debug!("Query stores: {:?}", query);
 
-        match state.delete_product(&product_id).await {
-            Ok(_) => {
-                info!("Product deleted successfully");
+        match state.query_stores(&query).await {
+            Ok(stores) => {
+                info!("Query stores successful, {} results", stores.len());
                 (
                     StatusCode::OK,
-                    Json(OnlyMessageResponse {
-                        message: "Product deleted successfully".to_string(),
+                    PageLinks::new(&uri, query.offset, query.limit, stores.len(), None),
+                    Json(StoreQueryResponse {
+                        message: "Stores found.".to_string(),
+                        stores,
                     }),
                 )
             }
             Err(err) => {
-                error!("Failed to delete product: {}", err);
+                error!("Failed to query stores: {}", err);
                 (
                     StatusCode::BAD_REQUEST,
-                    Json(OnlyMessageResponse {
+                    PageLinks::default(),
+                    Json(StoreQueryResponse {
                         message: err.to_string(),
+                        stores: Vec::new(),
                     }),
                 )
             }
         }
     }
 
-    /// GET: Handles getting the specified product.
-    async fn handle_get_product(
+    /// POST: Handles reporting a product as seen or as missing at the specified store.
+    async fn handle_report_availability(
         State(state): State<Arc<DB>>,
-        Path(product_id): Path<ProductID>,
-        query: Query<GetProductRequestQuery>,
-    ) -> (StatusCode, Json<GetProductResponse>) {
-        debug!("Get product with id={}", product_id);
+        Path(store_id): Path<DBId>,
+        Json(payload): Json<AvailabilityReportRequest>,
+    ) -> (StatusCode, Json<OnlyMessageResponse>) {
+        debug!(
+            "Report availability of product with id={} at store with id={}",
+            payload.product_id, store_id
+        );
 
-        match state.get_product(&product_id, query.with_preview).await {
-            Ok(Some(mut product_description)) => {
-                if query.with_full_image {
-                    match state.get_product_image(&product_id).await {
-                        Ok(Some(image)) => {
-                            product_description.full_image = Some(image);
-                        }
-                        Ok(None) => {
-                            warn!("Product with id={} has no full image", product_id);
-                        }
-                        Err(err) => {
-                            error!("Failed to receive product image: {}", err);
-                            return (
-                                StatusCode::BAD_REQUEST,
-                                Json(GetProductResponse {
-                                    message: err.to_string(),
-                                    product: None,
-                                }),
-                            );
-                        }
-                    }
-                }
+        let availability = ProductAvailability {
+            store_id,
+            product_id: resolve_product_id(&payload.product_id),
+            available: payload.available,
+            date: chrono::Utc::now(),
+        };
 
-                info!("Get product with id={} successful", product_id);
+        match state.report_availability(&availability).await {
+            Ok(true) => {
+                info!("Availability reported successfully");
                 (
                     StatusCode::OK,
-                    Json(GetProductResponse {
-                        message: "Product found.".to_string(),
-                        product: Some(product_description),
+                    Json(OnlyMessageResponse {
+                        message: "Availability reported successfully".to_string(),
                     }),
                 )
             }
-            Ok(None) => {
-                info!("Product with id={} not found", product_id);
+            Ok(false) => {
+                info!("Store with id={} not found", store_id);
                 (
                     StatusCode::NOT_FOUND,
-                    Json(GetProductResponse {
-                        message: format!("Product with id={} not found", product_id),
-                        product: None,
+                    Json(OnlyMessageResponse {
+                        message: format!("Store with id={} not found", store_id),
                     }),
                 )
             }
             Err(err) => {
-                error!("Failed to receive product: {}", err);
+                error!("Failed to report availability: {}", err);
                 (
                     StatusCode::BAD_REQUEST,
-                    Json(GetProductResponse {
+                    Json(OnlyMessageResponse {
                         message: err.to_string(),
-                        product: None,
                     }),
                 )
             }
         }
     }
 
-    /// POST: Handles executing a product query.
-    async fn handle_product_query(
+    /// GET: Handles getting the last observed availability of the product in the stores.
+    async fn handle_get_product_availability(
         State(state): State<Arc<DB>>,
-        Json(query): Json<ProductQuery>,
-    ) -> (StatusCode, Json<ProductQueryResponse>) {
-        debug!("Get product query [Decoded]: {:?}", query);
+        Path(product_id): Path<ProductID>,
+    ) -> (StatusCode, Json<ProductAvailabilityResponse>) {
+        let product_id = resolve_product_id(&product_id);
+        debug!("Get availability of product with id={}", product_id);
 
-        match state.query_products(&query, true).await {
-            Ok(result) => {
-                info!("Product query successful: {:?}", query);
+        match state.get_product_availability(&product_id).await {
+            Ok(availability) => {
+                info!(
+                    "Get availability of product with id={} successful, {} stores",
+                    product_id,
+                    availability.len()
+                );
                 (
                     StatusCode::OK,
-                    Json(ProductQueryResponse {
-                        message: "Query executed successful".to_string(),
-                        products: result,
+                    Json(ProductAvailabilityResponse {
+                        message: "Availability found.".to_string(),
+                        availability,
                     }),
                 )
             }
             Err(err) => {
-                error!("Failed to process product query: {}", err);
+                error!("Failed to get the availability: {}", err);
                 (
                     StatusCode::BAD_REQUEST,
-                    Json(ProductQueryResponse {
+                    Json(ProductAvailabilityResponse {
                         message: err.to_string(),
-                        products: Vec::new(),
+                        availability: Vec::new(),
                     }),
                 )
             }
         }
     }
 
+    /// GET: Handles getting the additional images of a product.
+    async fn handle_get_product_images(
+        State(state): State<Arc<DB>>,
+        Path(product_id): Path<ProductID>,
+    ) -> (StatusCode, Json<AdditionalImagesResponse>) {
+        let product_id = resolve_product_id(&product_id);
+        debug!("Get additional images of product with id={}", product_id);
+
+        Self::additional_images_response(
+            state.get_product_images(&product_id).await,
+            &format!("Product with id={}", product_id),
+        )
+    }
+
     /// GET: Handles getting the product image.
     async fn handle_get_product_image(
         State(state): State<Arc<DB>>,
         Path(product_id): Path<ProductID>,
+        headers: HeaderMap,
     ) -> impl IntoResponse {
+        let product_id = resolve_product_id(&product_id);
         debug!("Get product image with id={}", product_id);
 
         match state.get_product_image(&product_id).await {
             Ok(Some(image)) => {
                 info!("Get product image with id={} successful", product_id);
 
-                let mut headers = HeaderMap::new();
-                headers.insert(
-                    header::CONTENT_TYPE,
-                    HeaderValue::from_str(&image.content_type).unwrap(),
-                );
+                let content_type = match HeaderValue::from_str(&image.content_type) {
+                    Ok(content_type) => content_type,
+                    Err(err) => {
+                        error!(
+                            "Invalid content type '{}' of image of product with id={}: {}",
+                            image.content_type, product_id, err
+                        );
+                        let response = Json(OnlyMessageResponse {
+                            message: format!(
+                                "Image of product with id={} has an invalid content type",
+                                product_id
+                            ),
+                        });
 
-                Ok((headers, image.data))
+                        return Err((StatusCode::INTERNAL_SERVER_ERROR, response));
+                    }
+                };
+
+                Ok(ranged_response(&headers, content_type, image.data))
             }
             Ok(None) => {
                 info!("Product with id={} has no image", product_id);
@@ -701,10 +4485,119 @@ impl<DB: DataBackend + 'static> Service<DB> {
         }
     }
 
+    /// GET: Handles creating a signed url for the public image endpoint.
+    async fn handle_get_signed_image_url(
+        State((image_url_signer, public_image_base)): State<(Arc<ImageUrlSigner>, String)>,
+        Path(product_id): Path<ProductID>,
+    ) -> (StatusCode, Json<SignedImageUrlResponse>) {
+        let product_id = resolve_product_id(&product_id);
+        debug!("Create signed image url for product with id={}", product_id);
+
+        let signed = image_url_signer.sign(&product_id, chrono::Utc::now());
+        info!(
+            "Created signed image url for product with id={} valid until {}",
+            product_id, signed.expires
+        );
+
+        (
+            StatusCode::OK,
+            Json(SignedImageUrlResponse {
+                message: "Signed image url created.".to_string(),
+                url: Some(format!("{}/{}", public_image_base, signed.token)),
+                expires: Some(signed.expires),
+            }),
+        )
+    }
+
+    /// GET: Handles getting the product image referenced by a signed url.
+    async fn handle_get_public_image(
+        State((state, image_url_signer)): State<(Arc<DB>, Arc<ImageUrlSigner>)>,
+        Path(token): Path<String>,
+        headers: HeaderMap,
+    ) -> Response {
+        let product_id = match image_url_signer.verify(&token, chrono::Utc::now()) {
+            Some(product_id) => product_id,
+            None => {
+                info!("Rejected invalid or expired image url");
+                return (
+                    StatusCode::FORBIDDEN,
+                    Json(OnlyMessageResponse {
+                        message: "The image url is invalid or has expired".to_string(),
+                    }),
+                )
+                    .into_response();
+            }
+        };
+
+        Self::handle_get_product_image(State(state), Path(product_id), headers)
+            .await
+            .into_response()
+    }
+
+    /// GET: Handles getting the additional images of a requested product.
+    async fn handle_get_product_request_images(
+        State(state): State<Arc<DB>>,
+        Path(request_id): Path<DBId>,
+    ) -> (StatusCode, Json<AdditionalImagesResponse>) {
+        debug!(
+            "Get additional images of product request with id={}",
+            request_id
+        );
+
+        Self::additional_images_response(
+            state.get_product_request_images(request_id).await,
+            &format!("Product request with id={}", request_id),
+        )
+    }
+
+    /// Returns the response with the loaded additional images of a product or product request.
+    ///
+    /// # Arguments
+    /// - `images` - The loaded images or none if the product or request does not exist.
+    /// - `entity` - The description of the product or request for the messages.
+    fn additional_images_response(
+        images: Result<Option<Vec<AdditionalImage>>>,
+        entity: &str,
+    ) -> (StatusCode, Json<AdditionalImagesResponse>) {
+        match images {
+            Ok(Some(images)) => {
+                info!("Get additional images of {} successful", entity);
+                (
+                    StatusCode::OK,
+                    Json(AdditionalImagesResponse {
+                        message: format!("{} has {} additional images", entity, images.len()),
+                        images,
+                    }),
+                )
+            }
+            Ok(None) => {
+                info!("{} not found", entity);
+                (
+                    StatusCode::NOT_FOUND,
+                    Json(AdditionalImagesResponse {
+                        message: format!("{} not found", entity),
+                        images: Vec::new(),
+                    }),
+                )
+            }
+            Err(err) => {
+                error!("Failed to receive additional images: {}", err);
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(AdditionalImagesResponse {
+                        message: err.to_string(),
+                        images: Vec::new(),
+                    }),
+                )
+            }
+        }
+    }
+
     /// GET: Handles getting the product request image.
     async fn handle_get_product_request_image(
         State(state): State<Arc<DB>>,
         Path(request_id): Path<DBId>,
+        headers: HeaderMap,
     ) -> impl IntoResponse {
         debug!("Get product request image with id={}", request_id);
 
@@ -715,13 +4608,25 @@ impl<DB: DataBackend + 'static> Service<DB> {
                     request_id
                 );
 
-                let mut headers = HeaderMap::new();
-                headers.insert(
-                    header::CONTENT_TYPE,
-                    HeaderValue::from_str(&image.content_type).unwrap(),
-                );
+                let content_type = match HeaderValue::from_str(&image.content_type) {
+                    Ok(content_type) => content_type,
+                    Err(err) => {
+                        error!(
+                            "Invalid content type '{}' of image of product request with id={}: {}",
+                            image.content_type, request_id, err
+                        );
+                        let response = Json(OnlyMessageResponse {
+                            message: format!(
+                                "Image of product request with id={} has an invalid content type",
+                                request_id
+                            ),
+                        });
+
+                        return Err((StatusCode::INTERNAL_SERVER_ERROR, response));
+                    }
+                };
 
-                Ok((headers, image.data))
+                Ok(ranged_response(&headers, content_type, image.data))
             }
             Ok(None) => {
                 info!("Product request with id={} has no image", request_id);
@@ -741,4 +4646,113 @@ impl<DB: DataBackend + 'static> Service<DB> {
             }
         }
     }
+
+    /// GET: Handles getting the photo of a reported missing product.
+    async fn handle_get_missing_product_photo(
+        State(state): State<Arc<DB>>,
+        Path(report_id): Path<DBId>,
+        headers: HeaderMap,
+    ) -> impl IntoResponse {
+        debug!(
+            "Get photo of reported missing product with id={}",
+            report_id
+        );
+
+        match state.get_missing_product_photo(report_id).await {
+            Ok(Some(photo)) => {
+                info!(
+                    "Get photo of reported missing product with id={} successful",
+                    report_id
+                );
+
+                let content_type = match HeaderValue::from_str(&photo.content_type) {
+                    Ok(content_type) => content_type,
+                    Err(err) => {
+                        error!(
+                            "Invalid content type '{}' of photo of missing product with id={}: {}",
+                            photo.content_type, report_id, err
+                        );
+                        let response = Json(OnlyMessageResponse {
+                            message: format!(
+                                "Photo of missing product with id={} has an invalid content type",
+                                report_id
+                            ),
+                        });
+
+                        return Err((StatusCode::INTERNAL_SERVER_ERROR, response));
+                    }
+                };
+
+                Ok(ranged_response(&headers, content_type, photo.data))
+            }
+            Ok(None) => {
+                info!("Missing product with id={} has no photo", report_id);
+                let response = Json(OnlyMessageResponse {
+                    message: format!("Missing product with id={} has no photo", report_id),
+                });
+
+                Err((StatusCode::NOT_FOUND, response))
+            }
+            Err(err) => {
+                error!("Failed to receive missing product photo: {}", err);
+                let response = Json(OnlyMessageResponse {
+                    message: err.to_string(),
+                });
+
+                Err((StatusCode::BAD_REQUEST, response))
+            }
+        }
+    }
+}
+
+/// Returns the status code for a failed price operation, i.e., 404 if the price tracking is
+/// disabled and 400 otherwise.
+///
+/// # Arguments
+/// - `err` - The error of the price operation.
+fn price_error_status(err: &Error) -> StatusCode {
+    match err {
+        Error::PriceTrackingDisabled => StatusCode::NOT_FOUND,
+        _ => StatusCode::BAD_REQUEST,
+    }
+}
+
+/// Checks that the given submitted image does not exceed the limit for a single image and, if
+/// enabled, that it is of a supported format matching its content type.
+///
+/// # Arguments
+/// - `image_quota` - The configured limits of the image storage.
+/// - `image` - The submitted image.
+fn check_image(image_quota: &ImageQuotaOptions, image: &ProductImage) -> Result<()> {
+    let size = image.data.len() as u64;
+    if let Some(limit) = image_quota.max_image_bytes {
+        if size > limit {
+            return Err(Error::ImageTooLarge { size, limit });
+        }
+    }
+
+    if image_quota.verify_content_types {
+        verify_image_content_type(image)?;
+    }
+
+    Ok(())
+}
+
+/// Returns the status code for a submission rejected by the image quota, i.e., 413 if the
+/// images of the product are too large, 415 if an image is of an unsupported format, 507 if the
+/// image storage is exhausted and 500 otherwise.
+///
+/// # Arguments
+/// - `err` - The error of the quota check.
+fn image_quota_status(err: &Error) -> StatusCode {
+    match err {
+        Error::ProductImagesTooLarge { .. } | Error::ImageTooLarge { .. } => {
+            StatusCode::PAYLOAD_TOO_LARGE
+        }
+        Error::UnsupportedImageFormat(_) | Error::ImageContentTypeMismatch(_) => {
+            StatusCode::UNSUPPORTED_MEDIA_TYPE
+        }
+        Error::ImageQuotaExceeded { .. } => StatusCode::INSUFFICIENT_STORAGE,
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    }
 }
@@ -0,0 +1,126 @@
+use std::convert::Infallible;
+
+use axum::{extract::Request, response::IntoResponse, routing::Route, Router};
+use tower::{Layer, Service as TowerService};
+
+use crate::{
+    service::RouterExtension, CredentialsSource, DataBackend, NovaClassifier, Options,
+    PreviewGenerator, Result, Service,
+};
+
+/// Builder for the [`Service`] that allows registering additional tower layers and routes,
+/// e.g., for authentication, logging or tenant extraction.
+pub struct ServiceBuilder<DB: DataBackend> {
+    options: Options,
+    db: Option<DB>,
+    extensions: Vec<RouterExtension>,
+    credentials_source: Option<CredentialsSource>,
+    nova_classifier: Option<NovaClassifier>,
+    preview_generator: Option<PreviewGenerator>,
+}
+
+impl<DB: DataBackend + 'static> ServiceBuilder<DB> {
+    /// Creates a new builder for the service.
+    ///
+    /// # Arguments
+    /// - `options` - The options for the service.
+    pub fn new(options: Options) -> Self {
+        Self {
+            options,
+            db: None,
+            extensions: Vec::new(),
+            credentials_source: None,
+            nova_classifier: None,
+            preview_generator: None,
+        }
+    }
+
+    /// Uses the given data backend instead of creating a new one from the options.
+    ///
+    /// # Arguments
+    /// - `db` - The data backend instance to use.
+    pub fn backend(mut self, db: DB) -> Self {
+        self.db = Some(db);
+        self
+    }
+
+    /// Registers an additional layer that wraps all routes of the service.
+    /// Layers are applied in the order of their registration, i.e., the last registered layer
+    /// is the outermost one.
+    ///
+    /// # Arguments
+    /// - `layer` - The tower layer to add.
+    pub fn layer<L>(mut self, layer: L) -> Self
+    where
+        L: Layer<Route> + Clone + Send + Sync + 'static,
+        L::Service: TowerService<Request> + Clone + Send + Sync + 'static,
+        <L::Service as TowerService<Request>>::Response: IntoResponse + 'static,
+        <L::Service as TowerService<Request>>::Error: Into<Infallible> + 'static,
+        <L::Service as TowerService<Request>>::Future: Send + 'static,
+    {
+        self.extensions
+            .push(Box::new(move |app: Router| app.layer(layer.clone())));
+        self
+    }
+
+    /// Registers additional routes that are merged into the router of the service.
+    /// The routes are not affected by the configured endpoint prefix.
+    ///
+    /// # Arguments
+    /// - `routes` - The router with the additional routes.
+    pub fn routes(mut self, routes: Router) -> Self {
+        self.extensions
+            .push(Box::new(move |app: Router| app.merge(routes.clone())));
+        self
+    }
+
+    /// Uses the given source for reloading the database password, see
+    /// [`Service::reload_credentials`]. Defaults to the configured password file, if any.
+    ///
+    /// # Arguments
+    /// - `credentials_source` - The source for the database password.
+    pub fn credentials_source(mut self, credentials_source: CredentialsSource) -> Self {
+        self.credentials_source = Some(credentials_source);
+        self
+    }
+
+    /// Uses the given classifier to derive the NOVA group of submitted products that have none.
+    ///
+    /// # Arguments
+    /// - `nova_classifier` - The classifier for the NOVA group.
+    pub fn nova_classifier(mut self, nova_classifier: NovaClassifier) -> Self {
+        self.nova_classifier = Some(nova_classifier);
+        self
+    }
+
+    /// Uses the given generator to derive the previews from the full images when the images
+    /// are regenerated via `POST /v1/admin/maintenance/regenerate_images`.
+    ///
+    /// # Arguments
+    /// - `preview_generator` - The generator for the preview images.
+    pub fn preview_generator(mut self, preview_generator: PreviewGenerator) -> Self {
+        self.preview_generator = Some(preview_generator);
+        self
+    }
+
+    /// Builds the service and creates the data backend if none has been provided.
+    pub async fn build(self) -> Result<Service<DB>> {
+        let db = match self.db {
+            Some(db) => db,
+            None => {
+                let db = DB::new(&self.options).await?;
+                Service::check_health(&db).await?;
+                db
+            }
+        };
+
+        Ok(Service::from_parts(
+            self.options,
+            db,
+            self.extensions,
+            self.credentials_source,
+            self.nova_classifier,
+            self.preview_generator,
+        ))
+    }
+}
@@ -1,14 +1,47 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
-use crate::{DBId, MissingProduct, ProductDescription, ProductID, ProductRequest};
+use crate::{
+    AdditionalImage, ArchivedProductRequest, DBId, ImageStorageUsage, MissingProduct,
+    MissingProductSummary, NutrientAggregation, NutrientIssue, Portion, PriceObservation,
+    ProductAvailability, ProductDescription, ProductID, ProductImage, ProductProjection,
+    ProductRequest, Recipe, RecipeNutrients, RequestOutcome, SelfTestReport, SortingOrder, Store,
+};
+
+/// The submission of a new product or of a product request. The nutrients may be stated for a
+/// serving instead of 100g or 100ml, e.g. as printed on the label, and are normalized on
+/// ingestion.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct ProductSubmission {
+    /// The description of the product.
+    #[serde(flatten)]
+    pub product_description: ProductDescription,
+
+    /// The amount in g or ml the nutrients are stated for, e.g. 30 for a serving of 30g.
+    /// Defaults to 100, i.e., the nutrients are stated per 100g or 100ml.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reference_amount: Option<f32>,
+
+    /// The additional images of the product, e.g. of the nutrition table or of the ingredient
+    /// list.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub images: Vec<AdditionalImage>,
+}
 
 /// The response to a request to add a new product to the database.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct ProductRequestResponse {
     pub message: String,
     pub date: Option<DateTime<Utc>>,
     pub id: Option<DBId>,
+
+    /// The violated plausibility rules of the nutrients, which flag the request for review.
+    #[serde(default)]
+    pub warnings: Vec<NutrientIssue>,
 }
 
 /// The response to a reported missing product.
@@ -16,18 +49,69 @@ pub type MissingProductReportResponse = ProductRequestResponse;
 
 /// The request to report a missing product.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct MissingProductReportRequest {
     pub product_id: ProductID,
+
+    /// The store where the product has been scanned (optional).
+    #[serde(default)]
+    pub store_id: Option<DBId>,
+
+    /// A photo of the packaging or the barcode of the product (optional).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub photo: Option<ProductImage>,
+}
+
+/// The request to report several missing products at once, e.g. of an offline scanning session.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct MissingProductsBatchReportRequest {
+    /// The ids of the missing products.
+    pub product_ids: Vec<ProductID>,
+
+    /// The store where the products have been scanned (optional).
+    #[serde(default)]
+    pub store_id: Option<DBId>,
+}
+
+/// The result of a single product id of a batch report of missing products.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct MissingProductReportResult {
+    pub product_id: ProductID,
+
+    /// The internal id of the report or `None` if the product id has been rejected.
+    pub id: Option<DBId>,
+
+    pub message: String,
+}
+
+/// The response to a batch report of missing products with the results in the order of the
+/// reported product ids.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct MissingProductsBatchReportResponse {
+    pub message: String,
+    pub date: Option<DateTime<Utc>>,
+    pub results: Vec<MissingProductReportResult>,
 }
 
 /// The response is only a message.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct OnlyMessageResponse {
     pub message: String,
 }
 
 /// The query parameter for getting a product.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct GetProductRequestQuery {
     #[serde(default)]
     pub with_preview: bool,
@@ -38,27 +122,116 @@ pub struct GetProductRequestQuery {
 
 /// The response to a request to add a new product to the database.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct GetProductRequestResponse {
     pub message: String,
     pub product_request: Option<ProductRequest>,
+
+    /// The violated plausibility rules of the nutrients of the requested product.
+    #[serde(default)]
+    pub warnings: Vec<NutrientIssue>,
 }
 
 /// The response to a product request query.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct ProductRequestQueryResponse {
     pub message: String,
     pub product_requests: Vec<(DBId, ProductRequest)>,
+    /// The total number of results matching the filters of the query across all pages, e.g.
+    /// for rendering the pagination controls.
+    #[serde(default)]
+    #[cfg_attr(feature = "typescript", ts(type = "number"))]
+    pub total_count: u64,
 }
 
 /// The response to a missing products query.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct MissingProductsQueryResponse {
     pub message: String,
     pub missing_products: Vec<(DBId, MissingProduct)>,
 }
 
+/// The response with the reports of the missing products grouped by the product id.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct MissingProductsSummaryResponse {
+    pub message: String,
+    /// The missing products sorted by the number of reports in descending order.
+    pub missing_products: Vec<MissingProductSummary>,
+}
+
+/// The file formats of the exports.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum ExportFormat {
+    /// Comma-separated values with a header line, see RFC 4180.
+    Csv,
+    /// An Excel workbook with a single worksheet and a header line.
+    Xlsx,
+    /// A ZIP archive with a JSON file and the images of each entry, only supported by the
+    /// export of the product requests.
+    Zip,
+}
+
+impl ExportFormat {
+    /// Returns the file extension of the format.
+    pub fn file_extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Xlsx => "xlsx",
+            ExportFormat::Zip => "zip",
+        }
+    }
+}
+
+/// The query parameters for exporting the results of a query.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct ExportQuery {
+    /// The file format of the export.
+    pub format: ExportFormat,
+}
+
+/// The query parameters for exporting the missing products, i.e., the filters of the missing
+/// products query without the pagination.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct MissingProductsExportQuery {
+    /// The file format of the export.
+    pub format: ExportFormat,
+    /// The product id to filter the results for (optional).
+    #[serde(default)]
+    pub product_id: Option<ProductID>,
+    /// If the results are in ascending or descending order of the reported date, ascending by
+    /// default.
+    #[serde(default)]
+    pub order: Option<SortingOrder>,
+}
+
+/// The response to a count query, i.e., the number of matching entries without any of them.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct CountResponse {
+    pub message: String,
+    #[cfg_attr(feature = "typescript", ts(type = "number"))]
+    pub count: u64,
+}
+
 /// The response to a request to add a new product to the database.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct GetReportedMissingProductResponse {
     pub message: String,
     pub missing_product: Option<MissingProduct>,
@@ -66,6 +239,8 @@ pub struct GetReportedMissingProductResponse {
 
 /// The response for getting a product.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct GetProductResponse {
     pub message: String,
     pub product: Option<ProductDescription>,
@@ -73,7 +248,324 @@ pub struct GetProductResponse {
 
 /// The response to a query for products.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct ProductQueryResponse {
     pub message: String,
     pub products: Vec<ProductDescription>,
+    /// The similarity of each product to the search string, from 0 to 1, in the order of the
+    /// products. Only defined if the products are sorted by their similarity.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scores: Option<Vec<f32>>,
+    /// The total number of results matching the filters of the query across all pages, e.g.
+    /// for rendering the pagination controls.
+    #[serde(default)]
+    #[cfg_attr(feature = "typescript", ts(type = "number"))]
+    pub total_count: u64,
+    /// The cursor of the next page of a sorted query, which is only defined if the page is
+    /// full. Passed as `cursor` of the query, the products after this page are returned.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
+}
+
+/// The response to a query for products with selected fields, i.e., each product only
+/// contains the requested fields.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct ProductProjectionQueryResponse {
+    pub message: String,
+    #[cfg_attr(feature = "typescript", ts(type = "Array<Record<string, unknown>>"))]
+    pub products: Vec<ProductProjection>,
+    /// The similarity of each product to the search string, from 0 to 1, in the order of the
+    /// products. Only defined if the products are sorted by their similarity.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scores: Option<Vec<f32>>,
+    /// The total number of results matching the filters of the query across all pages, e.g.
+    /// for rendering the pagination controls.
+    #[serde(default)]
+    #[cfg_attr(feature = "typescript", ts(type = "number"))]
+    pub total_count: u64,
+    /// The cursor of the next page of a sorted query, which is only defined if the page is
+    /// full. Passed as `cursor` of the query, the products after this page are returned.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
+}
+
+/// The request to sum up the nutrients of a list of portions, e.g. of a meal.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct NutrientAggregationRequest {
+    pub portions: Vec<Portion>,
+}
+
+/// The response with the summed nutrients of a list of portions.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct NutrientAggregationResponse {
+    pub message: String,
+    pub aggregation: Option<NutrientAggregation>,
+}
+
+/// The response to a request to add a new recipe to the database.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct NewRecipeResponse {
+    pub message: String,
+    pub id: Option<DBId>,
+}
+
+/// The response for getting a recipe together with the nutrients derived from the current
+/// nutrients of its ingredients.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct GetRecipeResponse {
+    pub message: String,
+    pub recipe: Option<Recipe>,
+    pub nutrients: Option<RecipeNutrients>,
+}
+
+/// The response to the submission of an observed price.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct PriceSubmissionResponse {
+    pub message: String,
+    pub id: Option<DBId>,
+}
+
+/// The response to a query of the observed prices.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct PriceQueryResponse {
+    pub message: String,
+    pub prices: Vec<(DBId, PriceObservation)>,
+}
+
+/// The query parameters for getting the cheapest price of a product.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct CheapestPriceQuery {
+    /// The ISO 4217 code of the currency the price must be stated in.
+    pub currency: String,
+}
+
+/// The response with the cheapest price seen for a product.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct CheapestPriceResponse {
+    pub message: String,
+    pub price: Option<PriceObservation>,
+}
+
+/// The response to a request to add a new store to the database.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct NewStoreResponse {
+    pub message: String,
+    pub id: Option<DBId>,
+}
+
+/// The response for getting a store.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct GetStoreResponse {
+    pub message: String,
+    pub store: Option<Store>,
+}
+
+/// The response to a query of the stores.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct StoreQueryResponse {
+    pub message: String,
+    pub stores: Vec<(DBId, Store)>,
+}
+
+/// The request to report a product as seen or as missing at a store.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct AvailabilityReportRequest {
+    pub product_id: ProductID,
+    pub available: bool,
+}
+
+/// The response with the last observed availability of a product in the stores.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct ProductAvailabilityResponse {
+    pub message: String,
+    pub availability: Vec<ProductAvailability>,
+}
+
+/// The request to switch the read-only (maintenance) mode of the service.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct MaintenanceModeRequest {
+    pub read_only: bool,
+}
+
+/// The response with the current read-only (maintenance) mode of the service.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct MaintenanceModeResponse {
+    pub message: String,
+    pub read_only: bool,
+}
+
+/// The progress of the regeneration of the preview images from the full images.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct ImageRegenerationStatus {
+    /// True while the regeneration is running.
+    pub running: bool,
+    /// The number of products and product requests to process.
+    pub total: u64,
+    /// The number of processed products and product requests, including the ones without full
+    /// image and the failed ones.
+    pub processed: u64,
+    /// The number of regenerated preview images.
+    pub regenerated: u64,
+    /// The number of products and product requests whose preview could not be regenerated.
+    pub failed: u64,
+    /// The date when the last regeneration has been started, if any.
+    pub started: Option<DateTime<Utc>>,
+    /// The date when the last regeneration has finished, if any.
+    pub finished: Option<DateTime<Utc>>,
+}
+
+/// The response with the progress of the regeneration of the preview images.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct ImageRegenerationResponse {
+    pub message: String,
+    pub status: ImageRegenerationStatus,
+}
+
+/// The response with the storage used by the images and the configured limits.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct ImageStorageUsageResponse {
+    pub message: String,
+    /// The number of stored images.
+    #[cfg_attr(feature = "typescript", ts(type = "number"))]
+    pub total_images: u64,
+    /// The total size of the stored images in bytes.
+    #[cfg_attr(feature = "typescript", ts(type = "number"))]
+    pub total_bytes: u64,
+    /// The maximal number of bytes of all stored images, if limited.
+    #[cfg_attr(feature = "typescript", ts(type = "number | null"))]
+    pub max_total_bytes: Option<u64>,
+    /// The maximal number of bytes of the images of a single product, if limited.
+    #[cfg_attr(feature = "typescript", ts(type = "number | null"))]
+    pub max_product_bytes: Option<u64>,
+    /// The maximal number of bytes of a single image, if limited.
+    #[cfg_attr(feature = "typescript", ts(type = "number | null"))]
+    pub max_image_bytes: Option<u64>,
+    /// The storage used by the images broken down by their content type.
+    pub content_types: Vec<ImageStorageUsage>,
+}
+
+/// The request to archive a processed product request.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct ArchiveProductRequestRequest {
+    pub outcome: RequestOutcome,
+
+    /// Whether the additional images of an approved request are added to the product with the
+    /// same id, which must already exist.
+    #[serde(default)]
+    pub carry_over_images: bool,
+}
+
+/// The request to reject a product request, which is kept for later archiving.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct RejectProductRequestRequest {
+    /// The comment of the reviewer, e.g., the reason for rejecting the request.
+    #[serde(default)]
+    pub comment: Option<String>,
+}
+
+/// The response to approving a product request.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct ApproveProductRequestResponse {
+    pub message: String,
+    /// The id of the product the request has been added as.
+    pub product_id: Option<ProductID>,
+}
+
+/// The response with the additional images of a product or product request.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct AdditionalImagesResponse {
+    pub message: String,
+    pub images: Vec<AdditionalImage>,
+}
+
+/// The response with the report of a self-test against the data backend.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct SelfTestResponse {
+    pub message: String,
+    pub report: SelfTestReport,
+}
+
+/// The response to a query for archived product requests.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct ArchiveQueryResponse {
+    pub message: String,
+    pub archived_product_requests: Vec<ArchivedProductRequest>,
+}
+
+/// The response with a pre-signed url for the public image endpoint.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct SignedImageUrlResponse {
+    pub message: String,
+    pub url: Option<String>,
+    pub expires: Option<DateTime<Utc>>,
+}
+
+/// A problem document as defined in RFC 7807. Used for errors that are raised by the service
+/// itself and not by the individual handlers, e.g. timeouts.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct ProblemDocument {
+    #[serde(rename = "type")]
+    pub problem_type: String,
+    pub title: String,
+    pub status: u16,
+    pub detail: String,
+
+    /// The id of the failed request, i.e., the `x-request-id` of the response.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
 }
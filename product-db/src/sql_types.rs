@@ -1,6 +1,8 @@
 use crate::{
-    DBId, MissingProduct, Nutrients, ProductDescription, ProductID, ProductImage, ProductInfo,
-    ProductRequest, QuantityType, Weight,
+    Allergen, DBId, DietaryLabels, EcoScore, EcoScoreGrade, MissingProduct, MissingProductSummary,
+    Nutrients, Packaging, PackagingMaterial, Portion, PriceObservation, ProductDescription,
+    ProductID, ProductImage, ProductInfo, ProductRequest, QuantityType, Recipe, RequestStatus,
+    Store, Weight,
 };
 
 use chrono::{DateTime, Utc};
@@ -17,6 +19,9 @@ pub struct SQLMissingProduct {
 
     /// The date when the product has been reported as missing.
     pub date: DateTime<Utc>,
+
+    /// The store where the product has been scanned.
+    pub store_id: Option<DBId>,
 }
 
 impl From<SQLMissingProduct> for (DBId, MissingProduct) {
@@ -26,11 +31,73 @@ impl From<SQLMissingProduct> for (DBId, MissingProduct) {
             MissingProduct {
                 product_id: sql_missing_product.product_id,
                 date: sql_missing_product.date,
+                store_id: sql_missing_product.store_id,
             },
         )
     }
 }
 
+/// The reports of a missing product grouped by its id.
+#[derive(Debug, Clone, PartialEq, Eq, sqlx::FromRow)]
+pub struct SQLMissingProductSummary {
+    /// The id of the missing product.
+    pub product_id: ProductID,
+
+    /// The number of reports of the missing product.
+    pub reports: i64,
+
+    /// The date of the first report of the missing product.
+    pub first_reported: DateTime<Utc>,
+
+    /// The date of the last report of the missing product.
+    pub last_reported: DateTime<Utc>,
+}
+
+impl From<SQLMissingProductSummary> for MissingProductSummary {
+    fn from(summary: SQLMissingProductSummary) -> Self {
+        Self {
+            product_id: summary.product_id,
+            reports: summary.reports as u64,
+            first_reported: summary.first_reported,
+            last_reported: summary.last_reported,
+        }
+    }
+}
+
+/// A store with its internal id.
+#[derive(Debug, Clone, PartialEq, Eq, sqlx::FromRow)]
+pub struct SQLStore {
+    /// The internal id of the store.
+    pub id: DBId,
+
+    /// The store.
+    #[sqlx(flatten)]
+    pub store: Store,
+}
+
+impl From<SQLStore> for (DBId, Store) {
+    fn from(sql_store: SQLStore) -> Self {
+        (sql_store.id, sql_store.store)
+    }
+}
+
+/// An observed price with its internal id.
+#[derive(Debug, Clone, PartialEq, Eq, sqlx::FromRow)]
+pub struct SQLPriceObservation {
+    /// The internal id of the price.
+    pub id: DBId,
+
+    /// The observed price.
+    #[sqlx(flatten)]
+    pub price: PriceObservation,
+}
+
+impl From<SQLPriceObservation> for (DBId, PriceObservation) {
+    fn from(sql_price: SQLPriceObservation) -> Self {
+        (sql_price.id, sql_price.price)
+    }
+}
+
 /// A product request
 #[derive(Debug, Clone, PartialEq, sqlx::FromRow)]
 pub struct SQLProductDescription {
@@ -40,6 +107,7 @@ pub struct SQLProductDescription {
     pub quantity_type: QuantityType,
     pub portion: f32,
     pub volume_weight_ratio: Option<f32>,
+    pub market: Option<String>,
     pub kcal: f32,
     pub protein_grams: Option<f32>,
     pub fat_grams: Option<f32>,
@@ -54,9 +122,32 @@ pub struct SQLProductDescription {
     pub magnesium_mg: Option<f32>,
     pub sodium_mg: Option<f32>,
     pub zinc_mg: Option<f32>,
+    pub packaging_net_quantity: Option<f32>,
+    pub packaging_units: Option<i32>,
+    pub packaging_material: Option<PackagingMaterial>,
+    pub packaging_deposit: Option<bool>,
+    pub vegan: Option<bool>,
+    pub vegetarian: Option<bool>,
+    pub organic: Option<bool>,
+    pub gluten_free: Option<bool>,
+    pub lactose_free: Option<bool>,
+    pub nova_group: Option<i32>,
+    pub eco_score_grade: Option<EcoScoreGrade>,
+    pub eco_score_value: Option<i32>,
+    pub eco_score_source: Option<String>,
+    pub allergens: Vec<Allergen>,
 
     pub preview: Option<Vec<u8>>,
     pub preview_content_type: Option<String>,
+
+    /// The similarity to the search string, which is only selected for scored queries.
+    #[sqlx(default)]
+    pub score: Option<f32>,
+
+    /// The number of rows matching the filters of the query, which is only selected for
+    /// counted queries.
+    #[sqlx(default)]
+    pub total_count: Option<i64>,
 }
 
 /// A product request
@@ -64,6 +155,8 @@ pub struct SQLProductDescription {
 pub struct SQLRequestedProduct {
     pub desc: SQLProductDescription,
     pub date: DateTime<Utc>,
+    pub status: RequestStatus,
+    pub review_comment: Option<String>,
 }
 
 impl FromRow<'_, PgRow> for SQLRequestedProduct {
@@ -71,6 +164,8 @@ impl FromRow<'_, PgRow> for SQLRequestedProduct {
         Ok(Self {
             desc: SQLProductDescription::from_row(row)?,
             date: row.try_get("date")?,
+            status: row.try_get("status")?,
+            review_comment: row.try_get("review_comment")?,
         })
     }
 }
@@ -81,6 +176,8 @@ pub struct SQLRequestedProductWithId {
     pub id: DBId,
     pub desc: SQLProductDescription,
     pub date: DateTime<Utc>,
+    pub status: RequestStatus,
+    pub review_comment: Option<String>,
 }
 
 impl FromRow<'_, PgRow> for SQLRequestedProductWithId {
@@ -88,11 +185,46 @@ impl FromRow<'_, PgRow> for SQLRequestedProductWithId {
         Ok(Self {
             desc: SQLProductDescription::from_row(row)?,
             date: row.try_get("date")?,
+            status: row.try_get("status")?,
+            review_comment: row.try_get("review_comment")?,
             id: row.try_get("r_id")?,
         })
     }
 }
 
+/// A recipe with its ingredients aggregated into arrays ordered by their position.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SQLRecipe {
+    pub name: String,
+    pub servings: i32,
+    pub product_ids: Vec<ProductID>,
+    pub amounts: Vec<f32>,
+    pub image_data: Option<Vec<u8>>,
+    pub image_content_type: Option<String>,
+    pub image_storage_key: Option<String>,
+}
+
+impl From<SQLRecipe> for Recipe {
+    fn from(r: SQLRecipe) -> Self {
+        let image = match (r.image_data, r.image_content_type) {
+            (Some(data), Some(content_type)) => Some(ProductImage { content_type, data }),
+            _ => None,
+        };
+
+        Self {
+            name: r.name,
+            servings: r.servings,
+            ingredients: r
+                .product_ids
+                .into_iter()
+                .zip(r.amounts)
+                .map(|(product_id, amount)| Portion { product_id, amount })
+                .collect(),
+            image,
+        }
+    }
+}
+
 impl From<&SQLProductDescription> for Nutrients {
     fn from(r: &SQLProductDescription) -> Self {
         Self {
@@ -114,8 +246,43 @@ impl From<&SQLProductDescription> for Nutrients {
     }
 }
 
+impl From<&SQLProductDescription> for Option<Packaging> {
+    fn from(r: &SQLProductDescription) -> Self {
+        r.packaging_deposit.map(|deposit| Packaging {
+            net_quantity: r.packaging_net_quantity,
+            units: r.packaging_units,
+            material: r.packaging_material,
+            deposit,
+        })
+    }
+}
+
+impl From<&SQLProductDescription> for Option<EcoScore> {
+    fn from(r: &SQLProductDescription) -> Self {
+        r.eco_score_grade.map(|grade| EcoScore {
+            grade,
+            value: r.eco_score_value,
+            source: r.eco_score_source.clone(),
+        })
+    }
+}
+
+impl From<&SQLProductDescription> for DietaryLabels {
+    fn from(r: &SQLProductDescription) -> Self {
+        Self {
+            vegan: r.vegan,
+            vegetarian: r.vegetarian,
+            organic: r.organic,
+            gluten_free: r.gluten_free,
+            lactose_free: r.lactose_free,
+        }
+    }
+}
+
 impl From<SQLProductDescription> for ProductInfo {
     fn from(r: SQLProductDescription) -> Self {
+        let labels = (&r).into();
+
         Self {
             id: r.product_id,
             name: r.name,
@@ -123,12 +290,17 @@ impl From<SQLProductDescription> for ProductInfo {
             quantity_type: r.quantity_type,
             portion: r.portion,
             volume_weight_ratio: r.volume_weight_ratio,
+            market: r.market,
+            labels,
+            nova_group: r.nova_group,
+            allergens: r.allergens,
         }
     }
 }
 
 impl From<SQLProductDescription> for (Option<ProductImage>, ProductInfo) {
     fn from(r: SQLProductDescription) -> Self {
+        let labels = (&r).into();
         let preview = r.preview.map(|p| ProductImage {
             data: p,
             content_type: r.preview_content_type.unwrap(),
@@ -143,6 +315,10 @@ impl From<SQLProductDescription> for (Option<ProductImage>, ProductInfo) {
                 quantity_type: r.quantity_type,
                 portion: r.portion,
                 volume_weight_ratio: r.volume_weight_ratio,
+                market: r.market,
+                labels,
+                nova_group: r.nova_group,
+                allergens: r.allergens,
             },
         )
     }
@@ -151,6 +327,8 @@ impl From<SQLProductDescription> for (Option<ProductImage>, ProductInfo) {
 impl From<SQLProductDescription> for ProductDescription {
     fn from(r: SQLProductDescription) -> Self {
         let nutrients = (&r).into();
+        let packaging = (&r).into();
+        let eco_score = (&r).into();
         let (preview, info) = r.into();
 
         Self {
@@ -158,6 +336,8 @@ impl From<SQLProductDescription> for ProductDescription {
             nutrients,
             preview,
             full_image: None,
+            packaging,
+            eco_score,
         }
     }
 }
@@ -167,6 +347,8 @@ impl From<SQLRequestedProduct> for ProductRequest {
         Self {
             date: r.date,
             product_description: r.desc.into(),
+            status: r.status,
+            review_comment: r.review_comment,
         }
     }
 }
@@ -175,6 +357,8 @@ impl From<SQLRequestedProductWithId> for ProductRequest {
     fn from(r: SQLRequestedProductWithId) -> Self {
         let date = r.date;
         let nutrients = (&r.desc).into();
+        let packaging = (&r.desc).into();
+        let eco_score = (&r.desc).into();
         let (preview, info) = r.desc.into();
 
         Self {
@@ -184,7 +368,11 @@ impl From<SQLRequestedProductWithId> for ProductRequest {
                 nutrients,
                 preview,
                 full_image: None,
+                packaging,
+                eco_score,
             },
+            status: r.status,
+            review_comment: r.review_comment,
         }
     }
 }
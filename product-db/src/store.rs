@@ -0,0 +1,70 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{DBId, ProductID};
+#[cfg(feature = "service")]
+use crate::{Error, Result};
+
+/// The maximal length of the name of a store.
+pub const MAX_STORE_NAME_LENGTH: usize = 64;
+
+/// A store or retailer where the products are sold, e.g. a branch of a supermarket chain.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "service", derive(sqlx::FromRow))]
+pub struct Store {
+    /// The name of the store.
+    pub name: String,
+
+    /// The retail chain the store belongs to, e.g. for chain-specific catalogs.
+    #[serde(default)]
+    pub chain: Option<String>,
+}
+
+/// The last observed availability of a product in a store.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "service", derive(sqlx::FromRow))]
+pub struct ProductAvailability {
+    /// The id of the store.
+    pub store_id: DBId,
+
+    /// The id of the product.
+    pub product_id: ProductID,
+
+    /// If the product has been seen or has been missing at the store.
+    pub available: bool,
+
+    /// The date when the availability has been observed.
+    pub date: DateTime<Utc>,
+}
+
+/// Checks that the store and its chain have names of a valid length.
+///
+/// # Arguments
+/// - `store` - The store to check.
+#[cfg(feature = "service")]
+pub fn check_store(store: &Store) -> Result<()> {
+    let is_valid_name =
+        |name: &str| !name.trim().is_empty() && name.chars().count() <= MAX_STORE_NAME_LENGTH;
+
+    if !is_valid_name(&store.name) {
+        return Err(Error::InvalidStore(format!(
+            "the name must have between 1 and {} characters",
+            MAX_STORE_NAME_LENGTH
+        )));
+    }
+
+    if let Some(chain) = store.chain.as_ref() {
+        if !is_valid_name(chain) {
+            return Err(Error::InvalidStore(format!(
+                "the chain must have between 1 and {} characters",
+                MAX_STORE_NAME_LENGTH
+            )));
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,214 @@
+use std::{path::Path, pin::pin, sync::Arc};
+
+use axum::{
+    extract::{ConnectInfo, OriginalUri, Request},
+    http::StatusCode,
+    middleware::Next,
+    response::Response,
+    Router,
+};
+use futures::future::{select, Either};
+use hyper_util::{
+    rt::{TokioExecutor, TokioIo},
+    server::{conn::auto, graceful::GracefulShutdown},
+    service::TowerToHyperService,
+};
+use log::{debug, error, info, warn};
+use rustls::{
+    crypto::ring::default_provider,
+    pki_types::{pem::PemObject, CertificateDer, PrivateKeyDer},
+    server::WebPkiClientVerifier,
+    RootCertStore, ServerConfig,
+};
+use tokio::{net::TcpListener, sync::watch};
+use tokio_rustls::TlsAcceptor;
+use tower::ServiceExt;
+
+use crate::{layers::problem_response, Error, Result, TlsOptions};
+
+/// Request extension that marks requests received on a connection whose client certificate
+/// has been verified against the configured CAs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VerifiedClientCertificate;
+
+/// Loads the certificates from the given PEM file.
+///
+/// # Arguments
+/// - `path` - The path to the PEM file.
+fn load_certificates(path: &Path) -> Result<Vec<CertificateDer<'static>>> {
+    let certificates = CertificateDer::pem_file_iter(path)
+        .and_then(|certificates| certificates.collect::<std::result::Result<Vec<_>, _>>())
+        .map_err(|e| {
+            error!("Failed to load the certificates {}: {}", path.display(), e);
+            Error::ConfigError(format!(
+                "Failed to load the certificates {}: {}",
+                path.display(),
+                e
+            ))
+        })?;
+
+    if certificates.is_empty() {
+        return Err(Error::ConfigError(format!(
+            "No certificates found in {}",
+            path.display()
+        )));
+    }
+
+    Ok(certificates)
+}
+
+/// Creates the acceptor for TLS connections. If a client CA is configured, client
+/// certificates are requested and verified, but connections without client certificate are
+/// accepted as well, see [`client_certificate_guard`].
+///
+/// # Arguments
+/// - `options` - The TLS options.
+pub fn tls_acceptor(options: &TlsOptions) -> Result<TlsAcceptor> {
+    let certificates = load_certificates(&options.cert_file)?;
+    let key = PrivateKeyDer::from_pem_file(&options.key_file).map_err(|e| {
+        error!(
+            "Failed to load the private key {}: {}",
+            options.key_file.display(),
+            e
+        );
+        Error::ConfigError(format!(
+            "Failed to load the private key {}: {}",
+            options.key_file.display(),
+            e
+        ))
+    })?;
+
+    let provider = Arc::new(default_provider());
+    let builder = ServerConfig::builder_with_provider(provider.clone())
+        .with_safe_default_protocol_versions()
+        .map_err(|e| Error::ConfigError(format!("Invalid TLS configuration: {}", e)))?;
+
+    let builder = match &options.client_ca_file {
+        Some(client_ca_file) => {
+            let mut roots = RootCertStore::empty();
+            for certificate in load_certificates(client_ca_file)? {
+                roots.add(certificate).map_err(|e| {
+                    Error::ConfigError(format!(
+                        "Invalid client CA certificate in {}: {}",
+                        client_ca_file.display(),
+                        e
+                    ))
+                })?;
+            }
+
+            let verifier = WebPkiClientVerifier::builder_with_provider(Arc::new(roots), provider)
+                .allow_unauthenticated()
+                .build()
+                .map_err(|e| {
+                    Error::ConfigError(format!("Invalid client certificate verifier: {}", e))
+                })?;
+
+            builder.with_client_cert_verifier(verifier)
+        }
+        None => builder.with_no_client_auth(),
+    };
+
+    let mut config = builder
+        .with_single_cert(certificates, key)
+        .map_err(|e| Error::ConfigError(format!("Invalid TLS certificate or key: {}", e)))?;
+    config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+
+    Ok(TlsAcceptor::from(Arc::new(config)))
+}
+
+/// Serves the given router via TLS on the listener until the stop signal is received. Open
+/// connections are shut down gracefully.
+///
+/// # Arguments
+/// - `listener` - The listener to accept the connections on.
+/// - `app` - The router for handling the requests.
+/// - `acceptor` - The acceptor for the TLS connections.
+/// - `rx` - The receiver of the stop signal.
+pub(crate) async fn serve_tls(
+    listener: TcpListener,
+    app: Router,
+    acceptor: TlsAcceptor,
+    mut rx: watch::Receiver<i32>,
+) {
+    let graceful = GracefulShutdown::new();
+
+    loop {
+        let (stream, peer) = match select(pin!(listener.accept()), pin!(rx.changed())).await {
+            Either::Left((Ok(connection), _)) => connection,
+            Either::Left((Err(e), _)) => {
+                warn!("Failed to accept a connection: {}", e);
+                continue;
+            }
+            Either::Right(_) => {
+                info!("Received stop signal, stopping the server...");
+                break;
+            }
+        };
+
+        let acceptor = acceptor.clone();
+        let app = app.clone();
+        let watcher = graceful.watcher();
+
+        tokio::spawn(async move {
+            let stream = match acceptor.accept(stream).await {
+                Ok(stream) => stream,
+                Err(e) => {
+                    debug!("TLS handshake with {} failed: {}", peer, e);
+                    return;
+                }
+            };
+
+            // the verifier only accepts client certificates issued by the configured CAs
+            let verified = stream
+                .get_ref()
+                .1
+                .peer_certificates()
+                .is_some_and(|certificates| !certificates.is_empty());
+            let service = app.map_request(move |mut request: Request<_>| {
+                request.extensions_mut().insert(ConnectInfo(peer));
+                if verified {
+                    request.extensions_mut().insert(VerifiedClientCertificate);
+                }
+                request
+            });
+
+            let builder = auto::Builder::new(TokioExecutor::new());
+            let connection = builder.serve_connection_with_upgrades(
+                TokioIo::new(stream),
+                TowerToHyperService::new(service),
+            );
+            if let Err(e) = watcher.watch(connection).await {
+                debug!("Connection with {} failed: {}", peer, e);
+            }
+        });
+    }
+
+    graceful.shutdown().await;
+}
+
+/// Middleware that rejects requests without a verified client certificate with 403.
+pub async fn client_certificate_guard(request: Request, next: Next) -> Response {
+    if request
+        .extensions()
+        .get::<VerifiedClientCertificate>()
+        .is_none()
+    {
+        let uri = request
+            .extensions()
+            .get::<OriginalUri>()
+            .map(|uri| uri.0.clone())
+            .unwrap_or_else(|| request.uri().clone());
+        warn!(
+            "Rejected {} {} without verified client certificate",
+            request.method(),
+            uri
+        );
+
+        return problem_response(
+            StatusCode::FORBIDDEN,
+            "A verified client certificate is required".to_string(),
+        );
+    }
+
+    next.run(request).await
+}
@@ -0,0 +1,142 @@
+use ts_rs::TS;
+
+use crate::service_json::*;
+use crate::*;
+
+/// Appends the exported declaration of the given type including its documentation.
+///
+/// # Arguments
+/// - `out` - The string to append the declaration to.
+fn push_declaration<T: TS + ?Sized>(out: &mut String) {
+    if let Some(docs) = T::docs() {
+        out.push_str(&docs);
+    }
+
+    out.push_str("export ");
+    out.push_str(&T::decl());
+    out.push_str("\n\n");
+}
+
+/// Returns the TypeScript declarations of all types of the JSON API as the content of a
+/// `.d.ts` file.
+pub fn typescript_declarations() -> String {
+    let mut out = String::from(
+        "// This file has been generated by the generate-typescript binary of product-db.\n\
+         // Do not edit it by hand.\n\n",
+    );
+
+    // the domain types
+    push_declaration::<ProductDescription>(&mut out);
+    push_declaration::<ProductInfo>(&mut out);
+    push_declaration::<DietaryLabels>(&mut out);
+    push_declaration::<Allergen>(&mut out);
+    push_declaration::<ProductImage>(&mut out);
+    push_declaration::<ImageRole>(&mut out);
+    push_declaration::<AdditionalImage>(&mut out);
+    push_declaration::<ImageStorageUsage>(&mut out);
+    push_declaration::<ProductRequest>(&mut out);
+    push_declaration::<RequestStatus>(&mut out);
+    push_declaration::<RequestOutcome>(&mut out);
+    push_declaration::<ArchivedProductRequest>(&mut out);
+    push_declaration::<MissingProduct>(&mut out);
+    push_declaration::<MissingProductSummary>(&mut out);
+    push_declaration::<Nutrients>(&mut out);
+    push_declaration::<NutrientIssue>(&mut out);
+    push_declaration::<Weight>(&mut out);
+    push_declaration::<QuantityType>(&mut out);
+    push_declaration::<Packaging>(&mut out);
+    push_declaration::<PackagingMaterial>(&mut out);
+    push_declaration::<EcoScore>(&mut out);
+    push_declaration::<EcoScoreGrade>(&mut out);
+    push_declaration::<Portion>(&mut out);
+    push_declaration::<NutrientAggregation>(&mut out);
+    push_declaration::<Recipe>(&mut out);
+    push_declaration::<RecipeNutrients>(&mut out);
+    push_declaration::<PriceObservation>(&mut out);
+    push_declaration::<Store>(&mut out);
+    push_declaration::<ProductAvailability>(&mut out);
+
+    // the query parameters
+    push_declaration::<SortingOrder>(&mut out);
+    push_declaration::<MissingProductQuery>(&mut out);
+    push_declaration::<ArchiveQuery>(&mut out);
+    push_declaration::<PriceQuery>(&mut out);
+    push_declaration::<StoreQuery>(&mut out);
+    push_declaration::<SortingField>(&mut out);
+    push_declaration::<Sorting>(&mut out);
+    push_declaration::<SearchFilter>(&mut out);
+    push_declaration::<ProductField>(&mut out);
+    push_declaration::<ProductQuery>(&mut out);
+
+    // the requests and responses of the service
+    push_declaration::<ProductRequestResponse>(&mut out);
+    out.push_str("export type MissingProductReportResponse = ProductRequestResponse;\n\n");
+    push_declaration::<MissingProductReportRequest>(&mut out);
+    push_declaration::<MissingProductsBatchReportRequest>(&mut out);
+    push_declaration::<MissingProductReportResult>(&mut out);
+    push_declaration::<MissingProductsBatchReportResponse>(&mut out);
+    push_declaration::<ProductSubmission>(&mut out);
+    push_declaration::<OnlyMessageResponse>(&mut out);
+    push_declaration::<GetProductRequestQuery>(&mut out);
+    push_declaration::<GetProductRequestResponse>(&mut out);
+    push_declaration::<ProductRequestQueryResponse>(&mut out);
+    push_declaration::<MissingProductsQueryResponse>(&mut out);
+    push_declaration::<MissingProductsSummaryResponse>(&mut out);
+    push_declaration::<ExportFormat>(&mut out);
+    push_declaration::<ExportQuery>(&mut out);
+    push_declaration::<MissingProductsExportQuery>(&mut out);
+    push_declaration::<GetReportedMissingProductResponse>(&mut out);
+    push_declaration::<GetProductResponse>(&mut out);
+    push_declaration::<ProductQueryResponse>(&mut out);
+    push_declaration::<ProductProjectionQueryResponse>(&mut out);
+    push_declaration::<CountResponse>(&mut out);
+    push_declaration::<NutrientAggregationRequest>(&mut out);
+    push_declaration::<NutrientAggregationResponse>(&mut out);
+    push_declaration::<NewRecipeResponse>(&mut out);
+    push_declaration::<GetRecipeResponse>(&mut out);
+    push_declaration::<PriceSubmissionResponse>(&mut out);
+    push_declaration::<PriceQueryResponse>(&mut out);
+    push_declaration::<CheapestPriceQuery>(&mut out);
+    push_declaration::<CheapestPriceResponse>(&mut out);
+    push_declaration::<NewStoreResponse>(&mut out);
+    push_declaration::<GetStoreResponse>(&mut out);
+    push_declaration::<StoreQueryResponse>(&mut out);
+    push_declaration::<AvailabilityReportRequest>(&mut out);
+    push_declaration::<ProductAvailabilityResponse>(&mut out);
+    push_declaration::<MaintenanceModeRequest>(&mut out);
+    push_declaration::<MaintenanceModeResponse>(&mut out);
+    push_declaration::<ImageRegenerationStatus>(&mut out);
+    push_declaration::<ImageRegenerationResponse>(&mut out);
+    push_declaration::<ImageStorageUsageResponse>(&mut out);
+    push_declaration::<ArchiveProductRequestRequest>(&mut out);
+    push_declaration::<RejectProductRequestRequest>(&mut out);
+    push_declaration::<ApproveProductRequestResponse>(&mut out);
+    push_declaration::<ArchiveQueryResponse>(&mut out);
+    push_declaration::<AdditionalImagesResponse>(&mut out);
+    push_declaration::<SelfTestOperation>(&mut out);
+    push_declaration::<SelfTestStep>(&mut out);
+    push_declaration::<SelfTestReport>(&mut out);
+    push_declaration::<SelfTestResponse>(&mut out);
+    push_declaration::<SignedImageUrlResponse>(&mut out);
+    push_declaration::<ProblemDocument>(&mut out);
+
+    out
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_typescript_declarations() {
+        let declarations = typescript_declarations();
+
+        assert!(declarations.contains("export type ProductImage = {"));
+        assert!(declarations.contains("contentType: string"));
+        assert!(declarations.contains("data: string"));
+        assert!(declarations.contains("vitaminA: Weight | null"));
+        assert!(declarations.contains("export type QuantityType = \"weight\" | \"volume\";"));
+        assert!(declarations.contains("product_requests: Array<[number, ProductRequest]>"));
+        assert!(declarations.contains("export type ProblemDocument = { type: string"));
+    }
+}
@@ -0,0 +1,402 @@
+use std::{fmt::Display, str::FromStr};
+
+use crate::{Volume, Weight};
+
+/// The error of parsing a unit-annotated value like "1.2 mg".
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseUnitError {
+    /// The value is missing or not a finite, non-negative number.
+    InvalidValue(String),
+    /// The value has no unit.
+    MissingUnit(String),
+    /// The unit is unknown for the quantity, e.g. "ml" for a weight.
+    UnknownUnit(String),
+}
+
+impl Display for ParseUnitError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseUnitError::InvalidValue(s) => write!(f, "Invalid value in '{}'", s),
+            ParseUnitError::MissingUnit(s) => write!(f, "Missing unit in '{}'", s),
+            ParseUnitError::UnknownUnit(unit) => write!(f, "Unknown unit '{}'", unit),
+        }
+    }
+}
+
+impl std::error::Error for ParseUnitError {}
+
+/// The units of weights.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum WeightUnit {
+    Microgram,
+    Milligram,
+    Gram,
+    Kilogram,
+}
+
+impl WeightUnit {
+    /// Returns the symbol of the unit, e.g. "mg".
+    pub fn symbol(self) -> &'static str {
+        match self {
+            WeightUnit::Microgram => "µg",
+            WeightUnit::Milligram => "mg",
+            WeightUnit::Gram => "g",
+            WeightUnit::Kilogram => "kg",
+        }
+    }
+
+    /// Returns the number of gram of one unit.
+    pub fn gram(self) -> f32 {
+        match self {
+            WeightUnit::Microgram => 1e-6,
+            WeightUnit::Milligram => 1e-3,
+            WeightUnit::Gram => 1.0,
+            WeightUnit::Kilogram => 1e3,
+        }
+    }
+}
+
+impl FromStr for WeightUnit {
+    type Err = ParseUnitError;
+
+    /// Parses the symbol of the unit. Besides "µg", the micro sign may be written as Greek mu,
+    /// "u" or "mc", e.g. "mcg". The symbols are case-insensitive.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "µg" | "μg" | "ug" | "mcg" => Ok(WeightUnit::Microgram),
+            "mg" => Ok(WeightUnit::Milligram),
+            "g" => Ok(WeightUnit::Gram),
+            "kg" => Ok(WeightUnit::Kilogram),
+            _ => Err(ParseUnitError::UnknownUnit(s.to_string())),
+        }
+    }
+}
+
+/// The units of volumes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum VolumeUnit {
+    Millilitre,
+    Centilitre,
+    Decilitre,
+    Litre,
+}
+
+impl VolumeUnit {
+    /// Returns the symbol of the unit, e.g. "ml".
+    pub fn symbol(self) -> &'static str {
+        match self {
+            VolumeUnit::Millilitre => "ml",
+            VolumeUnit::Centilitre => "cl",
+            VolumeUnit::Decilitre => "dl",
+            VolumeUnit::Litre => "l",
+        }
+    }
+
+    /// Returns the number of litre of one unit.
+    pub fn litre(self) -> f32 {
+        match self {
+            VolumeUnit::Millilitre => 1e-3,
+            VolumeUnit::Centilitre => 1e-2,
+            VolumeUnit::Decilitre => 1e-1,
+            VolumeUnit::Litre => 1.0,
+        }
+    }
+}
+
+impl FromStr for VolumeUnit {
+    type Err = ParseUnitError;
+
+    /// Parses the symbol of the unit. The symbols are case-insensitive, e.g. "mL" and "L".
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "ml" => Ok(VolumeUnit::Millilitre),
+            "cl" => Ok(VolumeUnit::Centilitre),
+            "dl" => Ok(VolumeUnit::Decilitre),
+            "l" => Ok(VolumeUnit::Litre),
+            _ => Err(ParseUnitError::UnknownUnit(s.to_string())),
+        }
+    }
+}
+
+impl FromStr for Weight {
+    type Err = ParseUnitError;
+
+    /// Parses a weight with unit like "1.2 mg", "450 µg" or "0.5kg".
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (value, unit) = split_value_and_unit(s)?;
+        let unit: WeightUnit = unit.parse()?;
+
+        Ok(Weight::new_from_gram(value * unit.gram()))
+    }
+}
+
+impl FromStr for Volume {
+    type Err = ParseUnitError;
+
+    /// Parses a volume with unit like "0.33 l" or "250ml".
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (value, unit) = split_value_and_unit(s)?;
+        let unit: VolumeUnit = unit.parse()?;
+
+        Ok(Volume {
+            value: value * unit.litre(),
+        })
+    }
+}
+
+impl Weight {
+    /// Returns the unit in which the weight is most readable, i.e., the largest unit in which
+    /// the value is at least 1.
+    pub fn display_unit(self) -> WeightUnit {
+        let gram = self.gram().abs();
+        if gram >= 1e3 {
+            WeightUnit::Kilogram
+        } else if gram >= 1.0 || gram == 0.0 {
+            WeightUnit::Gram
+        } else if gram >= 1e-3 {
+            WeightUnit::Milligram
+        } else {
+            WeightUnit::Microgram
+        }
+    }
+
+    /// Returns the weight expressed in the given unit.
+    ///
+    /// # Arguments
+    /// - `unit` - The unit of the returned value.
+    pub fn value_in(self, unit: WeightUnit) -> f32 {
+        self.gram() / unit.gram()
+    }
+
+    /// Formats the weight in the given unit, e.g. "12 mg".
+    ///
+    /// # Arguments
+    /// - `unit` - The unit to format the weight in.
+    pub fn format_as(self, unit: WeightUnit) -> String {
+        format!(
+            "{} {}",
+            format_value(self.value_in(unit), None),
+            unit.symbol()
+        )
+    }
+}
+
+impl Display for Weight {
+    /// Formats the weight in its most readable unit, e.g. "12 mg" instead of "0.012 g". The
+    /// number of decimals can be set by the precision, e.g. `{:.1}`.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let unit = self.display_unit();
+        write!(
+            f,
+            "{} {}",
+            format_value(self.value_in(unit), f.precision()),
+            unit.symbol()
+        )
+    }
+}
+
+impl Volume {
+    /// Returns the unit in which the volume is most readable, i.e., litre from 1 l on and
+    /// millilitre below.
+    pub fn display_unit(self) -> VolumeUnit {
+        if self.litre().abs() >= 1.0 {
+            VolumeUnit::Litre
+        } else {
+            VolumeUnit::Millilitre
+        }
+    }
+
+    /// Returns the volume expressed in the given unit.
+    ///
+    /// # Arguments
+    /// - `unit` - The unit of the returned value.
+    pub fn value_in(self, unit: VolumeUnit) -> f32 {
+        self.litre() / unit.litre()
+    }
+
+    /// Formats the volume in the given unit, e.g. "33 cl".
+    ///
+    /// # Arguments
+    /// - `unit` - The unit to format the volume in.
+    pub fn format_as(self, unit: VolumeUnit) -> String {
+        format!(
+            "{} {}",
+            format_value(self.value_in(unit), None),
+            unit.symbol()
+        )
+    }
+}
+
+impl Display for Volume {
+    /// Formats the volume in its most readable unit, e.g. "330 ml" instead of "0.33 l". The
+    /// number of decimals can be set by the precision, e.g. `{:.1}`.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let unit = self.display_unit();
+        write!(
+            f,
+            "{} {}",
+            format_value(self.value_in(unit), f.precision()),
+            unit.symbol()
+        )
+    }
+}
+
+/// Formats the value with the given number of decimals. Without a precision, the number of
+/// decimals shrinks with the magnitude, i.e., about three significant digits are shown, and
+/// trailing zeros are removed.
+///
+/// # Arguments
+/// - `value` - The value to format.
+/// - `precision` - The number of decimals, if fixed.
+fn format_value(value: f32, precision: Option<usize>) -> String {
+    if let Some(precision) = precision {
+        return format!("{:.*}", precision, value);
+    }
+
+    let decimals = match value.abs() {
+        v if v >= 100.0 => 0,
+        v if v >= 10.0 => 1,
+        _ => 2,
+    };
+
+    let formatted = format!("{:.*}", decimals, value);
+    if formatted.contains('.') {
+        formatted
+            .trim_end_matches('0')
+            .trim_end_matches('.')
+            .to_string()
+    } else {
+        formatted
+    }
+}
+
+/// Splits the unit-annotated value into the number and the unit symbol.
+///
+/// # Arguments
+/// - `s` - The value with unit, e.g. "1.2 mg".
+fn split_value_and_unit(s: &str) -> Result<(f32, &str), ParseUnitError> {
+    let s = s.trim();
+
+    // the unit is the trailing run of letters, which keeps exponents like "1e-3 g" intact
+    let unit_start = s
+        .char_indices()
+        .rev()
+        .take_while(|(_, c)| c.is_alphabetic())
+        .last()
+        .map(|(index, _)| index)
+        .ok_or_else(|| ParseUnitError::MissingUnit(s.to_string()))?;
+    let (value, unit) = s.split_at(unit_start);
+
+    let value: f32 = value
+        .trim()
+        .parse()
+        .map_err(|_| ParseUnitError::InvalidValue(s.to_string()))?;
+    if !value.is_finite() || value < 0.0 {
+        return Err(ParseUnitError::InvalidValue(s.to_string()));
+    }
+
+    Ok((value, unit))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Asserts that the values are equal up to the precision of f32.
+    fn assert_close(lhs: f32, rhs: f32) {
+        assert!(
+            (lhs - rhs).abs() <= rhs.abs() * 1e-6,
+            "{} is not close to {}",
+            lhs,
+            rhs
+        );
+    }
+
+    #[test]
+    fn test_parse_weight() {
+        assert_close("1.2 mg".parse::<Weight>().unwrap().milligram(), 1.2);
+        assert_close("450 µg".parse::<Weight>().unwrap().microgram(), 450.0);
+        assert_close("450μg".parse::<Weight>().unwrap().microgram(), 450.0);
+        assert_close("450 mcg".parse::<Weight>().unwrap().microgram(), 450.0);
+        assert_close("12g".parse::<Weight>().unwrap().gram(), 12.0);
+        assert_close(" 0.5 KG ".parse::<Weight>().unwrap().gram(), 500.0);
+        assert_close("1e-3 g".parse::<Weight>().unwrap().milligram(), 1.0);
+    }
+
+    #[test]
+    fn test_parse_volume() {
+        assert_close("0.33 l".parse::<Volume>().unwrap().millilitre(), 330.0);
+        assert_close("250ml".parse::<Volume>().unwrap().litre(), 0.25);
+        assert_close("75 cl".parse::<Volume>().unwrap().litre(), 0.75);
+        assert_close("1 L".parse::<Volume>().unwrap().litre(), 1.0);
+    }
+
+    #[test]
+    fn test_display_weight() {
+        assert_eq!(Weight::new_from_gram(0.012).to_string(), "12 mg");
+        assert_eq!(Weight::new_from_microgram(450.0).to_string(), "450 µg");
+        assert_eq!(Weight::new_from_milligram(1.25).to_string(), "1.25 mg");
+        assert_eq!(Weight::new_from_gram(12.34).to_string(), "12.3 g");
+        assert_eq!(Weight::new_from_gram(1500.0).to_string(), "1.5 kg");
+        assert_eq!(Weight::new_from_gram(0.0).to_string(), "0 g");
+        assert_eq!(format!("{:.1}", Weight::new_from_gram(2.0)), "2.0 g");
+
+        let weight = Weight::new_from_milligram(1.2);
+        assert_eq!(weight.format_as(WeightUnit::Microgram), "1200 µg");
+        assert_eq!(weight.format_as(WeightUnit::Gram), "0 g");
+
+        // formatted weights can be parsed again
+        let parsed: Weight = weight.to_string().parse().unwrap();
+        assert_close(parsed.milligram(), 1.2);
+    }
+
+    #[test]
+    fn test_display_volume() {
+        assert_eq!(Volume::new_from_millilitre(330.0).to_string(), "330 ml");
+        assert_eq!(Volume::new_from_millilitre(1500.0).to_string(), "1.5 l");
+        assert_eq!(
+            Volume::new_from_millilitre(330.0).format_as(VolumeUnit::Centilitre),
+            "33 cl"
+        );
+        assert_eq!(
+            format!("{:.2}", Volume::new_from_millilitre(250.0)),
+            "250.00 ml"
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            "12".parse::<Weight>(),
+            Err(ParseUnitError::MissingUnit("12".to_string()))
+        );
+        assert_eq!(
+            "12 ml".parse::<Weight>(),
+            Err(ParseUnitError::UnknownUnit("ml".to_string()))
+        );
+        assert_eq!(
+            "12 g".parse::<Volume>(),
+            Err(ParseUnitError::UnknownUnit("g".to_string()))
+        );
+        assert_eq!(
+            "12 gram".parse::<Weight>(),
+            Err(ParseUnitError::UnknownUnit("gram".to_string()))
+        );
+        assert_eq!(
+            "mg".parse::<Weight>(),
+            Err(ParseUnitError::InvalidValue("mg".to_string()))
+        );
+        assert_eq!(
+            "1,2 mg".parse::<Weight>(),
+            Err(ParseUnitError::InvalidValue("1,2 mg".to_string()))
+        );
+        assert_eq!(
+            "-1 mg".parse::<Weight>(),
+            Err(ParseUnitError::InvalidValue("-1 mg".to_string()))
+        );
+        assert_eq!(
+            "inf mg".parse::<Weight>(),
+            Err(ParseUnitError::InvalidValue("inf mg".to_string()))
+        );
+    }
+}
@@ -0,0 +1,517 @@
+use chrono::{DateTime, Utc};
+use dockertest::{
+    DockerTest, Image, LogAction, LogOptions, LogPolicy, LogSource, TestBodySpecification,
+};
+use log::info;
+use product_db::{
+    ArchiveQuery, BoxedDataBackend, DataBackend, HealthStatus, ImageStorageUsage, MissingProduct,
+    MissingProductQuery, MongoBackend, MongoConfig, PriceObservation, ProductAvailability,
+    ProductDataIssue, ProductDescription, ProductImage, ProductQuery, ProductRequest, QuantityType,
+    ReadBackend, Recipe, RequestOutcome, RequestStatus, SearchFilter, Secret, Sorting,
+    SortingField, SortingOrder, Store,
+};
+
+/// Truncates the given datetime to milliseconds, which is the precision of BSON.
+///
+/// # Arguments
+/// - `d` - The datetime to truncate.
+fn truncate_datetime(d: DateTime<Utc>) -> DateTime<Utc> {
+    DateTime::from_timestamp_millis(d.timestamp_millis()).unwrap()
+}
+
+/// Initialize the logger for the tests.
+fn init_logger() {
+    match env_logger::builder()
+        .is_test(true)
+        .filter_level(log::LevelFilter::Debug)
+        .try_init()
+    {
+        Ok(_) => (),
+        Err(_) => println!("Logger already initialized"),
+    }
+}
+
+/// Loads the product data from the test_data/products.json file.
+fn load_products() -> Vec<ProductDescription> {
+    let product_data = include_str!("../../test_data/products.json");
+    serde_json::from_str(product_data).unwrap()
+}
+
+/// Returns a query for the first page of the products with the given filter.
+///
+/// # Arguments
+/// - `filter` - The filter of the query.
+fn product_query(filter: SearchFilter) -> ProductQuery {
+    ProductQuery {
+        offset: 0,
+        limit: 100,
+        filter,
+        sorting: None,
+        market: None,
+        producer: None,
+        quantity_type: None,
+        labels: None,
+        max_nova_group: None,
+        min_eco_score_grade: None,
+        exclude_allergens: Vec::new(),
+        data_issues: Vec::new(),
+        fields: None,
+        request_status: None,
+        cursor: None,
+    }
+}
+
+/// Runs the product tests with the given backend.
+///
+/// # Arguments
+/// - `backend` - The backend to run the tests with.
+async fn product_tests<B: DataBackend>(backend: &B) {
+    let products = load_products();
+    for product in products.iter() {
+        assert!(backend.new_product(product).await.unwrap());
+    }
+
+    // products are only added once
+    assert!(!backend.new_product(&products[0]).await.unwrap());
+
+    // the products are stored with their images
+    for product in products.iter() {
+        let stored = backend
+            .get_product(&product.info.id, true)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            stored,
+            ProductDescription {
+                full_image: None,
+                ..product.clone()
+            }
+        );
+        assert_eq!(
+            backend.get_product_image(&product.info.id).await.unwrap(),
+            product.full_image
+        );
+    }
+
+    // the full text search finds the product by its name
+    let product = &products[0];
+    let word = product.info.name.split_whitespace().next().unwrap();
+    let mut query = product_query(SearchFilter::Search(word.to_string()));
+    query.sorting = Some(Sorting {
+        order: SortingOrder::Descending,
+        field: SortingField::Similarity,
+    });
+    let found = backend.query_scored_products(&query, false).await.unwrap();
+    assert!(found.iter().any(|(p, _)| p.info.id == product.info.id));
+    assert!(found.iter().all(|(_, score)| *score > 0.0 && *score <= 1.0));
+    assert!(found.windows(2).all(|w| w[0].1 >= w[1].1));
+
+    // the products are sorted by their name and counted
+    let mut query = product_query(SearchFilter::NoFilter);
+    query.sorting = Some(Sorting {
+        order: SortingOrder::Ascending,
+        field: SortingField::Name,
+    });
+    let sorted = backend.query_products(&query, false).await.unwrap();
+    assert_eq!(sorted.len(), products.len());
+    assert!(sorted.windows(2).all(|w| w[0].info.name <= w[1].info.name));
+    assert_eq!(
+        backend.count_products(&query).await.unwrap(),
+        products.len() as u64
+    );
+
+    // the pages do not overlap
+    query.limit = 2;
+    let first = backend.query_products(&query, false).await.unwrap();
+    query.offset = 2;
+    let second = backend.query_products(&query, false).await.unwrap();
+    assert_eq!(first, sorted[..2]);
+    assert_eq!(second, sorted[2..4]);
+
+    // the products with data issues
+    let mut query = product_query(SearchFilter::NoFilter);
+    query.data_issues = vec![ProductDataIssue::MissingImage];
+    let without_image = backend.query_products(&query, false).await.unwrap();
+    assert_eq!(
+        without_image.len(),
+        products
+            .iter()
+            .filter(|p| p.preview.is_none() && p.full_image.is_none())
+            .count()
+    );
+
+    // the producer is matched case-insensitively against the whole producer
+    let mut query = product_query(SearchFilter::NoFilter);
+    query.producer = Some("ALPRO".to_string());
+    query.quantity_type = Some(QuantityType::Volume);
+    let alpro = backend.query_products(&query, false).await.unwrap();
+    assert_eq!(alpro.len(), 1);
+    assert_eq!(alpro[0].info.id, "5411188124689");
+    query.producer = Some("*brüggen*".to_string());
+    query.quantity_type = None;
+    assert_eq!(backend.count_products(&query).await.unwrap(), 1);
+    query.producer = Some("alp".to_string());
+    assert_eq!(backend.count_products(&query).await.unwrap(), 0);
+
+    // the same product id is added once per market and updated per market
+    let mut market_product = product.clone();
+    market_product.info.market = Some("DE".to_string());
+    market_product.info.name = "Market specific".to_string();
+    assert!(backend.new_product(&market_product).await.unwrap());
+    assert!(!backend.new_product(&market_product).await.unwrap());
+    market_product.info.quantity_type = QuantityType::Volume;
+    assert!(backend.update_product(&market_product).await.unwrap());
+    let stored = backend
+        .get_product(&product.info.id, false)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(stored.info, product.info);
+    let mut query = product_query(SearchFilter::ProductID(product.info.id.clone()));
+    query.market = Some("DE".to_string());
+    let found = backend.query_products(&query, false).await.unwrap();
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].info, market_product.info);
+
+    // the products of all markets are deleted
+    backend.delete_product(&product.info.id).await.unwrap();
+    assert_eq!(backend.count_products(&query).await.unwrap(), 0);
+    assert_eq!(
+        backend.get_product(&product.info.id, false).await.unwrap(),
+        None
+    );
+    assert!(backend.new_product(product).await.unwrap());
+}
+
+/// Runs the missing product, product request and archive tests with the given backend.
+///
+/// # Arguments
+/// - `backend` - The backend to run the tests with.
+async fn request_tests<B: DataBackend>(backend: &B) {
+    let products = load_products();
+
+    // the missing products are numbered consecutively
+    let missing_products: Vec<MissingProduct> = products
+        .iter()
+        .map(|p| MissingProduct {
+            product_id: p.info.id.clone(),
+            date: truncate_datetime(Utc::now()),
+            store_id: None,
+        })
+        .collect();
+    let ids = backend
+        .report_missing_products(missing_products.clone())
+        .await
+        .unwrap();
+    assert!(ids.windows(2).all(|w| w[0] + 1 == w[1]));
+    assert_eq!(
+        backend.get_missing_product(ids[0]).await.unwrap(),
+        Some(missing_products[0].clone())
+    );
+    let query = MissingProductQuery {
+        offset: 0,
+        limit: 100,
+        product_id: None,
+        order: SortingOrder::Ascending,
+    };
+    let reported = backend.query_missing_products(&query).await.unwrap();
+    assert_eq!(reported.len(), missing_products.len());
+    assert_eq!(
+        backend.count_missing_products(&query).await.unwrap(),
+        missing_products.len() as u64
+    );
+
+    // each product has been reported once
+    let summaries = backend.summarize_missing_products(0, 100).await.unwrap();
+    assert_eq!(summaries.len(), missing_products.len());
+    assert!(summaries
+        .iter()
+        .all(|s| s.reports == 1 && s.first_reported == s.last_reported));
+    backend
+        .delete_reported_missing_product(ids[0])
+        .await
+        .unwrap();
+    assert_eq!(backend.get_missing_product(ids[0]).await.unwrap(), None);
+
+    // the requests are stored with their images
+    let request = ProductRequest {
+        product_description: products[1].clone(),
+        date: truncate_datetime(Utc::now()),
+        status: RequestStatus::Pending,
+        review_comment: None,
+    };
+    let request_id = backend.request_new_product(&request).await.unwrap();
+    let stored = backend
+        .get_product_request(request_id, true)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(stored.date, request.date);
+    assert_eq!(
+        stored.product_description,
+        ProductDescription {
+            full_image: None,
+            ..products[1].clone()
+        }
+    );
+    assert_eq!(
+        backend.get_product_request_image(request_id).await.unwrap(),
+        products[1].full_image
+    );
+
+    // the preview is replaced, while the full image is kept
+    let preview = ProductImage {
+        content_type: "image/webp".to_string(),
+        data: vec![1, 2, 3, 4],
+    };
+    assert!(backend
+        .update_product_request_preview(request_id, &preview)
+        .await
+        .unwrap());
+    let stored = backend
+        .get_product_request(request_id, true)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(stored.product_description.preview, Some(preview));
+    assert_eq!(
+        backend.get_product_request_image(request_id).await.unwrap(),
+        products[1].full_image
+    );
+
+    // the replaced preview is no longer part of the image storage
+    let usage = backend.image_storage_usage().await.unwrap();
+    assert!(usage.contains(&ImageStorageUsage {
+        content_type: "image/webp".to_string(),
+        images: 1,
+        bytes: 4,
+    }));
+
+    // the requests are sorted by their reported date
+    let mut query = product_query(SearchFilter::NoFilter);
+    query.sorting = Some(Sorting {
+        order: SortingOrder::Descending,
+        field: SortingField::ReportedDate,
+    });
+    let requests = backend.query_product_requests(&query, false).await.unwrap();
+    assert_eq!(requests[0].0, request_id);
+
+    // the rejected requests are filtered by their review status
+    assert!(backend
+        .reject_product_request(request_id, Some("Blurry image"))
+        .await
+        .unwrap());
+    query.request_status = Some(RequestStatus::Rejected);
+    let requests = backend.query_product_requests(&query, false).await.unwrap();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(
+        requests[0].1.review_comment.as_deref(),
+        Some("Blurry image")
+    );
+    query.request_status = Some(RequestStatus::Pending);
+    assert_eq!(backend.count_product_requests(&query).await.unwrap(), 0);
+
+    // the request of an added product is archived as approved
+    let archived = backend.archive_approved_product_requests().await.unwrap();
+    assert_eq!(archived, 1);
+    assert_eq!(
+        backend
+            .get_product_request(request_id, false)
+            .await
+            .unwrap(),
+        None
+    );
+    let archive = backend
+        .query_archived_product_requests(&ArchiveQuery {
+            offset: 0,
+            limit: 10,
+            product_id: Some(products[1].info.id.clone()),
+            outcome: Some(RequestOutcome::Approved),
+            order: SortingOrder::Ascending,
+        })
+        .await
+        .unwrap();
+    assert_eq!(archive.len(), 1);
+    assert_eq!(archive[0].id, request_id);
+    assert_eq!(archive[0].date, request.date);
+    assert_eq!(archive[0].review_comment.as_deref(), Some("Blurry image"));
+}
+
+/// Runs the store, price and recipe tests with the given backend.
+///
+/// # Arguments
+/// - `backend` - The backend to run the tests with.
+async fn store_tests<B: DataBackend>(backend: &B) {
+    let products = load_products();
+
+    let store = Store {
+        name: "Market Street".to_string(),
+        chain: Some("FreshMart".to_string()),
+    };
+    let store_id = backend.new_store(&store).await.unwrap();
+    assert_eq!(backend.get_store(store_id).await.unwrap(), Some(store));
+
+    // the last observation replaces the previous one
+    let observe = |available: bool| ProductAvailability {
+        store_id,
+        product_id: products[0].info.id.clone(),
+        available,
+        date: truncate_datetime(Utc::now()),
+    };
+    assert!(backend.report_availability(&observe(true)).await.unwrap());
+    let missing = observe(false);
+    assert!(backend.report_availability(&missing).await.unwrap());
+    assert_eq!(
+        backend
+            .get_product_availability(&products[0].info.id)
+            .await
+            .unwrap(),
+        vec![missing]
+    );
+
+    // unknown stores are rejected
+    let mut unknown = observe(true);
+    unknown.store_id = store_id + 1000;
+    assert!(!backend.report_availability(&unknown).await.unwrap());
+
+    // the availability is deleted with the store
+    backend.delete_store(store_id).await.unwrap();
+    assert_eq!(backend.get_store(store_id).await.unwrap(), None);
+    assert!(backend
+        .get_product_availability(&products[0].info.id)
+        .await
+        .unwrap()
+        .is_empty());
+
+    // the most recent of the cheapest prices wins
+    let price = |price: i64, day: u32| PriceObservation {
+        product_id: products[0].info.id.clone(),
+        store: "Supermarket".to_string(),
+        price,
+        currency: "EUR".to_string(),
+        observed_at: DateTime::parse_from_rfc3339(&format!("2024-03-{:02}T10:00:00Z", day))
+            .unwrap()
+            .to_utc(),
+    };
+    for observation in [price(199, 1), price(249, 2), price(199, 3)] {
+        backend.submit_price(&observation).await.unwrap();
+    }
+    assert_eq!(
+        backend
+            .cheapest_price(&products[0].info.id, "EUR")
+            .await
+            .unwrap(),
+        Some(price(199, 3))
+    );
+
+    // the recipe is replaced including its image
+    let recipe = Recipe {
+        name: "Pancakes".to_string(),
+        servings: 4,
+        ingredients: Vec::new(),
+        image: products.iter().find_map(|p| p.full_image.clone()),
+    };
+    let recipe_id = backend.new_recipe(&recipe).await.unwrap();
+    assert_eq!(
+        backend.get_recipe(recipe_id).await.unwrap(),
+        Some(recipe.clone())
+    );
+    let updated = Recipe {
+        servings: 2,
+        image: None,
+        ..recipe
+    };
+    assert!(backend.update_recipe(recipe_id, &updated).await.unwrap());
+    assert_eq!(backend.get_recipe(recipe_id).await.unwrap(), Some(updated));
+    backend.delete_recipe(recipe_id).await.unwrap();
+    assert_eq!(backend.get_recipe(recipe_id).await.unwrap(), None);
+}
+
+/// Runs all tests against a fresh database of the given MongoDB deployment.
+///
+/// # Arguments
+/// - `uri` - The connection string of the MongoDB deployment.
+async fn backend_tests(uri: String) {
+    let config = MongoConfig {
+        uri,
+        user: None,
+        password: Secret::default(),
+        dbname: format!("product_db_test_{}", Utc::now().timestamp_millis()),
+        max_connections: Some(5),
+    };
+
+    info!("Creating MongoBackend instance...");
+    let backend = MongoBackend::new(config.clone()).await.unwrap();
+    info!("Creating MongoBackend instance...DONE");
+
+    let report = backend.health_check().await;
+    assert_eq!(report.status, HealthStatus::Healthy);
+    backend.ping().await.unwrap();
+
+    info!("Running product tests...");
+    product_tests(&backend).await;
+    info!("Running product tests...SUCCESS");
+
+    info!("Running request tests...");
+    request_tests(&backend).await;
+    info!("Running request tests...SUCCESS");
+
+    info!("Running store tests...");
+    store_tests(&backend).await;
+    info!("Running store tests...SUCCESS");
+
+    // the boxed backend keeps working after reconnecting
+    let backend = BoxedDataBackend::new(MongoBackend::new(config.clone()).await.unwrap());
+    ReadBackend::reconnect(&backend, config.password)
+        .await
+        .unwrap();
+    assert_eq!(
+        ReadBackend::health_check(&backend).await.status,
+        HealthStatus::Healthy
+    );
+}
+
+#[tokio::test]
+async fn test_mongodb_backend() {
+    init_logger();
+
+    // check if the TEST_MONGODB_URL environment variable is set
+    if let Ok(uri) = std::env::var("TEST_MONGODB_URL") {
+        info!("TEST_MONGODB_URL has been provided, skipping docker test and using the provided database");
+        backend_tests(uri).await;
+        return;
+    }
+
+    // Define our test instance
+    let mut test = DockerTest::new();
+
+    let image: Image = Image::with_repository("mongo")
+        .pull_policy(dockertest::PullPolicy::IfNotPresent)
+        .source(dockertest::Source::DockerHub)
+        .tag("7");
+
+    let mongo = TestBodySpecification::with_image(image)
+        .set_publish_all_ports(true)
+        .set_log_options(Some(LogOptions {
+            action: LogAction::ForwardToStdOut,
+            policy: LogPolicy::OnError,
+            source: LogSource::Both,
+        }));
+    test.provide_container(mongo);
+
+    test.run_async(|ops| async move {
+        let container = ops.handle("mongo");
+
+        // wait about 5 seconds for mongo to start
+        info!("Waiting for mongo to start...");
+        tokio::time::sleep(tokio::time::Duration::from_secs(5)).await;
+        info!("Waiting for mongo to start...DONE");
+
+        let (ip, port) = container.host_port(27017).unwrap();
+        info!("mongo running at {}:{}", ip, port);
+
+        backend_tests(format!("mongodb://localhost:{}", port)).await;
+    })
+    .await;
+}
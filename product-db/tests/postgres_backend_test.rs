@@ -1,15 +1,24 @@
 use std::{collections::HashSet, env::temp_dir, str::FromStr};
 
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Months, Utc};
 use dockertest::{
     DockerTest, Image, LogAction, LogOptions, LogPolicy, LogSource, TestBodySpecification,
 };
 use log::info;
 use product_db::{
-    DBId, DataBackend, MissingProduct, MissingProductQuery, Nutrients, PostgresBackend,
-    PostgresConfig, ProductDescription, ProductID, ProductImage, ProductQuery, ProductRequest,
-    SearchFilter, Secret, Sorting, SortingField, SortingOrder, Weight,
+    AdditionalImage, Allergen, ArchiveQuery, BoxedDataBackend, DBId, DataBackend, DietaryLabels,
+    EcoScoreGrade, Error, FilesystemImageStoreConfig, HealthStatus, ImageRole, ImageStorageUsage,
+    ImageStoreConfig, MirroredBackend, MissingProduct, MissingProductQuery, Nutrients, Portion,
+    PostgresBackend, PostgresConfig, PriceObservation, PriceQuery, ProductAvailability,
+    ProductCursor, ProductDataIssue, ProductDescription, ProductID, ProductImage, ProductQuery,
+    ProductRequest, QuantityType, ReadBackend, Recipe, RequestOutcome, RequestStatus, SearchFilter,
+    SearchTermMode, Secret, Sorting, SortingField, SortingOrder, Store, StoreQuery, Weight,
+    WriteBackend, MIGRATION_LOCK_KEY, SCHEMA_VERSION,
 };
+use sqlx::Connection;
+
+#[cfg(feature = "generator")]
+use product_db::{generate_products, ProductGenerator};
 
 /// Truncates the given datetime to seconds.
 /// This is being done for comparison reasons.
@@ -169,6 +178,8 @@ async fn simple_ops<B: DataBackend>(backend: &B) {
         .request_new_product(&ProductRequest {
             product_description: products[1].clone(),
             date: Utc::now(),
+            status: RequestStatus::Pending,
+            review_comment: None,
         })
         .await
         .unwrap();
@@ -293,6 +304,35 @@ async fn missing_product_tests<B: DataBackend>(backend: &B) {
     );
     assert!(foobar_products.iter().all(|p| p.1.product_id == "foobar"));
 
+    // the count ignores the offset and the limit
+    let count = backend
+        .count_missing_products(&MissingProductQuery {
+            limit: 1,
+            offset: 1,
+            product_id: Some("foobar".to_string()),
+            order: SortingOrder::Descending,
+        })
+        .await
+        .unwrap();
+    assert_eq!(count, 3);
+
+    // the summary groups the reports by the product id, most reported first
+    let summaries = backend.summarize_missing_products(0, 100).await.unwrap();
+    assert!(summaries.windows(2).all(|w| w[0].reports >= w[1].reports));
+    let foobar = summaries.iter().find(|s| s.product_id == "foobar").unwrap();
+    let foobar_dates: Vec<DateTime<Utc>> = products_to_report
+        .iter()
+        .filter(|p| p.product_id == "foobar")
+        .map(|p| p.date)
+        .collect();
+    assert_eq!(foobar.reports, 3);
+    assert_eq!(foobar.first_reported, *foobar_dates.iter().min().unwrap());
+    assert_eq!(foobar.last_reported, *foobar_dates.iter().max().unwrap());
+    assert_eq!(
+        backend.summarize_missing_products(0, 1).await.unwrap(),
+        summaries[..1].to_vec()
+    );
+
     // delete the first reported missing product
     backend
         .delete_reported_missing_product(ids[3])
@@ -332,6 +372,32 @@ async fn missing_product_tests<B: DataBackend>(backend: &B) {
 
     assert_eq!(foobar_products.len(), 2);
     assert!(foobar_products.iter().all(|p| p.1.product_id == "foobar"));
+
+    // the photo of a report is replaced and deleted together with the report
+    let photo = |data| ProductImage {
+        content_type: "image/jpeg".to_string(),
+        data,
+    };
+    let id = foobar_products[0].0;
+    assert_eq!(backend.get_missing_product_photo(id).await.unwrap(), None);
+    assert!(backend
+        .set_missing_product_photo(id, &photo(vec![1, 2]))
+        .await
+        .unwrap());
+    assert!(backend
+        .set_missing_product_photo(id, &photo(vec![3, 4, 5]))
+        .await
+        .unwrap());
+    assert_eq!(
+        backend.get_missing_product_photo(id).await.unwrap(),
+        Some(photo(vec![3, 4, 5]))
+    );
+    backend.delete_reported_missing_product(id).await.unwrap();
+    assert_eq!(backend.get_missing_product_photo(id).await.unwrap(), None);
+    assert!(!backend
+        .set_missing_product_photo(id, &photo(vec![1, 2]))
+        .await
+        .unwrap());
 }
 
 /// Runs the product requests tests with the given backend.
@@ -348,6 +414,8 @@ async fn product_requests_tests<B: DataBackend>(backend: &B) {
         .map(|p| ProductRequest {
             product_description: p.clone(),
             date: Utc::now(),
+            status: RequestStatus::Pending,
+            review_comment: None,
         })
         .collect();
 
@@ -355,7 +423,7 @@ async fn product_requests_tests<B: DataBackend>(backend: &B) {
     let mut ids = Vec::new();
     let mut product_requests_with_ids = Vec::new();
     for product_request in product_requests.iter() {
-        let id = backend.request_new_product(&product_request).await.unwrap();
+        let id = backend.request_new_product(product_request).await.unwrap();
         info!("Requested product with id: {}", id);
 
         ids.push(id);
@@ -405,22 +473,59 @@ async fn product_requests_tests<B: DataBackend>(backend: &B) {
     );
 
     // now query the modified product request
-    let product_requests = backend
-        .query_product_requests(
+    let query = ProductQuery {
+        limit: 40,
+        offset: 0,
+        filter: SearchFilter::ProductID(
+            modified_product_request.product_description.info.id.clone(),
+        ),
+        sorting: None,
+        market: None,
+        producer: None,
+        quantity_type: None,
+        labels: None,
+        max_nova_group: None,
+        min_eco_score_grade: None,
+        exclude_allergens: Vec::new(),
+        data_issues: Vec::new(),
+        fields: None,
+        request_status: None,
+        cursor: None,
+    };
+    let product_requests = backend.query_product_requests(&query, false).await.unwrap();
+
+    assert_eq!(product_requests.len(), 2);
+
+    // the count applies the same filters, but ignores the offset and the limit
+    let count_query = ProductQuery {
+        offset: 1,
+        limit: 1,
+        ..query
+    };
+    assert_eq!(
+        backend.count_product_requests(&count_query).await.unwrap(),
+        2
+    );
+    let (counted, total_count) = backend
+        .query_counted_product_requests(&count_query, false)
+        .await
+        .unwrap();
+    assert_eq!(counted.len(), 1);
+    assert_eq!(total_count, 2);
+
+    // the total is also returned for the pages after the last request
+    let (counted, total_count) = backend
+        .query_counted_product_requests(
             &ProductQuery {
-                limit: 40,
-                offset: 0,
-                filter: SearchFilter::ProductID(
-                    modified_product_request.product_description.info.id.clone(),
-                ),
-                sorting: None,
+                offset: 5,
+                ..count_query.clone()
             },
             false,
         )
         .await
         .unwrap();
-
-    assert_eq!(product_requests.len(), 2);
+    assert!(counted.is_empty());
+    assert_eq!(total_count, 2);
     assert_eq!(product_requests[0].0, ids[0]);
     assert_eq!(product_requests[1].0, ids[ids.len() - 1]);
 
@@ -490,6 +595,17 @@ async fn query_product_requests_tests<B: DataBackend>(
                     offset: 0,
                     filter: SearchFilter::NoFilter,
                     sorting: None,
+                    market: None,
+                    producer: None,
+                    quantity_type: None,
+                    labels: None,
+                    max_nova_group: None,
+                    min_eco_score_grade: None,
+                    exclude_allergens: Vec::new(),
+                    data_issues: Vec::new(),
+                    fields: None,
+                    request_status: None,
+                    cursor: None,
                 },
                 with_preview,
             )
@@ -560,6 +676,17 @@ async fn query_product_requests_tests<B: DataBackend>(
                         offset: *offset,
                         filter: SearchFilter::NoFilter,
                         sorting: *sorting,
+                        market: None,
+                        producer: None,
+                        quantity_type: None,
+                        labels: None,
+                        max_nova_group: None,
+                        min_eco_score_grade: None,
+                        exclude_allergens: Vec::new(),
+                        data_issues: Vec::new(),
+                        fields: None,
+                        request_status: None,
+                        cursor: None,
                     },
                     with_preview,
                 )
@@ -633,6 +760,17 @@ async fn query_product_requests_tests<B: DataBackend>(
                         order: SortingOrder::Descending,
                         field: SortingField::Similarity,
                     }),
+                    market: None,
+                    producer: None,
+                    quantity_type: None,
+                    labels: None,
+                    max_nova_group: None,
+                    min_eco_score_grade: None,
+                    exclude_allergens: Vec::new(),
+                    data_issues: Vec::new(),
+                    fields: None,
+                    request_status: None,
+                    cursor: None,
                 },
                 with_preview,
             )
@@ -675,6 +813,10 @@ fn compare_product_info(lhs: &ProductDescription, rhs: &ProductDescription) {
     assert_eq!(lhs.info.producer, rhs.info.producer);
     assert_eq!(lhs.info.quantity_type, rhs.info.quantity_type);
     assert_eq!(lhs.info.volume_weight_ratio, rhs.info.volume_weight_ratio);
+    assert_eq!(lhs.info.market, rhs.info.market);
+    assert_eq!(lhs.info.labels, rhs.info.labels);
+    assert_eq!(lhs.info.nova_group, rhs.info.nova_group);
+    assert_eq!(lhs.info.allergens, rhs.info.allergens);
 }
 
 /// Compares the product requests of two products.
@@ -715,6 +857,8 @@ fn compare_product_description(
 ) {
     compare_product_info(lhs, rhs);
     check_compare_nutrients(&lhs.nutrients, &rhs.nutrients);
+    assert_eq!(lhs.packaging, rhs.packaging);
+    assert_eq!(lhs.eco_score, rhs.eco_score);
 
     if check_preview {
         assert_eq!(lhs.preview, rhs.preview);
@@ -762,6 +906,36 @@ async fn product_tests<B: DataBackend>(backend: &B) {
         }
     }
 
+    // get all products in one batch, unknown ids are skipped
+    let mut ids: Vec<ProductID> = products.iter().map(|p| p.info.id.clone()).collect();
+    ids.push("unknown-product".to_string());
+    let out_products = backend.get_products(&ids).await.unwrap();
+    assert_eq!(out_products.len(), products.len());
+    for in_product in products.iter() {
+        let out_product = find_product_by_id(&out_products, in_product.info.id.clone()).unwrap();
+        compare_product_description(out_product, in_product, false);
+    }
+    assert!(backend.get_products(&[]).await.unwrap().is_empty());
+
+    // GTINs are found regardless of leading zeros, e.g. the EAN-8 42094722 as GTIN-14
+    for (lookup_id, id) in [
+        ("04061458061490", "4061458061490"),
+        ("00000042094722", "42094722"),
+        ("042094722", "42094722"),
+    ] {
+        let out_product = backend
+            .get_product(&lookup_id.to_string(), false)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(out_product.info.id, id);
+    }
+    assert!(backend
+        .get_product(&"14061458061490".to_string(), false)
+        .await
+        .unwrap()
+        .is_none());
+
     // execute the querying products tests
     query_products_tests(backend, products.as_slice()).await;
 
@@ -847,6 +1021,17 @@ async fn query_products_tests<B: DataBackend>(backend: &B, products: &[ProductDe
                     offset: 0,
                     filter: SearchFilter::NoFilter,
                     sorting: None,
+                    market: None,
+                    producer: None,
+                    quantity_type: None,
+                    labels: None,
+                    max_nova_group: None,
+                    min_eco_score_grade: None,
+                    exclude_allergens: Vec::new(),
+                    data_issues: Vec::new(),
+                    fields: None,
+                    request_status: None,
+                    cursor: None,
                 },
                 with_preview,
             )
@@ -898,6 +1083,17 @@ async fn query_products_tests<B: DataBackend>(backend: &B, products: &[ProductDe
                         offset: *offset,
                         filter: SearchFilter::NoFilter,
                         sorting: *sorting,
+                        market: None,
+                        producer: None,
+                        quantity_type: None,
+                        labels: None,
+                        max_nova_group: None,
+                        min_eco_score_grade: None,
+                        exclude_allergens: Vec::new(),
+                        data_issues: Vec::new(),
+                        fields: None,
+                        request_status: None,
+                        cursor: None,
                     },
                     with_preview,
                 )
@@ -955,6 +1151,17 @@ async fn query_products_tests<B: DataBackend>(backend: &B, products: &[ProductDe
                         order: SortingOrder::Descending,
                         field: SortingField::Similarity,
                     }),
+                    market: None,
+                    producer: None,
+                    quantity_type: None,
+                    labels: None,
+                    max_nova_group: None,
+                    min_eco_score_grade: None,
+                    exclude_allergens: Vec::new(),
+                    data_issues: Vec::new(),
+                    fields: None,
+                    request_status: None,
+                    cursor: None,
                 },
                 with_preview,
             )
@@ -975,122 +1182,2884 @@ async fn query_products_tests<B: DataBackend>(backend: &B, products: &[ProductDe
                 backend.get_product_image(&ret[0].info.id).await.unwrap();
             assert_eq!(full_image, ret[1].full_image);
         }
-    }
-
-    info!("Querying products tests...SUCCESS");
-}
-
-/// Runs the backend tests with the given backend.
-///
-/// # Arguments
-/// - `backend` - The backend to run the tests with.
-async fn backend_tests<B: DataBackend>(backend: B) {
-    info!("Do some operations with the backend...");
-    simple_ops(&backend).await;
-    info!("Do some operations with the backend...DONE");
 
-    info!("Running backend tests...");
-    missing_product_tests(&backend).await;
-    info!("Running backend tests...SUCCESS");
+        // the same products come with their similarity to the search string
+        let mut query = ProductQuery {
+            offset: 0,
+            limit: 5,
+            filter: SearchFilter::Search("Alpro".to_string()),
+            sorting: Some(Sorting {
+                order: SortingOrder::Descending,
+                field: SortingField::Similarity,
+            }),
+            market: None,
+            producer: None,
+            quantity_type: None,
+            labels: None,
+            max_nova_group: None,
+            min_eco_score_grade: None,
+            exclude_allergens: Vec::new(),
+            data_issues: Vec::new(),
+            fields: None,
+            request_status: None,
+            cursor: None,
+        };
+        let scored = backend
+            .query_scored_products(&query, with_preview)
+            .await
+            .unwrap();
+        assert_eq!(scored.len(), 2);
+        assert_eq!(scored[0].0, ret[0]);
+        assert_eq!(scored[1].0, ret[1]);
+        assert!(scored[0].1 >= scored[1].1);
+        assert!(scored[1].1 > 0.0 && scored[0].1 <= 1.0);
+
+        // the scores require a search string
+        query.filter = SearchFilter::NoFilter;
+        assert!(matches!(
+            backend.query_scored_products(&query, with_preview).await,
+            Err(Error::InvalidSortingError(SortingField::Similarity))
+        ));
+    }
 
-    info!("Running product requests tests...");
-    product_requests_tests(&backend).await;
-    info!("Running product requests tests...SUCCESS");
+    // filter by the dietary labels, unknown labels are not matched
+    let label_filters = [
+        DietaryLabels {
+            vegan: Some(true),
+            ..Default::default()
+        },
+        DietaryLabels {
+            vegan: Some(false),
+            vegetarian: Some(true),
+            ..Default::default()
+        },
+        DietaryLabels {
+            vegan: Some(true),
+            gluten_free: Some(false),
+            ..Default::default()
+        },
+    ];
+    for labels in label_filters {
+        let out_products = backend
+            .query_products(
+                &ProductQuery {
+                    offset: 0,
+                    limit: 40,
+                    filter: SearchFilter::NoFilter,
+                    sorting: None,
+                    market: None,
+                    producer: None,
+                    quantity_type: None,
+                    labels: Some(labels),
+                    max_nova_group: None,
+                    min_eco_score_grade: None,
+                    exclude_allergens: Vec::new(),
+                    data_issues: Vec::new(),
+                    fields: None,
+                    request_status: None,
+                    cursor: None,
+                },
+                false,
+            )
+            .await
+            .unwrap();
 
-    info!("Running product tests...");
-    product_tests(&backend).await;
-    info!("Running product tests...SUCCESS");
-}
+        let matches =
+            |expected: Option<bool>, actual: Option<bool>| expected.is_none() || expected == actual;
+        let expected: Vec<&ProductDescription> = products
+            .iter()
+            .filter(|p| {
+                let l = &p.info.labels;
+                matches(labels.vegan, l.vegan)
+                    && matches(labels.vegetarian, l.vegetarian)
+                    && matches(labels.gluten_free, l.gluten_free)
+            })
+            .collect();
+        assert!(!expected.is_empty());
+        assert_eq!(out_products.len(), expected.len());
+        for out_product in out_products.iter() {
+            let in_product = find_product_by_id(products, out_product.info.id.clone()).unwrap();
+            compare_product_description(out_product, in_product, false);
+            assert!(expected.contains(&in_product));
+        }
+    }
 
-#[tokio::test(flavor = "multi_thread")]
-async fn test_postgres_backend() {
-    init_logger();
+    // filter and sort by the NOVA group, products without NOVA group are excluded
+    for order in [SortingOrder::Ascending, SortingOrder::Descending] {
+        let out_products = backend
+            .query_products(
+                &ProductQuery {
+                    offset: 0,
+                    limit: 40,
+                    filter: SearchFilter::NoFilter,
+                    sorting: Some(Sorting {
+                        order,
+                        field: SortingField::NovaGroup,
+                    }),
+                    market: None,
+                    producer: None,
+                    quantity_type: None,
+                    labels: None,
+                    max_nova_group: Some(3),
+                    min_eco_score_grade: None,
+                    exclude_allergens: Vec::new(),
+                    data_issues: Vec::new(),
+                    fields: None,
+                    request_status: None,
+                    cursor: None,
+                },
+                false,
+            )
+            .await
+            .unwrap();
 
-    // check if the TEST_DATABASE_URL environment variable is set
-    if std::env::var("TEST_DATABASE_URL").is_ok() {
-        info!("TEST_DATABASE_URL has been provided, skipping docker test and using the provided database");
-        let options = PostgresConfig {
-            host: "localhost".to_string(),
-            port: 5432,
-            dbname: "postgres".to_string(),
-            user: "postgres".to_string(),
-            password: Secret::from_str("postgres").unwrap(),
-            max_connections: 5,
-        };
+        let expected = products
+            .iter()
+            .filter(|p| p.info.nova_group.is_some_and(|g| g <= 3))
+            .count();
+        assert_eq!(out_products.len(), expected);
 
-        let postgres_backend = PostgresBackend::new(options).await.unwrap();
+        let mut groups: Vec<i32> = out_products
+            .iter()
+            .map(|p| p.info.nova_group.unwrap())
+            .collect();
+        if order == SortingOrder::Descending {
+            groups.reverse();
+        }
+        assert!(groups.is_sorted());
+    }
 
-        info!("Running backend tests...");
-        backend_tests(postgres_backend).await;
-        info!("Running backend tests...SUCCESS");
+    // filter by the minimal Eco-Score grade, products without Eco-Score are excluded
+    for grade in [EcoScoreGrade::A, EcoScoreGrade::B, EcoScoreGrade::E] {
+        let out_products = backend
+            .query_products(
+                &ProductQuery {
+                    offset: 0,
+                    limit: 40,
+                    filter: SearchFilter::NoFilter,
+                    sorting: None,
+                    market: None,
+                    producer: None,
+                    quantity_type: None,
+                    labels: None,
+                    max_nova_group: None,
+                    min_eco_score_grade: Some(grade),
+                    exclude_allergens: Vec::new(),
+                    data_issues: Vec::new(),
+                    fields: None,
+                    request_status: None,
+                    cursor: None,
+                },
+                false,
+            )
+            .await
+            .unwrap();
 
-        return;
+        let expected: Vec<&ProductDescription> = products
+            .iter()
+            .filter(|p| p.eco_score.as_ref().is_some_and(|e| e.grade <= grade))
+            .collect();
+        assert!(!expected.is_empty());
+        assert_eq!(out_products.len(), expected.len());
+        for out_product in out_products.iter() {
+            assert!(out_product
+                .eco_score
+                .as_ref()
+                .is_some_and(|e| e.grade <= grade));
+        }
     }
 
-    // Define our test instance
-    let mut test = DockerTest::new();
-
-    let image: Image = Image::with_repository("postgres")
-        .pull_policy(dockertest::PullPolicy::IfNotPresent)
-        .source(dockertest::Source::DockerHub)
-        .tag("16");
+    // search, exclude allergens and filter by dietary labels in a single query
+    let out_products = backend
+        .query_products(
+            &ProductQuery {
+                offset: 0,
+                limit: 40,
+                filter: SearchFilter::Search("alpro".to_string()),
+                sorting: None,
+                market: None,
+                producer: None,
+                quantity_type: None,
+                labels: Some(DietaryLabels {
+                    vegan: Some(true),
+                    ..Default::default()
+                }),
+                max_nova_group: None,
+                min_eco_score_grade: None,
+                exclude_allergens: vec![Allergen::Nuts, Allergen::Gluten],
+                data_issues: Vec::new(),
+                fields: None,
+                request_status: None,
+                cursor: None,
+            },
+            false,
+        )
+        .await
+        .unwrap();
 
-    // define the postgres container
-    let mut postgres = TestBodySpecification::with_image(image).set_publish_all_ports(true);
+    let expected: Vec<&ProductDescription> = products
+        .iter()
+        .filter(|p| p.info.producer.as_deref() == Some("Alpro"))
+        .filter(|p| p.info.labels.vegan == Some(true))
+        .filter(|p| !p.info.allergens.contains(&Allergen::Gluten))
+        .collect();
+    assert_eq!(expected.len(), 1);
+    assert_eq!(out_products.len(), expected.len());
+    compare_product_description(&out_products[0], expected[0], false);
 
-    // set the environment variables for the postgres container
-    postgres
-        .modify_env("POSTGRES_USER", "postgres")
-        .modify_env("POSTGRES_PASSWORD", "password");
+    // products without declared allergens are included
+    let out_products = backend
+        .query_products(
+            &ProductQuery {
+                offset: 0,
+                limit: 40,
+                filter: SearchFilter::NoFilter,
+                sorting: None,
+                market: None,
+                producer: None,
+                quantity_type: None,
+                labels: None,
+                max_nova_group: None,
+                min_eco_score_grade: None,
+                exclude_allergens: vec![Allergen::Gluten, Allergen::Milk],
+                data_issues: Vec::new(),
+                fields: None,
+                request_status: None,
+                cursor: None,
+            },
+            false,
+        )
+        .await
+        .unwrap();
+    let expected = products
+        .iter()
+        .filter(|p| {
+            !p.info
+                .allergens
+                .iter()
+                .any(|a| [Allergen::Gluten, Allergen::Milk].contains(a))
+        })
+        .count();
+    assert_eq!(out_products.len(), expected);
+
+    // the count applies the same filters, but ignores the offset and the limit
+    let count = backend
+        .count_products(&ProductQuery {
+            offset: 1,
+            limit: 1,
+            filter: SearchFilter::NoFilter,
+            sorting: None,
+            market: None,
+            producer: None,
+            quantity_type: None,
+            labels: None,
+            max_nova_group: None,
+            min_eco_score_grade: None,
+            exclude_allergens: vec![Allergen::Gluten, Allergen::Milk],
+            data_issues: Vec::new(),
+            fields: None,
+            request_status: None,
+            cursor: None,
+        })
+        .await
+        .unwrap();
+    assert_eq!(count, expected as u64);
 
-    let mut postgres = postgres.set_log_options(Some(LogOptions {
-        action: LogAction::ForwardToStdOut,
-        policy: LogPolicy::Always,
-        source: LogSource::Both,
-    }));
+    let count = backend
+        .count_products(&ProductQuery {
+            offset: 0,
+            limit: 1,
+            filter: SearchFilter::Search("alpro".to_string()),
+            sorting: None,
+            market: None,
+            producer: None,
+            quantity_type: None,
+            labels: None,
+            max_nova_group: None,
+            min_eco_score_grade: None,
+            exclude_allergens: Vec::new(),
+            data_issues: Vec::new(),
+            fields: None,
+            request_status: None,
+            cursor: None,
+        })
+        .await
+        .unwrap();
+    assert_eq!(count, 2);
+
+    // filter by the producer and the quantity type instead of searching the name and producer
+    for (producer, quantity_type) in [
+        (Some("ALPRO"), None),
+        (Some("alpro"), Some(QuantityType::Volume)),
+        (Some("*brüggen*"), None),
+        (Some("alp"), None),
+        (None, Some(QuantityType::Weight)),
+    ] {
+        let query = ProductQuery {
+            offset: 0,
+            limit: 40,
+            filter: SearchFilter::NoFilter,
+            sorting: None,
+            market: None,
+            producer: producer.map(ToString::to_string),
+            quantity_type,
+            labels: None,
+            max_nova_group: None,
+            min_eco_score_grade: None,
+            exclude_allergens: Vec::new(),
+            data_issues: Vec::new(),
+            fields: None,
+            request_status: None,
+            cursor: None,
+        };
+        let out_products = backend.query_products(&query, false).await.unwrap();
 
-    // create a temporary file to store the database schema
-    let schema = include_str!("../../docker/db/init.sql");
-    let mut init_file = temp_dir();
-    init_file.push("init.sql");
-    std::fs::write(&init_file, schema).unwrap(); // write the schema to a file
+        let expected: HashSet<&str> = products
+            .iter()
+            .filter(|p| match producer {
+                Some("ALPRO") | Some("alpro") => p.info.producer.as_deref() == Some("Alpro"),
+                Some("*brüggen*") => p
+                    .info
+                    .producer
+                    .as_deref()
+                    .is_some_and(|producer| producer.contains("Brüggen")),
+                Some(_) => false,
+                None => true,
+            })
+            .filter(|p| quantity_type.is_none_or(|q| p.info.quantity_type == q))
+            .map(|p| p.info.id.as_str())
+            .collect();
+        let out_ids: HashSet<&str> = out_products.iter().map(|p| p.info.id.as_str()).collect();
+        assert_eq!(out_ids, expected, "{:?} {:?}", producer, quantity_type);
+        assert_eq!(
+            backend.count_products(&query).await.unwrap(),
+            expected.len() as u64
+        );
+    }
 
-    // bind the schema file to the postgres container
-    postgres.modify_bind_mount(
-        init_file.to_string_lossy(),
-        "/docker-entrypoint-initdb.d/init.sql",
+    // the data quality filters return the products with any of the issues
+    let query = ProductQuery {
+        offset: 0,
+        limit: 40,
+        filter: SearchFilter::NoFilter,
+        sorting: None,
+        market: None,
+        producer: None,
+        quantity_type: None,
+        labels: None,
+        max_nova_group: None,
+        min_eco_score_grade: None,
+        exclude_allergens: Vec::new(),
+        data_issues: vec![ProductDataIssue::MissingImage],
+        fields: None,
+        request_status: None,
+        cursor: None,
+    };
+    let out_products = backend.query_products(&query, false).await.unwrap();
+    let expected: Vec<&str> = products
+        .iter()
+        .filter(|p| p.preview.is_none() && p.full_image.is_none())
+        .map(|p| p.info.id.as_str())
+        .collect();
+    assert!(!expected.is_empty());
+    assert_eq!(
+        out_products
+            .iter()
+            .map(|p| p.info.id.as_str())
+            .collect::<Vec<_>>(),
+        expected
+    );
+    assert_eq!(
+        backend.count_products(&query).await.unwrap(),
+        expected.len() as u64
     );
 
-    // run the postgres container
-    test.provide_container(postgres);
+    let query = ProductQuery {
+        data_issues: vec![
+            ProductDataIssue::MissingMacroNutrients,
+            ProductDataIssue::MissingProducer,
+        ],
+        ..query
+    };
+    let out_products = backend.query_products(&query, false).await.unwrap();
+    let expected = products
+        .iter()
+        .filter(|p| {
+            p.nutrients.protein.is_none()
+                || p.nutrients.fat.is_none()
+                || p.nutrients.carbohydrates.is_none()
+                || p.info.producer.as_deref().unwrap_or_default().is_empty()
+        })
+        .count();
+    assert!(expected > 0);
+    assert_eq!(out_products.len(), expected);
 
-    test.run_async(|ops| async move {
-        let container = ops.handle("postgres");
+    info!("Querying products tests...SUCCESS");
+}
 
-        // wait about 5 seconds for postgres to start
-        info!("Waiting for postgres to start...");
-        tokio::time::sleep(tokio::time::Duration::from_secs(5)).await;
-        info!("Waiting for postgres to start...DONE");
+/// Runs the tests for replacing the preview images with the given backend.
+///
+/// # Arguments
+/// - `backend` - The backend to run the tests with.
+async fn preview_tests<B: DataBackend>(backend: &B) {
+    let mut product = load_products()[1].clone();
+    product.info.id = "preview-test".to_string();
+    let full_image = product.full_image.clone().unwrap();
+    let preview = ProductImage {
+        content_type: "image/webp".to_string(),
+        data: vec![1, 2, 3, 4],
+    };
+
+    // the preview of the product is replaced, while the full image is kept
+    assert!(backend.new_product(&product).await.unwrap());
+    assert!(backend
+        .update_product_preview(&product.info.id, &preview)
+        .await
+        .unwrap());
+    let stored = backend
+        .get_product(&product.info.id, true)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(stored.preview, Some(preview.clone()));
+    assert_eq!(
+        backend.get_product_image(&product.info.id).await.unwrap(),
+        Some(full_image.clone())
+    );
+    backend.delete_product(&product.info.id).await.unwrap();
+    assert!(!backend
+        .update_product_preview(&product.info.id, &preview)
+        .await
+        .unwrap());
+
+    // the preview of the product request is replaced
+    let request = ProductRequest {
+        product_description: product,
+        date: truncate_datetime(Utc::now()),
+        status: RequestStatus::Pending,
+        review_comment: None,
+    };
+    let id = backend.request_new_product(&request).await.unwrap();
+    assert!(backend
+        .update_product_request_preview(id, &preview)
+        .await
+        .unwrap());
+    let stored = backend
+        .get_product_request(id, true)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(stored.product_description.preview, Some(preview.clone()));
+    assert_eq!(
+        backend.get_product_request_image(id).await.unwrap(),
+        Some(full_image)
+    );
+    backend.delete_requested_product(id).await.unwrap();
+    assert!(!backend
+        .update_product_request_preview(id, &preview)
+        .await
+        .unwrap());
+}
 
-        let (ip, port) = container.host_port(5432).unwrap();
-        info!("postgres running at {}:{}", ip, port);
+/// Runs the tests for the additional images of products and product requests with the given
+/// backend.
+///
+/// # Arguments
+/// - `backend` - The backend to run the tests with.
+async fn additional_images_tests<B: DataBackend>(backend: &B) {
+    let mut product = load_products()[1].clone();
+    product.info.id = "additional-images-test".to_string();
+    let image = |role, data| AdditionalImage {
+        role,
+        image: ProductImage {
+            content_type: "image/jpeg".to_string(),
+            data,
+        },
+    };
+    let nutrition = image(ImageRole::Nutrition, vec![1, 2, 3]);
+    let ingredients = image(ImageRole::Ingredients, vec![4, 5]);
+
+    // the images of the product request are kept in the order they have been added
+    let request = ProductRequest {
+        product_description: product.clone(),
+        date: truncate_datetime(Utc::now()),
+        status: RequestStatus::Pending,
+        review_comment: None,
+    };
+    let id = backend.request_new_product(&request).await.unwrap();
+    assert_eq!(
+        backend.get_product_request_images(id).await.unwrap(),
+        Some(Vec::new())
+    );
+    assert!(backend
+        .add_product_request_images(id, std::slice::from_ref(&nutrition))
+        .await
+        .unwrap());
+    assert!(backend
+        .add_product_request_images(id, std::slice::from_ref(&ingredients))
+        .await
+        .unwrap());
+    assert_eq!(
+        backend.get_product_request_images(id).await.unwrap(),
+        Some(vec![nutrition.clone(), ingredients.clone()])
+    );
+    backend.delete_requested_product(id).await.unwrap();
+    assert_eq!(backend.get_product_request_images(id).await.unwrap(), None);
+    assert!(!backend
+        .add_product_request_images(id, std::slice::from_ref(&nutrition))
+        .await
+        .unwrap());
 
-        let options = PostgresConfig {
-            host: "localhost".to_string(),
-            port: *port as u16,
-            dbname: "postgres".to_string(),
-            user: "postgres".to_string(),
-            password: Secret::from_str("password").unwrap(),
-            max_connections: 5,
-        };
+    // the images of the product
+    assert_eq!(
+        backend.get_product_images(&product.info.id).await.unwrap(),
+        None
+    );
+    assert!(!backend
+        .add_product_images(&product.info.id, std::slice::from_ref(&nutrition))
+        .await
+        .unwrap());
+    assert!(backend.new_product(&product).await.unwrap());
+    assert!(backend
+        .add_product_images(&product.info.id, &[ingredients.clone(), nutrition.clone()])
+        .await
+        .unwrap());
+    assert_eq!(
+        backend.get_product_images(&product.info.id).await.unwrap(),
+        Some(vec![ingredients, nutrition])
+    );
+    backend.delete_product(&product.info.id).await.unwrap();
+    assert_eq!(
+        backend.get_product_images(&product.info.id).await.unwrap(),
+        None
+    );
+}
 
-        info!("Creating PostgresBackend instance...");
-        let postgres_backend = PostgresBackend::new(options).await.unwrap();
+/// Runs the tests for reporting the image storage usage with the given backend.
+///
+/// # Arguments
+/// - `backend` - The backend to run the tests with.
+async fn image_usage_tests<B: DataBackend>(backend: &B) {
+    const CONTENT_TYPE: &str = "image/x-usage-test";
+
+    // the usage of the content type before, as previous runs may have left images behind
+    let usage_of = |usage: Vec<ImageStorageUsage>| {
+        usage
+            .into_iter()
+            .find(|u| u.content_type == CONTENT_TYPE)
+            .map(|u| (u.images, u.bytes))
+            .unwrap_or_default()
+    };
+    let (images, bytes) = usage_of(backend.image_storage_usage().await.unwrap());
+
+    let mut product = load_products()[1].clone();
+    product.info.id = "image-usage-test".to_string();
+    product.preview = Some(ProductImage {
+        content_type: CONTENT_TYPE.to_string(),
+        data: vec![1, 2, 3],
+    });
+    product.full_image = Some(ProductImage {
+        content_type: CONTENT_TYPE.to_string(),
+        data: vec![1, 2, 3, 4, 5],
+    });
+    assert!(backend.new_product(&product).await.unwrap());
+
+    let usage = backend.image_storage_usage().await.unwrap();
+    assert!(usage
+        .windows(2)
+        .all(|w| w[0].content_type < w[1].content_type));
+    assert_eq!(usage_of(usage), (images + 2, bytes + 8));
+
+    backend.delete_product(&product.info.id).await.unwrap();
+}
+
+/// Runs the archive tests with the given backend.
+///
+/// # Arguments
+/// - `backend` - The backend to run the tests with.
+async fn archive_tests<B: DataBackend>(backend: &B) {
+    let products = load_products();
+    let request = |product_id: &str| {
+        let mut product_description = products[0].clone();
+        product_description.info.id = product_id.to_string();
+
+        ProductRequest {
+            product_description,
+            date: truncate_datetime(Utc::now()),
+            status: RequestStatus::Pending,
+            review_comment: None,
+        }
+    };
+    let query = |product_id: &str| ArchiveQuery {
+        offset: 0,
+        limit: 10,
+        product_id: Some(product_id.to_string()),
+        outcome: None,
+        order: SortingOrder::Ascending,
+    };
+
+    // a rejected request is kept with the review comment until it is archived
+    let rejected = request("archive-rejected");
+    let rejected_id = backend.request_new_product(&rejected).await.unwrap();
+    assert!(backend
+        .reject_product_request(rejected_id, Some("Not a food product"))
+        .await
+        .unwrap());
+    let stored = backend
+        .get_product_request(rejected_id, false)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(stored.status, RequestStatus::Rejected);
+    assert_eq!(stored.review_comment.as_deref(), Some("Not a food product"));
+
+    let status_query = |status| ProductQuery {
+        limit: 10,
+        offset: 0,
+        filter: SearchFilter::ProductID("archive-rejected".to_string()),
+        sorting: None,
+        market: None,
+        producer: None,
+        quantity_type: None,
+        labels: None,
+        max_nova_group: None,
+        min_eco_score_grade: None,
+        exclude_allergens: Vec::new(),
+        data_issues: Vec::new(),
+        fields: None,
+        request_status: Some(status),
+        cursor: None,
+    };
+    let requests = backend
+        .query_product_requests(&status_query(RequestStatus::Rejected), false)
+        .await
+        .unwrap();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0].0, rejected_id);
+    assert_eq!(
+        backend
+            .count_product_requests(&status_query(RequestStatus::Pending))
+            .await
+            .unwrap(),
+        0
+    );
+
+    // a rejected request is moved to the archive
+    assert!(backend
+        .archive_product_request(rejected_id, RequestOutcome::Rejected)
+        .await
+        .unwrap());
+    assert_eq!(
+        backend
+            .get_product_request(rejected_id, false)
+            .await
+            .unwrap(),
+        None
+    );
+    assert!(!backend
+        .archive_product_request(rejected_id, RequestOutcome::Rejected)
+        .await
+        .unwrap());
+
+    let archived = backend
+        .query_archived_product_requests(&query("archive-rejected"))
+        .await
+        .unwrap();
+    assert_eq!(archived.len(), 1);
+    assert_eq!(archived[0].id, rejected_id);
+    assert_eq!(archived[0].name, rejected.product_description.info.name);
+    assert_eq!(
+        archived[0].producer,
+        rejected.product_description.info.producer
+    );
+    assert_eq!(archived[0].date, rejected.date);
+    assert_eq!(archived[0].outcome, RequestOutcome::Rejected);
+    assert_eq!(
+        archived[0].review_comment.as_deref(),
+        Some("Not a food product")
+    );
+    assert!(!backend
+        .reject_product_request(rejected_id, None)
+        .await
+        .unwrap());
+
+    // requests are archived as approved once their product has been added
+    let approved = request("archive-approved");
+    let approved_id = backend.request_new_product(&approved).await.unwrap();
+    backend.archive_approved_product_requests().await.unwrap();
+    assert!(backend
+        .get_product_request(approved_id, false)
+        .await
+        .unwrap()
+        .is_some());
+
+    assert!(backend
+        .new_product(&approved.product_description)
+        .await
+        .unwrap());
+    assert!(backend.archive_approved_product_requests().await.unwrap() >= 1);
+    assert_eq!(
+        backend
+            .get_product_request(approved_id, false)
+            .await
+            .unwrap(),
+        None
+    );
+
+    let archived = backend
+        .query_archived_product_requests(&ArchiveQuery {
+            outcome: Some(RequestOutcome::Approved),
+            ..query("archive-approved")
+        })
+        .await
+        .unwrap();
+    assert_eq!(archived.len(), 1);
+    assert_eq!(archived[0].id, approved_id);
+    assert_eq!(archived[0].outcome, RequestOutcome::Approved);
+
+    let archived = backend
+        .query_archived_product_requests(&ArchiveQuery {
+            outcome: Some(RequestOutcome::Rejected),
+            ..query("archive-approved")
+        })
+        .await
+        .unwrap();
+    assert!(archived.is_empty());
+
+    backend
+        .delete_product(&approved.product_description.info.id)
+        .await
+        .unwrap();
+}
+
+/// Runs the tests for updating products with the given backend.
+///
+/// # Arguments
+/// - `backend` - The backend to run the tests with.
+async fn update_product_tests<B: DataBackend>(backend: &B) {
+    let mut product = load_products()[1].clone();
+    product.info.id = "update-test".to_string();
+    assert!(!backend.update_product(&product).await.unwrap());
+    assert!(backend.new_product(&product).await.unwrap());
+
+    // the images are kept if none are given
+    let mut updated = product.clone();
+    updated.info.name = "Updated name".to_string();
+    updated.info.producer = Some("Updated producer".to_string());
+    updated.nutrients.kcal += 10.0;
+    updated.nutrients.sugar = None;
+    updated.preview = None;
+    updated.full_image = None;
+    assert!(backend.update_product(&updated).await.unwrap());
+
+    let stored = backend
+        .get_product(&product.info.id, true)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(stored.info, updated.info);
+    assert_eq!(stored.nutrients, updated.nutrients);
+    assert_eq!(stored.preview, product.preview);
+    assert_eq!(
+        backend.get_product_image(&product.info.id).await.unwrap(),
+        product.full_image
+    );
+
+    // the updated name is searchable
+    let found = backend
+        .query_products(
+            &ProductQuery {
+                limit: 40,
+                offset: 0,
+                filter: SearchFilter::Search("Updated name".to_string()),
+                sorting: None,
+                market: None,
+                producer: None,
+                quantity_type: None,
+                labels: None,
+                max_nova_group: None,
+                min_eco_score_grade: None,
+                exclude_allergens: Vec::new(),
+                data_issues: Vec::new(),
+                fields: None,
+                request_status: None,
+                cursor: None,
+            },
+            false,
+        )
+        .await
+        .unwrap();
+    assert!(found.iter().any(|p| p.info.id == product.info.id));
+
+    // given images replace the stored ones
+    let image = |data| ProductImage {
+        content_type: "image/png".to_string(),
+        data,
+    };
+    updated.preview = Some(image(vec![1, 2]));
+    updated.full_image = Some(image(vec![3, 4, 5]));
+    assert!(backend.update_product(&updated).await.unwrap());
+    let stored = backend
+        .get_product(&product.info.id, true)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(stored.preview, updated.preview);
+    assert_eq!(
+        backend.get_product_image(&product.info.id).await.unwrap(),
+        updated.full_image
+    );
+
+    backend.delete_product(&product.info.id).await.unwrap();
+    assert!(!backend.update_product(&updated).await.unwrap());
+}
+
+/// Runs the tests for approving product requests with the given backend.
+///
+/// # Arguments
+/// - `backend` - The backend to run the tests with.
+async fn approve_tests<B: DataBackend>(backend: &B) {
+    let mut product = load_products()[1].clone();
+    product.info.id = "approve-test".to_string();
+    let request = ProductRequest {
+        product_description: product.clone(),
+        date: truncate_datetime(Utc::now()),
+        status: RequestStatus::Pending,
+        review_comment: None,
+    };
+    let nutrition = AdditionalImage {
+        role: ImageRole::Nutrition,
+        image: ProductImage {
+            content_type: "image/jpeg".to_string(),
+            data: vec![1, 2, 3],
+        },
+    };
+
+    // the product takes over the description and the images of the request
+    let id = backend.request_new_product(&request).await.unwrap();
+    assert!(backend
+        .add_product_request_images(id, std::slice::from_ref(&nutrition))
+        .await
+        .unwrap());
+    assert_eq!(
+        backend.approve_product_request(id).await.unwrap(),
+        Some(product.info.id.clone())
+    );
+    assert_eq!(backend.get_product_request(id, false).await.unwrap(), None);
+    assert_eq!(backend.approve_product_request(id).await.unwrap(), None);
+
+    let approved = backend
+        .get_product(&product.info.id, true)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(approved.info, product.info);
+    assert_eq!(approved.nutrients, product.nutrients);
+    assert_eq!(approved.preview, product.preview);
+    assert_eq!(
+        backend.get_product_image(&product.info.id).await.unwrap(),
+        product.full_image
+    );
+    assert_eq!(
+        backend.get_product_images(&product.info.id).await.unwrap(),
+        Some(vec![nutrition])
+    );
+
+    // the original date of the request is kept in the archive
+    let archived = backend
+        .query_archived_product_requests(&ArchiveQuery {
+            offset: 0,
+            limit: 10,
+            product_id: Some(product.info.id.clone()),
+            outcome: None,
+            order: SortingOrder::Ascending,
+        })
+        .await
+        .unwrap();
+    assert_eq!(archived.len(), 1);
+    assert_eq!(archived[0].id, id);
+    assert_eq!(archived[0].date, request.date);
+    assert_eq!(archived[0].outcome, RequestOutcome::Approved);
+
+    // a request for an existing product is kept
+    let id = backend.request_new_product(&request).await.unwrap();
+    assert!(matches!(
+        backend.approve_product_request(id).await,
+        Err(Error::ProductAlreadyExists(product_id)) if product_id == product.info.id
+    ));
+    assert!(backend
+        .get_product_request(id, false)
+        .await
+        .unwrap()
+        .is_some());
+
+    backend.delete_requested_product(id).await.unwrap();
+    backend.delete_product(&product.info.id).await.unwrap();
+    assert_eq!(
+        backend.get_product_images(&product.info.id).await.unwrap(),
+        None
+    );
+}
+
+/// Runs the backend tests with the given backend.
+///
+/// # Arguments
+/// - `backend` - The backend to run the tests with.
+async fn backend_tests<B: DataBackend>(backend: B) {
+    info!("Do some operations with the backend...");
+    simple_ops(&backend).await;
+    info!("Do some operations with the backend...DONE");
+
+    info!("Running backend tests...");
+    missing_product_tests(&backend).await;
+    info!("Running backend tests...SUCCESS");
+
+    info!("Running product requests tests...");
+    product_requests_tests(&backend).await;
+    info!("Running product requests tests...SUCCESS");
+
+    info!("Running product tests...");
+    product_tests(&backend).await;
+    info!("Running product tests...SUCCESS");
+
+    info!("Running archive tests...");
+    archive_tests(&backend).await;
+    info!("Running archive tests...SUCCESS");
+
+    info!("Running approve tests...");
+    approve_tests(&backend).await;
+    info!("Running approve tests...SUCCESS");
+
+    info!("Running update product tests...");
+    update_product_tests(&backend).await;
+    info!("Running update product tests...SUCCESS");
+
+    info!("Running preview tests...");
+    preview_tests(&backend).await;
+    info!("Running preview tests...SUCCESS");
+
+    info!("Running additional images tests...");
+    additional_images_tests(&backend).await;
+    info!("Running additional images tests...SUCCESS");
+
+    info!("Running image usage tests...");
+    image_usage_tests(&backend).await;
+    info!("Running image usage tests...SUCCESS");
+
+    info!("Running recipe tests...");
+    recipe_tests(&backend).await;
+    info!("Running recipe tests...SUCCESS");
+
+    info!("Running price tests...");
+    price_tests(&backend).await;
+    info!("Running price tests...SUCCESS");
+
+    info!("Running store tests...");
+    store_tests(&backend).await;
+    info!("Running store tests...SUCCESS");
+}
+
+/// Runs the store and product availability tests with the given backend.
+///
+/// # Arguments
+/// - `backend` - The backend to run the tests with.
+async fn store_tests<B: DataBackend>(backend: &B) {
+    let stores = [
+        Store {
+            name: "Market Street".to_string(),
+            chain: Some("FreshMart".to_string()),
+        },
+        Store {
+            name: "Harbour".to_string(),
+            chain: Some("FreshMart".to_string()),
+        },
+        Store {
+            name: "Corner shop".to_string(),
+            chain: None,
+        },
+    ];
+    let mut ids = Vec::new();
+    for store in stores.iter() {
+        let id = backend.new_store(store).await.unwrap();
+        assert_eq!(backend.get_store(id).await.unwrap(), Some(store.clone()));
+        ids.push(id);
+    }
+
+    // the stores of a chain ordered by their name
+    let chain_stores = backend
+        .query_stores(&StoreQuery {
+            offset: 0,
+            limit: 10,
+            chain: Some("FreshMart".to_string()),
+            order: SortingOrder::Ascending,
+        })
+        .await
+        .unwrap();
+    assert_eq!(
+        chain_stores,
+        vec![(ids[1], stores[1].clone()), (ids[0], stores[0].clone())]
+    );
+
+    // add the products and report their availability
+    let mut products = load_products();
+    products.truncate(2);
+    for (i, product) in products.iter_mut().enumerate() {
+        product.info.id = format!("store-test-{}", i);
+        assert!(backend.new_product(product).await.unwrap());
+    }
+
+    let observe =
+        |store_id: DBId, product: &ProductDescription, available: bool| ProductAvailability {
+            store_id,
+            product_id: product.info.id.clone(),
+            available,
+            date: truncate_datetime(Utc::now()),
+        };
+    let seen = observe(ids[2], &products[1], true);
+    for availability in [
+        observe(ids[0], &products[0], true),
+        observe(ids[0], &products[1], true),
+        seen.clone(),
+    ] {
+        assert!(backend.report_availability(&availability).await.unwrap());
+    }
+
+    // the last observation replaces the previous one
+    let missing = observe(ids[0], &products[1], false);
+    assert!(backend.report_availability(&missing).await.unwrap());
+    assert_eq!(
+        backend
+            .get_product_availability(&products[1].info.id)
+            .await
+            .unwrap(),
+        vec![missing, seen]
+    );
+
+    // the availability at unknown stores is rejected
+    assert!(!backend
+        .report_availability(&observe(-1, &products[0], true))
+        .await
+        .unwrap());
+
+    // only the products last seen at the store are returned
+    let store_query = |store_id: DBId| ProductQuery {
+        offset: 0,
+        limit: 10,
+        filter: SearchFilter::Store(store_id),
+        sorting: None,
+        market: None,
+        producer: None,
+        quantity_type: None,
+        labels: None,
+        max_nova_group: None,
+        min_eco_score_grade: None,
+        exclude_allergens: Vec::new(),
+        data_issues: Vec::new(),
+        fields: None,
+        request_status: None,
+        cursor: None,
+    };
+    let result = backend
+        .query_products(&store_query(ids[0]), false)
+        .await
+        .unwrap();
+    assert_eq!(result.len(), 1);
+    compare_product_description(&result[0], &products[0], false);
+    let result = backend
+        .query_products(&store_query(ids[2]), false)
+        .await
+        .unwrap();
+    assert_eq!(result.len(), 1);
+    compare_product_description(&result[0], &products[1], false);
+    assert!(backend
+        .query_products(&store_query(ids[1]), false)
+        .await
+        .unwrap()
+        .is_empty());
+
+    // missing products can be reported with the store
+    let report_id = backend
+        .report_missing_product(MissingProduct {
+            product_id: "store-test-missing".to_string(),
+            date: truncate_datetime(Utc::now()),
+            store_id: Some(ids[1]),
+        })
+        .await
+        .unwrap();
+    let report = backend
+        .get_missing_product(report_id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(report.store_id, Some(ids[1]));
+
+    // deleting the stores removes the availability and keeps the reports without store
+    for id in ids.iter() {
+        backend.delete_store(*id).await.unwrap();
+        assert_eq!(backend.get_store(*id).await.unwrap(), None);
+    }
+    assert!(backend
+        .get_product_availability(&products[1].info.id)
+        .await
+        .unwrap()
+        .is_empty());
+    let report = backend
+        .get_missing_product(report_id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(report.store_id, None);
+
+    backend
+        .delete_reported_missing_product(report_id)
+        .await
+        .unwrap();
+    for product in products.iter() {
+        backend.delete_product(&product.info.id).await.unwrap();
+    }
+}
+
+/// Runs the price tests with the given backend.
+///
+/// # Arguments
+/// - `backend` - The backend to run the tests with.
+async fn price_tests<B: DataBackend>(backend: &B) {
+    let product_id: ProductID = "price-tracking".to_string();
+    let observe = |store: &str, price: i64, currency: &str, day: u32| PriceObservation {
+        product_id: product_id.clone(),
+        store: store.to_string(),
+        price,
+        currency: currency.to_string(),
+        observed_at: DateTime::parse_from_rfc3339(&format!("2024-03-{:02}T10:00:00Z", day))
+            .unwrap()
+            .to_utc(),
+    };
+
+    // no price has been observed yet
+    assert_eq!(
+        backend.cheapest_price(&product_id, "EUR").await.unwrap(),
+        None
+    );
+
+    let prices = [
+        observe("Corner shop", 249, "EUR", 1),
+        observe("Supermarket", 199, "EUR", 2),
+        observe("Supermarket", 219, "EUR", 3),
+        observe("Discounter", 199, "EUR", 4),
+        observe("Duty free", 150, "USD", 5),
+    ];
+    let mut ids = Vec::new();
+    for price in prices.iter() {
+        ids.push(backend.submit_price(price).await.unwrap());
+    }
+
+    // the most recent of the cheapest prices in the currency wins
+    assert_eq!(
+        backend.cheapest_price(&product_id, "EUR").await.unwrap(),
+        Some(prices[3].clone())
+    );
+    assert_eq!(
+        backend.cheapest_price(&product_id, "USD").await.unwrap(),
+        Some(prices[4].clone())
+    );
+    assert_eq!(
+        backend.cheapest_price(&product_id, "GBP").await.unwrap(),
+        None
+    );
+
+    let query = PriceQuery {
+        offset: 0,
+        limit: 10,
+        product_id: Some(product_id.clone()),
+        store: None,
+        currency: None,
+        order: SortingOrder::Ascending,
+    };
+    let expected: Vec<(DBId, PriceObservation)> =
+        ids.iter().copied().zip(prices.iter().cloned()).collect();
+    assert_eq!(backend.query_prices(&query).await.unwrap(), expected);
+
+    // filtering by store and currency in descending order
+    let result = backend
+        .query_prices(&PriceQuery {
+            store: Some("Supermarket".to_string()),
+            currency: Some("EUR".to_string()),
+            order: SortingOrder::Descending,
+            ..query.clone()
+        })
+        .await
+        .unwrap();
+    assert_eq!(
+        result,
+        vec![(ids[2], prices[2].clone()), (ids[1], prices[1].clone())]
+    );
+
+    // offset and limit
+    let result = backend
+        .query_prices(&PriceQuery {
+            offset: 1,
+            limit: 2,
+            ..query.clone()
+        })
+        .await
+        .unwrap();
+    assert_eq!(result, expected[1..3].to_vec());
+}
+
+/// Runs the recipe tests with the given backend.
+///
+/// # Arguments
+/// - `backend` - The backend to run the tests with.
+async fn recipe_tests<B: DataBackend>(backend: &B) {
+    let products = load_products();
+    let image = products.iter().find_map(|p| p.full_image.clone());
+
+    let recipe = Recipe {
+        name: "Pancakes".to_string(),
+        servings: 4,
+        ingredients: vec![
+            Portion {
+                product_id: products[0].info.id.clone(),
+                amount: 250.0,
+            },
+            Portion {
+                product_id: products[1].info.id.clone(),
+                amount: 500.0,
+            },
+            Portion {
+                product_id: products[0].info.id.clone(),
+                amount: 20.0,
+            },
+        ],
+        image: image.clone(),
+    };
+
+    // the recipe is stored with its ingredients in order and its image
+    let id = backend.new_recipe(&recipe).await.unwrap();
+    assert_eq!(backend.get_recipe(id).await.unwrap(), Some(recipe.clone()));
+
+    // the recipe is replaced including its ingredients and image
+    let updated = Recipe {
+        name: "Crepes".to_string(),
+        servings: 2,
+        ingredients: vec![Portion {
+            product_id: products[2].info.id.clone(),
+            amount: 100.0,
+        }],
+        image: None,
+    };
+    assert!(backend.update_recipe(id, &updated).await.unwrap());
+    assert_eq!(backend.get_recipe(id).await.unwrap(), Some(updated.clone()));
+    assert!(backend.update_recipe(id, &recipe).await.unwrap());
+    assert_eq!(backend.get_recipe(id).await.unwrap(), Some(recipe.clone()));
+
+    // a recipe without ingredients and image
+    let empty = Recipe {
+        name: "Water".to_string(),
+        servings: 1,
+        ingredients: Vec::new(),
+        image: None,
+    };
+    let empty_id = backend.new_recipe(&empty).await.unwrap();
+    assert_ne!(empty_id, id);
+    assert_eq!(backend.get_recipe(empty_id).await.unwrap(), Some(empty));
+
+    // deleting the recipes
+    backend.delete_recipe(id).await.unwrap();
+    backend.delete_recipe(empty_id).await.unwrap();
+    assert_eq!(backend.get_recipe(id).await.unwrap(), None);
+    assert!(!backend.update_recipe(id, &updated).await.unwrap());
+    backend.delete_recipe(id).await.unwrap();
+}
+
+/// Runs some operations through the type-erased backend wrapper.
+///
+/// # Arguments
+/// - `options` - The options for connecting to the database.
+async fn boxed_backend_tests(options: PostgresConfig) {
+    let postgres_backend = PostgresBackend::new(options).await.unwrap();
+    let backend = BoxedDataBackend::new(postgres_backend);
+
+    simple_ops(&backend).await;
+
+    let mut product = load_products()[2].clone();
+    product.info.id = "boxed-backend".to_string();
+
+    assert!(backend.new_product(&product).await.unwrap());
+    let out_product = backend
+        .get_product(&product.info.id, true)
+        .await
+        .unwrap()
+        .unwrap();
+    compare_product_description(&out_product, &product, true);
+
+    backend.delete_product(&product.info.id).await.unwrap();
+    assert!(backend
+        .get_product(&product.info.id, false)
+        .await
+        .unwrap()
+        .is_none());
+}
+
+/// Checks the health check of the backend and of the boxed backend.
+///
+/// # Arguments
+/// - `options` - The options for connecting to the database.
+async fn health_check_tests(options: PostgresConfig) {
+    let backend = PostgresBackend::new(options).await.unwrap();
+    let report = ReadBackend::health_check(&backend).await;
+    assert_eq!(report.status, HealthStatus::Healthy);
+    assert_eq!(report.details, None);
+
+    let boxed_backend = BoxedDataBackend::new(backend);
+    let report = ReadBackend::health_check(&boxed_backend).await;
+    assert_eq!(report.status, HealthStatus::Healthy);
+}
+
+/// Checks that the backend keeps working after reconnecting with new credentials.
+///
+/// # Arguments
+/// - `options` - The options for connecting to the database.
+async fn reconnect_tests(options: PostgresConfig) {
+    let backend = PostgresBackend::new(options.clone()).await.unwrap();
+    simple_ops(&backend).await;
+
+    ReadBackend::reconnect(&backend, options.password.clone())
+        .await
+        .unwrap();
+    simple_ops(&backend).await;
+
+    let boxed_backend = BoxedDataBackend::new(backend);
+    ReadBackend::reconnect(&boxed_backend, options.password)
+        .await
+        .unwrap();
+    simple_ops(&boxed_backend).await;
+}
+
+/// Checks that the connections of the backend are labeled with the application name.
+///
+/// # Arguments
+/// - `options` - The options for connecting to the database.
+async fn application_name_tests(options: PostgresConfig) {
+    let options = PostgresConfig {
+        application_name: Some("product-db-test".to_string()),
+        instance_id: Some("instance-1".to_string()),
+        ..options
+    };
+    assert_eq!(
+        options.connection_application_name(),
+        "product-db-test (instance-1)"
+    );
+
+    let backend = PostgresBackend::new(options.clone()).await.unwrap();
+    simple_ops(&backend).await;
+
+    let url = format!(
+        "postgres://{}:{}@{}:{}/{}",
+        options.user,
+        options.password.secret().unwrap(),
+        options.host,
+        options.port,
+        options.dbname
+    );
+    let pool = sqlx::PgPool::connect(&url).await.unwrap();
+
+    let count: i64 =
+        sqlx::query_scalar("select count(*) from pg_stat_activity where application_name = $1;")
+            .bind("product-db-test (instance-1)")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    assert!(count >= 1);
+}
+
+/// Runs some operations with the PgBouncer compatibility mode enabled.
+///
+/// # Arguments
+/// - `options` - The options for connecting to the database.
+async fn pgbouncer_mode_tests(options: PostgresConfig) {
+    let options = PostgresConfig {
+        pgbouncer_mode: true,
+        ..options
+    };
+
+    let backend = PostgresBackend::new(options.clone()).await.unwrap();
+    simple_ops(&backend).await;
+    simple_ops(&backend).await;
+
+    // the schema is set per connection, which is not possible behind a transaction-mode pooler
+    let options = PostgresConfig {
+        schema: Some("product_db".to_string()),
+        ..options
+    };
+    assert!(matches!(
+        PostgresBackend::new(options).await,
+        Err(Error::InvalidConfigError(_))
+    ));
+}
+
+/// Runs some operations with the tables located in a separate schema.
+///
+/// # Arguments
+/// - `options` - The options for connecting to the database.
+async fn custom_schema_tests(options: PostgresConfig) {
+    const SCHEMA: &str = "product_db_custom";
+
+    let url = format!(
+        "postgres://{}:{}@{}:{}/{}",
+        options.user,
+        options.password.secret().unwrap(),
+        options.host,
+        options.port,
+        options.dbname
+    );
+    let pool = sqlx::PgPool::connect(&url).await.unwrap();
+
+    // create the tables in the separate schema
+    sqlx::raw_sql(&format!(
+        "create schema {}; set search_path to {},public; {}",
+        SCHEMA,
+        SCHEMA,
+        include_str!("../../docker/db/init.sql")
+    ))
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    let backend = PostgresBackend::new(PostgresConfig {
+        schema: Some(SCHEMA.to_string()),
+        ..options.clone()
+    })
+    .await
+    .unwrap();
+
+    let mut product = load_products()[0].clone();
+    product.info.id = "custom-schema".to_string();
+    assert!(backend.new_product(&product).await.unwrap());
+
+    // the product must only be stored in the separate schema
+    let count: i64 = sqlx::query_scalar(&format!(
+        "select count(*) from {}.products where product_id = $1;",
+        SCHEMA
+    ))
+    .bind(&product.info.id)
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    assert_eq!(count, 1);
+
+    let count: i64 =
+        sqlx::query_scalar("select count(*) from public.products where product_id = $1;")
+            .bind(&product.info.id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    assert_eq!(count, 0);
+
+    backend.delete_product(&product.info.id).await.unwrap();
+
+    sqlx::query(&format!("drop schema {} cascade;", SCHEMA))
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    // invalid schema names are rejected
+    assert!(PostgresBackend::new(PostgresConfig {
+        schema: Some("public; drop table products".to_string()),
+        ..options
+    })
+    .await
+    .is_err());
+}
+
+/// Runs the tests of mirroring the writes to a secondary backend, which stores its tables in a
+/// separate schema.
+///
+/// # Arguments
+/// - `options` - The options for connecting to the database.
+async fn mirrored_backend_tests(options: PostgresConfig) {
+    const SCHEMA: &str = "product_db_mirror";
+
+    let url = format!(
+        "postgres://{}:{}@{}:{}/{}",
+        options.user,
+        options.password.secret().unwrap(),
+        options.host,
+        options.port,
+        options.dbname
+    );
+    let pool = sqlx::PgPool::connect(&url).await.unwrap();
+    sqlx::raw_sql(&format!(
+        "create schema {}; set search_path to {},public; {}",
+        SCHEMA,
+        SCHEMA,
+        include_str!("../../docker/db/init.sql")
+    ))
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    // the secondary backend continues the sequence of the request ids of the primary
+    sqlx::query(&format!(
+        "select setval('{}.requested_products_id_seq', last_value, is_called)
+        from public.requested_products_id_seq;",
+        SCHEMA
+    ))
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    let primary = PostgresBackend::new(options.clone()).await.unwrap();
+    let secondary = PostgresBackend::new(PostgresConfig {
+        schema: Some(SCHEMA.to_string()),
+        ..options.clone()
+    })
+    .await
+    .unwrap();
+    let backend = MirroredBackend::new(primary, secondary);
+
+    // the writes are applied to both backends
+    let mut product = load_products()[0].clone();
+    product.info.id = "mirrored".to_string();
+    assert!(backend.new_product(&product).await.unwrap());
+    assert!(backend
+        .secondary()
+        .get_product(&product.info.id, false)
+        .await
+        .unwrap()
+        .is_some());
+
+    let request = ProductRequest {
+        product_description: product.clone(),
+        date: truncate_datetime(Utc::now()),
+        status: RequestStatus::Pending,
+        review_comment: None,
+    };
+    let id = backend.request_new_product(&request).await.unwrap();
+    assert!(backend
+        .get_product_request(id, false)
+        .await
+        .unwrap()
+        .is_some());
+    assert!(backend
+        .secondary()
+        .get_product_request(id, false)
+        .await
+        .unwrap()
+        .is_some());
+    backend.delete_requested_product(id).await.unwrap();
+    assert_eq!(backend.divergences(), 0);
+
+    // a product that already exists in the secondary backend is reported as divergence
+    let mut existing = product.clone();
+    existing.info.id = "mirrored-existing".to_string();
+    assert!(backend.secondary().new_product(&existing).await.unwrap());
+    assert!(backend.new_product(&existing).await.unwrap());
+    assert_eq!(backend.divergences(), 1);
+
+    // a request id generated by the secondary backend that differs is reported as divergence
+    let skipped = backend
+        .primary()
+        .request_new_product(&request)
+        .await
+        .unwrap();
+    let id = backend.request_new_product(&request).await.unwrap();
+    assert_eq!(backend.divergences(), 2);
+    for id in [skipped, id] {
+        backend
+            .primary()
+            .delete_requested_product(id)
+            .await
+            .unwrap();
+    }
+
+    for id in [&product.info.id, &existing.info.id] {
+        backend.delete_product(id).await.unwrap();
+        assert!(backend.get_product(id, false).await.unwrap().is_none());
+        assert!(backend
+            .secondary()
+            .get_product(id, false)
+            .await
+            .unwrap()
+            .is_none());
+    }
+
+    sqlx::query(&format!("drop schema {} cascade;", SCHEMA))
+        .execute(&pool)
+        .await
+        .unwrap();
+}
+
+/// Checks that the price operations are rejected if the price tracking is disabled.
+///
+/// # Arguments
+/// - `options` - The options for connecting to the database.
+async fn price_tracking_disabled_tests(options: PostgresConfig) {
+    let backend = PostgresBackend::new(PostgresConfig {
+        price_tracking: false,
+        ..options
+    })
+    .await
+    .unwrap();
+
+    let product_id: ProductID = "price-tracking".to_string();
+    let price = PriceObservation {
+        product_id: product_id.clone(),
+        store: "Corner shop".to_string(),
+        price: 199,
+        currency: "EUR".to_string(),
+        observed_at: truncate_datetime(Utc::now()),
+    };
+    assert!(matches!(
+        backend.submit_price(&price).await,
+        Err(Error::PriceTrackingDisabled)
+    ));
+    assert!(matches!(
+        backend.cheapest_price(&product_id, "EUR").await,
+        Err(Error::PriceTrackingDisabled)
+    ));
+    assert!(matches!(
+        backend
+            .query_prices(&PriceQuery {
+                offset: 0,
+                limit: 10,
+                product_id: None,
+                store: None,
+                currency: None,
+                order: SortingOrder::Ascending,
+            })
+            .await,
+        Err(Error::PriceTrackingDisabled)
+    ));
+}
+
+/// Checks that the images are stored in the configured filesystem image store and that the
+/// files of deleted images are removed.
+///
+/// # Arguments
+/// - `options` - The options for connecting to the database.
+async fn image_store_tests(options: PostgresConfig) {
+    let dir = tempfile::tempdir().unwrap();
+    let backend = PostgresBackend::new(PostgresConfig {
+        image_store: ImageStoreConfig::Filesystem(FilesystemImageStoreConfig {
+            path: dir.path().to_path_buf(),
+        }),
+        ..options
+    })
+    .await
+    .unwrap();
+
+    let stored_files = || count_files(dir.path());
+
+    let mut product = load_products()[0].clone();
+    product.info.id = "image-store".to_string();
+    product.full_image = Some(ProductImage {
+        content_type: "image/png".to_string(),
+        data: vec![1, 2, 3, 4],
+    });
+    assert!(backend.new_product(&product).await.unwrap());
+    assert_eq!(stored_files(), 1);
+
+    // the image is loaded from the store, the preview is still stored in Postgres
+    let out_product = backend
+        .get_product(&product.info.id, true)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(out_product.preview, product.preview);
+    assert_eq!(
+        backend.get_product_image(&product.info.id).await.unwrap(),
+        product.full_image
+    );
+
+    // deleting the product also deletes the stored file
+    backend.delete_product(&product.info.id).await.unwrap();
+    assert_eq!(
+        backend.get_product_image(&product.info.id).await.unwrap(),
+        None
+    );
+    assert_eq!(stored_files(), 0);
+}
+
+/// Counts the files in the given directory and its subdirectories.
+///
+/// # Arguments
+/// - `dir` - The directory to count the files in.
+fn count_files(dir: &std::path::Path) -> usize {
+    std::fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .map(|path| if path.is_dir() { count_files(&path) } else { 1 })
+        .sum()
+}
+
+/// Checks that adding an already existing product, also within a batch, or a failing product
+/// request leaves no orphaned rows behind.
+///
+/// # Arguments
+/// - `options` - The options for connecting to the database.
+async fn duplicate_product_tests(options: PostgresConfig) {
+    let url = format!(
+        "postgres://{}:{}@{}:{}/{}",
+        options.user,
+        options.password.secret().unwrap(),
+        options.host,
+        options.port,
+        options.dbname
+    );
+    let pool = sqlx::PgPool::connect(&url).await.unwrap();
+
+    let count_rows = || async {
+        let count: i64 = sqlx::query_scalar(
+            "select (select count(*) from product_description)
+                + (select count(*) from product_image)
+                + (select count(*) from nutrients);",
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        count
+    };
+
+    let backend = PostgresBackend::new(options).await.unwrap();
+
+    let mut product = load_products().remove(0);
+    product.info.id = "duplicate-product".to_string();
+    assert!(backend.new_product(&product).await.unwrap());
+
+    let num_rows = count_rows().await;
+    assert!(!backend.new_product(&product).await.unwrap());
+    assert_eq!(count_rows().await, num_rows);
+
+    // the existing and the repeated products of a batch are skipped
+    let mut new_product = product.clone();
+    new_product.info.id = "duplicate-product-batch".to_string();
+    let batch = [product.clone(), new_product.clone(), new_product.clone()];
+    assert_eq!(backend.new_products(&batch).await.unwrap(), 1);
+    assert!(backend
+        .get_product(&new_product.info.id, false)
+        .await
+        .unwrap()
+        .is_some());
+
+    let num_rows = count_rows().await;
+    assert_eq!(backend.new_products(&batch).await.unwrap(), 0);
+    assert_eq!(count_rows().await, num_rows);
+
+    // a failing product request rolls back its nutrients and images
+    let mut requested_product = product.clone();
+    requested_product.info.name = "x".repeat(200);
+    let request = ProductRequest {
+        product_description: requested_product,
+        date: Utc::now(),
+        status: RequestStatus::Pending,
+        review_comment: None,
+    };
+    assert!(backend.request_new_product(&request).await.is_err());
+    assert_eq!(count_rows().await, num_rows);
+
+    backend.delete_product(&product.info.id).await.unwrap();
+    backend.delete_product(&new_product.info.id).await.unwrap();
+}
+
+/// Checks that the pages of a query sorted by a non-unique field are disjoint.
+///
+/// # Arguments
+/// - `options` - The options for connecting to the database.
+async fn stable_ordering_tests(options: PostgresConfig) {
+    let backend = PostgresBackend::new(options).await.unwrap();
+
+    // many products sharing the same name
+    let mut product_ids = Vec::new();
+    for (i, mut product) in load_products().into_iter().enumerate() {
+        product.info.id = format!("stable-order-{}", i);
+        product.info.name = "Stable Order".to_string();
+        assert!(backend.new_product(&product).await.unwrap());
+        product_ids.push(product.info.id);
+    }
+
+    for order in [SortingOrder::Ascending, SortingOrder::Descending] {
+        let mut query = ProductQuery {
+            offset: 0,
+            limit: 1,
+            filter: SearchFilter::Search("stable order".to_string()),
+            sorting: Some(Sorting {
+                order,
+                field: SortingField::Name,
+            }),
+            market: None,
+            producer: None,
+            quantity_type: None,
+            labels: None,
+            max_nova_group: None,
+            min_eco_score_grade: None,
+            exclude_allergens: Vec::new(),
+            data_issues: Vec::new(),
+            fields: None,
+            request_status: None,
+            cursor: None,
+        };
+
+        let mut paged_ids = Vec::new();
+        for offset in 0..product_ids.len() {
+            query.offset = offset as i32;
+            let products = backend.query_products(&query, false).await.unwrap();
+            assert_eq!(products.len(), 1);
+            paged_ids.push(products[0].info.id.clone());
+        }
+
+        // the product id breaks the ties in the order of the sorting
+        let mut expected_ids = product_ids.clone();
+        expected_ids.sort();
+        if order == SortingOrder::Descending {
+            expected_ids.reverse();
+        }
+        assert_eq!(paged_ids, expected_ids);
+    }
+
+    for product_id in product_ids {
+        backend.delete_product(&product_id).await.unwrap();
+    }
+}
+
+/// Checks that the pages following the cursors of the previous pages yield the same order as
+/// the unpaged query, including the ties and the products without sorted value.
+///
+/// # Arguments
+/// - `options` - The options for connecting to the database.
+async fn cursor_pagination_tests(options: PostgresConfig) {
+    let backend = PostgresBackend::new(options).await.unwrap();
+
+    // the products share the names and NOVA groups in pairs, some without NOVA group
+    let mut product_ids = Vec::new();
+    for (i, mut product) in load_products().into_iter().enumerate() {
+        product.info.id = format!("cursor-page-{}", i);
+        product.info.name = format!("Cursor Page {}", i / 2);
+        product.info.nova_group = [Some(2), None, Some(4)][i / 2 % 3];
+        assert!(backend.new_product(&product).await.unwrap());
+        product_ids.push(product.info.id);
+    }
+
+    for field in [
+        SortingField::Name,
+        SortingField::ProductID,
+        SortingField::NovaGroup,
+        SortingField::Similarity,
+    ] {
+        for order in [SortingOrder::Ascending, SortingOrder::Descending] {
+            let sorting = Sorting { order, field };
+            let query = ProductQuery {
+                offset: 0,
+                limit: 100,
+                filter: SearchFilter::Search("cursor page".to_string()),
+                sorting: Some(sorting),
+                market: None,
+                producer: None,
+                quantity_type: None,
+                labels: None,
+                max_nova_group: None,
+                min_eco_score_grade: None,
+                exclude_allergens: Vec::new(),
+                data_issues: Vec::new(),
+                fields: None,
+                request_status: None,
+                cursor: None,
+            };
+            let expected = backend.query_scored_products(&query, false).await.unwrap();
+            assert_eq!(expected.len(), product_ids.len());
+
+            let mut paged = Vec::new();
+            let mut cursor = None;
+            loop {
+                let query = ProductQuery {
+                    limit: 2,
+                    offset: 1,
+                    cursor: cursor.take(),
+                    ..query.clone()
+                };
+                let page = backend.query_scored_products(&query, false).await.unwrap();
+
+                // the total includes the products before the cursor
+                let (counted, total_count) =
+                    backend.query_counted_products(&query, false).await.unwrap();
+                assert_eq!(counted.len(), page.len());
+                assert_eq!(total_count, product_ids.len() as u64);
+
+                let Some((last, score)) = page.last() else {
+                    break;
+                };
+                cursor = Some(ProductCursor::after(sorting, last, Some(*score)).encode());
+                paged.extend(page.into_iter().map(|(product, _)| product.info.id));
+            }
+
+            // the offset of the first page is kept
+            let expected_ids: Vec<ProductID> = expected
+                .into_iter()
+                .skip(1)
+                .map(|(product, _)| product.info.id)
+                .collect();
+            assert_eq!(paged, expected_ids, "{:?}", sorting);
+        }
+    }
+
+    // the cursor must match the sorting of the query
+    let sorting = Sorting {
+        order: SortingOrder::Ascending,
+        field: SortingField::Name,
+    };
+    let product = backend
+        .get_product(&product_ids[0], false)
+        .await
+        .unwrap()
+        .unwrap();
+    let query = ProductQuery {
+        offset: 0,
+        limit: 10,
+        filter: SearchFilter::NoFilter,
+        sorting: Some(Sorting {
+            order: SortingOrder::Descending,
+            field: SortingField::Name,
+        }),
+        market: None,
+        producer: None,
+        quantity_type: None,
+        labels: None,
+        max_nova_group: None,
+        min_eco_score_grade: None,
+        exclude_allergens: Vec::new(),
+        data_issues: Vec::new(),
+        fields: None,
+        request_status: None,
+        cursor: Some(ProductCursor::after(sorting, &product, None).encode()),
+    };
+    assert!(matches!(
+        backend.query_products(&query, false).await,
+        Err(Error::InvalidCursor(_))
+    ));
+
+    for product_id in product_ids {
+        backend.delete_product(&product_id).await.unwrap();
+    }
+}
+
+/// Checks that the search ignores the accents and the case if enabled.
+///
+/// # Arguments
+/// - `options` - The options for connecting to the database.
+async fn unaccent_search_tests(options: PostgresConfig) {
+    let mut product = load_products().remove(0);
+    product.info.id = "unaccent-product".to_string();
+    product.info.name = "Bircher Müsli".to_string();
+    product.info.producer = Some("Café Crème".to_string());
+
+    let search = |search_string: &str| ProductQuery {
+        offset: 0,
+        limit: 10,
+        filter: SearchFilter::Search(search_string.to_string()),
+        sorting: Some(Sorting {
+            order: SortingOrder::Descending,
+            field: SortingField::Similarity,
+        }),
+        market: None,
+        producer: None,
+        quantity_type: None,
+        labels: None,
+        max_nova_group: None,
+        min_eco_score_grade: None,
+        exclude_allergens: Vec::new(),
+        data_issues: Vec::new(),
+        fields: None,
+        request_status: None,
+        cursor: None,
+    };
+
+    // without the option, the accents must match
+    let backend = PostgresBackend::new(options.clone()).await.unwrap();
+    assert!(backend.new_product(&product).await.unwrap());
+    let products = backend
+        .query_products(&search("muesli"), false)
+        .await
+        .unwrap();
+    assert!(products.is_empty());
+
+    let backend = PostgresBackend::new(PostgresConfig {
+        unaccent_search: true,
+        ..options
+    })
+    .await
+    .unwrap();
+    for search_string in ["MÜSLI", "musli", "CAFE", "cafe creme"] {
+        let query = search(search_string);
+        let products = backend.query_products(&query, false).await.unwrap();
+        assert_eq!(products.len(), 1, "search for {}", search_string);
+        assert_eq!(products[0].info.id, product.info.id);
+        assert_eq!(backend.count_products(&query).await.unwrap(), 1);
+    }
+    let products = backend
+        .query_products(&search("müsli tea"), false)
+        .await
+        .unwrap();
+    assert!(products.is_empty());
+
+    // the umlauts of the search string also match their German transcription
+    let mut transcribed = product.clone();
+    transcribed.info.id = "unaccent-transcribed-product".to_string();
+    transcribed.info.name = "Bircher Muesli".to_string();
+    transcribed.info.producer = Some("Alpro".to_string());
+    assert!(backend.new_product(&transcribed).await.unwrap());
+
+    let query = search("Müsli");
+    let mut ids = backend
+        .query_products(&query, false)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|product| product.info.id)
+        .collect::<Vec<_>>();
+    ids.sort();
+    assert_eq!(
+        ids,
+        vec![product.info.id.clone(), transcribed.info.id.clone()]
+    );
+    assert_eq!(backend.count_products(&query).await.unwrap(), 2);
+
+    backend.delete_product(&product.info.id).await.unwrap();
+    backend.delete_product(&transcribed.info.id).await.unwrap();
+}
+
+/// Checks that the terms of a search string are matched according to the search term mode, for
+/// both the products and the product requests.
+///
+/// # Arguments
+/// - `options` - The options for connecting to the database.
+async fn search_terms_tests(options: PostgresConfig) {
+    let mut product = load_products().remove(0);
+    product.info.id = "search-terms-product".to_string();
+    product.info.name = "Vanilla Soy Drink".to_string();
+    product.info.producer = Some("Alpro".to_string());
+
+    let search = |search_string: &str| ProductQuery {
+        offset: 0,
+        limit: 10,
+        filter: SearchFilter::Search(search_string.to_string()),
+        sorting: None,
+        market: None,
+        producer: None,
+        quantity_type: None,
+        labels: None,
+        max_nova_group: None,
+        min_eco_score_grade: None,
+        exclude_allergens: Vec::new(),
+        data_issues: Vec::new(),
+        fields: None,
+        request_status: None,
+        cursor: None,
+    };
+
+    let backend = PostgresBackend::new(options.clone()).await.unwrap();
+    assert!(backend.new_product(&product).await.unwrap());
+    let request_id = backend
+        .request_new_product(&ProductRequest {
+            product_description: product.clone(),
+            date: Utc::now(),
+            status: RequestStatus::Pending,
+            review_comment: None,
+        })
+        .await
+        .unwrap();
+
+    let cases = [
+        (SearchTermMode::Phrase, "soy drink", true),
+        (SearchTermMode::Phrase, "alpro vanilla", false),
+        (SearchTermMode::All, "alpro vanilla", true),
+        (SearchTermMode::All, "  Vanilla   ALPRO ", true),
+        (SearchTermMode::All, "alpro chocolate", false),
+        (SearchTermMode::Any, "alpro chocolate", true),
+        (SearchTermMode::Any, "oat chocolate", false),
+    ];
+    for (mode, search_string, found) in cases {
+        let backend = PostgresBackend::new(PostgresConfig {
+            search_terms: mode,
+            ..options.clone()
+        })
+        .await
+        .unwrap();
+        let query = search(search_string);
+
+        let products = backend.query_products(&query, false).await.unwrap();
+        let found_product = products.iter().any(|p| p.info.id == product.info.id);
+        assert_eq!(
+            found_product, found,
+            "{:?} search for {}",
+            mode, search_string
+        );
+
+        let requests = backend.query_product_requests(&query, false).await.unwrap();
+        let found_request = requests.iter().any(|(id, _)| *id == request_id);
+        assert_eq!(
+            found_request, found,
+            "{:?} search for {}",
+            mode, search_string
+        );
+    }
+
+    backend.delete_requested_product(request_id).await.unwrap();
+    backend.delete_product(&product.info.id).await.unwrap();
+}
+
+/// Checks that the full-text search finds the products by the words of their name and producer
+/// in any order, by the stems of the language of their market and by word prefixes, and falls
+/// back to the trigram similarity for typos.
+///
+/// # Arguments
+/// - `options` - The options for connecting to the database.
+async fn full_text_search_tests(options: PostgresConfig) {
+    let backend = PostgresBackend::new(options).await.unwrap();
+
+    let mut yoghurt = load_products().remove(0);
+    yoghurt.info.id = "full-text-yoghurt".to_string();
+    yoghurt.info.name = "Griechischer Joghurt".to_string();
+    yoghurt.info.producer = Some("Gläserne Molkerei".to_string());
+    yoghurt.info.market = Some("DE".to_string());
+    let mut oats = load_products().remove(0);
+    oats.info.id = "full-text-oats".to_string();
+    oats.info.name = "Haferflocken Zart".to_string();
+    oats.info.producer = Some("Knusperone".to_string());
+    for product in [&yoghurt, &oats] {
+        assert!(backend.new_product(product).await.unwrap());
+    }
+
+    let search = |search_string: &str| ProductQuery {
+        offset: 0,
+        limit: 40,
+        filter: SearchFilter::Search(search_string.to_string()),
+        sorting: Some(Sorting {
+            order: SortingOrder::Descending,
+            field: SortingField::Similarity,
+        }),
+        market: None,
+        producer: None,
+        quantity_type: None,
+        labels: None,
+        max_nova_group: None,
+        min_eco_score_grade: None,
+        exclude_allergens: Vec::new(),
+        data_issues: Vec::new(),
+        fields: None,
+        request_status: None,
+        cursor: None,
+    };
+
+    let cases = [
+        ("joghurt griechischer", &yoghurt, true),
+        ("Molkerei JOGHURT", &yoghurt, true),
+        ("griech jog", &yoghurt, true),
+        ("griechischen joghurts", &yoghurt, true),
+        ("griechischer müsli", &yoghurt, false),
+        ("zart haferflocken", &oats, true),
+        ("haferfloccken", &oats, true),
+    ];
+    for (search_string, product, found) in cases {
+        let products = backend
+            .query_scored_products(&search(search_string), false)
+            .await
+            .unwrap();
+        assert_eq!(
+            products.iter().any(|(p, _)| p.info.id == product.info.id),
+            found,
+            "search for {}",
+            search_string
+        );
+    }
+
+    // the full-text matches are ranked above the products only found by the trigram fallback
+    let score = |products: &[(ProductDescription, f32)]| {
+        products
+            .iter()
+            .find(|(p, _)| p.info.id == oats.info.id)
+            .map(|(_, score)| *score)
+            .unwrap()
+    };
+    let exact = backend
+        .query_scored_products(&search("haferflocken"), false)
+        .await
+        .unwrap();
+    let typo = backend
+        .query_scored_products(&search("haferfloccken"), false)
+        .await
+        .unwrap();
+    assert!(score(&exact) >= 0.5 && score(&exact) <= 1.0);
+    assert!(score(&typo) > 0.0 && score(&typo) < 0.5);
+
+    for product in [&yoghurt, &oats] {
+        backend.delete_product(&product.info.id).await.unwrap();
+    }
+}
+
+/// Checks that the same product can be added once per market and filtered by the market.
+/// Must run after the other tests adding products, as the uniqueness key stays changed.
+///
+/// # Arguments
+/// - `options` - The options for connecting to the database.
+async fn market_tests(options: PostgresConfig) {
+    let backend = PostgresBackend::new(PostgresConfig {
+        market_specific_products: true,
+        ..options
+    })
+    .await
+    .unwrap();
+
+    let mut product = load_products().remove(0);
+    product.info.id = "market-product".to_string();
+    product.info.market = Some("DE".to_string());
+    assert!(backend.new_product(&product).await.unwrap());
+    assert!(!backend.new_product(&product).await.unwrap());
+
+    let mut french = product.clone();
+    french.info.market = Some("FR".to_string());
+    french.info.name = "Produit du marché".to_string();
+    assert!(backend.new_product(&french).await.unwrap());
+
+    let market_query = |market: &str| ProductQuery {
+        offset: 0,
+        limit: 10,
+        filter: SearchFilter::NoFilter,
+        sorting: None,
+        market: Some(market.to_string()),
+        producer: None,
+        quantity_type: None,
+        labels: None,
+        max_nova_group: None,
+        min_eco_score_grade: None,
+        exclude_allergens: Vec::new(),
+        data_issues: Vec::new(),
+        fields: None,
+        request_status: None,
+        cursor: None,
+    };
+
+    let products = backend
+        .query_products(&market_query("FR"), false)
+        .await
+        .unwrap();
+    assert_eq!(products.len(), 1);
+    assert_eq!(products[0].info.name, french.info.name);
+    assert_eq!(products[0].info.market, french.info.market);
+
+    let products = backend
+        .query_products(&market_query("DE"), false)
+        .await
+        .unwrap();
+    assert_eq!(products.len(), 1);
+    assert_eq!(products[0].info.name, product.info.name);
+
+    assert!(backend
+        .query_products(&market_query("IT"), false)
+        .await
+        .unwrap()
+        .is_empty());
+
+    // the update selects the product by its id and market and keeps the market
+    let mut updated = french.clone();
+    updated.info.name = "Produit mis à jour".to_string();
+    updated.preview = None;
+    updated.full_image = None;
+    assert!(backend.update_product(&updated).await.unwrap());
+    let products = backend
+        .query_products(&market_query("FR"), false)
+        .await
+        .unwrap();
+    assert_eq!(products.len(), 1);
+    assert_eq!(products[0].info.name, updated.info.name);
+    let products = backend
+        .query_products(&market_query("DE"), false)
+        .await
+        .unwrap();
+    assert_eq!(products.len(), 1);
+    assert_eq!(products[0].info.name, product.info.name);
+
+    updated.info.market = Some("IT".to_string());
+    assert!(!backend.update_product(&updated).await.unwrap());
+    assert!(backend
+        .query_products(&market_query("IT"), false)
+        .await
+        .unwrap()
+        .is_empty());
+
+    // products without market are still unique
+    let mut unknown_market = product.clone();
+    unknown_market.info.id = "market-product-unknown".to_string();
+    unknown_market.info.market = None;
+    assert!(backend.new_product(&unknown_market).await.unwrap());
+    assert!(!backend.new_product(&unknown_market).await.unwrap());
+
+    backend.delete_product(&product.info.id).await.unwrap();
+    backend
+        .delete_product(&unknown_market.info.id)
+        .await
+        .unwrap();
+    assert!(backend
+        .query_products(&market_query("FR"), false)
+        .await
+        .unwrap()
+        .is_empty());
+}
+
+/// Checks the monthly partitioning of the reported missing products and the retention.
+/// Must run after the other tests using the missing products, as the table stays partitioned.
+///
+/// # Arguments
+/// - `options` - The options for connecting to the database.
+async fn partitioning_tests(options: PostgresConfig) {
+    let url = format!(
+        "postgres://{}:{}@{}:{}/{}",
+        options.user,
+        options.password.secret().unwrap(),
+        options.host,
+        options.port,
+        options.dbname
+    );
+    let pool = sqlx::PgPool::connect(&url).await.unwrap();
+
+    let all_reports = MissingProductQuery {
+        limit: 200,
+        offset: 0,
+        product_id: None,
+        order: SortingOrder::Ascending,
+    };
+
+    // the retention is rejected for tables that are not partitioned
+    let result = PostgresBackend::new(PostgresConfig {
+        missing_products_retention_months: Some(2),
+        ..options.clone()
+    })
+    .await;
+    assert!(matches!(result, Err(Error::InvalidConfigError(_))));
+
+    // the existing reports are kept when partitioning the table
+    let backend = PostgresBackend::new(options.clone()).await.unwrap();
+    let reports = backend.query_missing_products(&all_reports).await.unwrap();
+
+    let partitioned_options = PostgresConfig {
+        partition_missing_products: true,
+        ..options.clone()
+    };
+    let backend = PostgresBackend::new(partitioned_options.clone())
+        .await
+        .unwrap();
+    assert_eq!(
+        backend.query_missing_products(&all_reports).await.unwrap(),
+        reports
+    );
+
+    let partitioned: bool = sqlx::query_scalar(
+        "select relkind = 'p' from pg_class where oid = 'reported_missing_products'::regclass;",
+    )
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    assert!(partitioned);
+
+    // run the regular tests on the partitioned table
+    sqlx::query("delete from reported_missing_products;")
+        .execute(&pool)
+        .await
+        .unwrap();
+    missing_product_tests(&backend).await;
+
+    // the table stays partitioned if the option is disabled again
+    let backend = PostgresBackend::new(options.clone()).await.unwrap();
+    backend
+        .report_missing_product(MissingProduct {
+            product_id: "future".to_string(),
+            date: Utc::now() + chrono::Duration::days(400),
+            store_id: None,
+        })
+        .await
+        .unwrap();
+
+    // reports older than the retention are dropped at startup
+    let now = Utc::now();
+    let report = |months: u32| MissingProduct {
+        product_id: format!("retention-{}", months),
+        date: truncate_datetime(now.checked_sub_months(Months::new(months)).unwrap()),
+        store_id: None,
+    };
+
+    let old_id = backend.report_missing_product(report(5)).await.unwrap();
+    let previous_id = backend.report_missing_product(report(1)).await.unwrap();
+    let current_id = backend.report_missing_product(report(0)).await.unwrap();
+    let photo = ProductImage {
+        content_type: "image/jpeg".to_string(),
+        data: vec![1, 2, 3],
+    };
+    assert!(backend
+        .set_missing_product_photo(old_id, &photo)
+        .await
+        .unwrap());
+
+    let backend = PostgresBackend::new(PostgresConfig {
+        missing_products_retention_months: Some(2),
+        ..partitioned_options
+    })
+    .await
+    .unwrap();
+    assert_eq!(backend.get_missing_product(old_id).await.unwrap(), None);
+    assert_eq!(
+        backend.get_missing_product_photo(old_id).await.unwrap(),
+        None
+    );
+    assert_eq!(
+        backend.get_missing_product(previous_id).await.unwrap(),
+        Some(report(1))
+    );
+
+    // two months later, only the reports of the current month are kept
+    let future = now.checked_add_months(Months::new(2)).unwrap();
+    assert!(
+        backend
+            .apply_missing_products_retention(future)
+            .await
+            .unwrap()
+            >= 1
+    );
+    assert_eq!(
+        backend.get_missing_product(previous_id).await.unwrap(),
+        None
+    );
+    assert_eq!(
+        backend.get_missing_product(current_id).await.unwrap(),
+        Some(report(0))
+    );
+}
+
+/// Checks that creating the backend fails for an incompatible database schema version and that
+/// the applied optional schema features are recorded.
+///
+/// # Arguments
+/// - `options` - The options for connecting to the database.
+async fn schema_version_tests(options: PostgresConfig) {
+    let url = format!(
+        "postgres://{}:{}@{}:{}/{}",
+        options.user,
+        options.password.secret().unwrap(),
+        options.host,
+        options.port,
+        options.dbname
+    );
+    let pool = sqlx::PgPool::connect(&url).await.unwrap();
+
+    // downgrade the schema version and make sure the backend refuses to start
+    sqlx::query("update schema_version set version = $1;")
+        .bind(SCHEMA_VERSION - 1)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    match PostgresBackend::new(options.clone()).await {
+        Err(Error::IncompatibleSchemaVersion { found, expected }) => {
+            assert_eq!(found, SCHEMA_VERSION - 1);
+            assert_eq!(expected, SCHEMA_VERSION);
+        }
+        Err(err) => panic!("Unexpected error: {}", err),
+        Ok(_) => panic!("Backend must not start with an incompatible schema version"),
+    }
+
+    // restore the schema version
+    sqlx::query("update schema_version set version = $1;")
+        .bind(SCHEMA_VERSION)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    PostgresBackend::new(options.clone()).await.unwrap();
+
+    // the enabled optional features are recorded with the schema version they were applied to
+    let options = PostgresConfig {
+        price_tracking: true,
+        ..options
+    };
+    PostgresBackend::new(options.clone()).await.unwrap();
+
+    let feature_version = |name: &'static str| {
+        sqlx::query_scalar::<_, i32>("select version from schema_features where name = $1;")
+            .bind(name)
+            .fetch_optional(&pool)
+    };
+    assert_eq!(
+        feature_version("price_tracking").await.unwrap(),
+        Some(SCHEMA_VERSION)
+    );
+    assert_eq!(
+        feature_version("partitioned_missing_products")
+            .await
+            .unwrap(),
+        Some(SCHEMA_VERSION)
+    );
+
+    // a feature applied to an older schema version is applied again
+    sqlx::query("update schema_features set version = $1 where name = 'price_tracking';")
+        .bind(SCHEMA_VERSION - 1)
+        .execute(&pool)
+        .await
+        .unwrap();
+    PostgresBackend::new(options).await.unwrap();
+    assert_eq!(
+        feature_version("price_tracking").await.unwrap(),
+        Some(SCHEMA_VERSION)
+    );
+}
+
+/// Checks that the backend waits for the migration lock held by another instance and that
+/// instances starting simultaneously do not interfere.
+///
+/// # Arguments
+/// - `options` - The options for connecting to the database.
+async fn migration_lock_tests(options: PostgresConfig) {
+    let url = format!(
+        "postgres://{}:{}@{}:{}/{}",
+        options.user,
+        options.password.secret().unwrap(),
+        options.host,
+        options.port,
+        options.dbname
+    );
+    let mut connection = sqlx::PgConnection::connect(&url).await.unwrap();
+
+    // hold the lock like another instance applying migrations
+    sqlx::query("select pg_advisory_lock($1);")
+        .bind(MIGRATION_LOCK_KEY)
+        .execute(&mut connection)
+        .await
+        .unwrap();
+
+    let startup = tokio::spawn(PostgresBackend::new(options.clone()));
+    tokio::time::sleep(std::time::Duration::from_millis(300)).await;
+    assert!(!startup.is_finished());
+
+    sqlx::query("select pg_advisory_unlock($1);")
+        .bind(MIGRATION_LOCK_KEY)
+        .execute(&mut connection)
+        .await
+        .unwrap();
+    startup.await.unwrap().unwrap();
+
+    // the backend released the lock after the migrations
+    let released: bool = sqlx::query_scalar("select pg_try_advisory_lock($1);")
+        .bind(MIGRATION_LOCK_KEY)
+        .fetch_one(&mut connection)
+        .await
+        .unwrap();
+    assert!(released);
+    sqlx::query("select pg_advisory_unlock($1);")
+        .bind(MIGRATION_LOCK_KEY)
+        .execute(&mut connection)
+        .await
+        .unwrap();
+
+    // multiple instances starting simultaneously
+    let backends =
+        futures::future::join_all((0..4).map(|_| PostgresBackend::new(options.clone()))).await;
+    for backend in backends {
+        simple_ops(&backend.unwrap()).await;
+    }
+}
+
+/// Runs the tests of seeding the database with generated products.
+///
+/// # Arguments
+/// - `options` - The options for connecting to the database.
+#[cfg(feature = "generator")]
+async fn generator_tests(options: PostgresConfig) {
+    let backend = PostgresBackend::new(options).await.unwrap();
+
+    let mut generator = ProductGenerator::new(4453).with_images(true);
+    assert_eq!(
+        generate_products(&backend, &mut generator, 25)
+            .await
+            .unwrap(),
+        25
+    );
+
+    // the same seed generates the same ids, which are skipped
+    let mut generator = ProductGenerator::new(4453).with_images(true);
+    assert_eq!(
+        generate_products(&backend, &mut generator, 25)
+            .await
+            .unwrap(),
+        0
+    );
+
+    for product in ProductGenerator::new(4453).with_images(true).take(25) {
+        let stored = backend
+            .get_product(&product.info.id, true)
+            .await
+            .unwrap()
+            .unwrap();
+        compare_product_description(&stored, &product, true);
+
+        let image = backend.get_product_image(&product.info.id).await.unwrap();
+        assert_eq!(image, product.full_image);
+
+        backend.delete_product(&product.info.id).await.unwrap();
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_postgres_backend() {
+    init_logger();
+
+    // check if the TEST_DATABASE_URL environment variable is set
+    if std::env::var("TEST_DATABASE_URL").is_ok() {
+        info!("TEST_DATABASE_URL has been provided, skipping docker test and using the provided database");
+        let options = PostgresConfig {
+            host: "localhost".to_string(),
+            port: 5432,
+            dbname: "postgres".to_string(),
+            user: "postgres".to_string(),
+            password: Secret::from_str("postgres").unwrap(),
+            password_file: None,
+            max_connections: 5,
+            pgbouncer_mode: false,
+            schema: None,
+            application_name: None,
+            instance_id: None,
+            partition_missing_products: false,
+            missing_products_retention_months: None,
+            price_tracking: true,
+            market_specific_products: false,
+            unaccent_search: false,
+            search_terms: SearchTermMode::All,
+            image_store: ImageStoreConfig::default(),
+        };
+
+        let postgres_backend = PostgresBackend::new(options.clone()).await.unwrap();
+
+        info!("Running backend tests...");
+        backend_tests(postgres_backend).await;
+        info!("Running backend tests...SUCCESS");
+
+        info!("Running boxed backend tests...");
+        boxed_backend_tests(options.clone()).await;
+        info!("Running boxed backend tests...SUCCESS");
+
+        info!("Running health check tests...");
+        health_check_tests(options.clone()).await;
+        info!("Running health check tests...SUCCESS");
+
+        info!("Running price tracking disabled tests...");
+        price_tracking_disabled_tests(options.clone()).await;
+        info!("Running price tracking disabled tests...SUCCESS");
+
+        info!("Running stable ordering tests...");
+        stable_ordering_tests(options.clone()).await;
+        info!("Running stable ordering tests...SUCCESS");
+
+        info!("Running cursor pagination tests...");
+        cursor_pagination_tests(options.clone()).await;
+        info!("Running cursor pagination tests...SUCCESS");
+
+        info!("Running unaccent search tests...");
+        unaccent_search_tests(options.clone()).await;
+        info!("Running unaccent search tests...SUCCESS");
+
+        info!("Running migration lock tests...");
+        migration_lock_tests(options.clone()).await;
+        info!("Running migration lock tests...SUCCESS");
+
+        info!("Running reconnect tests...");
+        reconnect_tests(options.clone()).await;
+        info!("Running reconnect tests...SUCCESS");
+
+        info!("Running application name tests...");
+        application_name_tests(options.clone()).await;
+        info!("Running application name tests...SUCCESS");
+
+        info!("Running PgBouncer mode tests...");
+        pgbouncer_mode_tests(options.clone()).await;
+        info!("Running PgBouncer mode tests...SUCCESS");
+
+        info!("Running custom schema tests...");
+        custom_schema_tests(options.clone()).await;
+        info!("Running custom schema tests...SUCCESS");
+
+        info!("Running mirrored backend tests...");
+        mirrored_backend_tests(options.clone()).await;
+        info!("Running mirrored backend tests...SUCCESS");
+
+        info!("Running duplicate product tests...");
+        duplicate_product_tests(options.clone()).await;
+        info!("Running duplicate product tests...SUCCESS");
+
+        info!("Running image store tests...");
+        image_store_tests(options.clone()).await;
+        info!("Running image store tests...SUCCESS");
+
+        info!("Running search terms tests...");
+        search_terms_tests(options.clone()).await;
+        info!("Running search terms tests...SUCCESS");
+
+        info!("Running full-text search tests...");
+        full_text_search_tests(options.clone()).await;
+        info!("Running full-text search tests...SUCCESS");
+
+        info!("Running market tests...");
+        market_tests(options.clone()).await;
+        info!("Running market tests...SUCCESS");
+
+        #[cfg(feature = "generator")]
+        {
+            info!("Running generator tests...");
+            generator_tests(options.clone()).await;
+            info!("Running generator tests...SUCCESS");
+        }
+
+        info!("Running partitioning tests...");
+        partitioning_tests(options.clone()).await;
+        info!("Running partitioning tests...SUCCESS");
+
+        info!("Running schema version tests...");
+        schema_version_tests(options).await;
+        info!("Running schema version tests...SUCCESS");
+
+        return;
+    }
+
+    // Define our test instance
+    let mut test = DockerTest::new();
+
+    let image: Image = Image::with_repository("postgres")
+        .pull_policy(dockertest::PullPolicy::IfNotPresent)
+        .source(dockertest::Source::DockerHub)
+        .tag("16");
+
+    // define the postgres container
+    let mut postgres = TestBodySpecification::with_image(image).set_publish_all_ports(true);
+
+    // set the environment variables for the postgres container
+    postgres
+        .modify_env("POSTGRES_USER", "postgres")
+        .modify_env("POSTGRES_PASSWORD", "password");
+
+    let mut postgres = postgres.set_log_options(Some(LogOptions {
+        action: LogAction::ForwardToStdOut,
+        policy: LogPolicy::Always,
+        source: LogSource::Both,
+    }));
+
+    // create a temporary file to store the database schema
+    let schema = include_str!("../../docker/db/init.sql");
+    let mut init_file = temp_dir();
+    init_file.push("init.sql");
+    std::fs::write(&init_file, schema).unwrap(); // write the schema to a file
+
+    // bind the schema file to the postgres container
+    postgres.modify_bind_mount(
+        init_file.to_string_lossy(),
+        "/docker-entrypoint-initdb.d/init.sql",
+    );
+
+    // run the postgres container
+    test.provide_container(postgres);
+
+    test.run_async(|ops| async move {
+        let container = ops.handle("postgres");
+
+        // wait about 5 seconds for postgres to start
+        info!("Waiting for postgres to start...");
+        tokio::time::sleep(tokio::time::Duration::from_secs(5)).await;
+        info!("Waiting for postgres to start...DONE");
+
+        let (ip, port) = container.host_port(5432).unwrap();
+        info!("postgres running at {}:{}", ip, port);
+
+        let options = PostgresConfig {
+            host: "localhost".to_string(),
+            port: *port as u16,
+            dbname: "postgres".to_string(),
+            user: "postgres".to_string(),
+            password: Secret::from_str("password").unwrap(),
+            password_file: None,
+            max_connections: 5,
+            pgbouncer_mode: false,
+            schema: None,
+            application_name: None,
+            instance_id: None,
+            partition_missing_products: false,
+            missing_products_retention_months: None,
+            price_tracking: true,
+            market_specific_products: false,
+            unaccent_search: false,
+            search_terms: SearchTermMode::All,
+            image_store: ImageStoreConfig::default(),
+        };
+
+        info!("Creating PostgresBackend instance...");
+        let postgres_backend = PostgresBackend::new(options.clone()).await.unwrap();
         info!("Creating PostgresBackend instance...DONE");
 
         info!("Running backend tests...");
         backend_tests(postgres_backend).await;
         info!("Running backend tests...SUCCESS");
+
+        info!("Running boxed backend tests...");
+        boxed_backend_tests(options.clone()).await;
+        info!("Running boxed backend tests...SUCCESS");
+
+        info!("Running reconnect tests...");
+        reconnect_tests(options.clone()).await;
+        info!("Running reconnect tests...SUCCESS");
+
+        info!("Running application name tests...");
+        application_name_tests(options.clone()).await;
+        info!("Running application name tests...SUCCESS");
+
+        info!("Running PgBouncer mode tests...");
+        pgbouncer_mode_tests(options.clone()).await;
+        info!("Running PgBouncer mode tests...SUCCESS");
+
+        info!("Running custom schema tests...");
+        custom_schema_tests(options.clone()).await;
+        info!("Running custom schema tests...SUCCESS");
+
+        info!("Running mirrored backend tests...");
+        mirrored_backend_tests(options.clone()).await;
+        info!("Running mirrored backend tests...SUCCESS");
+
+        info!("Running duplicate product tests...");
+        duplicate_product_tests(options.clone()).await;
+        info!("Running duplicate product tests...SUCCESS");
+
+        info!("Running image store tests...");
+        image_store_tests(options.clone()).await;
+        info!("Running image store tests...SUCCESS");
+
+        info!("Running search terms tests...");
+        search_terms_tests(options.clone()).await;
+        info!("Running search terms tests...SUCCESS");
+
+        info!("Running full-text search tests...");
+        full_text_search_tests(options.clone()).await;
+        info!("Running full-text search tests...SUCCESS");
+
+        info!("Running market tests...");
+        market_tests(options.clone()).await;
+        info!("Running market tests...SUCCESS");
+
+        #[cfg(feature = "generator")]
+        {
+            info!("Running generator tests...");
+            generator_tests(options.clone()).await;
+            info!("Running generator tests...SUCCESS");
+        }
+
+        info!("Running partitioning tests...");
+        partitioning_tests(options.clone()).await;
+        info!("Running partitioning tests...SUCCESS");
+
+        info!("Running schema version tests...");
+        schema_version_tests(options).await;
+        info!("Running schema version tests...SUCCESS");
     })
     .await;
 }
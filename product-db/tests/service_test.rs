@@ -1,4 +1,11 @@
-use std::{collections::HashSet, env::temp_dir, str::FromStr, sync::Arc};
+use std::{
+    collections::HashSet,
+    env::temp_dir,
+    io::{Cursor, Read},
+    str::FromStr,
+    sync::Arc,
+    time::Duration,
+};
 
 use chrono::{DateTime, Utc};
 use dockertest::{
@@ -6,12 +13,28 @@ use dockertest::{
 };
 use log::{debug, info};
 use product_db::{
-    service_json::*, DBId, DataBackend, EndpointOptions, MissingProduct, MissingProductQuery,
-    Nutrients, Options, PostgresBackend, PostgresConfig, ProductDescription, ProductID,
-    ProductImage, ProductQuery, ProductRequest, SearchFilter, Secret, Service, Sorting,
-    SortingField, SortingOrder, Weight,
+    image_file_extension, service_json::*, AdditionalImage, AdminAllowlistOptions, ArchiveOptions,
+    ArchiveQuery, ArchivedProductRequest, AuthOptions, CacheControlOptions, CredentialsSource,
+    DBId, DataBackend, EcoScore, EcoScoreGrade, EndpointOptions, Error, HealthReport, HealthStatus,
+    ImageQuotaOptions, ImageRole, ImageStoreConfig, MissingProduct, MissingProductQuery,
+    NovaClassifier, NutrientIssue, Nutrients, Options, Packaging, PackagingMaterial, Portion,
+    PostgresBackend, PostgresConfig, PreviewGenerator, PreviewOptions, PriceObservation,
+    PriceQuery, ProductDataIssue, ProductDescription, ProductField, ProductID, ProductImage,
+    ProductQuery, ProductRequest, RateLimitOptions, ReadBackend, ReadOnlyBackend, Recipe,
+    RequestOutcome, RequestSigner, RequestSigningOptions, RequestStatus, SearchFilter,
+    SearchTermMode, Secret, Service, Sorting, SortingField, SortingOrder, Store, StoreQuery,
+    TlsOptions, Weight, WriteBackend, API_KEY_HEADER, HTTP_REQUEST_DURATION, HTTP_REQUEST_ERRORS,
+    MAX_ADDITIONAL_IMAGES, MAX_MISSING_PRODUCTS_BATCH_SIZE, MAX_PRODUCT_ID_LENGTH,
+    POOL_ACQUIRE_DURATION, POOL_CONNECTIONS, POOL_IDLE_CONNECTIONS, POOL_MAX_CONNECTIONS,
+    POOL_WAITING_ACQUIRES, SIGNATURE_HEADER, SIGNATURE_TIMESTAMP_HEADER, TOTAL_COUNT_HEADER,
+};
+use reqwest::{
+    header::{
+        ACCEPT, ACCESS_CONTROL_ALLOW_ORIGIN, ACCESS_CONTROL_REQUEST_METHOD, CACHE_CONTROL,
+        CONTENT_DISPOSITION, CONTENT_TYPE, LINK, ORIGIN, RETRY_AFTER,
+    },
+    StatusCode, Url,
 };
-use reqwest::{header::CONTENT_TYPE, StatusCode, Url};
 
 /// Truncates the given datetime to seconds.
 /// This is being done for comparison reasons.
@@ -170,6 +193,10 @@ fn compare_product_info(lhs: &ProductDescription, rhs: &ProductDescription) {
     assert_eq!(lhs.info.producer, rhs.info.producer);
     assert_eq!(lhs.info.quantity_type, rhs.info.quantity_type);
     assert_eq!(lhs.info.volume_weight_ratio, rhs.info.volume_weight_ratio);
+    assert_eq!(lhs.info.market, rhs.info.market);
+    assert_eq!(lhs.info.labels, rhs.info.labels);
+    assert_eq!(lhs.info.nova_group, rhs.info.nova_group);
+    assert_eq!(lhs.info.allergens, rhs.info.allergens);
 }
 
 /// Compares the product requests of two products.
@@ -216,6 +243,8 @@ fn compare_product_description(
 ) {
     compare_product_info(lhs, rhs);
     check_compare_nutrients(&lhs.nutrients, &rhs.nutrients);
+    assert_eq!(lhs.packaging, rhs.packaging);
+    assert_eq!(lhs.eco_score, rhs.eco_score);
 
     if check_preview {
         assert_eq!(lhs.preview, rhs.preview);
@@ -318,6 +347,92 @@ impl ServiceClient {
         response.product_request
     }
 
+    /// Approves the product request with the given id and returns the status code together
+    /// with the id of the added product.
+    ///
+    /// # Arguments
+    /// - `id` - The id of the product request to approve.
+    pub async fn approve_product_request(&self, id: DBId) -> (StatusCode, Option<ProductID>) {
+        let url = self
+            .server_address
+            .join(&format!("admin/product_request/{}/approve", id))
+            .unwrap();
+        debug!("POST: {}", url);
+
+        let response = self.client.post(url).send().await.unwrap();
+        let status = response.status();
+        let response: ApproveProductRequestResponse = response.json().await.unwrap();
+
+        (status, response.product_id)
+    }
+
+    /// Rejects the product request with the given id and returns the status code.
+    ///
+    /// # Arguments
+    /// - `id` - The id of the product request to reject.
+    /// - `comment` - The optional review comment.
+    pub async fn reject_product_request(&self, id: DBId, comment: Option<&str>) -> StatusCode {
+        let url = self
+            .server_address
+            .join(&format!("admin/product_request/{}/reject", id))
+            .unwrap();
+        debug!("POST: {}", url);
+
+        let response = self
+            .client
+            .post(url)
+            .json(&RejectProductRequestRequest {
+                comment: comment.map(|c| c.to_string()),
+            })
+            .send()
+            .await
+            .unwrap();
+
+        response.status()
+    }
+
+    /// Archives the product request with the given id and returns the status code.
+    ///
+    /// # Arguments
+    /// - `id` - The id of the product request to archive.
+    /// - `outcome` - The outcome of the product request.
+    pub async fn archive_product_request(&self, id: DBId, outcome: RequestOutcome) -> StatusCode {
+        let url = self
+            .server_address
+            .join(&format!("admin/product_request/{}/archive", id))
+            .unwrap();
+        debug!("POST: {}", url);
+
+        let response = self
+            .client
+            .post(url)
+            .json(&ArchiveProductRequestRequest {
+                outcome,
+                carry_over_images: false,
+            })
+            .send()
+            .await
+            .unwrap();
+
+        response.status()
+    }
+
+    /// Searches the archived product requests.
+    ///
+    /// # Arguments
+    /// - `query` - The query to use.
+    pub async fn query_archive(&self, query: &ArchiveQuery) -> Vec<ArchivedProductRequest> {
+        let url = self.server_address.join("admin/archive/query").unwrap();
+        debug!("POST: {}", url);
+
+        let response = self.client.post(url).json(query).send().await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response: ArchiveQueryResponse = response.json().await.unwrap();
+
+        response.archived_product_requests
+    }
+
     /// Queries the product requests.
     ///
     /// # Arguments
@@ -382,7 +497,11 @@ impl ServiceClient {
 
         debug!("POST: {}", url);
 
-        let missing_product = MissingProductReportRequest { product_id };
+        let missing_product = MissingProductReportRequest {
+            product_id,
+            store_id: None,
+            photo: None,
+        };
 
         let response = self
             .client
@@ -686,6 +805,61 @@ impl ServiceClient {
             data: image_data,
         })
     }
+
+    /// Switches the read-only (maintenance) mode of the service and returns the new mode.
+    ///
+    /// # Arguments
+    /// - `read_only` - Whether the service should be in read-only mode.
+    pub async fn set_maintenance_mode(&self, read_only: bool) -> bool {
+        let url = self.server_address.join("admin/maintenance").unwrap();
+        debug!("PUT: {}", url);
+
+        let response = self
+            .client
+            .put(url)
+            .json(&MaintenanceModeRequest { read_only })
+            .send()
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let response: MaintenanceModeResponse = response.json().await.unwrap();
+
+        response.read_only
+    }
+
+    /// Returns the current read-only (maintenance) mode of the service.
+    pub async fn get_maintenance_mode(&self) -> bool {
+        let url = self.server_address.join("admin/maintenance").unwrap();
+        debug!("GET: {}", url);
+
+        let response = self.client.get(url).send().await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let response: MaintenanceModeResponse = response.json().await.unwrap();
+
+        response.read_only
+    }
+
+    /// Creates a signed url for the public image endpoint and returns it.
+    ///
+    /// # Arguments
+    /// - `product_id` - The id of the product whose image is made accessible.
+    pub async fn get_signed_image_url(&self, product_id: &ProductID) -> String {
+        let url = self
+            .server_address
+            .join(&format!("admin/product/{}/signed_image_url", product_id))
+            .unwrap();
+        debug!("GET: {}", url);
+
+        let response = self.client.get(url).send().await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let response: SignedImageUrlResponse = response.json().await.unwrap();
+        assert!(response.expires.unwrap() > Utc::now());
+
+        response.url.unwrap()
+    }
 }
 
 /// Runs the missing product tests against the service instance.
@@ -731,6 +905,102 @@ async fn missing_product_tests(options: &EndpointOptions) {
         compare_missing_products(&lhs.1, rhs);
     }
 
+    // count the reported missing products
+    let response = client
+        .client
+        .post(
+            client
+                .server_address
+                .join("admin/missing_products/count")
+                .unwrap(),
+        )
+        .json(&MissingProductQuery {
+            limit: 1,
+            offset: 0,
+            product_id: None,
+            order: SortingOrder::Ascending,
+        })
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let response: CountResponse = response.json().await.unwrap();
+    assert_eq!(response.count, products_to_report.len() as u64);
+
+    // summarize the reported missing products, 'foobar' has been reported 3 times
+    let response = client
+        .client
+        .get(
+            client
+                .server_address
+                .join("admin/missing_products/summary?limit=1")
+                .unwrap(),
+        )
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(response.headers().contains_key(LINK));
+    let response: MissingProductsSummaryResponse = response.json().await.unwrap();
+    assert_eq!(response.missing_products.len(), 1);
+    assert_eq!(response.missing_products[0].product_id, "foobar");
+    assert_eq!(response.missing_products[0].reports, 3);
+
+    // export the reported missing products as CSV in descending order
+    let response = client
+        .client
+        .get(
+            client
+                .server_address
+                .join("admin/missing_products/export?format=csv&order=desc")
+                .unwrap(),
+        )
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()[CONTENT_TYPE], "text/csv; charset=utf-8");
+    let csv = response.text().await.unwrap();
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(lines[0], "id,product_id,date,store_id");
+    assert_eq!(lines.len(), products_to_report.len() + 1);
+    let (last_id, last_product) = missing_products.last().unwrap();
+    assert!(lines[1].starts_with(&format!("{},{},", last_id, last_product.product_id)));
+
+    // the export respects the product id filter and rejects unknown formats
+    let url = client
+        .server_address
+        .join(&format!(
+            "admin/missing_products/export?format=csv&product_id={}",
+            last_product.product_id
+        ))
+        .unwrap();
+    let csv = client
+        .client
+        .get(url)
+        .send()
+        .await
+        .unwrap()
+        .text()
+        .await
+        .unwrap();
+    assert!(csv
+        .lines()
+        .skip(1)
+        .all(|line| line.split(',').nth(1) == Some(last_product.product_id.as_str())));
+    let response = client
+        .client
+        .get(
+            client
+                .server_address
+                .join("admin/missing_products/export?format=xlsx")
+                .unwrap(),
+        )
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
     // use the get_missing_product method to check if the reported missing products are the same as the inserted ones
     for (id, product) in missing_products.iter() {
         let missing_product = client.get_missing_product(*id).await;
@@ -826,6 +1096,142 @@ async fn missing_product_tests(options: &EndpointOptions) {
     assert!(foobar_products.iter().all(|p| p.1.product_id == "foobar"));
 }
 
+/// Runs the tests for the photos of reported missing products.
+///
+/// # Arguments
+/// - `options` - The endpoint options.
+async fn missing_product_photo_tests(options: &EndpointOptions) {
+    let client = ServiceClient::new(options.address.clone());
+    let photo = ProductImage {
+        content_type: "image/jpeg".to_string(),
+        data: vec![7, 8, 9],
+    };
+
+    let response = client
+        .client
+        .post(client.server_address.join("user/missing_products").unwrap())
+        .json(&MissingProductReportRequest {
+            product_id: "missing-product-photo-test".to_string(),
+            store_id: None,
+            photo: Some(photo.clone()),
+        })
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::CREATED);
+    let response: MissingProductReportResponse = response.json().await.unwrap();
+    let id = response.id.unwrap();
+
+    // the photo is retrievable by the curation team
+    let photo_url = client
+        .server_address
+        .join(&format!("admin/missing_products/{}/photo", id))
+        .unwrap();
+    let response = client.client.get(photo_url.clone()).send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()[CONTENT_TYPE], "image/jpeg");
+    assert_eq!(
+        response.bytes().await.unwrap().as_ref(),
+        photo.data.as_slice()
+    );
+
+    // reports without a photo have none
+    let (other_id, _) = client
+        .report_missing_product("missing-product-photo-test".to_string())
+        .await;
+    let response = client
+        .client
+        .get(
+            client
+                .server_address
+                .join(&format!("admin/missing_products/{}/photo", other_id))
+                .unwrap(),
+        )
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    // the photo is deleted together with the report
+    client.delete_reported_missing_product(id).await;
+    client.delete_reported_missing_product(other_id).await;
+    let response = client.client.get(photo_url).send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+/// Runs the tests for reporting several missing products at once.
+///
+/// # Arguments
+/// - `options` - The endpoint options.
+async fn missing_products_batch_tests(options: &EndpointOptions) {
+    let client = ServiceClient::new(options.address.clone());
+    let url = client
+        .server_address
+        .join("user/missing_products/batch")
+        .unwrap();
+
+    let report = |product_ids: Vec<String>, store_id: Option<DBId>| {
+        client
+            .client
+            .post(url.clone())
+            .json(&MissingProductsBatchReportRequest {
+                product_ids,
+                store_id,
+            })
+            .send()
+    };
+    let count_reports = |product_id: &str| {
+        let client = &client;
+        let query = MissingProductQuery {
+            limit: 40,
+            offset: 0,
+            product_id: Some(product_id.to_string()),
+            order: SortingOrder::Ascending,
+        };
+        async move { client.query_missing_products(&query).await }
+    };
+
+    // invalid product ids are rejected individually
+    let product_ids = vec![
+        "batch-1".to_string(),
+        " ".to_string(),
+        "batch-2".to_string(),
+        "x".repeat(MAX_PRODUCT_ID_LENGTH + 1),
+        "batch-1".to_string(),
+    ];
+    let response = report(product_ids.clone(), None).await.unwrap();
+    assert_eq!(response.status(), StatusCode::CREATED);
+    let response: MissingProductsBatchReportResponse = response.json().await.unwrap();
+    assert_eq!(response.results.len(), product_ids.len());
+    for (result, product_id) in response.results.iter().zip(product_ids.iter()) {
+        assert_eq!(&result.product_id, product_id);
+    }
+    let accepted: Vec<bool> = response.results.iter().map(|r| r.id.is_some()).collect();
+    assert_eq!(accepted, vec![true, false, true, false, true]);
+
+    assert_eq!(count_reports("batch-1").await.len(), 2);
+    assert_eq!(count_reports("batch-2").await.len(), 1);
+    assert_eq!(
+        Some(count_reports("batch-2").await[0].0),
+        response.results[2].id
+    );
+
+    // the batch is stored in a single transaction, i.e., nothing is stored for an unknown store
+    let response = report(vec!["batch-3".to_string()], Some(999_999))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert!(count_reports("batch-3").await.is_empty());
+
+    // empty and oversized batches are rejected
+    for num_ids in [0, MAX_MISSING_PRODUCTS_BATCH_SIZE + 1] {
+        let product_ids = vec!["batch-4".to_string(); num_ids];
+        let response = report(product_ids, None).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+    assert!(count_reports("batch-4").await.is_empty());
+}
+
 /// Runs the product requests tests against the service.
 ///
 /// # Arguments
@@ -848,6 +1254,8 @@ async fn product_requests_tests(options: &EndpointOptions) {
         product_requests.push(ProductRequest {
             date,
             product_description: product_desc.clone(),
+            status: RequestStatus::Pending,
+            review_comment: None,
         });
 
         product_requests_with_ids.push((id, product_requests.last().unwrap().clone()));
@@ -910,6 +1318,17 @@ async fn product_requests_tests(options: &EndpointOptions) {
                 modified_product_request.product_description.info.id.clone(),
             ),
             sorting: None,
+            market: None,
+            producer: None,
+            quantity_type: None,
+            labels: None,
+            max_nova_group: None,
+            min_eco_score_grade: None,
+            exclude_allergens: Vec::new(),
+            data_issues: Vec::new(),
+            fields: None,
+            request_status: None,
+            cursor: None,
         })
         .await;
 
@@ -962,6 +1381,89 @@ async fn query_product_requests_tests(
 ) {
     info!("Querying product requests tests...");
 
+    // export all product requests, i.e., regardless of the offset and limit
+    let response = client
+        .client
+        .post(
+            client
+                .server_address
+                .join("admin/product_request/export?format=csv")
+                .unwrap(),
+        )
+        .json(&ProductQuery {
+            limit: 1,
+            offset: 1,
+            filter: SearchFilter::NoFilter,
+            sorting: None,
+            market: None,
+            producer: None,
+            quantity_type: None,
+            labels: None,
+            max_nova_group: None,
+            min_eco_score_grade: None,
+            exclude_allergens: Vec::new(),
+            data_issues: Vec::new(),
+            fields: Some(vec![ProductField::Id]),
+            request_status: None,
+            cursor: None,
+        })
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let csv = response.text().await.unwrap();
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(lines[0], "request_id,date,id");
+    assert_eq!(lines.len(), product_requests.len() + 1);
+    for ((id, request), line) in product_requests.iter().zip(&lines[1..]) {
+        assert!(line.starts_with(&format!("{},", id)));
+        assert!(line.ends_with(&format!(",{}", request.product_description.info.id)));
+    }
+
+    // export all product requests as zip archive including their images
+    let response = client
+        .client
+        .get(
+            client
+                .server_address
+                .join("admin/product_request/export?format=zip")
+                .unwrap(),
+        )
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()[CONTENT_TYPE], "application/zip");
+    let archive = response.bytes().await.unwrap();
+    let mut archive = zip::ZipArchive::new(Cursor::new(archive)).unwrap();
+    for (id, request) in product_requests {
+        let mut json = String::new();
+        archive
+            .by_name(&format!("{}/request.json", id))
+            .unwrap()
+            .read_to_string(&mut json)
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(json["id"], *id);
+        let description: ProductDescription =
+            serde_json::from_value(json["product_description"].clone()).unwrap();
+        assert_eq!(description.info, request.product_description.info);
+
+        if let Some(full_image) = request.product_description.full_image.as_ref() {
+            let mut data = Vec::new();
+            archive
+                .by_name(&format!(
+                    "{}/full_image.{}",
+                    id,
+                    image_file_extension(&full_image.content_type)
+                ))
+                .unwrap()
+                .read_to_end(&mut data)
+                .unwrap();
+            assert_eq!(data, full_image.data);
+        }
+    }
+
     // query all product requests and check if they are the same as the inserted ones
     for with_preview in [true, false] {
         let out_products: Vec<(DBId, ProductRequest)> = client
@@ -970,6 +1472,17 @@ async fn query_product_requests_tests(
                 offset: 0,
                 filter: SearchFilter::NoFilter,
                 sorting: None,
+                market: None,
+                producer: None,
+                quantity_type: None,
+                labels: None,
+                max_nova_group: None,
+                min_eco_score_grade: None,
+                exclude_allergens: Vec::new(),
+                data_issues: Vec::new(),
+                fields: None,
+                request_status: None,
+                cursor: None,
             })
             .await;
 
@@ -1027,6 +1540,17 @@ async fn query_product_requests_tests(
                     offset: *offset,
                     filter: SearchFilter::NoFilter,
                     sorting: *sorting,
+                    market: None,
+                    producer: None,
+                    quantity_type: None,
+                    labels: None,
+                    max_nova_group: None,
+                    min_eco_score_grade: None,
+                    exclude_allergens: Vec::new(),
+                    data_issues: Vec::new(),
+                    fields: None,
+                    request_status: None,
+                    cursor: None,
                 })
                 .await;
 
@@ -1087,6 +1611,17 @@ async fn query_product_requests_tests(
                     order: SortingOrder::Descending,
                     field: SortingField::Similarity,
                 }),
+                market: None,
+                producer: None,
+                quantity_type: None,
+                labels: None,
+                max_nova_group: None,
+                min_eco_score_grade: None,
+                exclude_allergens: Vec::new(),
+                data_issues: Vec::new(),
+                fields: None,
+                request_status: None,
+                cursor: None,
             })
             .await;
 
@@ -1119,6 +1654,17 @@ async fn query_products_tests(client: &ServiceClient, products: &[ProductDescrip
             offset: 0,
             filter: SearchFilter::NoFilter,
             sorting: None,
+            market: None,
+            producer: None,
+            quantity_type: None,
+            labels: None,
+            max_nova_group: None,
+            min_eco_score_grade: None,
+            exclude_allergens: Vec::new(),
+            data_issues: Vec::new(),
+            fields: None,
+            request_status: None,
+            cursor: None,
         })
         .await;
 
@@ -1157,6 +1703,17 @@ async fn query_products_tests(client: &ServiceClient, products: &[ProductDescrip
                 offset: *offset,
                 filter: SearchFilter::NoFilter,
                 sorting: *sorting,
+                market: None,
+                producer: None,
+                quantity_type: None,
+                labels: None,
+                max_nova_group: None,
+                min_eco_score_grade: None,
+                exclude_allergens: Vec::new(),
+                data_issues: Vec::new(),
+                fields: None,
+                request_status: None,
+                cursor: None,
             })
             .await;
 
@@ -1201,6 +1758,17 @@ async fn query_products_tests(client: &ServiceClient, products: &[ProductDescrip
                 order: SortingOrder::Descending,
                 field: SortingField::Similarity,
             }),
+            market: None,
+            producer: None,
+            quantity_type: None,
+            labels: None,
+            max_nova_group: None,
+            min_eco_score_grade: None,
+            exclude_allergens: Vec::new(),
+            data_issues: Vec::new(),
+            fields: None,
+            request_status: None,
+            cursor: None,
         })
         .await;
 
@@ -1212,6 +1780,222 @@ async fn query_products_tests(client: &ServiceClient, products: &[ProductDescrip
     compare_product_description(&ret[0], alpro1, true);
     compare_product_description(&ret[1], alpro2, true);
 
+    // the products sorted by similarity come with their scores
+    let response = client
+        .client
+        .post(client.server_address.join("user/product/query").unwrap())
+        .json(&ProductQuery {
+            offset: 0,
+            limit: 5,
+            filter: SearchFilter::Search("Alpro".to_string()),
+            sorting: Some(Sorting {
+                order: SortingOrder::Descending,
+                field: SortingField::Similarity,
+            }),
+            market: None,
+            producer: None,
+            quantity_type: None,
+            labels: None,
+            max_nova_group: None,
+            min_eco_score_grade: None,
+            exclude_allergens: Vec::new(),
+            data_issues: Vec::new(),
+            fields: Some(vec![ProductField::Id]),
+            request_status: None,
+            cursor: None,
+        })
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let response: ProductProjectionQueryResponse = response.json().await.unwrap();
+    let scores = response.scores.unwrap();
+    assert_eq!(scores.len(), 2);
+    assert!(scores.iter().all(|score| *score > 0.0 && *score <= 1.0));
+    assert!(scores[0] >= scores[1]);
+
+    // count the products before fetching any page
+    let response = client
+        .client
+        .post(client.server_address.join("user/product/count").unwrap())
+        .json(&ProductQuery {
+            offset: 0,
+            limit: 1,
+            filter: SearchFilter::Search("Alpro".to_string()),
+            sorting: None,
+            market: None,
+            producer: None,
+            quantity_type: None,
+            labels: None,
+            max_nova_group: None,
+            min_eco_score_grade: None,
+            exclude_allergens: Vec::new(),
+            data_issues: Vec::new(),
+            fields: None,
+            request_status: None,
+            cursor: None,
+        })
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let response: CountResponse = response.json().await.unwrap();
+    assert_eq!(response.count, 2);
+
+    // export the matching products with the selected columns
+    let export_query = ProductQuery {
+        offset: 0,
+        limit: 1,
+        filter: SearchFilter::Search("Alpro".to_string()),
+        sorting: None,
+        market: None,
+        producer: None,
+        quantity_type: None,
+        labels: None,
+        max_nova_group: None,
+        min_eco_score_grade: None,
+        exclude_allergens: Vec::new(),
+        data_issues: Vec::new(),
+        fields: Some(vec![
+            ProductField::Id,
+            ProductField::Name,
+            ProductField::Kcal,
+            ProductField::Preview,
+        ]),
+        request_status: None,
+        cursor: None,
+    };
+    let response = client
+        .client
+        .post(
+            client
+                .server_address
+                .join("user/product/export?format=csv")
+                .unwrap(),
+        )
+        .json(&export_query)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let csv = response.text().await.unwrap();
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(lines[0], "id,name,kcal");
+    assert_eq!(lines.len(), 3);
+
+    let response = client
+        .client
+        .post(
+            client
+                .server_address
+                .join("user/product/export?format=xlsx")
+                .unwrap(),
+        )
+        .json(&export_query)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.headers()[CONTENT_TYPE],
+        "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet"
+    );
+    assert_eq!(
+        response.headers()[CONTENT_DISPOSITION],
+        "attachment; filename=\"products.xlsx\""
+    );
+    let xlsx = response.bytes().await.unwrap();
+    assert!(xlsx.starts_with(b"PK"));
+
+    // the admins query the products with data quality issues for curation
+    let response = client
+        .client
+        .post(client.server_address.join("admin/product/query").unwrap())
+        .json(&ProductQuery {
+            offset: 0,
+            limit: 40,
+            filter: SearchFilter::NoFilter,
+            sorting: None,
+            market: None,
+            producer: None,
+            quantity_type: None,
+            labels: None,
+            max_nova_group: None,
+            min_eco_score_grade: None,
+            exclude_allergens: Vec::new(),
+            data_issues: vec![ProductDataIssue::MissingImage],
+            fields: None,
+            request_status: None,
+            cursor: None,
+        })
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let response: ProductQueryResponse = response.json().await.unwrap();
+    assert!(!response.products.is_empty());
+    assert!(response
+        .products
+        .iter()
+        .all(|p| p.preview.is_none() && p.full_image.is_none()));
+
+    // select only the fields of a list view
+    let query = ProductQuery {
+        offset: 0,
+        limit: 40,
+        filter: SearchFilter::NoFilter,
+        sorting: Some(Sorting {
+            order: SortingOrder::Ascending,
+            field: SortingField::ProductID,
+        }),
+        market: None,
+        producer: None,
+        quantity_type: None,
+        labels: None,
+        max_nova_group: None,
+        min_eco_score_grade: None,
+        exclude_allergens: Vec::new(),
+        data_issues: Vec::new(),
+        fields: Some(vec![
+            ProductField::Id,
+            ProductField::Name,
+            ProductField::Kcal,
+        ]),
+        request_status: None,
+        cursor: None,
+    };
+    let response = client
+        .client
+        .post(client.server_address.join("user/product/query").unwrap())
+        .json(&query)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let response: ProductProjectionQueryResponse = response.json().await.unwrap();
+
+    let mut sorted_products = products.to_vec();
+    sorted_products.sort_by_key(|p| p.info.id.clone());
+    assert_eq!(response.products.len(), sorted_products.len());
+    for (projection, product) in response.products.iter().zip(sorted_products.iter()) {
+        assert_eq!(projection.len(), 3);
+        assert_eq!(projection["id"], product.info.id.as_str());
+        assert_eq!(projection["name"], product.info.name.as_str());
+        assert!(
+            (projection["kcal"].as_f64().unwrap() as f32 - product.nutrients.kcal).abs() < 1e-3
+        );
+    }
+
+    // unknown fields are rejected
+    let response = client
+        .client
+        .post(client.server_address.join("user/product/query").unwrap())
+        .json(&serde_json::json!({"limit": 10, "fields": ["id", "unknown"]}))
+        .send()
+        .await
+        .unwrap();
+    assert!(response.status().is_client_error());
+
     info!("Querying products tests...SUCCESS");
 }
 
@@ -1306,36 +2090,3081 @@ async fn product_tests(options: &EndpointOptions) {
             assert_eq!(out_product.full_image, in_product.full_image);
         }
     }
+
+    // the product can be found by the GS1 Digital Link URI of its QR code
+    let digital_link = "https%3A%2F%2Fid.gs1.org%2F01%2F04260026433480%2F10%2FABC123".to_string();
+    let out_product = client
+        .get_product(&digital_link, false, false)
+        .await
+        .unwrap();
+    compare_product_description(&out_product, &products[2], false);
 }
 
-/// Runs the service tests with the given backend.
+/// Runs the read-only (maintenance) mode tests against the service.
 ///
 /// # Arguments
-/// - `options` - The options for initializing the service.
-async fn service_tests<B: DataBackend + 'static>(options: Options) {
-    let endpoint_options = options.endpoint.clone();
+/// - `options` - The endpoint options.
+async fn maintenance_tests(options: &EndpointOptions) {
+    let client = ServiceClient::new(options.address.clone());
 
-    info!("TEST: Creating service instance...");
-    let service: Arc<Service<B>> = Arc::new(Service::new(options).await.unwrap());
-    let service_clone = service.clone();
+    assert!(!client.get_maintenance_mode().await);
+    assert!(client.set_maintenance_mode(true).await);
+    assert!(client.get_maintenance_mode().await);
+
+    // mutating requests must be rejected in read-only mode
+    let url = client.server_address.join("user/missing_products").unwrap();
+    let response = client
+        .client
+        .post(url)
+        .json(&MissingProductReportRequest {
+            product_id: "maintenance".to_string(),
+            store_id: None,
+            photo: None,
+        })
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+    let url = client.server_address.join("admin/selftest").unwrap();
+    let response = client.client.post(url).send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+
+    // queries are still working in read-only mode
+    let missing_products = client
+        .query_missing_products(&MissingProductQuery {
+            limit: 40,
+            offset: 0,
+            product_id: Some("maintenance".to_string()),
+            order: SortingOrder::Ascending,
+        })
+        .await;
+    assert!(missing_products.is_empty());
 
-    let ret = service.run();
+    // switch back and make sure mutations work again
+    assert!(!client.set_maintenance_mode(false).await);
+    let (id, _) = client
+        .report_missing_product("maintenance".to_string())
+        .await;
+    client.delete_reported_missing_product(id).await;
+}
 
-    info!("TEST: Creating service instance...DONE");
+/// Runs the tests for range requests on the image endpoints.
+///
+/// # Arguments
+/// - `options` - The endpoint options.
+async fn image_range_tests(options: &EndpointOptions) {
+    let client = ServiceClient::new(options.address.clone());
 
-    // spawn a task that will stop the service after 1 second
-    tokio::spawn(async move {
-        info!("Running backend tests...");
-        missing_product_tests(&endpoint_options).await;
-        info!("Running backend tests...SUCCESS");
+    let mut product = load_products()[1].clone();
+    product.info.id = "range-test".to_string();
+    let full_image = product.full_image.clone().unwrap();
+    assert!(client.new_product(&product).await);
 
-        info!("Running product requests tests...");
-        product_requests_tests(&endpoint_options).await;
-        info!("Running product requests tests...SUCCESS");
+    let url = format!(
+        "http://{}/v1/user/product/{}/image",
+        options.address, product.info.id
+    );
 
-        info!("Running product tests...");
-        product_tests(&endpoint_options).await;
-        info!("Running product tests...SUCCESS");
+    // get the full image and its entity tag
+    let response = client.client.get(&url).send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers().get("accept-ranges").unwrap(), "bytes");
+    let etag = response.headers().get("etag").unwrap().clone();
+    let data = response.bytes().await.unwrap();
+    assert_eq!(data.as_ref(), full_image.data.as_slice());
+
+    // get the first 10 bytes
+    let response = client
+        .client
+        .get(&url)
+        .header("range", "bytes=0-9")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::PARTIAL_CONTENT);
+    assert_eq!(
+        response.headers().get("content-range").unwrap(),
+        format!("bytes 0-9/{}", full_image.data.len()).as_str()
+    );
+    let data = response.bytes().await.unwrap();
+    assert_eq!(data.as_ref(), &full_image.data[..10]);
+
+    // resume from byte 10 if the image has not changed
+    let response = client
+        .client
+        .get(&url)
+        .header("range", "bytes=10-")
+        .header("if-range", etag.clone())
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::PARTIAL_CONTENT);
+    let data = response.bytes().await.unwrap();
+    assert_eq!(data.as_ref(), &full_image.data[10..]);
+
+    // the full image is returned if the image has changed
+    let response = client
+        .client
+        .get(&url)
+        .header("range", "bytes=10-")
+        .header("if-range", "\"outdated\"")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let data = response.bytes().await.unwrap();
+    assert_eq!(data.as_ref(), full_image.data.as_slice());
+
+    // unsatisfiable range
+    let response = client
+        .client
+        .get(&url)
+        .header("range", format!("bytes={}-", full_image.data.len()))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::RANGE_NOT_SATISFIABLE);
+
+    // the unchanged image is not downloaded again
+    let response = client
+        .client
+        .get(&url)
+        .header("if-none-match", etag.clone())
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+    assert_eq!(response.headers().get("etag").unwrap(), &etag);
+    assert!(response.bytes().await.unwrap().is_empty());
+
+    // the image is returned if the cached version is outdated
+    let response = client
+        .client
+        .get(&url)
+        .header("if-none-match", "\"outdated\"")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    client.delete_product(&product.info.id).await;
+}
+
+/// Runs the tests for the signed public image urls.
+///
+/// # Arguments
+/// - `options` - The endpoint options.
+async fn signed_image_url_tests(options: &EndpointOptions) {
+    let client = ServiceClient::new(options.address.clone());
+
+    let mut product = load_products()[1].clone();
+    product.info.id = "signed-image-test".to_string();
+    let full_image = product.full_image.clone().unwrap();
+    assert!(client.new_product(&product).await);
+
+    let path = client.get_signed_image_url(&product.info.id).await;
+    assert!(path.starts_with("/v1/public/image/"));
+    let url = format!("http://{}{}", options.address, path);
+
+    // the image is accessible without any further headers
+    let response = client.client.get(&url).send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.headers().get(CONTENT_TYPE).unwrap(),
+        full_image.content_type.as_str()
+    );
+    let data = response.bytes().await.unwrap();
+    assert_eq!(data.as_ref(), full_image.data.as_slice());
+
+    // tampered tokens are rejected
+    let tampered = format!("{}0", url);
+    let response = client.client.get(&tampered).send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    client.delete_product(&product.info.id).await;
+}
+
+/// Runs the tests for the configured Cache-Control headers.
+///
+/// # Arguments
+/// - `options` - The endpoint options.
+async fn cache_control_tests(options: &EndpointOptions) {
+    let client = ServiceClient::new(options.address.clone());
+
+    let mut product = load_products()[1].clone();
+    product.info.id = "cache-control-test".to_string();
+    assert!(client.new_product(&product).await);
+
+    let base = format!("http://{}/v1/user", options.address);
+
+    // images
+    let response = client
+        .client
+        .get(format!("{}/product/{}/image", base, product.info.id))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.headers().get(CACHE_CONTROL).unwrap(),
+        "public, max-age=2592000, immutable"
+    );
+
+    // query results
+    let response = client
+        .client
+        .post(format!("{}/product/query", base))
+        .json(&ProductQuery {
+            offset: 0,
+            limit: 10,
+            filter: SearchFilter::default(),
+            sorting: None,
+            market: None,
+            producer: None,
+            quantity_type: None,
+            labels: None,
+            max_nova_group: None,
+            min_eco_score_grade: None,
+            exclude_allergens: Vec::new(),
+            data_issues: Vec::new(),
+            fields: None,
+            request_status: None,
+            cursor: None,
+        })
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers().get(CACHE_CONTROL).unwrap(), "no-store");
+
+    // no directive has been configured for products
+    let response = client
+        .client
+        .get(format!("{}/product/{}", base, product.info.id))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(response.headers().get(CACHE_CONTROL).is_none());
+
+    // error responses are not cached
+    let response = client
+        .client
+        .get(format!("{}/product/cache-control-missing/image", base))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    assert!(response.headers().get(CACHE_CONTROL).is_none());
+
+    // the images of product requests are never cached
+    let (request_id, _) = client.request_new_product(&product).await;
+    let response = client
+        .client
+        .get(format!(
+            "http://{}/v1/admin/product_request/{}/image",
+            options.address, request_id
+        ))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.headers().get(CACHE_CONTROL).unwrap(),
+        "private, no-store"
+    );
+
+    client.delete_product(&product.info.id).await;
+    client.delete_requested_product(request_id).await;
+}
+
+/// Runs the tests for submitting products with nutrients stated per serving.
+///
+/// # Arguments
+/// - `options` - The endpoint options.
+async fn reference_amount_tests(options: &EndpointOptions) {
+    let client = ServiceClient::new(options.address.clone());
+
+    let mut product = load_products()[0].clone();
+    product.info.id = "reference-amount-test".to_string();
+
+    // the nutrients of a serving of 50g are doubled
+    let response = client
+        .client
+        .post(client.server_address.join("admin/product").unwrap())
+        .json(&ProductSubmission {
+            product_description: product.clone(),
+            reference_amount: Some(50.0),
+            images: Vec::new(),
+        })
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::CREATED);
+
+    let stored = client
+        .get_product(&product.info.id, false, false)
+        .await
+        .unwrap();
+    check_compare_nutrients(&stored.nutrients, &product.nutrients.normalized(50.0));
+    assert!((stored.nutrients.kcal - 2.0 * product.nutrients.kcal).abs() < 1e-2);
+    client.delete_product(&product.info.id).await;
+
+    // the nutrients of product requests are normalized as well
+    let response = client
+        .client
+        .post(client.server_address.join("user/product_request").unwrap())
+        .json(&ProductSubmission {
+            product_description: product.clone(),
+            reference_amount: Some(25.0),
+            images: Vec::new(),
+        })
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::CREATED);
+    let response: ProductRequestResponse = response.json().await.unwrap();
+    let id = response.id.unwrap();
+
+    let stored = client.get_product_request(id, false, false).await.unwrap();
+    check_compare_nutrients(
+        &stored.product_description.nutrients,
+        &product.nutrients.normalized(25.0),
+    );
+    client.delete_requested_product(id).await;
+
+    // the reference amount must be positive
+    for path in ["admin/product", "user/product_request"] {
+        let response = client
+            .client
+            .post(client.server_address.join(path).unwrap())
+            .json(&ProductSubmission {
+                product_description: product.clone(),
+                reference_amount: Some(0.0),
+                images: Vec::new(),
+            })
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+    assert!(client
+        .get_product(&product.info.id, false, false)
+        .await
+        .is_none());
+}
+
+/// Runs the tests for the market of the products.
+///
+/// # Arguments
+/// - `options` - The endpoint options.
+async fn market_tests(options: &EndpointOptions) {
+    let client = ServiceClient::new(options.address.clone());
+
+    let mut product = load_products()[0].clone();
+    product.info.id = "market-test".to_string();
+    product.info.market = Some("DE".to_string());
+    assert!(client.new_product(&product).await);
+
+    let market_query = |market: &str| ProductQuery {
+        offset: 0,
+        limit: 10,
+        filter: SearchFilter::NoFilter,
+        sorting: None,
+        market: Some(market.to_string()),
+        producer: None,
+        quantity_type: None,
+        labels: None,
+        max_nova_group: None,
+        min_eco_score_grade: None,
+        exclude_allergens: Vec::new(),
+        data_issues: Vec::new(),
+        fields: None,
+        request_status: None,
+        cursor: None,
+    };
+
+    let products = client.query_products(&market_query("DE")).await;
+    assert_eq!(products.len(), 1);
+    assert_eq!(products[0].info.market, product.info.market);
+    assert!(client.query_products(&market_query("FR")).await.is_empty());
+    client.delete_product(&product.info.id).await;
+
+    // the market must be an ISO 3166-1 alpha-2 country code
+    for market in ["de", "DEU", ""] {
+        product.info.market = Some(market.to_string());
+        let response = client
+            .client
+            .post(client.server_address.join("admin/product").unwrap())
+            .json(&product)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+}
+
+/// Runs the tests for the packaging of the products.
+///
+/// # Arguments
+/// - `options` - The endpoint options.
+async fn packaging_tests(options: &EndpointOptions) {
+    let client = ServiceClient::new(options.address.clone());
+
+    let mut product = load_products()[0].clone();
+    product.info.id = "packaging-test".to_string();
+    product.packaging = Some(Packaging {
+        net_quantity: Some(1980.0),
+        units: Some(6),
+        material: Some(PackagingMaterial::Glass),
+        deposit: true,
+    });
+    assert!(client.new_product(&product).await);
+
+    let stored = client
+        .get_product(&product.info.id, false, false)
+        .await
+        .unwrap();
+    assert_eq!(stored.packaging, product.packaging);
+    client.delete_product(&product.info.id).await;
+
+    // the pack must contain at least one unit
+    product.packaging = Some(Packaging {
+        units: Some(0),
+        ..product.packaging.unwrap()
+    });
+    for path in ["admin/product", "user/product_request"] {
+        let response = client
+            .client
+            .post(client.server_address.join(path).unwrap())
+            .json(&product)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+}
+
+/// Runs the tests for the Eco-Score of the products.
+///
+/// # Arguments
+/// - `options` - The endpoint options.
+async fn eco_score_tests(options: &EndpointOptions) {
+    let client = ServiceClient::new(options.address.clone());
+
+    let mut product = load_products()[1].clone();
+    product.info.id = "eco-score-test".to_string();
+    product.eco_score = Some(EcoScore {
+        grade: EcoScoreGrade::C,
+        value: Some(48),
+        source: Some("Open Food Facts".to_string()),
+    });
+    assert!(client.new_product(&product).await);
+
+    let stored = client
+        .get_product(&product.info.id, false, false)
+        .await
+        .unwrap();
+    assert_eq!(stored.eco_score, product.eco_score);
+    client.delete_product(&product.info.id).await;
+
+    // the value must be between 0 and 100
+    product.eco_score = Some(EcoScore {
+        value: Some(120),
+        ..product.eco_score.unwrap()
+    });
+    for path in ["admin/product", "user/product_request"] {
+        let response = client
+            .client
+            .post(client.server_address.join(path).unwrap())
+            .json(&product)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+}
+
+/// Runs the tests for the NOVA group of the products. The service classifies the products whose
+/// id starts with "nova-" as NOVA group 4.
+///
+/// # Arguments
+/// - `options` - The endpoint options.
+async fn nova_group_tests(options: &EndpointOptions) {
+    let client = ServiceClient::new(options.address.clone());
+
+    // the missing NOVA group is derived by the classifier
+    let mut product = load_products()[0].clone();
+    product.info.id = "nova-classified".to_string();
+    product.info.nova_group = None;
+    assert!(client.new_product(&product).await);
+    let stored = client
+        .get_product(&product.info.id, false, false)
+        .await
+        .unwrap();
+    assert_eq!(stored.info.nova_group, Some(4));
+    client.delete_product(&product.info.id).await;
+
+    // a submitted NOVA group is kept
+    product.info.id = "nova-submitted".to_string();
+    product.info.nova_group = Some(1);
+    assert!(client.new_product(&product).await);
+    let stored = client
+        .get_product(&product.info.id, false, false)
+        .await
+        .unwrap();
+    assert_eq!(stored.info.nova_group, Some(1));
+    client.delete_product(&product.info.id).await;
+
+    // the NOVA group must be between 1 and 4
+    product.info.nova_group = Some(5);
+    for path in ["admin/product", "user/product_request"] {
+        let response = client
+            .client
+            .post(client.server_address.join(path).unwrap())
+            .json(&product)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+}
+
+/// Runs the tests for checking the plausibility of the submitted nutrients.
+///
+/// # Arguments
+/// - `options` - The endpoint options.
+async fn plausibility_tests(options: &EndpointOptions) {
+    let client = ServiceClient::new(options.address.clone());
+
+    // the sugar exceeds the carbohydrates
+    let mut product = load_products()[0].clone();
+    product.info.id = "plausibility-test".to_string();
+    product.nutrients.sugar = Some(Weight::new_from_gram(21.0));
+
+    // the product request is accepted, but flagged for review
+    let response = client
+        .client
+        .post(client.server_address.join("user/product_request").unwrap())
+        .json(&product)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::CREATED);
+    let response: ProductRequestResponse = response.json().await.unwrap();
+    assert_eq!(
+        response.warnings,
+        vec![NutrientIssue::SugarExceedsCarbohydrates]
+    );
+
+    let id = response.id.unwrap();
+    let response: GetProductRequestResponse = client
+        .client
+        .get(
+            client
+                .server_address
+                .join(&format!("admin/product_request/{}", id))
+                .unwrap(),
+        )
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(
+        response.warnings,
+        vec![NutrientIssue::SugarExceedsCarbohydrates]
+    );
+    client.delete_requested_product(id).await;
+
+    // the product is rejected
+    let response = client
+        .client
+        .post(client.server_address.join("admin/product").unwrap())
+        .json(&product)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert!(client
+        .get_product(&product.info.id, false, false)
+        .await
+        .is_none());
+}
+
+/// Runs the tests for aggregating the nutrients of a list of portions.
+///
+/// # Arguments
+/// - `options` - The endpoint options.
+async fn nutrient_aggregation_tests(options: &EndpointOptions) {
+    let client = ServiceClient::new(options.address.clone());
+
+    let products = load_products();
+    let mut first = products[0].clone();
+    first.info.id = "aggregation-test-1".to_string();
+    let mut second = products[1].clone();
+    second.info.id = "aggregation-test-2".to_string();
+    assert!(client.new_product(&first).await);
+    assert!(client.new_product(&second).await);
+
+    let aggregate = |portions: Vec<Portion>| {
+        client
+            .client
+            .post(
+                client
+                    .server_address
+                    .join("user/nutrients/aggregate")
+                    .unwrap(),
+            )
+            .json(&NutrientAggregationRequest { portions })
+            .send()
+    };
+
+    let portion = |product: &ProductDescription, amount: f32| Portion {
+        product_id: product.info.id.clone(),
+        amount,
+    };
+
+    // the portions are scaled from 100g and summed up, unknown products are reported
+    let response = aggregate(vec![
+        portion(&first, 250.0),
+        portion(&second, 50.0),
+        portion(&first, 50.0),
+        Portion {
+            product_id: "aggregation-unknown".to_string(),
+            amount: 100.0,
+        },
+    ])
+    .await
+    .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let response: NutrientAggregationResponse = response.json().await.unwrap();
+    let aggregation = response.aggregation.unwrap();
+    assert_eq!(
+        aggregation.missing_products,
+        vec!["aggregation-unknown".to_string()]
+    );
+    let expected = first.nutrients.scale(3.0) + second.nutrients.scale(0.5);
+    check_compare_nutrients(&aggregation.nutrients, &expected);
+    assert!((aggregation.nutrients.kcal - expected.kcal).abs() < 1e-2);
+
+    // negative amounts are rejected
+    let response = aggregate(vec![portion(&first, -1.0)]).await.unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let response: NutrientAggregationResponse = response.json().await.unwrap();
+    assert!(response.aggregation.is_none());
+
+    client.delete_product(&first.info.id).await;
+    client.delete_product(&second.info.id).await;
+}
+
+/// Runs the tests for the recipes and their derived nutrients.
+///
+/// # Arguments
+/// - `options` - The endpoint options.
+async fn recipe_tests(options: &EndpointOptions) {
+    let client = ServiceClient::new(options.address.clone());
+
+    let products = load_products();
+    let mut flour = products[0].clone();
+    flour.info.id = "recipe-test-1".to_string();
+    let mut milk = products[1].clone();
+    milk.info.id = "recipe-test-2".to_string();
+    assert!(client.new_product(&flour).await);
+    assert!(client.new_product(&milk).await);
+
+    let recipe = Recipe {
+        name: "Pancakes".to_string(),
+        servings: 4,
+        ingredients: vec![
+            Portion {
+                product_id: flour.info.id.clone(),
+                amount: 250.0,
+            },
+            Portion {
+                product_id: milk.info.id.clone(),
+                amount: 500.0,
+            },
+        ],
+        image: flour.full_image.clone(),
+    };
+
+    let get_recipe = |id: DBId| {
+        client
+            .client
+            .get(
+                client
+                    .server_address
+                    .join(&format!("user/recipe/{}", id))
+                    .unwrap(),
+            )
+            .send()
+    };
+    let recipe_url = |id: DBId| {
+        client
+            .server_address
+            .join(&format!("admin/recipe/{}", id))
+            .unwrap()
+    };
+
+    // add the recipe
+    let response = client
+        .client
+        .post(client.server_address.join("admin/recipe").unwrap())
+        .json(&recipe)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::CREATED);
+    let response: NewRecipeResponse = response.json().await.unwrap();
+    let id = response.id.unwrap();
+
+    // the nutrients are derived from the ingredients
+    let response = get_recipe(id).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let response: GetRecipeResponse = response.json().await.unwrap();
+    assert_eq!(response.recipe.as_ref(), Some(&recipe));
+    let nutrients = response.nutrients.unwrap();
+    let total = flour.nutrients.scale(2.5) + milk.nutrients.scale(5.0);
+    assert!((nutrients.per_portion.kcal - total.kcal / 4.0).abs() < 1e-1);
+    assert!(nutrients.missing_products.is_empty());
+
+    // changes of the products are reflected in the nutrients of the recipe
+    client.delete_product(&milk.info.id).await;
+    let response: GetRecipeResponse = get_recipe(id).await.unwrap().json().await.unwrap();
+    let nutrients = response.nutrients.unwrap();
+    assert_eq!(nutrients.missing_products, vec![milk.info.id.clone()]);
+    assert!((nutrients.per_portion.kcal - flour.nutrients.kcal * 2.5 / 4.0).abs() < 1e-1);
+
+    // replace the recipe
+    let updated = Recipe {
+        name: "Flour".to_string(),
+        servings: 1,
+        ingredients: vec![Portion {
+            product_id: flour.info.id.clone(),
+            amount: 100.0,
+        }],
+        image: None,
+    };
+    let response = client
+        .client
+        .put(recipe_url(id))
+        .json(&updated)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let response: GetRecipeResponse = get_recipe(id).await.unwrap().json().await.unwrap();
+    assert_eq!(response.recipe, Some(updated.clone()));
+    check_compare_nutrients(&response.nutrients.unwrap().per_100g, &flour.nutrients);
+
+    // invalid recipes are rejected
+    let invalid = Recipe {
+        servings: 0,
+        ..updated.clone()
+    };
+    let response = client
+        .client
+        .put(recipe_url(id))
+        .json(&invalid)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    // delete the recipe
+    let response = client.client.delete(recipe_url(id)).send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let response = get_recipe(id).await.unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    let response = client
+        .client
+        .put(recipe_url(id))
+        .json(&updated)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    client.delete_product(&flour.info.id).await;
+}
+
+/// Runs the tests for submitting and querying the observed prices.
+///
+/// # Arguments
+/// - `options` - The endpoint options.
+async fn price_tests(options: &EndpointOptions) {
+    let client = ServiceClient::new(options.address.clone());
+
+    let product_id: ProductID = "price-service-test".to_string();
+    let observe = |store: &str, price: i64, currency: &str| PriceObservation {
+        product_id: product_id.clone(),
+        store: store.to_string(),
+        price,
+        currency: currency.to_string(),
+        observed_at: truncate_datetime(Utc::now()),
+    };
+    let submit_price = |price: PriceObservation| {
+        client
+            .client
+            .post(client.server_address.join("user/price").unwrap())
+            .json(&price)
+            .send()
+    };
+    let cheapest_price = |currency: &str| {
+        client
+            .client
+            .get(
+                client
+                    .server_address
+                    .join(&format!(
+                        "user/product/{}/cheapest_price?currency={}",
+                        product_id, currency
+                    ))
+                    .unwrap(),
+            )
+            .send()
+    };
+
+    // no price has been observed yet
+    let response = cheapest_price("EUR").await.unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    // submit the prices
+    let prices = [
+        observe("Corner shop", 249, "EUR"),
+        observe("Supermarket", 199, "EUR"),
+        observe("Duty free", 150, "USD"),
+    ];
+    let mut ids = Vec::new();
+    for price in prices.iter() {
+        let response = submit_price(price.clone()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let response: PriceSubmissionResponse = response.json().await.unwrap();
+        ids.push(response.id.unwrap());
+    }
+
+    // invalid prices are rejected
+    for invalid in [observe("", 199, "EUR"), observe("Supermarket", -1, "EUR")] {
+        let response = submit_price(invalid).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+    let response = submit_price(observe("Supermarket", 199, "euro"))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    // the cheapest price in the requested currency
+    let response = cheapest_price("EUR").await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let response: CheapestPriceResponse = response.json().await.unwrap();
+    assert_eq!(response.price, Some(prices[1].clone()));
+
+    // query the prices of the product
+    let response = client
+        .client
+        .post(client.server_address.join("user/price/query").unwrap())
+        .json(&PriceQuery {
+            offset: 0,
+            limit: 10,
+            product_id: Some(product_id.clone()),
+            store: None,
+            currency: Some("EUR".to_string()),
+            order: SortingOrder::Ascending,
+        })
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let response: PriceQueryResponse = response.json().await.unwrap();
+    assert_eq!(
+        response.prices,
+        vec![(ids[0], prices[0].clone()), (ids[1], prices[1].clone())]
+    );
+}
+
+/// Runs the tests for the stores and the availability of the products in the stores.
+///
+/// # Arguments
+/// - `options` - The endpoint options.
+async fn store_tests(options: &EndpointOptions) {
+    let client = ServiceClient::new(options.address.clone());
+
+    let store = Store {
+        name: "Market Street".to_string(),
+        chain: Some("FreshMart".to_string()),
+    };
+    let url = |path: &str| client.server_address.join(path).unwrap();
+
+    // add the store
+    let response = client
+        .client
+        .post(url("admin/store"))
+        .json(&store)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::CREATED);
+    let response: NewStoreResponse = response.json().await.unwrap();
+    let id = response.id.unwrap();
+
+    let response = client
+        .client
+        .get(url(&format!("user/store/{}", id)))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let response: GetStoreResponse = response.json().await.unwrap();
+    assert_eq!(response.store, Some(store.clone()));
+
+    // stores without name are rejected
+    let response = client
+        .client
+        .post(url("admin/store"))
+        .json(&Store {
+            name: "".to_string(),
+            chain: None,
+        })
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    // query the stores of the chain
+    let response = client
+        .client
+        .post(url("user/store/query"))
+        .json(&StoreQuery {
+            offset: 0,
+            limit: 10,
+            chain: Some("FreshMart".to_string()),
+            order: SortingOrder::Ascending,
+        })
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let response: StoreQueryResponse = response.json().await.unwrap();
+    assert_eq!(response.stores, vec![(id, store)]);
+
+    // report the product as seen at the store
+    let mut product = load_products()[0].clone();
+    product.info.id = "store-service-test".to_string();
+    assert!(client.new_product(&product).await);
+
+    let report_availability = |store_id: DBId, available: bool| {
+        client
+            .client
+            .post(url(&format!("user/store/{}/availability", store_id)))
+            .json(&AvailabilityReportRequest {
+                product_id: product.info.id.clone(),
+                available,
+            })
+            .send()
+    };
+    let response = report_availability(id, true).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let response = report_availability(-1, true).await.unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    let response = client
+        .client
+        .get(url(&format!(
+            "user/product/{}/availability",
+            product.info.id
+        )))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let response: ProductAvailabilityResponse = response.json().await.unwrap();
+    assert_eq!(response.availability.len(), 1);
+    assert_eq!(response.availability[0].store_id, id);
+    assert!(response.availability[0].available);
+
+    // query the products of the store
+    let store_query = ProductQuery {
+        offset: 0,
+        limit: 10,
+        filter: SearchFilter::Store(id),
+        sorting: None,
+        market: None,
+        producer: None,
+        quantity_type: None,
+        labels: None,
+        max_nova_group: None,
+        min_eco_score_grade: None,
+        exclude_allergens: Vec::new(),
+        data_issues: Vec::new(),
+        fields: None,
+        request_status: None,
+        cursor: None,
+    };
+    let products = client.query_products(&store_query).await;
+    assert_eq!(products.len(), 1);
+    assert_eq!(products[0].info.id, product.info.id);
+
+    // the product is no longer returned once it has been missing at the store
+    let response = report_availability(id, false).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(client.query_products(&store_query).await.is_empty());
+
+    // report a missing product at the store
+    let response = client
+        .client
+        .post(url("user/missing_products"))
+        .json(&MissingProductReportRequest {
+            product_id: "store-service-test-missing".to_string(),
+            store_id: Some(id),
+            photo: None,
+        })
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::CREATED);
+    let response: MissingProductReportResponse = response.json().await.unwrap();
+    let report_id = response.id.unwrap();
+    let report = client.get_missing_product(report_id).await.unwrap();
+    assert_eq!(report.store_id, Some(id));
+
+    // delete the store
+    let response = client
+        .client
+        .delete(url(&format!("admin/store/{}", id)))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let response = client
+        .client
+        .get(url(&format!("user/store/{}", id)))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    client.delete_reported_missing_product(report_id).await;
+    client.delete_product(&product.info.id).await;
+}
+
+/// Runs the tests for reloading the database credentials.
+///
+/// # Arguments
+/// - `options` - The endpoint options.
+async fn reload_credentials_tests(options: &EndpointOptions) {
+    let client = ServiceClient::new(options.address.clone());
+
+    let url = client
+        .server_address
+        .join("admin/reload_credentials")
+        .unwrap();
+    let response = client.client.post(url).send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // the service keeps working with the new connection pool
+    let mut product = load_products()[0].clone();
+    product.info.id = "reload-credentials-test".to_string();
+    assert!(client.new_product(&product).await);
+    assert!(client
+        .get_product(&product.info.id, false, false)
+        .await
+        .is_some());
+    client.delete_product(&product.info.id).await;
+}
+
+/// Runs the tests for the self-test against the data backend.
+///
+/// # Arguments
+/// - `options` - The endpoint options.
+async fn self_test_tests(options: &EndpointOptions) {
+    let client = ServiceClient::new(options.address.clone());
+
+    let url = client.server_address.join("admin/selftest").unwrap();
+    let response = client.client.post(url).send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let response: SelfTestResponse = response.json().await.unwrap();
+    assert!(response.report.ok);
+    assert_eq!(response.report.steps.len(), 7);
+    assert!(response
+        .report
+        .steps
+        .iter()
+        .all(|step| step.error.is_none()));
+
+    // the synthetic data has been deleted again
+    assert!(client
+        .get_product(&response.report.product_id, false, false)
+        .await
+        .is_none());
+    let missing_products = client
+        .query_missing_products(&MissingProductQuery {
+            limit: 40,
+            offset: 0,
+            product_id: Some(response.report.product_id),
+            order: SortingOrder::Ascending,
+        })
+        .await;
+    assert!(missing_products.is_empty());
+}
+
+/// Runs the tests for updating products.
+///
+/// # Arguments
+/// - `options` - The endpoint options.
+async fn update_product_tests(options: &EndpointOptions) {
+    let client = ServiceClient::new(options.address.clone());
+
+    let mut product = load_products()[1].clone();
+    product.info.id = "update-test".to_string();
+    let url = client
+        .server_address
+        .join(&format!("admin/product/{}", product.info.id))
+        .unwrap();
+    let update = |product_description: ProductDescription| {
+        let client = &client;
+        let url = url.clone();
+        async move {
+            client
+                .client
+                .put(url)
+                .json(&ProductSubmission {
+                    product_description,
+                    reference_amount: None,
+                    images: Vec::new(),
+                })
+                .send()
+                .await
+                .unwrap()
+                .status()
+        }
+    };
+    assert_eq!(update(product.clone()).await, StatusCode::NOT_FOUND);
+    assert!(client.new_product(&product).await);
+
+    // the id of the path is used and the images are kept if none are given
+    let mut updated = product.clone();
+    updated.info.id = "other-id".to_string();
+    updated.info.name = "Updated name".to_string();
+    updated.preview = None;
+    updated.full_image = None;
+    assert_eq!(update(updated).await, StatusCode::OK);
+
+    let stored = client
+        .get_product(&product.info.id, true, false)
+        .await
+        .unwrap();
+    assert_eq!(stored.info.name, "Updated name");
+    assert_eq!(stored.preview, product.preview);
+    assert!(client
+        .get_product(&"other-id".to_string(), false, false)
+        .await
+        .is_none());
+
+    // the description is validated like a new product
+    let mut invalid = product.clone();
+    invalid.info.market = Some("germany".to_string());
+    assert_eq!(update(invalid).await, StatusCode::BAD_REQUEST);
+
+    client.delete_product(&product.info.id).await;
+}
+
+/// Runs the tests for archiving processed product requests.
+///
+/// # Arguments
+/// - `options` - The endpoint options.
+async fn archive_tests(options: &EndpointOptions) {
+    let client = ServiceClient::new(options.address.clone());
+
+    let mut product = load_products()[0].clone();
+    product.info.id = "archive-test".to_string();
+    let (id, date) = client.request_new_product(&product).await;
+
+    // a rejected request keeps its review comment until it is archived
+    assert_eq!(
+        client.reject_product_request(id, Some("Duplicate")).await,
+        StatusCode::OK
+    );
+    let request = client.get_product_request(id, false, false).await.unwrap();
+    assert_eq!(request.status, RequestStatus::Rejected);
+    assert_eq!(request.review_comment.as_deref(), Some("Duplicate"));
+
+    assert_eq!(
+        client
+            .archive_product_request(id, RequestOutcome::Rejected)
+            .await,
+        StatusCode::OK
+    );
+    assert_eq!(
+        client.reject_product_request(id, None).await,
+        StatusCode::NOT_FOUND
+    );
+    assert!(client.get_product_request(id, false, false).await.is_none());
+    assert_eq!(
+        client
+            .archive_product_request(id, RequestOutcome::Rejected)
+            .await,
+        StatusCode::NOT_FOUND
+    );
+
+    let archived = client
+        .query_archive(&ArchiveQuery {
+            offset: 0,
+            limit: 10,
+            product_id: Some(product.info.id.clone()),
+            outcome: None,
+            order: SortingOrder::Descending,
+        })
+        .await;
+    assert_eq!(archived.len(), 1);
+    assert_eq!(archived[0].id, id);
+    assert_eq!(archived[0].name, product.info.name);
+    assert_eq!(archived[0].date.timestamp_micros(), date.timestamp_micros());
+    assert_eq!(archived[0].outcome, RequestOutcome::Rejected);
+    assert_eq!(archived[0].review_comment.as_deref(), Some("Duplicate"));
+
+    // an approved request is added to the products
+    let (id, date) = client.request_new_product(&product).await;
+    assert_eq!(
+        client.approve_product_request(id).await,
+        (StatusCode::OK, Some(product.info.id.clone()))
+    );
+    assert!(client.get_product_request(id, false, false).await.is_none());
+    assert_eq!(
+        client
+            .get_product(&product.info.id, false, false)
+            .await
+            .unwrap()
+            .info
+            .name,
+        product.info.name
+    );
+    assert_eq!(
+        client.approve_product_request(id).await,
+        (StatusCode::NOT_FOUND, None)
+    );
+
+    let archived = client
+        .query_archive(&ArchiveQuery {
+            offset: 0,
+            limit: 10,
+            product_id: Some(product.info.id.clone()),
+            outcome: Some(RequestOutcome::Approved),
+            order: SortingOrder::Descending,
+        })
+        .await;
+    assert_eq!(archived.len(), 1);
+    assert_eq!(archived[0].id, id);
+    assert_eq!(archived[0].date.timestamp_micros(), date.timestamp_micros());
+
+    // requests for existing products are rejected
+    let (id, _) = client.request_new_product(&product).await;
+    assert_eq!(
+        client.approve_product_request(id).await,
+        (StatusCode::CONFLICT, None)
+    );
+    client.delete_requested_product(id).await;
+    client.delete_product(&product.info.id).await;
+}
+
+/// Runs the tests for the additional images of products and product requests.
+///
+/// # Arguments
+/// - `options` - The endpoint options.
+async fn additional_images_tests(options: &EndpointOptions) {
+    let client = ServiceClient::new(options.address.clone());
+
+    let mut product = load_products()[0].clone();
+    product.info.id = "additional-images-test".to_string();
+    let image = |role, data| AdditionalImage {
+        role,
+        image: ProductImage {
+            content_type: "image/jpeg".to_string(),
+            data,
+        },
+    };
+    let images = vec![
+        image(ImageRole::Nutrition, vec![1, 2, 3]),
+        image(ImageRole::Ingredients, vec![4, 5, 6]),
+    ];
+    let get_images = |path: String| {
+        let client = &client;
+        async move {
+            let response = client
+                .client
+                .get(client.server_address.join(&path).unwrap())
+                .send()
+                .await
+                .unwrap();
+            let status = response.status();
+            let response: AdditionalImagesResponse = response.json().await.unwrap();
+            (status, response.images)
+        }
+    };
+
+    // the number of additional images is limited
+    let response = client
+        .client
+        .post(client.server_address.join("user/product_request").unwrap())
+        .json(&ProductSubmission {
+            product_description: product.clone(),
+            reference_amount: None,
+            images: vec![images[0].clone(); MAX_ADDITIONAL_IMAGES + 1],
+        })
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    // the images of the request are retrievable by the reviewers
+    let response = client
+        .client
+        .post(client.server_address.join("user/product_request").unwrap())
+        .json(&ProductSubmission {
+            product_description: product.clone(),
+            reference_amount: None,
+            images: images.clone(),
+        })
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::CREATED);
+    let response: ProductRequestResponse = response.json().await.unwrap();
+    let id = response.id.unwrap();
+    assert_eq!(
+        get_images(format!("admin/product_request/{}/images", id)).await,
+        (StatusCode::OK, images.clone())
+    );
+
+    // the images cannot be carried over to a product that does not exist
+    let archive = |carry_over_images| {
+        let client = &client;
+        async move {
+            client
+                .client
+                .post(
+                    client
+                        .server_address
+                        .join(&format!("admin/product_request/{}/archive", id))
+                        .unwrap(),
+                )
+                .json(&ArchiveProductRequestRequest {
+                    outcome: RequestOutcome::Approved,
+                    carry_over_images,
+                })
+                .send()
+                .await
+                .unwrap()
+                .status()
+        }
+    };
+    assert_eq!(archive(true).await, StatusCode::CONFLICT);
+    assert!(client.get_product_request(id, false, false).await.is_some());
+
+    // the images are carried over to the approved product
+    assert!(client.new_product(&product).await);
+    let product_images = format!("user/product/{}/images", product.info.id);
+    assert_eq!(
+        get_images(product_images.clone()).await,
+        (StatusCode::OK, Vec::new())
+    );
+    assert_eq!(archive(true).await, StatusCode::OK);
+    assert_eq!(
+        get_images(format!("admin/product_request/{}/images", id)).await,
+        (StatusCode::NOT_FOUND, Vec::new())
+    );
+    assert_eq!(
+        get_images(product_images.clone()).await,
+        (StatusCode::OK, images.clone())
+    );
+    client.delete_product(&product.info.id).await;
+    assert_eq!(
+        get_images(product_images.clone()).await,
+        (StatusCode::NOT_FOUND, Vec::new())
+    );
+
+    // the images of new products are stored as well
+    let response = client
+        .client
+        .post(client.server_address.join("admin/product").unwrap())
+        .json(&ProductSubmission {
+            product_description: product.clone(),
+            reference_amount: None,
+            images: images.clone(),
+        })
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::CREATED);
+    assert_eq!(get_images(product_images).await, (StatusCode::OK, images));
+    client.delete_product(&product.info.id).await;
+}
+
+/// Runs the tests for regenerating the preview images from the full images.
+///
+/// # Arguments
+/// - `options` - The endpoint options.
+async fn regenerate_images_tests(options: &EndpointOptions) {
+    let client = ServiceClient::new(options.address.clone());
+    let url = client
+        .server_address
+        .join("admin/maintenance/regenerate_images")
+        .unwrap();
+
+    // only the full images with the marked content type are accepted by the generator
+    let mut product = load_products()[1].clone();
+    product.info.id = "regenerate-images".to_string();
+    product.full_image.as_mut().unwrap().content_type = "image/x-regenerate".to_string();
+    let full_image = product.full_image.clone().unwrap();
+    assert!(client.new_product(&product).await);
+
+    let response = client.client.post(url.clone()).send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::ACCEPTED);
+    let response: ImageRegenerationResponse = response.json().await.unwrap();
+    assert!(response.status.running);
+    assert!(response.status.started.is_some());
+
+    // wait for the regeneration in the background
+    let mut status = response.status;
+    for _ in 0..100 {
+        if !status.running {
+            break;
+        }
+
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        let response = client.client.get(url.clone()).send().await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        status = response
+            .json::<ImageRegenerationResponse>()
+            .await
+            .unwrap()
+            .status;
+    }
+    assert!(!status.running);
+    assert!(status.finished.is_some());
+    assert_eq!(status.processed, status.total);
+    assert_eq!(status.regenerated, 1);
+
+    // the preview is replaced and the full image is kept
+    let stored = client
+        .get_product(&product.info.id, true, false)
+        .await
+        .unwrap();
+    assert_eq!(
+        stored.preview,
+        Some(ProductImage {
+            content_type: "image/x-preview".to_string(),
+            data: full_image.data[..16].to_vec(),
+        })
+    );
+    assert_eq!(
+        client.get_product_image(&product.info.id).await,
+        Some(full_image)
+    );
+
+    client.delete_product(&product.info.id).await;
+}
+
+/// Runs the tests for the MessagePack and CBOR encoding of the query endpoints.
+///
+/// # Arguments
+/// - `options` - The endpoint options.
+async fn content_negotiation_tests(options: &EndpointOptions) {
+    let client = ServiceClient::new(options.address.clone());
+    let url = client.server_address.join("user/product/query").unwrap();
+
+    let query = ProductQuery {
+        offset: 0,
+        limit: 10,
+        filter: SearchFilter::NoFilter,
+        sorting: Some(Sorting {
+            order: SortingOrder::Ascending,
+            field: SortingField::ProductID,
+        }),
+        market: None,
+        producer: None,
+        quantity_type: None,
+        labels: None,
+        max_nova_group: None,
+        min_eco_score_grade: None,
+        exclude_allergens: Vec::new(),
+        data_issues: Vec::new(),
+        fields: None,
+        request_status: None,
+        cursor: None,
+    };
+    let expected = client.query_products(&query).await;
+
+    // MessagePack request and response
+    let response = client
+        .client
+        .post(url.clone())
+        .header(CONTENT_TYPE, "application/msgpack")
+        .header(ACCEPT, "application/msgpack")
+        .body(rmp_serde::to_vec_named(&query).unwrap())
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.headers().get(CONTENT_TYPE).unwrap(),
+        "application/msgpack"
+    );
+    let response: ProductQueryResponse =
+        rmp_serde::from_slice(&response.bytes().await.unwrap()).unwrap();
+    assert_eq!(response.products, expected);
+    let total_count = response.total_count;
+    let next_cursor = response.next_cursor;
+
+    // CBOR request with a preferred CBOR response
+    let mut body = Vec::new();
+    ciborium::into_writer(&query, &mut body).unwrap();
+    let response = client
+        .client
+        .post(url.clone())
+        .header(CONTENT_TYPE, "application/cbor")
+        .header(ACCEPT, "application/json;q=0.5, application/cbor")
+        .body(body)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.headers().get(CONTENT_TYPE).unwrap(),
+        "application/cbor"
+    );
+    // the base64 encoded images cannot be deserialized from CBOR readers, compare the values
+    let response: ciborium::Value =
+        ciborium::from_reader(response.bytes().await.unwrap().as_ref()).unwrap();
+    let expected = ciborium::Value::serialized(&ProductQueryResponse {
+        message: "Query executed successful".to_string(),
+        products: expected,
+        scores: None,
+        total_count,
+        next_cursor,
+    })
+    .unwrap();
+    assert_eq!(response, expected);
+
+    // invalid MessagePack bodies are rejected
+    let response = client
+        .client
+        .post(url)
+        .header(CONTENT_TYPE, "application/msgpack")
+        .body(vec![0xc1])
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+/// Runs the tests for the JSON Schemas of the request and response types.
+///
+/// # Arguments
+/// - `options` - The endpoint options.
+#[cfg(feature = "json-schema")]
+async fn json_schema_tests(options: &EndpointOptions) {
+    let client = ServiceClient::new(options.address.clone());
+
+    let url = client
+        .server_address
+        .join("user/schema/ProductDescription")
+        .unwrap();
+    let response = client.client.get(url).send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let schema: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(schema["title"], "ProductDescription");
+
+    let url = client.server_address.join("user/schema/Unknown").unwrap();
+    let response = client.client.get(url).send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    let response: OnlyMessageResponse = response.json().await.unwrap();
+    assert!(response.message.contains("ProductQuery"));
+}
+
+/// Runs the tests for the metrics endpoint. Must run after other tests have used the
+/// database.
+///
+/// # Arguments
+/// - `options` - The endpoint options.
+async fn metrics_tests(options: &EndpointOptions) {
+    let client = reqwest::Client::new();
+
+    // an unknown product is answered with 404
+    let url = format!("http://{}/v1/user/product/unknown", options.address);
+    let response = client.get(url).send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    let url = format!("http://{}/metrics", options.address);
+    let response = client.get(url).send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(response.headers()[CONTENT_TYPE]
+        .to_str()
+        .unwrap()
+        .starts_with("text/plain"));
+
+    let metrics = response.text().await.unwrap();
+    for name in [
+        POOL_CONNECTIONS,
+        POOL_IDLE_CONNECTIONS,
+        POOL_MAX_CONNECTIONS,
+        POOL_WAITING_ACQUIRES,
+    ] {
+        assert!(
+            metrics.lines().any(|line| line.starts_with(name)),
+            "missing metric {}",
+            name
+        );
+    }
+    assert!(metrics.contains(&format!("{}_bucket", POOL_ACQUIRE_DURATION)));
+
+    // the requests are labeled by the route template instead of the raw path
+    assert!(metrics.contains(&format!(
+        r#"{}_bucket{{method="POST",route="/v1/user/product/query""#,
+        HTTP_REQUEST_DURATION
+    )));
+    assert!(metrics
+        .lines()
+        .any(|line| line.starts_with(HTTP_REQUEST_ERRORS)
+            && line.contains(r#"route="/v1/user/product/{id}""#)
+            && line.contains(r#"status="404""#)));
+    assert!(!metrics.contains("/v1/user/product/unknown"));
+}
+
+/// Runs the tests for unknown paths and unsupported methods.
+///
+/// # Arguments
+/// - `options` - The endpoint options.
+async fn fallback_tests(options: &EndpointOptions) {
+    let client = reqwest::Client::new();
+
+    // unknown path
+    let url = format!("http://{}/v1/user/unknown", options.address);
+    let response = client.get(url).send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    let response: OnlyMessageResponse = response.json().await.unwrap();
+    assert!(response.message.contains("/v1/user"));
+
+    // wrong method
+    let url = format!("http://{}/v1/user/product/query", options.address);
+    let response = client.get(url).send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::METHOD_NOT_ALLOWED);
+    assert_eq!(response.headers().get("allow").unwrap(), "POST");
+    let response: OnlyMessageResponse = response.json().await.unwrap();
+    assert!(response.message.contains("GET"));
+}
+
+/// Runs the tests for the OpenAPI specification and the Swagger UI, which is disabled.
+///
+/// # Arguments
+/// - `options` - The endpoint options.
+async fn openapi_tests(options: &EndpointOptions) {
+    let client = reqwest::Client::new();
+
+    let url = format!("http://{}/v1/openapi.json", options.address);
+    let response = client.get(url).send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let document: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(document["servers"][0]["url"], "/v1");
+    assert!(document["paths"]["/user/product/query"].is_object());
+
+    let url = format!("http://{}/v1/docs", options.address);
+    let response = client.get(url).send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+/// Runs the tests for the liveness and readiness probes.
+///
+/// # Arguments
+/// - `options` - The endpoint options.
+async fn probe_tests(options: &EndpointOptions) {
+    let client = reqwest::Client::new();
+
+    let url = format!("http://{}/healthz", options.address);
+    let response = client.get(url).send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let url = format!("http://{}/readyz", options.address);
+    let response = client.get(url).send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let report: HealthReport = response.json().await.unwrap();
+    assert_eq!(report.status, HealthStatus::Healthy);
+    assert!(report.details.is_none());
+}
+
+/// Runs the tests for the pagination headers of the query endpoints.
+///
+/// # Arguments
+/// - `options` - The endpoint options.
+async fn pagination_tests(options: &EndpointOptions) {
+    let client = reqwest::Client::new();
+
+    let url = format!("http://{}/v1/user/product/query", options.address);
+    let query = ProductQuery {
+        offset: 0,
+        limit: 1,
+        filter: SearchFilter::NoFilter,
+        sorting: Some(Sorting {
+            field: SortingField::Name,
+            order: SortingOrder::Ascending,
+        }),
+        market: None,
+        producer: None,
+        quantity_type: None,
+        labels: None,
+        max_nova_group: None,
+        min_eco_score_grade: None,
+        exclude_allergens: Vec::new(),
+        data_issues: Vec::new(),
+        fields: Some(vec![ProductField::Id]),
+        request_status: None,
+        cursor: None,
+    };
+
+    // the first page links to the next page only
+    let response = client.post(&url).json(&query).send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let total_count: u64 = response.headers()[TOTAL_COUNT_HEADER]
+        .to_str()
+        .unwrap()
+        .parse()
+        .unwrap();
+    assert!(total_count >= 2);
+    let link = response.headers()[LINK].to_str().unwrap().to_string();
+    assert_eq!(
+        link,
+        "</v1/user/product/query?offset=1&limit=1>; rel=\"next\""
+    );
+
+    // the next page is requested with the same body and links back to the first page
+    let next = format!(
+        "http://{}/v1/user/product/query?offset=1&limit=1",
+        options.address
+    );
+    let response = client.post(&next).json(&query).send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let link = response.headers()[LINK].to_str().unwrap().to_string();
+    assert!(link.contains("</v1/user/product/query?offset=0&limit=1>; rel=\"prev\""));
+    assert_eq!(link.contains("rel=\"next\""), total_count > 2);
+    let page: ProductProjectionQueryResponse = response.json().await.unwrap();
+    assert_eq!(page.products.len(), 1);
+    assert_eq!(page.total_count, total_count);
+
+    // the last page has no next page
+    let last = format!(
+        "http://{}/v1/user/product/query?offset={}",
+        options.address,
+        total_count - 1
+    );
+    let response = client.post(&last).json(&query).send().await.unwrap();
+    let link = response.headers()[LINK].to_str().unwrap().to_string();
+    assert!(!link.contains("rel=\"next\""));
+    assert!(link.contains(&format!(
+        "offset={}&limit=1>; rel=\"prev\"",
+        total_count - 2
+    )));
+
+    // the cursor of a full page continues with the next page instead of the offset
+    let response = client
+        .post(&url)
+        .json(&ProductQuery {
+            limit: total_count as i32,
+            ..query.clone()
+        })
+        .send()
+        .await
+        .unwrap();
+    let all: ProductProjectionQueryResponse = response.json().await.unwrap();
+    assert!(all.next_cursor.is_some());
+    let mut cursor_query = query.clone();
+    let mut paged = Vec::new();
+    loop {
+        let response = client.post(&url).json(&cursor_query).send().await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        if cursor_query.cursor.is_some() {
+            assert!(response.headers().get(LINK).is_none());
+            assert!(response.headers().contains_key(TOTAL_COUNT_HEADER));
+        }
+        let page: ProductProjectionQueryResponse = response.json().await.unwrap();
+        paged.extend(page.products);
+        match page.next_cursor {
+            Some(cursor) => cursor_query.cursor = Some(cursor),
+            None => break,
+        }
+    }
+    assert_eq!(paged, all.products);
+
+    // the cursor requires the sorting it has been created for
+    let response = client
+        .post(&url)
+        .json(&ProductQuery {
+            sorting: None,
+            ..cursor_query
+        })
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    // the product requests are counted as well
+    let url = format!("http://{}/v1/admin/product_request/query", options.address);
+    let request_query = ProductQuery {
+        fields: None,
+        ..query.clone()
+    };
+    let response = client.post(&url).json(&request_query).send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let total_count: u64 = response.headers()[TOTAL_COUNT_HEADER]
+        .to_str()
+        .unwrap()
+        .parse()
+        .unwrap();
+    let page: ProductRequestQueryResponse = response.json().await.unwrap();
+    assert_eq!(page.total_count, total_count);
+    assert!(page.product_requests.len() as u64 <= total_count);
+
+    // the endpoints without count only link to the next page if the page is full
+    let url = format!("http://{}/v1/user/store/query", options.address);
+    let query = StoreQuery {
+        offset: 0,
+        limit: 1000,
+        chain: None,
+        order: SortingOrder::Ascending,
+    };
+    let response = client.post(&url).json(&query).send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(response.headers().get(LINK).is_none());
+    assert!(response.headers().get(TOTAL_COUNT_HEADER).is_none());
+}
+
+/// Runs the tests for the separate CORS policies of the user and admin endpoints.
+///
+/// # Arguments
+/// - `options` - The endpoint options.
+async fn cors_tests(options: &EndpointOptions) {
+    let client = reqwest::Client::new();
+
+    // the user endpoints allow any origin
+    let url = format!("http://{}/v1/user/product/query", options.address);
+    let response = client
+        .request(reqwest::Method::OPTIONS, url)
+        .header(ORIGIN, "https://app.example.com")
+        .header(ACCESS_CONTROL_REQUEST_METHOD, "POST")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()[ACCESS_CONTROL_ALLOW_ORIGIN], "*");
+
+    // the admin endpoints only allow the origin of the dashboard
+    let url = format!("http://{}/v1/admin/maintenance", options.address);
+    let response = client
+        .request(reqwest::Method::OPTIONS, url.clone())
+        .header(ORIGIN, "https://app.example.com")
+        .header(ACCESS_CONTROL_REQUEST_METHOD, "PUT")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.headers()[ACCESS_CONTROL_ALLOW_ORIGIN],
+        "https://dashboard.example.com"
+    );
+
+    let response = client
+        .get(url)
+        .header(ORIGIN, "https://dashboard.example.com")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.headers()[ACCESS_CONTROL_ALLOW_ORIGIN],
+        "https://dashboard.example.com"
+    );
+
+    // unknown paths fall back to the default origin
+    let url = format!("http://{}/v1/unknown", options.address);
+    let response = client
+        .get(url)
+        .header(ORIGIN, "https://app.example.com")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    assert_eq!(response.headers()[ACCESS_CONTROL_ALLOW_ORIGIN], "*");
+}
+
+/// Runs the tests for the custom layer and routes registered via the service builder.
+///
+/// # Arguments
+/// - `options` - The endpoint options.
+async fn extension_tests(options: &EndpointOptions) {
+    let client = reqwest::Client::new();
+
+    let url = format!("http://{}/custom/ping", options.address);
+    let response = client.get(url).send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers().get("x-custom-layer").unwrap(), "active");
+    assert_eq!(response.text().await.unwrap(), "pong");
+
+    // the layer must also wrap the routes of the service
+    let url = format!("http://{}/v1/admin/maintenance", options.address);
+    let response = client.get(url).send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers().get("x-custom-layer").unwrap(), "active");
+}
+
+/// Runs the tests for the service router embedded into another axum application.
+///
+/// # Arguments
+/// - `router` - The router of the service.
+async fn embedded_router_tests(router: axum::Router) {
+    const EMBEDDED_ADDRESS: &str = "0.0.0.0:8889";
+
+    let app = axum::Router::new().nest("/embedded", router);
+    let listener = tokio::net::TcpListener::bind(EMBEDDED_ADDRESS)
+        .await
+        .unwrap();
+    let server = tokio::spawn(async move {
+        axum::serve(listener, app).await.unwrap();
+    });
+
+    let client = ServiceClient::new(format!("{}/embedded", EMBEDDED_ADDRESS));
+    let products = client
+        .query_products(&ProductQuery {
+            offset: 0,
+            limit: 10,
+            filter: SearchFilter::NoFilter,
+            sorting: None,
+            market: None,
+            producer: None,
+            quantity_type: None,
+            labels: None,
+            max_nova_group: None,
+            min_eco_score_grade: None,
+            exclude_allergens: Vec::new(),
+            data_issues: Vec::new(),
+            fields: None,
+            request_status: None,
+            cursor: None,
+        })
+        .await;
+    assert!(!products.is_empty());
+
+    let product = client.get_product(&products[0].info.id, false, false).await;
+    assert!(product.is_some());
+
+    server.abort();
+}
+
+/// Runs the tests for the signed requests on the admin endpoints with a separate service
+/// instance that requires signatures.
+///
+/// # Arguments
+/// - `options` - The options for initializing the service.
+async fn request_signing_tests<B: DataBackend + 'static>(mut options: Options) {
+    const SIGNED_ADDRESS: &str = "0.0.0.0:8890";
+
+    let signer = RequestSigner::new(
+        Secret::from_str("request-signing-secret").unwrap(),
+        Duration::from_secs(60),
+    );
+    options.endpoint.address = SIGNED_ADDRESS.to_string();
+    options.endpoint.request_signing = Some(RequestSigningOptions {
+        secret: Secret::from_str("request-signing-secret").unwrap(),
+        max_clock_skew_secs: 60,
+    });
+
+    let service: Service<B> = Service::new(options).await.unwrap();
+    let router = service.router().unwrap();
+    let listener = tokio::net::TcpListener::bind(SIGNED_ADDRESS).await.unwrap();
+    let server = tokio::spawn(async move {
+        axum::serve(listener, router).await.unwrap();
+    });
+
+    let client = reqwest::Client::new();
+    let url = format!("http://{}/v1/admin/maintenance", SIGNED_ADDRESS);
+    let body = serde_json::to_vec(&MaintenanceModeRequest { read_only: false }).unwrap();
+
+    // unsigned requests are rejected
+    let response = client.get(&url).send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    // signed requests are accepted
+    let timestamp = Utc::now().timestamp();
+    let signature = signer.sign(timestamp, "PUT", "/v1/admin/maintenance", &body);
+    let response = client
+        .put(&url)
+        .header(CONTENT_TYPE, "application/json")
+        .header(SIGNATURE_TIMESTAMP_HEADER, timestamp.to_string())
+        .header(SIGNATURE_HEADER, &signature)
+        .body(body.clone())
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // the signature does not match a modified body
+    let response = client
+        .put(&url)
+        .header(CONTENT_TYPE, "application/json")
+        .header(SIGNATURE_TIMESTAMP_HEADER, timestamp.to_string())
+        .header(SIGNATURE_HEADER, &signature)
+        .body(serde_json::to_vec(&MaintenanceModeRequest { read_only: true }).unwrap())
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    // expired signatures are rejected
+    let timestamp = Utc::now().timestamp() - 120;
+    let signature = signer.sign(timestamp, "PUT", "/v1/admin/maintenance", &body);
+    let response = client
+        .put(&url)
+        .header(CONTENT_TYPE, "application/json")
+        .header(SIGNATURE_TIMESTAMP_HEADER, timestamp.to_string())
+        .header(SIGNATURE_HEADER, signature)
+        .body(body)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    // the user endpoints stay open
+    let client = ServiceClient::new(SIGNED_ADDRESS.to_string());
+    let products = client
+        .query_products(&ProductQuery {
+            offset: 0,
+            limit: 10,
+            filter: SearchFilter::NoFilter,
+            sorting: None,
+            market: None,
+            producer: None,
+            quantity_type: None,
+            labels: None,
+            max_nova_group: None,
+            min_eco_score_grade: None,
+            exclude_allergens: Vec::new(),
+            data_issues: Vec::new(),
+            fields: None,
+            request_status: None,
+            cursor: None,
+        })
+        .await;
+    assert!(!products.is_empty());
+
+    server.abort();
+}
+
+/// Runs the tests for the client certificates on the admin endpoints with a separate service
+/// instance that is served via TLS.
+///
+/// # Arguments
+/// - `options` - The options for initializing the service.
+async fn tls_tests<B: DataBackend + 'static>(mut options: Options) {
+    const TLS_ADDRESS: &str = "localhost:8891";
+
+    // create the CA, the server certificate and the client certificate
+    let mut ca_params = rcgen::CertificateParams::new(Vec::new()).unwrap();
+    ca_params.is_ca = rcgen::IsCa::Ca(rcgen::BasicConstraints::Unconstrained);
+    ca_params
+        .distinguished_name
+        .push(rcgen::DnType::CommonName, "product-db test CA");
+    let ca = rcgen::CertifiedIssuer::self_signed(ca_params, rcgen::KeyPair::generate().unwrap())
+        .unwrap();
+
+    let server_key = rcgen::KeyPair::generate().unwrap();
+    let mut server_params = rcgen::CertificateParams::new(vec!["localhost".to_string()]).unwrap();
+    server_params
+        .distinguished_name
+        .push(rcgen::DnType::CommonName, "localhost");
+    let server_cert = server_params.signed_by(&server_key, &ca).unwrap();
+
+    let client_key = rcgen::KeyPair::generate().unwrap();
+    let mut client_params = rcgen::CertificateParams::new(Vec::new()).unwrap();
+    client_params
+        .distinguished_name
+        .push(rcgen::DnType::CommonName, "product-db admin");
+    client_params.extended_key_usages = vec![rcgen::ExtendedKeyUsagePurpose::ClientAuth];
+    let client_cert = client_params.signed_by(&client_key, &ca).unwrap();
+
+    let dir = tempfile::tempdir().unwrap();
+    let write = |name: &str, content: String| {
+        let path = dir.path().join(name);
+        std::fs::write(&path, content).unwrap();
+        path
+    };
+
+    options.endpoint.address = TLS_ADDRESS.to_string();
+    options.endpoint.tls = Some(TlsOptions {
+        cert_file: write("server.pem", server_cert.pem()),
+        key_file: write("server.key", server_key.serialize_pem()),
+        client_ca_file: Some(write("ca.pem", ca.pem())),
+    });
+
+    let service: Arc<Service<B>> = Arc::new(Service::new(options).await.unwrap());
+    let server = tokio::spawn({
+        let service = service.clone();
+        async move { service.run().await.unwrap() }
+    });
+    tokio::time::sleep(Duration::from_millis(200)).await;
+
+    let ca_cert = reqwest::Certificate::from_pem(ca.pem().as_bytes()).unwrap();
+    let anonymous = reqwest::Client::builder()
+        .add_root_certificate(ca_cert.clone())
+        .build()
+        .unwrap();
+    let identity = reqwest::Identity::from_pkcs8_pem(
+        client_cert.pem().as_bytes(),
+        client_key.serialize_pem().as_bytes(),
+    )
+    .unwrap();
+    let authenticated = reqwest::Client::builder()
+        .add_root_certificate(ca_cert)
+        .identity(identity)
+        .build()
+        .unwrap();
+
+    // the admin endpoints require a client certificate
+    let url = format!("https://{}/v1/admin/maintenance", TLS_ADDRESS);
+    let response = anonymous.get(&url).send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    let response = authenticated.get(&url).send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // the user endpoints stay open
+    let url = format!("https://{}/v1/user/product/query", TLS_ADDRESS);
+    let query = ProductQuery {
+        offset: 0,
+        limit: 10,
+        filter: SearchFilter::NoFilter,
+        sorting: None,
+        market: None,
+        producer: None,
+        quantity_type: None,
+        labels: None,
+        max_nova_group: None,
+        min_eco_score_grade: None,
+        exclude_allergens: Vec::new(),
+        data_issues: Vec::new(),
+        fields: None,
+        request_status: None,
+        cursor: None,
+    };
+    let response = anonymous.post(&url).json(&query).send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    service.stop();
+    server.await.unwrap();
+}
+
+/// Runs the tests for the IP allowlist of the admin endpoints with a separate service instance
+/// behind a single trusted proxy.
+///
+/// # Arguments
+/// - `options` - The options for initializing the service.
+async fn admin_allowlist_tests<B: DataBackend + 'static>(mut options: Options) {
+    const ALLOWLIST_ADDRESS: &str = "127.0.0.1:8892";
+
+    options.endpoint.address = ALLOWLIST_ADDRESS.to_string();
+    options.endpoint.admin_allowlist = Some(AdminAllowlistOptions {
+        networks: vec!["10.0.0.0/8".parse().unwrap()],
+        trusted_proxy_depth: 1,
+    });
+
+    let service: Arc<Service<B>> = Arc::new(Service::new(options).await.unwrap());
+    let server = tokio::spawn({
+        let service = service.clone();
+        async move { service.run().await.unwrap() }
+    });
+    tokio::time::sleep(Duration::from_millis(200)).await;
+
+    let client = reqwest::Client::new();
+    let url = format!("http://{}/v1/admin/maintenance", ALLOWLIST_ADDRESS);
+
+    // requests that did not pass the trusted proxy are rejected
+    let response = client.get(&url).send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    // the trusted proxy appended an address of the allowed network
+    let response = client
+        .get(&url)
+        .header("x-forwarded-for", "10.1.2.3")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // addresses in front of the one appended by the trusted proxy are ignored
+    let response = client
+        .get(&url)
+        .header("x-forwarded-for", "10.1.2.3, 192.168.0.1")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    // the user endpoints stay open
+    let url = format!("http://{}/v1/user/product/query", ALLOWLIST_ADDRESS);
+    let query = ProductQuery {
+        offset: 0,
+        limit: 10,
+        filter: SearchFilter::NoFilter,
+        sorting: None,
+        market: None,
+        producer: None,
+        quantity_type: None,
+        labels: None,
+        max_nova_group: None,
+        min_eco_score_grade: None,
+        exclude_allergens: Vec::new(),
+        data_issues: Vec::new(),
+        fields: None,
+        request_status: None,
+        cursor: None,
+    };
+    let response = client.post(&url).json(&query).send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    service.stop();
+    server.await.unwrap();
+}
+
+/// Runs the tests for the API-key authentication of the admin endpoints with a separate service
+/// instance.
+///
+/// # Arguments
+/// - `options` - The options for initializing the service.
+async fn api_key_auth_tests<B: DataBackend + 'static>(mut options: Options) {
+    const API_KEY_ADDRESS: &str = "127.0.0.1:8898";
+
+    options.endpoint.address = API_KEY_ADDRESS.to_string();
+    options.endpoint.auth = Some(AuthOptions {
+        admin_api_keys: vec![
+            Secret::new("first-key".to_string()),
+            Secret::new("second-key".to_string()),
+        ],
+        jwt: None,
+    });
+
+    let service: Arc<Service<B>> = Arc::new(Service::new(options).await.unwrap());
+    let server = tokio::spawn({
+        let service = service.clone();
+        async move { service.run().await.unwrap() }
+    });
+    tokio::time::sleep(Duration::from_millis(200)).await;
+
+    let client = reqwest::Client::new();
+    let url = format!("http://{}/v1/admin/maintenance", API_KEY_ADDRESS);
+
+    // unauthenticated requests are rejected with a problem document
+    let response = client.get(&url).send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    assert_eq!(
+        response.headers().get(CONTENT_TYPE).unwrap(),
+        "application/problem+json"
+    );
+
+    // unknown keys are rejected
+    let response = client
+        .get(&url)
+        .header(API_KEY_HEADER, "third-key")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    // each configured key is accepted via the header or as bearer token
+    let response = client
+        .get(&url)
+        .header(API_KEY_HEADER, "first-key")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let response = client
+        .get(&url)
+        .bearer_auth("second-key")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // mutating requests are rejected before reaching the handler
+    let response = client
+        .delete(format!(
+            "http://{}/v1/admin/product/foobar",
+            API_KEY_ADDRESS
+        ))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    // the user endpoints stay open
+    let url = format!("http://{}/v1/user/product/query", API_KEY_ADDRESS);
+    let query = ProductQuery {
+        offset: 0,
+        limit: 10,
+        filter: SearchFilter::NoFilter,
+        sorting: None,
+        market: None,
+        producer: None,
+        quantity_type: None,
+        labels: None,
+        max_nova_group: None,
+        min_eco_score_grade: None,
+        exclude_allergens: Vec::new(),
+        data_issues: Vec::new(),
+        fields: None,
+        request_status: None,
+        cursor: None,
+    };
+    let response = client.post(&url).json(&query).send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    service.stop();
+    server.await.unwrap();
+}
+
+/// Runs the tests for a read-only service that serves the read operations of a backend, e.g.
+/// of a read replica.
+///
+/// # Arguments
+/// - `options` - The options for initializing the service.
+async fn read_only_service_tests<B: DataBackend + 'static>(mut options: Options) {
+    const READ_ONLY_ADDRESS: &str = "127.0.0.1:8895";
+
+    options.endpoint.address = READ_ONLY_ADDRESS.to_string();
+
+    // the write operations of a read-only backend fail
+    let backend = ReadOnlyBackend::new(B::new(&options).await.unwrap());
+    assert!(matches!(
+        backend.delete_product(&"foobar".to_string()).await,
+        Err(Error::ReadOnlyBackend)
+    ));
+    assert!(backend.health_check().await.is_available());
+    backend.ping().await.unwrap();
+
+    let service = Arc::new(Service::read_only(options, backend.into_inner()));
+    assert!(service.is_read_only());
+    let server = tokio::spawn({
+        let service = service.clone();
+        async move { service.run().await.unwrap() }
+    });
+    tokio::time::sleep(Duration::from_millis(200)).await;
+
+    let client = reqwest::Client::new();
+
+    // the queries are served
+    let url = format!("http://{}/v1/user/product/query", READ_ONLY_ADDRESS);
+    let query = ProductQuery {
+        offset: 0,
+        limit: 10,
+        filter: SearchFilter::NoFilter,
+        sorting: None,
+        market: None,
+        producer: None,
+        quantity_type: None,
+        labels: None,
+        max_nova_group: None,
+        min_eco_score_grade: None,
+        exclude_allergens: Vec::new(),
+        data_issues: Vec::new(),
+        fields: None,
+        request_status: None,
+        cursor: None,
+    };
+    let response = client.post(&url).json(&query).send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // the writes are rejected
+    let url = format!("http://{}/v1/user/missing_products", READ_ONLY_ADDRESS);
+    let response = client
+        .post(&url)
+        .json(&MissingProductReportRequest {
+            product_id: "4001724819806".to_string(),
+            store_id: None,
+            photo: None,
+        })
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+
+    // the read-only mode cannot be switched off
+    assert!(matches!(
+        service.set_read_only(false),
+        Err(Error::ReadOnlyBackend)
+    ));
+    let url = format!("http://{}/v1/admin/maintenance", READ_ONLY_ADDRESS);
+    let response = client
+        .put(&url)
+        .json(&MaintenanceModeRequest { read_only: false })
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+    assert!(service.is_read_only());
+
+    service.stop();
+    server.await.unwrap();
+}
+
+/// Runs the tests for the independent rate limit of the image endpoints with a separate
+/// service instance.
+///
+/// # Arguments
+/// - `options` - The options for the service.
+async fn image_rate_limit_tests<B: DataBackend + 'static>(mut options: Options) {
+    const RATE_LIMIT_ADDRESS: &str = "127.0.0.1:8896";
+
+    options.endpoint.address = RATE_LIMIT_ADDRESS.to_string();
+    options.endpoint.image_rate_limit = Some(RateLimitOptions {
+        requests_per_second: 0.1,
+        burst: 2,
+        bytes_per_second: None,
+        burst_bytes: None,
+        trusted_proxy_depth: 0,
+    });
+
+    let service: Arc<Service<B>> = Arc::new(Service::new(options).await.unwrap());
+    let server = tokio::spawn({
+        let service = service.clone();
+        async move { service.run().await.unwrap() }
+    });
+    tokio::time::sleep(Duration::from_millis(200)).await;
+
+    let client = reqwest::Client::new();
+
+    // the burst of image requests is served, the next one is rejected
+    let url = format!(
+        "http://{}/v1/user/product/rate-limit-missing/image",
+        RATE_LIMIT_ADDRESS
+    );
+    for _ in 0..2 {
+        let response = client.get(&url).send().await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+    let response = client.get(&url).send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+    let retry_after: u64 = response.headers()[RETRY_AFTER]
+        .to_str()
+        .unwrap()
+        .parse()
+        .unwrap();
+    assert!(retry_after >= 1);
+
+    // the other endpoints are not affected
+    let url = format!("http://{}/v1/user/product/query", RATE_LIMIT_ADDRESS);
+    let query = ProductQuery {
+        offset: 0,
+        limit: 10,
+        filter: SearchFilter::NoFilter,
+        sorting: None,
+        market: None,
+        producer: None,
+        quantity_type: None,
+        labels: None,
+        max_nova_group: None,
+        min_eco_score_grade: None,
+        exclude_allergens: Vec::new(),
+        data_issues: Vec::new(),
+        fields: None,
+        request_status: None,
+        cursor: None,
+    };
+    for _ in 0..3 {
+        let response = client.post(&url).json(&query).send().await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    service.stop();
+    server.await.unwrap();
+}
+
+/// Runs the tests for the image quota with a separate service instance. The total quota is
+/// exhausted from the start, i.e., only products without images are accepted.
+///
+/// # Arguments
+/// - `options` - The options for initializing the service.
+async fn image_quota_tests<B: DataBackend + 'static>(mut options: Options) {
+    const IMAGE_QUOTA_ADDRESS: &str = "127.0.0.1:8897";
+
+    options.endpoint.address = IMAGE_QUOTA_ADDRESS.to_string();
+    options.endpoint.image_quota = ImageQuotaOptions {
+        max_total_bytes: Some(0),
+        max_product_bytes: Some(10),
+        max_image_bytes: None,
+        verify_content_types: false,
+    };
+
+    let service: Arc<Service<B>> = Arc::new(Service::new(options).await.unwrap());
+    let server = tokio::spawn({
+        let service = service.clone();
+        async move { service.run().await.unwrap() }
+    });
+    tokio::time::sleep(Duration::from_millis(200)).await;
+
+    let client = ServiceClient::new(IMAGE_QUOTA_ADDRESS.to_string());
+    let product_url = client.server_address.join("admin/product").unwrap();
+    let request_url = client.server_address.join("user/product_request").unwrap();
+
+    let mut product = load_products()[1].clone();
+    product.info.id = "image-quota".to_string();
+    product.preview = Some(ProductImage {
+        content_type: "image/webp".to_string(),
+        data: vec![1, 2, 3, 4, 5, 6],
+    });
+    product.full_image = Some(ProductImage {
+        content_type: "image/jpeg".to_string(),
+        data: vec![1, 2, 3, 4, 5],
+    });
+
+    // the images of the product exceed the per-product limit
+    for url in [&product_url, &request_url] {
+        let response = client
+            .client
+            .post(url.clone())
+            .json(&product)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    }
+
+    // the images of the product exceed the total quota
+    product.full_image = None;
+    for url in [&product_url, &request_url] {
+        let response = client
+            .client
+            .post(url.clone())
+            .json(&product)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::INSUFFICIENT_STORAGE);
+    }
+
+    // products without images are not affected
+    product.preview = None;
+    assert!(client.new_product(&product).await);
+    client.delete_product(&product.info.id).await;
+
+    // the usage reports the limits and the totals of the content types
+    let response = client
+        .client
+        .get(client.server_address.join("admin/images/usage").unwrap())
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let usage: ImageStorageUsageResponse = response.json().await.unwrap();
+    assert_eq!(usage.max_total_bytes, Some(0));
+    assert_eq!(usage.max_product_bytes, Some(10));
+    assert_eq!(
+        usage.total_bytes,
+        usage.content_types.iter().map(|u| u.bytes).sum::<u64>()
+    );
+    assert_eq!(
+        usage.total_images,
+        usage.content_types.iter().map(|u| u.images).sum::<u64>()
+    );
+
+    service.stop();
+    server.await.unwrap();
+}
+
+/// Runs the tests for rejecting submitted images that are too large or whose data does not match
+/// their content type.
+async fn image_validation_tests<B: DataBackend + 'static>(mut options: Options) {
+    const IMAGE_VALIDATION_ADDRESS: &str = "127.0.0.1:8900";
+
+    options.endpoint.address = IMAGE_VALIDATION_ADDRESS.to_string();
+    options.endpoint.image_quota = ImageQuotaOptions {
+        max_total_bytes: None,
+        max_product_bytes: None,
+        max_image_bytes: Some(8),
+        verify_content_types: true,
+    };
+
+    let service: Arc<Service<B>> = Arc::new(Service::new(options).await.unwrap());
+    let server = tokio::spawn({
+        let service = service.clone();
+        async move { service.run().await.unwrap() }
+    });
+    tokio::time::sleep(Duration::from_millis(200)).await;
+
+    let client = ServiceClient::new(IMAGE_VALIDATION_ADDRESS.to_string());
+    let product_url = client.server_address.join("admin/product").unwrap();
+    let request_url = client.server_address.join("user/product_request").unwrap();
+
+    let mut product = load_products()[1].clone();
+    product.info.id = "image-validation".to_string();
+    product.preview = None;
+
+    let rejected = [
+        // an arbitrary blob declared as JPEG
+        (
+            ProductImage {
+                content_type: "image/jpeg".to_string(),
+                data: vec![1, 2, 3, 4, 5],
+            },
+            StatusCode::UNSUPPORTED_MEDIA_TYPE,
+        ),
+        // an unsupported format
+        (
+            ProductImage {
+                content_type: "image/svg+xml".to_string(),
+                data: b"<svg/>".to_vec(),
+            },
+            StatusCode::UNSUPPORTED_MEDIA_TYPE,
+        ),
+        // a JPEG exceeding the limit for a single image
+        (
+            ProductImage {
+                content_type: "image/jpeg".to_string(),
+                data: vec![0xff, 0xd8, 0xff, 0xe0, 0, 0, 0, 0, 0],
+            },
+            StatusCode::PAYLOAD_TOO_LARGE,
+        ),
+    ];
+    for (image, status) in rejected {
+        product.full_image = Some(image);
+        for url in [&product_url, &request_url] {
+            let response = client
+                .client
+                .post(url.clone())
+                .json(&product)
+                .send()
+                .await
+                .unwrap();
+            assert_eq!(response.status(), status);
+        }
+    }
+
+    // a JPEG within the limit is accepted
+    product.full_image = Some(ProductImage {
+        content_type: "image/jpeg".to_string(),
+        data: vec![0xff, 0xd8, 0xff, 0xe0, 0, 0, 0, 0],
+    });
+    assert!(client.new_product(&product).await);
+    client.delete_product(&product.info.id).await;
+
+    service.stop();
+    server.await.unwrap();
+}
+
+/// Runs the tests for generating the previews of submitted products from their full images.
+///
+/// # Arguments
+/// - `options` - The options for initializing the service.
+async fn preview_tests<B: DataBackend + 'static>(mut options: Options) {
+    const PREVIEW_ADDRESS: &str = "127.0.0.1:8899";
+
+    options.endpoint.address = PREVIEW_ADDRESS.to_string();
+    options.endpoint.previews = Some(PreviewOptions {
+        max_width: 32,
+        max_height: 32,
+    });
+
+    let service: Arc<Service<B>> = Arc::new(Service::new(options).await.unwrap());
+    let server = tokio::spawn({
+        let service = service.clone();
+        async move { service.run().await.unwrap() }
+    });
+    tokio::time::sleep(Duration::from_millis(200)).await;
+
+    let client = ServiceClient::new(PREVIEW_ADDRESS.to_string());
+
+    // encode a gray image of 128x64 pixels as full image
+    let mut full_image = Vec::new();
+    let mut encoder = png::Encoder::new(&mut full_image, 128, 64);
+    encoder.set_color(png::ColorType::Grayscale);
+    let mut writer = encoder.write_header().unwrap();
+    writer.write_image_data(&[128; 128 * 64]).unwrap();
+    writer.finish().unwrap();
+
+    let mut product = load_products()[1].clone();
+    product.info.id = "generated-preview".to_string();
+    product.preview = None;
+    product.full_image = Some(ProductImage {
+        content_type: "image/png".to_string(),
+        data: full_image,
+    });
+    assert!(client.new_product(&product).await);
+
+    let stored = client
+        .get_product(&product.info.id, true, true)
+        .await
+        .unwrap();
+    assert_eq!(stored.full_image, product.full_image);
+    let preview = stored.preview.unwrap();
+    assert_eq!(preview.content_type, "image/png");
+    let info = png::Decoder::new(preview.data.as_slice())
+        .read_info()
+        .unwrap()
+        .info()
+        .clone();
+    assert_eq!((info.width, info.height), (32, 16));
+    client.delete_product(&product.info.id).await;
+
+    // undecodable full images are stored without preview
+    product.full_image = Some(ProductImage {
+        content_type: "image/png".to_string(),
+        data: vec![1, 2, 3],
+    });
+    assert!(client.new_product(&product).await);
+    let stored = client
+        .get_product(&product.info.id, true, true)
+        .await
+        .unwrap();
+    assert!(stored.preview.is_none());
+    assert_eq!(stored.full_image, product.full_image);
+    client.delete_product(&product.info.id).await;
+
+    service.stop();
+    server.await.unwrap();
+}
+
+/// Runs the tests for serving the admin endpoints on a separate address with a separate
+/// service instance.
+///
+/// # Arguments
+/// - `options` - The options for initializing the service.
+async fn separate_admin_address_tests<B: DataBackend + 'static>(mut options: Options) {
+    const USER_ADDRESS: &str = "127.0.0.1:8893";
+    const ADMIN_ADDRESS: &str = "127.0.0.1:8894";
+
+    options.endpoint.address = USER_ADDRESS.to_string();
+    options.endpoint.admin_address = Some(ADMIN_ADDRESS.to_string());
+
+    let service: Arc<Service<B>> = Arc::new(Service::new(options).await.unwrap());
+    let server = tokio::spawn({
+        let service = service.clone();
+        async move { service.run().await.unwrap() }
+    });
+    tokio::time::sleep(Duration::from_millis(200)).await;
+
+    let client = reqwest::Client::new();
+    let query = ProductQuery {
+        offset: 0,
+        limit: 10,
+        filter: SearchFilter::NoFilter,
+        sorting: None,
+        market: None,
+        producer: None,
+        quantity_type: None,
+        labels: None,
+        max_nova_group: None,
+        min_eco_score_grade: None,
+        exclude_allergens: Vec::new(),
+        data_issues: Vec::new(),
+        fields: None,
+        request_status: None,
+        cursor: None,
+    };
+
+    // the admin endpoints are only served on the admin address
+    let response = client
+        .get(format!("http://{}/v1/admin/maintenance", USER_ADDRESS))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    let response = client
+        .get(format!("http://{}/v1/admin/maintenance", ADMIN_ADDRESS))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // the user endpoints are only served on the user address
+    let response = client
+        .post(format!("http://{}/v1/user/product/query", USER_ADDRESS))
+        .json(&query)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = client
+        .post(format!("http://{}/v1/user/product/query", ADMIN_ADDRESS))
+        .json(&query)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    // the probes are served on both addresses
+    for address in [USER_ADDRESS, ADMIN_ADDRESS] {
+        let response = client
+            .get(format!("http://{}/readyz", address))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    // both servers stop on the stop signal
+    service.stop();
+    server.await.unwrap();
+}
+
+/// Runs the service tests with the given backend.
+///
+/// # Arguments
+/// - `options` - The options for initializing the service.
+async fn service_tests<B: DataBackend + 'static>(options: Options) {
+    let endpoint_options = options.endpoint.clone();
+
+    // register a custom layer and route to check the extension points of the service
+    let custom_layer = axum::middleware::from_fn(
+        |request: axum::extract::Request, next: axum::middleware::Next| async move {
+            let mut response = next.run(request).await;
+            response
+                .headers_mut()
+                .insert("x-custom-layer", "active".parse().unwrap());
+            response
+        },
+    );
+    let custom_routes = axum::Router::new().route(
+        "/custom/ping",
+        axum::routing::get(|| async { "pong".to_string() }),
+    );
+
+    // the credentials are "rotated" to the same password
+    let password = options.postgres.password.clone();
+    let credentials_source: CredentialsSource = Arc::new(move || {
+        let password = password.clone();
+        Box::pin(async move { Ok(password) })
+    });
+
+    // the products whose id starts with "nova-" are classified as ultra-processed
+    let nova_classifier: NovaClassifier =
+        Arc::new(|product: &ProductDescription| product.info.id.starts_with("nova-").then_some(4));
+
+    // the previews are the first bytes of the marked full images, all other images fail
+    let preview_generator: PreviewGenerator = Arc::new(|image: &ProductImage| {
+        if image.content_type != "image/x-regenerate" {
+            return Err(Error::InternalError("Unsupported image".to_string()));
+        }
+
+        Ok(ProductImage {
+            content_type: "image/x-preview".to_string(),
+            data: image.data[..16].to_vec(),
+        })
+    });
+
+    let separate_options = options.clone();
+
+    info!("TEST: Creating service instance...");
+    let service: Arc<Service<B>> = Arc::new(
+        Service::builder(options)
+            .routes(custom_routes)
+            .layer(custom_layer)
+            .credentials_source(credentials_source)
+            .nova_classifier(nova_classifier)
+            .preview_generator(preview_generator)
+            .build()
+            .await
+            .unwrap(),
+    );
+    let service_clone = service.clone();
+    let router = service.router().unwrap();
+
+    let ret = service.run();
+
+    info!("TEST: Creating service instance...DONE");
+
+    // spawn a task that will stop the service after 1 second
+    tokio::spawn(async move {
+        info!("Running backend tests...");
+        missing_product_tests(&endpoint_options).await;
+        info!("Running backend tests...SUCCESS");
+
+        info!("Running missing products batch tests...");
+        missing_products_batch_tests(&endpoint_options).await;
+        info!("Running missing products batch tests...SUCCESS");
+
+        info!("Running missing product photo tests...");
+        missing_product_photo_tests(&endpoint_options).await;
+        info!("Running missing product photo tests...SUCCESS");
+
+        info!("Running product requests tests...");
+        product_requests_tests(&endpoint_options).await;
+        info!("Running product requests tests...SUCCESS");
+
+        info!("Running product tests...");
+        product_tests(&endpoint_options).await;
+        info!("Running product tests...SUCCESS");
+
+        info!("Running reference amount tests...");
+        reference_amount_tests(&endpoint_options).await;
+        info!("Running reference amount tests...SUCCESS");
+
+        info!("Running market tests...");
+        market_tests(&endpoint_options).await;
+        info!("Running market tests...SUCCESS");
+
+        info!("Running plausibility tests...");
+        plausibility_tests(&endpoint_options).await;
+        info!("Running plausibility tests...SUCCESS");
+
+        info!("Running packaging tests...");
+        packaging_tests(&endpoint_options).await;
+        info!("Running packaging tests...SUCCESS");
+
+        info!("Running Eco-Score tests...");
+        eco_score_tests(&endpoint_options).await;
+        info!("Running Eco-Score tests...SUCCESS");
+
+        info!("Running NOVA group tests...");
+        nova_group_tests(&endpoint_options).await;
+        info!("Running NOVA group tests...SUCCESS");
+
+        info!("Running nutrient aggregation tests...");
+        nutrient_aggregation_tests(&endpoint_options).await;
+        info!("Running nutrient aggregation tests...SUCCESS");
+
+        info!("Running recipe tests...");
+        recipe_tests(&endpoint_options).await;
+        info!("Running recipe tests...SUCCESS");
+
+        info!("Running price tests...");
+        price_tests(&endpoint_options).await;
+        info!("Running price tests...SUCCESS");
+
+        info!("Running store tests...");
+        store_tests(&endpoint_options).await;
+        info!("Running store tests...SUCCESS");
+
+        info!("Running content negotiation tests...");
+        content_negotiation_tests(&endpoint_options).await;
+        info!("Running content negotiation tests...SUCCESS");
+
+        info!("Running image range tests...");
+        image_range_tests(&endpoint_options).await;
+        info!("Running image range tests...SUCCESS");
+
+        info!("Running signed image url tests...");
+        signed_image_url_tests(&endpoint_options).await;
+        info!("Running signed image url tests...SUCCESS");
+
+        info!("Running cache control tests...");
+        cache_control_tests(&endpoint_options).await;
+        info!("Running cache control tests...SUCCESS");
+
+        info!("Running pagination tests...");
+        pagination_tests(&endpoint_options).await;
+        info!("Running pagination tests...SUCCESS");
+
+        info!("Running reload credentials tests...");
+        reload_credentials_tests(&endpoint_options).await;
+        info!("Running reload credentials tests...SUCCESS");
+
+        info!("Running self-test tests...");
+        self_test_tests(&endpoint_options).await;
+        info!("Running self-test tests...SUCCESS");
+
+        info!("Running update product tests...");
+        update_product_tests(&endpoint_options).await;
+        info!("Running update product tests...SUCCESS");
+
+        info!("Running archive tests...");
+        archive_tests(&endpoint_options).await;
+        info!("Running archive tests...SUCCESS");
+
+        info!("Running additional images tests...");
+        additional_images_tests(&endpoint_options).await;
+        info!("Running additional images tests...SUCCESS");
+
+        info!("Running regenerate images tests...");
+        regenerate_images_tests(&endpoint_options).await;
+        info!("Running regenerate images tests...SUCCESS");
+
+        #[cfg(feature = "json-schema")]
+        {
+            info!("Running JSON Schema tests...");
+            json_schema_tests(&endpoint_options).await;
+            info!("Running JSON Schema tests...SUCCESS");
+        }
+
+        info!("Running metrics tests...");
+        metrics_tests(&endpoint_options).await;
+        info!("Running metrics tests...SUCCESS");
+
+        info!("Running fallback tests...");
+        fallback_tests(&endpoint_options).await;
+        info!("Running fallback tests...SUCCESS");
+
+        info!("Running OpenAPI tests...");
+        openapi_tests(&endpoint_options).await;
+        info!("Running OpenAPI tests...SUCCESS");
+
+        info!("Running probe tests...");
+        probe_tests(&endpoint_options).await;
+        info!("Running probe tests...SUCCESS");
+
+        info!("Running CORS tests...");
+        cors_tests(&endpoint_options).await;
+        info!("Running CORS tests...SUCCESS");
+
+        info!("Running extension tests...");
+        extension_tests(&endpoint_options).await;
+        info!("Running extension tests...SUCCESS");
+
+        info!("Running maintenance tests...");
+        maintenance_tests(&endpoint_options).await;
+        info!("Running maintenance tests...SUCCESS");
+
+        info!("Running embedded router tests...");
+        embedded_router_tests(router).await;
+        info!("Running embedded router tests...SUCCESS");
+
+        info!("Running request signing tests...");
+        request_signing_tests::<B>(separate_options.clone()).await;
+        info!("Running request signing tests...SUCCESS");
+
+        info!("Running TLS tests...");
+        tls_tests::<B>(separate_options.clone()).await;
+        info!("Running TLS tests...SUCCESS");
+
+        info!("Running admin allowlist tests...");
+        admin_allowlist_tests::<B>(separate_options.clone()).await;
+        info!("Running admin allowlist tests...SUCCESS");
+
+        info!("Running API key auth tests...");
+        api_key_auth_tests::<B>(separate_options.clone()).await;
+        info!("Running API key auth tests...SUCCESS");
+
+        info!("Running read-only service tests...");
+        read_only_service_tests::<B>(separate_options.clone()).await;
+        info!("Running read-only service tests...SUCCESS");
+
+        info!("Running image rate limit tests...");
+        image_rate_limit_tests::<B>(separate_options.clone()).await;
+        info!("Running image rate limit tests...SUCCESS");
+
+        info!("Running image quota tests...");
+        image_quota_tests::<B>(separate_options.clone()).await;
+        info!("Running image quota tests...SUCCESS");
+
+        info!("Running image validation tests...");
+        image_validation_tests::<B>(separate_options.clone()).await;
+        info!("Running image validation tests...SUCCESS");
+
+        info!("Running preview tests...");
+        preview_tests::<B>(separate_options.clone()).await;
+        info!("Running preview tests...SUCCESS");
+
+        info!("Running separate admin address tests...");
+        separate_admin_address_tests::<B>(separate_options).await;
+        info!("Running separate admin address tests...SUCCESS");
 
         service_clone.stop();
     });
@@ -1349,6 +5178,19 @@ async fn test_service() {
 
     let endpoint_options = EndpointOptions {
         address: SERVICE_ADDRESS.to_string(),
+        admin_allow_origin: Some("https://dashboard.example.com".to_string()),
+        image_url_secret: Some(Secret::from_str("image-url-secret").unwrap()),
+        cache_control: CacheControlOptions {
+            product: None,
+            query: Some("no-store".to_string()),
+            image: Some("public, max-age=2592000, immutable".to_string()),
+        },
+        metrics: true,
+        // the test data labels some PNG images as JPEG
+        image_quota: ImageQuotaOptions {
+            verify_content_types: false,
+            ..Default::default()
+        },
         ..Default::default()
     };
 
@@ -1363,12 +5205,26 @@ async fn test_service() {
             dbname: "postgres".to_string(),
             user: "postgres".to_string(),
             password: Secret::from_str("postgres").unwrap(),
+            password_file: None,
             max_connections: 5,
+            pgbouncer_mode: false,
+            schema: None,
+            application_name: None,
+            instance_id: None,
+            partition_missing_products: false,
+            missing_products_retention_months: None,
+            price_tracking: true,
+            market_specific_products: false,
+            unaccent_search: false,
+            search_terms: SearchTermMode::All,
+            image_store: ImageStoreConfig::default(),
         };
 
         let options = Options {
             postgres: options,
             endpoint: endpoint_options,
+            archive: ArchiveOptions::default(),
+            mongodb: None,
         };
 
         info!("Running service tests...");
@@ -1432,12 +5288,26 @@ async fn test_service() {
             dbname: "postgres".to_string(),
             user: "postgres".to_string(),
             password: Secret::from_str("password").unwrap(),
+            password_file: None,
             max_connections: 5,
+            pgbouncer_mode: false,
+            schema: None,
+            application_name: None,
+            instance_id: None,
+            partition_missing_products: false,
+            missing_products_retention_months: None,
+            price_tracking: true,
+            market_specific_products: false,
+            unaccent_search: false,
+            search_terms: SearchTermMode::All,
+            image_store: ImageStoreConfig::default(),
         };
 
         let options = Options {
             postgres: postgres_options,
             endpoint: endpoint_options,
+            archive: ArchiveOptions::default(),
+            mongodb: None,
         };
 
         info!("Running service tests...");